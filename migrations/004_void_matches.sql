@@ -0,0 +1,20 @@
+-- Soft-delete: voided matches are kept for admins but excluded from ratings
+ALTER TABLE matches ADD COLUMN voided_at TIMESTAMPTZ;
+
+-- Starting Elo per player, so ratings can be replayed from match history.
+-- Backfilled from the player's earliest snapshot, or current Elo if they haven't played.
+ALTER TABLE players ADD COLUMN initial_elo REAL;
+
+UPDATE players p SET initial_elo = COALESCE(
+    (
+        SELECT (m.elo_snapshot -> p.id::text ->> 'before')::REAL
+        FROM matches m
+        WHERE m.elo_snapshot ? p.id::text
+        ORDER BY m.played_at, m.created_at
+        LIMIT 1
+    ),
+    p.elo
+);
+
+ALTER TABLE players ALTER COLUMN initial_elo SET NOT NULL;
+ALTER TABLE players ALTER COLUMN initial_elo SET DEFAULT 1200.0;
@@ -0,0 +1,13 @@
+-- Free-text notes entered when recording a match
+ALTER TABLE matches ADD COLUMN notes TEXT NOT NULL DEFAULT '';
+
+-- Comments added to a match after it was recorded
+CREATE TABLE IF NOT EXISTS match_comments (
+    id SERIAL PRIMARY KEY,
+    match_id INTEGER NOT NULL REFERENCES matches(id) ON DELETE CASCADE,
+    author VARCHAR(255) NOT NULL DEFAULT '',
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_match_comments_match_id ON match_comments(match_id);
@@ -0,0 +1,2 @@
+-- Preferred shirt number (NULL = no preference)
+ALTER TABLE players ADD COLUMN jersey_number INTEGER CHECK (jersey_number BETWEEN 0 AND 99);
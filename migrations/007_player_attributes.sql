@@ -0,0 +1,4 @@
+-- Optional balancing attributes (NULL = unknown, treated as average)
+ALTER TABLE players ADD COLUMN stamina INTEGER CHECK (stamina BETWEEN 1 AND 5);
+ALTER TABLE players ADD COLUMN pace INTEGER CHECK (pace BETWEEN 1 AND 5);
+ALTER TABLE players ADD COLUMN age_band TEXT;
@@ -0,0 +1,11 @@
+-- Suspensions and bans (returns_on NULL = indefinite ban)
+CREATE TABLE IF NOT EXISTS suspensions (
+    id SERIAL PRIMARY KEY,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL DEFAULT '',
+    returns_on DATE,
+    lifted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_suspensions_player_id ON suspensions(player_id);
@@ -0,0 +1,3 @@
+-- Appearances are now counted from non-voided match records instead of a
+-- counter bumped on each result, so the stored column can no longer drift
+ALTER TABLE players DROP COLUMN IF EXISTS matches_played;
@@ -0,0 +1,20 @@
+-- Season rollovers: ratings are pulled toward 1200 by `carry` after `ended_on`
+CREATE TABLE IF NOT EXISTS seasons (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    ended_on DATE NOT NULL UNIQUE,
+    carry REAL NOT NULL CHECK (carry >= 0 AND carry <= 1),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Final standings archived at each rollover (names kept if players are deleted)
+CREATE TABLE IF NOT EXISTS season_standings (
+    season_id INTEGER NOT NULL REFERENCES seasons(id) ON DELETE CASCADE,
+    player_id INTEGER REFERENCES players(id) ON DELETE SET NULL,
+    name TEXT NOT NULL,
+    rank INTEGER NOT NULL,
+    elo REAL NOT NULL,
+    matches_played INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_season_standings_season_id ON season_standings(season_id);
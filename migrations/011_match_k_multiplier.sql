@@ -0,0 +1,3 @@
+-- Per-match K factor multiplier for special events (1 = normal match)
+ALTER TABLE matches ADD COLUMN k_multiplier REAL NOT NULL DEFAULT 1
+    CHECK (k_multiplier > 0 AND k_multiplier <= 5);
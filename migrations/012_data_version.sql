@@ -0,0 +1,28 @@
+-- Single-row counter bumped by any change to the data pages are rendered from,
+-- so page ETags and Last-Modified headers change exactly when content can
+CREATE TABLE IF NOT EXISTS data_version (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    version BIGINT NOT NULL DEFAULT 1,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+INSERT INTO data_version DEFAULT VALUES ON CONFLICT DO NOTHING;
+
+CREATE OR REPLACE FUNCTION bump_data_version() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE data_version SET version = version + 1, changed_at = NOW();
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER players_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON players
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
+CREATE TRIGGER matches_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON matches
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
+CREATE TRIGGER match_comments_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON match_comments
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
+CREATE TRIGGER suspensions_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON suspensions
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
+CREATE TRIGGER seasons_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON seasons
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
+CREATE TRIGGER season_standings_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON season_standings
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,9 @@
+-- A player's position, separate from style tags (NULL = not set)
+ALTER TABLE players ADD COLUMN position TEXT CHECK (position IN ('GK', 'DEF', 'MID', 'ATT'));
+
+-- Seed positions from unambiguous positional tags
+UPDATE players SET position = CASE
+    WHEN tags LIKE '%GK%' THEN 'GK'
+    WHEN tags LIKE '%DEF%' AND tags NOT LIKE '%ATK%' THEN 'DEF'
+    WHEN tags LIKE '%ATK%' AND tags NOT LIKE '%DEF%' THEN 'ATT'
+END;
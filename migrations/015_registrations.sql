@@ -0,0 +1,12 @@
+-- Players who signed themselves up, waiting for an admin to approve them
+CREATE TABLE IF NOT EXISTS registrations (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    position TEXT CHECK (position IN ('GK', 'DEF', 'MID', 'ATT')),
+    note TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- The pending queue is shown on the Roster
+CREATE TRIGGER registrations_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON registrations
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
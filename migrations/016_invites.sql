@@ -0,0 +1,22 @@
+-- A player's own contact details and optional PIN, set by claiming an invite
+CREATE TABLE IF NOT EXISTS player_accounts (
+    player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+    email TEXT,
+    notify_teams BOOLEAN NOT NULL DEFAULT FALSE,
+    notify_results BOOLEAN NOT NULL DEFAULT FALSE,
+    pin_hash TEXT, -- argon2 PHC string
+    claimed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Single-use links for a player to claim their roster entry
+CREATE TABLE IF NOT EXISTS invites (
+    id SERIAL PRIMARY KEY,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    claimed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_invites_player ON invites(player_id);
+
@@ -0,0 +1,12 @@
+-- Players' own answers for upcoming match days
+CREATE TABLE IF NOT EXISTS rsvps (
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    match_on DATE NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('in', 'maybe', 'out')),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (player_id, match_on)
+);
+
+-- RSVPs are shown at check-in on Match Day
+CREATE TRIGGER rsvps_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON rsvps
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,14 @@
+-- Outgoing webhooks: POSTed a signed JSON payload when subscribed events happen
+CREATE TABLE IF NOT EXISTS webhooks (
+    id SERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL, -- HMAC-SHA256 key for the X-SFM-Signature header
+    events TEXT NOT NULL, -- comma-separated event names, e.g. "result.recorded"
+    last_status TEXT, -- outcome of the latest delivery, e.g. "200" or the error
+    last_attempt_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Webhooks and their delivery status are listed on the Admin page
+CREATE TRIGGER webhooks_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON webhooks
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,11 @@
+-- Teams picked automatically from RSVPs once the RSVP deadline passes (one split per match day)
+CREATE TABLE IF NOT EXISTS auto_teams (
+    match_on DATE PRIMARY KEY,
+    team_a INTEGER[] NOT NULL,
+    team_b INTEGER[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- The picked teams are shown on Match Day
+CREATE TRIGGER auto_teams_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON auto_teams
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
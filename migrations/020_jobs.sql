@@ -0,0 +1,22 @@
+-- Background job queue: work that must survive restarts (e.g. webhook deliveries).
+-- Finished jobs are deleted; jobs out of attempts keep failed_at and last_error
+-- so the Admin page can show and retry them.
+CREATE TABLE IF NOT EXISTS jobs (
+    id SERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    -- Next run; claiming a job pushes it out by the lease, so a job whose
+    -- worker died is picked up again
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    failed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS jobs_due ON jobs (run_at) WHERE failed_at IS NULL;
+
+-- Failed jobs are listed on the Admin page
+CREATE TRIGGER jobs_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON jobs
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,21 @@
+-- Results waiting for a second person to confirm them (when result
+-- confirmation is on). Nothing is rated until they are confirmed.
+CREATE TABLE IF NOT EXISTS pending_results (
+    id SERIAL PRIMARY KEY,
+    played_at DATE NOT NULL,
+    team_a INTEGER[] NOT NULL,
+    team_b INTEGER[] NOT NULL,
+    score_a INTEGER NOT NULL,
+    score_b INTEGER NOT NULL,
+    -- Participation share by player ID, e.g. {"7": 0.5}
+    participation JSONB NOT NULL DEFAULT '{}',
+    k_multiplier REAL NOT NULL DEFAULT 1.0,
+    notes TEXT NOT NULL DEFAULT '',
+    -- Who submitted it ("admin" or "hook"); they can't confirm it themselves
+    submitted_by TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Pending results are listed on History
+CREATE TRIGGER pending_results_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON pending_results
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,15 @@
+-- Matches flagged by players as possibly wrong, queued for an admin to review
+CREATE TABLE IF NOT EXISTS match_flags (
+    id SERIAL PRIMARY KEY,
+    match_id INTEGER NOT NULL REFERENCES matches(id) ON DELETE CASCADE,
+    flagged_by TEXT NOT NULL, -- player name, or "Admin"
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ -- set when an admin has dealt with it
+);
+
+CREATE INDEX IF NOT EXISTS idx_match_flags_open ON match_flags(match_id) WHERE resolved_at IS NULL;
+
+-- Open flags are highlighted on History and listed on the Admin page
+CREATE TRIGGER match_flags_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON match_flags
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
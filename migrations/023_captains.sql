@@ -0,0 +1,6 @@
+-- Who captained each team (player IDs; NULL when not recorded)
+ALTER TABLE matches ADD COLUMN IF NOT EXISTS captain_a INTEGER;
+ALTER TABLE matches ADD COLUMN IF NOT EXISTS captain_b INTEGER;
+
+ALTER TABLE pending_results ADD COLUMN IF NOT EXISTS captain_a INTEGER;
+ALTER TABLE pending_results ADD COLUMN IF NOT EXISTS captain_b INTEGER;
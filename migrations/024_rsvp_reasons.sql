@@ -0,0 +1,3 @@
+-- Why a player is out for a match day (only for "out" answers; NULL when not given)
+ALTER TABLE rsvps ADD COLUMN IF NOT EXISTS reason TEXT
+    CHECK (reason IN ('injured', 'away', 'work', 'other'));
@@ -0,0 +1,11 @@
+-- Private admin notes per player (injury history, "don't put in goal", payment quirks).
+-- Kept out of the players table so public pages and exports never load them.
+CREATE TABLE IF NOT EXISTS player_notes (
+    player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+    notes TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Notes show on the Roster for admins
+CREATE TRIGGER player_notes_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON player_notes
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,17 @@
+-- Injuries keep a player out of check-in until an admin clears them;
+-- expected_return is only a guide (NULL = unknown)
+CREATE TABLE IF NOT EXISTS injuries (
+    id SERIAL PRIMARY KEY,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    note TEXT NOT NULL DEFAULT '',
+    started_on DATE NOT NULL,
+    expected_return DATE,
+    cleared_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_injuries_player_id ON injuries(player_id);
+
+-- Injury badges show on the Roster, Match Day and /me
+CREATE TRIGGER injuries_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON injuries
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
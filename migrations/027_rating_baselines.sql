@@ -0,0 +1,14 @@
+-- Ratings imported from before the app (e.g. an old spreadsheet), one per
+-- player. The rating also becomes the player's initial_elo so replays start
+-- from it; this row records where it came from for the History chart.
+CREATE TABLE IF NOT EXISTS rating_baselines (
+    player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+    elo REAL NOT NULL,
+    as_of DATE NOT NULL,
+    source TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Baselines start each player's line on the History chart
+CREATE TRIGGER rating_baselines_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON rating_baselines
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,3 @@
+-- Players can opt out of public pages: anonymous visitors see a pseudonym
+-- instead of their name (logged-in admins and players still see it)
+ALTER TABLE players ADD COLUMN IF NOT EXISTS hide_name BOOLEAN NOT NULL DEFAULT FALSE;
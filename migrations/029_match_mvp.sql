@@ -0,0 +1,2 @@
+-- Player of the match (player ID; NULL until one is awarded)
+ALTER TABLE matches ADD COLUMN IF NOT EXISTS mvp INTEGER;
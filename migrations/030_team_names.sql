@@ -0,0 +1,3 @@
+-- Themed team names as shown when the match was recorded (NULL = Team A / Team B)
+ALTER TABLE matches ADD COLUMN IF NOT EXISTS team_name_a TEXT;
+ALTER TABLE matches ADD COLUMN IF NOT EXISTS team_name_b TEXT;
@@ -0,0 +1,16 @@
+-- Coin tosses for kickoff, one per match day and split (the first toss stands)
+CREATE TABLE IF NOT EXISTS coin_tosses (
+    id SERIAL PRIMARY KEY,
+    match_on DATE NOT NULL,
+    -- Sorted player IDs, so the same teams find the same toss
+    team_a INTEGER[] NOT NULL,
+    team_b INTEGER[] NOT NULL,
+    -- Which team kicks off ('a' or 'b'); the other picks ends
+    kickoff_team TEXT NOT NULL CHECK (kickoff_team IN ('a', 'b')),
+    tossed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (match_on, team_a, team_b)
+);
+
+-- The toss is shown with the generated teams
+CREATE TRIGGER coin_tosses_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON coin_tosses
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
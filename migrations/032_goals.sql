@@ -0,0 +1,4 @@
+-- Goal events in the order they were scored, e.g. [{"team": "a", "scorer": 7, "minute": 12}].
+-- Empty when only the final score was recorded.
+ALTER TABLE matches ADD COLUMN IF NOT EXISTS goals JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE pending_results ADD COLUMN IF NOT EXISTS goals JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,20 @@
+-- Per-player totals over non-voided matches, so player rows no longer scan
+-- the whole match history. The app refreshes a match's players when it's
+-- recorded, and replaying history (after a void, restore, fix or import, or
+-- `sfm recalc-elo`) rebuilds every row.
+CREATE TABLE IF NOT EXISTS player_stats (
+    player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+    matches_played INTEGER NOT NULL DEFAULT 0,
+    last_played DATE
+);
+
+INSERT INTO player_stats (player_id, matches_played, last_played)
+SELECT p.id, COUNT(m.id)::INTEGER, MAX(m.played_at)
+FROM players p
+LEFT JOIN matches m ON m.voided_at IS NULL AND p.id = ANY(m.team_a || m.team_b)
+GROUP BY p.id
+ON CONFLICT (player_id) DO NOTHING;
+
+-- Appearances show on the roster and stats pages
+CREATE TRIGGER player_stats_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON player_stats
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,13 @@
+-- Every webhook delivery attempt, successful or not. The app trims it to the
+-- latest few hundred; the Admin page lists the newest.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id SERIAL PRIMARY KEY,
+    webhook_id INTEGER NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event TEXT NOT NULL,
+    status TEXT NOT NULL, -- HTTP status, e.g. "200", or the request error
+    delivered BOOLEAN NOT NULL,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER webhook_deliveries_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON webhook_deliveries
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,12 @@
+-- Tag suggestions an admin dismissed on the Roster, so they stay hidden
+CREATE TABLE IF NOT EXISTS tag_suggestion_dismissals (
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    change TEXT NOT NULL CHECK (change IN ('add', 'remove')),
+    dismissed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (player_id, tag, change)
+);
+
+-- Suggestions are listed on the Roster page
+CREATE TRIGGER tag_suggestion_dismissals_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON tag_suggestion_dismissals
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,19 @@
+-- Monthly peer survey answers: one row per rater, player and survey month.
+-- Raters are kept only to count each person once; pages show averages.
+CREATE TABLE IF NOT EXISTS peer_ratings (
+    rater_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    period DATE NOT NULL,
+    skill INTEGER NOT NULL CHECK (skill BETWEEN 1 AND 5),
+    stamina INTEGER NOT NULL CHECK (stamina BETWEEN 1 AND 5),
+    pace INTEGER NOT NULL CHECK (pace BETWEEN 1 AND 5),
+    rated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (rater_id, player_id, period),
+    CHECK (rater_id <> player_id)
+);
+
+CREATE INDEX IF NOT EXISTS peer_ratings_period ON peer_ratings (period);
+
+-- Peer ratings are shown on the Roster page
+CREATE TRIGGER peer_ratings_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON peer_ratings
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
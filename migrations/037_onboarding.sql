@@ -0,0 +1,20 @@
+-- New-player questionnaire: sign-ups carry the answers until approved, and
+-- each player keeps the answers and formula their starting Elo came from
+ALTER TABLE registrations
+    ADD COLUMN IF NOT EXISTS experience TEXT CHECK (experience IN ('new', 'casual', 'regular', 'club')),
+    ADD COLUMN IF NOT EXISTS fitness INTEGER CHECK (fitness BETWEEN 1 AND 5);
+
+CREATE TABLE IF NOT EXISTS onboarding_answers (
+    player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+    experience TEXT NOT NULL CHECK (experience IN ('new', 'casual', 'regular', 'club')),
+    position TEXT CHECK (position IN ('GK', 'DEF', 'MID', 'ATT')),
+    fitness INTEGER CHECK (fitness BETWEEN 1 AND 5),
+    starting_elo REAL NOT NULL,
+    -- OnboardingFormula as text, e.g. "base=1100,experience=100,fitness=25,keeper=0"
+    formula TEXT NOT NULL,
+    answered_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Shown on the player's /me page
+CREATE TRIGGER onboarding_answers_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON onboarding_answers
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
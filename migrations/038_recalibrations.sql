@@ -0,0 +1,12 @@
+-- Starting Elos re-estimated from a new player's first matches, so each
+-- player is only offered one recalibration
+CREATE TABLE IF NOT EXISTS recalibrations (
+    player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+    old_elo REAL NOT NULL,
+    new_elo REAL NOT NULL,
+    recalibrated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Offers are listed on the Roster page
+CREATE TRIGGER recalibrations_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON recalibrations
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
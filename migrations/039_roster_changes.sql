@@ -0,0 +1,51 @@
+-- Recent additions, edits and removals of players for the admin activity
+-- dashboard. Written by a trigger so every path that touches the roster
+-- (forms, imports, sign-ups) is covered; entries older than 30 days are pruned.
+CREATE TABLE IF NOT EXISTS roster_changes (
+    id SERIAL PRIMARY KEY,
+    player_id INTEGER NOT NULL, -- no FK: removals outlive the player
+    player_name TEXT NOT NULL,
+    change TEXT NOT NULL, -- "added", "edited" or "removed"
+    detail TEXT NOT NULL DEFAULT '', -- changed fields for edits, never old values
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION log_roster_change() RETURNS TRIGGER AS $$
+DECLARE
+    fields TEXT[] := '{}';
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO roster_changes (player_id, player_name, change) VALUES (NEW.id, NEW.name, 'added');
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO roster_changes (player_id, player_name, change) VALUES (OLD.id, OLD.name, 'removed');
+    ELSE
+        IF NEW.name IS DISTINCT FROM OLD.name THEN
+            fields := fields || 'name'::TEXT;
+            -- Keep no trace of the old name (anonymization renames players)
+            UPDATE roster_changes SET player_name = NEW.name WHERE player_id = NEW.id;
+        END IF;
+        IF NEW.tags IS DISTINCT FROM OLD.tags THEN fields := fields || 'tags'::TEXT; END IF;
+        IF NEW.jersey_number IS DISTINCT FROM OLD.jersey_number THEN fields := fields || 'jersey number'::TEXT; END IF;
+        IF NEW.stamina IS DISTINCT FROM OLD.stamina THEN fields := fields || 'stamina'::TEXT; END IF;
+        IF NEW.pace IS DISTINCT FROM OLD.pace THEN fields := fields || 'pace'::TEXT; END IF;
+        IF NEW.age_band IS DISTINCT FROM OLD.age_band THEN fields := fields || 'age band'::TEXT; END IF;
+        IF NEW.position IS DISTINCT FROM OLD.position THEN fields := fields || 'position'::TEXT; END IF;
+        IF NEW.hide_name IS DISTINCT FROM OLD.hide_name THEN fields := fields || 'name visibility'::TEXT; END IF;
+        IF cardinality(fields) > 0 THEN
+            INSERT INTO roster_changes (player_id, player_name, change, detail)
+                VALUES (NEW.id, NEW.name, 'edited', array_to_string(fields, ', '));
+        END IF;
+    END IF;
+    DELETE FROM roster_changes WHERE changed_at < NOW() - INTERVAL '30 days';
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Rating updates (elo) are not roster changes
+CREATE TRIGGER players_roster_changes
+    AFTER INSERT OR DELETE OR UPDATE OF name, tags, jersey_number, stamina, pace, age_band, position, hide_name
+    ON players FOR EACH ROW EXECUTE FUNCTION log_roster_change();
+
+-- Listed on the admin activity dashboard
+CREATE TRIGGER roster_changes_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON roster_changes
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,5 @@
+-- With result confirmation on, fixes to a recorded match wait for a second
+-- person too: the pending row carries the new score and notes, and `amends`
+-- names the match they replace (NULL = a new result)
+ALTER TABLE pending_results
+    ADD COLUMN IF NOT EXISTS amends INTEGER REFERENCES matches(id) ON DELETE CASCADE;
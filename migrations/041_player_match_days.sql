@@ -0,0 +1,42 @@
+-- Per-player results by match day over non-voided matches, so the Stats
+-- page (attendance, captains) and `/api/stats` no longer load every match.
+-- Rebuilt alongside player_stats: a recorded match refreshes its players and
+-- replaying history rebuilds every row. No FK, as matches keep former
+-- players' IDs too.
+CREATE TABLE IF NOT EXISTS player_match_days (
+    player_id INTEGER NOT NULL,
+    played_on DATE NOT NULL,
+    wins INTEGER NOT NULL DEFAULT 0,
+    draws INTEGER NOT NULL DEFAULT 0,
+    losses INTEGER NOT NULL DEFAULT 0,
+    -- Results of the matches they captained that day
+    captain_wins INTEGER NOT NULL DEFAULT 0,
+    captain_draws INTEGER NOT NULL DEFAULT 0,
+    captain_losses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (player_id, played_on)
+);
+
+INSERT INTO player_match_days
+    (player_id, played_on, wins, draws, losses, captain_wins, captain_draws, captain_losses)
+SELECT t.player_id, m.played_at,
+    COUNT(*) FILTER (WHERE t.result > 0),
+    COUNT(*) FILTER (WHERE t.result = 0),
+    COUNT(*) FILTER (WHERE t.result < 0),
+    COUNT(*) FILTER (WHERE t.captain AND t.result > 0),
+    COUNT(*) FILTER (WHERE t.captain AND t.result = 0),
+    COUNT(*) FILTER (WHERE t.captain AND t.result < 0)
+FROM matches m
+CROSS JOIN LATERAL (
+    SELECT a.id AS player_id, SIGN(m.score_a - m.score_b) AS result, a.id = m.captain_a AS captain
+    FROM unnest(m.team_a) AS a(id)
+    UNION ALL
+    SELECT b.id, SIGN(m.score_b - m.score_a), b.id = m.captain_b
+    FROM unnest(m.team_b) AS b(id)
+) t
+WHERE m.voided_at IS NULL
+GROUP BY t.player_id, m.played_at
+ON CONFLICT (player_id, played_on) DO NOTHING;
+
+-- Shown on the stats page
+CREATE TRIGGER player_match_days_data_version AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON player_match_days
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_data_version();
@@ -0,0 +1,42 @@
+#!/usr/bin/env bash
+# Download the pinned front-end assets into static/vendor (existing files are kept)
+# and check each against its sha256 in static/vendor/SHA256SUMS, failing on a mismatch.
+# Swagger UI is committed; the rest are fetched until their files are committed too.
+# Usage: ./scripts/vendor-assets.sh          fetch and verify
+#        ./scripts/vendor-assets.sh --pin    record sums for assets not pinned yet
+#                                            (review the SHA256SUMS diff before committing)
+set -euo pipefail
+DIR="$(dirname "$0")/../static/vendor"
+SUMS="$DIR/SHA256SUMS"
+PIN=false
+[ "${1:-}" = "--pin" ] && PIN=true
+mkdir -p "$DIR"
+touch "$SUMS"
+
+fetch() {
+  if [ ! -s "$DIR/$1" ]; then
+    echo "Fetching $1"
+    curl -fsSL "$2" -o "$DIR/$1.tmp"
+    mv "$DIR/$1.tmp" "$DIR/$1"
+  fi
+  expected="$(awk -v f="$1" '$2 == f { print $1 }' "$SUMS")"
+  actual="$(sha256sum "$DIR/$1" | cut -d' ' -f1)"
+  if [ -z "$expected" ]; then
+    if $PIN; then
+      echo "$actual  $1" >> "$SUMS"
+      echo "Pinned $1 ($actual)"
+    else
+      echo "warning: $1 has no pinned sha256; run with --pin to record $actual" >&2
+    fi
+  elif [ "$expected" != "$actual" ]; then
+    echo "error: $1 sha256 is $actual, expected $expected" >&2
+    rm -f "$DIR/$1"
+    exit 1
+  fi
+}
+
+fetch pico.min.css https://cdn.jsdelivr.net/npm/@picocss/pico@2.0.6/css/pico.min.css
+fetch htmx.min.js https://cdn.jsdelivr.net/npm/htmx.org@2.0.4/dist/htmx.min.js
+fetch chart.umd.js https://cdn.jsdelivr.net/npm/chart.js@4.4.7/dist/chart.umd.js
+fetch swagger-ui.css https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.17.14/swagger-ui.css
+fetch swagger-ui-bundle.js https://cdn.jsdelivr.net/npm/swagger-ui-dist@5.17.14/swagger-ui-bundle.js
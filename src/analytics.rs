@@ -0,0 +1,350 @@
+use crate::auth::AuthJar;
+use crate::models::{EloSnapshot, Match, Player};
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::replay::chronological;
+use crate::{db, AppState};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// One player's part in one match: a row of the analytics export
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Appearance {
+    pub played_at: NaiveDate,
+    pub match_id: i32,
+    pub player_id: i32,
+    /// Current name (pseudonym for hidden names unless the viewer may see
+    /// them, "Unknown" for deleted players)
+    pub player: String,
+    /// "A" or "B"
+    pub team: &'static str,
+    /// "win", "draw" or "loss" for the player's team
+    pub result: &'static str,
+    pub goals_for: i32,
+    pub goals_against: i32,
+    /// Elo before the match
+    pub elo_before: f32,
+    /// Elo change actually applied (scaled by participation)
+    pub delta: f32,
+    /// 0.0 to 1.0
+    pub participation: f32,
+}
+
+/// Every player's every non-voided match, oldest match first and Team A
+/// before Team B within a match. Players missing from a match's snapshot
+/// are left out.
+pub fn appearances(players: &[Player], matches: &[Match]) -> Vec<Appearance> {
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    let mut rows = Vec::new();
+    for m in chronological(matches)
+        .into_iter()
+        .filter(|m| m.voided_at.is_none())
+    {
+        let snapshot: HashMap<i32, EloSnapshot> =
+            serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+        let sides = [
+            ("A", &m.team_a, m.score_a, m.score_b),
+            ("B", &m.team_b, m.score_b, m.score_a),
+        ];
+        for (team, ids, goals_for, goals_against) in sides {
+            let result = match goals_for.cmp(&goals_against) {
+                std::cmp::Ordering::Greater => "win",
+                std::cmp::Ordering::Equal => "draw",
+                std::cmp::Ordering::Less => "loss",
+            };
+            for id in ids {
+                let Some(s) = snapshot.get(id) else { continue };
+                rows.push(Appearance {
+                    played_at: m.played_at,
+                    match_id: m.id,
+                    player_id: *id,
+                    player: names.get(id).copied().unwrap_or("Unknown").to_string(),
+                    team,
+                    result,
+                    goals_for,
+                    goals_against,
+                    elo_before: s.before,
+                    delta: s.delta * s.participation,
+                    participation: s.participation,
+                });
+            }
+        }
+    }
+    rows
+}
+
+/// One JSON object per line, each line ending in a newline
+pub fn to_jsonl(rows: &[Appearance]) -> String {
+    let mut jsonl = String::new();
+    for row in rows {
+        if let Ok(line) = serde_json::to_string(row) {
+            jsonl.push_str(&line);
+            jsonl.push('\n');
+        }
+    }
+    jsonl
+}
+
+/// Every appearance with names masked for the viewer, or the error response
+async fn load_appearances(state: &AppState, jar: &AuthJar) -> Result<Vec<Appearance>, Response> {
+    let players = state.players.get(&state.db).await;
+    let matches = db::get_all_matches(&state.db).await;
+    let (Ok(mut players), Ok(matches)) = (players, matches) else {
+        tracing::error!("Failed to load appearances for export");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    };
+    mask_names(&mut players, shows_hidden_names(jar, state));
+    Ok(appearances(&players, &matches))
+}
+
+/// Every appearance as JSON Lines, one player in one match per line, for
+/// loading into pandas, DuckDB and the like
+#[utoipa::path(
+    get,
+    path = "/api/export/appearances.jsonl",
+    tag = "stats",
+    responses(
+        (status = 200, description = "One object per line with played_at, match_id, player_id, player, team (A/B), result (win/draw/loss), goals_for, goals_against, elo_before, delta and participation, oldest match first", content_type = "application/x-ndjson"),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn appearances_jsonl(State(state): State<Arc<AppState>>, jar: AuthJar) -> Response {
+    let rows = match load_appearances(&state, &jar).await {
+        Ok(rows) => rows,
+        Err(response) => return response,
+    };
+    (
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"appearances.jsonl\"",
+            ),
+        ],
+        to_jsonl(&rows),
+    )
+        .into_response()
+}
+
+/// Parquet schema of the export: the JSON Lines fields, dates as DATE
+#[cfg(feature = "parquet")]
+const PARQUET_SCHEMA: &str = "
+    message appearance {
+        REQUIRED INT32 played_at (DATE);
+        REQUIRED INT32 match_id;
+        REQUIRED INT32 player_id;
+        REQUIRED BYTE_ARRAY player (UTF8);
+        REQUIRED BYTE_ARRAY team (UTF8);
+        REQUIRED BYTE_ARRAY result (UTF8);
+        REQUIRED INT32 goals_for;
+        REQUIRED INT32 goals_against;
+        REQUIRED FLOAT elo_before;
+        REQUIRED FLOAT delta;
+        REQUIRED FLOAT participation;
+    }
+";
+
+/// Write the next column of a row group
+#[cfg(feature = "parquet")]
+fn write_column<T: parquet::data_type::DataType>(
+    row_group: &mut parquet::file::writer::SerializedRowGroupWriter<'_, Vec<u8>>,
+    values: &[T::T],
+) -> parquet::errors::Result<()> {
+    let mut column = row_group
+        .next_column()?
+        .ok_or_else(|| parquet::errors::ParquetError::General("Too few columns".to_string()))?;
+    column.typed::<T>().write_batch(values, None, None)?;
+    column.close()
+}
+
+/// The appearances as a Snappy-compressed Parquet file in one row group
+#[cfg(feature = "parquet")]
+pub fn to_parquet(rows: &[Appearance]) -> parquet::errors::Result<Vec<u8>> {
+    use parquet::basic::Compression;
+    use parquet::data_type::{ByteArray, ByteArrayType, FloatType, Int32Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let properties = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+    let mut writer = SerializedFileWriter::new(Vec::new(), schema, properties)?;
+    let mut row_group = writer.next_row_group()?;
+
+    // Parquet dates are days since 1970-01-01 (NaiveDate's default)
+    let epoch = NaiveDate::default();
+    let ints = |f: fn(&Appearance) -> i32| rows.iter().map(f).collect::<Vec<_>>();
+    let floats = |f: fn(&Appearance) -> f32| rows.iter().map(f).collect::<Vec<_>>();
+    let text = |f: fn(&Appearance) -> &str| {
+        rows.iter()
+            .map(|row| ByteArray::from(f(row)))
+            .collect::<Vec<_>>()
+    };
+    let days: Vec<i32> = rows
+        .iter()
+        .map(|row| (row.played_at - epoch).num_days() as i32)
+        .collect();
+
+    write_column::<Int32Type>(&mut row_group, &days)?;
+    write_column::<Int32Type>(&mut row_group, &ints(|row| row.match_id))?;
+    write_column::<Int32Type>(&mut row_group, &ints(|row| row.player_id))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|row| &row.player))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|row| row.team))?;
+    write_column::<ByteArrayType>(&mut row_group, &text(|row| row.result))?;
+    write_column::<Int32Type>(&mut row_group, &ints(|row| row.goals_for))?;
+    write_column::<Int32Type>(&mut row_group, &ints(|row| row.goals_against))?;
+    write_column::<FloatType>(&mut row_group, &floats(|row| row.elo_before))?;
+    write_column::<FloatType>(&mut row_group, &floats(|row| row.delta))?;
+    write_column::<FloatType>(&mut row_group, &floats(|row| row.participation))?;
+    row_group.close()?;
+    writer.into_inner()
+}
+
+/// The Parquet download, or 500 if it can't be written
+#[cfg(feature = "parquet")]
+fn parquet_response(rows: &[Appearance]) -> Response {
+    match to_parquet(rows) {
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, "application/vnd.apache.parquet"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"appearances.parquet\"",
+                ),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to write Parquet export: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Without the `parquet` feature the export isn't available
+#[cfg(not(feature = "parquet"))]
+fn parquet_response(_rows: &[Appearance]) -> Response {
+    (
+        StatusCode::NOT_IMPLEMENTED,
+        "Parquet export isn't built in; build with --features parquet",
+    )
+        .into_response()
+}
+
+/// The same appearances as a Parquet file, for bigger histories (only when
+/// built with the `parquet` feature)
+#[utoipa::path(
+    get,
+    path = "/api/export/appearances.parquet",
+    tag = "stats",
+    responses(
+        (status = 200, description = "The JSON Lines export's columns as a Snappy-compressed Parquet file (played_at as DATE)", content_type = "application/vnd.apache.parquet"),
+        (status = 500, description = "Database error"),
+        (status = 501, description = "Built without the parquet feature", content_type = "text/plain")
+    )
+)]
+pub async fn appearances_parquet(State(state): State<Arc<AppState>>, jar: AuthJar) -> Response {
+    match load_appearances(&state, &jar).await {
+        Ok(rows) => parquet_response(&rows),
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn make_match(id: i32, day: u32, score: (i32, i32)) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2025, 2, day).unwrap(),
+            team_a: vec![1],
+            team_b: vec![2, 99],
+            score_a: score.0,
+            score_b: score.1,
+            elo_snapshot: json!({
+                "1": { "before": 1200.0, "delta": 16.0 },
+                "2": { "before": 1210.0, "delta": -16.0, "participation": 0.5 },
+                "99": { "before": 1190.0, "delta": -16.0 }
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_appearances() {
+        let players = vec![
+            Player {
+                id: 1,
+                name: "Ann".to_string(),
+                ..Default::default()
+            },
+            Player {
+                id: 2,
+                name: "Bob".to_string(),
+                ..Default::default()
+            },
+        ];
+        let mut voided = make_match(3, 1, (0, 5));
+        voided.voided_at = Some(Utc::now());
+        // Out of order on purpose
+        let matches = vec![make_match(2, 9, (1, 1)), voided, make_match(1, 2, (2, 1))];
+
+        let rows = appearances(&players, &matches);
+
+        assert_eq!(rows.len(), 6);
+        assert_eq!((rows[0].match_id, rows[0].player.as_str()), (1, "Ann"));
+        assert_eq!((rows[0].team, rows[0].result), ("A", "win"));
+        assert_eq!((rows[1].team, rows[1].result), ("B", "loss"));
+        // Half participation halves the change
+        assert_eq!(rows[1].delta, -8.0);
+        assert_eq!(rows[2].player, "Unknown");
+        assert_eq!(rows[3].result, "draw");
+
+        let jsonl = to_jsonl(&rows[..1]);
+        assert_eq!(
+            jsonl,
+            "{\"played_at\":\"2025-02-02\",\"match_id\":1,\"player_id\":1,\"player\":\"Ann\",\
+             \"team\":\"A\",\"result\":\"win\",\"goals_for\":2,\"goals_against\":1,\
+             \"elo_before\":1200.0,\"delta\":16.0,\"participation\":1.0}\n"
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_round_trip() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::RowAccessor;
+
+        let players = vec![Player {
+            id: 1,
+            name: "Ann".to_string(),
+            ..Default::default()
+        }];
+        let rows = appearances(&players, &[make_match(1, 2, (2, 1))]);
+        let path = std::env::temp_dir().join(format!("appearances-{}.parquet", std::process::id()));
+        std::fs::write(&path, to_parquet(&rows).unwrap()).unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+        let first = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        assert_eq!(first.get_int(1).unwrap(), 1);
+        assert_eq!(first.get_string(3).unwrap(), "Ann");
+        assert_eq!(first.get_string(5).unwrap(), "win");
+        assert_eq!(first.get_float(9).unwrap(), 16.0);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+use crate::models::Match;
+use chrono::Datelike;
+use std::collections::{BTreeMap, HashMap};
+
+/// Non-voided appearances per player ID, split by season (calendar year of
+/// the match date). Players without matches are absent.
+pub fn appearances_by_season(matches: &[Match]) -> HashMap<i32, BTreeMap<i32, i32>> {
+    let mut appearances: HashMap<i32, BTreeMap<i32, i32>> = HashMap::new();
+    for m in matches.iter().filter(|m| m.voided_at.is_none()) {
+        let season = m.played_at.year();
+        for id in m.team_a.iter().chain(&m.team_b) {
+            *appearances
+                .entry(*id)
+                .or_default()
+                .entry(season)
+                .or_default() += 1;
+        }
+    }
+    appearances
+}
+
+/// Every season with at least one counted match, newest first
+pub fn seasons(appearances: &HashMap<i32, BTreeMap<i32, i32>>) -> Vec<i32> {
+    let mut seasons: Vec<i32> = appearances
+        .values()
+        .flat_map(|by_season| by_season.keys().copied())
+        .collect();
+    seasons.sort_unstable_by(|a, b| b.cmp(a));
+    seasons.dedup();
+    seasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    fn make_match(id: i32, date: (i32, u32, u32), team_a: Vec<i32>, team_b: Vec<i32>) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+            team_a,
+            team_b,
+            score_a: 1,
+            score_b: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_appearances_split_by_season() {
+        let mut voided = make_match(4, (2025, 6, 1), vec![1], vec![2]);
+        voided.voided_at = Some(Utc::now());
+        let matches = vec![
+            make_match(1, (2024, 12, 29), vec![1], vec![2]),
+            make_match(2, (2025, 1, 5), vec![1, 3], vec![2]),
+            make_match(3, (2025, 1, 12), vec![3], vec![1]),
+            voided,
+        ];
+
+        let appearances = appearances_by_season(&matches);
+
+        assert_eq!(appearances[&1][&2024], 1);
+        assert_eq!(appearances[&1][&2025], 2);
+        assert_eq!(appearances[&2].get(&2025), Some(&1));
+        assert!(!appearances[&3].contains_key(&2024));
+        assert_eq!(seasons(&appearances), vec![2025, 2024]);
+    }
+}
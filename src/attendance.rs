@@ -0,0 +1,275 @@
+use crate::models::{Rsvp, RsvpStatus};
+use chrono::{Datelike, NaiveDate};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Months shown in the heat table
+pub const HEAT_MONTHS: usize = 12;
+
+/// Elo taken off, for balancing, a player who never turns up after saying In
+/// (scaled by how often they don't)
+pub const RELIABILITY_WEIGHT: f32 = 100.0;
+
+/// One player's attendance, counted in match days (several matches on one
+/// day count once)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attendance {
+    pub player_id: i32,
+    /// Match days played
+    pub played: usize,
+    /// Match days since (and including) their first
+    pub possible: usize,
+    /// Match days played in a row up to the latest one (0 if they missed it)
+    pub streak: usize,
+    /// Match days they RSVP'd In for but didn't play
+    pub no_shows: usize,
+}
+
+impl Attendance {
+    /// Share of possible match days played (0-1)
+    pub fn rate(&self) -> f32 {
+        self.played as f32 / self.possible.max(1) as f32
+    }
+
+    /// Share of the match days they were due at (played, or RSVP'd In) that
+    /// they turned up for (0-1)
+    pub fn reliability(&self) -> f32 {
+        self.played as f32 / (self.played + self.no_shows).max(1) as f32
+    }
+}
+
+/// Elo taken off per player ID for their no-shows, so a team counting on a
+/// flaky player isn't left short when they don't come. Players who have
+/// always turned up are absent.
+pub fn reliability_discount(report: &[Attendance]) -> HashMap<i32, f32> {
+    report
+        .iter()
+        .filter(|a| a.no_shows > 0)
+        .map(|a| (a.player_id, -(1.0 - a.reliability()) * RELIABILITY_WEIGHT))
+        .collect()
+}
+
+/// Match days played per player ID, from `(player ID, match day)` rows (see
+/// [`crate::db::get_match_days_played`])
+pub fn days_played(rows: &[(i32, NaiveDate)]) -> HashMap<i32, BTreeSet<NaiveDate>> {
+    let mut played: HashMap<i32, BTreeSet<NaiveDate>> = HashMap::new();
+    for (id, day) in rows {
+        played.entry(*id).or_default().insert(*day);
+    }
+    played
+}
+
+/// Every date with a non-voided match, oldest first
+pub fn match_days(played: &HashMap<i32, BTreeSet<NaiveDate>>) -> Vec<NaiveDate> {
+    let days: BTreeSet<NaiveDate> = played.values().flatten().copied().collect();
+    days.into_iter().collect()
+}
+
+/// Attendance for everyone who has played, most match days first (best
+/// rate breaks ties)
+pub fn attendance(played: &HashMap<i32, BTreeSet<NaiveDate>>, rsvps: &[Rsvp]) -> Vec<Attendance> {
+    let days = match_days(played);
+    let day_set: HashSet<NaiveDate> = days.iter().copied().collect();
+
+    let mut report: Vec<Attendance> = played
+        .iter()
+        .map(|(&player_id, dates)| {
+            let first = dates.first().copied().unwrap_or_default();
+            let no_shows = rsvps
+                .iter()
+                .filter(|r| r.player_id == player_id && r.status() == Some(RsvpStatus::In))
+                .filter(|r| day_set.contains(&r.match_on) && !dates.contains(&r.match_on))
+                .count();
+            Attendance {
+                player_id,
+                played: dates.len(),
+                possible: days.iter().filter(|d| **d >= first).count(),
+                streak: days.iter().rev().take_while(|d| dates.contains(d)).count(),
+                no_shows,
+            }
+        })
+        .collect();
+    report.sort_by(|a, b| {
+        b.played
+            .cmp(&a.played)
+            .then(b.rate().total_cmp(&a.rate()))
+            .then(a.player_id.cmp(&b.player_id))
+    });
+    report
+}
+
+/// Match days played and held per month, for the heat table
+pub struct MonthlyAttendance {
+    /// (year, month), oldest first: the last `HEAT_MONTHS` months with matches
+    pub months: Vec<(i32, u32)>,
+    /// Match days held per month
+    pub held: HashMap<(i32, u32), usize>,
+    /// Match days played per player ID and month
+    pub played: HashMap<i32, HashMap<(i32, u32), usize>>,
+}
+
+impl MonthlyAttendance {
+    /// Share of a month's match days a player played (None if none were held)
+    pub fn share(&self, player_id: i32, month: (i32, u32)) -> Option<f32> {
+        let held = *self.held.get(&month)?;
+        let played = self
+            .played
+            .get(&player_id)
+            .and_then(|months| months.get(&month))
+            .copied()
+            .unwrap_or(0);
+        Some(played as f32 / held as f32)
+    }
+}
+
+/// Month-by-month attendance over the most recent months with matches
+pub fn monthly(played: &HashMap<i32, BTreeSet<NaiveDate>>) -> MonthlyAttendance {
+    let month = |d: &NaiveDate| (d.year(), d.month());
+    let mut held: HashMap<(i32, u32), usize> = HashMap::new();
+    for day in match_days(played) {
+        *held.entry(month(&day)).or_default() += 1;
+    }
+    let mut months: Vec<(i32, u32)> = held.keys().copied().collect();
+    months.sort_unstable();
+    let months = months.split_off(months.len().saturating_sub(HEAT_MONTHS));
+
+    let played = played
+        .iter()
+        .map(|(id, dates)| {
+            let mut by_month: HashMap<(i32, u32), usize> = HashMap::new();
+            for day in dates {
+                *by_month.entry(month(day)).or_default() += 1;
+            }
+            (*id, by_month)
+        })
+        .collect();
+
+    MonthlyAttendance {
+        months,
+        held,
+        played,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Match;
+    use chrono::Utc;
+
+    fn make_match(date: (i32, u32, u32), team_a: Vec<i32>, team_b: Vec<i32>) -> Match {
+        Match {
+            id: 0,
+            played_at: NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+            team_a,
+            team_b,
+            score_a: 1,
+            score_b: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Match days played, as `player_match_days` holds them
+    fn played(matches: &[Match]) -> HashMap<i32, BTreeSet<NaiveDate>> {
+        let rows: Vec<(i32, NaiveDate)> = matches
+            .iter()
+            .filter(|m| m.voided_at.is_none())
+            .flat_map(|m| {
+                m.team_a
+                    .iter()
+                    .chain(&m.team_b)
+                    .map(|id| (*id, m.played_at))
+            })
+            .collect();
+        days_played(&rows)
+    }
+
+    fn rsvp(player_id: i32, date: (i32, u32, u32), status: RsvpStatus) -> Rsvp {
+        Rsvp {
+            player_id,
+            match_on: NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+            status: status.as_str().to_string(),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_attendance() {
+        let mut voided = make_match((2025, 3, 30), vec![3], vec![4]);
+        voided.voided_at = Some(Utc::now());
+        let matches = vec![
+            make_match((2025, 3, 2), vec![1], vec![2]),
+            // Two matches on one day count once
+            make_match((2025, 3, 9), vec![1], vec![2]),
+            make_match((2025, 3, 9), vec![1], vec![3]),
+            make_match((2025, 3, 16), vec![1, 3], vec![2]),
+            make_match((2025, 3, 23), vec![3], vec![2]),
+            voided,
+        ];
+        let rsvps = vec![
+            rsvp(1, (2025, 3, 23), RsvpStatus::In),
+            rsvp(2, (2025, 3, 23), RsvpStatus::In),
+            rsvp(3, (2025, 3, 2), RsvpStatus::Out),
+            // Not a match day
+            rsvp(1, (2025, 3, 30), RsvpStatus::In),
+        ];
+
+        let report = attendance(&played(&matches), &rsvps);
+        let by_id: HashMap<i32, &Attendance> = report.iter().map(|a| (a.player_id, a)).collect();
+
+        assert_eq!(report[0].player_id, 2);
+        assert_eq!(
+            (by_id[&2].played, by_id[&2].possible, by_id[&2].streak),
+            (4, 4, 4)
+        );
+        assert_eq!(
+            (by_id[&1].played, by_id[&1].streak, by_id[&1].no_shows),
+            (3, 0, 1)
+        );
+        // Only match days since their first count
+        assert_eq!((by_id[&3].played, by_id[&3].possible), (3, 3));
+        assert_eq!(by_id[&3].rate(), 1.0);
+        assert_eq!(by_id[&3].no_shows, 0);
+        assert!(!by_id.contains_key(&4));
+
+        // Player 1 missed one of the four days they were due at
+        assert_eq!(by_id[&1].reliability(), 0.75);
+        let discount = reliability_discount(&report);
+        assert_eq!(discount[&1], -0.25 * RELIABILITY_WEIGHT);
+        assert!(!discount.contains_key(&3));
+    }
+
+    #[test]
+    fn test_monthly() {
+        let matches = vec![
+            make_match((2025, 2, 23), vec![1], vec![2]),
+            make_match((2025, 3, 2), vec![1], vec![2]),
+            make_match((2025, 3, 9), vec![2], vec![3]),
+        ];
+
+        let monthly = monthly(&played(&matches));
+
+        assert_eq!(monthly.months, vec![(2025, 2), (2025, 3)]);
+        assert_eq!(monthly.share(1, (2025, 3)), Some(0.5));
+        assert_eq!(monthly.share(2, (2025, 3)), Some(1.0));
+        assert_eq!(monthly.share(3, (2025, 2)), Some(0.0));
+        assert_eq!(monthly.share(1, (2025, 4)), None);
+    }
+
+    #[test]
+    fn test_monthly_keeps_recent_months() {
+        let matches: Vec<Match> = (1..=12)
+            .chain([1, 2])
+            .enumerate()
+            .map(|(i, month)| {
+                let year = if i < 12 { 2024 } else { 2025 };
+                make_match((year, month, 1), vec![1], vec![2])
+            })
+            .collect();
+
+        let months = monthly(&played(&matches)).months;
+
+        assert_eq!(months.len(), HEAT_MONTHS);
+        assert_eq!(months.first(), Some(&(2024, 3)));
+        assert_eq!(months.last(), Some(&(2025, 2)));
+    }
+}
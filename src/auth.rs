@@ -1,51 +1,267 @@
+use crate::views::errors::{error_fragment, error_page};
+use crate::views::layout::AuthState;
 use crate::AppState;
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, SaltString};
+use argon2::{Argon2, PasswordVerifier};
 use axum::{
-    extract::State,
-    response::{Html, IntoResponse, Redirect},
+    extract::{FromRef, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
     Form,
 };
-use axum_extra::extract::cookie::{Cookie, CookieJar};
+use axum_extra::extract::cookie::{Cookie, Key, SignedCookieJar};
+use chrono::{Duration, Utc};
 use std::sync::Arc;
 
 const AUTH_COOKIE_NAME: &str = "sfm_auth";
+/// Separate cookie for a player's own login, so it never grants admin rights
+const PLAYER_COOKIE_NAME: &str = "sfm_player";
+
+/// Session lifetime without "remember me"
+const SESSION_HOURS: i64 = 12;
+/// Session lifetime with "remember me"
+const REMEMBER_ME_DAYS: i64 = 30;
+/// Player session lifetime (players log in on their phone and stay in)
+const PLAYER_SESSION_DAYS: i64 = 30;
+
+/// Admin credentials. Only the argon2 hash of the password is kept in memory.
+#[derive(Clone)]
+pub struct AdminAuth {
+    password_hash: String,
+}
+
+impl AdminAuth {
+    /// Build from AUTH_PASSWORD_HASH (argon2 PHC string) or AUTH_PASSWORD (hashed at startup)
+    pub fn from_env() -> Option<Self> {
+        let password_hash = match std::env::var("AUTH_PASSWORD_HASH") {
+            Ok(hash) => {
+                PasswordHash::new(&hash).expect("AUTH_PASSWORD_HASH must be an argon2 PHC string");
+                hash
+            }
+            Err(_) => hash_password(&std::env::var("AUTH_PASSWORD").ok()?),
+        };
+        Some(Self { password_hash })
+    }
+
+    /// An admin password for tests
+    #[cfg(test)]
+    pub fn with_password(password: &str) -> Self {
+        Self {
+            password_hash: hash_password(password),
+        }
+    }
+
+    /// Verify a login attempt against the stored hash
+    pub fn verify_password(&self, password: &str) -> bool {
+        verify_hash(&self.password_hash, password)
+    }
+}
+
+/// Check a secret against an argon2 PHC string (false if the hash is malformed)
+pub fn verify_hash(password_hash: &str, password: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Hash a password with argon2 and a random salt
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("Failed to hash password")
+        .to_string()
+}
+
+/// Random URL-safe token for single-use links (32 alphanumerics, ~190 bits)
+pub fn random_token() -> String {
+    use rand::distributions::{Alphanumeric, DistString};
+    Alphanumeric.sample_string(&mut rand::thread_rng(), 32)
+}
+
+/// Key used to sign auth cookies
+#[derive(Clone)]
+pub struct CookieKey(Key);
+
+impl CookieKey {
+    /// Derive from SESSION_SECRET, or generate a random key (sessions reset on restart)
+    pub fn from_env() -> Self {
+        match std::env::var("SESSION_SECRET") {
+            Ok(secret) if secret.len() >= 32 => Self(Key::derive_from(secret.as_bytes())),
+            Ok(_) => panic!("SESSION_SECRET must be at least 32 bytes"),
+            Err(_) => {
+                tracing::warn!(
+                    "No SESSION_SECRET set - logins will reset when the server restarts"
+                );
+                Self(Key::generate())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Default for CookieKey {
+    fn default() -> Self {
+        Self(Key::generate())
+    }
+}
+
+impl From<CookieKey> for Key {
+    fn from(key: CookieKey) -> Self {
+        key.0
+    }
+}
+
+impl FromRef<Arc<AppState>> for CookieKey {
+    fn from_ref(state: &Arc<AppState>) -> Self {
+        state.cookie_key.clone()
+    }
+}
+
+/// Cookie jar whose cookies are HMAC-signed with the app's [`CookieKey`]
+pub type AuthJar = SignedCookieJar<CookieKey>;
+
+/// Cookie payload: the session's expiry as a unix timestamp. The cookie is
+/// HMAC-signed, so the value can't be forged or extended by the client.
+fn session_value(expires_at: i64) -> String {
+    expires_at.to_string()
+}
+
+/// Whether a (signature-verified) cookie value is an unexpired session
+fn is_valid_session(value: &str, now: i64) -> bool {
+    value
+        .parse::<i64>()
+        .map(|expires_at| now < expires_at)
+        .unwrap_or(false)
+}
 
 /// Check if the request is authenticated
-pub fn is_authenticated(jar: &CookieJar, state: &AppState) -> bool {
+pub fn is_authenticated(jar: &AuthJar, state: &AppState) -> bool {
     // If no password is set, everyone is authenticated
-    let Some(ref password) = state.auth_password else {
+    if state.auth.is_none() {
         return true;
-    };
+    }
 
-    // Check for valid auth cookie
+    // Check for a validly signed, unexpired auth cookie
     jar.get(AUTH_COOKIE_NAME)
-        .map(|cookie| cookie.value() == password)
+        .map(|cookie| is_valid_session(cookie.value(), Utc::now().timestamp()))
         .unwrap_or(false)
 }
 
+/// Player cookie payload: `player_id:expires_at` (signed like the admin cookie)
+fn player_session_value(player_id: i32, expires_at: i64) -> String {
+    format!("{}:{}", player_id, expires_at)
+}
+
+/// The player ID in a (signature-verified) player cookie value, if unexpired
+fn parse_player_session(value: &str, now: i64) -> Option<i32> {
+    let (player_id, expires_at) = value.split_once(':')?;
+    let expires_at: i64 = expires_at.parse().ok()?;
+    (now < expires_at).then_some(player_id.parse().ok()?)
+}
+
+/// The player logged in with their own PIN or invite link, if any. Players
+/// only get their own profile and RSVPs; admin checks use [`is_authenticated`].
+pub fn current_player(jar: &AuthJar) -> Option<i32> {
+    jar.get(PLAYER_COOKIE_NAME)
+        .and_then(|cookie| parse_player_session(cookie.value(), Utc::now().timestamp()))
+}
+
+/// Add a player session cookie to the jar
+pub fn login_player(jar: AuthJar, player_id: i32, secure: bool) -> AuthJar {
+    let lifetime = Duration::days(PLAYER_SESSION_DAYS);
+    let expires_at = (Utc::now() + lifetime).timestamp();
+    let cookie = Cookie::build((
+        PLAYER_COOKIE_NAME,
+        player_session_value(player_id, expires_at),
+    ))
+    .path("/")
+    .http_only(true)
+    .secure(secure)
+    .max_age(time::Duration::seconds(lifetime.num_seconds()))
+    .build();
+    jar.add(cookie)
+}
+
+/// Remove the player session cookie
+pub fn logout_player(jar: AuthJar) -> AuthJar {
+    jar.remove(Cookie::build(PLAYER_COOKIE_NAME).path("/").build())
+}
+
+/// Middleware for read-only routes: when PRIVATE_PAGES is set, anonymous
+/// visitors get a login prompt instead of the page
+pub async fn require_login_if_private(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    request: Request,
+    next: Next,
+) -> Response {
+    let settings = state.settings.get();
+    if !settings.private_pages || is_authenticated(&jar, &state) {
+        return next.run(request).await;
+    }
+
+    if request.uri().path().starts_with("/api/") {
+        return unauthorized().into_response();
+    }
+
+    error_page(
+        StatusCode::UNAUTHORIZED,
+        "This group's pages are private. Log in above to view them.",
+        &AuthState::new(true, false),
+        &settings.group_name,
+    )
+}
+
 /// Login form data
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct LoginForm {
     password: String,
+    /// Keep the session for 30 days instead of 12 hours
+    #[serde(default)]
+    remember_me: Option<String>,
 }
 
 /// Handle login POST
+#[utoipa::path(
+    post,
+    path = "/api/login",
+    tag = "auth",
+    request_body(content = LoginForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 303, description = "Redirect to home, with `?auth_error=1` on a wrong password"))
+)]
 pub async fn login(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    jar: AuthJar,
     Form(form): Form<LoginForm>,
 ) -> impl IntoResponse {
-    let Some(ref password) = state.auth_password else {
+    let Some(ref auth) = state.auth else {
         // No password configured, redirect to home
         return (jar, Redirect::to("/")).into_response();
     };
 
-    if form.password == *password {
-        // Set auth cookie
-        let cookie = Cookie::build((AUTH_COOKIE_NAME, password.clone()))
+    if auth.verify_password(&form.password) {
+        let lifetime = if form.remember_me.is_some() {
+            Duration::days(REMEMBER_ME_DAYS)
+        } else {
+            Duration::hours(SESSION_HOURS)
+        };
+        let expires_at = Utc::now() + lifetime;
+
+        // Set signed auth cookie (persistent only with "remember me")
+        let mut cookie = Cookie::build((AUTH_COOKIE_NAME, session_value(expires_at.timestamp())))
             .path("/")
             .http_only(true)
             .secure(state.secure_cookies)
             .build();
+        if form.remember_me.is_some() {
+            cookie.set_max_age(time::Duration::seconds(lifetime.num_seconds()));
+        }
         (jar.add(cookie), Redirect::to("/")).into_response()
     } else {
         // Wrong password - redirect back with error indicator
@@ -54,15 +270,90 @@ pub async fn login(
 }
 
 /// Handle logout POST
-pub async fn logout(jar: CookieJar) -> impl IntoResponse {
+#[utoipa::path(
+    post,
+    path = "/api/logout",
+    tag = "auth",
+    responses((status = 303, description = "Auth cookie cleared, redirect to home"))
+)]
+pub async fn logout(jar: AuthJar) -> impl IntoResponse {
     let cookie = Cookie::build(AUTH_COOKIE_NAME).path("/").build();
     (jar.remove(cookie), Redirect::to("/"))
 }
 
+/// A cookie jar with no logins, for handler tests
+#[cfg(test)]
+pub async fn anonymous_jar(state: &Arc<AppState>) -> AuthJar {
+    use axum::extract::FromRequestParts;
+    let (mut parts, _) = axum::http::Request::new(()).into_parts();
+    match AuthJar::from_request_parts(&mut parts, state).await {
+        Ok(jar) => jar,
+        Err(never) => match never {},
+    }
+}
+
 /// Return 401 Unauthorized response
-pub fn unauthorized() -> impl IntoResponse {
-    (
-        axum::http::StatusCode::UNAUTHORIZED,
-        Html("<p class=\"error\">Unauthorized. Please log in.</p>".to_string()),
-    )
+pub fn unauthorized() -> Response {
+    error_fragment(StatusCode::UNAUTHORIZED, "Unauthorized. Please log in.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_password() {
+        let auth = AdminAuth {
+            password_hash: hash_password("hunter2"),
+        };
+        assert!(auth.verify_password("hunter2"));
+        assert!(!auth.verify_password("hunter3"));
+        assert!(!auth.verify_password(""));
+    }
+
+    #[test]
+    fn test_hash_does_not_contain_password() {
+        let hash = hash_password("hunter2");
+        assert!(hash.starts_with("$argon2"));
+        assert!(!hash.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_random_token() {
+        let token = random_token();
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(token, random_token());
+    }
+
+    #[test]
+    fn test_session_expiry() {
+        let now = 1_700_000_000;
+        assert!(is_valid_session(&session_value(now + 60), now));
+        assert!(!is_valid_session(&session_value(now - 60), now));
+        assert!(!is_valid_session(&session_value(now), now));
+    }
+
+    #[test]
+    fn test_player_session() {
+        let value = player_session_value(7, 2000);
+        assert_eq!(parse_player_session(&value, 1000), Some(7));
+        assert_eq!(parse_player_session(&value, 2000), None);
+        assert_eq!(parse_player_session("7", 1000), None);
+        assert_eq!(parse_player_session("x:2000", 1000), None);
+    }
+
+    #[test]
+    fn test_session_rejects_garbage() {
+        assert!(!is_valid_session("", 0));
+        assert!(!is_valid_session("hunter2", 0));
+    }
+
+    #[test]
+    fn test_signed_cookie_roundtrip() {
+        let jar = SignedCookieJar::new(Key::generate())
+            .add(Cookie::new(AUTH_COOKIE_NAME, session_value(1_700_000_060)));
+        let cookie = jar.get(AUTH_COOKIE_NAME).unwrap();
+        assert!(is_valid_session(cookie.value(), 1_700_000_000));
+    }
 }
@@ -0,0 +1,167 @@
+use crate::discipline;
+use crate::injuries;
+use crate::models::TeamSplit;
+use crate::privacy::mask_names;
+use crate::rsvp::{next_match_days, Deadline};
+use crate::share::whatsapp_text;
+use crate::team_names::team_names;
+use crate::webhooks::{teams_json, WebhookEvent};
+use crate::{db, AppState};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often to check whether the RSVP deadline has passed
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The match day whose RSVPs have closed by `now` (group time), if any.
+/// After the match day itself it moves on to the next one.
+fn closed_match_day(now: NaiveDateTime, deadline: Deadline) -> Option<NaiveDate> {
+    let match_on = next_match_days(now.date(), 1)[0];
+    (now >= deadline.before(match_on)).then_some(match_on)
+}
+
+/// Check the RSVP deadline every minute in the background
+pub fn spawn(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            pick_if_due(&state).await;
+        }
+    });
+}
+
+/// Once RSVPs close, balance the players who said In, save the split for
+/// Match Day and announce it through the `teams.generated` webhook
+async fn pick_if_due(state: &AppState) {
+    let settings = state.settings.get();
+    let Some(deadline) = settings.rsvp_deadline else {
+        return;
+    };
+    let now = Utc::now().with_timezone(&settings.timezone).naive_local();
+    let Some(match_on) = closed_match_day(now, deadline) else {
+        return;
+    };
+    match db::get_auto_teams(&state.db, match_on).await {
+        Ok(None) => {}
+        Ok(Some(_)) => return,
+        Err(e) => {
+            tracing::error!("Failed to check for picked teams: {}", e);
+            return;
+        }
+    }
+
+    // Players who said In and aren't suspended or injured, earliest answers first;
+    // only two full teams get a place, later RSVPs are left for Match Day
+    let confirmed = match db::get_in_rsvps(&state.db, match_on).await {
+        Ok(ids) => ids,
+        Err(e) => {
+            tracing::error!("Failed to load RSVPs: {}", e);
+            return;
+        }
+    };
+    let suspensions = discipline::load_active(&state.db, settings.today()).await;
+    let injuries = injuries::load_active(&state.db).await;
+    let available: Vec<i32> = confirmed
+        .into_iter()
+        .filter(|id| !suspensions.contains_key(id) && !injuries.contains_key(id))
+        .take(settings.players_per_team * 2)
+        .collect();
+    // The announcement goes out to the group, so hidden names stay hidden
+    let mut players = match db::get_players_by_ids(&state.db, &available).await {
+        Ok(players) => players,
+        Err(e) => {
+            tracing::error!("Failed to load RSVP'd players: {}", e);
+            return;
+        }
+    };
+    if players.len() < 2 {
+        return;
+    }
+    mask_names(&mut players, false);
+
+    // The search is CPU-bound, so keep it off the async workers
+    let balancer = settings.balancer;
+    let options = settings.balance_options();
+    let search = tokio::task::spawn_blocking(move || {
+        let split = balancer.split(&players, &options);
+        (split, players.len())
+    });
+    let (split, picked_from) = match search.await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!("Team picking for {} failed: {}", match_on, e);
+            return;
+        }
+    };
+    let Some(split) = split else {
+        tracing::error!("Could not pick teams for {}", match_on);
+        return;
+    };
+    let ids = |team: &[crate::models::Player]| team.iter().map(|p| p.id).collect::<Vec<_>>();
+    match db::create_auto_teams(
+        &state.db,
+        match_on,
+        &ids(&split.team_a),
+        &ids(&split.team_b),
+    )
+    .await
+    {
+        Ok(true) => {
+            tracing::info!("Picked teams for {} from {} RSVPs", match_on, picked_from);
+            state.webhooks.send(
+                WebhookEvent::TeamsGenerated,
+                announcement(state, match_on, &split),
+            );
+        }
+        // Another instance got there first
+        Ok(false) => {}
+        Err(e) => tracing::error!("Failed to save picked teams: {}", e),
+    }
+}
+
+/// `teams.generated` payload for picked teams, with the match day and the
+/// WhatsApp message so a bot can post it as is
+fn announcement(state: &AppState, match_on: NaiveDate, split: &TeamSplit) -> serde_json::Value {
+    let settings = state.settings.get();
+    let mut data = teams_json(split);
+    data["match_on"] = serde_json::json!(match_on);
+    data["automatic"] = serde_json::json!(true);
+    data["message"] = serde_json::json!(whatsapp_text(
+        &settings.group_name,
+        match_on,
+        &settings.kickoff,
+        &settings.bib_colors,
+        &team_names(settings.team_names, &split.team_a, &split.team_b),
+        &split.team_a,
+        &split.team_b,
+    ));
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 4, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_closed_match_day() {
+        let deadline = Deadline::parse("Sat 20:00").unwrap();
+        let sunday = NaiveDate::from_ymd_opt(2025, 4, 6).unwrap();
+        // Friday and Saturday afternoon: still open
+        assert_eq!(closed_match_day(at(4, 12), deadline), None);
+        assert_eq!(closed_match_day(at(5, 19), deadline), None);
+        // Saturday evening and Sunday: closed for Sunday
+        assert_eq!(closed_match_day(at(5, 20), deadline), Some(sunday));
+        assert_eq!(closed_match_day(at(6, 23), deadline), Some(sunday));
+        // Monday: next week's RSVPs are open again
+        assert_eq!(closed_match_day(at(7, 9), deadline), None);
+    }
+}
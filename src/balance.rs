@@ -1,13 +1,397 @@
-use crate::elo::average_elo;
-use crate::models::{Player, Tag, TeamSplit};
+use crate::models::{Player, Position, TeamSplit};
+use crate::tiers::Tier;
 use itertools::Itertools;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+
+/// Cost per player of difference in each outfield position's headcount
+pub const POSITION_WEIGHT: f32 = 25.0;
+
+/// Cost per player of difference in each skill tier's headcount
+pub const TIER_WEIGHT: f32 = 25.0;
+
+/// Cost per point of difference in team-average stamina, pace and age band
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttributeWeights {
+    pub stamina: f32,
+    pub pace: f32,
+    pub age: f32,
+}
+
+impl Default for AttributeWeights {
+    fn default() -> Self {
+        Self {
+            stamina: 20.0,
+            pace: 20.0,
+            age: 10.0,
+        }
+    }
+}
+
+/// What the balancer optimizes for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Objective {
+    /// Equal average Elo (plus tags and attributes)
+    #[default]
+    Average,
+    /// Also equal Elo spread, so one team can't pair a star with weak players
+    /// against a team of all-rounders
+    Spread,
+}
+
+impl Objective {
+    pub const ALL: [Objective; 2] = [Objective::Average, Objective::Spread];
+
+    /// Form value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Objective::Average => "average",
+            Objective::Spread => "spread",
+        }
+    }
+
+    /// Human-readable label for the Match Day selector
+    pub fn label(self) -> &'static str {
+        match self {
+            Objective::Average => "Balanced averages",
+            Objective::Spread => "Balanced averages + spread",
+        }
+    }
+
+    /// Parse a form value (unknown = default)
+    pub fn from_str(s: &str) -> Objective {
+        match s.trim() {
+            "spread" => Objective::Spread,
+            _ => Objective::Average,
+        }
+    }
+}
+
+/// A way of splitting checked-in players into two teams. Every balancer in
+/// [`BALANCERS`] is offered on Match Day, in the Admin default and in the
+/// comparison, so a new strategy only needs an implementation and an entry there.
+pub trait Balancer: std::fmt::Debug + Sync {
+    /// Form and setting value, e.g. "exact"
+    fn key(&self) -> &'static str;
+
+    /// Human-readable label
+    fn label(&self) -> &'static str;
+
+    /// Split `players`, or None if there are too few
+    fn split(&self, players: &[Player], options: &BalanceOptions) -> Option<TeamSplit>;
+}
+
+/// Every split scored, lowest cost wins ("Generate Teams" by default)
+#[derive(Debug)]
+pub struct Exact;
+
+/// A random split within 10% of the best ("Shuffle")
+#[derive(Debug)]
+pub struct Randomized;
+
+/// Simulated annealing from the draft split ([`annealed_split`])
+#[derive(Debug)]
+pub struct Annealing;
+
+/// Best players first, each to the weaker team ([`greedy_split`])
+#[derive(Debug)]
+pub struct Draft;
+
+impl Balancer for Exact {
+    fn key(&self) -> &'static str {
+        "exact"
+    }
+
+    fn label(&self) -> &'static str {
+        "Exact search"
+    }
+
+    fn split(&self, players: &[Player], options: &BalanceOptions) -> Option<TeamSplit> {
+        balance_teams_with(players, false, options)
+    }
+}
+
+impl Balancer for Randomized {
+    fn key(&self) -> &'static str {
+        "randomized"
+    }
+
+    fn label(&self) -> &'static str {
+        "Randomized"
+    }
+
+    fn split(&self, players: &[Player], options: &BalanceOptions) -> Option<TeamSplit> {
+        balance_teams_with(players, true, options)
+    }
+}
+
+impl Balancer for Annealing {
+    fn key(&self) -> &'static str {
+        "annealing"
+    }
+
+    fn label(&self) -> &'static str {
+        "Simulated annealing"
+    }
+
+    fn split(&self, players: &[Player], options: &BalanceOptions) -> Option<TeamSplit> {
+        annealed_split(players, options)
+    }
+}
+
+impl Balancer for Draft {
+    fn key(&self) -> &'static str {
+        "draft"
+    }
+
+    fn label(&self) -> &'static str {
+        "Greedy draft"
+    }
+
+    fn split(&self, players: &[Player], options: &BalanceOptions) -> Option<TeamSplit> {
+        greedy_split(players, options)
+    }
+}
+
+/// Every balancer, the default (exact search) first
+pub const BALANCERS: [&dyn Balancer; 4] = [&Exact, &Randomized, &Annealing, &Draft];
+
+/// Look up a balancer by its key
+pub fn balancer(key: &str) -> Option<&'static dyn Balancer> {
+    BALANCERS.into_iter().find(|b| b.key() == key.trim())
+}
+
+/// Tuning for the team balancer
+#[derive(Debug, Clone, Default)]
+pub struct BalanceOptions {
+    pub weights: AttributeWeights,
+    pub objective: Objective,
+    /// Elo added per player ID for recent form (empty = form ignored)
+    pub form_bonus: HashMap<i32, f32>,
+    /// Elo added per player ID for their peer rating (empty = peer ratings ignored)
+    pub peer_bonus: HashMap<i32, f32>,
+    /// Elo taken off per player ID for their no-shows (empty = reliability ignored)
+    pub reliability_discount: HashMap<i32, f32>,
+    /// Rating in the player's tagged positions, used instead of overall Elo
+    /// (empty = position ratings ignored)
+    pub position_elo: HashMap<i32, f32>,
+    /// Skill tier per player ID, spread evenly between the teams
+    /// (empty = tiers ignored)
+    pub tiers: HashMap<i32, Tier>,
+}
+
+impl BalanceOptions {
+    /// Base rating: position rating if known, else overall Elo
+    pub fn base_elo(&self, player: &Player) -> f32 {
+        self.position_elo
+            .get(&player.id)
+            .copied()
+            .unwrap_or(player.elo)
+    }
+
+    /// Rating used for balancing: base rating plus any recent-form and
+    /// peer-rating bonus, less any reliability discount
+    pub fn effective_elo(&self, player: &Player) -> f32 {
+        self.base_elo(player)
+            + self.form_bonus.get(&player.id).copied().unwrap_or(0.0)
+            + self.peer_bonus.get(&player.id).copied().unwrap_or(0.0)
+            + self
+                .reliability_discount
+                .get(&player.id)
+                .copied()
+                .unwrap_or(0.0)
+    }
+}
+
+/// Standard deviation of a team's effective Elo (0 for fewer than two players)
+fn elo_spread(team: &[Player], options: &BalanceOptions) -> f32 {
+    if team.len() < 2 {
+        return 0.0;
+    }
+    let mean = team_average(team, |p| options.effective_elo(p));
+    let variance = team
+        .iter()
+        .map(|p| (options.effective_elo(p) - mean).powi(2))
+        .sum::<f32>()
+        / team.len() as f32;
+    variance.sqrt()
+}
+
+/// Highest effective Elo on a team
+fn top_elo(team: &[Player], options: &BalanceOptions) -> f32 {
+    team.iter()
+        .map(|p| options.effective_elo(p))
+        .fold(0.0, f32::max)
+}
+
+/// Difference in Elo spread plus half the difference between each team's best player
+fn spread_cost(team_a: &[Player], team_b: &[Player], options: &BalanceOptions) -> f32 {
+    (elo_spread(team_a, options) - elo_spread(team_b, options)).abs()
+        + (top_elo(team_a, options) - top_elo(team_b, options)).abs() / 2.0
+}
+
+/// Average of a per-player value across a team (0 for an empty team)
+fn team_average(team: &[Player], value: impl Fn(&Player) -> f32) -> f32 {
+    if team.is_empty() {
+        return 0.0;
+    }
+    team.iter().map(value).sum::<f32>() / team.len() as f32
+}
+
+/// Weighted difference in average stamina, pace and age band
+fn attribute_cost(team_a: &[Player], team_b: &[Player], weights: &AttributeWeights) -> f32 {
+    let diff = |value: fn(&Player) -> f32| {
+        (team_average(team_a, value) - team_average(team_b, value)).abs()
+    };
+    weights.stamina * diff(Player::stamina_value)
+        + weights.pace * diff(Player::pace_value)
+        + weights.age * diff(Player::age_value)
+}
+
+/// Number of players on a team in a position
+fn position_count(team: &[Player], position: Position) -> f32 {
+    team.iter()
+        .filter(|p| p.position() == Some(position))
+        .count() as f32
+}
+
+/// Weighted difference in defender, midfielder and attacker headcounts
+/// (goalkeepers are split by the GK rules instead)
+fn position_cost(team_a: &[Player], team_b: &[Player]) -> f32 {
+    Position::OUTFIELD
+        .iter()
+        .map(|&position| {
+            POSITION_WEIGHT
+                * (position_count(team_a, position) - position_count(team_b, position)).abs()
+        })
+        .sum()
+}
+
+/// Number of players on a team in a skill tier
+fn tier_count(team: &[Player], tier: Tier, options: &BalanceOptions) -> f32 {
+    team.iter()
+        .filter(|p| options.tiers.get(&p.id) == Some(&tier))
+        .count() as f32
+}
+
+/// Weighted difference in each skill tier's headcount (0 with tiers off)
+fn tier_cost(team_a: &[Player], team_b: &[Player], options: &BalanceOptions) -> f32 {
+    if options.tiers.is_empty() {
+        return 0.0;
+    }
+    Tier::ALL
+        .iter()
+        .map(|&tier| {
+            TIER_WEIGHT
+                * (tier_count(team_a, tier, options) - tier_count(team_b, tier, options)).abs()
+        })
+        .sum()
+}
+
+/// One term of the split cost, with each team's value for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostTerm {
+    pub name: &'static str,
+    pub value_a: f32,
+    pub value_b: f32,
+    /// This term's contribution to the total cost
+    pub cost: f32,
+}
+
+/// Break a split's cost down by term. The term costs add up to the split's cost.
+pub fn explain_split(
+    team_a: &[Player],
+    team_b: &[Player],
+    options: &BalanceOptions,
+) -> Vec<CostTerm> {
+    let average_term = |name, value: &dyn Fn(&Player) -> f32, weight: f32| {
+        let value_a = team_average(team_a, value);
+        let value_b = team_average(team_b, value);
+        CostTerm {
+            name,
+            value_a,
+            value_b,
+            cost: weight * (value_a - value_b).abs(),
+        }
+    };
+    let tag_value = |team: &[Player]| team.iter().map(|p| p.tag_value()).sum::<i32>() as f32;
+    let weights = &options.weights;
+
+    let mut terms = vec![
+        average_term("Average Elo", &|p| options.effective_elo(p), 1.0),
+        CostTerm {
+            name: "Tag value",
+            value_a: tag_value(team_a),
+            value_b: tag_value(team_b),
+            cost: (tag_value(team_a) - tag_value(team_b)).abs(),
+        },
+        average_term("Stamina", &Player::stamina_value, weights.stamina),
+        average_term("Pace", &Player::pace_value, weights.pace),
+        average_term("Age band", &Player::age_value, weights.age),
+    ];
+
+    // Position terms only mean something once positions are filled in
+    if team_a.iter().chain(team_b).any(|p| p.position().is_some()) {
+        for (name, position) in [
+            ("Defenders", Position::Def),
+            ("Midfielders", Position::Mid),
+            ("Attackers", Position::Att),
+        ] {
+            let value_a = position_count(team_a, position);
+            let value_b = position_count(team_b, position);
+            terms.push(CostTerm {
+                name,
+                value_a,
+                value_b,
+                cost: POSITION_WEIGHT * (value_a - value_b).abs(),
+            });
+        }
+    }
+
+    if !options.tiers.is_empty() {
+        for tier in Tier::ALL {
+            let value_a = tier_count(team_a, tier, options);
+            let value_b = tier_count(team_b, tier, options);
+            terms.push(CostTerm {
+                name: tier.term_name(),
+                value_a,
+                value_b,
+                cost: TIER_WEIGHT * (value_a - value_b).abs(),
+            });
+        }
+    }
+
+    if options.objective == Objective::Spread {
+        let spread_a = elo_spread(team_a, options);
+        let spread_b = elo_spread(team_b, options);
+        let top_a = top_elo(team_a, options);
+        let top_b = top_elo(team_b, options);
+        terms.push(CostTerm {
+            name: "Elo spread (σ)",
+            value_a: spread_a,
+            value_b: spread_b,
+            cost: (spread_a - spread_b).abs(),
+        });
+        terms.push(CostTerm {
+            name: "Best player (½)",
+            value_a: top_a,
+            value_b: top_b,
+            cost: (top_a - top_b).abs() / 2.0,
+        });
+    }
+
+    terms
+}
 
 /// Calculate the cost of a team split
-fn calculate_split_cost(team_a: &[Player], team_b: &[Player]) -> TeamSplit {
-    let elo_a = average_elo(team_a);
-    let elo_b = average_elo(team_b);
+fn calculate_split_cost(
+    team_a: &[Player],
+    team_b: &[Player],
+    options: &BalanceOptions,
+) -> TeamSplit {
+    let elo_a = team_average(team_a, |p| options.effective_elo(p));
+    let elo_b = team_average(team_b, |p| options.effective_elo(p));
     let elo_diff = (elo_a - elo_b).abs();
 
     // Balance team "tag value" (sum of player tag values) instead of per-tag counts
@@ -15,40 +399,158 @@ fn calculate_split_cost(team_a: &[Player], team_b: &[Player]) -> TeamSplit {
     let tag_value_b: i32 = team_b.iter().map(|p| p.tag_value()).sum();
     let tag_diff = (tag_value_a - tag_value_b).abs() as f32;
 
+    let attribute_cost = attribute_cost(team_a, team_b, &options.weights);
+    let position_cost = position_cost(team_a, team_b);
+    let tier_cost = tier_cost(team_a, team_b, options);
+
+    let spread_cost = match options.objective {
+        Objective::Average => 0.0,
+        Objective::Spread => spread_cost(team_a, team_b, options),
+    };
+
     TeamSplit {
         team_a: team_a.to_vec(),
         team_b: team_b.to_vec(),
-        cost: elo_diff + tag_diff,
+        cost: elo_diff + tag_diff + attribute_cost + position_cost + tier_cost + spread_cost,
         elo_diff,
-        tag_value_a,
-        tag_value_b,
     }
 }
 
-/// Balance teams from a list of players
-/// Returns the optimal split, or a random good split if randomize=true
+/// Score an existing split (e.g. teams picked earlier or by hand)
+pub fn score_split(team_a: &[Player], team_b: &[Player], options: &BalanceOptions) -> TeamSplit {
+    calculate_split_cost(team_a, team_b, options)
+}
+
+/// Balance teams from a list of players with default options
+#[cfg(test)]
 pub fn balance_teams(players: &[Player], randomize: bool) -> Option<TeamSplit> {
+    balance_teams_with(players, randomize, &BalanceOptions::default())
+}
+
+/// Largest turnout the exact search enumerates (C(18, 9) = 48,620 splits).
+/// Bigger turnouts are balanced by [`annealed_split`] instead.
+pub const EXACT_LIMIT: usize = 18;
+
+/// Balance teams from a list of players
+/// Returns the optimal split, or a random good split if randomize=true.
+/// Above [`EXACT_LIMIT`] players it falls back to simulated annealing.
+#[tracing::instrument(skip_all, fields(players = players.len(), randomize))]
+pub fn balance_teams_with(
+    players: &[Player],
+    randomize: bool,
+    options: &BalanceOptions,
+) -> Option<TeamSplit> {
     if players.len() < 2 {
         return None;
     }
+    if players.len() > EXACT_LIMIT {
+        return annealed_split(players, options);
+    }
 
+    // Single GK: assign to team A (deterministic), or random if randomize=true
+    let gk_on_team_a = if randomize {
+        rand::random::<bool>()
+    } else {
+        true
+    };
+
+    let mut best: Option<TeamSplit> = None;
+    for_each_split(players, gk_on_team_a, options, |split| {
+        if best.as_ref().is_none_or(|b| split.cost < b.cost) {
+            best = Some(split);
+        }
+    });
+    let best = best?;
+    if !randomize {
+        return Some(best);
+    }
+
+    // Pick uniformly from splits within 10% of optimal (+ 1 for zero cost case),
+    // one pass of reservoir sampling so no split is kept longer than needed
+    let threshold = best.cost * 1.1 + 1.0;
+    let mut rng = thread_rng();
+    let mut good_splits = 0;
+    let mut picked = None;
+    for_each_split(players, gk_on_team_a, options, |split| {
+        if split.cost <= threshold {
+            good_splits += 1;
+            if rng.gen_range(0..good_splits) == 0 {
+                picked = Some(split);
+            }
+        }
+    });
+    picked.or(Some(best))
+}
+
+/// The lowest-cost distinct splits, best first (mirror-image splits count once).
+/// Used to show which alternatives the balancer considered. Only the best
+/// `limit` are kept while searching; above [`EXACT_LIMIT`] players there is
+/// just the annealed split.
+#[tracing::instrument(skip_all, fields(players = players.len()))]
+pub fn top_splits(players: &[Player], options: &BalanceOptions, limit: usize) -> Vec<TeamSplit> {
+    if players.len() < 2 {
+        return Vec::new();
+    }
+    if players.len() > EXACT_LIMIT {
+        return annealed_split(players, options)
+            .into_iter()
+            .take(limit)
+            .collect();
+    }
+
+    let mut best: Vec<TeamSplit> = Vec::with_capacity(limit + 1);
+    for_each_split(players, true, options, |split| {
+        if best.len() == limit && best.last().is_none_or(|worst| split.cost >= worst.cost) {
+            return;
+        }
+        let key = split_key(&split);
+        if best.iter().any(|kept| split_key(kept) == key) {
+            return;
+        }
+        // After any equal-cost splits, so ties keep the order they were found in
+        let at = best.partition_point(|kept| kept.cost <= split.cost);
+        best.insert(at, split);
+        best.truncate(limit);
+    });
+    best
+}
+
+/// Identify a split regardless of which side is called A: the sorted IDs of
+/// the team holding the lowest player ID
+pub fn split_key(split: &TeamSplit) -> Vec<i32> {
+    let min_id = |team: &[Player]| team.iter().map(|p| p.id).min();
+    let side = if min_id(&split.team_a) <= min_id(&split.team_b) {
+        &split.team_a
+    } else {
+        &split.team_b
+    };
+    let mut ids: Vec<i32> = side.iter().map(|p| p.id).collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// Visit every split the balancer considers, honouring GK rules. Splits are
+/// handed over one at a time rather than collected: 11-a-side has 705,432.
+fn for_each_split(
+    players: &[Player],
+    gk_on_team_a: bool,
+    options: &BalanceOptions,
+    mut visit: impl FnMut(TeamSplit),
+) {
     let team_size = players.len() / 2;
 
     // Identify goalkeepers
     let gks: Vec<_> = players
         .iter()
-        .filter(|p| p.has_tag(Tag::Gk))
+        .filter(|p| p.is_goalkeeper())
         .cloned()
         .collect();
     let non_gks: Vec<_> = players
         .iter()
-        .filter(|p| !p.has_tag(Tag::Gk))
+        .filter(|p| !p.is_goalkeeper())
         .cloned()
         .collect();
 
-    let mut all_splits: Vec<TeamSplit> = Vec::new();
-    let mut best_split: Option<TeamSplit> = None;
-
     if gks.len() == 2 {
         // Force split: one GK per team
         let gk_a = &gks[0];
@@ -68,24 +570,13 @@ pub fn balance_teams(players: &[Player], randomize: bool) -> Option<TeamSplit> {
                 let mut team_b = vec![gk_b.clone()];
                 team_b.extend(team_b_rest);
 
-                let split = calculate_split_cost(&team_a, &team_b);
-
-                if best_split.is_none() || split.cost < best_split.as_ref().unwrap().cost {
-                    best_split = Some(split.clone());
-                }
-                all_splits.push(split);
+                visit(calculate_split_cost(&team_a, &team_b, options));
             }
 
-            return pick_split(best_split, all_splits, randomize);
+            return;
         }
     } else if gks.len() == 1 {
-        // Single GK: assign to team A (deterministic), or random if randomize=true
         let gk = &gks[0];
-        let gk_on_team_a = if randomize {
-            rand::random::<bool>()
-        } else {
-            true
-        };
 
         // Combo size depends on which team gets the GK
         // Team A needs (team_size - 1) non-GKs if GK is on team A
@@ -122,15 +613,10 @@ pub fn balance_teams(players: &[Player], randomize: bool) -> Option<TeamSplit> {
                 (a, b)
             };
 
-            let split = calculate_split_cost(&team_a, &team_b);
-
-            if best_split.is_none() || split.cost < best_split.as_ref().unwrap().cost {
-                best_split = Some(split.clone());
-            }
-            all_splits.push(split);
+            visit(calculate_split_cost(&team_a, &team_b, options));
         }
 
-        return pick_split(best_split, all_splits, randomize);
+        return;
     }
 
     // No GK special logic - standard brute force
@@ -142,39 +628,222 @@ pub fn balance_teams(players: &[Player], randomize: bool) -> Option<TeamSplit> {
             .cloned()
             .collect();
 
-        let split = calculate_split_cost(&team_a, &team_b);
+        visit(calculate_split_cost(&team_a, &team_b, options));
+    }
+}
+
+/// Greedy heuristic split: goalkeepers alternate between the teams, then
+/// everyone else, best effective Elo first, joins the team with the lower
+/// total effective Elo that still has room. Linear after sorting, so it scales
+/// to any turnout, but ignores tags and attributes while choosing (they still
+/// count in the reported cost).
+pub fn greedy_split(players: &[Player], options: &BalanceOptions) -> Option<TeamSplit> {
+    if players.len() < 2 {
+        return None;
+    }
+    let capacity_a = players.len() / 2;
+    let capacity_b = players.len() - capacity_a;
+
+    let mut order: Vec<&Player> = players.iter().collect();
+    order.sort_by(|a, b| {
+        b.is_goalkeeper().cmp(&a.is_goalkeeper()).then(
+            options
+                .effective_elo(b)
+                .total_cmp(&options.effective_elo(a)),
+        )
+    });
 
-        if best_split.is_none() || split.cost < best_split.as_ref().unwrap().cost {
-            best_split = Some(split.clone());
+    let mut team_a: Vec<Player> = Vec::with_capacity(capacity_a);
+    let mut team_b: Vec<Player> = Vec::with_capacity(capacity_b);
+    let (mut total_a, mut total_b) = (0.0, 0.0);
+    for player in order {
+        let to_a = if team_a.len() == capacity_a {
+            false
+        } else if team_b.len() == capacity_b {
+            true
+        } else if player.is_goalkeeper() {
+            // Keepers alternate so two end up on opposite teams
+            team_a.iter().filter(|p| p.is_goalkeeper()).count()
+                <= team_b.iter().filter(|p| p.is_goalkeeper()).count()
+        } else {
+            total_a <= total_b
+        };
+        if to_a {
+            total_a += options.effective_elo(player);
+            team_a.push(player.clone());
+        } else {
+            total_b += options.effective_elo(player);
+            team_b.push(player.clone());
         }
-        all_splits.push(split);
     }
 
-    pick_split(best_split, all_splits, randomize)
+    Some(calculate_split_cost(&team_a, &team_b, options))
 }
 
-/// Pick the final split - either best or random from near-optimal
-fn pick_split(best: Option<TeamSplit>, all: Vec<TeamSplit>, randomize: bool) -> Option<TeamSplit> {
-    let best = best?;
+/// Temperature (in cost points) simulated annealing starts at
+const ANNEALING_START: f32 = 50.0;
+/// Share of the temperature kept after each step
+const ANNEALING_COOLING: f32 = 0.995;
+/// Swaps tried by simulated annealing
+const ANNEALING_STEPS: usize = 2000;
 
-    if randomize && !all.is_empty() {
-        // Pick randomly from splits within 10% of optimal (+ 1 for zero cost case)
-        let threshold = best.cost * 1.1 + 1.0;
-        let good_splits: Vec<_> = all.into_iter().filter(|s| s.cost <= threshold).collect();
+/// Simulated annealing: start from the [`greedy_split`], then try random
+/// swaps between the teams, always keeping cheaper ones and sometimes worse
+/// ones while the temperature is high, to escape local minima. Only keepers
+/// swap with keepers, so the draft's goalkeeper split stays. Unlike the exact
+/// search it stays fast for any turnout and, unlike the draft, it weighs tags
+/// and attributes while choosing. Returns the cheapest split seen.
+pub fn annealed_split(players: &[Player], options: &BalanceOptions) -> Option<TeamSplit> {
+    let start = greedy_split(players, options)?;
+    let mut rng = thread_rng();
+    let mut current = start.clone();
+    let mut best = start;
+    let mut temperature = ANNEALING_START;
 
-        if !good_splits.is_empty() {
-            let mut rng = thread_rng();
-            return good_splits.choose(&mut rng).cloned();
+    for _ in 0..ANNEALING_STEPS {
+        temperature *= ANNEALING_COOLING;
+        let i = rng.gen_range(0..current.team_a.len());
+        let keeper = current.team_a[i].is_goalkeeper();
+        let swappable: Vec<usize> = (0..current.team_b.len())
+            .filter(|j| current.team_b[*j].is_goalkeeper() == keeper)
+            .collect();
+        let Some(&j) = swappable.choose(&mut rng) else {
+            continue;
+        };
+
+        let mut team_a = current.team_a.clone();
+        let mut team_b = current.team_b.clone();
+        std::mem::swap(&mut team_a[i], &mut team_b[j]);
+        let candidate = calculate_split_cost(&team_a, &team_b, options);
+        let worse_by = candidate.cost - current.cost;
+        if worse_by <= 0.0 || rng.gen::<f32>() < (-worse_by / temperature).exp() {
+            if candidate.cost < best.cost {
+                best = candidate.clone();
+            }
+            current = candidate;
         }
     }
 
     Some(best)
 }
 
+/// Result of adding a late arrival to existing teams
+#[derive(Debug, Clone)]
+pub struct LateArrival {
+    pub split: TeamSplit,
+    /// Whether the newcomer joined Team A
+    pub joined_team_a: bool,
+    /// Compensating swap made, if any: (moved from A to B, moved from B to A)
+    pub swap: Option<(Player, Player)>,
+}
+
+/// Add a late arrival to an existing split without regenerating it.
+///
+/// The newcomer joins whichever team gives the lower cost. With `allow_swap`,
+/// one swap between the teams is also considered (the newcomer and GKs stay
+/// put), and made only if it strictly lowers the cost.
+pub fn add_late_player(
+    team_a: &[Player],
+    team_b: &[Player],
+    newcomer: &Player,
+    allow_swap: bool,
+    options: &BalanceOptions,
+) -> LateArrival {
+    let mut best: Option<LateArrival> = None;
+
+    for joined_team_a in [true, false] {
+        let mut a = team_a.to_vec();
+        let mut b = team_b.to_vec();
+        if joined_team_a {
+            a.push(newcomer.clone());
+        } else {
+            b.push(newcomer.clone());
+        }
+
+        let mut candidates = vec![(calculate_split_cost(&a, &b, options), None)];
+        if allow_swap {
+            let swappable = |p: &&Player| p.id != newcomer.id && !p.is_goalkeeper();
+            for (i, from_a) in a.iter().enumerate().filter(|(_, p)| swappable(p)) {
+                for (j, from_b) in b.iter().enumerate().filter(|(_, p)| swappable(p)) {
+                    let mut swapped_a = a.clone();
+                    let mut swapped_b = b.clone();
+                    swapped_a[i] = from_b.clone();
+                    swapped_b[j] = from_a.clone();
+                    let split = calculate_split_cost(&swapped_a, &swapped_b, options);
+                    candidates.push((split, Some((from_a.clone(), from_b.clone()))));
+                }
+            }
+        }
+
+        for (split, swap) in candidates {
+            if best.as_ref().is_none_or(|b| split.cost < b.split.cost) {
+                best = Some(LateArrival {
+                    split,
+                    joined_team_a,
+                    swap,
+                });
+            }
+        }
+    }
+
+    best.expect("at least two candidate placements")
+}
+
+/// A single swap between the teams and how much it would lower the cost
+#[derive(Debug, Clone)]
+pub struct SwapSuggestion {
+    /// Player moving from Team A to Team B
+    pub from_a: Player,
+    /// Player moving from Team B to Team A
+    pub from_b: Player,
+    /// Cost reduction (always positive)
+    pub improvement: f32,
+    /// The split after the swap
+    pub split: TeamSplit,
+}
+
+/// Up to `limit` single-player swaps that would most improve the current split,
+/// best first. Swaps that would move a GK without a GK coming back are skipped.
+pub fn suggest_swaps(
+    team_a: &[Player],
+    team_b: &[Player],
+    options: &BalanceOptions,
+    limit: usize,
+) -> Vec<SwapSuggestion> {
+    let current = calculate_split_cost(team_a, team_b, options).cost;
+    let mut suggestions = Vec::new();
+
+    for (i, from_a) in team_a.iter().enumerate() {
+        for (j, from_b) in team_b.iter().enumerate() {
+            if from_a.is_goalkeeper() != from_b.is_goalkeeper() {
+                continue;
+            }
+            let mut a = team_a.to_vec();
+            let mut b = team_b.to_vec();
+            a[i] = from_b.clone();
+            b[j] = from_a.clone();
+            let split = calculate_split_cost(&a, &b, options);
+            let improvement = current - split.cost;
+            if improvement > 0.01 {
+                suggestions.push(SwapSuggestion {
+                    from_a: from_a.clone(),
+                    from_b: from_b.clone(),
+                    improvement,
+                    split,
+                });
+            }
+        }
+    }
+
+    suggestions.sort_by(|x, y| y.improvement.total_cmp(&x.improvement));
+    suggestions.truncate(limit);
+    suggestions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use crate::models::Tag;
 
     fn make_player(id: i32, name: &str, elo: f32, tags: &str) -> Player {
         Player {
@@ -182,8 +851,7 @@ mod tests {
             name: name.to_string(),
             elo,
             tags: tags.to_string(),
-            matches_played: 0,
-            created_at: Utc::now(),
+            ..Default::default()
         }
     }
 
@@ -332,4 +1000,443 @@ mod tests {
         let star2_in_a = split.team_a.iter().any(|p| p.id == 2);
         assert_ne!(star1_in_a, star2_in_a, "Stars should be on different teams");
     }
+
+    #[test]
+    fn test_attributes_split_between_teams() {
+        let mut players = vec![
+            make_player(1, "Fast1", 1200.0, ""),
+            make_player(2, "Fast2", 1200.0, ""),
+            make_player(3, "Slow1", 1200.0, ""),
+            make_player(4, "Slow2", 1200.0, ""),
+        ];
+        players[0].pace = Some(5);
+        players[1].pace = Some(5);
+        players[2].pace = Some(1);
+        players[3].pace = Some(1);
+
+        let split = balance_teams(&players, false).unwrap();
+
+        let fast_a = split.team_a.iter().filter(|p| p.pace == Some(5)).count();
+        assert_eq!(fast_a, 1);
+        assert_eq!(
+            attribute_cost(&split.team_a, &split.team_b, &AttributeWeights::default()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_zero_weights_ignore_attributes() {
+        let mut fast = make_player(1, "Fast", 1200.0, "");
+        fast.pace = Some(5);
+        let slow = make_player(2, "Slow", 1200.0, "");
+        let options = BalanceOptions {
+            weights: AttributeWeights {
+                stamina: 0.0,
+                pace: 0.0,
+                age: 0.0,
+            },
+            ..Default::default()
+        };
+
+        let split = calculate_split_cost(&[fast], &[slow], &options);
+        assert_eq!(split.cost, 0.0);
+    }
+
+    #[test]
+    fn test_spread_objective_avoids_stacked_pairing() {
+        let players = vec![
+            make_player(1, "Star", 1600.0, ""),
+            make_player(2, "Newbie", 1000.0, ""),
+            make_player(3, "Mid1", 1300.0, ""),
+            make_player(4, "Mid2", 1300.0, ""),
+            make_player(5, "Mid3", 1250.0, ""),
+            make_player(6, "Mid4", 1250.0, ""),
+        ];
+        let together = |split: &TeamSplit| {
+            [&split.team_a, &split.team_b]
+                .iter()
+                .any(|t| t.iter().any(|p| p.id == 1) && t.iter().any(|p| p.id == 2))
+        };
+
+        // Star + Newbie averages out perfectly, so the default objective pairs them
+        let average = balance_teams(&players, false).unwrap();
+        assert!(together(&average));
+
+        let options = BalanceOptions {
+            objective: Objective::Spread,
+            ..Default::default()
+        };
+        let spread = balance_teams_with(&players, false, &options).unwrap();
+        assert!(!together(&spread));
+    }
+
+    #[test]
+    fn test_average_objective_has_no_spread_cost() {
+        let players = vec![
+            make_player(1, "Star", 1600.0, ""),
+            make_player(2, "Newbie", 1000.0, ""),
+        ];
+        let split = balance_teams(&players, false).unwrap();
+        let terms = explain_split(&split.team_a, &split.team_b, &BalanceOptions::default());
+        assert!(terms.iter().all(|t| !t.name.starts_with("Elo spread")));
+        assert_eq!(split.cost, split.elo_diff);
+    }
+
+    #[test]
+    fn test_elo_spread() {
+        let team = vec![
+            make_player(1, "A", 1100.0, ""),
+            make_player(2, "B", 1300.0, ""),
+        ];
+        let options = BalanceOptions::default();
+        assert!((elo_spread(&team, &options) - 100.0).abs() < 0.001);
+        assert_eq!(elo_spread(&team[..1], &options), 0.0);
+    }
+
+    #[test]
+    fn test_objective_from_str() {
+        for objective in Objective::ALL {
+            assert_eq!(Objective::from_str(objective.as_str()), objective);
+        }
+        assert_eq!(Objective::from_str("bogus"), Objective::Average);
+    }
+
+    #[test]
+    fn test_form_bonus_shifts_effective_elo() {
+        let players = vec![
+            make_player(1, "Hot", 1200.0, ""),
+            make_player(2, "Cold", 1200.0, ""),
+        ];
+        let options = BalanceOptions {
+            form_bonus: HashMap::from([(1, 50.0), (2, -50.0)]),
+            ..Default::default()
+        };
+
+        let split = balance_teams_with(&players, false, &options).unwrap();
+        assert_eq!(split.elo_diff, 100.0);
+        assert_eq!(options.effective_elo(&players[0]), 1250.0);
+
+        // Peer-rating bonuses stack on top
+        let options = BalanceOptions {
+            peer_bonus: HashMap::from([(1, -25.0)]),
+            ..options
+        };
+        assert_eq!(options.effective_elo(&players[0]), 1225.0);
+
+        // And so do reliability discounts
+        let options = BalanceOptions {
+            reliability_discount: HashMap::from([(1, -20.0)]),
+            ..options
+        };
+        assert_eq!(options.effective_elo(&players[0]), 1205.0);
+    }
+
+    #[test]
+    fn test_position_elo_replaces_overall_elo() {
+        let player = make_player(1, "Defender", 1200.0, "DEF");
+        let options = BalanceOptions {
+            position_elo: HashMap::from([(1, 1300.0)]),
+            form_bonus: HashMap::from([(1, 10.0)]),
+            ..Default::default()
+        };
+
+        assert_eq!(options.base_elo(&player), 1300.0);
+        assert_eq!(options.effective_elo(&player), 1310.0);
+        assert_eq!(BalanceOptions::default().base_elo(&player), 1200.0);
+    }
+
+    #[test]
+    fn test_late_player_joins_weaker_team() {
+        let team_a = vec![make_player(1, "Strong", 1400.0, "")];
+        let team_b = vec![make_player(2, "Weak", 1000.0, "")];
+        let late = make_player(3, "Late", 1300.0, "");
+
+        let result = add_late_player(&team_a, &team_b, &late, false, &BalanceOptions::default());
+
+        assert!(!result.joined_team_a);
+        assert!(result.swap.is_none());
+        assert_eq!(result.split.team_b.len(), 2);
+    }
+
+    #[test]
+    fn test_late_player_swap_only_when_better() {
+        // Already balanced: no swap needed
+        let team_a = vec![
+            make_player(1, "A1", 1200.0, ""),
+            make_player(2, "A2", 1200.0, ""),
+        ];
+        let team_b = vec![
+            make_player(3, "B1", 1200.0, ""),
+            make_player(4, "B2", 1200.0, ""),
+        ];
+        let late = make_player(5, "Late", 1200.0, "");
+        let result = add_late_player(&team_a, &team_b, &late, true, &BalanceOptions::default());
+        assert!(result.swap.is_none());
+
+        // Lopsided: a swap evens it out
+        let team_a = vec![
+            make_player(1, "Star1", 1500.0, ""),
+            make_player(2, "Star2", 1500.0, ""),
+        ];
+        let team_b = vec![
+            make_player(3, "Weak1", 1000.0, ""),
+            make_player(4, "Weak2", 1000.0, ""),
+        ];
+        let late = make_player(5, "Late", 1250.0, "");
+        let no_swap = add_late_player(&team_a, &team_b, &late, false, &BalanceOptions::default());
+        let with_swap = add_late_player(&team_a, &team_b, &late, true, &BalanceOptions::default());
+        assert!(with_swap.swap.is_some());
+        assert!(with_swap.split.cost < no_swap.split.cost);
+    }
+
+    #[test]
+    fn test_late_player_swap_keeps_gks() {
+        let team_a = vec![
+            make_player(1, "GK", 1600.0, "GK"),
+            make_player(2, "A", 1200.0, ""),
+        ];
+        let team_b = vec![make_player(3, "B", 1000.0, "")];
+        let late = make_player(4, "Late", 1000.0, "");
+
+        let result = add_late_player(&team_a, &team_b, &late, true, &BalanceOptions::default());
+
+        assert!(result.split.team_a.iter().any(|p| p.id == 1));
+    }
+
+    #[test]
+    fn test_suggest_swaps_best_first() {
+        let team_a = vec![
+            make_player(1, "Star", 1600.0, ""),
+            make_player(2, "Good", 1400.0, ""),
+        ];
+        let team_b = vec![
+            make_player(3, "Okay", 1200.0, ""),
+            make_player(4, "Weak", 1000.0, ""),
+        ];
+
+        let swaps = suggest_swaps(&team_a, &team_b, &BalanceOptions::default(), 3);
+
+        assert!(!swaps.is_empty() && swaps.len() <= 3);
+        // Star <-> Okay evens it out completely (1300 vs 1300)
+        assert_eq!(swaps[0].from_a.id, 1);
+        assert_eq!(swaps[0].from_b.id, 3);
+        assert_eq!(swaps[0].split.elo_diff, 0.0);
+        assert!(swaps
+            .windows(2)
+            .all(|w| w[0].improvement >= w[1].improvement));
+    }
+
+    #[test]
+    fn test_suggest_swaps_none_when_balanced() {
+        let team_a = vec![make_player(1, "A", 1200.0, "")];
+        let team_b = vec![make_player(2, "B", 1200.0, "")];
+        assert!(suggest_swaps(&team_a, &team_b, &BalanceOptions::default(), 3).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_swaps_keeps_gks_apart() {
+        let team_a = vec![
+            make_player(1, "GK", 1600.0, "GK"),
+            make_player(2, "A", 1200.0, ""),
+        ];
+        let team_b = vec![
+            make_player(3, "B", 1000.0, ""),
+            make_player(4, "C", 1000.0, ""),
+        ];
+        let swaps = suggest_swaps(&team_a, &team_b, &BalanceOptions::default(), 3);
+        assert!(swaps.iter().all(|s| s.from_a.id != 1));
+    }
+
+    #[test]
+    fn test_explain_split_adds_up_to_cost() {
+        let mut players = [
+            make_player(1, "Star", 1600.0, "PLAYMAKER"),
+            make_player(2, "Newbie", 1000.0, "GK"),
+            make_player(3, "Mid1", 1300.0, "RUNNER"),
+            make_player(4, "Mid2", 1250.0, ""),
+        ];
+        players[0].stamina = Some(5);
+        players[2].pace = Some(2);
+        players[3].age_band = Some("45+".to_string());
+        players[2].position = Some("MID".to_string());
+        let options = BalanceOptions {
+            objective: Objective::Spread,
+            form_bonus: HashMap::from([(1, 25.0)]),
+            ..Default::default()
+        };
+
+        let split = score_split(&players[..2], &players[2..], &options);
+        let terms = explain_split(&split.team_a, &split.team_b, &options);
+
+        let total: f32 = terms.iter().map(|t| t.cost).sum();
+        assert!((total - split.cost).abs() < 0.01);
+        assert_eq!(terms[0].value_a, 1312.5); // (1625 + 1000) / 2
+    }
+
+    #[test]
+    fn test_positions_split_between_teams() {
+        let mut players = vec![
+            make_player(1, "Def1", 1200.0, ""),
+            make_player(2, "Def2", 1200.0, ""),
+            make_player(3, "Att1", 1200.0, ""),
+            make_player(4, "Att2", 1200.0, ""),
+        ];
+        for (player, position) in players.iter_mut().zip(["DEF", "DEF", "ATT", "ATT"]) {
+            player.position = Some(position.to_string());
+        }
+
+        let split = balance_teams(&players, false).unwrap();
+
+        assert_eq!(position_count(&split.team_a, Position::Def), 1.0);
+        assert_eq!(position_count(&split.team_a, Position::Att), 1.0);
+        assert_eq!(split.cost, 0.0);
+    }
+
+    #[test]
+    fn test_tiers_split_between_teams() {
+        // Elo alone would pair the two S-tier players against the two B-tier ones
+        let players = vec![
+            make_player(1, "S1", 1400.0, ""),
+            make_player(2, "S2", 1400.0, ""),
+            make_player(3, "B1", 1000.0, ""),
+            make_player(4, "B2", 1000.0, ""),
+            make_player(5, "A1", 1200.0, ""),
+            make_player(6, "A2", 1200.0, ""),
+        ];
+        let options = BalanceOptions {
+            tiers: HashMap::from([
+                (1, Tier::S),
+                (2, Tier::S),
+                (3, Tier::B),
+                (4, Tier::B),
+                (5, Tier::A),
+                (6, Tier::A),
+            ]),
+            ..Default::default()
+        };
+
+        let split = balance_teams_with(&players, false, &options).unwrap();
+
+        for tier in Tier::ALL {
+            assert_eq!(
+                tier_count(&split.team_a, tier, &options),
+                tier_count(&split.team_b, tier, &options)
+            );
+        }
+        assert_eq!(split.cost, 0.0);
+    }
+
+    #[test]
+    fn test_gk_position_counts_as_goalkeeper() {
+        let mut players = vec![
+            make_player(1, "Keeper1", 1200.0, ""),
+            make_player(2, "Keeper2", 1200.0, "GK"),
+            make_player(3, "Out1", 1200.0, ""),
+            make_player(4, "Out2", 1200.0, ""),
+        ];
+        players[0].position = Some("GK".to_string());
+
+        let split = balance_teams(&players, false).unwrap();
+
+        let gks_a = split.team_a.iter().filter(|p| p.is_goalkeeper()).count();
+        assert_eq!(gks_a, 1);
+    }
+
+    #[test]
+    fn test_greedy_split() {
+        let players = vec![
+            make_player(1, "Star", 1500.0, ""),
+            make_player(2, "Good", 1400.0, ""),
+            make_player(3, "Keeper", 1100.0, "GK"),
+            make_player(4, "Okay", 1300.0, ""),
+            make_player(5, "Weak", 1000.0, ""),
+        ];
+
+        let split = greedy_split(&players, &BalanceOptions::default()).unwrap();
+
+        assert_eq!(split.team_a.len(), 2);
+        assert_eq!(split.team_b.len(), 3);
+        assert_eq!(split.team_a[0].id, 3); // keepers first
+        assert!(split.team_b.iter().any(|p| p.id == 1));
+        // Never better than the exact search
+        let exact = Exact.split(&players, &BalanceOptions::default()).unwrap();
+        assert!(exact.cost <= split.cost);
+    }
+
+    #[test]
+    fn test_annealed_split() {
+        let players: Vec<Player> = (1..=10)
+            .map(|id| {
+                let tags = if id <= 2 { "GK" } else { "" };
+                make_player(id, &format!("P{}", id), 1000.0 + 50.0 * id as f32, tags)
+            })
+            .collect();
+        let options = BalanceOptions::default();
+
+        let split = annealed_split(&players, &options).unwrap();
+
+        assert_eq!(split.team_a.len(), 5);
+        assert_eq!(split.team_b.len(), 5);
+        // Keepers stay on opposite teams
+        assert_eq!(split.team_a.iter().filter(|p| p.is_goalkeeper()).count(), 1);
+        // Never worse than the draft it starts from, never better than exact
+        let draft = greedy_split(&players, &options).unwrap();
+        let exact = Exact.split(&players, &options).unwrap();
+        assert!(split.cost <= draft.cost);
+        assert!(exact.cost <= split.cost + 0.001);
+    }
+
+    #[test]
+    fn test_balancer_lookup() {
+        for b in BALANCERS {
+            assert_eq!(balancer(b.key()).unwrap().key(), b.key());
+        }
+        assert_eq!(balancer(" draft ").unwrap().label(), "Greedy draft");
+        assert!(balancer("magic").is_none());
+    }
+
+    #[test]
+    fn test_top_splits_skips_mirrors() {
+        let players = vec![
+            make_player(1, "A", 1400.0, ""),
+            make_player(2, "B", 1300.0, ""),
+            make_player(3, "C", 1200.0, ""),
+            make_player(4, "D", 1100.0, ""),
+        ];
+
+        // 4 players → only 3 distinct 2v2 splits
+        let splits = top_splits(&players, &BalanceOptions::default(), 10);
+        assert_eq!(splits.len(), 3);
+        assert!(splits.windows(2).all(|w| w[0].cost <= w[1].cost));
+        // Best: {A, D} vs {B, C}
+        assert_eq!(split_key(&splits[0]), vec![1, 4]);
+    }
+
+    #[test]
+    fn test_top_splits_keeps_only_the_best() {
+        let players: Vec<Player> = (1..=8)
+            .map(|id| make_player(id, "P", 1000.0 + id as f32 * 50.0, ""))
+            .collect();
+
+        let all = top_splits(&players, &BalanceOptions::default(), 100);
+        let top = top_splits(&players, &BalanceOptions::default(), 3);
+        // 8 players → 35 distinct 4v4 splits; the top 3 are the same either way
+        assert_eq!(all.len(), 35);
+        assert_eq!(top.len(), 3);
+        for (kept, expected) in top.iter().zip(&all) {
+            assert_eq!(kept.cost, expected.cost);
+        }
+    }
+
+    #[test]
+    fn test_large_turnout_falls_back_to_annealing() {
+        let players: Vec<Player> = (1..=EXACT_LIMIT as i32 + 4)
+            .map(|id| make_player(id, "P", 800.0 + id as f32 * 30.0, ""))
+            .collect();
+
+        let split = Exact.split(&players, &BalanceOptions::default()).unwrap();
+        assert_eq!(split.team_a.len() + split.team_b.len(), players.len());
+        assert_eq!(split.team_a.len(), players.len() / 2);
+        assert!(top_splits(&players, &BalanceOptions::default(), 5).len() <= 1);
+    }
 }
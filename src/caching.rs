@@ -0,0 +1,168 @@
+use crate::auth::{current_player, is_authenticated, AuthJar};
+use crate::{db, AppState};
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use std::sync::Arc;
+
+/// Cache-Control for vendored assets: reuse for a day, then revalidate
+pub const STATIC_CACHE_CONTROL: &str = "public, max-age=86400";
+/// Cache-Control for pages: always revalidate (pages can depend on the login)
+const PAGE_CACHE_CONTROL: &str = "private, no-cache";
+
+/// Weak ETag for a rendered page. Besides the data version it covers what
+/// else pages depend on: the running build, the date, whether the visitor
+/// is logged in and which player (if any) they are.
+pub fn page_etag(
+    version: i64,
+    started_at: DateTime<Utc>,
+    today: NaiveDate,
+    logged_in: bool,
+    player: Option<i32>,
+) -> String {
+    format!(
+        "W/\"{}-{}-{}-{}-{}\"",
+        version,
+        started_at.timestamp(),
+        today.format("%Y%m%d"),
+        u8::from(logged_in),
+        player.unwrap_or_default()
+    )
+}
+
+/// Whether an If-None-Match header matches `etag` (weak comparison)
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_none_match.trim() == "*"
+        || if_none_match
+            .split(',')
+            .any(|tag| opaque(tag) == opaque(etag))
+}
+
+/// Format a timestamp as an HTTP date (e.g. "Sun, 30 Mar 2025 10:00:00 GMT")
+pub fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Whether an If-Modified-Since header is at or after `last_modified`
+/// (unparseable dates never match)
+pub fn not_modified_since(if_modified_since: &str, last_modified: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc2822(if_modified_since.trim())
+        .is_ok_and(|since| last_modified.timestamp() <= since.timestamp())
+}
+
+/// Middleware for page routes: answer GETs with 304 Not Modified when the
+/// client's copy is current, and tag fresh pages with ETag and Last-Modified
+pub async fn conditional_get(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) {
+        return next.run(request).await;
+    }
+    let data = match db::get_data_version(&state.db).await {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::warn!("Skipping page caching: {}", e);
+            return next.run(request).await;
+        }
+    };
+
+    let settings = state.settings.get();
+    let today = settings.today();
+    let etag = page_etag(
+        data.version,
+        state.started_at,
+        today,
+        is_authenticated(&jar, &state),
+        current_player(&jar),
+    );
+    // Pages also change at midnight (group timezone) and on restart
+    let midnight = settings
+        .timezone
+        .from_local_datetime(&today.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .map_or(data.changed_at, |t| t.with_timezone(&Utc));
+    let last_modified = data.changed_at.max(state.started_at).max(midnight);
+
+    let fresh = match request.headers().get(header::IF_NONE_MATCH) {
+        Some(value) => value.to_str().is_ok_and(|v| etag_matches(v, &etag)),
+        None => request
+            .headers()
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| not_modified_since(v, last_modified)),
+    };
+
+    let mut response = if fresh {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        next.run(request).await
+    };
+    if matches!(response.status(), StatusCode::OK | StatusCode::NOT_MODIFIED) {
+        set_validators(response.headers_mut(), &etag, last_modified);
+    }
+    response
+}
+
+fn set_validators(headers: &mut HeaderMap, etag: &str, last_modified: DateTime<Utc>) {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&http_date(last_modified)) {
+        headers.insert(header::LAST_MODIFIED, value);
+    }
+    headers
+        .entry(header::CACHE_CONTROL)
+        .or_insert(HeaderValue::from_static(PAGE_CACHE_CONTROL));
+    headers.insert(header::VARY, HeaderValue::from_static("Cookie"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_etag() {
+        let started = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap();
+        let etag = page_etag(42, started, today, true, None);
+        assert_eq!(etag, "W/\"42-1700000000-20250330-1-0\"");
+        assert_ne!(etag, page_etag(43, started, today, true, None));
+        assert_ne!(etag, page_etag(42, started, today, false, None));
+        assert_ne!(etag, page_etag(42, started, today, true, Some(7)));
+        assert_ne!(
+            etag,
+            page_etag(42, started, today.succ_opt().unwrap(), true, None)
+        );
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = "W/\"42-1-20250330-0\"";
+        assert!(etag_matches(etag, etag));
+        assert!(etag_matches("\"42-1-20250330-0\"", etag));
+        assert!(etag_matches("\"old\", W/\"42-1-20250330-0\"", etag));
+        assert!(etag_matches("*", etag));
+        assert!(!etag_matches("W/\"41-1-20250330-0\"", etag));
+    }
+
+    #[test]
+    fn test_not_modified_since() {
+        let changed = DateTime::from_timestamp(1_743_328_800, 0).unwrap(); // 2025-03-30 10:00 UTC
+        assert_eq!(http_date(changed), "Sun, 30 Mar 2025 10:00:00 GMT");
+        assert!(not_modified_since("Sun, 30 Mar 2025 10:00:00 GMT", changed));
+        assert!(not_modified_since("Sun, 30 Mar 2025 11:00:00 GMT", changed));
+        assert!(!not_modified_since(
+            "Sun, 30 Mar 2025 09:59:59 GMT",
+            changed
+        ));
+        assert!(!not_modified_since("yesterday", changed));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::models::{Match, Player, TeamSplit, WinRecord};
+use crate::{db, AppState};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// How the team generator nominates captains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptainPick {
+    /// Highest Elo on each team
+    Elo,
+    /// Whoever has captained least, then longest ago
+    Rotation,
+}
+
+impl CaptainPick {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "elo" => Some(Self::Elo),
+            "rotation" => Some(Self::Rotation),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Elo => "elo",
+            Self::Rotation => "rotation",
+        }
+    }
+}
+
+/// Nominated captain per team (player IDs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Captains {
+    pub a: i32,
+    pub b: i32,
+}
+
+impl Captains {
+    pub fn contains(&self, player_id: i32) -> bool {
+        self.a == player_id || self.b == player_id
+    }
+}
+
+/// Times captained and the last date, per player ID (non-voided matches only)
+fn captaincies(matches: &[Match]) -> HashMap<i32, (usize, NaiveDate)> {
+    let mut captaincies: HashMap<i32, (usize, NaiveDate)> = HashMap::new();
+    for m in matches.iter().filter(|m| m.voided_at.is_none()) {
+        for id in [m.captain_a, m.captain_b].into_iter().flatten() {
+            let entry = captaincies.entry(id).or_insert((0, m.played_at));
+            entry.0 += 1;
+            entry.1 = entry.1.max(m.played_at);
+        }
+    }
+    captaincies
+}
+
+/// Pick a team's captain. Rotation favours whoever has captained least, then
+/// longest ago (never first), with Elo breaking ties.
+pub fn nominate(team: &[Player], pick: CaptainPick, matches: &[Match]) -> Option<i32> {
+    let by_elo = |a: &&Player, b: &&Player| a.elo.total_cmp(&b.elo);
+    match pick {
+        CaptainPick::Elo => team.iter().max_by(by_elo).map(|p| p.id),
+        CaptainPick::Rotation => {
+            let captaincies = captaincies(matches);
+            team.iter()
+                .min_by(|a, b| {
+                    let record = |p: &Player| captaincies.get(&p.id).copied();
+                    match (record(a), record(b)) {
+                        (None, None) => std::cmp::Ordering::Equal,
+                        (None, Some(_)) => std::cmp::Ordering::Less,
+                        (Some(_), None) => std::cmp::Ordering::Greater,
+                        (Some(a), Some(b)) => a.cmp(&b),
+                    }
+                    .then_with(|| by_elo(b, a))
+                })
+                .map(|p| p.id)
+        }
+    }
+}
+
+/// Nominate both captains for generated teams (None when the setting is off)
+pub async fn nominate_for(state: &AppState, split: &TeamSplit) -> Option<Captains> {
+    let pick = state.settings.get().captain_pick?;
+    let matches = match pick {
+        CaptainPick::Elo => Vec::new(),
+        CaptainPick::Rotation => db::get_all_matches(&state.db).await.unwrap_or_else(|e| {
+            tracing::error!("Failed to load matches for captain rotation: {}", e);
+            Vec::new()
+        }),
+    };
+    Some(Captains {
+        a: nominate(&split.team_a, pick, &matches)?,
+        b: nominate(&split.team_b, pick, &matches)?,
+    })
+}
+
+/// Captains from their records (see [`db::get_captain_records`]), best win
+/// rate first (more matches captained breaks ties)
+pub fn rank_captains(mut records: Vec<WinRecord>) -> Vec<WinRecord> {
+    records.retain(|r| r.played() > 0);
+    records.sort_by(|a, b| {
+        b.win_rate()
+            .total_cmp(&a.win_rate())
+            .then(b.played().cmp(&a.played()))
+            .then(a.player_id.cmp(&b.player_id))
+    });
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn make_player(id: i32, elo: f32) -> Player {
+        Player {
+            id,
+            name: format!("Player {}", id),
+            elo,
+            ..Default::default()
+        }
+    }
+
+    fn make_match(day: u32, captains: (i32, i32), score: (i32, i32)) -> Match {
+        Match {
+            id: day as i32,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+            team_a: vec![captains.0],
+            team_b: vec![captains.1],
+            score_a: score.0,
+            score_b: score.1,
+            captain_a: Some(captains.0),
+            captain_b: Some(captains.1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_captain_pick_round_trip() {
+        for pick in [CaptainPick::Elo, CaptainPick::Rotation] {
+            assert_eq!(CaptainPick::from_str(pick.as_str()), Some(pick));
+        }
+        assert_eq!(CaptainPick::from_str("random"), None);
+    }
+
+    #[test]
+    fn test_nominate_by_elo() {
+        let team = vec![make_player(1, 1200.0), make_player(2, 1350.0)];
+        assert_eq!(nominate(&team, CaptainPick::Elo, &[]), Some(2));
+        assert_eq!(nominate(&[], CaptainPick::Elo, &[]), None);
+    }
+
+    #[test]
+    fn test_nominate_by_rotation() {
+        let team = vec![
+            make_player(1, 1200.0),
+            make_player(2, 1350.0),
+            make_player(3, 1100.0),
+        ];
+        // Nobody has captained: highest Elo
+        assert_eq!(nominate(&team, CaptainPick::Rotation, &[]), Some(2));
+
+        // Never captained beats captained before
+        let matches = vec![make_match(2, (2, 9), (1, 0))];
+        assert_eq!(nominate(&team, CaptainPick::Rotation, &matches), Some(1));
+
+        // Fewest captaincies, then longest ago
+        let matches = vec![
+            make_match(2, (2, 9), (1, 0)),
+            make_match(9, (1, 9), (1, 0)),
+            make_match(16, (3, 2), (1, 0)),
+        ];
+        assert_eq!(nominate(&team, CaptainPick::Rotation, &matches), Some(1));
+
+        // Voided matches don't count
+        let mut voided = make_match(23, (1, 9), (1, 0));
+        voided.voided_at = Some(Utc::now());
+        let matches = vec![make_match(2, (2, 3), (1, 0)), voided];
+        assert_eq!(nominate(&team, CaptainPick::Rotation, &matches), Some(1));
+    }
+
+    #[test]
+    fn test_rank_captains() {
+        let record = |player_id, wins, draws, losses| WinRecord {
+            player_id,
+            wins,
+            draws,
+            losses,
+        };
+        let records = rank_captains(vec![
+            record(3, 0, 1, 0),
+            record(2, 0, 0, 2),
+            record(1, 2, 1, 0),
+            record(4, 0, 0, 0),
+        ]);
+
+        assert_eq!(
+            records.iter().map(|r| r.player_id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!((records[0].win_rate() - 2.0 / 3.0).abs() < 1e-6);
+        // Equal win rates: more matches captained first
+        assert_eq!(records[1].played(), 2);
+    }
+}
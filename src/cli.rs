@@ -0,0 +1,201 @@
+use crate::models::{Match, MatchComment, NewPlayer, Player, Season, SeasonStanding};
+use crate::settings::Settings;
+use crate::{db, demo, replay};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Sunday Football Manager
+#[derive(Parser)]
+#[command(name = "sfm", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run the web server (the default with no command)
+    Serve,
+    /// Apply pending database migrations and exit
+    Migrate,
+    /// Replay every non-voided match and rewrite all ratings, snapshots and
+    /// appearance stats
+    RecalcElo,
+    /// Print players, matches, comments and seasons to stdout
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+    },
+    /// Add players from a CSV file: name, then optional starting Elo and tags per line
+    Import { file: PathBuf },
+    /// Fill an empty database with a demo roster and a season of weekly matches
+    SeedDemo {
+        /// Number of weekly matches, ending last Sunday
+        #[arg(long, default_value_t = 20)]
+        weeks: usize,
+        /// Random seed, for a reproducible season
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+}
+
+/// Everything `sfm export` writes
+#[derive(Serialize)]
+struct Export {
+    exported_at: DateTime<Utc>,
+    players: Vec<Player>,
+    matches: Vec<Match>,
+    comments: Vec<MatchComment>,
+    seasons: Vec<Season>,
+    season_standings: Vec<SeasonStanding>,
+}
+
+/// Run an admin command (everything except `serve`) against a migrated database
+pub async fn run(command: Command, pool: &PgPool) -> Result<(), String> {
+    match command {
+        Command::Serve => unreachable!("serve is handled by main"),
+        Command::Migrate => {
+            println!("Migrations are up to date");
+            Ok(())
+        }
+        Command::RecalcElo => recalc_elo(pool).await,
+        Command::Export { format } => export(pool, format).await,
+        Command::Import { file } => import_players(pool, &file).await,
+        Command::SeedDemo { weeks, seed } => seed_demo(pool, weeks, seed).await,
+    }
+}
+
+async fn recalc_elo(pool: &PgPool) -> Result<(), String> {
+    let settings = Settings::load(pool)
+        .await
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+    let replayed = replay::recalculate_all(pool, &settings.elo)
+        .await
+        .map_err(|e| format!("Failed to recalculate ratings: {}", e))?;
+    println!(
+        "Recalculated {} ratings from {} matches",
+        replayed.ratings.len(),
+        replayed.snapshots.len()
+    );
+    Ok(())
+}
+
+async fn export(pool: &PgPool, format: ExportFormat) -> Result<(), String> {
+    let failed = |e: sqlx::Error| format!("Failed to load data: {}", e);
+    let export = Export {
+        exported_at: Utc::now(),
+        players: db::get_all_players(pool).await.map_err(failed)?,
+        matches: db::get_all_matches(pool).await.map_err(failed)?,
+        comments: db::get_all_comments(pool).await.map_err(failed)?,
+        seasons: db::get_seasons(pool).await.map_err(failed)?,
+        season_standings: db::get_season_standings(pool).await.map_err(failed)?,
+    };
+    let output = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&export),
+    }
+    .map_err(|e| format!("Failed to serialize export: {}", e))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Add players line by line like the roster's bulk paste; a leading "name,..." header is skipped
+async fn import_players(pool: &PgPool, file: &PathBuf) -> Result<(), String> {
+    let contents = std::fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let mut seen: HashSet<String> = db::get_all_players(pool)
+        .await
+        .map_err(|e| format!("Failed to load players: {}", e))?
+        .into_iter()
+        .map(|p| p.name.to_lowercase())
+        .collect();
+
+    let mut added = 0;
+    let mut failed = 0;
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (i == 0 && line.to_lowercase().starts_with("name")) {
+            continue;
+        }
+        let result = match NewPlayer::parse_line(line) {
+            Ok(player) if seen.contains(&player.name.to_lowercase()) => {
+                Err(format!("{} already exists", player.name))
+            }
+            Ok(player) => db::create_player(pool, &player)
+                .await
+                .map_err(|e| format!("could not add {}: {}", player.name, e)),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(player) => {
+                seen.insert(player.name.to_lowercase());
+                added += 1;
+            }
+            Err(e) => {
+                eprintln!("Line {}: {}", i + 1, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!("Added {} players", added);
+    if failed > 0 {
+        return Err(format!("{} lines could not be imported", failed));
+    }
+    Ok(())
+}
+
+/// Create the demo roster and season, then replay it to fill in ratings and snapshots
+async fn seed_demo(pool: &PgPool, weeks: usize, seed: Option<u64>) -> Result<(), String> {
+    let failed = |e: sqlx::Error| format!("Failed to seed demo data: {}", e);
+    let existing = db::get_all_players(pool).await.map_err(failed)?;
+    let matches = db::get_all_matches(pool).await.map_err(failed)?;
+    if !existing.is_empty() || !matches.is_empty() {
+        return Err(
+            "The database already has players or matches; seed-demo only fills an empty one"
+                .to_string(),
+        );
+    }
+
+    let settings = Settings::load(pool)
+        .await
+        .map_err(|e| format!("Failed to load settings: {}", e))?;
+    let mut players = Vec::new();
+    for player in demo::demo_roster() {
+        players.push(db::create_player(pool, &player).await.map_err(failed)?);
+    }
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let last_match = demo::last_sunday(settings.today());
+    let season = demo::demo_matches(&players, last_match, weeks, &mut rng);
+    let mut tx = pool.begin().await.map_err(failed)?;
+    for new_match in &season {
+        db::create_match(&mut *tx, new_match)
+            .await
+            .map_err(failed)?;
+    }
+    tx.commit().await.map_err(failed)?;
+
+    replay::recalculate_all(pool, &settings.elo)
+        .await
+        .map_err(|e| format!("Failed to recalculate ratings: {}", e))?;
+    println!(
+        "Added {} players and {} matches",
+        players.len(),
+        season.len()
+    );
+    Ok(())
+}
@@ -1,72 +1,525 @@
-use crate::models::{Match, NewPlayer, Player, UpdatePlayer, ELO_DEFAULT};
-use sqlx::{Executor, PgPool, Postgres};
+use crate::models::{
+    AbsenceReason, AutoTeams, CoinToss, DataVersion, Injury, Invite, Match, MatchComment,
+    MatchFlag, NewMatch, NewPlayer, OnboardingAnswers, PeerRating, PendingResult, Player,
+    PlayerAccount, PlayerFilter, PlayerSort, PlayerStatus, QueuedJob, RatingBaseline, Registration,
+    RosterChange, Rsvp, RsvpStatus, Season, SeasonStanding, Suspension, TableStats, UpdatePlayer,
+    Webhook, WebhookDelivery, WinRecord, ELO_DEFAULT,
+};
+use crate::onboarding::{Answers, Experience, OnboardingFormula};
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{Executor, PgConnection, PgPool, Postgres};
+use std::collections::HashMap;
+
+/// Columns selected for `Player` rows. `matches_played` and `days_away` come
+/// from `player_stats` (see [`refresh_player_stats`]) rather than a scan of
+/// every match. `days_away` counts to today; saving a result recounts it to
+/// the match date.
+const PLAYER_COLUMNS: &str =
+    "id, name, elo, tags, created_at, jersey_number, stamina, pace, age_band, position, hide_name,
+    COALESCE((SELECT s.matches_played FROM player_stats s WHERE s.player_id = players.id), 0)
+        AS matches_played,
+    (SELECT CURRENT_DATE - s.last_played FROM player_stats s WHERE s.player_id = players.id)
+        AS days_away";
+
+/// Columns selected for `Match` rows
+const MATCH_COLUMNS: &str =
+    "id, played_at, team_a, team_b, score_a, score_b, elo_snapshot, created_at, voided_at, notes, k_multiplier, \
+     captain_a, captain_b, mvp, team_name_a, team_name_b, goals";
+
+/// Columns selected for `Webhook` rows
+const WEBHOOK_COLUMNS: &str = "id, url, secret, events, last_status, last_attempt_at, created_at";
+
+const PENDING_RESULT_COLUMNS: &str = "id, played_at, team_a, team_b, score_a, score_b, \
+     participation, k_multiplier, notes, submitted_by, created_at, captain_a, captain_b, goals, \
+     amends";
+
+const JOB_COLUMNS: &str =
+    "id, kind, payload, attempts, max_attempts, last_error, failed_at, created_at";
 
 /// Get all players ordered by Elo (descending)
-pub async fn get_all_players(pool: &PgPool) -> Result<Vec<Player>, sqlx::Error> {
-    sqlx::query_as::<_, Player>(
-        "SELECT id, name, elo, tags, matches_played, created_at FROM players ORDER BY elo DESC",
-    )
+#[tracing::instrument(skip_all)]
+pub async fn get_all_players<'e, E>(executor: E) -> Result<Vec<Player>, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_as::<_, Player>(&format!(
+        "SELECT {PLAYER_COLUMNS} FROM players ORDER BY elo DESC"
+    ))
+    .fetch_all(executor)
+    .await
+}
+
+/// Escape LIKE wildcards so a search term matches literally
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// Search, filter and sort players for the roster table
+#[tracing::instrument(skip_all)]
+pub async fn search_players(
+    pool: &PgPool,
+    filter: &PlayerFilter,
+    today: NaiveDate,
+) -> Result<Vec<Player>, sqlx::Error> {
+    let column = match filter.sort {
+        PlayerSort::Name => "LOWER(name)",
+        PlayerSort::Number => "jersey_number",
+        PlayerSort::Elo => "elo",
+        PlayerSort::Matches => "matches_played",
+    };
+    let direction = if filter.descending {
+        "DESC NULLS LAST"
+    } else {
+        "ASC NULLS LAST"
+    };
+    let status = match filter.status {
+        PlayerStatus::All => None,
+        status => Some(status.as_str()),
+    };
+
+    sqlx::query_as::<_, Player>(&format!(
+        "SELECT {PLAYER_COLUMNS} FROM (
+             SELECT players.*,
+                    EXISTS (
+                        SELECT 1 FROM suspensions s
+                        WHERE s.player_id = players.id
+                          AND s.lifted_at IS NULL
+                          AND (s.returns_on IS NULL OR s.returns_on > $4)) AS suspended,
+                    EXISTS (
+                        SELECT 1 FROM injuries i
+                        WHERE i.player_id = players.id AND i.cleared_at IS NULL) AS injured
+             FROM players
+         ) players
+         WHERE name ILIKE $1
+           AND ($2::TEXT IS NULL OR ',' || tags || ',' LIKE '%,' || $2 || ',%')
+           AND ($3::TEXT IS NULL OR CASE $3
+                WHEN 'available' THEN NOT (suspended OR injured)
+                WHEN 'suspended' THEN suspended
+                ELSE injured
+                END)
+         ORDER BY {column} {direction}, LOWER(name) ASC"
+    ))
+    .bind(like_pattern(filter.search.trim()))
+    .bind(filter.tag.map(|t| t.to_string()))
+    .bind(status)
+    .bind(today)
     .fetch_all(pool)
     .await
 }
 
 /// Get players by IDs
+#[tracing::instrument(skip_all)]
 pub async fn get_players_by_ids(pool: &PgPool, ids: &[i32]) -> Result<Vec<Player>, sqlx::Error> {
-    sqlx::query_as::<_, Player>(
-        "SELECT id, name, elo, tags, matches_played, created_at FROM players WHERE id = ANY($1)",
-    )
+    sqlx::query_as::<_, Player>(&format!(
+        "SELECT {PLAYER_COLUMNS} FROM players WHERE id = ANY($1)"
+    ))
     .bind(ids)
     .fetch_all(pool)
     .await
 }
 
 /// Create a new player
-pub async fn create_player(pool: &PgPool, player: &NewPlayer) -> Result<Player, sqlx::Error> {
+#[tracing::instrument(skip_all)]
+pub async fn create_player<'e, E>(executor: E, player: &NewPlayer) -> Result<Player, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
     let elo = player.elo.unwrap_or(ELO_DEFAULT);
     let tags = player.tags.as_deref().unwrap_or("");
 
-    sqlx::query_as::<_, Player>(
-        "INSERT INTO players (name, elo, tags) VALUES ($1, $2, $3)
-         RETURNING id, name, elo, tags, matches_played, created_at",
-    )
+    sqlx::query_as::<_, Player>(&format!(
+        "INSERT INTO players
+             (name, elo, initial_elo, tags, jersey_number, stamina, pace, age_band, position)
+         VALUES ($1, $2, $2, $3, $4, $5, $6, $7, $8)
+         RETURNING {PLAYER_COLUMNS}"
+    ))
     .bind(&player.name)
     .bind(elo)
     .bind(tags)
-    .fetch_one(pool)
+    .bind(player.jersey_number)
+    .bind(player.stamina)
+    .bind(player.pace)
+    .bind(&player.age_band)
+    .bind(&player.position)
+    .fetch_one(executor)
     .await
 }
 
-/// Update a player
+/// Update a player (a manual Elo change also shifts their starting Elo, so replays keep it)
+#[tracing::instrument(skip_all)]
 pub async fn update_player(
     pool: &PgPool,
     id: i32,
     update: &UpdatePlayer,
 ) -> Result<Option<Player>, sqlx::Error> {
-    sqlx::query_as::<_, Player>(
-        "UPDATE players SET elo = $1, tags = $2 WHERE id = $3
-         RETURNING id, name, elo, tags, matches_played, created_at",
-    )
+    sqlx::query_as::<_, Player>(&format!(
+        "UPDATE players SET initial_elo = initial_elo + ($1 - elo), elo = $1, tags = $2,
+             jersey_number = $3, stamina = $4, pace = $5, age_band = $6, position = $7,
+             hide_name = $8
+         WHERE id = $9
+         RETURNING {PLAYER_COLUMNS}"
+    ))
     .bind(update.elo)
     .bind(&update.tags)
+    .bind(update.jersey_number)
+    .bind(update.stamina)
+    .bind(update.pace)
+    .bind(&update.age_band)
+    .bind(&update.position)
+    .bind(update.hide_name)
     .bind(id)
     .fetch_optional(pool)
     .await
 }
 
-/// Update player Elo and match count after a match
+/// Update player Elo after a match
+#[tracing::instrument(skip_all)]
 pub async fn update_player_elo<'e, E>(executor: E, id: i32, new_elo: f32) -> Result<(), sqlx::Error>
 where
     E: Executor<'e, Database = Postgres>,
 {
-    sqlx::query("UPDATE players SET elo = $1, matches_played = matches_played + 1 WHERE id = $2")
+    sqlx::query("UPDATE players SET elo = $1 WHERE id = $2")
+        .bind(new_elo)
+        .bind(id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Private admin notes, keyed by player ID (players without notes are absent)
+#[tracing::instrument(skip_all)]
+pub async fn get_player_notes(pool: &PgPool) -> Result<HashMap<i32, String>, sqlx::Error> {
+    let rows: Vec<(i32, String)> = sqlx::query_as("SELECT player_id, notes FROM player_notes")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Replace a player's private admin notes (empty notes delete them)
+#[tracing::instrument(skip_all)]
+pub async fn set_player_notes(
+    pool: &PgPool,
+    player_id: i32,
+    notes: &str,
+) -> Result<(), sqlx::Error> {
+    if notes.is_empty() {
+        sqlx::query("DELETE FROM player_notes WHERE player_id = $1")
+            .bind(player_id)
+            .execute(pool)
+            .await?;
+    } else {
+        sqlx::query(
+            "INSERT INTO player_notes (player_id, notes) VALUES ($1, $2)
+             ON CONFLICT (player_id) DO UPDATE SET notes = EXCLUDED.notes, updated_at = NOW()",
+        )
+        .bind(player_id)
+        .bind(notes)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Get every imported rating baseline
+#[tracing::instrument(skip_all)]
+pub async fn get_rating_baselines(pool: &PgPool) -> Result<Vec<RatingBaseline>, sqlx::Error> {
+    sqlx::query_as::<_, RatingBaseline>(
+        "SELECT player_id, elo, as_of, source, created_at FROM rating_baselines",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record imported baselines (replacing earlier ones) and make each the
+/// player's starting Elo. Ratings need a replay afterwards.
+#[tracing::instrument(skip_all)]
+pub async fn set_rating_baselines(
+    pool: &PgPool,
+    baselines: &[(i32, f32, NaiveDate)],
+    source: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for (player_id, elo, as_of) in baselines {
+        sqlx::query(
+            "INSERT INTO rating_baselines (player_id, elo, as_of, source) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (player_id) DO UPDATE
+             SET elo = EXCLUDED.elo, as_of = EXCLUDED.as_of, source = EXCLUDED.source,
+                 created_at = NOW()",
+        )
+        .bind(player_id)
+        .bind(elo)
+        .bind(as_of)
+        .bind(source)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE players SET initial_elo = $1 WHERE id = $2")
+            .bind(elo)
+            .bind(player_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+/// `pg_advisory_xact_lock` key for rating: saving a result and replaying
+/// history take it, so neither works from Elo the other is about to replace
+const RATINGS_LOCK: i64 = 0x0053_464d_5f45_4c4f; // "SFM_ELO"
+
+/// Hold the ratings lock until the transaction ends. Take it before reading
+/// the Elo a write depends on.
+#[tracing::instrument(skip_all)]
+pub async fn lock_ratings(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(RATINGS_LOCK)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Current Elo of the given players, keyed by player ID
+#[tracing::instrument(skip_all)]
+pub async fn get_elos<'e, E>(executor: E, ids: &[i32]) -> Result<HashMap<i32, f32>, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let rows: Vec<(i32, f32)> = sqlx::query_as("SELECT id, elo FROM players WHERE id = ANY($1)")
+        .bind(ids)
+        .fetch_all(executor)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Get every player's starting Elo (before any matches), keyed by player ID
+#[tracing::instrument(skip_all)]
+pub async fn get_initial_elos<'e, E>(executor: E) -> Result<HashMap<i32, f32>, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let rows: Vec<(i32, f32)> = sqlx::query_as("SELECT id, initial_elo FROM players")
+        .fetch_all(executor)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// IDs of players whose starting Elo has already been recalibrated
+#[tracing::instrument(skip_all)]
+pub async fn get_recalibrated(pool: &PgPool) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar("SELECT player_id FROM recalibrations")
+        .fetch_all(pool)
+        .await
+}
+
+/// Replace a player's starting Elo with a recalibrated one and record the
+/// change. Ratings need a replay afterwards.
+#[tracing::instrument(skip_all)]
+pub async fn recalibrate_player(
+    pool: &PgPool,
+    player_id: i32,
+    old_elo: f32,
+    new_elo: f32,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO recalibrations (player_id, old_elo, new_elo) VALUES ($1, $2, $3)
+         ON CONFLICT (player_id) DO UPDATE
+         SET old_elo = EXCLUDED.old_elo, new_elo = EXCLUDED.new_elo, recalibrated_at = NOW()",
+    )
+    .bind(player_id)
+    .bind(old_elo)
+    .bind(new_elo)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE players SET initial_elo = $1 WHERE id = $2")
         .bind(new_elo)
+        .bind(player_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+/// Each player's Elo after each of their last `limit` non-voided matches,
+/// oldest first, read from the match snapshots. Keyed by player ID.
+#[tracing::instrument(skip_all)]
+pub async fn get_recent_elo_history(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<HashMap<i32, Vec<f32>>, sqlx::Error> {
+    let rows: Vec<(i32, f32)> = sqlx::query_as(
+        "SELECT player_id, elo FROM (
+             SELECT s.key::int AS player_id,
+                    ((s.value->>'before')::real
+                        + (s.value->>'delta')::real
+                        * COALESCE((s.value->>'participation')::real, 1)) AS elo,
+                    m.played_at, m.id,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY s.key ORDER BY m.played_at DESC, m.id DESC
+                    ) AS recent
+             FROM matches m, jsonb_each(m.elo_snapshot) s
+             WHERE m.voided_at IS NULL
+         ) history
+         WHERE recent <= $1
+         ORDER BY player_id, played_at, id",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let mut history: HashMap<i32, Vec<f32>> = HashMap::new();
+    for (player_id, elo) in rows {
+        history.entry(player_id).or_default().push(elo);
+    }
+    Ok(history)
+}
+
+/// Overwrite a player's Elo (used when replaying history)
+#[tracing::instrument(skip_all)]
+pub async fn set_player_rating<'e, E>(executor: E, id: i32, elo: f32) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query("UPDATE players SET elo = $1 WHERE id = $2")
+        .bind(elo)
         .bind(id)
         .execute(executor)
         .await?;
     Ok(())
 }
 
+/// Replace a player's tags. Returns false if the player doesn't exist.
+#[tracing::instrument(skip_all)]
+pub async fn set_player_tags(pool: &PgPool, id: i32, tags: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE players SET tags = $2 WHERE id = $1")
+        .bind(id)
+        .bind(tags)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Dismissed tag suggestions as (player ID, tag, change)
+#[tracing::instrument(skip_all)]
+pub async fn get_tag_dismissals(pool: &PgPool) -> Result<Vec<(i32, String, String)>, sqlx::Error> {
+    sqlx::query_as("SELECT player_id, tag, change FROM tag_suggestion_dismissals")
+        .fetch_all(pool)
+        .await
+}
+
+/// Hide a tag suggestion for good (dismissing it twice is fine)
+#[tracing::instrument(skip_all)]
+pub async fn dismiss_tag_suggestion(
+    pool: &PgPool,
+    player_id: i32,
+    tag: &str,
+    change: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tag_suggestion_dismissals (player_id, tag, change) VALUES ($1, $2, $3)
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(player_id)
+    .bind(tag)
+    .bind(change)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Recount appearances, the last match played and the per-day results in
+/// `player_match_days` over non-voided matches, for the given players or
+/// (with `None`) everyone
+#[tracing::instrument(skip_all)]
+pub async fn refresh_player_stats(
+    conn: &mut PgConnection,
+    player_ids: Option<&[i32]>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO player_stats (player_id, matches_played, last_played)
+         SELECT p.id, COUNT(m.id)::INTEGER, MAX(m.played_at)
+         FROM players p
+         LEFT JOIN matches m ON m.voided_at IS NULL AND p.id = ANY(m.team_a || m.team_b)
+         WHERE $1::INTEGER[] IS NULL OR p.id = ANY($1)
+         GROUP BY p.id
+         ON CONFLICT (player_id) DO UPDATE
+         SET matches_played = EXCLUDED.matches_played, last_played = EXCLUDED.last_played",
+    )
+    .bind(player_ids)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("DELETE FROM player_match_days WHERE $1::INTEGER[] IS NULL OR player_id = ANY($1)")
+        .bind(player_ids)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "INSERT INTO player_match_days
+             (player_id, played_on, wins, draws, losses, captain_wins, captain_draws, captain_losses)
+         SELECT t.player_id, m.played_at,
+             COUNT(*) FILTER (WHERE t.result > 0),
+             COUNT(*) FILTER (WHERE t.result = 0),
+             COUNT(*) FILTER (WHERE t.result < 0),
+             COUNT(*) FILTER (WHERE t.captain AND t.result > 0),
+             COUNT(*) FILTER (WHERE t.captain AND t.result = 0),
+             COUNT(*) FILTER (WHERE t.captain AND t.result < 0)
+         FROM matches m
+         CROSS JOIN LATERAL (
+             SELECT a.id AS player_id, SIGN(m.score_a - m.score_b) AS result, a.id = m.captain_a AS captain
+             FROM unnest(m.team_a) AS a(id)
+             UNION ALL
+             SELECT b.id, SIGN(m.score_b - m.score_a), b.id = m.captain_b
+             FROM unnest(m.team_b) AS b(id)
+         ) t
+         WHERE m.voided_at IS NULL AND ($1::INTEGER[] IS NULL OR t.player_id = ANY($1))
+         GROUP BY t.player_id, m.played_at",
+    )
+    .bind(player_ids)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Every (player ID, match day) with a non-voided match they played
+#[tracing::instrument(skip_all)]
+pub async fn get_match_days_played(pool: &PgPool) -> Result<Vec<(i32, NaiveDate)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT player_id, played_on FROM player_match_days ORDER BY played_on, player_id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Wins, draws and losses per player over non-voided matches (everyone who
+/// has played, former players included)
+#[tracing::instrument(skip_all)]
+pub async fn get_win_records(pool: &PgPool) -> Result<Vec<WinRecord>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT player_id, SUM(wins)::INTEGER AS wins, SUM(draws)::INTEGER AS draws,
+             SUM(losses)::INTEGER AS losses
+         FROM player_match_days GROUP BY player_id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Results as captain per player, for everyone who has captained a
+/// non-voided match
+#[tracing::instrument(skip_all)]
+pub async fn get_captain_records(pool: &PgPool) -> Result<Vec<WinRecord>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT player_id, SUM(captain_wins)::INTEGER AS wins,
+             SUM(captain_draws)::INTEGER AS draws, SUM(captain_losses)::INTEGER AS losses
+         FROM player_match_days GROUP BY player_id
+         HAVING SUM(captain_wins + captain_draws + captain_losses) > 0",
+    )
+    .fetch_all(pool)
+    .await
+}
+
 /// Delete a player
+#[tracing::instrument(skip_all)]
 pub async fn delete_player(pool: &PgPool, id: i32) -> Result<bool, sqlx::Error> {
     let result = sqlx::query("DELETE FROM players WHERE id = $1")
         .bind(id)
@@ -76,37 +529,1373 @@ pub async fn delete_player(pool: &PgPool, id: i32) -> Result<bool, sqlx::Error>
 }
 
 /// Get all matches ordered by date (most recent first)
-pub async fn get_all_matches(pool: &PgPool) -> Result<Vec<Match>, sqlx::Error> {
-    sqlx::query_as::<_, Match>(
-        "SELECT id, played_at, team_a, team_b, score_a, score_b, elo_snapshot, created_at
-         FROM matches ORDER BY played_at DESC, created_at DESC",
-    )
+#[tracing::instrument(skip_all)]
+pub async fn get_all_matches<'e, E>(executor: E) -> Result<Vec<Match>, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_as::<_, Match>(&format!(
+        "SELECT {MATCH_COLUMNS} FROM matches ORDER BY played_at DESC, created_at DESC"
+    ))
+    .fetch_all(executor)
+    .await
+}
+
+/// Non-voided matches played after `since`, or the latest `at_least` if
+/// fewer were (most recent first)
+#[tracing::instrument(skip_all)]
+pub async fn get_matches_since(
+    pool: &PgPool,
+    since: NaiveDate,
+    at_least: i64,
+) -> Result<Vec<Match>, sqlx::Error> {
+    sqlx::query_as::<_, Match>(&format!(
+        "SELECT {MATCH_COLUMNS} FROM matches WHERE voided_at IS NULL AND (played_at > $1 OR id IN (
+             SELECT id FROM matches WHERE voided_at IS NULL
+             ORDER BY played_at DESC, created_at DESC LIMIT $2
+         ))
+         ORDER BY played_at DESC, created_at DESC"
+    ))
+    .bind(since)
+    .bind(at_least)
+    .fetch_all(pool)
+    .await
+}
+
+/// The most recently recorded matches (by when they were entered, not played)
+#[tracing::instrument(skip_all)]
+pub async fn get_recently_recorded_matches(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<Match>, sqlx::Error> {
+    sqlx::query_as::<_, Match>(&format!(
+        "SELECT {MATCH_COLUMNS} FROM matches ORDER BY created_at DESC LIMIT $1"
+    ))
+    .bind(limit)
     .fetch_all(pool)
     .await
 }
 
+/// Get one match by ID
+#[tracing::instrument(skip_all)]
+pub async fn get_match(pool: &PgPool, id: i32) -> Result<Option<Match>, sqlx::Error> {
+    sqlx::query_as::<_, Match>(&format!(
+        "SELECT {MATCH_COLUMNS} FROM matches WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
 /// Create a new match record
-pub async fn create_match<'e, E>(
+#[tracing::instrument(skip_all)]
+pub async fn create_match<'e, E>(executor: E, new_match: &NewMatch) -> Result<Match, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_as::<_, Match>(&format!(
+        "INSERT INTO matches
+             (played_at, team_a, team_b, score_a, score_b, elo_snapshot, notes, k_multiplier, captain_a, captain_b,
+              team_name_a, team_name_b, goals)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+         RETURNING {MATCH_COLUMNS}"
+    ))
+    .bind(new_match.played_at)
+    .bind(&new_match.team_a)
+    .bind(&new_match.team_b)
+    .bind(new_match.score_a)
+    .bind(new_match.score_b)
+    .bind(&new_match.elo_snapshot)
+    .bind(&new_match.notes)
+    .bind(new_match.k_multiplier)
+    .bind(new_match.captain_a)
+    .bind(new_match.captain_b)
+    .bind(&new_match.team_name_a)
+    .bind(&new_match.team_name_b)
+    .bind(serde_json::to_value(&new_match.goals).unwrap_or_default())
+    .fetch_one(executor)
+    .await
+}
+
+/// Void or restore a match. Returns false if the match doesn't exist.
+#[tracing::instrument(skip_all)]
+pub async fn set_match_voided(pool: &PgPool, id: i32, voided: bool) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE matches SET voided_at = CASE WHEN $1 THEN NOW() ELSE NULL END WHERE id = $2",
+    )
+    .bind(voided)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Award a match's MVP. Returns false if the match doesn't exist or is voided.
+#[tracing::instrument(skip_all)]
+pub async fn set_match_mvp(pool: &PgPool, id: i32, player_id: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE matches SET mvp = $2 WHERE id = $1 AND voided_at IS NULL")
+        .bind(id)
+        .bind(player_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Hold a submitted result, or a fix to a recorded match (`amends`), until
+/// someone else confirms it
+#[tracing::instrument(skip_all)]
+pub async fn create_pending_result(
+    pool: &PgPool,
+    new_match: &NewMatch,
+    participation: &serde_json::Value,
+    submitted_by: &str,
+    amends: Option<i32>,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO pending_results
+             (played_at, team_a, team_b, score_a, score_b, participation, k_multiplier, notes,
+              submitted_by, captain_a, captain_b, goals, amends)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+         RETURNING id",
+    )
+    .bind(new_match.played_at)
+    .bind(&new_match.team_a)
+    .bind(&new_match.team_b)
+    .bind(new_match.score_a)
+    .bind(new_match.score_b)
+    .bind(participation)
+    .bind(new_match.k_multiplier)
+    .bind(&new_match.notes)
+    .bind(submitted_by)
+    .bind(new_match.captain_a)
+    .bind(new_match.captain_b)
+    .bind(serde_json::to_value(&new_match.goals).unwrap_or_default())
+    .bind(amends)
+    .fetch_one(pool)
+    .await
+}
+
+/// Results waiting for confirmation, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_pending_results(pool: &PgPool) -> Result<Vec<PendingResult>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {PENDING_RESULT_COLUMNS} FROM pending_results ORDER BY id"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// One pending result
+#[tracing::instrument(skip_all)]
+pub async fn get_pending_result(
+    pool: &PgPool,
+    id: i32,
+) -> Result<Option<PendingResult>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {PENDING_RESULT_COLUMNS} FROM pending_results WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Remove a pending result (confirmed or discarded). Returns false if it was
+/// already gone, so two people confirming at once can't record it twice.
+#[tracing::instrument(skip_all)]
+pub async fn delete_pending_result<'e, E>(executor: E, id: i32) -> Result<bool, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let result = sqlx::query("DELETE FROM pending_results WHERE id = $1")
+        .bind(id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Fix a match's score and notes (not voided ones). A new score drops the
+/// goal timeline, which no longer adds up. Replay afterwards so ratings and
+/// snapshots follow.
+#[tracing::instrument(skip_all)]
+pub async fn amend_match<'e, E>(
     executor: E,
-    team_a_ids: &[i32],
-    team_b_ids: &[i32],
+    id: i32,
     score_a: i32,
     score_b: i32,
-    elo_snapshot: serde_json::Value,
-) -> Result<Match, sqlx::Error>
+    notes: &str,
+) -> Result<bool, sqlx::Error>
 where
     E: Executor<'e, Database = Postgres>,
 {
-    sqlx::query_as::<_, Match>(
-        "INSERT INTO matches (team_a, team_b, score_a, score_b, elo_snapshot)
-         VALUES ($1, $2, $3, $4, $5)
-         RETURNING id, played_at, team_a, team_b, score_a, score_b, elo_snapshot, created_at",
+    let result = sqlx::query(
+        "UPDATE matches SET score_a = $2, score_b = $3, notes = $4,
+             goals = CASE WHEN score_a = $2 AND score_b = $3 THEN goals ELSE '[]' END
+         WHERE id = $1 AND voided_at IS NULL",
     )
-    .bind(team_a_ids)
-    .bind(team_b_ids)
+    .bind(id)
     .bind(score_a)
     .bind(score_b)
-    .bind(elo_snapshot)
-    .fetch_one(executor)
+    .bind(notes)
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace a match's Elo snapshot (used when replaying history)
+#[tracing::instrument(skip_all)]
+pub async fn update_match_snapshot<'e, E>(
+    executor: E,
+    id: i32,
+    elo_snapshot: serde_json::Value,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query("UPDATE matches SET elo_snapshot = $1 WHERE id = $2")
+        .bind(elo_snapshot)
+        .bind(id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Get all match comments, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_all_comments(pool: &PgPool) -> Result<Vec<MatchComment>, sqlx::Error> {
+    sqlx::query_as::<_, MatchComment>(
+        "SELECT id, match_id, author, body, created_at FROM match_comments ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Get comments for one match, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_comments_for_match(
+    pool: &PgPool,
+    match_id: i32,
+) -> Result<Vec<MatchComment>, sqlx::Error> {
+    sqlx::query_as::<_, MatchComment>(
+        "SELECT id, match_id, author, body, created_at FROM match_comments
+         WHERE match_id = $1 ORDER BY created_at",
+    )
+    .bind(match_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Add a comment to a match
+#[tracing::instrument(skip_all)]
+pub async fn create_comment(
+    pool: &PgPool,
+    match_id: i32,
+    author: &str,
+    body: &str,
+) -> Result<MatchComment, sqlx::Error> {
+    sqlx::query_as::<_, MatchComment>(
+        "INSERT INTO match_comments (match_id, author, body) VALUES ($1, $2, $3)
+         RETURNING id, match_id, author, body, created_at",
+    )
+    .bind(match_id)
+    .bind(author)
+    .bind(body)
+    .fetch_one(pool)
+    .await
+}
+
+/// Flags not yet resolved, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_open_flags(pool: &PgPool) -> Result<Vec<MatchFlag>, sqlx::Error> {
+    sqlx::query_as::<_, MatchFlag>(
+        "SELECT id, match_id, flagged_by, reason, created_at FROM match_flags
+         WHERE resolved_at IS NULL ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Open flags on one match, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_open_flags_for_match(
+    pool: &PgPool,
+    match_id: i32,
+) -> Result<Vec<MatchFlag>, sqlx::Error> {
+    sqlx::query_as::<_, MatchFlag>(
+        "SELECT id, match_id, flagged_by, reason, created_at FROM match_flags
+         WHERE match_id = $1 AND resolved_at IS NULL ORDER BY created_at",
+    )
+    .bind(match_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Flag a match. Returns false if there is no such match.
+#[tracing::instrument(skip_all)]
+pub async fn create_flag(
+    pool: &PgPool,
+    match_id: i32,
+    flagged_by: &str,
+    reason: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO match_flags (match_id, flagged_by, reason)
+         SELECT id, $2, $3 FROM matches WHERE id = $1",
+    )
+    .bind(match_id)
+    .bind(flagged_by)
+    .bind(reason)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Mark a flag as dealt with. Returns false if it was already resolved.
+#[tracing::instrument(skip_all)]
+pub async fn resolve_flag(pool: &PgPool, id: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE match_flags SET resolved_at = NOW() WHERE id = $1 AND resolved_at IS NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get suspensions that haven't been lifted (may include expired ones)
+#[tracing::instrument(skip_all)]
+pub async fn get_unlifted_suspensions(pool: &PgPool) -> Result<Vec<Suspension>, sqlx::Error> {
+    sqlx::query_as::<_, Suspension>(
+        "SELECT id, player_id, reason, returns_on, lifted_at, created_at FROM suspensions
+         WHERE lifted_at IS NULL ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Suspend a player until `returns_on` (None = indefinite ban)
+#[tracing::instrument(skip_all)]
+pub async fn create_suspension(
+    pool: &PgPool,
+    player_id: i32,
+    reason: &str,
+    returns_on: Option<NaiveDate>,
+) -> Result<Suspension, sqlx::Error> {
+    sqlx::query_as::<_, Suspension>(
+        "INSERT INTO suspensions (player_id, reason, returns_on) VALUES ($1, $2, $3)
+         RETURNING id, player_id, reason, returns_on, lifted_at, created_at",
+    )
+    .bind(player_id)
+    .bind(reason)
+    .bind(returns_on)
+    .fetch_one(pool)
+    .await
+}
+
+/// Get injuries that haven't been cleared
+#[tracing::instrument(skip_all)]
+pub async fn get_uncleared_injuries(pool: &PgPool) -> Result<Vec<Injury>, sqlx::Error> {
+    sqlx::query_as::<_, Injury>(
+        "SELECT id, player_id, note, started_on, expected_return, cleared_at, created_at
+         FROM injuries WHERE cleared_at IS NULL ORDER BY started_on, id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record an injury from `started_on`, expected back on `expected_return` (None = unknown)
+#[tracing::instrument(skip_all)]
+pub async fn create_injury(
+    pool: &PgPool,
+    player_id: i32,
+    note: &str,
+    started_on: NaiveDate,
+    expected_return: Option<NaiveDate>,
+) -> Result<Injury, sqlx::Error> {
+    sqlx::query_as::<_, Injury>(
+        "INSERT INTO injuries (player_id, note, started_on, expected_return) VALUES ($1, $2, $3, $4)
+         RETURNING id, player_id, note, started_on, expected_return, cleared_at, created_at",
+    )
+    .bind(player_id)
+    .bind(note)
+    .bind(started_on)
+    .bind(expected_return)
+    .fetch_one(pool)
     .await
 }
+
+/// Pending self-registrations, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_registrations(pool: &PgPool) -> Result<Vec<Registration>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, name, position, note, created_at, experience, fitness FROM registrations
+         ORDER BY created_at, id",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Get one pending registration by ID
+#[tracing::instrument(skip_all)]
+pub async fn get_registration(pool: &PgPool, id: i32) -> Result<Option<Registration>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT id, name, position, note, created_at, experience, fitness FROM registrations
+         WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Queue a self-registration, unless `max_pending` are already waiting.
+/// Returns false if the queue is full.
+#[tracing::instrument(skip_all)]
+pub async fn create_registration(
+    pool: &PgPool,
+    name: &str,
+    position: Option<&str>,
+    note: &str,
+    experience: Experience,
+    fitness: Option<i32>,
+    max_pending: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO registrations (name, position, note, experience, fitness)
+         SELECT $1, $2, $3, $4, $5 WHERE (SELECT COUNT(*) FROM registrations) < $6",
+    )
+    .bind(name)
+    .bind(position)
+    .bind(note)
+    .bind(experience.as_str())
+    .bind(fitness)
+    .bind(max_pending)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove a registration (approved or rejected). Returns false if it was already gone.
+#[tracing::instrument(skip_all)]
+pub async fn delete_registration<'e, E>(executor: E, id: i32) -> Result<bool, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let result = sqlx::query("DELETE FROM registrations WHERE id = $1")
+        .bind(id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Keep the questionnaire answers and formula a new player's starting Elo came from
+#[tracing::instrument(skip_all)]
+pub async fn save_onboarding<'e, E>(
+    executor: E,
+    player_id: i32,
+    answers: &Answers,
+    formula: &OnboardingFormula,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query(
+        "INSERT INTO onboarding_answers
+             (player_id, experience, position, fitness, starting_elo, formula)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(player_id)
+    .bind(answers.experience.as_str())
+    .bind(answers.position.map(|p| p.to_string()))
+    .bind(answers.fitness)
+    .bind(answers.starting_elo(formula))
+    .bind(formula.to_string())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// A player's questionnaire answers, if their starting Elo came from them
+#[tracing::instrument(skip_all)]
+pub async fn get_onboarding(
+    pool: &PgPool,
+    player_id: i32,
+) -> Result<Option<OnboardingAnswers>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT player_id, experience, position, fitness, starting_elo, formula, answered_at
+         FROM onboarding_answers WHERE player_id = $1",
+    )
+    .bind(player_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Create an invite link for a player, replacing any unclaimed one they had
+#[tracing::instrument(skip_all)]
+pub async fn create_invite(
+    pool: &PgPool,
+    player_id: i32,
+    token: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<Invite, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM invites WHERE player_id = $1 AND claimed_at IS NULL")
+        .bind(player_id)
+        .execute(&mut *tx)
+        .await?;
+    let invite = sqlx::query_as(
+        "INSERT INTO invites (player_id, token, expires_at) VALUES ($1, $2, $3)
+         RETURNING player_id, token, expires_at",
+    )
+    .bind(player_id)
+    .bind(token)
+    .bind(expires_at)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(invite)
+}
+
+/// Get an unclaimed, unexpired invite by its token
+#[tracing::instrument(skip_all)]
+pub async fn get_open_invite(pool: &PgPool, token: &str) -> Result<Option<Invite>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT player_id, token, expires_at FROM invites
+         WHERE token = $1 AND claimed_at IS NULL AND expires_at > NOW()",
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Mark an open invite as used. Returns its player ID, or None if the
+/// invite doesn't exist, has expired or was already claimed.
+#[tracing::instrument(skip_all)]
+pub async fn claim_invite<'e, E>(executor: E, token: &str) -> Result<Option<i32>, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_scalar(
+        "UPDATE invites SET claimed_at = NOW()
+         WHERE token = $1 AND claimed_at IS NULL AND expires_at > NOW()
+         RETURNING player_id",
+    )
+    .bind(token)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Get a player's account, if they have claimed an invite
+#[tracing::instrument(skip_all)]
+pub async fn get_player_account(
+    pool: &PgPool,
+    player_id: i32,
+) -> Result<Option<PlayerAccount>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT email, notify_teams, notify_results, pin_hash, claimed_at
+         FROM player_accounts WHERE player_id = $1",
+    )
+    .bind(player_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Create or replace a player's account details (claimed_at is reset to now)
+#[tracing::instrument(skip_all)]
+pub async fn upsert_player_account<'e, E>(
+    executor: E,
+    player_id: i32,
+    email: Option<&str>,
+    notify_teams: bool,
+    notify_results: bool,
+    pin_hash: Option<&str>,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query(
+        "INSERT INTO player_accounts (player_id, email, notify_teams, notify_results, pin_hash)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (player_id) DO UPDATE SET
+             email = EXCLUDED.email,
+             notify_teams = EXCLUDED.notify_teams,
+             notify_results = EXCLUDED.notify_results,
+             pin_hash = EXCLUDED.pin_hash,
+             claimed_at = NOW()",
+    )
+    .bind(player_id)
+    .bind(email)
+    .bind(notify_teams)
+    .bind(notify_results)
+    .bind(pin_hash)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Find a player by their exact name with their PIN hash, if they set one.
+/// Names are only unique as typed, so "sam" and "Sam" are different players.
+#[tracing::instrument(skip_all)]
+pub async fn get_player_pin_hash(
+    pool: &PgPool,
+    name: &str,
+) -> Result<Option<(i32, Option<String>)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT p.id, a.pin_hash FROM players p
+         LEFT JOIN player_accounts a ON a.player_id = p.id
+         WHERE p.name = $1",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Players who answered In for a match day, earliest answer first
+#[tracing::instrument(skip_all)]
+pub async fn get_in_rsvps(pool: &PgPool, match_on: NaiveDate) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT player_id FROM rsvps WHERE match_on = $1 AND status = 'in'
+         ORDER BY updated_at, player_id",
+    )
+    .bind(match_on)
+    .fetch_all(pool)
+    .await
+}
+
+/// RSVPs for match days from `from` to `to` (inclusive)
+#[tracing::instrument(skip_all)]
+pub async fn get_rsvps(
+    pool: &PgPool,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Rsvp>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT player_id, match_on, status, reason FROM rsvps
+         WHERE match_on BETWEEN $1 AND $2 ORDER BY match_on, player_id",
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
+
+/// Set a player's answer (and reason, if out) for a match day, replacing any earlier one
+#[tracing::instrument(skip_all)]
+pub async fn set_rsvp(
+    pool: &PgPool,
+    player_id: i32,
+    match_on: NaiveDate,
+    status: RsvpStatus,
+    reason: Option<AbsenceReason>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO rsvps (player_id, match_on, status, reason) VALUES ($1, $2, $3, $4)
+         ON CONFLICT (player_id, match_on)
+         DO UPDATE SET status = EXCLUDED.status, reason = EXCLUDED.reason, updated_at = NOW()",
+    )
+    .bind(player_id)
+    .bind(match_on)
+    .bind(status.as_str())
+    .bind(reason.map(|r| r.as_str()))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Teams picked automatically for a match day, if any
+#[tracing::instrument(skip_all)]
+pub async fn get_auto_teams(
+    pool: &PgPool,
+    match_on: NaiveDate,
+) -> Result<Option<AutoTeams>, sqlx::Error> {
+    sqlx::query_as("SELECT team_a, team_b FROM auto_teams WHERE match_on = $1")
+        .bind(match_on)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Save the automatically picked teams for a match day. Returns false if
+/// that day already has a split (so only one run ever announces it).
+#[tracing::instrument(skip_all)]
+pub async fn create_auto_teams(
+    pool: &PgPool,
+    match_on: NaiveDate,
+    team_a: &[i32],
+    team_b: &[i32],
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO auto_teams (match_on, team_a, team_b) VALUES ($1, $2, $3)
+         ON CONFLICT (match_on) DO NOTHING",
+    )
+    .bind(match_on)
+    .bind(team_a)
+    .bind(team_b)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// The coin toss for a match day's teams, if one was made. Teams are
+/// matched by their sorted player IDs.
+#[tracing::instrument(skip_all)]
+pub async fn get_coin_toss(
+    pool: &PgPool,
+    match_on: NaiveDate,
+    team_a: &[i32],
+    team_b: &[i32],
+) -> Result<Option<CoinToss>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT kickoff_team, tossed_at FROM coin_tosses
+         WHERE match_on = $1 AND team_a = $2 AND team_b = $3",
+    )
+    .bind(match_on)
+    .bind(sorted(team_a))
+    .bind(sorted(team_b))
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record a coin toss for a match day's teams and return the toss that
+/// stands: if those teams were already tossed for, the first toss wins.
+#[tracing::instrument(skip_all)]
+pub async fn create_coin_toss(
+    pool: &PgPool,
+    match_on: NaiveDate,
+    team_a: &[i32],
+    team_b: &[i32],
+    kickoff_team: &str,
+) -> Result<CoinToss, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO coin_tosses (match_on, team_a, team_b, kickoff_team) VALUES ($1, $2, $3, $4)
+         ON CONFLICT (match_on, team_a, team_b) DO NOTHING",
+    )
+    .bind(match_on)
+    .bind(sorted(team_a))
+    .bind(sorted(team_b))
+    .bind(kickoff_team)
+    .execute(pool)
+    .await?;
+    get_coin_toss(pool, match_on, team_a, team_b)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)
+}
+
+/// Player IDs in ascending order
+fn sorted(ids: &[i32]) -> Vec<i32> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids
+}
+
+/// All outgoing webhooks, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_webhooks(pool: &PgPool) -> Result<Vec<Webhook>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {WEBHOOK_COLUMNS} FROM webhooks ORDER BY id"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// Add an outgoing webhook
+#[tracing::instrument(skip_all)]
+pub async fn create_webhook(
+    pool: &PgPool,
+    url: &str,
+    secret: &str,
+    events: &str,
+) -> Result<Webhook, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "INSERT INTO webhooks (url, secret, events) VALUES ($1, $2, $3)
+         RETURNING {WEBHOOK_COLUMNS}"
+    ))
+    .bind(url)
+    .bind(secret)
+    .bind(events)
+    .fetch_one(pool)
+    .await
+}
+
+/// Remove an outgoing webhook. Returns false if it was already gone.
+#[tracing::instrument(skip_all)]
+pub async fn delete_webhook(pool: &PgPool, id: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record the outcome of a webhook's latest delivery
+#[tracing::instrument(skip_all)]
+pub async fn set_webhook_status(pool: &PgPool, id: i32, status: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE webhooks SET last_status = $2, last_attempt_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(status)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Log a delivery attempt, keeping only the latest `keep` attempts
+#[tracing::instrument(skip_all)]
+pub async fn log_webhook_delivery(
+    pool: &PgPool,
+    webhook_id: i32,
+    event: &str,
+    status: &str,
+    delivered: bool,
+    keep: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO webhook_deliveries (webhook_id, event, status, delivered)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(webhook_id)
+    .bind(event)
+    .bind(status)
+    .bind(delivered)
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "DELETE FROM webhook_deliveries WHERE id < (
+             SELECT MIN(id) FROM (SELECT id FROM webhook_deliveries ORDER BY id DESC LIMIT $1) newest
+         )",
+    )
+    .bind(keep)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The latest delivery attempts across all webhooks, newest first
+#[tracing::instrument(skip_all)]
+pub async fn get_webhook_deliveries(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT w.url, d.event, d.status, d.delivered, d.attempted_at
+         FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id
+         ORDER BY d.id DESC LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// The latest roster changes, newest first
+#[tracing::instrument(skip_all)]
+pub async fn get_roster_changes(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<RosterChange>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT player_name, change, detail, changed_at
+         FROM roster_changes ORDER BY id DESC LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Total size of the database in bytes
+#[tracing::instrument(skip_all)]
+pub async fn get_database_size(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT pg_database_size(current_database())")
+        .fetch_one(pool)
+        .await
+}
+
+/// Row counts and sizes of the app's tables, largest first
+#[tracing::instrument(skip_all)]
+pub async fn get_table_stats(pool: &PgPool) -> Result<Vec<TableStats>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT relname::TEXT AS name, n_live_tup AS rows, pg_total_relation_size(relid) AS bytes
+         FROM pg_stat_user_tables ORDER BY bytes DESC, name",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// One webhook by ID
+#[tracing::instrument(skip_all)]
+pub async fn get_webhook(pool: &PgPool, id: i32) -> Result<Option<Webhook>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {WEBHOOK_COLUMNS} FROM webhooks WHERE id = $1"
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Add a job to the background queue, due now
+#[tracing::instrument(skip_all)]
+pub async fn enqueue_job(
+    pool: &PgPool,
+    kind: &str,
+    payload: &serde_json::Value,
+    max_attempts: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO jobs (kind, payload, max_attempts) VALUES ($1, $2, $3)")
+        .bind(kind)
+        .bind(payload)
+        .bind(max_attempts)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Take the next due job, counting the attempt and pushing its next run out
+/// by `lease_secs` (so it runs again if this worker dies). `SKIP LOCKED`
+/// lets several servers share the queue.
+#[tracing::instrument(skip_all)]
+pub async fn claim_job(pool: &PgPool, lease_secs: f64) -> Result<Option<QueuedJob>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "UPDATE jobs SET attempts = attempts + 1, run_at = NOW() + make_interval(secs => $1)
+         WHERE id = (
+             SELECT id FROM jobs WHERE failed_at IS NULL AND run_at <= NOW()
+             ORDER BY run_at LIMIT 1 FOR UPDATE SKIP LOCKED
+         )
+         RETURNING {JOB_COLUMNS}"
+    ))
+    .bind(lease_secs)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Try a job again after `delay_secs`, noting why it failed
+#[tracing::instrument(skip_all)]
+pub async fn retry_job_later(
+    pool: &PgPool,
+    id: i32,
+    error: &str,
+    delay_secs: f64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE jobs SET last_error = $2, run_at = NOW() + make_interval(secs => $3) WHERE id = $1",
+    )
+    .bind(id)
+    .bind(error)
+    .bind(delay_secs)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Give up on a job; it stays listed on the Admin page
+#[tracing::instrument(skip_all)]
+pub async fn fail_job(pool: &PgPool, id: i32, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE jobs SET last_error = $2, failed_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(error)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Remove a finished (or dismissed) job
+#[tracing::instrument(skip_all)]
+pub async fn delete_job(pool: &PgPool, id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM jobs WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Jobs that ran out of attempts, newest first
+#[tracing::instrument(skip_all)]
+pub async fn get_failed_jobs(pool: &PgPool) -> Result<Vec<QueuedJob>, sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT {JOB_COLUMNS} FROM jobs WHERE failed_at IS NOT NULL ORDER BY failed_at DESC"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// Number of jobs waiting to run (including retries)
+#[tracing::instrument(skip_all)]
+pub async fn count_pending_jobs(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE failed_at IS NULL")
+        .fetch_one(pool)
+        .await
+}
+
+/// Give a failed job a fresh set of attempts, due now
+#[tracing::instrument(skip_all)]
+pub async fn requeue_job(pool: &PgPool, id: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE jobs SET failed_at = NULL, attempts = 0, run_at = NOW()
+         WHERE id = $1 AND failed_at IS NOT NULL",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Lift a suspension early. Returns false if it doesn't exist or was already lifted.
+#[tracing::instrument(skip_all)]
+pub async fn lift_suspension(pool: &PgPool, id: i32) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("UPDATE suspensions SET lifted_at = NOW() WHERE id = $1 AND lifted_at IS NULL")
+            .bind(id)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Clear a player's injuries so they can be checked in again. Returns false
+/// if they had none.
+#[tracing::instrument(skip_all)]
+pub async fn clear_injuries(pool: &PgPool, player_id: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE injuries SET cleared_at = NOW() WHERE player_id = $1 AND cleared_at IS NULL",
+    )
+    .bind(player_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Tables of a player's personal data, deleted outright when they are anonymized
+const ANONYMIZED_TABLES: [&str; 6] = [
+    "player_accounts",
+    "invites",
+    "player_notes",
+    "rsvps",
+    "injuries",
+    "onboarding_answers",
+];
+
+/// Anonymize a departed player: rename them, strip their profile and delete
+/// everything personal (account, invites, notes, RSVPs, injuries), keeping
+/// their matches and ratings. Comments and flags under their old name move to
+/// the new one. Returns false if the player doesn't exist.
+#[tracing::instrument(skip_all)]
+pub async fn anonymize_player(pool: &PgPool, id: i32, new_name: &str) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let old_name: Option<(String,)> =
+        sqlx::query_as("SELECT name FROM players WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let Some((old_name,)) = old_name else {
+        return Ok(false);
+    };
+
+    sqlx::query(
+        "UPDATE players SET name = $1, tags = '', jersey_number = NULL, stamina = NULL,
+             pace = NULL, age_band = NULL, position = NULL
+         WHERE id = $2",
+    )
+    .bind(new_name)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    for table in ANONYMIZED_TABLES {
+        sqlx::query(&format!("DELETE FROM {table} WHERE player_id = $1"))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query("UPDATE suspensions SET reason = '' WHERE player_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE season_standings SET name = $1 WHERE player_id = $2")
+        .bind(new_name)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE match_comments SET author = $1 WHERE LOWER(author) = LOWER($2)")
+        .bind(new_name)
+        .bind(&old_name)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE match_flags SET flagged_by = $1 WHERE LOWER(flagged_by) = LOWER($2)")
+        .bind(new_name)
+        .bind(&old_name)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Set whether a player's name is hidden from anonymous visitors. Returns
+/// false if the player doesn't exist.
+#[tracing::instrument(skip_all)]
+pub async fn set_hide_name(pool: &PgPool, player_id: i32, hide: bool) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE players SET hide_name = $1 WHERE id = $2")
+        .bind(hide)
+        .bind(player_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Every RSVP a player has given, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_player_rsvps(pool: &PgPool, player_id: i32) -> Result<Vec<Rsvp>, sqlx::Error> {
+    sqlx::query_as::<_, Rsvp>(
+        "SELECT player_id, match_on, status, reason FROM rsvps
+         WHERE player_id = $1 ORDER BY match_on",
+    )
+    .bind(player_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Peer survey answers from `since` on, newest survey first
+#[tracing::instrument(skip_all)]
+pub async fn get_peer_ratings_since(
+    pool: &PgPool,
+    since: NaiveDate,
+) -> Result<Vec<PeerRating>, sqlx::Error> {
+    sqlx::query_as::<_, PeerRating>(
+        "SELECT rater_id, player_id, period, skill, stamina, pace FROM peer_ratings
+         WHERE period >= $1 ORDER BY period DESC, rated_at DESC",
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
+/// Every peer survey answer a player has given
+#[tracing::instrument(skip_all)]
+pub async fn get_peer_ratings_by(
+    pool: &PgPool,
+    rater_id: i32,
+) -> Result<Vec<PeerRating>, sqlx::Error> {
+    sqlx::query_as::<_, PeerRating>(
+        "SELECT rater_id, player_id, period, skill, stamina, pace FROM peer_ratings
+         WHERE rater_id = $1 ORDER BY period, player_id",
+    )
+    .bind(rater_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Save a peer survey answer, replacing the rater's earlier answer about the
+/// same player in the same survey
+#[tracing::instrument(skip_all)]
+pub async fn set_peer_rating(pool: &PgPool, rating: &PeerRating) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO peer_ratings (rater_id, player_id, period, skill, stamina, pace)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (rater_id, player_id, period)
+         DO UPDATE SET skill = EXCLUDED.skill, stamina = EXCLUDED.stamina,
+                       pace = EXCLUDED.pace, rated_at = NOW()",
+    )
+    .bind(rating.rater_id)
+    .bind(rating.player_id)
+    .bind(rating.period)
+    .bind(rating.skill)
+    .bind(rating.stamina)
+    .bind(rating.pace)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Every suspension a player has had, lifted or not
+#[tracing::instrument(skip_all)]
+pub async fn get_player_suspensions(
+    pool: &PgPool,
+    player_id: i32,
+) -> Result<Vec<Suspension>, sqlx::Error> {
+    sqlx::query_as::<_, Suspension>(
+        "SELECT id, player_id, reason, returns_on, lifted_at, created_at FROM suspensions
+         WHERE player_id = $1 ORDER BY created_at",
+    )
+    .bind(player_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Every injury a player has had, cleared or not
+#[tracing::instrument(skip_all)]
+pub async fn get_player_injuries(
+    pool: &PgPool,
+    player_id: i32,
+) -> Result<Vec<Injury>, sqlx::Error> {
+    sqlx::query_as::<_, Injury>(
+        "SELECT id, player_id, note, started_on, expected_return, cleared_at, created_at
+         FROM injuries WHERE player_id = $1 ORDER BY started_on, id",
+    )
+    .bind(player_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Every flag raised under a name (case-insensitive), resolved or not
+#[tracing::instrument(skip_all)]
+pub async fn get_flags_by(pool: &PgPool, name: &str) -> Result<Vec<MatchFlag>, sqlx::Error> {
+    sqlx::query_as::<_, MatchFlag>(
+        "SELECT id, match_id, flagged_by, reason, created_at FROM match_flags
+         WHERE LOWER(flagged_by) = LOWER($1) ORDER BY created_at",
+    )
+    .bind(name)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the current data version, for page ETags
+#[tracing::instrument(skip_all)]
+pub async fn get_data_version(pool: &PgPool) -> Result<DataVersion, sqlx::Error> {
+    sqlx::query_as::<_, DataVersion>("SELECT version, changed_at FROM data_version")
+        .fetch_one(pool)
+        .await
+}
+
+/// Get the settings saved on the admin page as (key, value) pairs
+#[tracing::instrument(skip_all)]
+pub async fn get_settings(pool: &PgPool) -> Result<Vec<(String, String)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, String)>("SELECT key, value FROM settings ORDER BY key")
+        .fetch_all(pool)
+        .await
+}
+
+/// Save one setting, replacing any earlier value
+#[tracing::instrument(skip_all)]
+pub async fn save_setting<'e, E>(executor: E, key: &str, value: &str) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query(
+        "INSERT INTO settings (key, value) VALUES ($1, $2)
+         ON CONFLICT (key) DO UPDATE SET value = $2, updated_at = NOW()",
+    )
+    .bind(key)
+    .bind(value)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Delete every saved setting, falling back to the environment
+#[tracing::instrument(skip_all)]
+pub async fn clear_settings<'e, E>(executor: E) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query("DELETE FROM settings")
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Get all season rollovers, oldest first
+#[tracing::instrument(skip_all)]
+pub async fn get_seasons<'e, E>(executor: E) -> Result<Vec<Season>, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_as::<_, Season>(
+        "SELECT id, name, ended_on, carry, created_at FROM seasons ORDER BY ended_on, id",
+    )
+    .fetch_all(executor)
+    .await
+}
+
+/// Get archived standings for every season, best rank first
+#[tracing::instrument(skip_all)]
+pub async fn get_season_standings(pool: &PgPool) -> Result<Vec<SeasonStanding>, sqlx::Error> {
+    sqlx::query_as::<_, SeasonStanding>(
+        "SELECT season_id, player_id, name, rank, elo, matches_played FROM season_standings
+         ORDER BY season_id, rank",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a season rollover
+#[tracing::instrument(skip_all)]
+pub async fn create_season<'e, E>(
+    executor: E,
+    name: &str,
+    ended_on: NaiveDate,
+    carry: f32,
+) -> Result<Season, sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query_as::<_, Season>(
+        "INSERT INTO seasons (name, ended_on, carry) VALUES ($1, $2, $3)
+         RETURNING id, name, ended_on, carry, created_at",
+    )
+    .bind(name)
+    .bind(ended_on)
+    .bind(carry)
+    .fetch_one(executor)
+    .await
+}
+
+/// Archive one player's final standing in a season
+#[tracing::instrument(skip_all)]
+pub async fn create_season_standing<'e, E>(
+    executor: E,
+    standing: &SeasonStanding,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    sqlx::query(
+        "INSERT INTO season_standings (season_id, player_id, name, rank, elo, matches_played)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(standing.season_id)
+    .bind(standing.player_id)
+    .bind(&standing.name)
+    .bind(standing.rank)
+    .bind(standing.elo)
+    .bind(standing.matches_played)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Player tables anonymizing keeps: ratings, stats and answers that are
+    /// anonymous anyway (suspension reasons are blanked and standing and
+    /// roster-change names rewritten instead)
+    const RETAINED_TABLES: [&str; 9] = [
+        "suspensions",
+        "season_standings",
+        "rating_baselines",
+        "player_stats",
+        "player_match_days",
+        "tag_suggestion_dismissals",
+        "peer_ratings",
+        "recalibrations",
+        "roster_changes",
+    ];
+
+    #[test]
+    fn test_anonymize_covers_every_player_table() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/migrations");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let sql = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            for block in sql.split("CREATE TABLE IF NOT EXISTS ").skip(1) {
+                let table = block.split_whitespace().next().unwrap();
+                let columns = block.split(");").next().unwrap();
+                if columns.contains("player_id") {
+                    assert!(
+                        ANONYMIZED_TABLES.contains(&table) || RETAINED_TABLES.contains(&table),
+                        "{table} holds player data: delete it in anonymize_player or list it as retained"
+                    );
+                }
+            }
+        }
+        assert!(ANONYMIZED_TABLES.contains(&"onboarding_answers"));
+    }
+
+    #[test]
+    fn test_like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("sam"), "%sam%");
+        assert_eq!(like_pattern("100%_"), "%100\\%\\_%");
+        assert_eq!(like_pattern("a\\b"), "%a\\\\b%");
+    }
+}
@@ -0,0 +1,225 @@
+use crate::balance::{balance_teams_with, BalanceOptions};
+use crate::elo::expected_score;
+use crate::models::{
+    goal_score, Goal, NewMatch, NewPlayer, Player, Side, DEFAULT_MATCH_MINUTES, MAX_PLAYERS,
+};
+use chrono::{Datelike, Duration, NaiveDate};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Fewest players turning up to a demo match
+const MIN_TURNOUT: usize = 10;
+
+/// Demo player: name, position, tags, jersey number, stamina, pace, age band
+/// and the hidden strength that decides their simulated results
+type DemoPlayer = (
+    &'static str,
+    &'static str,
+    &'static str,
+    i32,
+    i32,
+    i32,
+    &'static str,
+    f32,
+);
+
+/// Demo roster
+const ROSTER: &[DemoPlayer] = &[
+    ("Alex", "GK", "GK", 1, 3, 2, "35-44", 1250.0),
+    ("Ben", "DEF", "DEF", 4, 4, 3, "25-34", 1280.0),
+    ("Carlos", "ATT", "ATK,RUNNER", 9, 4, 5, "U25", 1380.0),
+    ("Dan", "MID", "PLAYMAKER", 8, 3, 3, "25-34", 1320.0),
+    ("Eddie", "DEF", "DEF", 5, 3, 2, "45+", 1120.0),
+    ("Femi", "MID", "RUNNER", 7, 5, 5, "U25", 1260.0),
+    ("George", "ATT", "ATK", 10, 2, 3, "35-44", 1200.0),
+    ("Hugo", "DEF", "DEF,PLAYMAKER", 6, 4, 3, "25-34", 1240.0),
+    ("Ivan", "GK", "GK", 13, 2, 1, "45+", 1100.0),
+    ("Jamal", "ATT", "ATK", 11, 4, 4, "25-34", 1300.0),
+    ("Kenji", "MID", "PLAYMAKER,RUNNER", 14, 5, 4, "U25", 1220.0),
+    ("Liam", "DEF", "DEF", 3, 3, 3, "35-44", 1160.0),
+    ("Marco", "ATT", "ATK,PLAYMAKER", 17, 3, 4, "25-34", 1340.0),
+    ("Nico", "MID", "RUNNER", 21, 4, 4, "25-34", 1180.0),
+    ("Ollie", "DEF", "DEF", 2, 4, 2, "U25", 1140.0),
+    ("Pete", "ATT", "ATK", 19, 2, 2, "45+", 1080.0),
+];
+
+/// Players added by `sfm seed-demo`, all starting at the default Elo
+pub fn demo_roster() -> Vec<NewPlayer> {
+    ROSTER
+        .iter()
+        .map(
+            |&(name, position, tags, jersey, stamina, pace, age_band, _)| NewPlayer {
+                name: name.to_string(),
+                elo: None,
+                tags: Some(tags.to_string()),
+                jersey_number: Some(jersey),
+                stamina: Some(stamina),
+                pace: Some(pace),
+                age_band: Some(age_band.to_string()),
+                position: Some(position.to_string()),
+            },
+        )
+        .collect()
+}
+
+/// Hidden strength of each demo player by name
+fn strengths() -> HashMap<&'static str, f32> {
+    ROSTER
+        .iter()
+        .map(|&(name, .., strength)| (name, strength))
+        .collect()
+}
+
+/// The Sunday on or before `date`
+pub fn last_sunday(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_sunday() as i64)
+}
+
+/// A weekly season of matches between the (already created) demo players,
+/// ending on `last_match`. Each week a random squad turns up and is split by
+/// the balancer as on match day; scores are drawn from the players' hidden
+/// strengths. Snapshots are left empty for a replay to fill in.
+pub fn demo_matches(
+    players: &[Player],
+    last_match: NaiveDate,
+    weeks: usize,
+    rng: &mut impl Rng,
+) -> Vec<NewMatch> {
+    let strengths = strengths();
+    let strength = |p: &Player| strengths.get(p.name.as_str()).copied().unwrap_or_default();
+    let team_strength =
+        |team: &[Player]| team.iter().map(strength).sum::<f32>() / team.len().max(1) as f32;
+    let max_turnout = players.len().min(MAX_PLAYERS);
+    if max_turnout < MIN_TURNOUT {
+        return Vec::new();
+    }
+
+    (0..weeks)
+        .rev()
+        .filter_map(|week| {
+            let mut squad = players.to_vec();
+            squad.shuffle(rng);
+            squad.truncate(rng.gen_range(MIN_TURNOUT..=max_turnout));
+            let split = balance_teams_with(&squad, false, &BalanceOptions::default())?;
+
+            // Each goal goes to team A with its expected score as the probability,
+            // scored by anyone on the team at a random minute
+            let expected_a =
+                expected_score(team_strength(&split.team_a), team_strength(&split.team_b));
+            let mut goals: Vec<Goal> = (0..rng.gen_range(2..=10))
+                .map(|_| {
+                    let (team, scorers) = if rng.gen_bool(expected_a as f64) {
+                        (Side::A, &split.team_a)
+                    } else {
+                        (Side::B, &split.team_b)
+                    };
+                    Goal {
+                        team,
+                        scorer: scorers.choose(rng).map(|p| p.id),
+                        minute: Some(rng.gen_range(1..=DEFAULT_MATCH_MINUTES)),
+                    }
+                })
+                .collect();
+            goals.sort_by_key(|g| g.minute);
+            let (score_a, score_b) = goal_score(&goals);
+
+            Some(NewMatch {
+                played_at: last_match - Duration::weeks(week as i64),
+                team_a: split.team_a.iter().map(|p| p.id).collect(),
+                team_b: split.team_b.iter().map(|p| p.id).collect(),
+                score_a,
+                score_b,
+                elo_snapshot: serde_json::json!({}),
+                notes: String::new(),
+                k_multiplier: 1.0,
+                captain_a: None,
+                captain_b: None,
+                team_name_a: None,
+                team_name_b: None,
+                goals,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MAX_PER_TEAM, MAX_STARTING_ELO, MIN_STARTING_ELO};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    fn demo_players() -> Vec<Player> {
+        demo_roster()
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| Player {
+                id: i as i32 + 1,
+                name: p.name,
+                elo: 1200.0,
+                tags: p.tags.unwrap_or_default(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_demo_roster() {
+        let roster = demo_roster();
+        let names: HashSet<_> = roster.iter().map(|p| p.name.as_str()).collect();
+        let jerseys: HashSet<_> = roster.iter().map(|p| p.jersey_number).collect();
+        assert_eq!(names.len(), roster.len());
+        assert_eq!(jerseys.len(), roster.len());
+        assert!(ROSTER
+            .iter()
+            .all(|p| (MIN_STARTING_ELO..=MAX_STARTING_ELO).contains(&p.7)));
+    }
+
+    #[test]
+    fn test_last_sunday() {
+        let sunday = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap();
+        assert_eq!(last_sunday(sunday), sunday);
+        assert_eq!(last_sunday(sunday + Duration::days(3)), sunday);
+    }
+
+    #[test]
+    fn test_demo_matches() {
+        let players = demo_players();
+        let last = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap();
+        let matches = demo_matches(&players, last, 20, &mut StdRng::seed_from_u64(7));
+
+        assert_eq!(matches.len(), 20);
+        assert_eq!(matches[0].played_at, last - Duration::weeks(19));
+        assert_eq!(matches[19].played_at, last);
+        for m in &matches {
+            let a: HashSet<_> = m.team_a.iter().collect();
+            assert!(m.team_b.iter().all(|id| !a.contains(id)));
+            assert!(m.team_a.len() <= MAX_PER_TEAM && m.team_b.len() <= MAX_PER_TEAM);
+            assert!(m.team_a.len() + m.team_b.len() >= MIN_TURNOUT);
+            assert!((2..=10).contains(&(m.score_a + m.score_b)));
+            // Every goal is scored by someone on the team it counts for
+            for goal in &m.goals {
+                let team = if goal.team == Side::A {
+                    &m.team_a
+                } else {
+                    &m.team_b
+                };
+                assert!(goal.scorer.is_some_and(|id| team.contains(&id)));
+            }
+        }
+
+        // The same seed gives the same season
+        let again = demo_matches(&players, last, 20, &mut StdRng::seed_from_u64(7));
+        assert_eq!(again[5].team_a, matches[5].team_a);
+        assert_eq!(again[5].score_a, matches[5].score_a);
+    }
+
+    #[test]
+    fn test_too_few_players() {
+        let players = demo_players()[..MIN_TURNOUT - 1].to_vec();
+        let last = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap();
+        assert!(demo_matches(&players, last, 5, &mut StdRng::seed_from_u64(1)).is_empty());
+    }
+}
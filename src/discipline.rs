@@ -0,0 +1,99 @@
+use crate::db;
+use crate::models::Suspension;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// The suspension currently keeping each player out, keyed by player ID.
+/// If a player has several, the one ending last wins (an indefinite ban beats all).
+pub fn active_by_player(
+    suspensions: Vec<Suspension>,
+    today: NaiveDate,
+) -> HashMap<i32, Suspension> {
+    let mut active: HashMap<i32, Suspension> = HashMap::new();
+    for suspension in suspensions.into_iter().filter(|s| s.is_active(today)) {
+        let longer = match active.get(&suspension.player_id) {
+            Some(current) => ends_later(&suspension, current),
+            None => true,
+        };
+        if longer {
+            active.insert(suspension.player_id, suspension);
+        }
+    }
+    active
+}
+
+/// Load suspensions in force on `today` (the group's local date), keyed by player ID
+pub async fn load_active(pool: &PgPool, today: NaiveDate) -> HashMap<i32, Suspension> {
+    let suspensions = db::get_unlifted_suspensions(pool).await.unwrap_or_default();
+    active_by_player(suspensions, today)
+}
+
+/// Whether `a` ends after `b` (None = never ends)
+fn ends_later(a: &Suspension, b: &Suspension) -> bool {
+    match (a.returns_on, b.returns_on) {
+        (None, _) => b.returns_on.is_some(),
+        (Some(_), None) => false,
+        (Some(a), Some(b)) => a > b,
+    }
+}
+
+/// Short status shown on the roster and at check-in
+pub fn status_label(suspension: &Suspension) -> String {
+    match suspension.returns_on {
+        Some(date) => format!("Suspended until {}", date.format("%Y-%m-%d")),
+        None => "Banned".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn make_suspension(id: i32, player_id: i32, returns_on: Option<NaiveDate>) -> Suspension {
+        Suspension {
+            id,
+            player_id,
+            reason: String::new(),
+            returns_on,
+            lifted_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+    }
+
+    #[test]
+    fn test_expired_and_lifted_are_inactive() {
+        let mut lifted = make_suspension(2, 2, None);
+        lifted.lifted_at = Some(Utc::now());
+        let suspensions = vec![make_suspension(1, 1, Some(date(10))), lifted];
+
+        assert_eq!(active_by_player(suspensions.clone(), date(9)).len(), 1);
+        // Back on the return date
+        assert!(active_by_player(suspensions, date(10)).is_empty());
+    }
+
+    #[test]
+    fn test_longest_suspension_wins() {
+        let suspensions = vec![
+            make_suspension(1, 1, Some(date(20))),
+            make_suspension(2, 1, None),
+            make_suspension(3, 1, Some(date(25))),
+        ];
+        let active = active_by_player(suspensions, date(1));
+        assert_eq!(active[&1].id, 2);
+    }
+
+    #[test]
+    fn test_status_label() {
+        assert_eq!(
+            status_label(&make_suspension(1, 1, Some(date(10)))),
+            "Suspended until 2024-06-10"
+        );
+        assert_eq!(status_label(&make_suspension(1, 1, None)), "Banned");
+    }
+}
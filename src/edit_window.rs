@@ -0,0 +1,86 @@
+use crate::auth::{current_player, is_authenticated, AuthJar};
+use crate::models::Match;
+use crate::AppState;
+use chrono::{DateTime, Duration, Utc};
+
+/// Someone who may fix a recorded result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Editor {
+    /// Logged in with the admin password: any match, any time
+    Admin,
+    /// A logged-in player: matches they played in, within the edit window
+    Player(i32),
+}
+
+impl Editor {
+    /// Who is asking: an admin session wins over a player session
+    pub fn from_request(jar: &AuthJar, state: &AppState) -> Option<Self> {
+        if is_authenticated(jar, state) {
+            Some(Editor::Admin)
+        } else {
+            current_player(jar).map(Editor::Player)
+        }
+    }
+}
+
+/// When players can no longer fix a match (`window_hours` after it was recorded)
+pub fn window_closes(m: &Match, window_hours: u32) -> DateTime<Utc> {
+    m.created_at + Duration::hours(window_hours as i64)
+}
+
+/// Whether `editor` may amend `m` at `now`. Voided matches are never
+/// amended (restore them first); a zero-hour window leaves it to admins.
+pub fn can_amend(editor: Editor, m: &Match, window_hours: u32, now: DateTime<Utc>) -> bool {
+    if m.voided_at.is_some() {
+        return false;
+    }
+    match editor {
+        Editor::Admin => true,
+        Editor::Player(id) => {
+            (m.team_a.contains(&id) || m.team_b.contains(&id))
+                && now < window_closes(m, window_hours)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn make_match() -> Match {
+        Match {
+            id: 1,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, 30).unwrap(),
+            team_a: vec![1, 2],
+            team_b: vec![3, 4],
+            score_a: 2,
+            score_b: 1,
+            created_at: DateTime::from_timestamp(1_743_336_000, 0).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_players_within_window() {
+        let m = make_match();
+        let soon = m.created_at + Duration::hours(23);
+        let later = m.created_at + Duration::hours(25);
+        assert!(can_amend(Editor::Player(1), &m, 24, soon));
+        assert!(can_amend(Editor::Player(4), &m, 24, soon));
+        // Not in the match
+        assert!(!can_amend(Editor::Player(5), &m, 24, soon));
+        // Window closed, or no window at all
+        assert!(!can_amend(Editor::Player(1), &m, 24, later));
+        assert!(!can_amend(Editor::Player(1), &m, 0, m.created_at));
+    }
+
+    #[test]
+    fn test_admins_any_time() {
+        let mut m = make_match();
+        let much_later = m.created_at + Duration::days(365);
+        assert!(can_amend(Editor::Admin, &m, 0, much_later));
+        m.voided_at = Some(much_later);
+        assert!(!can_amend(Editor::Admin, &m, 24, much_later));
+    }
+}
@@ -1,17 +1,177 @@
-use crate::models::{EloSnapshot, Player, ELO_K_FACTOR, GD_MULTIPLIER_CAP, HANDICAP_PER_PLAYER};
+use crate::models::{
+    EloSnapshot, Player, CALIBRATION_K_MULTIPLIER, CALIBRATION_WEIGHT, ELO_DEFAULT, ELO_K_FACTOR,
+    GD_MULTIPLIER_CAP, HANDICAP_PER_PLAYER, RETURN_K_MULTIPLIER_MAX,
+};
 use std::collections::HashMap;
 
+/// Tunable parts of the rating engine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EloConfig {
+    /// Share of the gap to 1200 closed for every participant each match (0 = off)
+    pub regression: f32,
+    /// Elo handicap for the first missing player-equivalent
+    pub handicap_per_player: f32,
+    /// Exponent on the number of missing players (1 = linear, above 1 = each extra
+    /// missing player is worth more than the last)
+    pub handicap_curve: f32,
+    /// K-factor: the most a team's players can gain or lose in a normal match
+    pub k_factor: f32,
+    /// Cap on the goal-difference multiplier
+    pub gd_cap: f32,
+    /// Elo handicap for a team without a goalkeeper when the other has one (0 = off)
+    pub keeper_handicap: f32,
+    /// Most a player's rating can move in one match, either way (None = no cap)
+    pub max_change: Option<f32>,
+    /// Days away after which a player's first match back moves their rating
+    /// faster (None = off)
+    pub absence_days: Option<u32>,
+    /// Player whose rating never moves, to hold the scale steady over the
+    /// years; their share of each result goes to their teammates (None = off)
+    pub anchor: Option<i32>,
+}
+
+impl Default for EloConfig {
+    fn default() -> Self {
+        Self {
+            regression: 0.0,
+            handicap_per_player: HANDICAP_PER_PLAYER,
+            handicap_curve: 1.0,
+            k_factor: ELO_K_FACTOR,
+            gd_cap: GD_MULTIPLIER_CAP,
+            keeper_handicap: 0.0,
+            max_change: None,
+            absence_days: None,
+            anchor: None,
+        }
+    }
+}
+
+/// Elo handicap for a team `missing` player-equivalents short (negative if it has extra)
+pub fn handicap(missing: f32, config: &EloConfig) -> f32 {
+    missing.signum() * config.handicap_per_player * missing.abs().powf(config.handicap_curve)
+}
+
+/// Goalkeeper handicap on Team A: positive when only Team B fields a
+/// goalkeeper, negative when only Team A does, 0 when both or neither do
+pub fn keeper_handicap(team_a: &[Player], team_b: &[Player], config: &EloConfig) -> f32 {
+    let has_keeper = |team: &[Player]| team.iter().any(Player::is_goalkeeper);
+    match (has_keeper(team_a), has_keeper(team_b)) {
+        (false, true) => config.keeper_handicap,
+        (true, false) => -config.keeper_handicap,
+        _ => 0.0,
+    }
+}
+
+/// Goalkeeper handicap applied to Team A in a recorded snapshot (0 for older snapshots)
+pub fn recorded_keeper_handicap(team_a: &[i32], snapshot: &HashMap<i32, EloSnapshot>) -> f32 {
+    team_a
+        .iter()
+        .find_map(|id| snapshot.get(id))
+        .map(|s| s.keeper_handicap)
+        .unwrap_or(0.0)
+}
+
+/// Handicap applied to Team A in a recorded snapshot (0 for older snapshots)
+pub fn recorded_handicap(team_a: &[i32], snapshot: &HashMap<i32, EloSnapshot>) -> f32 {
+    team_a
+        .iter()
+        .find_map(|id| snapshot.get(id))
+        .map(|s| s.handicap)
+        .unwrap_or(0.0)
+}
+
+/// Per-match pull toward 1200 for a player at `elo`
+pub fn regression_pull(elo: f32, regression: f32) -> f32 {
+    (ELO_DEFAULT - elo) * regression
+}
+
+/// Clamp a player's delta to `config.max_change`, if set
+fn capped(delta: f32, config: &EloConfig) -> f32 {
+    match config.max_change {
+        Some(cap) => delta.clamp(-cap, cap),
+        None => delta,
+    }
+}
+
+/// Hold the anchor player (if on `team`) at their rating and share the change
+/// they would have had among their teammates by participation, so the team
+/// still gains or loses the same total. An anchor without teammates' change
+/// is simply dropped.
+fn redistribute_anchor(
+    changes: &mut HashMap<i32, EloSnapshot>,
+    team: &[Player],
+    team_delta: f32,
+    config: &EloConfig,
+) {
+    let Some(anchor) = config
+        .anchor
+        .and_then(|id| team.iter().find(|p| p.id == id))
+    else {
+        return;
+    };
+    let Some(snapshot) = changes.get_mut(&anchor.id) else {
+        return;
+    };
+    let missing = capped(team_delta * k_boost(anchor, config), config) * snapshot.participation;
+    snapshot.delta = 0.0;
+
+    let teammates: Vec<i32> = team
+        .iter()
+        .map(|p| p.id)
+        .filter(|id| *id != anchor.id)
+        .collect();
+    let weight: f32 = teammates
+        .iter()
+        .filter_map(|id| changes.get(id))
+        .map(|s| s.participation)
+        .sum();
+    if weight == 0.0 {
+        return;
+    }
+    for id in teammates {
+        if let Some(snapshot) = changes.get_mut(&id) {
+            snapshot.delta = capped(snapshot.delta + missing / weight, config);
+        }
+    }
+}
+
+/// How much faster a player's own rating moves this match
+fn calibration_multiplier(player: &Player) -> f32 {
+    if player.is_calibrating() {
+        CALIBRATION_K_MULTIPLIER
+    } else {
+        1.0
+    }
+}
+
+/// K boost for a player back after `days_away`: ×1.5 at `config.absence_days`,
+/// growing to ×2 at twice that (1 when shorter or off)
+pub fn return_multiplier(days_away: Option<i32>, config: &EloConfig) -> f32 {
+    match (days_away, config.absence_days) {
+        (Some(days), Some(threshold)) if threshold > 0 && days >= threshold as i32 => {
+            (1.0 + 0.5 * days as f32 / threshold as f32).min(RETURN_K_MULTIPLIER_MAX)
+        }
+        _ => 1.0,
+    }
+}
+
+/// A player's own K boost this match: calibration or a long absence,
+/// whichever is larger (they don't stack)
+fn k_boost(player: &Player, config: &EloConfig) -> f32 {
+    calibration_multiplier(player).max(return_multiplier(player.days_away, config))
+}
+
 /// Calculate expected score for team A
 pub fn expected_score(elo_a: f32, elo_b: f32) -> f32 {
     1.0 / (1.0 + 10_f32.powf((elo_b - elo_a) / 400.0))
 }
 
-/// Calculate goal difference multiplier (capped)
-pub fn goal_diff_multiplier(goal_diff: i32) -> f32 {
+/// Calculate goal difference multiplier (capped at `cap`)
+pub fn goal_diff_multiplier(goal_diff: i32, cap: f32) -> f32 {
     if goal_diff <= 1 {
         1.0
     } else {
-        (1.0 + (goal_diff - 1) as f32 * 0.5).min(GD_MULTIPLIER_CAP)
+        (1.0 + (goal_diff - 1) as f32 * 0.5).min(cap)
     }
 }
 
@@ -23,18 +183,45 @@ pub fn average_elo(players: &[Player]) -> f32 {
     players.iter().map(|p| p.elo).sum::<f32>() / players.len() as f32
 }
 
+/// Team Elo for rating a match: calibrating players count for less,
+/// so an unproven rating doesn't skew everyone else's expected score
+pub fn rated_team_elo(players: &[Player]) -> f32 {
+    let weight = |p: &Player| {
+        if p.is_calibrating() {
+            CALIBRATION_WEIGHT
+        } else {
+            1.0
+        }
+    };
+    let total: f32 = players.iter().map(weight).sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    players.iter().map(|p| p.elo * weight(p)).sum::<f32>() / total
+}
+
 /// Calculate Elo changes for all players in a match
 /// participation: map of player ID -> participation (0.0 to 1.0), defaults to 1.0
-/// Returns a map of player ID -> EloSnapshot (before elo, delta, and participation)
+/// k_multiplier: scales K for this match (1.0 normally, higher for special events)
+/// Returns a map of player ID -> EloSnapshot (before elo, delta, and participation).
+/// With `config.regression` set, each delta also includes that player's pull toward 1200.
+/// Players in their first CALIBRATION_MATCHES matches move faster and weigh less in their
+/// team's Elo, and are flagged `calibrating` in the snapshot. With `config.absence_days`
+/// set, a player's first match back after that long also moves faster (`returning`).
+/// `config.max_change` caps each final delta, so a lopsided upset can't swing anyone
+/// too far. With `config.anchor` set, that player's delta is 0 and their teammates
+/// share it.
 pub fn calculate_elo_changes(
     team_a: &[Player],
     team_b: &[Player],
     score_a: i32,
     score_b: i32,
     participation: &HashMap<i32, f32>,
+    k_multiplier: f32,
+    config: &EloConfig,
 ) -> HashMap<i32, EloSnapshot> {
-    let elo_a = average_elo(team_a);
-    let elo_b = average_elo(team_b);
+    let elo_a = rated_team_elo(team_a);
+    let elo_b = rated_team_elo(team_b);
 
     // Calculate effective team sizes based on participation
     let effective_a: f32 = team_a
@@ -48,10 +235,13 @@ pub fn calculate_elo_changes(
 
     // Calculate handicap: if Team A has fewer effective players, they're disadvantaged
     let player_diff = effective_b - effective_a;
-    let handicap = player_diff * HANDICAP_PER_PLAYER;
+    let handicap = handicap(player_diff, config);
+
+    // A team without a goalkeeper against one with is disadvantaged too
+    let keeper = keeper_handicap(team_a, team_b, config);
 
     // Adjust Team A's Elo for expected score calculation
-    let adjusted_elo_a = elo_a - handicap;
+    let adjusted_elo_a = elo_a - handicap - keeper;
     let expected_a = expected_score(adjusted_elo_a, elo_b);
 
     let actual_a = if score_a > score_b {
@@ -63,9 +253,9 @@ pub fn calculate_elo_changes(
     };
 
     let gd = (score_a - score_b).abs();
-    let multiplier = goal_diff_multiplier(gd);
+    let multiplier = goal_diff_multiplier(gd, config.gd_cap);
 
-    let delta_a = ELO_K_FACTOR * multiplier * (actual_a - expected_a);
+    let delta_a = config.k_factor * k_multiplier * multiplier * (actual_a - expected_a);
     let delta_b = -delta_a; // Zero-sum
 
     let mut changes = HashMap::new();
@@ -76,8 +266,16 @@ pub fn calculate_elo_changes(
             p.id,
             EloSnapshot {
                 before: p.elo,
-                delta: delta_a,
+                delta: capped(
+                    delta_a * k_boost(p, config) + regression_pull(p.elo, config.regression),
+                    config,
+                ),
                 participation: player_participation,
+                handicap,
+                keeper_handicap: keeper,
+                calibrating: p.is_calibrating(),
+                returning: return_multiplier(p.days_away, config) > 1.0,
+                tags: p.tags.clone(),
             },
         );
     }
@@ -88,19 +286,29 @@ pub fn calculate_elo_changes(
             p.id,
             EloSnapshot {
                 before: p.elo,
-                delta: delta_b,
+                delta: capped(
+                    delta_b * k_boost(p, config) + regression_pull(p.elo, config.regression),
+                    config,
+                ),
                 participation: player_participation,
+                handicap: -handicap,
+                keeper_handicap: -keeper,
+                calibrating: p.is_calibrating(),
+                returning: return_multiplier(p.days_away, config) > 1.0,
+                tags: p.tags.clone(),
             },
         );
     }
 
+    redistribute_anchor(&mut changes, team_a, delta_a, config);
+    redistribute_anchor(&mut changes, team_b, delta_b, config);
+
     changes
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
 
     fn make_player(id: i32, name: &str, elo: f32) -> Player {
         Player {
@@ -108,8 +316,15 @@ mod tests {
             name: name.to_string(),
             elo,
             tags: String::new(),
+            matches_played: 10,
+            ..Default::default()
+        }
+    }
+
+    fn make_newcomer(id: i32, name: &str, elo: f32) -> Player {
+        Player {
             matches_played: 0,
-            created_at: Utc::now(),
+            ..make_player(id, name, elo)
         }
     }
 
@@ -127,11 +342,15 @@ mod tests {
 
     #[test]
     fn test_goal_diff_multiplier() {
-        assert_eq!(goal_diff_multiplier(0), 1.0);
-        assert_eq!(goal_diff_multiplier(1), 1.0);
-        assert_eq!(goal_diff_multiplier(2), 1.5);
-        assert_eq!(goal_diff_multiplier(3), 2.0);
-        assert_eq!(goal_diff_multiplier(10), GD_MULTIPLIER_CAP);
+        assert_eq!(goal_diff_multiplier(0, GD_MULTIPLIER_CAP), 1.0);
+        assert_eq!(goal_diff_multiplier(1, GD_MULTIPLIER_CAP), 1.0);
+        assert_eq!(goal_diff_multiplier(2, GD_MULTIPLIER_CAP), 1.5);
+        assert_eq!(goal_diff_multiplier(3, GD_MULTIPLIER_CAP), 2.0);
+        assert_eq!(
+            goal_diff_multiplier(10, GD_MULTIPLIER_CAP),
+            GD_MULTIPLIER_CAP
+        );
+        assert_eq!(goal_diff_multiplier(10, 1.5), 1.5);
     }
 
     #[test]
@@ -141,7 +360,15 @@ mod tests {
         let participation = HashMap::new(); // All 100%
 
         // Team A wins 2-1
-        let changes = calculate_elo_changes(&team_a, &team_b, 2, 1, &participation);
+        let changes = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            2,
+            1,
+            &participation,
+            1.0,
+            &EloConfig::default(),
+        );
 
         // All players should have Elo changes
         assert_eq!(changes.len(), 4);
@@ -164,7 +391,15 @@ mod tests {
         let participation = HashMap::new();
 
         // Draw 1-1
-        let changes = calculate_elo_changes(&team_a, &team_b, 1, 1, &participation);
+        let changes = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            1,
+            1,
+            &participation,
+            1.0,
+            &EloConfig::default(),
+        );
 
         // Equal Elo teams drawing should result in no change (keyed by player ID)
         let delta_a = changes.get(&1).unwrap().delta;
@@ -182,7 +417,15 @@ mod tests {
         let participation = HashMap::new();
 
         // Underdog wins 3-0 (big upset with large goal diff)
-        let changes = calculate_elo_changes(&favorites, &underdogs, 0, 3, &participation);
+        let changes = calculate_elo_changes(
+            &favorites,
+            &underdogs,
+            0,
+            3,
+            &participation,
+            1.0,
+            &EloConfig::default(),
+        );
 
         // Favorites lose a lot, underdogs gain a lot (keyed by player ID)
         let fav_delta = changes.get(&1).unwrap().delta;
@@ -200,7 +443,15 @@ mod tests {
         let participation = HashMap::new(); // All 100%
 
         // Draw - Team A should gain because they were handicapped
-        let changes = calculate_elo_changes(&team_a, &team_b, 1, 1, &participation);
+        let changes = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            1,
+            1,
+            &participation,
+            1.0,
+            &EloConfig::default(),
+        );
 
         // Team A had 1 player vs 2, so 100 Elo handicap
         // With handicap, Team A expected to lose, so draw = gain (keyed by player ID)
@@ -208,6 +459,282 @@ mod tests {
         assert!(delta_a > 0.0, "Short-handed team should gain Elo on draw");
     }
 
+    #[test]
+    fn test_elo_regression_to_mean() {
+        let team_a = vec![make_player(1, "High", 1400.0)];
+        let team_b = vec![make_player(2, "Low", 1000.0)];
+        let participation = HashMap::from([(2, 0.5)]);
+        let config = EloConfig {
+            regression: 0.05,
+            ..Default::default()
+        };
+
+        let plain = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            1,
+            1,
+            &participation,
+            1.0,
+            &EloConfig::default(),
+        );
+        let pulled = calculate_elo_changes(&team_a, &team_b, 1, 1, &participation, 1.0, &config);
+
+        // 5% of the gap to 1200 on top of the match result
+        assert!((pulled[&1].delta - (plain[&1].delta - 10.0)).abs() < 0.001);
+        assert!((pulled[&2].delta - (plain[&2].delta + 10.0)).abs() < 0.001);
+        // Still scaled by participation when applied
+        assert_eq!(pulled[&2].participation, 0.5);
+        assert_eq!(regression_pull(1200.0, 0.05), 0.0);
+    }
+
+    #[test]
+    fn test_calibrating_player() {
+        let regulars = vec![make_player(1, "A1", 1200.0), make_player(2, "A2", 1200.0)];
+        let with_newcomer = vec![
+            make_player(1, "A1", 1200.0),
+            make_newcomer(2, "New", 1500.0),
+        ];
+        let team_b = vec![make_player(3, "B1", 1200.0), make_player(4, "B2", 1200.0)];
+        let established = vec![make_player(1, "A1", 1200.0), make_player(2, "Old", 1500.0)];
+
+        // The newcomer's 1500 only counts half toward the team Elo
+        assert_eq!(rated_team_elo(&with_newcomer), 1300.0);
+        assert_eq!(rated_team_elo(&established), 1350.0);
+
+        let plain = calculate_elo_changes(
+            &regulars,
+            &team_b,
+            2,
+            1,
+            &HashMap::new(),
+            1.0,
+            &EloConfig::default(),
+        );
+        let changes = calculate_elo_changes(
+            &with_newcomer,
+            &team_b,
+            2,
+            1,
+            &HashMap::new(),
+            1.0,
+            &EloConfig::default(),
+        );
+
+        // Newcomer moves twice as fast as their teammate and is flagged
+        assert!((changes[&2].delta - changes[&1].delta * CALIBRATION_K_MULTIPLIER).abs() < 0.001);
+        assert!(changes[&2].calibrating);
+        assert!(!changes[&1].calibrating);
+        // Teammate gains less than with an equal partner, but not as little as with a proven 1500
+        assert!(changes[&1].delta < plain[&1].delta);
+        let against_proven = calculate_elo_changes(
+            &established,
+            &team_b,
+            2,
+            1,
+            &HashMap::new(),
+            1.0,
+            &EloConfig::default(),
+        );
+        assert!(changes[&1].delta > against_proven[&1].delta);
+    }
+
+    #[test]
+    fn test_k_multiplier_scales_changes() {
+        let team_a = vec![make_player(1, "A", 1200.0)];
+        let team_b = vec![make_player(2, "B", 1200.0)];
+
+        let normal = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            1,
+            0,
+            &HashMap::new(),
+            1.0,
+            &EloConfig::default(),
+        );
+        let final_ = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            1,
+            0,
+            &HashMap::new(),
+            2.0,
+            &EloConfig::default(),
+        );
+
+        assert_eq!(normal[&1].delta, 16.0);
+        assert_eq!(final_[&1].delta, 32.0);
+        assert_eq!(final_[&2].delta, -32.0);
+    }
+
+    #[test]
+    fn test_max_change_caps_upsets() {
+        let favorites = vec![make_player(1, "Favorite", 1600.0)];
+        let underdogs = vec![make_player(2, "Underdog", 1000.0)];
+        let config = EloConfig {
+            max_change: Some(25.0),
+            ..Default::default()
+        };
+
+        // 0-9 with the goal-difference multiplier would be far more than 25
+        let uncapped = calculate_elo_changes(
+            &favorites,
+            &underdogs,
+            0,
+            9,
+            &HashMap::new(),
+            1.0,
+            &EloConfig::default(),
+        );
+        assert!(uncapped[&2].delta > 25.0);
+        let changes =
+            calculate_elo_changes(&favorites, &underdogs, 0, 9, &HashMap::new(), 1.0, &config);
+        assert_eq!(changes[&1].delta, -25.0);
+        assert_eq!(changes[&2].delta, 25.0);
+
+        // Small changes are untouched
+        let even = vec![make_player(3, "Even", 1200.0)];
+        let other = vec![make_player(4, "Other", 1200.0)];
+        let changes = calculate_elo_changes(&even, &other, 1, 0, &HashMap::new(), 1.0, &config);
+        assert_eq!(changes[&3].delta, 16.0);
+    }
+
+    #[test]
+    fn test_anchor_rating_never_moves() {
+        let team_a = vec![
+            make_player(1, "Anchor", 1200.0),
+            make_player(2, "A2", 1200.0),
+            make_player(3, "A3", 1200.0),
+        ];
+        let team_b = vec![make_player(4, "B1", 1200.0), make_player(5, "B2", 1200.0)];
+        let participation = HashMap::from([(3, 0.5)]);
+        let config = EloConfig {
+            anchor: Some(1),
+            ..Default::default()
+        };
+
+        let plain = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            1,
+            0,
+            &participation,
+            1.0,
+            &EloConfig::default(),
+        );
+        let changes = calculate_elo_changes(&team_a, &team_b, 1, 0, &participation, 1.0, &config);
+        assert_eq!(changes[&1].delta, 0.0);
+        // The anchor's share goes to teammates by participation, so Team A's
+        // total is unchanged
+        let applied = |changes: &HashMap<i32, EloSnapshot>, ids: &[i32]| -> f32 {
+            ids.iter()
+                .map(|id| changes[id].delta * changes[id].participation)
+                .sum()
+        };
+        assert!((applied(&changes, &[2, 3]) - applied(&plain, &[1, 2, 3])).abs() < 0.001);
+        assert!(changes[&2].delta > plain[&2].delta);
+        assert_eq!(changes[&4].delta, plain[&4].delta);
+    }
+
+    #[test]
+    fn test_return_multiplier() {
+        let config = EloConfig {
+            absence_days: Some(90),
+            ..Default::default()
+        };
+        assert_eq!(return_multiplier(None, &config), 1.0);
+        assert_eq!(return_multiplier(Some(89), &config), 1.0);
+        assert_eq!(return_multiplier(Some(90), &config), 1.5);
+        assert_eq!(return_multiplier(Some(135), &config), 1.75);
+        assert_eq!(return_multiplier(Some(400), &config), 2.0);
+        // Off by default
+        assert_eq!(return_multiplier(Some(400), &EloConfig::default()), 1.0);
+    }
+
+    #[test]
+    fn test_returning_player_moves_faster() {
+        let mut returning = make_player(1, "Back", 1200.0);
+        returning.days_away = Some(180);
+        let team_a = vec![returning, make_player(2, "Regular", 1200.0)];
+        let team_b = vec![make_player(3, "B1", 1200.0), make_player(4, "B2", 1200.0)];
+        let config = EloConfig {
+            absence_days: Some(90),
+            ..Default::default()
+        };
+
+        let changes = calculate_elo_changes(&team_a, &team_b, 1, 0, &HashMap::new(), 1.0, &config);
+
+        assert_eq!(changes[&1].delta, 2.0 * changes[&2].delta);
+        assert!(changes[&1].returning);
+        assert!(!changes[&2].returning);
+    }
+
+    #[test]
+    fn test_handicap_curve() {
+        let linear = EloConfig::default();
+        assert_eq!(handicap(1.0, &linear), 100.0);
+        assert_eq!(handicap(2.0, &linear), 200.0);
+        assert_eq!(handicap(-0.5, &linear), -50.0);
+        assert_eq!(handicap(0.0, &linear), 0.0);
+
+        // Second missing player worth more than the first
+        let steep = EloConfig {
+            handicap_per_player: 80.0,
+            handicap_curve: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(handicap(1.0, &steep), 80.0);
+        assert!((handicap(2.0, &steep) - 226.27).abs() < 0.01);
+        assert!((handicap(-2.0, &steep) + 226.27).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_keeper_handicap() {
+        let mut keeper = make_player(1, "Keeper", 1200.0);
+        keeper.tags = "GK".to_string();
+        let team_a = vec![keeper, make_player(2, "A2", 1200.0)];
+        let team_b = vec![make_player(3, "B1", 1200.0), make_player(4, "B2", 1200.0)];
+        let config = EloConfig {
+            keeper_handicap: 50.0,
+            ..Default::default()
+        };
+
+        assert_eq!(keeper_handicap(&team_a, &team_b, &config), -50.0);
+        assert_eq!(keeper_handicap(&team_b, &team_a, &config), 50.0);
+        assert_eq!(keeper_handicap(&team_a, &team_a, &config), 0.0);
+        assert_eq!(keeper_handicap(&team_b, &team_b, &config), 0.0);
+        assert_eq!(
+            keeper_handicap(&team_a, &team_b, &EloConfig::default()),
+            0.0
+        );
+
+        // Drawing without a keeper is worth points; the snapshot records it per side
+        let changes = calculate_elo_changes(&team_a, &team_b, 1, 1, &HashMap::new(), 1.0, &config);
+        assert!(changes[&3].delta > 0.0);
+        assert!(changes[&1].delta < 0.0);
+        assert_eq!(changes[&3].keeper_handicap, 50.0);
+        assert_eq!(recorded_keeper_handicap(&[1], &changes), -50.0);
+        assert_eq!(recorded_keeper_handicap(&[9], &changes), 0.0);
+    }
+
+    #[test]
+    fn test_handicap_recorded_in_snapshot() {
+        let team_a = vec![make_player(1, "A", 1200.0)];
+        let team_b = vec![make_player(2, "B1", 1200.0), make_player(3, "B2", 1200.0)];
+        let config = EloConfig {
+            handicap_per_player: 150.0,
+            ..Default::default()
+        };
+
+        let changes = calculate_elo_changes(&team_a, &team_b, 1, 1, &HashMap::new(), 1.0, &config);
+
+        assert_eq!(changes[&1].handicap, 150.0);
+        assert_eq!(changes[&2].handicap, -150.0);
+        assert_eq!(recorded_handicap(&[1], &changes), 150.0);
+        assert_eq!(recorded_handicap(&[9], &changes), 0.0);
+    }
+
     #[test]
     fn test_elo_injury_partial_participation() {
         let team_a = vec![
@@ -220,7 +747,15 @@ mod tests {
         participation.insert(2, 0.5); // Player ID 2 (A2) played 50%
 
         // Team A wins
-        let changes = calculate_elo_changes(&team_a, &team_b, 2, 1, &participation);
+        let changes = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            2,
+            1,
+            &participation,
+            1.0,
+            &EloConfig::default(),
+        );
 
         // A2 (ID 2) should have 50% participation recorded
         assert_eq!(changes.get(&2).unwrap().participation, 0.5);
@@ -0,0 +1,248 @@
+use crate::settings::Settings;
+use crate::AppState;
+use axum::{
+    extract::State,
+    response::sse::{self, KeepAlive, Sse},
+};
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+
+/// Events held per subscriber; a slower client skips ahead (see [`stream`])
+const CHANNEL_CAPACITY: usize = 16;
+
+/// Postgres channel that carries changes between app instances
+const NOTIFY_CHANNEL: &str = "sfm_changes";
+
+/// Notification payload for settings saved or reset on the admin page
+const SETTINGS_CHANGED: &str = "settings";
+
+/// Notification payload for a change that may touch players or results
+const PLAYERS_CHANGED: &str = "players";
+
+/// How long to wait before listening again after losing the connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A change that open pages should show without a reload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Results or ratings changed: a match was recorded, voided, restored or
+    /// imported, or a season ended
+    Results,
+    /// A coin toss was made for a match day's teams
+    CoinToss,
+}
+
+impl Event {
+    /// SSE event name; sections with `data-live` set to it refresh when it arrives
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Results => "results",
+            Event::CoinToss => "toss",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Event::Results, Event::CoinToss]
+            .into_iter()
+            .find(|event| event.name() == name)
+    }
+}
+
+/// Event bus shared by all requests. `None` on the channel tells streams to
+/// end, so open pages don't hold up a graceful shutdown.
+///
+/// With a database attached, every change is also sent with Postgres NOTIFY
+/// so other instances of the app can pass it on to their own pages (see
+/// [`spawn_listener`]).
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Option<Event>>,
+    /// Tags this instance's notifications so it can skip its own
+    instance: u64,
+    db: Option<PgPool>,
+}
+
+impl EventBus {
+    /// A bus for this instance alone
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            sender,
+            instance: rand::random(),
+            db: None,
+        }
+    }
+
+    /// A bus that shares its events with every instance on the same database
+    pub fn with_db(db: PgPool) -> Self {
+        Self {
+            db: Some(db),
+            ..Self::new()
+        }
+    }
+
+    /// Send an event to every open page (nobody listening is fine)
+    pub fn publish(&self, event: Event) {
+        let _ = self.sender.send(Some(event));
+        self.notify(event.name());
+    }
+
+    /// Tell other instances to reload the settings saved on the admin page
+    pub fn settings_changed(&self) {
+        self.notify(SETTINGS_CHANGED);
+    }
+
+    /// Tell other instances to drop their cached players
+    pub fn players_changed(&self) {
+        self.notify(PLAYERS_CHANGED);
+    }
+
+    /// Send a change to other instances in the background
+    fn notify(&self, change: &str) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let payload = format!("{} {}", self.instance, change);
+        tokio::spawn(async move {
+            if let Err(e) = sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(NOTIFY_CHANNEL)
+                .bind(&payload)
+                .execute(&db)
+                .await
+            {
+                tracing::warn!("Failed to notify other instances: {}", e);
+            }
+        });
+    }
+
+    /// End every open event stream (on shutdown)
+    pub fn close(&self) {
+        let _ = self.sender.send(None);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Option<Event>> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split a notification into the instance that sent it and the change
+fn parse_payload(payload: &str) -> Option<(u64, &str)> {
+    let (instance, change) = payload.split_once(' ')?;
+    Some((instance.parse().ok()?, change))
+}
+
+/// Pass changes made on other instances on to this one: events go to open
+/// pages, saved settings are reloaded and cached players dropped.
+/// Notifications sent while the connection was down are lost, so after
+/// reconnecting all three happen anyway.
+pub fn spawn_listener(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut reconnecting = false;
+        loop {
+            if let Err(e) = listen(&state, reconnecting).await {
+                tracing::warn!("Lost the connection for notifications: {}", e);
+            }
+            reconnecting = true;
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+/// Listen until the connection fails
+async fn listen(state: &AppState, reconnecting: bool) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(&state.db).await?;
+    listener.listen(NOTIFY_CHANNEL).await?;
+    if reconnecting {
+        tracing::info!("Listening for changes from other instances again");
+        state.settings.reload(Settings::load(&state.db).await);
+        state.players.invalidate();
+        let _ = state.events.sender.send(Some(Event::Results));
+    }
+
+    loop {
+        let notification = listener.recv().await?;
+        let Some((instance, change)) = parse_payload(notification.payload()) else {
+            tracing::warn!("Ignoring notification {:?}", notification.payload());
+            continue;
+        };
+        if instance == state.events.instance {
+            continue;
+        }
+        if change == SETTINGS_CHANGED {
+            state.settings.reload(Settings::load(&state.db).await);
+        } else if change == PLAYERS_CHANGED {
+            state.players.invalidate();
+        } else if let Some(event) = Event::from_name(change) {
+            let _ = state.events.sender.send(Some(event));
+        }
+    }
+}
+
+/// Live change events for open pages (Server-Sent Events)
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "events",
+    responses((status = 200, description = "Event stream; event names: results, toss", content_type = "text/event-stream"))
+)]
+pub async fn stream(
+    State(state): State<Arc<AppState>>,
+) -> Sse<impl Stream<Item = Result<sse::Event, Infallible>>> {
+    let events = BroadcastStream::new(state.events.subscribe()).map_while(|event| {
+        let name = match event {
+            Ok(Some(event)) => event.name(),
+            Ok(None) => return None,
+            // A client that fell behind missed events; refreshing catches it up
+            Err(_) => Event::Results.name(),
+        };
+        Some(Ok(sse::Event::default().event(name).data("")))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_reaches_every_subscriber() {
+        let bus = EventBus::new();
+        // Publishing with nobody listening is not an error
+        bus.publish(Event::Results);
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        bus.publish(Event::Results);
+
+        assert_eq!(first.try_recv(), Ok(Some(Event::Results)));
+        assert_eq!(second.try_recv(), Ok(Some(Event::Results)));
+        assert!(first.try_recv().is_err());
+
+        bus.close();
+        assert_eq!(first.try_recv(), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_payload() {
+        assert_eq!(parse_payload("42 results"), Some((42, "results")));
+        assert_eq!(
+            parse_payload("7 settings").map(|(_, change)| change),
+            Some(SETTINGS_CHANGED)
+        );
+        assert_eq!(parse_payload("results"), None);
+        assert_eq!(parse_payload("x results"), None);
+        assert_eq!(Event::from_name("toss"), Some(Event::CoinToss));
+        assert_eq!(Event::from_name("nope"), None);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::models::Match;
+use crate::replay::chronological;
+use std::collections::HashMap;
+
+/// Recent form per player: the mean result over their last `games` counted
+/// matches (win = 1, draw = 0, loss = -1). Players without matches are absent.
+pub fn recent_form(matches: &[Match], games: usize) -> HashMap<i32, f32> {
+    let mut results: HashMap<i32, Vec<f32>> = HashMap::new();
+
+    // Newest first, so each player's first `games` entries are the most recent
+    for m in chronological(matches).into_iter().rev() {
+        if m.voided_at.is_some() {
+            continue;
+        }
+        let result_a = (m.score_a - m.score_b).signum() as f32;
+        let sides = [(&m.team_a, result_a), (&m.team_b, -result_a)];
+        for (team, result) in sides {
+            for id in team {
+                let player_results = results.entry(*id).or_default();
+                if player_results.len() < games {
+                    player_results.push(result);
+                }
+            }
+        }
+    }
+
+    results
+        .into_iter()
+        .filter(|(_, r)| !r.is_empty())
+        .map(|(id, r)| (id, r.iter().sum::<f32>() / r.len() as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    fn make_match(id: i32, day: u32, score: (i32, i32)) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            team_a: vec![1],
+            team_b: vec![2],
+            score_a: score.0,
+            score_b: score.1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_winning_streak() {
+        let matches: Vec<_> = (1..=3).map(|d| make_match(d as i32, d, (3, 1))).collect();
+        let form = recent_form(&matches, 5);
+        assert_eq!(form[&1], 1.0);
+        assert_eq!(form[&2], -1.0);
+    }
+
+    #[test]
+    fn test_only_last_n_games_count() {
+        // Lost three long ago, then won the two most recent
+        let matches = vec![
+            make_match(1, 1, (0, 1)),
+            make_match(2, 2, (0, 1)),
+            make_match(3, 3, (0, 1)),
+            make_match(4, 4, (2, 1)),
+            make_match(5, 5, (2, 1)),
+        ];
+        assert_eq!(recent_form(&matches, 2)[&1], 1.0);
+        assert_eq!(recent_form(&matches, 5)[&1], -0.2);
+    }
+
+    #[test]
+    fn test_draws_and_voided_matches() {
+        let mut voided = make_match(2, 2, (5, 0));
+        voided.voided_at = Some(Utc::now());
+        let matches = vec![make_match(1, 1, (1, 1)), voided];
+
+        let form = recent_form(&matches, 5);
+        assert_eq!(form[&1], 0.0);
+        assert_eq!(form[&2], 0.0);
+    }
+}
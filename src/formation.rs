@@ -0,0 +1,226 @@
+use crate::models::Player;
+use crate::positions::{roles_for_player, roles_for_tags, Role};
+use std::fmt;
+
+/// A suggested shape and starting lineup for one team
+#[derive(Debug, Clone)]
+pub struct Formation<'a> {
+    /// Who starts in goal: a goalkeeper, else the first in the goal rotation
+    pub keeper: Option<&'a Player>,
+    /// Whether the keeper is a dedicated goalkeeper
+    pub dedicated_keeper: bool,
+    /// Outfield players per line, in [`Role::ALL`] order (back to front)
+    pub lines: Vec<(Role, Vec<&'a Player>)>,
+}
+
+impl Formation<'_> {
+    /// Players per line, back to front
+    pub fn shape(&self) -> Vec<usize> {
+        self.lines
+            .iter()
+            .map(|(_, players)| players.len())
+            .collect()
+    }
+}
+
+impl fmt::Display for Formation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shape: Vec<String> = self.shape().iter().map(|n| n.to_string()).collect();
+        write!(f, "{}", shape.join("-"))
+    }
+}
+
+/// Default shape for `outfield` players: one attacker per four outfielders
+/// (at least one), then midfield gets the larger half of the rest.
+/// 6 outfield (7-a-side) gives 2-3-1.
+pub fn default_shape(outfield: usize) -> [usize; 3] {
+    if outfield == 0 {
+        return [0, 0, 0];
+    }
+    let attack = (outfield / 4).max(1);
+    let midfield = (outfield - attack).div_ceil(2);
+    [outfield - attack - midfield, midfield, attack]
+}
+
+/// Suggest a formation for a team from its players' positions (or tags).
+///
+/// The keeper is the highest-rated goalkeeper; with none, the lowest-rated
+/// player (first in the goal rotation) starts in goal. Outfield players with
+/// one role go to that line. Players with several tagged roles, and then
+/// players with none, fill whichever of their lines is furthest below the
+/// default shape. Lines are never left empty while another has players to
+/// spare. Each line is listed best first.
+pub fn suggest_formation(team: &[Player]) -> Formation<'_> {
+    let mut players: Vec<&Player> = team.iter().collect();
+    players.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+
+    let dedicated_keeper = players.iter().any(|p| p.is_goalkeeper());
+    let keeper = if dedicated_keeper {
+        players.iter().position(|p| p.is_goalkeeper())
+    } else {
+        players.len().checked_sub(1)
+    }
+    .map(|i| players.remove(i));
+
+    let target = default_shape(players.len());
+    let mut lines: [Vec<&Player>; 3] = Default::default();
+    let index = |role: Role| Role::ALL.iter().position(|r| *r == role).unwrap_or(1);
+
+    // Most certain players first: one role, then several, then none
+    let mut flexible: Vec<(&Player, Vec<Role>)> = Vec::new();
+    for player in players {
+        let roles = roles_for_player(player);
+        match roles.as_slice() {
+            [role] => lines[index(*role)].push(player),
+            _ => flexible.push((player, roles)),
+        }
+    }
+    flexible.sort_by_key(|(_, roles)| roles.is_empty());
+
+    for (player, roles) in flexible {
+        let options: Vec<usize> = if roles.is_empty() {
+            // Midfield first so ties go to the middle of the pitch
+            vec![1, 0, 2]
+        } else {
+            roles.into_iter().map(index).collect()
+        };
+        let deficit = |i: usize| target[i] as isize - lines[i].len() as isize;
+        let best = options
+            .iter()
+            .copied()
+            .reduce(|best, i| if deficit(i) > deficit(best) { i } else { best })
+            .unwrap_or(1);
+        lines[best].push(player);
+    }
+
+    // Don't leave a line empty while another has two or more
+    for empty in 0..lines.len() {
+        if !lines[empty].is_empty() {
+            continue;
+        }
+        let Some(fullest) = (0..lines.len())
+            .filter(|&i| lines[i].len() >= 2)
+            .max_by_key(|&i| lines[i].len())
+        else {
+            continue;
+        };
+        // Prefer someone tagged for the empty line, else the lowest rated
+        let role = Role::ALL[empty];
+        let moving = lines[fullest]
+            .iter()
+            .rposition(|p| roles_for_tags(&p.tags).contains(&role))
+            .unwrap_or(lines[fullest].len() - 1);
+        let player = lines[fullest].remove(moving);
+        lines[empty].push(player);
+    }
+
+    for line in &mut lines {
+        line.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+    }
+
+    Formation {
+        keeper,
+        dedicated_keeper,
+        lines: Role::ALL.into_iter().zip(lines).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_player(id: i32, elo: f32, position: Option<&str>, tags: &str) -> Player {
+        Player {
+            id,
+            name: format!("P{}", id),
+            elo,
+            tags: tags.to_string(),
+            position: position.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn ids(formation: &Formation, line: usize) -> Vec<i32> {
+        formation.lines[line].1.iter().map(|p| p.id).collect()
+    }
+
+    #[test]
+    fn test_default_shape() {
+        assert_eq!(default_shape(6), [2, 3, 1]);
+        assert_eq!(default_shape(5), [2, 2, 1]);
+        assert_eq!(default_shape(8), [3, 3, 2]);
+        assert_eq!(default_shape(10), [4, 4, 2]);
+        assert_eq!(default_shape(1), [0, 0, 1]);
+        assert_eq!(default_shape(0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_unknown_players_fill_default_shape() {
+        let team: Vec<Player> = (1..=7)
+            .map(|id| make_player(id, 1200.0 + id as f32, None, ""))
+            .collect();
+
+        let formation = suggest_formation(&team);
+
+        assert_eq!(formation.to_string(), "2-3-1");
+        // Nobody is a GK, so the lowest rated starts in goal
+        assert_eq!(formation.keeper.map(|p| p.id), Some(1));
+        assert!(!formation.dedicated_keeper);
+    }
+
+    #[test]
+    fn test_positions_pick_lines() {
+        let team = vec![
+            make_player(1, 1200.0, Some("GK"), ""),
+            make_player(2, 1300.0, Some("DEF"), ""),
+            make_player(3, 1250.0, Some("DEF"), ""),
+            make_player(4, 1200.0, Some("DEF"), ""),
+            make_player(5, 1400.0, Some("ATT"), ""),
+            make_player(6, 1350.0, Some("ATT"), ""),
+            make_player(7, 1100.0, None, "PLAYMAKER"),
+        ];
+
+        let formation = suggest_formation(&team);
+
+        assert_eq!(formation.keeper.map(|p| p.id), Some(1));
+        assert!(formation.dedicated_keeper);
+        assert_eq!(formation.to_string(), "3-1-2");
+        assert_eq!(ids(&formation, 0), vec![2, 3, 4]);
+        assert_eq!(ids(&formation, 2), vec![5, 6]);
+    }
+
+    #[test]
+    fn test_multi_role_players_fill_gaps() {
+        let team = vec![
+            make_player(1, 1200.0, Some("GK"), ""),
+            make_player(2, 1200.0, Some("MID"), ""),
+            make_player(3, 1200.0, Some("MID"), ""),
+            make_player(4, 1200.0, Some("MID"), ""),
+            make_player(5, 1200.0, None, "DEF,PLAYMAKER"),
+            make_player(6, 1200.0, None, "ATK,RUNNER"),
+            make_player(7, 1200.0, None, "DEF"),
+        ];
+
+        let formation = suggest_formation(&team);
+
+        assert_eq!(formation.to_string(), "2-3-1");
+        assert_eq!(ids(&formation, 2), vec![6]);
+    }
+
+    #[test]
+    fn test_no_empty_line_when_players_to_spare() {
+        let team = vec![
+            make_player(1, 1200.0, Some("GK"), ""),
+            make_player(2, 1300.0, Some("DEF"), ""),
+            make_player(3, 1250.0, Some("DEF"), "ATK"),
+            make_player(4, 1200.0, Some("DEF"), ""),
+            make_player(5, 1200.0, Some("MID"), ""),
+        ];
+
+        let formation = suggest_formation(&team);
+
+        assert_eq!(formation.shape(), vec![2, 1, 1]);
+        // The defender tagged ATK moves up, not the lowest rated
+        assert_eq!(ids(&formation, 2), vec![3]);
+    }
+}
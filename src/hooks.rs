@@ -0,0 +1,245 @@
+use crate::models::{EloSnapshot, MAX_SCORE};
+use crate::views::pending::{hold_result, SUBMITTED_BY_HOOK};
+use crate::views::record::{save_result, NewResult};
+use crate::{db, AppState};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Token for inbound hooks from HOOK_TOKEN (unset = hooks are off)
+pub fn token_from_env() -> Option<String> {
+    match std::env::var("HOOK_TOKEN") {
+        Ok(token) if token.len() >= 16 => Some(token),
+        Ok(_) => panic!("HOOK_TOKEN must be at least 16 characters"),
+        Err(_) => None,
+    }
+}
+
+/// Compare tokens without bailing out at the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Whether the request carries `Authorization: Bearer <HOOK_TOKEN>`
+fn is_authorized(headers: &HeaderMap, expected: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| tokens_match(token.trim(), expected))
+}
+
+/// A result posted by a bot
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct HookRecordRequest {
+    /// Player IDs on Team A
+    pub team_a: Vec<i32>,
+    /// Player IDs on Team B
+    pub team_b: Vec<i32>,
+    pub score_a: i32,
+    pub score_b: i32,
+    /// Optional match notes
+    #[serde(default)]
+    pub notes: String,
+    /// Optional Team A captain (a player ID from `team_a`)
+    #[serde(default)]
+    pub captain_a: Option<i32>,
+    /// Optional Team B captain (a player ID from `team_b`)
+    #[serde(default)]
+    pub captain_b: Option<i32>,
+}
+
+/// The recorded match and each player's Elo change
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct HookRecordResponse {
+    pub match_id: i32,
+    pub played_at: chrono::NaiveDate,
+    /// Elo change by player ID
+    pub elo_changes: HashMap<i32, f32>,
+}
+
+/// A result held until someone confirms it (result confirmation is on)
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct HookPendingResponse {
+    pub pending_id: i32,
+}
+
+/// Check a posted result's teams and score before loading anything
+fn validate(request: &HookRecordRequest) -> Result<(), &'static str> {
+    if request.team_a.is_empty() || request.team_b.is_empty() {
+        return Err("Both teams need players");
+    }
+    let ids_a: HashSet<i32> = request.team_a.iter().copied().collect();
+    let ids_b: HashSet<i32> = request.team_b.iter().copied().collect();
+    if ids_a.len() != request.team_a.len()
+        || ids_b.len() != request.team_b.len()
+        || !ids_a.is_disjoint(&ids_b)
+    {
+        return Err("A player can only be listed once");
+    }
+    if !(0..=MAX_SCORE).contains(&request.score_a) || !(0..=MAX_SCORE).contains(&request.score_b) {
+        return Err("Scores must be between 0 and 50");
+    }
+    if request.captain_a.is_some_and(|id| !ids_a.contains(&id))
+        || request.captain_b.is_some_and(|id| !ids_b.contains(&id))
+    {
+        return Err("A captain must play for the team they captain");
+    }
+    Ok(())
+}
+
+/// Record a match result from a bot or script. Needs `Authorization: Bearer
+/// <HOOK_TOKEN>`; uneven teams are recorded without asking. With result
+/// confirmation on, the result waits on History for a player to confirm it.
+#[utoipa::path(
+    post,
+    path = "/api/hooks/record",
+    tag = "matches",
+    request_body = HookRecordRequest,
+    responses(
+        (status = 200, description = "Result recorded", body = HookRecordResponse),
+        (status = 202, description = "Result held for confirmation", body = HookPendingResponse),
+        (status = 401, description = "Missing or wrong token", content_type = "text/plain"),
+        (status = 404, description = "Inbound hooks are off (no HOOK_TOKEN set)", content_type = "text/plain"),
+        (status = 422, description = "Empty, overlapping or unknown teams, or a score out of range", content_type = "text/plain"),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn record(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<HookRecordRequest>,
+) -> Response {
+    let Some(expected) = state.hook_token.as_deref() else {
+        return (StatusCode::NOT_FOUND, "Inbound hooks are off").into_response();
+    };
+    if !is_authorized(&headers, expected) {
+        return (StatusCode::UNAUTHORIZED, "Missing or wrong hook token").into_response();
+    }
+    if let Err(e) = validate(&request) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, e).into_response();
+    }
+
+    let (Ok(team_a), Ok(team_b)) = (
+        db::get_players_by_ids(&state.db, &request.team_a).await,
+        db::get_players_by_ids(&state.db, &request.team_b).await,
+    ) else {
+        tracing::error!("Failed to load players for a hooked result");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if team_a.len() != request.team_a.len() || team_b.len() != request.team_b.len() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, "Unknown player ID").into_response();
+    }
+
+    let settings = state.settings.get();
+    let result = NewResult {
+        played_at: settings.today(),
+        score_a: request.score_a,
+        score_b: request.score_b,
+        participation: HashMap::new(),
+        k_multiplier: 1.0,
+        notes: crate::models::clean_note(&request.notes),
+        captain_a: request.captain_a,
+        captain_b: request.captain_b,
+        goals: Vec::new(),
+        confirms: None,
+    };
+    if settings.confirm_results {
+        return match hold_result(&state, &team_a, &team_b, &result, SUBMITTED_BY_HOOK).await {
+            Ok(pending_id) => (
+                StatusCode::ACCEPTED,
+                Json(HookPendingResponse { pending_id }),
+            )
+                .into_response(),
+            Err(e) => {
+                tracing::error!("Failed to save hooked pending result: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        };
+    }
+    match save_result(&state, &team_a, &team_b, result).await {
+        Ok((saved_match, elo_changes, _)) => Json(HookRecordResponse {
+            match_id: saved_match.id,
+            played_at: saved_match.played_at,
+            elo_changes: effective_changes(&elo_changes),
+        })
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to save hooked result: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Each player's applied Elo change (after partial participation)
+fn effective_changes(elo_changes: &HashMap<i32, EloSnapshot>) -> HashMap<i32, f32> {
+    elo_changes
+        .iter()
+        .map(|(id, change)| (*id, change.delta * change.participation))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(team_a: Vec<i32>, team_b: Vec<i32>, score_a: i32) -> HookRecordRequest {
+        HookRecordRequest {
+            team_a,
+            team_b,
+            score_a,
+            score_b: 2,
+            notes: String::new(),
+            captain_a: None,
+            captain_b: None,
+        }
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("0123456789abcdef", "0123456789abcdef"));
+        assert!(!tokens_match("0123456789abcdeF", "0123456789abcdef"));
+        assert!(!tokens_match("0123456789abcde", "0123456789abcdef"));
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_authorized(&headers, "secret-token-1234"));
+        headers.insert(
+            header::AUTHORIZATION,
+            "Bearer secret-token-1234".parse().unwrap(),
+        );
+        assert!(is_authorized(&headers, "secret-token-1234"));
+        headers.insert(header::AUTHORIZATION, "secret-token-1234".parse().unwrap());
+        assert!(!is_authorized(&headers, "secret-token-1234"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&request(vec![1, 2], vec![3, 4], 3)).is_ok());
+        assert!(validate(&request(vec![], vec![3, 4], 3)).is_err());
+        assert!(validate(&request(vec![1, 2], vec![2, 4], 3)).is_err());
+        assert!(validate(&request(vec![1, 1], vec![3, 4], 3)).is_err());
+        assert!(validate(&request(vec![1, 2], vec![3, 4], -1)).is_err());
+        assert!(validate(&request(vec![1, 2], vec![3, 4], 51)).is_err());
+
+        let mut captained = request(vec![1, 2], vec![3, 4], 3);
+        captained.captain_a = Some(2);
+        captained.captain_b = Some(3);
+        assert!(validate(&captained).is_ok());
+        captained.captain_a = Some(3);
+        assert!(validate(&captained).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::db;
+use crate::models::Injury;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// The injury currently keeping each player out, keyed by player ID. Only
+/// uncleared injuries count; if a player has several, the latest one wins.
+pub fn active_by_player(injuries: Vec<Injury>) -> HashMap<i32, Injury> {
+    let mut active: HashMap<i32, Injury> = HashMap::new();
+    for injury in injuries.into_iter().filter(|i| i.cleared_at.is_none()) {
+        let later = active.get(&injury.player_id).is_none_or(|current| {
+            (injury.started_on, injury.id) > (current.started_on, current.id)
+        });
+        if later {
+            active.insert(injury.player_id, injury);
+        }
+    }
+    active
+}
+
+/// Load injuries that haven't been cleared, keyed by player ID. Unlike
+/// suspensions they don't end on their own: the expected return is a guide.
+pub async fn load_active(pool: &PgPool) -> HashMap<i32, Injury> {
+    let injuries = db::get_uncleared_injuries(pool).await.unwrap_or_default();
+    active_by_player(injuries)
+}
+
+/// Short status shown on the roster, at check-in and on /me
+pub fn status_label(injury: &Injury, today: NaiveDate) -> String {
+    match injury.expected_return {
+        Some(date) if date > today => format!("Injured, back ~{}", date.format("%Y-%m-%d")),
+        Some(_) => "Injured, due back".to_string(),
+        None => "Injured".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn make_injury(id: i32, player_id: i32, started: u32, expected: Option<u32>) -> Injury {
+        Injury {
+            id,
+            player_id,
+            note: String::new(),
+            started_on: date(started),
+            expected_return: expected.map(date),
+            cleared_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+    }
+
+    #[test]
+    fn test_cleared_injuries_are_inactive() {
+        let mut cleared = make_injury(2, 2, 1, None);
+        cleared.cleared_at = Some(Utc::now());
+        // Past the expected return but not cleared: still out
+        let injuries = vec![make_injury(1, 1, 1, Some(5)), cleared];
+
+        let active = active_by_player(injuries);
+
+        assert_eq!(active.len(), 1);
+        assert!(active.contains_key(&1));
+    }
+
+    #[test]
+    fn test_latest_injury_wins() {
+        let injuries = vec![
+            make_injury(1, 1, 10, Some(20)),
+            make_injury(2, 1, 3, None),
+            make_injury(3, 1, 10, Some(25)),
+        ];
+        assert_eq!(active_by_player(injuries)[&1].id, 3);
+    }
+
+    #[test]
+    fn test_status_label() {
+        let injury = make_injury(1, 1, 1, Some(10));
+        assert_eq!(status_label(&injury, date(9)), "Injured, back ~2024-06-10");
+        assert_eq!(status_label(&injury, date(10)), "Injured, due back");
+        assert_eq!(
+            status_label(&make_injury(1, 1, 1, None), date(9)),
+            "Injured"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::models::QueuedJob;
+use crate::{db, webhooks};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Wait between polls while the queue is empty
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a claimed job is held before another worker may take it
+const LEASE: Duration = Duration::from_secs(60);
+/// Wait before the first retry; doubled for each one after
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Work for the background queue. Stored as JSON, so keep variants and
+/// fields backwards compatible: queued jobs outlive a deploy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Job {
+    /// POST a signed event body to one webhook
+    DeliverWebhook {
+        webhook_id: i32,
+        event: String,
+        body: String,
+    },
+}
+
+impl Job {
+    /// Name in the `kind` column (the same as the payload's tag)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Job::DeliverWebhook { .. } => "deliver_webhook",
+        }
+    }
+
+    /// What the job does, for the Admin page
+    pub fn describe(&self) -> String {
+        match self {
+            Job::DeliverWebhook {
+                webhook_id, event, ..
+            } => format!("{} to webhook #{}", event, webhook_id),
+        }
+    }
+
+    /// Runs before the job is marked failed
+    fn max_attempts(&self) -> i32 {
+        match self {
+            Job::DeliverWebhook { .. } => webhooks::MAX_ATTEMPTS,
+        }
+    }
+}
+
+/// Queue a job; the worker picks it up within a second or so
+pub async fn enqueue(pool: &PgPool, job: &Job) -> Result<(), sqlx::Error> {
+    let payload = serde_json::to_value(job).expect("Jobs serialize to JSON");
+    db::enqueue_job(pool, job.kind(), &payload, job.max_attempts()).await
+}
+
+/// Wait before retry number `retry` (1-based): 2s, 4s, 8s, ...
+fn retry_delay(retry: u32) -> Duration {
+    FIRST_RETRY_DELAY * 2u32.pow(retry.saturating_sub(1))
+}
+
+/// Run queued jobs one at a time in the background
+pub fn spawn(pool: PgPool) {
+    tokio::spawn(async move {
+        let client = webhooks::http_client();
+        loop {
+            match db::claim_job(&pool, LEASE.as_secs_f64()).await {
+                Ok(Some(job)) => run(&pool, &client, job).await,
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => {
+                    tracing::error!("Failed to claim a job: {}", e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    });
+}
+
+/// Run one claimed job, then delete it, schedule a retry or mark it failed
+#[tracing::instrument(skip_all, fields(job.id = queued.id, job.kind = %queued.kind))]
+async fn run(pool: &PgPool, client: &reqwest::Client, queued: QueuedJob) {
+    let result = match serde_json::from_value::<Job>(queued.payload) {
+        Ok(Job::DeliverWebhook {
+            webhook_id,
+            event,
+            body,
+        }) => webhooks::deliver(pool, client, webhook_id, &event, &body).await,
+        Err(e) => Err(format!("Unreadable job: {}", e)),
+    };
+
+    let saved = match result {
+        Ok(()) => db::delete_job(pool, queued.id).await,
+        Err(error) if queued.attempts >= queued.max_attempts => {
+            tracing::error!(
+                "Job {} ({}) failed after {} attempts: {}",
+                queued.id,
+                queued.kind,
+                queued.attempts,
+                error
+            );
+            db::fail_job(pool, queued.id, &error).await
+        }
+        Err(error) => {
+            tracing::warn!(
+                "Job {} ({}) attempt {}/{} failed: {}",
+                queued.id,
+                queued.kind,
+                queued.attempts,
+                queued.max_attempts,
+                error
+            );
+            let delay = retry_delay(queued.attempts as u32);
+            db::retry_job_later(pool, queued.id, &error, delay.as_secs_f64()).await
+        }
+    };
+    if let Err(e) = saved {
+        tracing::error!("Failed to update job {}: {}", queued.id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_payload_roundtrip() {
+        let job = Job::DeliverWebhook {
+            webhook_id: 3,
+            event: "result.recorded".to_string(),
+            body: "{}".to_string(),
+        };
+        let payload = serde_json::to_value(&job).unwrap();
+        assert_eq!(payload["kind"], job.kind());
+        assert_eq!(serde_json::from_value::<Job>(payload).unwrap(), job);
+        assert_eq!(job.describe(), "result.recorded to webhook #3");
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(2), Duration::from_secs(4));
+        assert_eq!(retry_delay(4), Duration::from_secs(16));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::models::{Player, MAX_JERSEY_NUMBER};
+use std::collections::HashSet;
+
+/// A player and the shirt number they wear on the day
+#[derive(Debug, Clone)]
+pub struct KitEntry<'a> {
+    pub number: i32,
+    pub player: &'a Player,
+}
+
+/// Parse a jersey number form field (empty = no preference)
+pub fn parse_jersey_number(value: Option<&str>) -> Result<Option<i32>, String> {
+    let value = value.unwrap_or_default().trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<i32>() {
+        Ok(n) if (0..=MAX_JERSEY_NUMBER).contains(&n) => Ok(Some(n)),
+        _ => Err(format!(
+            "Jersey number must be between 0 and {}",
+            MAX_JERSEY_NUMBER
+        )),
+    }
+}
+
+/// Assign shirt numbers within one team.
+///
+/// Players keep their preferred number unless a teammate already claimed it
+/// (regulars, by matches played, get first pick). Everyone else gets the
+/// lowest free number from 1. Returned in shirt-number order.
+pub fn assign_numbers(team: &[Player]) -> Vec<KitEntry<'_>> {
+    let mut by_seniority: Vec<&Player> = team.iter().collect();
+    by_seniority.sort_by(|a, b| {
+        b.matches_played
+            .cmp(&a.matches_played)
+            .then(a.id.cmp(&b.id))
+    });
+
+    let mut taken = HashSet::new();
+    let mut entries = Vec::with_capacity(team.len());
+    let mut unassigned = Vec::new();
+
+    for player in by_seniority {
+        match player.jersey_number {
+            Some(n) if taken.insert(n) => entries.push(KitEntry { number: n, player }),
+            _ => unassigned.push(player),
+        }
+    }
+
+    let mut next = 1;
+    for player in unassigned {
+        while taken.contains(&next) {
+            next += 1;
+        }
+        taken.insert(next);
+        entries.push(KitEntry {
+            number: next,
+            player,
+        });
+    }
+
+    entries.sort_by_key(|e| e.number);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_player(id: i32, jersey_number: Option<i32>, matches_played: i32) -> Player {
+        Player {
+            id,
+            name: format!("P{}", id),
+            jersey_number,
+            matches_played,
+            ..Default::default()
+        }
+    }
+
+    fn numbers(entries: &[KitEntry]) -> Vec<(i32, i32)> {
+        entries.iter().map(|e| (e.player.id, e.number)).collect()
+    }
+
+    #[test]
+    fn test_preferred_numbers_kept() {
+        let team = vec![make_player(1, Some(10), 0), make_player(2, Some(7), 0)];
+        assert_eq!(numbers(&assign_numbers(&team)), vec![(2, 7), (1, 10)]);
+    }
+
+    #[test]
+    fn test_clash_goes_to_regular() {
+        let team = vec![make_player(1, Some(10), 2), make_player(2, Some(10), 20)];
+        assert_eq!(numbers(&assign_numbers(&team)), vec![(1, 1), (2, 10)]);
+    }
+
+    #[test]
+    fn test_no_preference_gets_lowest_free() {
+        let team = vec![
+            make_player(1, Some(1), 0),
+            make_player(2, None, 0),
+            make_player(3, None, 0),
+        ];
+        assert_eq!(
+            numbers(&assign_numbers(&team)),
+            vec![(1, 1), (2, 2), (3, 3)]
+        );
+    }
+
+    #[test]
+    fn test_parse_jersey_number() {
+        assert_eq!(parse_jersey_number(Some("10")), Ok(Some(10)));
+        assert_eq!(parse_jersey_number(Some(" ")), Ok(None));
+        assert_eq!(parse_jersey_number(None), Ok(None));
+        assert!(parse_jersey_number(Some("100")).is_err());
+        assert!(parse_jersey_number(Some("ten")).is_err());
+    }
+}
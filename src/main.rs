@@ -1,52 +1,179 @@
+mod analytics;
+mod appearances;
+mod attendance;
 mod auth;
+mod auto_teams;
 mod balance;
+mod caching;
+mod captains;
+mod cli;
 mod db;
+mod demo;
+mod discipline;
+mod edit_window;
 mod elo;
+mod events;
+mod form;
+mod formation;
+mod hooks;
+mod injuries;
+mod jobs;
+mod kit;
+mod milestones;
 mod models;
+mod mvp;
+mod onboarding;
+mod openapi;
+mod peer_ratings;
+mod player_cache;
+mod pool;
+mod positions;
+mod predict;
+mod privacy;
+mod rate_limit;
+mod recalibration;
+mod records;
+mod replay;
+mod rsvp;
+mod seasons;
+mod security;
+mod settings;
+mod share;
+mod similarity;
+mod stats;
+mod tag_suggestions;
+mod team_names;
+mod team_of_season;
+mod telemetry;
+mod tiers;
+mod tournament;
+mod uncertainty;
 mod views;
+mod webhooks;
+mod what_if;
 
 use axum::{
+    http::{header, HeaderValue},
+    middleware,
     routing::{delete, get, post, put},
     Router,
 };
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use rate_limit::{PinLockout, RateLimiter};
 use sqlx::PgPool;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tower_http::trace::TraceLayer;
+use tower_http::{
+    compression::CompressionLayer, services::ServeDir, set_header::SetResponseHeader,
+    trace::TraceLayer,
+};
 
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
-    pub auth_password: Option<String>,
+    pub auth: Option<auth::AdminAuth>,
     pub secure_cookies: bool,
+    pub rate_limiter: RateLimiter,
+    /// Wrong player PINs, locking a player's login after too many
+    pub pin_lockout: PinLockout,
+    pub cookie_key: auth::CookieKey,
+    /// Group settings; the admin page replaces them while running
+    pub settings: settings::SharedSettings,
+    /// When this process started; part of page ETags so deploys invalidate them
+    pub started_at: DateTime<Utc>,
+    /// Change events pushed to open pages over SSE
+    pub events: events::EventBus,
+    /// Players for pages, cleared by every mutation
+    pub players: player_cache::PlayerCache,
+    /// Outgoing webhook deliveries
+    pub webhooks: webhooks::Dispatcher,
+    /// Bearer token for inbound hooks like `/api/hooks/record` (None = off)
+    pub hook_token: Option<String>,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State for handler tests: an admin password is set (so a request
+    /// without cookies is anonymous) and the database is never reached
+    /// unless a handler gets past its checks
+    pub fn for_tests() -> Self {
+        let db = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/sfm_tests")
+            .expect("Failed to build the test pool");
+        AppState {
+            db: db.clone(),
+            auth: Some(auth::AdminAuth::with_password("hunter2")),
+            secure_cookies: false,
+            rate_limiter: RateLimiter::new(30, std::time::Duration::from_secs(60)),
+            pin_lockout: PinLockout::default(),
+            cookie_key: auth::CookieKey::default(),
+            settings: settings::SharedSettings::new(settings::Settings::default()),
+            started_at: Utc::now(),
+            events: events::EventBus::new(),
+            players: player_cache::PlayerCache::default(),
+            webhooks: webhooks::Dispatcher::new(db),
+            hook_token: None,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
+    let cli = cli::Cli::parse();
+
     dotenvy::from_filename(".env.local").ok();
     dotenvy::dotenv().ok();
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-        )
-        .init();
+    let command = cli.command.unwrap_or(cli::Command::Serve);
+    let tracer_provider = telemetry::init(!matches!(command, cli::Command::Serve));
+    let pool = open_database().await;
+
+    let result = match command {
+        cli::Command::Serve => {
+            serve(pool).await;
+            Ok(())
+        }
+        command => cli::run(command, &pool).await,
+    };
 
+    telemetry::shutdown(tracer_provider);
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Connect to the database (retrying while it comes up) and apply migrations
+async fn open_database() -> PgPool {
     let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
 
-    let pool = PgPool::connect(&database_url)
-        .await
-        .expect("Failed to connect to database");
+    let pool_config = pool::PoolConfig::from_env();
+    let pool = match pool::connect(&database_url, &pool_config).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            tracing::error!(
+                "Could not connect to the database after {} attempts: {}",
+                pool_config.connect_attempts,
+                e
+            );
+            std::process::exit(1);
+        }
+    };
 
     // Run migrations
     sqlx::migrate!("./migrations")
         .run(&pool)
         .await
         .expect("Failed to run migrations");
+    pool
+}
 
-    let auth_password = std::env::var("AUTH_PASSWORD").ok();
-    if auth_password.is_some() {
+/// Run the web server until Ctrl+C or SIGTERM
+async fn serve(pool: PgPool) {
+    let auth = auth::AdminAuth::from_env();
+    if auth.is_some() {
         tracing::info!("Auth password configured - login required for mutations");
     } else {
         tracing::warn!("No AUTH_PASSWORD set - site is unprotected");
@@ -60,33 +187,275 @@ async fn main() {
         tracing::warn!("SECURE_COOKIES=false - cookies will be sent over HTTP (dev only!)");
     }
 
+    // Environment settings, overridden by anything saved on the admin page
+    let settings = settings::Settings::load(&pool).await.unwrap_or_else(|e| {
+        tracing::error!(
+            "Failed to load saved settings, using the environment: {}",
+            e
+        );
+        settings::Settings::from_env()
+    });
+
+    // Pages are public by default; private pages require login to view anything
+    if settings.private_pages {
+        if auth.is_some() {
+            tracing::info!("Private pages on - login required to view pages");
+        } else {
+            tracing::warn!("Private pages have no effect without AUTH_PASSWORD");
+        }
+    }
+
+    let rate_limiter = RateLimiter::from_env();
+    if !rate_limiter.enabled() {
+        tracing::warn!("RATE_LIMIT_REQUESTS=0 - mutation endpoints are not rate limited");
+    }
+
+    let cookie_key = auth::CookieKey::from_env();
+
+    let dispatcher = webhooks::Dispatcher::new(pool.clone());
+    let hook_token = hooks::token_from_env();
+    if hook_token.is_some() {
+        tracing::info!("HOOK_TOKEN set - /api/hooks/record accepts results");
+    }
+
     let state = Arc::new(AppState {
-        db: pool,
-        auth_password,
+        db: pool.clone(),
+        auth,
         secure_cookies,
+        rate_limiter,
+        pin_lockout: PinLockout::default(),
+        cookie_key,
+        settings: settings::SharedSettings::new(settings),
+        started_at: Utc::now(),
+        events: events::EventBus::with_db(pool.clone()),
+        players: player_cache::PlayerCache::default(),
+        webhooks: dispatcher,
+        hook_token,
     });
 
-    let router = Router::new()
+    // Background job worker (webhook deliveries)
+    jobs::spawn(state.db.clone());
+    // Changes made on other instances (when running more than one)
+    events::spawn_listener(state.clone());
+    // Pick teams from RSVPs once they close (when RSVP_DEADLINE is set)
+    auto_teams::spawn(state.clone());
+
+    // Mutations - rate limited per client IP
+    let mutations = Router::new()
+        .route("/api/players", post(views::roster::create_player))
+        .route(
+            "/api/players/bulk",
+            post(views::roster::create_players_bulk),
+        )
+        .route("/api/players/{id}", put(views::roster::update_player))
+        .route("/api/players/{id}", delete(views::roster::delete_player))
+        .route("/api/suspensions", post(views::roster::suspend_player))
+        .route("/api/injuries", post(views::roster::record_injury))
+        .route(
+            "/api/players/{id}/anonymize",
+            post(privacy::anonymize_player),
+        )
+        .route(
+            "/api/players/{id}/injury/clear",
+            post(views::roster::clear_injury),
+        )
+        .route(
+            "/api/players/{id}/tag-suggestions/accept",
+            post(views::tag_suggestions::accept_tag_suggestion),
+        )
+        .route(
+            "/api/players/{id}/tag-suggestions/dismiss",
+            post(views::tag_suggestions::dismiss_tag_suggestion),
+        )
+        .route(
+            "/api/players/{id}/recalibrate",
+            post(views::recalibration::recalibrate),
+        )
+        .route(
+            "/api/suspensions/{id}/lift",
+            post(views::roster::lift_suspension),
+        )
+        .route("/api/toss", post(views::match_day::toss_coin))
+        .route("/api/record", post(views::record::submit_result))
+        .route("/api/hooks/record", post(hooks::record))
+        .route("/api/seasons", post(views::seasons::end_season))
+        .route("/api/matches/{id}", put(views::history::amend_match))
+        .route(
+            "/api/pending/{id}/confirm",
+            post(views::pending::confirm_pending),
+        )
+        .route("/api/pending/{id}", delete(views::pending::discard_pending))
+        .route("/api/matches/{id}/void", post(views::history::void_match))
+        .route("/api/matches/{id}/mvp", post(views::history::award_mvp))
+        .route("/api/matches/import", post(views::history::import_matches))
+        .route("/api/ratings/import", post(views::history::import_ratings))
+        .route(
+            "/api/matches/{id}/comments",
+            post(views::history::add_comment),
+        )
+        .route("/api/matches/{id}/flags", post(views::flags::flag_match))
+        .route(
+            "/api/matches/{id}/restore",
+            post(views::history::restore_match),
+        )
+        .route("/api/admin/settings", post(views::admin::save_settings))
+        .route(
+            "/api/admin/settings/reset",
+            post(views::admin::reset_settings),
+        )
+        .route("/api/admin/what-if", post(views::admin::what_if))
+        .route("/api/admin/webhooks", post(views::webhooks::create_webhook))
+        .route(
+            "/api/admin/webhooks/{id}",
+            delete(views::webhooks::delete_webhook),
+        )
+        .route("/api/admin/jobs/{id}/retry", post(views::jobs::retry_job))
+        .route("/api/admin/jobs/{id}", delete(views::jobs::dismiss_job))
+        .route(
+            "/api/admin/flags/{id}/resolve",
+            post(views::flags::resolve_flag),
+        )
+        .route(
+            "/api/players/{id}/invite",
+            post(views::invite::create_invite),
+        )
+        .route("/api/invites/{token}", post(views::invite::claim))
+        .route("/api/me/login", post(views::me::login))
+        .route("/api/me/rsvp", post(views::me::rsvp))
+        .route("/api/me/peer-ratings", post(views::me::rate_teammate))
+        .route("/api/me/visibility", post(views::me::set_visibility))
+        .route("/api/registrations", post(views::join::register))
+        .route(
+            "/api/registrations/{id}/approve",
+            post(views::join::approve),
+        )
+        .route("/api/registrations/{id}", delete(views::join::reject))
+        // Players and results may have changed
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            player_cache::invalidate_after,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::limit_by_ip,
+        ));
+
+    // Read-only pages and API - login required when pages are private
+    let pages = Router::new()
         // Pages
         .route("/", get(views::match_day::page))
         .route("/roster", get(views::roster::page))
         .route("/record", get(views::record::page))
         .route("/history", get(views::history::page))
-        // Auth
-        .route("/api/login", post(auth::login))
-        .route("/api/logout", post(auth::logout))
-        // API - Players
-        .route("/api/players", post(views::roster::create_player))
-        .route("/api/players/{id}", put(views::roster::update_player))
-        .route("/api/players/{id}", delete(views::roster::delete_player))
+        .route("/seasons", get(views::seasons::page))
+        .route("/seasons/{id}/team", get(views::seasons::team_page))
+        .route("/stats", get(views::stats::page))
+        .route("/records", get(views::records::page))
+        .route("/kit", get(views::match_day::kit_list))
+        .route("/teams/print", get(views::match_day::team_sheet))
+        .route("/teams/share", get(views::match_day::share_text))
+        .route("/admin", get(views::admin::page))
+        .route("/admin/activity", get(views::activity::page))
+        // Fragments - page sections for lazy-loading and refreshing
+        .route("/fragments/players", get(views::fragments::player_list))
+        .route("/fragments/history", get(views::fragments::history))
+        .route("/fragments/matches/{id}", get(views::fragments::match_card))
+        .route("/fragments/teams", get(views::fragments::team_panel))
+        .route("/fragments/toss", get(views::match_day::coin_toss))
+        // API - Roster
+        .route("/api/players/{id}/edit", get(views::roster::edit_player))
         // API - Team Generator
         .route("/api/generate", post(views::match_day::generate_teams))
         .route("/api/shuffle", post(views::match_day::shuffle_teams))
-        .route("/api/teams", get(views::match_day::view_teams))
-        // API - Record
-        .route("/api/record", post(views::record::submit_result))
+        .route(
+            "/api/generate/compare",
+            post(views::match_day::compare_balancers),
+        )
+        .route(
+            "/api/tournament/squads",
+            post(views::match_day::tournament_squads),
+        )
+        .route(
+            "/api/late-arrival",
+            post(views::match_day::add_late_arrival),
+        )
+        .route("/api/predict", post(predict::predict_match))
+        // API - Seasons
+        .route(
+            "/api/seasons/preview",
+            post(views::seasons::preview_rollover),
+        )
+        // API - Stats (JSON for embedding)
+        .route("/api/stats", get(stats::stats))
+        .route(
+            "/api/players/{id}/history.csv",
+            get(stats::player_history_csv),
+        )
+        .route(
+            "/api/export/appearances.jsonl",
+            get(analytics::appearances_jsonl),
+        )
+        .route(
+            "/api/export/appearances.parquet",
+            get(analytics::appearances_parquet),
+        )
+        // API - Live updates (Server-Sent Events)
+        .route("/api/events", get(events::stream))
+        // 304 Not Modified for unchanged pages (runs after the private-pages check)
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            caching::conditional_get,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_login_if_private,
+        ));
+
+    let router = Router::new()
+        .merge(pages)
+        // Auth
+        .route(
+            "/api/login",
+            post(auth::login).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                rate_limit::limit_by_ip,
+            )),
+        )
+        .route("/api/logout", post(auth::logout))
+        // Sign-up and invite pages - public so newcomers can join a private group
+        .route("/join", get(views::join::page))
+        .route("/invite/{token}", get(views::invite::page))
+        // Player login, profile and RSVPs - players handle their own login
+        .route("/me", get(views::me::page))
+        .route("/api/me/logout", post(views::me::logout))
+        // Checks for an admin or the player themself
+        .route("/api/players/{id}/data", get(privacy::export_player_data))
+        // API - Players, Record
+        .merge(mutations)
+        // API - Docs
+        .route("/api/openapi.json", get(openapi::spec))
+        .route("/api/docs", get(openapi::docs))
+        // Unknown routes get the 404 page
+        .fallback(views::errors::not_found)
+        // Vendored CSS/JS (see scripts/vendor-assets.sh)
+        .nest_service(
+            "/static",
+            SetResponseHeader::overriding(
+                ServeDir::new("static"),
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(caching::STATIC_CACHE_CONTROL),
+            ),
+        )
+        // CSP and other security headers on every response
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            security::add_security_headers,
+        ))
+        // gzip/brotli for HTML, JSON and assets when the client accepts it
+        .layer(CompressionLayer::new())
         .layer(TraceLayer::new_for_http())
-        .with_state(state);
+        .with_state(state.clone());
+    let events = state.events.clone();
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "8000".to_string());
     let addr = format!("0.0.0.0:{}", port);
@@ -96,5 +465,40 @@ async fn main() {
         .await
         .expect("Failed to bind to address");
 
-    axum::serve(listener, router).await.expect("Server error");
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        // Open event streams never finish on their own
+        events.close();
+    })
+    .await
+    .expect("Server error");
+}
+
+/// Resolve on Ctrl+C or SIGTERM (e.g. `docker stop`) so traces can be flushed
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutting down");
 }
@@ -0,0 +1,239 @@
+use crate::models::{EloSnapshot, Match};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Appearance counts worth celebrating
+pub const APPEARANCE_MILESTONES: [usize; 8] = [10, 25, 50, 100, 150, 200, 300, 500];
+/// Ratings worth celebrating the first time a player reaches them
+pub const ELO_MILESTONES: [i32; 4] = [1300, 1400, 1500, 1600];
+
+/// Something a player achieved in a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Milestone {
+    /// Their nth match
+    Appearances { count: usize },
+    /// Their team conceded nothing, for the first time
+    FirstCleanSheet,
+    /// Their rating reached this mark for the first time
+    Elo { elo: i32 },
+}
+
+impl Milestone {
+    /// Short text for the result card and webhooks, e.g. "100th appearance"
+    pub fn label(&self) -> String {
+        match self {
+            Milestone::Appearances { count } => format!("{} appearance", ordinal(*count)),
+            Milestone::FirstCleanSheet => "First clean sheet".to_string(),
+            Milestone::Elo { elo } => format!("Reached {} Elo", elo),
+        }
+    }
+}
+
+/// A milestone and who reached it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PlayerMilestone {
+    pub player_id: i32,
+    #[serde(flatten)]
+    pub milestone: Milestone,
+}
+
+/// 1st, 2nd, 3rd, 4th, ..., 11th, 12th, 13th, ..., 21st
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+/// Milestones reached in a newly recorded match, judged against the matches
+/// before it (voided matches and the match itself are ignored). Players are
+/// listed in team order.
+pub fn milestones(recorded: &Match, earlier: &[Match]) -> Vec<PlayerMilestone> {
+    let earlier: Vec<&Match> = earlier
+        .iter()
+        .filter(|m| m.voided_at.is_none() && m.id != recorded.id)
+        .collect();
+    let snapshot: HashMap<i32, EloSnapshot> =
+        serde_json::from_value(recorded.elo_snapshot.clone()).unwrap_or_default();
+
+    let mut reached = Vec::new();
+    for (team, conceded) in [
+        (&recorded.team_a, recorded.score_b),
+        (&recorded.team_b, recorded.score_a),
+    ] {
+        for &player_id in team {
+            let mut add = |milestone| {
+                reached.push(PlayerMilestone {
+                    player_id,
+                    milestone,
+                })
+            };
+            let played: Vec<&&Match> = earlier
+                .iter()
+                .filter(|m| m.team_a.contains(&player_id) || m.team_b.contains(&player_id))
+                .collect();
+
+            let count = played.len() + 1;
+            if APPEARANCE_MILESTONES.contains(&count) {
+                add(Milestone::Appearances { count });
+            }
+
+            let kept_clean_sheet = |m: &&&Match| {
+                let kept = if m.team_a.contains(&player_id) {
+                    m.score_b
+                } else {
+                    m.score_a
+                };
+                kept == 0
+            };
+            if conceded == 0 && !played.iter().any(kept_clean_sheet) {
+                add(Milestone::FirstCleanSheet);
+            }
+
+            if let Some(change) = snapshot.get(&player_id) {
+                // Highest rating before this match, from their earlier snapshots
+                let peak = played
+                    .iter()
+                    .filter_map(|m| {
+                        let snapshot: HashMap<i32, EloSnapshot> =
+                            serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+                        snapshot
+                            .get(&player_id)
+                            .map(|s| s.before.max(s.before + s.delta * s.participation))
+                    })
+                    .fold(change.before, f32::max);
+                let after = change.before + change.delta * change.participation;
+                // Only the highest mark passed, if a big jump passes several
+                if let Some(elo) = ELO_MILESTONES
+                    .into_iter()
+                    .rfind(|mark| peak < *mark as f32 && after >= *mark as f32)
+                {
+                    add(Milestone::Elo { elo });
+                }
+            }
+        }
+    }
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+    use serde_json::json;
+
+    fn make_match(
+        id: i32,
+        team_a: Vec<i32>,
+        team_b: Vec<i32>,
+        score: (i32, i32),
+        snapshot: serde_json::Value,
+    ) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, 2).unwrap(),
+            team_a,
+            team_b,
+            score_a: score.0,
+            score_b: score.1,
+            elo_snapshot: snapshot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ordinal() {
+        let ordinals: Vec<String> = [1, 2, 3, 4, 11, 12, 13, 21, 100, 112]
+            .into_iter()
+            .map(ordinal)
+            .collect();
+        assert_eq!(
+            ordinals,
+            ["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "100th", "112th"]
+        );
+    }
+
+    #[test]
+    fn test_appearances_and_clean_sheets() {
+        // Player 1 has played 9 and kept a clean sheet; player 2 never has
+        let mut earlier: Vec<Match> = (1..=9)
+            .map(|id| make_match(id, vec![1], vec![2], (1, 1), json!({})))
+            .collect();
+        earlier[0].score_b = 0;
+        let mut voided = make_match(10, vec![2], vec![3], (0, 0), json!({}));
+        voided.voided_at = Some(Utc::now());
+        earlier.push(voided);
+
+        // Player 1's second clean sheet isn't a milestone
+        let recorded = make_match(11, vec![1], vec![2], (2, 0), json!({}));
+        let reached: Vec<(i32, Milestone)> = milestones(&recorded, &earlier)
+            .into_iter()
+            .map(|m| (m.player_id, m.milestone))
+            .collect();
+        assert_eq!(
+            reached,
+            vec![
+                (1, Milestone::Appearances { count: 10 }),
+                (2, Milestone::Appearances { count: 10 }),
+            ]
+        );
+
+        let recorded = make_match(11, vec![3, 2], vec![1], (3, 0), json!({}));
+        let reached: Vec<(i32, Milestone)> = milestones(&recorded, &earlier)
+            .into_iter()
+            .map(|m| (m.player_id, m.milestone))
+            .collect();
+        assert_eq!(
+            reached,
+            vec![
+                // The voided match doesn't count for player 3
+                (3, Milestone::FirstCleanSheet),
+                (2, Milestone::Appearances { count: 10 }),
+                (2, Milestone::FirstCleanSheet),
+                (1, Milestone::Appearances { count: 10 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_elo_milestones() {
+        let earlier = vec![make_match(
+            1,
+            vec![1, 2],
+            vec![3],
+            (1, 0),
+            json!({
+                "1": {"before": 1380.0, "delta": 30.0},
+                "2": {"before": 1290.0, "delta": 10.0}
+            }),
+        )];
+        let recorded = make_match(
+            2,
+            vec![1, 2],
+            vec![3],
+            (2, 1),
+            json!({
+                "1": {"before": 1390.0, "delta": 20.0},
+                "2": {"before": 1280.0, "delta": 240.0},
+                "3": {"before": 1200.0, "delta": -20.0}
+            }),
+        );
+
+        let reached = milestones(&recorded, &earlier);
+
+        // Player 1 already reached 1400 last time; player 2 passes two marks
+        assert_eq!(
+            reached,
+            vec![PlayerMilestone {
+                player_id: 2,
+                milestone: Milestone::Elo { elo: 1500 },
+            }]
+        );
+        assert_eq!(reached[0].milestone.label(), "Reached 1500 Elo");
+    }
+}
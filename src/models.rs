@@ -1,3 +1,4 @@
+use crate::onboarding::{Answers, Experience, OnboardingFormula};
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -50,6 +51,118 @@ impl fmt::Display for Tag {
     }
 }
 
+/// Where a player lines up. Unlike tags (which describe playing style), a
+/// player has at most one position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Position {
+    Gk,
+    Def,
+    Mid,
+    Att,
+}
+
+impl Position {
+    pub const ALL: [Position; 4] = [Position::Gk, Position::Def, Position::Mid, Position::Att];
+    /// Positions balanced by count between the teams (GKs are split separately)
+    pub const OUTFIELD: [Position; 3] = [Position::Def, Position::Mid, Position::Att];
+
+    /// Parse from its short label (case-insensitive)
+    pub fn from_str(s: &str) -> Option<Position> {
+        match s.trim().to_uppercase().as_str() {
+            "GK" => Some(Position::Gk),
+            "DEF" => Some(Position::Def),
+            "MID" => Some(Position::Mid),
+            "ATT" => Some(Position::Att),
+            _ => None,
+        }
+    }
+
+    /// Full name for forms and tooltips
+    pub fn label(self) -> &'static str {
+        match self {
+            Position::Gk => "Goalkeeper",
+            Position::Def => "Defender",
+            Position::Mid => "Midfielder",
+            Position::Att => "Attacker",
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Position::Gk => write!(f, "GK"),
+            Position::Def => write!(f, "DEF"),
+            Position::Mid => write!(f, "MID"),
+            Position::Att => write!(f, "ATT"),
+        }
+    }
+}
+
+/// Player age band (a balancing attribute)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AgeBand {
+    Under25,
+    From25To34,
+    From35To44,
+    Over45,
+}
+
+impl AgeBand {
+    pub const ALL: [AgeBand; 4] = [
+        AgeBand::Under25,
+        AgeBand::From25To34,
+        AgeBand::From35To44,
+        AgeBand::Over45,
+    ];
+
+    /// Numeric value for team balancing (0 = youngest)
+    pub fn value(self) -> f32 {
+        match self {
+            AgeBand::Under25 => 0.0,
+            AgeBand::From25To34 => 1.0,
+            AgeBand::From35To44 => 2.0,
+            AgeBand::Over45 => 3.0,
+        }
+    }
+
+    /// Parse from its display label
+    pub fn from_str(s: &str) -> Option<AgeBand> {
+        match s.trim() {
+            "U25" => Some(AgeBand::Under25),
+            "25-34" => Some(AgeBand::From25To34),
+            "35-44" => Some(AgeBand::From35To44),
+            "45+" => Some(AgeBand::Over45),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AgeBand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AgeBand::Under25 => write!(f, "U25"),
+            AgeBand::From25To34 => write!(f, "25-34"),
+            AgeBand::From35To44 => write!(f, "35-44"),
+            AgeBand::Over45 => write!(f, "45+"),
+        }
+    }
+}
+
+/// Stamina and pace are rated 1-5
+pub const MIN_ATTRIBUTE_LEVEL: i32 = 1;
+pub const MAX_ATTRIBUTE_LEVEL: i32 = 5;
+/// Value assumed for players with an unknown attribute
+const NEUTRAL_ATTRIBUTE_LEVEL: f32 = 3.0;
+const NEUTRAL_AGE_BAND: f32 = 1.5;
+
+/// Parse a 1-5 attribute level from a form field (empty or invalid = unknown)
+pub fn parse_attribute_level(value: Option<&str>) -> Option<i32> {
+    value
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|n| (MIN_ATTRIBUTE_LEVEL..=MAX_ATTRIBUTE_LEVEL).contains(n))
+}
+
 /// Legacy constant for backwards compatibility with views
 pub const TAG_WEIGHTS: &[(&str, i32)] =
     &[("PLAYMAKER", 50), ("RUNNER", 40), ("DEF", 20), ("ATK", 10)];
@@ -57,12 +170,22 @@ pub const TAG_WEIGHTS: &[(&str, i32)] =
 pub const ELO_DEFAULT: f32 = 1200.0;
 pub const ELO_K_FACTOR: f32 = 32.0;
 pub const GD_MULTIPLIER_CAP: f32 = 2.5;
-pub const HANDICAP_PER_PLAYER: f32 = 100.0; // Elo penalty per missing player-equivalent
+pub const HANDICAP_PER_PLAYER: f32 = 100.0; // Default Elo penalty per missing player-equivalent
+pub const MAX_K_MULTIPLIER: f32 = 5.0; // Largest per-match K multiplier (special events)
+pub const CALIBRATION_MATCHES: i32 = 3; // A player's first matches, rated as calibration
+pub const CALIBRATION_K_MULTIPLIER: f32 = 2.0; // Calibrating players' own deltas move this much faster
+pub const CALIBRATION_WEIGHT: f32 = 0.5; // Weight of a calibrating player in their team's average Elo
+pub const RETURN_K_MULTIPLIER_MAX: f32 = 2.0; // Most a returning player's own delta is boosted after a long absence
 pub const MAX_PLAYERS: usize = 14;
 pub const MAX_PER_TEAM: usize = MAX_PLAYERS / 2;
+pub const MAX_JERSEY_NUMBER: i32 = 99;
+pub const MIN_STARTING_ELO: f32 = 800.0;
+pub const MAX_STARTING_ELO: f32 = 2000.0;
+/// Most players accepted in one bulk paste
+pub const MAX_BULK_PLAYERS: usize = 50;
 
 /// Player from database
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
 pub struct Player {
     pub id: i32,
     pub name: String,
@@ -70,6 +193,15 @@ pub struct Player {
     pub tags: String,
     pub matches_played: i32,
     pub created_at: DateTime<Utc>,
+    pub jersey_number: Option<i32>, // Preferred shirt number
+    pub stamina: Option<i32>,       // 1-5
+    pub pace: Option<i32>,          // 1-5
+    pub age_band: Option<String>,   // AgeBand label
+    pub position: Option<String>,   // Position label
+    /// Days since their last match (None if they haven't played)
+    pub days_away: Option<i32>,
+    /// Shown under a pseudonym to anonymous visitors
+    pub hide_name: bool,
 }
 
 impl Player {
@@ -87,14 +219,105 @@ impl Player {
     pub fn tag_value(&self) -> i32 {
         self.tags().iter().map(|t| t.weight()).sum()
     }
+
+    /// Parsed position, if set
+    pub fn position(&self) -> Option<Position> {
+        self.position.as_deref().and_then(Position::from_str)
+    }
+
+    /// Whether the balancer treats the player as a goalkeeper (GK position or tag)
+    pub fn is_goalkeeper(&self) -> bool {
+        self.position() == Some(Position::Gk) || self.has_tag(Tag::Gk)
+    }
+
+    /// Parsed age band, if set
+    pub fn age_band(&self) -> Option<AgeBand> {
+        self.age_band.as_deref().and_then(AgeBand::from_str)
+    }
+
+    /// Stamina for balancing (unknown = average)
+    pub fn stamina_value(&self) -> f32 {
+        self.stamina.map_or(NEUTRAL_ATTRIBUTE_LEVEL, |s| s as f32)
+    }
+
+    /// Pace for balancing (unknown = average)
+    pub fn pace_value(&self) -> f32 {
+        self.pace.map_or(NEUTRAL_ATTRIBUTE_LEVEL, |p| p as f32)
+    }
+
+    /// Age band for balancing (unknown = middle of the range)
+    pub fn age_value(&self) -> f32 {
+        self.age_band().map_or(NEUTRAL_AGE_BAND, AgeBand::value)
+    }
+
+    /// Whether the player's next match is still one of their calibration matches
+    pub fn is_calibrating(&self) -> bool {
+        self.matches_played < CALIBRATION_MATCHES
+    }
 }
 
 /// Form data for creating a new player
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct NewPlayer {
     pub name: String,
     pub elo: Option<f32>,
     pub tags: Option<String>,
+    pub jersey_number: Option<i32>,
+    pub stamina: Option<i32>,
+    pub pace: Option<i32>,
+    pub age_band: Option<String>,
+    pub position: Option<String>,
+}
+
+impl NewPlayer {
+    /// Parse one bulk-paste line: a name, then optionally a starting Elo and
+    /// tags, comma-separated (e.g. "Name, 1250, DEF" or "Name, ATK RUNNER")
+    pub fn parse_line(line: &str) -> Result<NewPlayer, String> {
+        let mut fields = line.split(',').map(str::trim);
+        let name = fields.next().unwrap_or_default();
+        if name.is_empty() {
+            return Err("missing name".to_string());
+        }
+
+        let mut elo = None;
+        let mut tags: Vec<Tag> = Vec::new();
+        for field in fields.filter(|f| !f.is_empty()) {
+            if let Ok(value) = field.parse::<f32>() {
+                if elo.is_some() {
+                    return Err(format!("more than one Elo (\"{}\")", field));
+                }
+                if !(MIN_STARTING_ELO..=MAX_STARTING_ELO).contains(&value) {
+                    return Err(format!(
+                        "Elo {} is outside {}-{}",
+                        field, MIN_STARTING_ELO, MAX_STARTING_ELO
+                    ));
+                }
+                elo = Some(value);
+                continue;
+            }
+            for word in field.split(|c: char| c.is_whitespace() || c == '/') {
+                if word.is_empty() {
+                    continue;
+                }
+                let tag = Tag::from_str(word).ok_or_else(|| format!("unknown tag \"{}\"", word))?;
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+
+        Ok(NewPlayer {
+            name: name.to_string(),
+            elo,
+            tags: Some(
+                tags.iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            ..Default::default()
+        })
+    }
 }
 
 /// Form data for updating a player
@@ -102,6 +325,12 @@ pub struct NewPlayer {
 pub struct UpdatePlayer {
     pub elo: f32,
     pub tags: String,
+    pub jersey_number: Option<i32>,
+    pub stamina: Option<i32>,
+    pub pace: Option<i32>,
+    pub age_band: Option<String>,
+    pub position: Option<String>,
+    pub hide_name: bool,
 }
 
 /// Match from database
@@ -115,6 +344,745 @@ pub struct Match {
     pub score_b: i32,
     pub elo_snapshot: serde_json::Value,
     pub created_at: DateTime<Utc>,
+    pub voided_at: Option<DateTime<Utc>>, // Voided matches don't count towards ratings
+    pub notes: String,
+    pub k_multiplier: f32,      // Scales K for special events (1.0 = normal)
+    pub captain_a: Option<i32>, // Player ID
+    pub captain_b: Option<i32>, // Player ID
+    pub mvp: Option<i32>,       // Player ID, once awarded
+    pub team_name_a: Option<String>, // Themed name (None = "Team A")
+    pub team_name_b: Option<String>,
+    pub goals: serde_json::Value, // [Goal], empty if only the score was recorded
+}
+
+/// An empty 0-0 match with no players: normal K, no goals, nothing voided
+impl Default for Match {
+    fn default() -> Self {
+        Match {
+            id: 0,
+            played_at: NaiveDate::default(),
+            team_a: Vec::new(),
+            team_b: Vec::new(),
+            score_a: 0,
+            score_b: 0,
+            elo_snapshot: serde_json::json!({}),
+            created_at: DateTime::default(),
+            voided_at: None,
+            notes: String::new(),
+            k_multiplier: 1.0,
+            captain_a: None,
+            captain_b: None,
+            mvp: None,
+            team_name_a: None,
+            team_name_b: None,
+            goals: serde_json::json!([]),
+        }
+    }
+}
+
+impl Match {
+    /// The teams' names as recorded: themed ones, or Team A and Team B
+    pub fn team_names(&self) -> [&str; 2] {
+        [
+            self.team_name_a.as_deref().unwrap_or(DEFAULT_TEAM_NAMES[0]),
+            self.team_name_b.as_deref().unwrap_or(DEFAULT_TEAM_NAMES[1]),
+        ]
+    }
+
+    /// Goal events in the order they were scored (empty if only the score was recorded)
+    pub fn goals(&self) -> Vec<Goal> {
+        serde_json::from_value(self.goals.clone()).unwrap_or_default()
+    }
+}
+
+/// Team names when themed names are off (and for matches recorded without them)
+pub const DEFAULT_TEAM_NAMES: [&str; 2] = ["Team A", "Team B"];
+
+/// Data for recording a new match
+#[derive(Debug, Clone)]
+pub struct NewMatch {
+    /// Match date in the group timezone
+    pub played_at: NaiveDate,
+    pub team_a: Vec<i32>,
+    pub team_b: Vec<i32>,
+    pub score_a: i32,
+    pub score_b: i32,
+    pub elo_snapshot: serde_json::Value,
+    pub notes: String,
+    pub k_multiplier: f32,
+    pub captain_a: Option<i32>,
+    pub captain_b: Option<i32>,
+    /// Themed team names (None = Team A / Team B)
+    pub team_name_a: Option<String>,
+    pub team_name_b: Option<String>,
+    /// Goal events, ordered by minute (empty = score only)
+    pub goals: Vec<Goal>,
+}
+
+/// A submitted result waiting for a second person to confirm it
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingResult {
+    pub id: i32,
+    pub played_at: NaiveDate,
+    pub team_a: Vec<i32>,
+    pub team_b: Vec<i32>,
+    pub score_a: i32,
+    pub score_b: i32,
+    pub participation: serde_json::Value, // player ID -> share
+    pub k_multiplier: f32,
+    pub notes: String,
+    pub submitted_by: String, // "admin", "hook" or "player:<id>"
+    pub created_at: DateTime<Utc>,
+    pub captain_a: Option<i32>,
+    pub captain_b: Option<i32>,
+    pub goals: serde_json::Value, // [Goal]
+    pub amends: Option<i32>,      // Match ID this fixes (None = a new result)
+}
+
+/// Most matches accepted in one history import
+pub const MAX_IMPORT_MATCHES: usize = 500;
+
+/// Past match parsed from an import line, with players still named
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedMatch {
+    pub played_at: NaiveDate,
+    pub team_a: Vec<String>,
+    pub team_b: Vec<String>,
+    pub score_a: i32,
+    pub score_b: i32,
+    pub notes: String,
+}
+
+impl ImportedMatch {
+    /// Parse one CSV import line: date, Team A, Team B, score and optional notes
+    /// (e.g. "2023-09-10, Sam; Alex; Jo, Kim; Lee; Max, 3-2, Rainy").
+    /// Players within a team are separated by semicolons.
+    pub fn parse_line(line: &str) -> Result<ImportedMatch, String> {
+        let fields: Vec<&str> = line.splitn(5, ',').map(str::trim).collect();
+        let [date, team_a, team_b, score, rest @ ..] = fields.as_slice() else {
+            return Err("expected date, Team A, Team B and score".to_string());
+        };
+
+        let played_at = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("invalid date \"{}\" (use YYYY-MM-DD)", date))?;
+
+        let team = |field: &str, label: &str| -> Result<Vec<String>, String> {
+            let names: Vec<String> = field
+                .split(';')
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(String::from)
+                .collect();
+            if names.is_empty() {
+                return Err(format!("{} has no players", label));
+            }
+            if names.len() > MAX_PER_TEAM {
+                return Err(format!("{} has more than {} players", label, MAX_PER_TEAM));
+            }
+            Ok(names)
+        };
+        let team_a = team(team_a, "Team A")?;
+        let team_b = team(team_b, "Team B")?;
+
+        let mut seen: Vec<String> = Vec::new();
+        for name in team_a.iter().chain(&team_b) {
+            let key = name.to_lowercase();
+            if seen.contains(&key) {
+                return Err(format!("{} is listed twice", name));
+            }
+            seen.push(key);
+        }
+
+        let goals = |value: &str| {
+            value
+                .trim()
+                .parse::<i32>()
+                .ok()
+                .filter(|g| (0..=50).contains(g))
+        };
+        let (score_a, score_b) = score
+            .split_once('-')
+            .and_then(|(a, b)| Some((goals(a)?, goals(b)?)))
+            .ok_or_else(|| format!("invalid score \"{}\" (use e.g. 3-2)", score))?;
+
+        Ok(ImportedMatch {
+            played_at,
+            team_a,
+            team_b,
+            score_a,
+            score_b,
+            notes: clean_note(rest.first().copied().unwrap_or_default()),
+        })
+    }
+}
+
+/// Rating imported from before the app, the start of a player's history
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct RatingBaseline {
+    pub player_id: i32,
+    pub elo: f32,
+    /// Date the rating was last right in the old records
+    pub as_of: NaiveDate,
+    /// Where it came from, e.g. "2023 spreadsheet"
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Legacy rating parsed from an import line, with the player still named
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegacyRating {
+    pub name: String,
+    pub elo: f32,
+    /// None = the date given for the whole import
+    pub as_of: Option<NaiveDate>,
+}
+
+impl LegacyRating {
+    /// Parse one CSV import line: name, Elo and an optional date
+    /// (e.g. "Sam, 1340" or "Sam, 1340, 2023-08-27")
+    pub fn parse_line(line: &str) -> Result<LegacyRating, String> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let (name, elo, date) = match fields.as_slice() {
+            [name, elo] => (*name, *elo, None),
+            [name, elo, date] => (*name, *elo, Some(*date)),
+            _ => return Err("expected name, Elo and an optional date".to_string()),
+        };
+        if name.is_empty() {
+            return Err("missing name".to_string());
+        }
+        let elo = elo
+            .parse::<f32>()
+            .ok()
+            .filter(|e| (MIN_STARTING_ELO..=MAX_STARTING_ELO).contains(e))
+            .ok_or_else(|| {
+                format!(
+                    "Elo \"{}\" must be a number from {} to {}",
+                    elo, MIN_STARTING_ELO, MAX_STARTING_ELO
+                )
+            })?;
+        let as_of = date
+            .map(|date| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map_err(|_| format!("invalid date \"{}\" (use YYYY-MM-DD)", date))
+            })
+            .transpose()?;
+        Ok(LegacyRating {
+            name: name.to_string(),
+            elo,
+            as_of,
+        })
+    }
+}
+
+/// Max length of match notes and comments
+pub const MAX_NOTE_LENGTH: usize = 1000;
+
+/// Trim a free-text note and cap it at MAX_NOTE_LENGTH characters
+pub fn clean_note(text: &str) -> String {
+    text.trim().chars().take(MAX_NOTE_LENGTH).collect()
+}
+
+/// Comment added to a match after it was recorded
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct MatchComment {
+    pub id: i32,
+    pub match_id: i32,
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A player's report that a recorded match looks wrong; open until an admin
+/// resolves it
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct MatchFlag {
+    pub id: i32,
+    pub match_id: i32,
+    pub flagged_by: String,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Season rollover: after `ended_on`, ratings were pulled toward the default
+/// Elo, keeping `carry` of each player's distance from it
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Season {
+    pub id: i32,
+    pub name: String,
+    pub ended_on: NaiveDate,
+    pub carry: f32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Counter bumped by the database whenever page data changes (see migration 012)
+#[derive(Debug, Clone, FromRow)]
+pub struct DataVersion {
+    pub version: i64,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A player's final place in an archived season
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct SeasonStanding {
+    pub season_id: i32,
+    pub player_id: Option<i32>, // None once the player is deleted
+    pub name: String,
+    pub rank: i32,
+    pub elo: f32,
+    pub matches_played: i32,
+}
+
+/// Most self-registrations waiting for approval at once (a cap on spam)
+pub const MAX_PENDING_REGISTRATIONS: i64 = 50;
+/// Longest name accepted from the public sign-up form
+pub const MAX_REGISTRATION_NAME_LENGTH: usize = 60;
+
+/// A player's own sign-up, pending until an admin approves it
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Registration {
+    pub id: i32,
+    pub name: String,
+    pub position: Option<String>, // Position label
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+    pub experience: Option<String>, // Experience::as_str
+    pub fitness: Option<i32>,       // 1-5
+}
+
+impl Registration {
+    /// Parsed position, if given
+    pub fn position(&self) -> Option<Position> {
+        self.position.as_deref().and_then(Position::from_str)
+    }
+
+    /// Questionnaire answers (None for sign-ups from before the questionnaire)
+    pub fn answers(&self) -> Option<Answers> {
+        Some(Answers {
+            experience: Experience::from_str(self.experience.as_deref()?)?,
+            position: self.position(),
+            fitness: self.fitness,
+        })
+    }
+}
+
+/// The questionnaire answers a player's starting Elo came from
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct OnboardingAnswers {
+    pub player_id: i32,
+    pub experience: String, // Experience::as_str
+    pub position: Option<String>,
+    pub fitness: Option<i32>,
+    pub starting_elo: f32,
+    /// OnboardingFormula at the time, e.g. "base=1100,experience=100,fitness=25,keeper=0"
+    pub formula: String,
+    pub answered_at: DateTime<Utc>,
+}
+
+impl OnboardingAnswers {
+    /// How the starting Elo was worked out, with the formula used back then
+    pub fn explain(&self) -> Option<String> {
+        let answers = Answers {
+            experience: Experience::from_str(&self.experience)?,
+            position: self.position.as_deref().and_then(Position::from_str),
+            fitness: self.fitness,
+        };
+        let formula = OnboardingFormula::default().parse(&self.formula)?;
+        Some(answers.explain(&formula))
+    }
+}
+
+/// Days an invite link stays valid
+pub const INVITE_DAYS: i64 = 14;
+/// Longest email address accepted (RFC 5321 path limit)
+pub const MAX_EMAIL_LENGTH: usize = 254;
+/// Personal PINs are this many digits
+pub const MIN_PIN_LENGTH: usize = 4;
+pub const MAX_PIN_LENGTH: usize = 8;
+
+/// Single-use link for a player to claim their roster entry
+#[derive(Debug, Clone, FromRow)]
+pub struct Invite {
+    pub player_id: i32,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A player's own contact details and login, set when they claim an invite
+#[derive(Debug, Clone, FromRow)]
+pub struct PlayerAccount {
+    pub email: Option<String>,
+    /// Email when teams are picked
+    pub notify_teams: bool,
+    /// Email when a result is recorded
+    pub notify_results: bool,
+    pub pin_hash: Option<String>, // argon2 PHC string
+    pub claimed_at: DateTime<Utc>,
+}
+
+/// Parse an optional email address: empty is None, otherwise it needs one
+/// `@` with something on both sides and a dot in the domain
+pub fn parse_email(text: &str) -> Result<Option<String>, String> {
+    let email = text.trim();
+    if email.is_empty() {
+        return Ok(None);
+    }
+    let valid = email.len() <= MAX_EMAIL_LENGTH
+        && !email.chars().any(char::is_whitespace)
+        && matches!(
+            email.split_once('@'),
+            Some((local, domain)) if !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() >= 2
+                && domain.split('.').all(|part| !part.is_empty())
+        );
+    if valid {
+        Ok(Some(email.to_string()))
+    } else {
+        Err(format!("\"{}\" doesn't look like an email address", email))
+    }
+}
+
+/// Parse an optional PIN and its confirmation: empty is None, otherwise
+/// MIN_PIN_LENGTH-MAX_PIN_LENGTH digits typed the same twice
+pub fn parse_pin(pin: &str, confirm: &str) -> Result<Option<String>, String> {
+    let pin = pin.trim();
+    if pin.is_empty() && confirm.trim().is_empty() {
+        return Ok(None);
+    }
+    if !(MIN_PIN_LENGTH..=MAX_PIN_LENGTH).contains(&pin.len())
+        || !pin.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(format!(
+            "PIN must be {}-{} digits",
+            MIN_PIN_LENGTH, MAX_PIN_LENGTH
+        ));
+    }
+    if pin != confirm.trim() {
+        return Err("PINs don't match".to_string());
+    }
+    Ok(Some(pin.to_string()))
+}
+
+/// A player's answer for a match day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RsvpStatus {
+    In,
+    Maybe,
+    Out,
+}
+
+impl RsvpStatus {
+    pub const ALL: [RsvpStatus; 3] = [RsvpStatus::In, RsvpStatus::Maybe, RsvpStatus::Out];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "in" => Some(RsvpStatus::In),
+            "maybe" => Some(RsvpStatus::Maybe),
+            "out" => Some(RsvpStatus::Out),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RsvpStatus::In => "in",
+            RsvpStatus::Maybe => "maybe",
+            RsvpStatus::Out => "out",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RsvpStatus::In => "In",
+            RsvpStatus::Maybe => "Maybe",
+            RsvpStatus::Out => "Out",
+        }
+    }
+}
+
+/// Why a player answered Out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AbsenceReason {
+    Injured,
+    Away,
+    Work,
+    Other,
+}
+
+impl AbsenceReason {
+    pub const ALL: [AbsenceReason; 4] = [
+        AbsenceReason::Injured,
+        AbsenceReason::Away,
+        AbsenceReason::Work,
+        AbsenceReason::Other,
+    ];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "injured" => Some(AbsenceReason::Injured),
+            "away" => Some(AbsenceReason::Away),
+            "work" => Some(AbsenceReason::Work),
+            "other" => Some(AbsenceReason::Other),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AbsenceReason::Injured => "injured",
+            AbsenceReason::Away => "away",
+            AbsenceReason::Work => "work",
+            AbsenceReason::Other => "other",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AbsenceReason::Injured => "Injured",
+            AbsenceReason::Away => "Away",
+            AbsenceReason::Work => "Work",
+            AbsenceReason::Other => "Other",
+        }
+    }
+}
+
+/// A player's RSVP for one match day
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Rsvp {
+    pub player_id: i32,
+    pub match_on: NaiveDate,
+    pub status: String,         // RsvpStatus::as_str
+    pub reason: Option<String>, // AbsenceReason::as_str, only when out
+}
+
+impl Rsvp {
+    pub fn status(&self) -> Option<RsvpStatus> {
+        RsvpStatus::from_str(&self.status)
+    }
+
+    pub fn reason(&self) -> Option<AbsenceReason> {
+        self.reason.as_deref().and_then(AbsenceReason::from_str)
+    }
+}
+
+/// One player's answer about another in a monthly peer survey (attributes 1-5)
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PeerRating {
+    pub rater_id: i32,
+    pub player_id: i32,
+    /// First day of the survey month
+    pub period: NaiveDate,
+    pub skill: i32,
+    pub stamina: i32,
+    pub pace: i32,
+}
+
+/// Teams picked from RSVPs when the RSVP deadline passed
+#[derive(Debug, Clone, FromRow)]
+pub struct AutoTeams {
+    pub team_a: Vec<i32>,
+    pub team_b: Vec<i32>,
+}
+
+/// Coin toss for kickoff on a match day's generated teams
+#[derive(Debug, Clone, FromRow)]
+pub struct CoinToss {
+    /// "a" or "b": the team that kicks off (the other picks ends)
+    pub kickoff_team: String,
+    pub tossed_at: DateTime<Utc>,
+}
+
+/// Outgoing webhook: a URL POSTed signed JSON when subscribed events happen
+#[derive(Debug, Clone, FromRow)]
+pub struct Webhook {
+    pub id: i32,
+    pub url: String,
+    pub secret: String,
+    pub events: String, // comma-separated WebhookEvent names
+    pub last_status: Option<String>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One attempt to deliver an event to a webhook
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookDelivery {
+    /// The webhook's URL
+    pub url: String,
+    pub event: String,
+    /// HTTP status (e.g. "200") or the request error
+    pub status: String,
+    pub delivered: bool,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// A player added, edited or removed (logged by a database trigger)
+#[derive(Debug, Clone, FromRow)]
+pub struct RosterChange {
+    pub player_name: String,
+    /// "added", "edited" or "removed"
+    pub change: String,
+    /// The fields changed by an edit, e.g. "tags, position"
+    pub detail: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A player's wins, draws and losses (overall, or as captain)
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct WinRecord {
+    pub player_id: i32,
+    pub wins: i32,
+    pub draws: i32,
+    pub losses: i32,
+}
+
+impl WinRecord {
+    pub fn played(&self) -> i32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Share of matches won (0-1)
+    pub fn win_rate(&self) -> f32 {
+        self.wins as f32 / self.played().max(1) as f32
+    }
+}
+
+/// Row count and on-disk size of one table
+#[derive(Debug, Clone, FromRow)]
+pub struct TableStats {
+    pub name: String,
+    /// Estimated by Postgres' statistics collector
+    pub rows: i64,
+    /// Bytes, including indexes and TOAST
+    pub bytes: i64,
+}
+
+/// A background job (see `jobs::Job` for the payloads)
+#[derive(Debug, Clone, FromRow)]
+pub struct QueuedJob {
+    pub id: i32,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub last_error: Option<String>,
+    pub failed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Suspension or ban keeping a player out of check-in
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Suspension {
+    pub id: i32,
+    pub player_id: i32,
+    pub reason: String,
+    pub returns_on: Option<NaiveDate>, // None = indefinite ban
+    pub lifted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Suspension {
+    /// Whether the player is still out on `today` (they're back on the return date)
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        self.lifted_at.is_none() && self.returns_on.is_none_or(|date| today < date)
+    }
+}
+
+/// Injury keeping a player out of check-in until it's cleared
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Injury {
+    pub id: i32,
+    pub player_id: i32,
+    pub note: String,
+    pub started_on: NaiveDate,
+    pub expected_return: Option<NaiveDate>, // None = unknown
+    pub cleared_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Roster column to sort by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlayerSort {
+    Name,
+    Number,
+    #[default]
+    Elo,
+    Matches,
+}
+
+impl PlayerSort {
+    pub fn from_str(s: &str) -> Option<PlayerSort> {
+        match s {
+            "name" => Some(PlayerSort::Name),
+            "number" => Some(PlayerSort::Number),
+            "elo" => Some(PlayerSort::Elo),
+            "matches" => Some(PlayerSort::Matches),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerSort::Name => "name",
+            PlayerSort::Number => "number",
+            PlayerSort::Elo => "elo",
+            PlayerSort::Matches => "matches",
+        }
+    }
+
+    /// Direction used when first sorting by this column
+    pub fn default_descending(&self) -> bool {
+        matches!(self, PlayerSort::Elo | PlayerSort::Matches)
+    }
+}
+
+/// Which players to list by suspension or injury status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlayerStatus {
+    #[default]
+    All,
+    /// Neither suspended nor injured
+    Available,
+    Suspended,
+    Injured,
+}
+
+impl PlayerStatus {
+    pub fn from_str(s: &str) -> PlayerStatus {
+        match s {
+            "available" => PlayerStatus::Available,
+            "suspended" => PlayerStatus::Suspended,
+            "injured" => PlayerStatus::Injured,
+            _ => PlayerStatus::All,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlayerStatus::All => "all",
+            PlayerStatus::Available => "available",
+            PlayerStatus::Suspended => "suspended",
+            PlayerStatus::Injured => "injured",
+        }
+    }
+}
+
+/// Roster search, filter and sort
+#[derive(Debug, Clone, Default)]
+pub struct PlayerFilter {
+    /// Case-insensitive substring of the name (empty = everyone)
+    pub search: String,
+    pub tag: Option<Tag>,
+    pub status: PlayerStatus,
+    pub sort: PlayerSort,
+    pub descending: bool,
+}
+
+impl PlayerFilter {
+    /// Whether any filter (rather than just sorting) is applied
+    pub fn is_filtered(&self) -> bool {
+        !self.search.is_empty() || self.tag.is_some() || self.status != PlayerStatus::All
+    }
 }
 
 /// Elo snapshot entry for a player
@@ -124,12 +1092,61 @@ pub struct EloSnapshot {
     pub delta: f32,
     #[serde(default = "default_participation")]
     pub participation: f32, // 0.0 to 1.0, default 1.0
+    /// Tags the player was fielded with (empty in snapshots recorded before this was kept)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tags: String,
+    /// Elo handicap applied to this player's team for the expected score
+    /// (positive when the team was short, 0 in older snapshots)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub handicap: f32,
+    /// Elo handicap for this player's team having no goalkeeper when the other
+    /// did (negative for the side with one, 0 in older snapshots)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub keeper_handicap: f32,
+    /// Whether this was one of the player's calibration matches
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub calibrating: bool,
+    /// Whether this was their first match back after `ABSENCE_DAYS` or more away
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub returning: bool,
+}
+
+fn is_zero(value: &f32) -> bool {
+    *value == 0.0
 }
 
 fn default_participation() -> f32 {
     1.0
 }
 
+/// Default match length in minutes, for participation from arrival/departure times
+pub const DEFAULT_MATCH_MINUTES: u32 = 60;
+/// Longest match length accepted on the record form
+pub const MAX_MATCH_MINUTES: u32 = 180;
+/// Highest score a team can be recorded with
+pub const MAX_SCORE: i32 = 50;
+
+/// Smallest participation a player can be recorded with (1%)
+pub const MIN_PARTICIPATION: f32 = 0.01;
+
+/// Parse a "PlayerID=0.17" participation entry, rejecting shares outside 1-100%
+pub fn parse_participation(entry: &str) -> Result<(i32, f32), String> {
+    let (id, value) = entry
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid participation entry \"{}\"", entry))?;
+    let id = id
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid player in participation entry \"{}\"", entry))?;
+    match value.trim().parse::<f32>() {
+        Ok(share) if (MIN_PARTICIPATION..=1.0).contains(&share) => Ok((id, share)),
+        _ => Err(format!(
+            "Participation must be between 1% and 100% (got \"{}\")",
+            value.trim()
+        )),
+    }
+}
+
 /// Result of team balancing
 #[derive(Debug, Clone)]
 pub struct TeamSplit {
@@ -137,14 +1154,99 @@ pub struct TeamSplit {
     pub team_b: Vec<Player>,
     pub cost: f32,
     pub elo_diff: f32,
-    pub tag_value_a: i32,
-    pub tag_value_b: i32,
+}
+
+/// Parse a "PlayerID=20-55" entry (joined at 20', left at 55') into a participation
+/// share of a `duration`-minute match. A blank start means from kick-off and a
+/// blank end means until full time.
+pub fn parse_minutes(entry: &str, duration: u32) -> Result<(i32, f32), String> {
+    let invalid = || format!("Invalid arrival/departure entry \"{}\"", entry);
+    let (id, range) = entry.split_once('=').ok_or_else(invalid)?;
+    let id = id.trim().parse().map_err(|_| invalid())?;
+    let (joined, left) = range.split_once('-').ok_or_else(invalid)?;
+    let minute = |value: &str, blank: u32| -> Result<u32, String> {
+        match value.trim() {
+            "" => Ok(blank),
+            value => value.parse().map_err(|_| invalid()),
+        }
+    };
+    let joined = minute(joined, 0)?;
+    let left = minute(left, duration)?;
+    if left > duration {
+        return Err(format!(
+            "Departure at {}' is after the end of a {}-minute match",
+            left, duration
+        ));
+    }
+    if joined >= left {
+        return Err(format!(
+            "Departure ({}') must come after arrival ({}')",
+            left, joined
+        ));
+    }
+    Ok((id, (left - joined) as f32 / duration as f32))
+}
+
+/// The team a goal counted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    A,
+    B,
+}
+
+/// A goal in a recorded match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Goal {
+    pub team: Side,
+    /// Player ID (None = not known). A scorer from the other team means an own goal.
+    pub scorer: Option<i32>,
+    /// Minute scored (None = not known)
+    pub minute: Option<u32>,
+}
+
+/// Parse an "a:7:12" entry (Team A goal by player 7 in the 12th minute).
+/// Scorer and minute may be blank.
+pub fn parse_goal(entry: &str) -> Result<Goal, String> {
+    let invalid = || format!("Invalid goal entry \"{}\"", entry);
+    let mut parts = entry.split(':');
+    let (Some(team), Some(scorer), Some(minute), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    let team = match team.trim() {
+        "a" | "A" => Side::A,
+        "b" | "B" => Side::B,
+        _ => return Err(invalid()),
+    };
+    let scorer = match scorer.trim() {
+        "" => None,
+        id => Some(id.parse().map_err(|_| invalid())?),
+    };
+    let minute = match minute.trim() {
+        "" => None,
+        minute => Some(minute.parse().map_err(|_| invalid())?),
+    };
+    if minute.is_some_and(|m| m > MAX_MATCH_MINUTES) {
+        return Err(format!("Goal minutes can't be past {}'", MAX_MATCH_MINUTES));
+    }
+    Ok(Goal {
+        team,
+        scorer,
+        minute,
+    })
+}
+
+/// The score the goals add up to: (Team A, Team B)
+pub fn goal_score(goals: &[Goal]) -> (i32, i32) {
+    let count = |side| goals.iter().filter(|g| g.team == side).count() as i32;
+    (count(Side::A), count(Side::B))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
 
     fn make_player(tags: &str) -> Player {
         Player {
@@ -152,8 +1254,7 @@ mod tests {
             name: "Test".to_string(),
             elo: 1200.0,
             tags: tags.to_string(),
-            matches_played: 0,
-            created_at: Utc::now(),
+            ..Default::default()
         }
     }
 
@@ -232,6 +1333,249 @@ mod tests {
         assert_eq!(empty.tag_value(), 0);
     }
 
+    #[test]
+    fn test_position_roundtrip() {
+        for position in Position::ALL {
+            assert_eq!(Position::from_str(&position.to_string()), Some(position));
+        }
+        assert_eq!(Position::from_str("mid"), Some(Position::Mid));
+        assert_eq!(Position::from_str("RUNNER"), None);
+    }
+
+    #[test]
+    fn test_goalkeeper_by_position_or_tag() {
+        let mut player = Player::default();
+        assert!(!player.is_goalkeeper());
+        player.position = Some("GK".to_string());
+        assert!(player.is_goalkeeper());
+        player.position = Some("DEF".to_string());
+        player.tags = "GK".to_string();
+        assert!(player.is_goalkeeper());
+    }
+
+    #[test]
+    fn test_age_band_roundtrip() {
+        for band in AgeBand::ALL {
+            assert_eq!(AgeBand::from_str(&band.to_string()), Some(band));
+        }
+        assert_eq!(AgeBand::from_str("old"), None);
+    }
+
+    #[test]
+    fn test_attribute_values_default_to_neutral() {
+        let mut player = make_player("");
+        assert_eq!(player.stamina_value(), 3.0);
+        assert_eq!(player.age_value(), 1.5);
+
+        player.stamina = Some(5);
+        player.age_band = Some("45+".to_string());
+        assert_eq!(player.stamina_value(), 5.0);
+        assert_eq!(player.age_value(), 3.0);
+    }
+
+    #[test]
+    fn test_parse_attribute_level() {
+        assert_eq!(parse_attribute_level(Some("4")), Some(4));
+        assert_eq!(parse_attribute_level(Some("")), None);
+        assert_eq!(parse_attribute_level(Some("6")), None);
+        assert_eq!(parse_attribute_level(None), None);
+    }
+
+    #[test]
+    fn test_rsvp_status_roundtrip() {
+        for status in RsvpStatus::ALL {
+            assert_eq!(RsvpStatus::from_str(status.as_str()), Some(status));
+        }
+        assert_eq!(RsvpStatus::from_str("IN"), None);
+    }
+
+    #[test]
+    fn test_parse_email() {
+        assert_eq!(parse_email("  "), Ok(None));
+        assert_eq!(
+            parse_email(" sam@example.com "),
+            Ok(Some("sam@example.com".to_string()))
+        );
+        assert!(parse_email("sam").is_err());
+        assert!(parse_email("@example.com").is_err());
+        assert!(parse_email("sam@localhost").is_err());
+        assert!(parse_email("sam@example..com").is_err());
+        assert!(parse_email("sam smith@example.com").is_err());
+    }
+
+    #[test]
+    fn test_parse_pin() {
+        assert_eq!(parse_pin("", ""), Ok(None));
+        assert_eq!(parse_pin("1234", "1234"), Ok(Some("1234".to_string())));
+        assert!(parse_pin("123", "123").is_err());
+        assert!(parse_pin("12a4", "12a4").is_err());
+        assert!(parse_pin("123456789", "123456789").is_err());
+        assert_eq!(
+            parse_pin("1234", "4321"),
+            Err("PINs don't match".to_string())
+        );
+        assert!(parse_pin("", "1234").is_err());
+    }
+
+    #[test]
+    fn test_clean_note() {
+        assert_eq!(clean_note("  rained all game \n"), "rained all game");
+        assert_eq!(clean_note(""), "");
+        assert_eq!(clean_note(&"x".repeat(5000)).len(), MAX_NOTE_LENGTH);
+    }
+
+    #[test]
+    fn test_player_sort_roundtrip() {
+        for sort in [
+            PlayerSort::Name,
+            PlayerSort::Number,
+            PlayerSort::Elo,
+            PlayerSort::Matches,
+        ] {
+            assert_eq!(PlayerSort::from_str(sort.as_str()), Some(sort));
+        }
+        assert_eq!(PlayerSort::from_str("created_at; DROP TABLE"), None);
+        for status in [
+            PlayerStatus::All,
+            PlayerStatus::Available,
+            PlayerStatus::Suspended,
+            PlayerStatus::Injured,
+        ] {
+            assert_eq!(PlayerStatus::from_str(status.as_str()), status);
+        }
+        assert_eq!(PlayerStatus::from_str("bogus"), PlayerStatus::All);
+    }
+
+    #[test]
+    fn test_parse_bulk_line() {
+        let player = NewPlayer::parse_line("  Sam, 1250, def ATK ").unwrap();
+        assert_eq!(player.name, "Sam");
+        assert_eq!(player.elo, Some(1250.0));
+        assert_eq!(player.tags.as_deref(), Some("DEF,ATK"));
+
+        let player = NewPlayer::parse_line("Alex").unwrap();
+        assert_eq!(player.elo, None);
+        assert_eq!(player.tags.as_deref(), Some(""));
+
+        let player = NewPlayer::parse_line("Jo, GK/DEF, GK").unwrap();
+        assert_eq!(player.tags.as_deref(), Some("GK,DEF"));
+    }
+
+    #[test]
+    fn test_parse_participation() {
+        assert_eq!(parse_participation("7=0.17"), Ok((7, 0.17)));
+        assert_eq!(parse_participation("7=1"), Ok((7, 1.0)));
+        assert!(parse_participation("7=0").is_err());
+        assert!(parse_participation("7=1.5").is_err());
+        assert!(parse_participation("7=NaN").is_err());
+        assert!(parse_participation("Sam=0.5").is_err());
+        assert!(parse_participation("0.5").is_err());
+    }
+
+    #[test]
+    fn test_parse_minutes() {
+        let (id, share) = parse_minutes("7=20-55", 60).unwrap();
+        assert_eq!(id, 7);
+        assert!((share - 35.0 / 60.0).abs() < 0.001);
+        // Last 10 of 60 minutes
+        let (_, share) = parse_minutes("7=50-", 60).unwrap();
+        assert!((share - 1.0 / 6.0).abs() < 0.001);
+        assert_eq!(parse_minutes("7=-45", 90), Ok((7, 0.5)));
+
+        assert!(parse_minutes("7=55-20", 60).is_err());
+        assert!(parse_minutes("7=30-30", 60).is_err());
+        assert!(parse_minutes("7=0-70", 60).is_err());
+        assert!(parse_minutes("7=20", 60).is_err());
+        assert!(parse_minutes("7=a-b", 60).is_err());
+    }
+
+    #[test]
+    fn test_parse_goal() {
+        assert_eq!(
+            parse_goal("a:7:12"),
+            Ok(Goal {
+                team: Side::A,
+                scorer: Some(7),
+                minute: Some(12),
+            })
+        );
+        // Scorer and minute are optional
+        let unknown = parse_goal("b::").unwrap();
+        assert_eq!(
+            (unknown.team, unknown.scorer, unknown.minute),
+            (Side::B, None, None)
+        );
+        assert_eq!(
+            goal_score(&[parse_goal("a:7:").unwrap(), unknown, unknown]),
+            (1, 2)
+        );
+
+        assert!(parse_goal("c:7:12").is_err());
+        assert!(parse_goal("a:7").is_err());
+        assert!(parse_goal("a:x:12").is_err());
+        assert!(parse_goal("a:7:181").is_err());
+        assert!(parse_goal("a:7:12:3").is_err());
+    }
+
+    #[test]
+    fn test_parse_imported_match() {
+        let imported = ImportedMatch::parse_line(
+            "2023-09-10, Sam; Alex ;Jo, Kim;Lee, 3-2, Rainy, short on bibs",
+        )
+        .unwrap();
+        assert_eq!(
+            imported.played_at,
+            NaiveDate::from_ymd_opt(2023, 9, 10).unwrap()
+        );
+        assert_eq!(imported.team_a, vec!["Sam", "Alex", "Jo"]);
+        assert_eq!(imported.team_b, vec!["Kim", "Lee"]);
+        assert_eq!((imported.score_a, imported.score_b), (3, 2));
+        assert_eq!(imported.notes, "Rainy, short on bibs");
+
+        let imported = ImportedMatch::parse_line("2023-09-17,Sam,Kim,0 - 0").unwrap();
+        assert_eq!(imported.notes, "");
+    }
+
+    #[test]
+    fn test_parse_imported_match_errors() {
+        assert!(ImportedMatch::parse_line("2023-09-10, Sam, Kim").is_err());
+        assert!(ImportedMatch::parse_line("10/09/2023, Sam, Kim, 1-0").is_err());
+        assert!(ImportedMatch::parse_line("2023-09-10, , Kim, 1-0").is_err());
+        assert!(ImportedMatch::parse_line("2023-09-10, Sam, Kim, 1:0").is_err());
+        assert!(ImportedMatch::parse_line("2023-09-10, Sam, Kim, 1-99").is_err());
+        assert!(ImportedMatch::parse_line("2023-09-10, Sam, sam, 1-0").is_err());
+    }
+
+    #[test]
+    fn test_parse_legacy_rating() {
+        assert_eq!(
+            LegacyRating::parse_line("Sam, 1340").unwrap(),
+            LegacyRating {
+                name: "Sam".to_string(),
+                elo: 1340.0,
+                as_of: None,
+            }
+        );
+        let dated = LegacyRating::parse_line(" Alex ,1185.5, 2023-08-27 ").unwrap();
+        assert_eq!(dated.name, "Alex");
+        assert_eq!(dated.as_of, NaiveDate::from_ymd_opt(2023, 8, 27));
+
+        assert!(LegacyRating::parse_line("Sam").is_err());
+        assert!(LegacyRating::parse_line(", 1200").is_err());
+        assert!(LegacyRating::parse_line("Sam, 5000").is_err());
+        assert!(LegacyRating::parse_line("Sam, good").is_err());
+        assert!(LegacyRating::parse_line("Sam, 1200, 27/08/2023").is_err());
+        assert!(LegacyRating::parse_line("Sam, 1200, 2023-08-27, extra").is_err());
+    }
+
+    #[test]
+    fn test_parse_bulk_line_errors() {
+        assert!(NewPlayer::parse_line(", 1200").is_err());
+        assert!(NewPlayer::parse_line("Sam, 5000").is_err());
+        assert!(NewPlayer::parse_line("Sam, 1200, 1300").is_err());
+        assert!(NewPlayer::parse_line("Sam, STRIKER").is_err());
+    }
+
     #[test]
     fn test_elo_snapshot_default_participation() {
         let json = r#"{"before": 1200.0, "delta": 16.0}"#;
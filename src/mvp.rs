@@ -0,0 +1,132 @@
+use crate::elo::expected_score;
+use crate::models::{EloSnapshot, Match};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A suggested player of the match
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Suggestion {
+    pub player_id: i32,
+    /// How far the result beat what their rating predicted (see `contribution`)
+    pub contribution: f32,
+}
+
+/// How far a player's result beat expectations: their team's actual score
+/// (1 win, 0.5 draw, 0 loss) minus the score expected from their own
+/// pre-match Elo against the other team's average, scaled by participation
+pub fn contribution(change: &EloSnapshot, actual: f32, opponent_elo: f32) -> f32 {
+    (actual - expected_score(change.before, opponent_elo)) * change.participation
+}
+
+/// Average pre-match Elo of a team (None if no one on it was rated)
+fn average_before(team: &[i32], snapshot: &HashMap<i32, EloSnapshot>) -> Option<f32> {
+    let before: Vec<f32> = team
+        .iter()
+        .filter_map(|id| snapshot.get(id).map(|s| s.before))
+        .collect();
+    (!before.is_empty()).then(|| before.iter().sum::<f32>() / before.len() as f32)
+}
+
+/// Suggest a match's MVP: whoever beat expectations by the most. Goals aren't
+/// recorded, so this comes from ratings alone; None if nobody beat them.
+pub fn suggest(m: &Match) -> Option<Suggestion> {
+    let snapshot: HashMap<i32, EloSnapshot> =
+        serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+    let actual_a = match m.score_a.cmp(&m.score_b) {
+        Ordering::Greater => 1.0,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.0,
+    };
+    let elo_a = average_before(&m.team_a, &snapshot)?;
+    let elo_b = average_before(&m.team_b, &snapshot)?;
+    let snapshot = &snapshot;
+
+    [
+        (&m.team_a, actual_a, elo_b),
+        (&m.team_b, 1.0 - actual_a, elo_a),
+    ]
+    .into_iter()
+    .flat_map(|(team, actual, opponent_elo)| {
+        team.iter().filter_map(move |id| {
+            snapshot.get(id).map(|change| Suggestion {
+                player_id: *id,
+                contribution: contribution(change, actual, opponent_elo),
+            })
+        })
+    })
+    .filter(|s| s.contribution > 0.0)
+    // Lower IDs win ties
+    .max_by(|a, b| {
+        a.contribution
+            .total_cmp(&b.contribution)
+            .then(b.player_id.cmp(&a.player_id))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    fn make_match(score: (i32, i32), before: &[(i32, f32, f32)]) -> Match {
+        let snapshot: serde_json::Map<String, serde_json::Value> = before
+            .iter()
+            .map(|(id, elo, participation)| {
+                (
+                    id.to_string(),
+                    json!({"before": elo, "delta": 0.0, "participation": participation}),
+                )
+            })
+            .collect();
+        Match {
+            id: 1,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, 2).unwrap(),
+            team_a: vec![1, 2],
+            team_b: vec![3, 4],
+            score_a: score.0,
+            score_b: score.1,
+            elo_snapshot: json!(snapshot),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_underdog_on_the_winning_team() {
+        let before = [
+            (1, 1300.0, 1.0),
+            (2, 1100.0, 1.0),
+            (3, 1200.0, 1.0),
+            (4, 1200.0, 1.0),
+        ];
+        let suggestion = suggest(&make_match((3, 1), &before)).unwrap();
+        assert_eq!(suggestion.player_id, 2);
+        assert!((suggestion.contribution - 0.64).abs() < 0.01);
+
+        // A losing side's players never beat expectations
+        assert_eq!(suggest(&make_match((1, 3), &before)).unwrap().player_id, 3);
+    }
+
+    #[test]
+    fn test_participation_scales_contribution() {
+        let before = [
+            (1, 1200.0, 1.0),
+            (2, 1100.0, 0.25),
+            (3, 1200.0, 1.0),
+            (4, 1200.0, 1.0),
+        ];
+        assert_eq!(suggest(&make_match((2, 0), &before)).unwrap().player_id, 1);
+    }
+
+    #[test]
+    fn test_draw_between_equals_has_no_mvp() {
+        let before = [
+            (1, 1200.0, 1.0),
+            (2, 1200.0, 1.0),
+            (3, 1200.0, 1.0),
+            (4, 1200.0, 1.0),
+        ];
+        assert_eq!(suggest(&make_match((2, 2), &before)), None);
+        assert_eq!(suggest(&make_match((2, 1), &[])), None);
+    }
+}
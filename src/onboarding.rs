@@ -0,0 +1,228 @@
+use crate::models::{Position, MAX_STARTING_ELO, MIN_STARTING_ELO};
+use std::fmt;
+
+/// Fitness answer treated as average (adds nothing to the starting Elo)
+const AVERAGE_FITNESS: i32 = 3;
+
+/// How much football a new player has played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Experience {
+    New,
+    Casual,
+    Regular,
+    Club,
+}
+
+impl Experience {
+    pub const ALL: [Experience; 4] = [
+        Experience::New,
+        Experience::Casual,
+        Experience::Regular,
+        Experience::Club,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Experience::New => "new",
+            Experience::Casual => "casual",
+            Experience::Regular => "regular",
+            Experience::Club => "club",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == s.trim())
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Experience::New => "New to football",
+            Experience::Casual => "The odd kickabout",
+            Experience::Regular => "Plays most weeks",
+            Experience::Club => "Played club or league football",
+        }
+    }
+
+    /// Steps above "new" (0-3)
+    fn level(self) -> f32 {
+        match self {
+            Experience::New => 0.0,
+            Experience::Casual => 1.0,
+            Experience::Regular => 2.0,
+            Experience::Club => 3.0,
+        }
+    }
+}
+
+/// How questionnaire answers turn into a starting Elo:
+/// `base + experience × level + fitness × (answer - 3) + keeper (GK only)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OnboardingFormula {
+    pub base: f32,
+    /// Elo per experience step (new 0, casual 1, regular 2, club 3)
+    pub experience: f32,
+    /// Elo per fitness point above or below 3
+    pub fitness: f32,
+    /// Elo added for goalkeepers
+    pub keeper: f32,
+}
+
+impl Default for OnboardingFormula {
+    fn default() -> Self {
+        Self {
+            base: 1100.0,
+            experience: 100.0,
+            fitness: 25.0,
+            keeper: 0.0,
+        }
+    }
+}
+
+impl OnboardingFormula {
+    /// Parse "base=1100,experience=100" over `self`; unnamed parts keep their value
+    pub fn parse(self, value: &str) -> Option<Self> {
+        let mut formula = self;
+        for pair in value.split(',').filter(|p| !p.trim().is_empty()) {
+            let (name, amount) = pair.split_once('=')?;
+            let amount: f32 = amount.trim().parse().ok().filter(|a: &f32| a.is_finite())?;
+            match name.trim() {
+                "base" => formula.base = amount,
+                "experience" => formula.experience = amount,
+                "fitness" => formula.fitness = amount,
+                "keeper" => formula.keeper = amount,
+                _ => return None,
+            }
+        }
+        Some(formula)
+    }
+}
+
+impl fmt::Display for OnboardingFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "base={},experience={},fitness={},keeper={}",
+            self.base, self.experience, self.fitness, self.keeper
+        )
+    }
+}
+
+/// A new player's questionnaire answers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Answers {
+    pub experience: Experience,
+    pub position: Option<Position>,
+    /// 1-5 (None = not answered, counted as average)
+    pub fitness: Option<i32>,
+}
+
+impl Answers {
+    /// Each part of the starting Elo with its name, in the order they add up
+    fn parts(&self, formula: &OnboardingFormula) -> Vec<(String, f32)> {
+        let mut parts = vec![
+            ("base".to_string(), formula.base),
+            (
+                format!("experience ({})", self.experience.label().to_lowercase()),
+                formula.experience * self.experience.level(),
+            ),
+        ];
+        if let Some(fitness) = self.fitness {
+            parts.push((
+                format!("fitness ({}/5)", fitness),
+                formula.fitness * (fitness - AVERAGE_FITNESS) as f32,
+            ));
+        }
+        if self.position == Some(Position::Gk) {
+            parts.push(("goalkeeper".to_string(), formula.keeper));
+        }
+        parts
+    }
+
+    /// Starting Elo from the formula, kept within the allowed starting range
+    pub fn starting_elo(&self, formula: &OnboardingFormula) -> f32 {
+        let total: f32 = self.parts(formula).iter().map(|(_, elo)| elo).sum();
+        total.clamp(MIN_STARTING_ELO, MAX_STARTING_ELO)
+    }
+
+    /// How the starting Elo was worked out, e.g.
+    /// "1100 base + 200 experience (plays most weeks) + 25 fitness (4/5) = 1325"
+    pub fn explain(&self, formula: &OnboardingFormula) -> String {
+        let mut text = String::new();
+        for (i, (name, elo)) in self
+            .parts(formula)
+            .iter()
+            .filter(|(_, elo)| *elo != 0.0)
+            .enumerate()
+        {
+            match i {
+                0 => text.push_str(&format!("{:.0} {}", elo, name)),
+                _ if *elo < 0.0 => text.push_str(&format!(" - {:.0} {}", -elo, name)),
+                _ => text.push_str(&format!(" + {:.0} {}", elo, name)),
+            }
+        }
+        format!("{} = {:.0}", text, self.starting_elo(formula))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starting_elo() {
+        let formula = OnboardingFormula::default();
+        let answers = Answers {
+            experience: Experience::Regular,
+            position: Some(Position::Mid),
+            fitness: Some(4),
+        };
+        assert_eq!(answers.starting_elo(&formula), 1325.0);
+        assert_eq!(
+            answers.explain(&formula),
+            "1100 base + 200 experience (plays most weeks) + 25 fitness (4/5) = 1325"
+        );
+
+        let keeper = Answers {
+            experience: Experience::New,
+            position: Some(Position::Gk),
+            fitness: Some(1),
+        };
+        let formula = OnboardingFormula {
+            keeper: 75.0,
+            ..formula
+        };
+        assert_eq!(keeper.starting_elo(&formula), 1125.0);
+        assert_eq!(
+            keeper.explain(&formula),
+            "1100 base - 50 fitness (1/5) + 75 goalkeeper = 1125"
+        );
+    }
+
+    #[test]
+    fn test_starting_elo_stays_in_range() {
+        let answers = Answers {
+            experience: Experience::Club,
+            position: None,
+            fitness: None,
+        };
+        let formula = OnboardingFormula {
+            experience: 500.0,
+            ..Default::default()
+        };
+        assert_eq!(answers.starting_elo(&formula), MAX_STARTING_ELO);
+    }
+
+    #[test]
+    fn test_parse_formula() {
+        let base = OnboardingFormula::default();
+        let formula = base.parse("base=1000, keeper=50").unwrap();
+        assert_eq!(formula.base, 1000.0);
+        assert_eq!(formula.experience, base.experience);
+        assert_eq!(formula.keeper, 50.0);
+        assert_eq!(base.parse(&formula.to_string()), Some(formula));
+
+        assert!(base.parse("skill=10").is_none());
+        assert!(base.parse("base").is_none());
+        assert!(base.parse("base=lots").is_none());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::security::DOCS_CONTENT_SECURITY_POLICY;
+use crate::views::layout::asset;
+use axum::{
+    http::header,
+    response::{Html, IntoResponse},
+    Json,
+};
+use maud::{html, DOCTYPE};
+use utoipa::OpenApi;
+
+/// OpenAPI document for the HTTP API
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Sunday Football Manager API",
+        description = "Endpoints used by the web UI. Most return htmx HTML fragments."
+    ),
+    paths(
+        crate::auth::login,
+        crate::auth::logout,
+        crate::views::roster::edit_player,
+        crate::views::roster::create_player,
+        crate::views::roster::create_players_bulk,
+        crate::views::roster::update_player,
+        crate::views::roster::delete_player,
+        crate::views::roster::suspend_player,
+        crate::views::roster::lift_suspension,
+        crate::views::roster::record_injury,
+        crate::views::roster::clear_injury,
+        crate::views::tag_suggestions::accept_tag_suggestion,
+        crate::views::tag_suggestions::dismiss_tag_suggestion,
+        crate::views::recalibration::recalibrate,
+        crate::privacy::export_player_data,
+        crate::privacy::anonymize_player,
+        crate::views::invite::create_invite,
+        crate::views::invite::claim,
+        crate::views::join::register,
+        crate::views::join::approve,
+        crate::views::join::reject,
+        crate::views::me::login,
+        crate::views::me::logout,
+        crate::views::me::rsvp,
+        crate::views::me::rate_teammate,
+        crate::views::me::set_visibility,
+        crate::views::match_day::generate_teams,
+        crate::views::match_day::shuffle_teams,
+        crate::views::match_day::compare_balancers,
+        crate::views::match_day::tournament_squads,
+        crate::views::match_day::add_late_arrival,
+        crate::views::match_day::share_text,
+        crate::views::match_day::coin_toss,
+        crate::views::match_day::toss_coin,
+        crate::predict::predict_match,
+        crate::views::record::submit_result,
+        crate::hooks::record,
+        crate::views::history::amend_match,
+        crate::views::pending::confirm_pending,
+        crate::views::pending::discard_pending,
+        crate::views::history::void_match,
+        crate::views::history::restore_match,
+        crate::views::history::award_mvp,
+        crate::views::history::add_comment,
+        crate::views::flags::flag_match,
+        crate::views::history::import_matches,
+        crate::views::history::import_ratings,
+        crate::views::seasons::preview_rollover,
+        crate::views::seasons::end_season,
+        crate::views::fragments::player_list,
+        crate::views::fragments::history,
+        crate::views::fragments::match_card,
+        crate::views::fragments::team_panel,
+        crate::stats::stats,
+        crate::stats::player_history_csv,
+        crate::analytics::appearances_jsonl,
+        crate::analytics::appearances_parquet,
+        crate::events::stream,
+        crate::views::admin::save_settings,
+        crate::views::admin::reset_settings,
+        crate::views::admin::what_if,
+        crate::views::webhooks::create_webhook,
+        crate::views::webhooks::delete_webhook,
+        crate::views::jobs::retry_job,
+        crate::views::jobs::dismiss_job,
+        crate::views::flags::resolve_flag,
+    ),
+    tags(
+        (name = "auth", description = "Login and logout"),
+        (name = "players", description = "Roster management"),
+        (name = "teams", description = "Team generation"),
+        (name = "matches", description = "Match results"),
+        (name = "seasons", description = "Season rollover and archived standings"),
+        (name = "fragments", description = "Page sections for htmx to lazy-load or refresh"),
+        (name = "stats", description = "Public JSON stats for embedding"),
+        (name = "events", description = "Live updates for open pages"),
+        (name = "admin", description = "Group settings and rating what-ifs"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Serve the OpenAPI document as JSON
+pub async fn spec() -> impl IntoResponse {
+    Json(ApiDoc::openapi())
+}
+
+/// Serve Swagger UI pointed at the OpenAPI document
+pub async fn docs() -> impl IntoResponse {
+    (
+        [(
+            header::CONTENT_SECURITY_POLICY,
+            DOCS_CONTENT_SECURITY_POLICY,
+        )],
+        Html(
+            html! {
+                (DOCTYPE)
+                html lang="en" {
+                    head {
+                        meta charset="utf-8";
+                        meta name="viewport" content="width=device-width, initial-scale=1";
+                        title { "API Docs - Sunday Manager" }
+                        link rel="stylesheet" href="/static/vendor/swagger-ui.css";
+                    }
+                    body {
+                        div id="swagger-ui" {}
+                        script src="/static/vendor/swagger-ui-bundle.js" {}
+                        script src=(asset("js/api-docs.js")) {}
+                    }
+                }
+            }
+            .into_string(),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_lists_api_paths() {
+        let spec = ApiDoc::openapi();
+        let paths: Vec<_> = spec.paths.paths.keys().map(|p| p.as_str()).collect();
+        assert!(paths.contains(&"/api/players"));
+        assert!(paths.contains(&"/api/players/{id}"));
+        assert!(paths.contains(&"/api/record"));
+        assert!(paths.contains(&"/fragments/players"));
+        assert!(paths.contains(&"/fragments/matches/{id}"));
+        assert!(paths.contains(&"/fragments/teams"));
+        assert!(paths.contains(&"/api/stats"));
+        assert!(paths.contains(&"/api/predict"));
+        assert!(paths.contains(&"/api/registrations/{id}/approve"));
+        assert!(paths.contains(&"/api/invites/{token}"));
+        assert!(paths.contains(&"/api/me/rsvp"));
+        assert!(paths.contains(&"/api/me/peer-ratings"));
+        assert!(paths.contains(&"/api/me/visibility"));
+        assert!(paths.contains(&"/teams/share"));
+        assert!(paths.contains(&"/api/admin/webhooks/{id}"));
+        assert!(paths.contains(&"/api/hooks/record"));
+        assert!(paths.contains(&"/api/admin/jobs/{id}/retry"));
+        assert!(paths.contains(&"/api/matches/{id}"));
+        assert!(paths.contains(&"/api/pending/{id}/confirm"));
+        assert!(paths.contains(&"/api/matches/{id}/flags"));
+        assert!(paths.contains(&"/api/players/{id}/injury/clear"));
+        assert!(paths.contains(&"/api/players/{id}/tag-suggestions/accept"));
+        assert!(paths.contains(&"/api/players/{id}/recalibrate"));
+        assert!(paths.contains(&"/api/players/{id}/history.csv"));
+        assert!(paths.contains(&"/api/export/appearances.jsonl"));
+        assert!(paths.contains(&"/api/export/appearances.parquet"));
+        assert!(paths.contains(&"/api/ratings/import"));
+        assert!(paths.contains(&"/api/players/{id}/anonymize"));
+        assert!(paths.contains(&"/api/matches/{id}/mvp"));
+        assert!(paths.contains(&"/api/toss"));
+        assert!(paths.contains(&"/api/tournament/squads"));
+    }
+}
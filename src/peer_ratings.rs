@@ -0,0 +1,183 @@
+use crate::models::{Match, PeerRating};
+use crate::{db, AppState};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Different raters a player needs before their peer rating is shown, so no
+/// single answer can be picked out
+pub const MIN_RATERS: usize = 3;
+/// Monthly surveys counted, the current one included
+const SURVEY_MONTHS: u32 = 6;
+/// How far back a shared match makes someone a teammate to rate
+const TEAMMATE_DAYS: i64 = 90;
+/// Elo added per point of peer rating above the middle of the 1-5 scale
+/// when balancing with peer ratings
+pub const PEER_WEIGHT: f32 = 50.0;
+const MIDDLE_RATING: f32 = 3.0;
+
+/// A player's averaged survey answers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeerScore {
+    pub skill: f32,
+    pub stamina: f32,
+    pub pace: f32,
+    pub raters: usize,
+}
+
+impl PeerScore {
+    /// The three attributes averaged (1-5)
+    pub fn overall(&self) -> f32 {
+        (self.skill + self.stamina + self.pace) / 3.0
+    }
+
+    /// Elo added for balancing (PEER_WEIGHT per point above 3)
+    pub fn balance_bonus(&self) -> f32 {
+        (self.overall() - MIDDLE_RATING) * PEER_WEIGHT
+    }
+}
+
+/// The survey a rating given today belongs to: surveys run by calendar month
+pub fn survey_period(today: NaiveDate) -> NaiveDate {
+    today.with_day(1).unwrap_or(today)
+}
+
+/// First survey still counted towards peer ratings
+pub fn counted_since(today: NaiveDate) -> NaiveDate {
+    let period = survey_period(today);
+    period
+        .checked_sub_months(Months::new(SURVEY_MONTHS - 1))
+        .unwrap_or(period)
+}
+
+/// Everyone who played in a non-voided match with `player_id` (on either
+/// side) in the last 90 days, by ID
+pub fn teammates(matches: &[Match], player_id: i32, today: NaiveDate) -> Vec<i32> {
+    let since = today - Duration::days(TEAMMATE_DAYS);
+    let mut ids = BTreeSet::new();
+    for m in matches
+        .iter()
+        .filter(|m| m.voided_at.is_none() && m.played_at >= since)
+    {
+        let players = m.team_a.iter().chain(&m.team_b);
+        if players.clone().any(|id| *id == player_id) {
+            ids.extend(players.filter(|id| **id != player_id));
+        }
+    }
+    ids.into_iter().collect()
+}
+
+/// Average each player's ratings, counting only each rater's latest answer
+/// (ratings come newest survey first) and leaving out anyone with fewer than
+/// MIN_RATERS raters
+pub fn aggregate(ratings: &[PeerRating]) -> HashMap<i32, PeerScore> {
+    let mut seen = HashSet::new();
+    let mut answers: HashMap<i32, Vec<&PeerRating>> = HashMap::new();
+    for rating in ratings {
+        if seen.insert((rating.rater_id, rating.player_id)) {
+            answers.entry(rating.player_id).or_default().push(rating);
+        }
+    }
+    answers
+        .into_iter()
+        .filter(|(_, answers)| answers.len() >= MIN_RATERS)
+        .map(|(id, answers)| {
+            let n = answers.len() as f32;
+            let average =
+                |f: fn(&PeerRating) -> i32| answers.iter().map(|r| f(r) as f32).sum::<f32>() / n;
+            let score = PeerScore {
+                skill: average(|r| r.skill),
+                stamina: average(|r| r.stamina),
+                pace: average(|r| r.pace),
+                raters: answers.len(),
+            };
+            (id, score)
+        })
+        .collect()
+}
+
+/// Load everyone's peer rating from the surveys still counted (empty if the
+/// data can't be loaded)
+pub async fn load_peer_scores(state: &AppState) -> HashMap<i32, PeerScore> {
+    let since = counted_since(state.settings.get().today());
+    match db::get_peer_ratings_since(&state.db, since).await {
+        Ok(ratings) => aggregate(&ratings),
+        Err(e) => {
+            tracing::error!("Failed to load peer ratings: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn rating(rater_id: i32, player_id: i32, month: u32, level: i32) -> PeerRating {
+        PeerRating {
+            rater_id,
+            player_id,
+            period: NaiveDate::from_ymd_opt(2025, month, 1).unwrap(),
+            skill: level,
+            stamina: level,
+            pace: level,
+        }
+    }
+
+    #[test]
+    fn test_survey_periods() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 18).unwrap();
+        assert_eq!(
+            survey_period(today),
+            NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()
+        );
+        assert_eq!(
+            counted_since(today),
+            NaiveDate::from_ymd_opt(2024, 10, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_aggregate_needs_enough_raters() {
+        // Rater 2 answered twice; only their March answer counts
+        let ratings = vec![
+            rating(2, 1, 3, 5),
+            rating(3, 1, 3, 4),
+            rating(2, 1, 2, 1),
+            rating(4, 1, 2, 3),
+            rating(2, 5, 3, 5),
+            rating(3, 5, 3, 5),
+        ];
+        let scores = aggregate(&ratings);
+
+        assert_eq!(scores[&1].raters, 3);
+        assert_eq!(scores[&1].overall(), 4.0);
+        assert_eq!(scores[&1].balance_bonus(), PEER_WEIGHT);
+        assert!(!scores.contains_key(&5));
+    }
+
+    #[test]
+    fn test_teammates() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 18).unwrap();
+        let make_match = |id, played_at, team_a: Vec<i32>, team_b: Vec<i32>| Match {
+            id,
+            played_at,
+            team_a,
+            team_b,
+            score_a: 1,
+            score_b: 0,
+            ..Default::default()
+        };
+        let mut voided = make_match(3, today, vec![1, 6], vec![7]);
+        voided.voided_at = Some(Utc::now());
+        let matches = vec![
+            make_match(1, today, vec![1, 2], vec![3, 4]),
+            make_match(2, today - Duration::days(120), vec![1, 5], vec![2]),
+            make_match(4, today, vec![8], vec![9]),
+            voided,
+        ];
+
+        assert_eq!(teammates(&matches, 1, today), vec![2, 3, 4]);
+        assert!(teammates(&matches, 5, today).is_empty());
+    }
+}
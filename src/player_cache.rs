@@ -0,0 +1,138 @@
+use crate::models::Player;
+use crate::{db, AppState};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long loaded players are reused. Mutations clear them straight away, so
+/// this only bounds what the app isn't told about: `sfm` commands run against
+/// the database, and `days_away` moving on at midnight.
+const TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default)]
+struct Cached {
+    /// Bumped on every invalidation, so a load that raced one isn't kept
+    generation: u64,
+    players: Option<(Instant, Vec<Player>)>,
+}
+
+/// Short-lived copy of `db::get_all_players` for pages and read-only API
+/// calls. Anything that writes ratings or players reads the database itself.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerCache(Arc<Mutex<Cached>>);
+
+impl PlayerCache {
+    /// All players ordered by Elo (descending), from the cache while it's fresh
+    pub async fn get(&self, pool: &PgPool) -> Result<Vec<Player>, sqlx::Error> {
+        let generation = match self.cached() {
+            Ok(players) => return Ok(players),
+            Err(generation) => generation,
+        };
+        let players = db::get_all_players(pool).await?;
+        self.store(generation, players.clone());
+        Ok(players)
+    }
+
+    /// Forget the cached players (after a change to players or results)
+    pub fn invalidate(&self) {
+        let mut cached = self.lock();
+        cached.generation += 1;
+        cached.players = None;
+    }
+
+    /// The cached players if fresh, otherwise the generation to store a load under
+    fn cached(&self) -> Result<Vec<Player>, u64> {
+        let cached = self.lock();
+        match &cached.players {
+            Some((loaded_at, players)) if loaded_at.elapsed() < TTL => Ok(players.clone()),
+            _ => Err(cached.generation),
+        }
+    }
+
+    /// Keep players loaded under `generation`, unless they were invalidated meanwhile
+    fn store(&self, generation: u64, players: Vec<Player>) {
+        let mut cached = self.lock();
+        if cached.generation == generation {
+            cached.players = Some((Instant::now(), players));
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cached> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Middleware for mutation routes: forget the cached players after every
+/// change, here and (through the event bus) on other instances
+pub async fn invalidate_after(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    forget_players(&state, &response);
+    response
+}
+
+/// Invalidate after a successful mutation. A refused one (401, 422, 429...)
+/// changed nothing, so it shouldn't cost every instance a reload.
+fn forget_players(state: &AppState, response: &Response) {
+    if response.status().is_success() {
+        state.players.invalidate();
+        state.events.players_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    fn make_players() -> Vec<Player> {
+        vec![Player {
+            id: 1,
+            name: "Alice".to_string(),
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn test_invalidate_drops_cached_players() {
+        let cache = PlayerCache::default();
+        let generation = cache.cached().unwrap_err();
+        cache.store(generation, make_players());
+        assert_eq!(cache.cached().map(|p| p.len()), Ok(1));
+
+        cache.invalidate();
+        assert_eq!(cache.cached().map(|p| p.len()), Err(generation + 1));
+    }
+
+    #[tokio::test]
+    async fn test_refused_mutation_keeps_cached_players() {
+        let state = AppState::for_tests();
+        let generation = state.players.cached().unwrap_err();
+        state.players.store(generation, make_players());
+
+        let refused = StatusCode::UNPROCESSABLE_ENTITY.into_response();
+        forget_players(&state, &refused);
+        assert_eq!(state.players.cached().map(|p| p.len()), Ok(1));
+
+        forget_players(&state, &StatusCode::OK.into_response());
+        assert!(state.players.cached().is_err());
+    }
+
+    #[test]
+    fn test_load_racing_an_invalidation_is_not_kept() {
+        let cache = PlayerCache::default();
+        let generation = cache.cached().unwrap_err();
+        // A mutation lands while the players are loading
+        cache.invalidate();
+        cache.store(generation, make_players());
+        assert!(cache.cached().is_err());
+    }
+}
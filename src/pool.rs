@@ -0,0 +1,116 @@
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Longest wait between connection attempts at startup
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Connection pool settings, loaded from the environment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    /// How long a request waits for a free connection before failing
+    pub acquire_timeout: Duration,
+    /// Idle connections are closed after this long
+    pub idle_timeout: Duration,
+    /// Connection attempts at startup before giving up
+    pub connect_attempts: u32,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(600),
+            connect_attempts: 10,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Load from DB_MAX_CONNECTIONS, DB_MIN_CONNECTIONS, DB_ACQUIRE_TIMEOUT_SECS,
+    /// DB_IDLE_TIMEOUT_SECS and DB_CONNECT_ATTEMPTS, falling back to defaults
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        let defaults = Self::default();
+        let max_connections = var("DB_MAX_CONNECTIONS")
+            .filter(|n| *n > 0)
+            .map_or(defaults.max_connections, |n| n as u32);
+        Self {
+            max_connections,
+            min_connections: var("DB_MIN_CONNECTIONS")
+                .map_or(defaults.min_connections, |n| n as u32)
+                .min(max_connections),
+            acquire_timeout: var("DB_ACQUIRE_TIMEOUT_SECS")
+                .filter(|s| *s > 0)
+                .map_or(defaults.acquire_timeout, Duration::from_secs),
+            idle_timeout: var("DB_IDLE_TIMEOUT_SECS")
+                .filter(|s| *s > 0)
+                .map_or(defaults.idle_timeout, Duration::from_secs),
+            connect_attempts: var("DB_CONNECT_ATTEMPTS")
+                .filter(|n| *n > 0)
+                .map_or(defaults.connect_attempts, |n| n as u32),
+        }
+    }
+
+    fn options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+            // Check connections before handing them out, so ones dropped by a
+            // database restart are replaced instead of failing a request
+            .test_before_acquire(true)
+    }
+}
+
+/// Delay before connection attempt `attempt + 1`: 1s, 2s, 4s... capped at 30s
+pub fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.min(5)).min(MAX_RETRY_DELAY)
+}
+
+/// Connect to the database, retrying with backoff while it comes up
+/// (e.g. when Postgres and the app start together in docker-compose)
+pub async fn connect(database_url: &str, config: &PoolConfig) -> Result<PgPool, sqlx::Error> {
+    let mut attempt = 0;
+    loop {
+        match config.options().connect(database_url).await {
+            Ok(pool) => return Ok(pool),
+            Err(e) if attempt + 1 < config.connect_attempts => {
+                let delay = retry_delay(attempt);
+                tracing::warn!(
+                    "Database not reachable (attempt {}/{}): {}. Retrying in {}s",
+                    attempt + 1,
+                    config.connect_attempts,
+                    e,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(0), Duration::from_secs(1));
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(4), Duration::from_secs(16));
+        assert_eq!(retry_delay(5), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(40), MAX_RETRY_DELAY);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::db;
+use crate::models::{EloSnapshot, Match, Player, Position, Tag};
+use crate::replay::chronological;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Outfield role a player's tags put them in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Role {
+    Defence,
+    Midfield,
+    Attack,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Defence, Role::Midfield, Role::Attack];
+
+    /// Role a tag plays in (GK is balanced separately)
+    pub fn from_tag(tag: Tag) -> Option<Role> {
+        match tag {
+            Tag::Def => Some(Role::Defence),
+            Tag::Playmaker | Tag::Runner => Some(Role::Midfield),
+            Tag::Atk => Some(Role::Attack),
+            Tag::Gk => None,
+        }
+    }
+
+    /// Role a position plays in (GK is balanced separately)
+    pub fn from_position(position: Position) -> Option<Role> {
+        match position {
+            Position::Def => Some(Role::Defence),
+            Position::Mid => Some(Role::Midfield),
+            Position::Att => Some(Role::Attack),
+            Position::Gk => None,
+        }
+    }
+
+    /// Short label for tables
+    pub fn short(&self) -> &'static str {
+        match self {
+            Role::Defence => "DEF",
+            Role::Midfield => "MID",
+            Role::Attack => "ATK",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Role::Defence => write!(f, "Defence"),
+            Role::Midfield => write!(f, "Midfield"),
+            Role::Attack => write!(f, "Attack"),
+        }
+    }
+}
+
+/// Distinct roles for a comma-separated tag string, in [`Role::ALL`] order
+pub fn roles_for_tags(tags: &str) -> Vec<Role> {
+    let mut roles: Vec<Role> = tags
+        .split(',')
+        .filter_map(Tag::from_str)
+        .filter_map(Role::from_tag)
+        .collect();
+    roles.sort();
+    roles.dedup();
+    roles
+}
+
+/// A player's current roles: their position's role if one is set, otherwise
+/// the roles of their tags
+pub fn roles_for_player(player: &Player) -> Vec<Role> {
+    match player.position() {
+        Some(position) => Role::from_position(position).into_iter().collect(),
+        None => roles_for_tags(&player.tags),
+    }
+}
+
+/// Per-role rating for each player, keyed by player ID.
+///
+/// A role rating starts at the player's initial Elo and collects the Elo
+/// changes from matches where they were fielded in that role (taken from the
+/// tags stored in the match snapshot, or their current roles for older
+/// matches). Roles a player has never been fielded in are absent.
+pub fn role_ratings(
+    players: &[Player],
+    initial_elo: &HashMap<i32, f32>,
+    matches: &[Match],
+) -> HashMap<i32, HashMap<Role, f32>> {
+    let current_roles: HashMap<i32, Vec<Role>> = players
+        .iter()
+        .map(|p| (p.id, roles_for_player(p)))
+        .collect();
+    let mut ratings: HashMap<i32, HashMap<Role, f32>> = HashMap::new();
+
+    for m in chronological(matches) {
+        if m.voided_at.is_some() {
+            continue;
+        }
+        let snapshot: HashMap<i32, EloSnapshot> =
+            serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+
+        for (id, change) in &snapshot {
+            let Some(roles) = current_roles.get(id) else {
+                continue;
+            };
+            let fielded = if change.tags.is_empty() {
+                roles.clone()
+            } else {
+                roles_for_tags(&change.tags)
+            };
+            let start = initial_elo.get(id).copied().unwrap_or(change.before);
+            for role in fielded {
+                *ratings.entry(*id).or_default().entry(role).or_insert(start) +=
+                    change.delta * change.participation;
+            }
+        }
+    }
+
+    ratings
+}
+
+/// A player's rating in their current roles (the mean if several), or None if
+/// they have no rated role
+pub fn current_position_elo(player: &Player, ratings: &HashMap<Role, f32>) -> Option<f32> {
+    let rated: Vec<f32> = roles_for_player(player)
+        .into_iter()
+        .filter_map(|role| ratings.get(&role).copied())
+        .collect();
+    (!rated.is_empty()).then(|| rated.iter().sum::<f32>() / rated.len() as f32)
+}
+
+/// Load role ratings for all players from the full match history
+pub async fn load_role_ratings(pool: &PgPool) -> HashMap<i32, HashMap<Role, f32>> {
+    let players = db::get_all_players(pool).await.unwrap_or_default();
+    let initial_elo = db::get_initial_elos(pool).await.unwrap_or_default();
+    let matches = db::get_all_matches(pool).await.unwrap_or_default();
+    role_ratings(&players, &initial_elo, &matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    fn make_player(id: i32, tags: &str) -> Player {
+        Player {
+            id,
+            name: format!("P{}", id),
+            elo: 1200.0,
+            tags: tags.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn make_match(id: i32, day: u32, snapshot: serde_json::Value) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            team_a: vec![1],
+            team_b: vec![2],
+            score_a: 1,
+            score_b: 0,
+            elo_snapshot: snapshot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_roles_for_tags() {
+        assert_eq!(roles_for_tags("ATK,DEF"), vec![Role::Defence, Role::Attack]);
+        assert_eq!(roles_for_tags("PLAYMAKER,RUNNER"), vec![Role::Midfield]);
+        assert!(roles_for_tags("GK").is_empty());
+        assert!(roles_for_tags("").is_empty());
+    }
+
+    #[test]
+    fn test_ratings_follow_fielded_tags() {
+        let players = vec![make_player(1, "ATK")];
+        let initial = HashMap::from([(1, 1200.0)]);
+        let matches = vec![
+            // Fielded in defence, won
+            make_match(
+                1,
+                1,
+                json!({ "1": { "before": 1200.0, "delta": 10.0, "tags": "DEF" } }),
+            ),
+            // Fielded in attack, lost
+            make_match(
+                2,
+                8,
+                json!({ "1": { "before": 1210.0, "delta": -8.0, "tags": "ATK" } }),
+            ),
+        ];
+
+        let ratings = role_ratings(&players, &initial, &matches);
+
+        assert_eq!(ratings[&1][&Role::Defence], 1210.0);
+        assert_eq!(ratings[&1][&Role::Attack], 1192.0);
+        assert!(!ratings[&1].contains_key(&Role::Midfield));
+        // Currently tagged ATK, so the attack rating is the one used
+        assert_eq!(
+            current_position_elo(&players[0], &ratings[&1]),
+            Some(1192.0)
+        );
+    }
+
+    #[test]
+    fn test_old_snapshots_use_current_tags() {
+        let players = vec![make_player(1, "DEF")];
+        let initial = HashMap::from([(1, 1200.0)]);
+        let matches = vec![make_match(
+            1,
+            1,
+            json!({ "1": { "before": 1200.0, "delta": 16.0, "participation": 0.5 } }),
+        )];
+
+        let ratings = role_ratings(&players, &initial, &matches);
+
+        assert_eq!(ratings[&1][&Role::Defence], 1208.0);
+    }
+
+    #[test]
+    fn test_position_overrides_tags() {
+        let mut player = make_player(1, "ATK,PLAYMAKER");
+        assert_eq!(
+            roles_for_player(&player),
+            vec![Role::Midfield, Role::Attack]
+        );
+        player.position = Some("DEF".to_string());
+        assert_eq!(roles_for_player(&player), vec![Role::Defence]);
+        player.position = Some("GK".to_string());
+        assert!(roles_for_player(&player).is_empty());
+    }
+
+    #[test]
+    fn test_untagged_players_have_no_position_elo() {
+        let player = make_player(1, "");
+        assert_eq!(current_position_elo(&player, &HashMap::new()), None);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::auth::AuthJar;
+use crate::elo::{expected_score, handicap, keeper_handicap, rated_team_elo, EloConfig};
+use crate::models::{Match, Player};
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::{db, AppState};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Draw rate assumed before any matches are recorded
+const PRIOR_DRAW_RATE: f32 = 0.2;
+/// How many matches the prior draw rate is worth against the recorded ones
+const PRIOR_MATCHES: f32 = 10.0;
+
+/// Two teams to predict a match between
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct PredictRequest {
+    /// Player IDs on Team A
+    pub team_a: Vec<i32>,
+    /// Player IDs on Team B
+    pub team_b: Vec<i32>,
+}
+
+/// Predicted outcome of a match between two teams
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Prediction {
+    pub team_a: Vec<String>,
+    pub team_b: Vec<String>,
+    /// Team Elo as used when rating a match (calibrating players count for less)
+    pub elo_a: f32,
+    pub elo_b: f32,
+    /// Elo handicap on Team A for being short or having no goalkeeper
+    /// (negative if Team B is)
+    pub handicap_a: f32,
+    /// Elo expected score: win = 1, draw = ½, loss = 0
+    pub expected_score_a: f32,
+    pub expected_score_b: f32,
+    pub win_a: f32,
+    pub draw: f32,
+    pub win_b: f32,
+}
+
+/// Share of non-voided matches drawn, pulled toward a 20% prior while there
+/// are few matches
+pub fn draw_rate(matches: &[Match]) -> f32 {
+    let counted: Vec<&Match> = matches.iter().filter(|m| m.voided_at.is_none()).collect();
+    let draws = counted.iter().filter(|m| m.score_a == m.score_b).count() as f32;
+    (draws + PRIOR_DRAW_RATE * PRIOR_MATCHES) / (counted.len() as f32 + PRIOR_MATCHES)
+}
+
+/// Predict a match from the rating model.
+///
+/// The expected score is what the Elo update uses (including the handicaps
+/// for a short team or a missing goalkeeper). It is split into outcomes with the draw chance peaking
+/// at `draw_rate` for an even match and shrinking as it gets one-sided:
+/// `draw = 2 × draw_rate × min(E, 1 − E)`, and each team's win chance is its
+/// expected score less half the draw chance.
+pub fn predict(
+    team_a: &[Player],
+    team_b: &[Player],
+    draw_rate: f32,
+    config: &EloConfig,
+) -> Prediction {
+    let elo_a = rated_team_elo(team_a);
+    let elo_b = rated_team_elo(team_b);
+    let handicap_a = handicap(team_b.len() as f32 - team_a.len() as f32, config)
+        + keeper_handicap(team_a, team_b, config);
+    let expected_a = expected_score(elo_a - handicap_a, elo_b);
+    let draw = 2.0 * draw_rate.clamp(0.0, 1.0) * expected_a.min(1.0 - expected_a);
+    let names = |team: &[Player]| team.iter().map(|p| p.name.clone()).collect();
+
+    Prediction {
+        team_a: names(team_a),
+        team_b: names(team_b),
+        elo_a,
+        elo_b,
+        handicap_a,
+        expected_score_a: expected_a,
+        expected_score_b: 1.0 - expected_a,
+        win_a: expected_a - draw / 2.0,
+        draw,
+        win_b: 1.0 - expected_a - draw / 2.0,
+    }
+}
+
+/// Win/draw chances and expected score for any two teams of players, e.g. to
+/// see what swapping two players would do
+#[utoipa::path(
+    post,
+    path = "/api/predict",
+    tag = "teams",
+    request_body = PredictRequest,
+    responses(
+        (status = 200, description = "Predicted outcome", body = Prediction),
+        (status = 422, description = "Empty, overlapping or unknown teams", content_type = "text/plain"),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn predict_match(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Json(request): Json<PredictRequest>,
+) -> Response {
+    if request.team_a.is_empty() || request.team_b.is_empty() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, "Both teams need players").into_response();
+    }
+    let ids_a: HashSet<i32> = request.team_a.iter().copied().collect();
+    let ids_b: HashSet<i32> = request.team_b.iter().copied().collect();
+    if ids_a.len() != request.team_a.len()
+        || ids_b.len() != request.team_b.len()
+        || !ids_a.is_disjoint(&ids_b)
+    {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "A player can only be listed once",
+        )
+            .into_response();
+    }
+
+    let (Ok(mut team_a), Ok(mut team_b), Ok(matches)) = (
+        db::get_players_by_ids(&state.db, &request.team_a).await,
+        db::get_players_by_ids(&state.db, &request.team_b).await,
+        db::get_all_matches(&state.db).await,
+    ) else {
+        tracing::error!("Failed to load players for a prediction");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if team_a.len() != ids_a.len() || team_b.len() != ids_b.len() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, "Unknown player ID").into_response();
+    }
+    let show_hidden = shows_hidden_names(&jar, &state);
+    mask_names(&mut team_a, show_hidden);
+    mask_names(&mut team_b, show_hidden);
+
+    let prediction = predict(
+        &team_a,
+        &team_b,
+        draw_rate(&matches),
+        &state.settings.get().elo,
+    );
+    Json(prediction).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    fn make_team(ids: std::ops::RangeInclusive<i32>, elo: f32) -> Vec<Player> {
+        ids.map(|id| Player {
+            id,
+            name: format!("P{}", id),
+            elo,
+            matches_played: 10,
+            ..Default::default()
+        })
+        .collect()
+    }
+
+    fn make_match(score_a: i32, score_b: i32) -> Match {
+        Match {
+            id: 1,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, 30).unwrap(),
+            team_a: vec![1],
+            team_b: vec![2],
+            score_a,
+            score_b,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_even_teams() {
+        let prediction = predict(
+            &make_team(1..=7, 1200.0),
+            &make_team(8..=14, 1200.0),
+            0.2,
+            &EloConfig::default(),
+        );
+        assert_eq!(prediction.expected_score_a, 0.5);
+        assert!((prediction.draw - 0.2).abs() < 1e-6);
+        assert!((prediction.win_a - 0.4).abs() < 1e-6);
+        assert!((prediction.win_b - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stronger_team_favoured() {
+        let prediction = predict(
+            &make_team(1..=7, 1400.0),
+            &make_team(8..=14, 1200.0),
+            0.2,
+            &EloConfig::default(),
+        );
+        assert!(prediction.win_a > prediction.win_b);
+        assert!(prediction.draw < 0.2);
+        let total = prediction.win_a + prediction.draw + prediction.win_b;
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_short_team_handicapped() {
+        let prediction = predict(
+            &make_team(1..=6, 1200.0),
+            &make_team(8..=14, 1200.0),
+            0.2,
+            &EloConfig::default(),
+        );
+        assert!(prediction.handicap_a > 0.0);
+        assert!(prediction.expected_score_a < 0.5);
+    }
+
+    #[test]
+    fn test_draw_rate() {
+        assert_eq!(draw_rate(&[]), PRIOR_DRAW_RATE);
+        let mut matches = vec![make_match(2, 2); 10];
+        matches.extend(vec![make_match(3, 1); 10]);
+        // 10 draws in 20, plus the prior's 2 in 10
+        assert!((draw_rate(&matches) - 0.4).abs() < 1e-6);
+        matches[0].voided_at = Some(Utc::now());
+        assert!(draw_rate(&matches) < 0.4);
+    }
+}
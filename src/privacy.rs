@@ -0,0 +1,245 @@
+use crate::auth::{current_player, is_authenticated, AuthJar};
+use crate::models::{
+    Injury, MatchComment, MatchFlag, OnboardingAnswers, PeerRating, Player, RatingBaseline, Rsvp,
+    Suspension,
+};
+use crate::stats::{player_history, HistoryRow};
+use crate::views::errors::error_fragment;
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Name a player is given when anonymized
+pub fn anonymized_name(player_id: i32) -> String {
+    format!("Player #{}", player_id)
+}
+
+/// Name shown to anonymous visitors for a player who hides theirs
+pub fn pseudonym(player_id: i32) -> String {
+    format!("Anonymous #{}", player_id)
+}
+
+/// Whether the request may see hidden names: a logged-in admin or player
+/// (everyone when the site has no password)
+pub fn shows_hidden_names(jar: &AuthJar, state: &AppState) -> bool {
+    is_authenticated(jar, state) || current_player(jar).is_some()
+}
+
+/// Swap in pseudonyms for players who hide their name, unless the viewer
+/// may see them. Call this on players loaded for any public page.
+pub fn mask_names(players: &mut [Player], show_hidden: bool) {
+    if show_hidden {
+        return;
+    }
+    for player in players.iter_mut().filter(|p| p.hide_name) {
+        player.name = pseudonym(player.id);
+    }
+}
+
+/// A player's account details, without the PIN hash
+#[derive(Debug, Serialize)]
+pub struct AccountExport {
+    pub email: Option<String>,
+    pub notify_teams: bool,
+    pub notify_results: bool,
+    pub has_pin: bool,
+    pub claimed_at: DateTime<Utc>,
+}
+
+/// Everything the app holds about one player
+#[derive(Debug, Serialize)]
+pub struct PlayerDataExport {
+    pub exported_at: DateTime<Utc>,
+    pub player: Player,
+    pub account: Option<AccountExport>,
+    /// Private admin note (only in exports an admin downloads)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin_note: Option<String>,
+    pub rating_baseline: Option<RatingBaseline>,
+    /// Questionnaire answers the starting Elo came from
+    pub onboarding: Option<OnboardingAnswers>,
+    /// Non-voided matches with the rating change from each, oldest first
+    pub matches: Vec<HistoryRow>,
+    pub rsvps: Vec<Rsvp>,
+    pub injuries: Vec<Injury>,
+    pub suspensions: Vec<Suspension>,
+    /// Comments and flags left under the player's name
+    pub comments: Vec<MatchComment>,
+    pub flags: Vec<MatchFlag>,
+    /// Peer survey answers the player gave, only in their own export so
+    /// nobody else sees who rated whom (answers about them stay anonymous)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_ratings_given: Option<Vec<PeerRating>>,
+}
+
+/// Gather everything held about a player (None if they don't exist). The
+/// private admin note is only included for an admin, and the peer survey
+/// answers they gave only for the player themself.
+async fn collect(
+    state: &AppState,
+    id: i32,
+    admin: bool,
+    own: bool,
+) -> Result<Option<PlayerDataExport>, sqlx::Error> {
+    let Some(player) = db::get_players_by_ids(&state.db, &[id])
+        .await?
+        .into_iter()
+        .next()
+    else {
+        return Ok(None);
+    };
+    let account = db::get_player_account(&state.db, id)
+        .await?
+        .map(|account| AccountExport {
+            email: account.email,
+            notify_teams: account.notify_teams,
+            notify_results: account.notify_results,
+            has_pin: account.pin_hash.is_some(),
+            claimed_at: account.claimed_at,
+        });
+    let matches = db::get_all_matches(&state.db).await?;
+    let comments = db::get_all_comments(&state.db)
+        .await?
+        .into_iter()
+        .filter(|c| c.author.eq_ignore_ascii_case(&player.name))
+        .collect();
+
+    Ok(Some(PlayerDataExport {
+        exported_at: Utc::now(),
+        account,
+        admin_note: if admin {
+            db::get_player_notes(&state.db).await?.remove(&id)
+        } else {
+            None
+        },
+        rating_baseline: db::get_rating_baselines(&state.db)
+            .await?
+            .into_iter()
+            .find(|b| b.player_id == id),
+        onboarding: db::get_onboarding(&state.db, id).await?,
+        matches: player_history(id, &matches),
+        rsvps: db::get_player_rsvps(&state.db, id).await?,
+        injuries: db::get_player_injuries(&state.db, id).await?,
+        suspensions: db::get_player_suspensions(&state.db, id).await?,
+        comments,
+        flags: db::get_flags_by(&state.db, &player.name).await?,
+        peer_ratings_given: if own {
+            Some(db::get_peer_ratings_by(&state.db, id).await?)
+        } else {
+            None
+        },
+        player,
+    }))
+}
+
+/// Download everything held about a player as JSON (admins, or the player themself)
+#[utoipa::path(
+    get,
+    path = "/api/players/{id}/data",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    responses(
+        (status = 200, description = "Profile, account (without the PIN), admin note (admins only), rating baseline, onboarding answers, matches, RSVPs, injuries, suspensions, comments, flags and peer survey answers given (the player's own export only)", content_type = "application/json"),
+        (status = 401, description = "Not an admin or this player", content_type = "text/html"),
+        (status = 404, description = "Player not found", content_type = "text/html")
+    )
+)]
+pub async fn export_player_data(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    let admin = is_authenticated(&jar, &state);
+    let own = current_player(&jar) == Some(id);
+    if !admin && !own {
+        return crate::auth::unauthorized();
+    }
+
+    match collect(&state, id, admin, own).await {
+        Ok(Some(export)) => (
+            [(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"player-{id}-data.json\""),
+            )],
+            Json(export),
+        )
+            .into_response(),
+        Ok(None) => error_fragment(StatusCode::NOT_FOUND, "Player not found"),
+        Err(e) => {
+            tracing::error!("Failed to export data for player {}: {}", id, e);
+            error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to export data")
+        }
+    }
+}
+
+/// Anonymize a departed player, keeping their matches and ratings (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/players/{id}/anonymize",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    responses(
+        (status = 200, description = "Anonymized; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Player not found", content_type = "text/html")
+    )
+)]
+pub async fn anonymize_player(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    match db::anonymize_player(&state.db, id, &anonymized_name(id)).await {
+        Ok(true) => {
+            tracing::info!("Player {} anonymized", id);
+            ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+        }
+        Ok(false) => error_fragment(StatusCode::NOT_FOUND, "Player not found"),
+        Err(e) => {
+            tracing::error!("Failed to anonymize player {}: {}", id, e);
+            error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to anonymize player",
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_player(id: i32, name: &str, hide_name: bool) -> Player {
+        Player {
+            id,
+            name: name.to_string(),
+            hide_name,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mask_names() {
+        let players = vec![make_player(1, "Sam", false), make_player(2, "Alex", true)];
+
+        let mut masked = players.clone();
+        mask_names(&mut masked, false);
+        assert_eq!(masked[0].name, "Sam");
+        assert_eq!(masked[1].name, "Anonymous #2");
+
+        let mut shown = players.clone();
+        mask_names(&mut shown, true);
+        assert_eq!(shown[1].name, "Alex");
+    }
+}
@@ -0,0 +1,294 @@
+use crate::views::errors::error_fragment;
+use crate::AppState;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Prune expired entries once the map grows past this many IPs
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Fixed-window request counter per client IP
+#[derive(Clone)]
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    /// Reverse proxies in front of the app that append to X-Forwarded-For
+    /// (0 = use the socket address)
+    proxy_hops: usize,
+    hits: Arc<Mutex<HashMap<IpAddr, (Instant, u32)>>>,
+}
+
+impl RateLimiter {
+    /// `limit` requests per `window`; a limit of 0 disables limiting
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            proxy_hops: 0,
+            hits: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Load limits from RATE_LIMIT_REQUESTS / RATE_LIMIT_WINDOW_SECS (default 30 per 60s)
+    /// and the proxy count from TRUSTED_PROXY_HOPS (default 0)
+    pub fn from_env() -> Self {
+        let limit = std::env::var("RATE_LIMIT_REQUESTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+        let window_secs = std::env::var("RATE_LIMIT_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+        let proxy_hops = std::env::var("TRUSTED_PROXY_HOPS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        Self {
+            proxy_hops,
+            ..Self::new(limit, Duration::from_secs(window_secs))
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.limit > 0
+    }
+
+    /// Record a hit for `ip`. Returns `Err(retry_after)` if the limit is exceeded.
+    pub fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        if !self.enabled() {
+            return Ok(());
+        }
+
+        let mut hits = self.hits.lock().unwrap();
+        if hits.len() > PRUNE_THRESHOLD {
+            let window = self.window;
+            hits.retain(|_, (start, _)| now.duration_since(*start) < window);
+        }
+
+        let entry = hits.entry(ip).or_insert((now, 0));
+        if now.duration_since(entry.0) >= self.window {
+            *entry = (now, 0);
+        }
+
+        if entry.1 >= self.limit {
+            return Err(self.window - now.duration_since(entry.0));
+        }
+        entry.1 += 1;
+        Ok(())
+    }
+}
+
+/// Wrong PINs allowed per player before their login is locked
+pub const PIN_MAX_FAILURES: u32 = 5;
+/// How long a player's wrong PINs count, and how long the lock lasts
+pub const PIN_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Failed PIN logins per player. A 4-digit PIN is only 10,000 guesses, which
+/// the per-IP limit alone would let a handful of addresses get through, so
+/// after `max_failures` wrong PINs within `window` the player is locked out
+/// until it ends, whichever PIN is sent.
+#[derive(Clone)]
+pub struct PinLockout {
+    max_failures: u32,
+    window: Duration,
+    failures: Arc<Mutex<HashMap<i32, (Instant, u32)>>>,
+}
+
+impl Default for PinLockout {
+    fn default() -> Self {
+        Self::new(PIN_MAX_FAILURES, PIN_LOCKOUT)
+    }
+}
+
+impl PinLockout {
+    pub fn new(max_failures: u32, window: Duration) -> Self {
+        Self {
+            max_failures,
+            window,
+            failures: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Run `pin_ok` for `player_id` unless they're locked out. A wrong PIN
+    /// counts towards the lock and a right one clears the count.
+    pub fn attempt(&self, player_id: i32, now: Instant, pin_ok: impl FnOnce() -> bool) -> bool {
+        let mut failures = self.failures.lock().unwrap();
+        if failures.len() > PRUNE_THRESHOLD {
+            let window = self.window;
+            failures.retain(|_, (start, _)| now.duration_since(*start) < window);
+        }
+        if let Some((start, count)) = failures.get(&player_id) {
+            if now.duration_since(*start) >= self.window {
+                failures.remove(&player_id);
+            } else if *count >= self.max_failures {
+                return false;
+            }
+        }
+
+        if pin_ok() {
+            failures.remove(&player_id);
+            true
+        } else {
+            failures.entry(player_id).or_insert((now, 0)).1 += 1;
+            false
+        }
+    }
+}
+
+/// Client IP. Each trusted proxy appends the address it got the request from
+/// to X-Forwarded-For, so the client is the `proxy_hops`-th entry from the
+/// right; anything further left was sent by the client and can't be trusted.
+/// With no proxies (or a request that skipped them) it's the socket address.
+fn client_ip(
+    headers: &HeaderMap,
+    connect_info: Option<&ConnectInfo<SocketAddr>>,
+    proxy_hops: usize,
+) -> Option<IpAddr> {
+    let entries: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .collect();
+    let forwarded = proxy_hops
+        .checked_sub(1)
+        .and_then(|skip| entries.iter().rev().nth(skip))
+        .and_then(|ip| ip.trim().parse().ok());
+    forwarded.or_else(|| connect_info.map(|ci| ci.0.ip()))
+}
+
+/// Middleware rejecting requests over the per-IP limit with 429
+pub async fn limit_by_ip(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = client_ip(
+        request.headers(),
+        request.extensions().get::<ConnectInfo<SocketAddr>>(),
+        state.rate_limiter.proxy_hops,
+    );
+
+    if let Some(ip) = ip {
+        if let Err(retry_after) = state.rate_limiter.check(ip, Instant::now()) {
+            tracing::warn!("Rate limit exceeded for {}", ip);
+            return (
+                [(
+                    header::RETRY_AFTER,
+                    retry_after.as_secs().max(1).to_string(),
+                )],
+                error_fragment(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "Too many requests. Please wait a moment.",
+                ),
+            )
+                .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_allows_up_to_limit() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(limiter.check(ip("10.0.0.1"), now).is_ok());
+        assert!(limiter.check(ip("10.0.0.1"), now).is_ok());
+        assert!(limiter.check(ip("10.0.0.1"), now).is_ok());
+        assert!(limiter.check(ip("10.0.0.1"), now).is_err());
+    }
+
+    #[test]
+    fn test_limits_are_per_ip() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(limiter.check(ip("10.0.0.1"), now).is_ok());
+        assert!(limiter.check(ip("10.0.0.2"), now).is_ok());
+        assert!(limiter.check(ip("10.0.0.1"), now).is_err());
+    }
+
+    #[test]
+    fn test_window_resets() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(limiter.check(ip("10.0.0.1"), now).is_ok());
+        assert!(limiter
+            .check(ip("10.0.0.1"), now + Duration::from_secs(30))
+            .is_err());
+        assert!(limiter
+            .check(ip("10.0.0.1"), now + Duration::from_secs(61))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_zero_limit_disables() {
+        let limiter = RateLimiter::new(0, Duration::from_secs(60));
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert!(limiter.check(ip("10.0.0.1"), now).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_pin_lockout_refuses_the_right_pin_after_too_many_wrong_ones() {
+        let lockout = PinLockout::new(3, Duration::from_secs(900));
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(!lockout.attempt(7, now, || false));
+        }
+        assert!(!lockout.attempt(7, now, || true));
+        // Other players are unaffected, and the lock ends with the window
+        assert!(lockout.attempt(8, now, || true));
+        assert!(lockout.attempt(7, now + Duration::from_secs(901), || true));
+    }
+
+    #[test]
+    fn test_pin_lockout_clears_on_a_right_pin() {
+        let lockout = PinLockout::new(3, Duration::from_secs(900));
+        let now = Instant::now();
+        assert!(!lockout.attempt(7, now, || false));
+        assert!(!lockout.attempt(7, now, || false));
+        assert!(lockout.attempt(7, now, || true));
+        assert!(!lockout.attempt(7, now, || false));
+        assert!(!lockout.attempt(7, now, || false));
+        assert!(lockout.attempt(7, now, || true));
+    }
+
+    #[test]
+    fn test_client_ip_takes_the_entry_the_proxy_added() {
+        let mut headers = HeaderMap::new();
+        // The client sent a made-up address; the proxy appended the real one
+        headers.insert(
+            "x-forwarded-for",
+            "198.51.100.1, 203.0.113.7".parse().unwrap(),
+        );
+        let ci = ConnectInfo("10.0.0.1:1234".parse::<SocketAddr>().unwrap());
+        assert_eq!(client_ip(&headers, Some(&ci), 1), Some(ip("203.0.113.7")));
+        assert_eq!(client_ip(&headers, Some(&ci), 2), Some(ip("198.51.100.1")));
+        // No trusted proxy: the header is ignored
+        assert_eq!(client_ip(&headers, Some(&ci), 0), Some(ip("10.0.0.1")));
+        // Fewer entries than proxies (or none): the socket address
+        assert_eq!(client_ip(&headers, Some(&ci), 3), Some(ip("10.0.0.1")));
+        assert_eq!(
+            client_ip(&HeaderMap::new(), Some(&ci), 1),
+            Some(ip("10.0.0.1"))
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use crate::elo::expected_score;
+use crate::models::{
+    EloSnapshot, Match, Player, CALIBRATION_WEIGHT, MAX_STARTING_ELO, MIN_STARTING_ELO,
+};
+use crate::replay::chronological;
+use std::collections::{HashMap, HashSet};
+
+/// Matches a new player needs before their starting Elo can be re-estimated
+pub const RECALIBRATION_MATCHES: usize = 5;
+/// Smallest gap between the estimate and the current starting Elo worth offering
+const MIN_CHANGE: f32 = 25.0;
+/// Rounds of bisection (the search range shrinks 2^40 times)
+const SEARCH_ROUNDS: usize = 40;
+
+/// A player whose first results suggest a different starting Elo
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recalibration {
+    pub player_id: i32,
+    pub current: f32,
+    pub estimate: f32,
+    /// Wins, draws and losses in the matches the estimate is based on
+    pub record: (usize, usize, usize),
+}
+
+/// A player's first non-voided matches, oldest first
+pub fn first_matches(matches: &[Match], player_id: i32) -> Vec<&Match> {
+    chronological(matches)
+        .into_iter()
+        .filter(|m| m.voided_at.is_none())
+        .filter(|m| m.team_a.contains(&player_id) || m.team_b.contains(&player_id))
+        .take(RECALIBRATION_MATCHES)
+        .collect()
+}
+
+/// The player's team result (1, 0.5 or 0) and expected score in a match if
+/// they had been rated `elo`, with everyone else at their recorded rating
+/// and the recorded handicaps and calibration weights. None if the match has
+/// no snapshot for them.
+fn result_at(m: &Match, player_id: i32, elo: f32) -> Option<(f32, f32)> {
+    let snapshot: HashMap<i32, EloSnapshot> =
+        serde_json::from_value(m.elo_snapshot.clone()).ok()?;
+    let own = snapshot.get(&player_id)?;
+    let (team, opponents, scored, conceded) = if m.team_a.contains(&player_id) {
+        (&m.team_a, &m.team_b, m.score_a, m.score_b)
+    } else {
+        (&m.team_b, &m.team_a, m.score_b, m.score_a)
+    };
+    let team_elo = |ids: &[i32]| {
+        let (mut total, mut weights) = (0.0, 0.0);
+        for id in ids {
+            let Some(s) = snapshot.get(id) else { continue };
+            let weight = if s.calibrating {
+                CALIBRATION_WEIGHT
+            } else {
+                1.0
+            };
+            total += weight * if *id == player_id { elo } else { s.before };
+            weights += weight;
+        }
+        if weights == 0.0 {
+            0.0
+        } else {
+            total / weights
+        }
+    };
+    let own_elo = team_elo(team) - own.handicap - own.keeper_handicap;
+    let actual = match scored.cmp(&conceded) {
+        std::cmp::Ordering::Greater => 1.0,
+        std::cmp::Ordering::Equal => 0.5,
+        std::cmp::Ordering::Less => 0.0,
+    };
+    Some((actual, expected_score(own_elo, team_elo(opponents))))
+}
+
+/// Back-solve the starting Elo at which the player's expected results in
+/// `matches` add up to what actually happened. One imagined draw against
+/// `current` is added, so five straight wins or losses don't fly off to the
+/// ends of the 800-2000 range.
+pub fn estimate_starting_elo(matches: &[&Match], player_id: i32, current: f32) -> f32 {
+    let gap = |elo: f32| -> f32 {
+        let mut gap = expected_score(elo, current) - 0.5;
+        for m in matches {
+            if let Some((actual, expected)) = result_at(m, player_id, elo) {
+                gap += expected - actual;
+            }
+        }
+        gap
+    };
+    // Expected scores only grow with Elo, so halve the range towards the zero
+    let (mut low, mut high) = (MIN_STARTING_ELO, MAX_STARTING_ELO);
+    for _ in 0..SEARCH_ROUNDS {
+        let mid = (low + high) / 2.0;
+        if gap(mid) > 0.0 {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    ((low + high) / 2.0).round()
+}
+
+/// Players with exactly enough matches behind them whose results point to a
+/// starting Elo at least 25 away from theirs. Players already recalibrated
+/// or with an imported rating baseline are left alone.
+pub fn recalibrations(
+    players: &[Player],
+    initial_elo: &HashMap<i32, f32>,
+    matches: &[Match],
+    skip: &HashSet<i32>,
+) -> Vec<Recalibration> {
+    players
+        .iter()
+        .filter(|p| p.matches_played as usize >= RECALIBRATION_MATCHES && !skip.contains(&p.id))
+        .filter_map(|p| {
+            let current = initial_elo.get(&p.id).copied()?;
+            let first = first_matches(matches, p.id);
+            if first.len() < RECALIBRATION_MATCHES {
+                return None;
+            }
+            let estimate = estimate_starting_elo(&first, p.id, current);
+            if (estimate - current).abs() < MIN_CHANGE {
+                return None;
+            }
+            let mut record = (0, 0, 0);
+            for (actual, _) in first.iter().filter_map(|m| result_at(m, p.id, current)) {
+                match actual {
+                    1.0 => record.0 += 1,
+                    0.5 => record.1 += 1,
+                    _ => record.2 += 1,
+                }
+            }
+            Some(Recalibration {
+                player_id: p.id,
+                current,
+                estimate,
+                record,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    /// Player 1 (new, calibrating for 3 matches) with 2 on team A against 3 and 4
+    fn make_match(id: i32, score: (i32, i32)) -> Match {
+        let snapshot = |before: f32, calibrating: bool| json!({"before": before, "delta": 0.0, "calibrating": calibrating});
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, id as u32).unwrap(),
+            team_a: vec![1, 2],
+            team_b: vec![3, 4],
+            score_a: score.0,
+            score_b: score.1,
+            elo_snapshot: json!({
+                "1": snapshot(1200.0, id <= 3),
+                "2": snapshot(1300.0, false),
+                "3": snapshot(1300.0, false),
+                "4": snapshot(1300.0, false),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_even_results_point_to_opponents_level() {
+        // Winning and losing in turn against equal teammates and opponents
+        let matches: Vec<Match> = (1..=6)
+            .map(|id| make_match(id, if id % 2 == 0 { (0, 1) } else { (1, 0) }))
+            .collect();
+        let first = first_matches(&matches, 1);
+        assert_eq!(first.len(), RECALIBRATION_MATCHES);
+        // Three wins, two losses and the imagined draw at 1200: a little
+        // above 1300 once the draw is weighed in
+        let estimate = estimate_starting_elo(&first, 1, 1200.0);
+        assert!((1300.0..1400.0).contains(&estimate), "{}", estimate);
+    }
+
+    #[test]
+    fn test_winning_streak_raises_estimate_within_range() {
+        let matches: Vec<Match> = (1..=5).map(|id| make_match(id, (3, 0))).collect();
+        let first = first_matches(&matches, 1);
+        let estimate = estimate_starting_elo(&first, 1, 1200.0);
+        assert!(
+            estimate > 1500.0 && estimate <= MAX_STARTING_ELO,
+            "{}",
+            estimate
+        );
+
+        let players = vec![Player {
+            id: 1,
+            matches_played: 5,
+            ..Default::default()
+        }];
+        let initial = HashMap::from([(1, 1200.0)]);
+        let found = recalibrations(&players, &initial, &matches, &HashSet::new());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].estimate, estimate);
+        assert_eq!(found[0].record, (5, 0, 0));
+
+        // Already recalibrated
+        assert!(recalibrations(&players, &initial, &matches, &HashSet::from([1])).is_empty());
+    }
+}
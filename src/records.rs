@@ -0,0 +1,213 @@
+use crate::models::{EloSnapshot, Match};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// Most matches won in a row by one player
+#[derive(Debug, Clone, PartialEq)]
+pub struct WinStreak {
+    pub player_id: i32,
+    pub wins: usize,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// A record set in one match
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchRecord {
+    pub match_id: i32,
+    pub played_at: NaiveDate,
+    /// The record holder (None for team records)
+    pub player_id: Option<i32>,
+    /// Goal margin, Elo reached or Elo change, depending on the record
+    pub value: f32,
+}
+
+/// All-time club records over non-voided matches. Ties go to whoever set
+/// the record first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClubRecords {
+    pub win_streak: Option<WinStreak>,
+    /// Biggest winning margin in goals
+    pub biggest_win: Option<MatchRecord>,
+    /// Highest Elo anyone reached after a match
+    pub highest_elo: Option<MatchRecord>,
+    /// Most matches played: (player ID, matches)
+    pub most_appearances: Option<(i32, usize)>,
+    /// Biggest Elo change in one match, up or down (scaled by participation)
+    pub biggest_swing: Option<MatchRecord>,
+}
+
+/// Replace `best` with `candidate` if it's strictly bigger
+fn keep_best(best: &mut Option<MatchRecord>, candidate: MatchRecord, key: fn(f32) -> f32) {
+    if best
+        .as_ref()
+        .is_none_or(|b| key(candidate.value) > key(b.value))
+    {
+        *best = Some(candidate);
+    }
+}
+
+/// Work out the club records from the match history
+pub fn club_records(matches: &[Match]) -> ClubRecords {
+    let mut counted: Vec<&Match> = matches.iter().filter(|m| m.voided_at.is_none()).collect();
+    counted.sort_by_key(|m| (m.played_at, m.created_at, m.id));
+
+    let mut records = ClubRecords::default();
+    // Current run of wins per player: (wins, first win's date)
+    let mut streaks: HashMap<i32, (usize, NaiveDate)> = HashMap::new();
+    let mut appearances: HashMap<i32, usize> = HashMap::new();
+
+    for m in counted {
+        let snapshot: HashMap<i32, EloSnapshot> =
+            serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+        let margin = (m.score_a - m.score_b).abs();
+        if margin > 0 {
+            keep_best(
+                &mut records.biggest_win,
+                MatchRecord {
+                    match_id: m.id,
+                    played_at: m.played_at,
+                    player_id: None,
+                    value: margin as f32,
+                },
+                |v| v,
+            );
+        }
+
+        for (team, won) in [
+            (&m.team_a, m.score_a > m.score_b),
+            (&m.team_b, m.score_b > m.score_a),
+        ] {
+            for &id in team {
+                *appearances.entry(id).or_default() += 1;
+
+                if won {
+                    let streak = streaks.entry(id).or_insert((0, m.played_at));
+                    streak.0 += 1;
+                    if records
+                        .win_streak
+                        .as_ref()
+                        .is_none_or(|s| streak.0 > s.wins)
+                    {
+                        records.win_streak = Some(WinStreak {
+                            player_id: id,
+                            wins: streak.0,
+                            from: streak.1,
+                            to: m.played_at,
+                        });
+                    }
+                } else {
+                    streaks.remove(&id);
+                }
+
+                let Some(change) = snapshot.get(&id) else {
+                    continue;
+                };
+                let delta = change.delta * change.participation;
+                let record = |value| MatchRecord {
+                    match_id: m.id,
+                    played_at: m.played_at,
+                    player_id: Some(id),
+                    value,
+                };
+                keep_best(
+                    &mut records.highest_elo,
+                    record(change.before + delta),
+                    |v| v,
+                );
+                keep_best(&mut records.biggest_swing, record(delta), f32::abs);
+            }
+        }
+    }
+
+    records.most_appearances = appearances
+        .into_iter()
+        // Lower IDs win ties
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)));
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn make_match(
+        id: i32,
+        day: u32,
+        team_a: Vec<i32>,
+        team_b: Vec<i32>,
+        score: (i32, i32),
+        snapshot: serde_json::Value,
+    ) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+            team_a,
+            team_b,
+            score_a: score.0,
+            score_b: score.1,
+            elo_snapshot: snapshot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_club_records() {
+        let mut voided = make_match(9, 30, vec![1], vec![2], (10, 0), json!({}));
+        voided.voided_at = Some(Utc::now());
+        let matches = vec![
+            make_match(
+                1,
+                2,
+                vec![1, 2],
+                vec![3],
+                (2, 1),
+                json!({
+                    "1": {"before": 1200.0, "delta": 20.0},
+                    "2": {"before": 1100.0, "delta": 40.0, "participation": 0.5},
+                    "3": {"before": 1300.0, "delta": -30.0}
+                }),
+            ),
+            make_match(
+                2,
+                9,
+                vec![1],
+                vec![3],
+                (5, 1),
+                json!({
+                    "1": {"before": 1220.0, "delta": 25.0},
+                    "3": {"before": 1270.0, "delta": -25.0}
+                }),
+            ),
+            // A draw ends player 1's streak
+            make_match(3, 16, vec![1], vec![2], (1, 1), json!({})),
+            make_match(4, 23, vec![3], vec![2], (0, 3), json!({})),
+            voided,
+        ];
+
+        let records = club_records(&matches);
+
+        let streak = records.win_streak.unwrap();
+        assert_eq!((streak.player_id, streak.wins), (1, 2));
+        assert_eq!(streak.to, NaiveDate::from_ymd_opt(2025, 3, 9).unwrap());
+
+        let win = records.biggest_win.unwrap();
+        assert_eq!((win.match_id, win.value), (2, 4.0));
+
+        let highest = records.highest_elo.unwrap();
+        assert_eq!((highest.player_id, highest.value), (Some(3), 1300.0 - 30.0));
+
+        let swing = records.biggest_swing.unwrap();
+        assert_eq!((swing.player_id, swing.value), (Some(3), -30.0));
+
+        // Everyone played three; the lower ID wins the tie
+        assert_eq!(records.most_appearances, Some((1, 3)));
+    }
+
+    #[test]
+    fn test_no_matches_no_records() {
+        assert_eq!(club_records(&[]), ClubRecords::default());
+    }
+}
@@ -0,0 +1,451 @@
+use crate::db;
+use crate::elo::{calculate_elo_changes, EloConfig};
+use crate::models::{EloSnapshot, Match, Player, Season};
+use crate::seasons::rollover_rating;
+use chrono::NaiveDate;
+use serde_json::json;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// Ratings and snapshots produced by replaying match history
+#[derive(Debug, Default)]
+pub struct Replay {
+    /// Final Elo per player ID
+    pub ratings: HashMap<i32, f32>,
+    /// Recomputed Elo snapshot per match ID (voided matches are skipped)
+    pub snapshots: HashMap<i32, HashMap<i32, EloSnapshot>>,
+}
+
+/// Sort matches oldest first (the order ratings are applied in)
+pub fn chronological(matches: &[Match]) -> Vec<&Match> {
+    let mut sorted: Vec<_> = matches.iter().collect();
+    sorted.sort_by_key(|m| (m.played_at, m.created_at, m.id));
+    sorted
+}
+
+/// Replay all non-voided matches from each player's starting Elo.
+///
+/// Participation is taken from each match's existing snapshot. Players that no
+/// longer exist keep the rating they had at the time of the match, so team
+/// strengths stay faithful to what was recorded. Calibration follows how many
+/// matches each player had played at the time, and the return boost on how
+/// long they had been away then. The rating anchor (`config.anchor`) stays at
+/// their current Elo throughout. Season rollovers (oldest
+/// first) soft-reset every rating before the first match played after them
+/// (or recorded after them on the rollover day).
+#[tracing::instrument(skip_all, fields(matches = matches.len()))]
+pub fn replay(
+    players: &[Player],
+    initial_elo: &HashMap<i32, f32>,
+    matches: &[Match],
+    seasons: &[Season],
+    config: &EloConfig,
+) -> Replay {
+    // The anchor is held at their current rating from the start
+    let mut ratings: HashMap<i32, f32> = players
+        .iter()
+        .map(|p| match initial_elo.get(&p.id) {
+            Some(elo) if config.anchor != Some(p.id) => (p.id, *elo),
+            _ => (p.id, p.elo),
+        })
+        .collect();
+    let by_id: HashMap<i32, &Player> = players.iter().map(|p| (p.id, p)).collect();
+    let mut result = Replay::default();
+    let mut rollovers = seasons.iter().peekable();
+    let mut played: HashMap<i32, i32> = HashMap::new();
+    let mut last_played: HashMap<i32, NaiveDate> = HashMap::new();
+    let reset_all = |ratings: &mut HashMap<i32, f32>, season: &Season| {
+        for (id, elo) in ratings.iter_mut() {
+            *elo = rollover_rating(*id, *elo, season.carry, config.anchor);
+        }
+    };
+
+    for m in chronological(matches) {
+        while let Some(season) =
+            rollovers.next_if(|s| (s.ended_on, s.created_at) < (m.played_at, m.created_at))
+        {
+            reset_all(&mut ratings, season);
+        }
+        if m.voided_at.is_some() {
+            continue;
+        }
+
+        let recorded: HashMap<i32, EloSnapshot> =
+            serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+
+        let field =
+            |ids: &[i32], ratings: &HashMap<i32, f32>, played: &HashMap<i32, i32>| -> Vec<Player> {
+                ids.iter()
+                    .filter_map(|id| {
+                        let elo = ratings
+                            .get(id)
+                            .copied()
+                            .or_else(|| recorded.get(id).map(|s| s.before))?;
+                        let mut player =
+                            by_id
+                                .get(id)
+                                .map(|p| (*p).clone())
+                                .unwrap_or_else(|| Player {
+                                    id: *id,
+                                    elo,
+                                    created_at: m.created_at,
+                                    ..Default::default()
+                                });
+                        player.elo = elo;
+                        player.matches_played = played.get(id).copied().unwrap_or(0);
+                        player.days_away = last_played
+                            .get(id)
+                            .map(|last| (m.played_at - *last).num_days() as i32);
+                        // Keep the tags the player was fielded with at the time
+                        if let Some(snapshot) = recorded.get(id).filter(|s| !s.tags.is_empty()) {
+                            player.tags = snapshot.tags.clone();
+                        }
+                        Some(player)
+                    })
+                    .collect()
+            };
+        let team_a = field(&m.team_a, &ratings, &played);
+        let team_b = field(&m.team_b, &ratings, &played);
+
+        let participation: HashMap<i32, f32> = recorded
+            .iter()
+            .map(|(id, s)| (*id, s.participation))
+            .collect();
+
+        let changes = calculate_elo_changes(
+            &team_a,
+            &team_b,
+            m.score_a,
+            m.score_b,
+            &participation,
+            m.k_multiplier,
+            config,
+        );
+
+        for (id, change) in &changes {
+            if let Some(elo) = ratings.get_mut(id) {
+                *elo = change.before + change.delta * change.participation;
+            }
+            *played.entry(*id).or_default() += 1;
+            last_played.insert(*id, m.played_at);
+        }
+        result.snapshots.insert(m.id, changes);
+    }
+
+    for season in rollovers {
+        reset_all(&mut ratings, season);
+    }
+
+    result.ratings = ratings;
+    result
+}
+
+/// Recompute every player's Elo, every match snapshot and every player's
+/// appearance stats from the full (non-voided) match history in a single
+/// transaction. The history is read inside it under the ratings lock, so a
+/// result saved meanwhile waits rather than being overwritten.
+#[tracing::instrument(skip_all)]
+pub async fn recalculate_all(pool: &PgPool, config: &EloConfig) -> Result<Replay, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    db::lock_ratings(&mut tx).await?;
+    let players = db::get_all_players(&mut *tx).await?;
+    let initial_elo = db::get_initial_elos(&mut *tx).await?;
+    let matches = db::get_all_matches(&mut *tx).await?;
+    let seasons = db::get_seasons(&mut *tx).await?;
+
+    let replayed = replay(&players, &initial_elo, &matches, &seasons, config);
+
+    for player in &players {
+        let elo = replayed
+            .ratings
+            .get(&player.id)
+            .copied()
+            .unwrap_or(player.elo);
+        db::set_player_rating(&mut *tx, player.id, elo).await?;
+    }
+    for (match_id, snapshot) in &replayed.snapshots {
+        let snapshot_json = serde_json::to_value(snapshot).unwrap_or(json!({}));
+        db::update_match_snapshot(&mut *tx, *match_id, snapshot_json).await?;
+    }
+    db::refresh_player_stats(&mut tx, None).await?;
+    tx.commit().await?;
+
+    Ok(replayed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seasons::soft_reset;
+    use chrono::{NaiveDate, Utc};
+
+    fn make_player(id: i32, elo: f32) -> Player {
+        Player {
+            id,
+            name: format!("P{}", id),
+            elo,
+            tags: String::new(),
+            ..Default::default()
+        }
+    }
+
+    fn make_match(
+        id: i32,
+        day: u32,
+        team_a: Vec<i32>,
+        team_b: Vec<i32>,
+        score: (i32, i32),
+    ) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            team_a,
+            team_b,
+            score_a: score.0,
+            score_b: score.1,
+            ..Default::default()
+        }
+    }
+
+    fn initial(players: &[Player]) -> HashMap<i32, f32> {
+        players.iter().map(|p| (p.id, p.elo)).collect()
+    }
+
+    #[test]
+    fn test_replay_matches_incremental_updates() {
+        let players = vec![make_player(1, 1200.0), make_player(2, 1200.0)];
+        let matches = vec![
+            make_match(1, 1, vec![1], vec![2], (2, 1)),
+            make_match(2, 8, vec![1], vec![2], (0, 3)),
+        ];
+
+        let result = replay(
+            &players,
+            &initial(&players),
+            &matches,
+            &[],
+            &EloConfig::default(),
+        );
+
+        // Apply the same two matches by hand
+        let first = calculate_elo_changes(
+            &players[..1],
+            &players[1..],
+            2,
+            1,
+            &HashMap::new(),
+            1.0,
+            &EloConfig::default(),
+        );
+        let a = make_player(1, 1200.0 + first[&1].delta);
+        let b = make_player(2, 1200.0 + first[&2].delta);
+        let second = calculate_elo_changes(
+            std::slice::from_ref(&a),
+            std::slice::from_ref(&b),
+            0,
+            3,
+            &HashMap::new(),
+            1.0,
+            &EloConfig::default(),
+        );
+
+        assert!((result.ratings[&1] - (a.elo + second[&1].delta)).abs() < 0.001);
+        assert!((result.ratings[&2] - (b.elo + second[&2].delta)).abs() < 0.001);
+        assert!(result.snapshots.values().all(|s| s.contains_key(&1)));
+        assert_eq!(result.snapshots.len(), 2);
+    }
+
+    #[test]
+    fn test_replay_is_chronological() {
+        let players = vec![make_player(1, 1200.0), make_player(2, 1200.0)];
+        // Newest first, as returned by get_all_matches
+        let matches = vec![
+            make_match(2, 8, vec![1], vec![2], (0, 3)),
+            make_match(1, 1, vec![1], vec![2], (2, 1)),
+        ];
+
+        let result = replay(
+            &players,
+            &initial(&players),
+            &matches,
+            &[],
+            &EloConfig::default(),
+        );
+
+        // First match was between equal ratings
+        assert_eq!(result.snapshots[&1][&1].before, 1200.0);
+        assert!(result.snapshots[&2][&1].before > 1200.0);
+    }
+
+    #[test]
+    fn test_replay_skips_voided_matches() {
+        let players = vec![make_player(1, 1200.0), make_player(2, 1200.0)];
+        let mut voided = make_match(1, 1, vec![1], vec![2], (5, 0));
+        voided.voided_at = Some(Utc::now());
+
+        let result = replay(
+            &players,
+            &initial(&players),
+            &[voided],
+            &[],
+            &EloConfig::default(),
+        );
+
+        assert_eq!(result.ratings[&1], 1200.0);
+        assert_eq!(result.ratings[&2], 1200.0);
+        assert!(result.snapshots.is_empty());
+    }
+
+    #[test]
+    fn test_replay_keeps_participation() {
+        let players = vec![make_player(1, 1200.0), make_player(2, 1200.0)];
+        let mut m = make_match(1, 1, vec![1], vec![2], (1, 0));
+        m.elo_snapshot = json!({ "1": { "before": 1200.0, "delta": 16.0, "participation": 0.5 } });
+
+        let result = replay(
+            &players,
+            &initial(&players),
+            &[m],
+            &[],
+            &EloConfig::default(),
+        );
+
+        let change = &result.snapshots[&1][&1];
+        assert_eq!(change.participation, 0.5);
+        assert!((result.ratings[&1] - (1200.0 + change.delta * 0.5)).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_replay_honours_k_multiplier() {
+        let players = vec![make_player(1, 1200.0), make_player(2, 1200.0)];
+        let normal = make_match(1, 1, vec![1], vec![2], (1, 0));
+        let mut special = normal.clone();
+        special.k_multiplier = 2.0;
+
+        let base = replay(
+            &players,
+            &initial(&players),
+            &[normal],
+            &[],
+            &EloConfig::default(),
+        );
+        let doubled = replay(
+            &players,
+            &initial(&players),
+            &[special],
+            &[],
+            &EloConfig::default(),
+        );
+
+        assert!((doubled.ratings[&1] - 1200.0 - 2.0 * (base.ratings[&1] - 1200.0)).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_replay_counts_calibration_matches() {
+        let players = vec![make_player(1, 1200.0), make_player(2, 1200.0)];
+        let matches: Vec<Match> = (1..=4)
+            .map(|i| make_match(i, i as u32, vec![1], vec![2], (1, 0)))
+            .collect();
+
+        let result = replay(
+            &players,
+            &initial(&players),
+            &matches,
+            &[],
+            &EloConfig::default(),
+        );
+
+        assert!(result.snapshots[&3][&1].calibrating);
+        assert!(!result.snapshots[&4][&1].calibrating);
+    }
+
+    #[test]
+    fn test_replay_applies_season_rollovers() {
+        let players = vec![make_player(1, 1200.0), make_player(2, 1200.0)];
+        let season = Season {
+            id: 1,
+            name: "Autumn".to_string(),
+            ended_on: NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+            carry: 0.5,
+            created_at: Utc::now(),
+        };
+        let matches = vec![
+            make_match(1, 1, vec![1], vec![2], (3, 0)),
+            make_match(2, 8, vec![1], vec![2], (0, 0)),
+        ];
+
+        let before_rollover = replay(
+            &players,
+            &initial(&players),
+            &matches[..1],
+            &[],
+            &EloConfig::default(),
+        );
+        let result = replay(
+            &players,
+            &initial(&players),
+            &matches,
+            std::slice::from_ref(&season),
+            &EloConfig::default(),
+        );
+
+        // Second match starts from the compressed ratings
+        let reset = soft_reset(before_rollover.ratings[&1], 0.5);
+        assert!((result.snapshots[&2][&1].before - reset).abs() < 0.001);
+
+        // A rollover after the last match still applies
+        let ended = replay(
+            &players,
+            &initial(&players),
+            &matches[..1],
+            &[season],
+            &EloConfig::default(),
+        );
+        assert!((ended.ratings[&1] - reset).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_replay_uses_initial_elo() {
+        // Current Elo 1300 after matches, but started at 1250
+        let players = vec![make_player(1, 1300.0)];
+        let initial_elo = HashMap::from([(1, 1250.0)]);
+
+        let result = replay(&players, &initial_elo, &[], &[], &EloConfig::default());
+
+        assert_eq!(result.ratings[&1], 1250.0);
+    }
+
+    #[test]
+    fn test_replay_holds_anchor() {
+        // The anchor is 1350 now, though they started at 1200
+        let players = vec![
+            make_player(1, 1350.0),
+            make_player(2, 1200.0),
+            make_player(3, 1200.0),
+        ];
+        let season = Season {
+            id: 1,
+            name: "Autumn".to_string(),
+            ended_on: NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+            carry: 0.5,
+            created_at: Utc::now(),
+        };
+        let matches = vec![
+            make_match(1, 1, vec![1, 2], vec![3], (3, 0)),
+            make_match(2, 8, vec![1, 2], vec![3], (0, 2)),
+        ];
+        let config = EloConfig {
+            anchor: Some(1),
+            ..Default::default()
+        };
+
+        let started = HashMap::from([(1, 1200.0), (2, 1200.0), (3, 1200.0)]);
+
+        let result = replay(&players, &started, &matches, &[season], &config);
+
+        assert_eq!(result.ratings[&1], 1350.0);
+        assert_eq!(result.snapshots[&2][&1].before, 1350.0);
+        assert_eq!(result.snapshots[&1][&1].delta, 0.0);
+        assert!(result.snapshots[&1][&2].delta > 0.0);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::db;
+use crate::models::{AbsenceReason, Rsvp, RsvpStatus};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Match days a player can RSVP for ahead of time
+pub const RSVP_WEEKS: usize = 4;
+/// Past match days covered by the absence report
+pub const ABSENCE_WEEKS: usize = 12;
+
+/// The next `count` match days (Sundays), starting today if it is one
+pub fn next_match_days(today: NaiveDate, count: usize) -> Vec<NaiveDate> {
+    let days_to_sunday = (7 - today.weekday().num_days_from_sunday() as i64) % 7;
+    let first = today + Duration::days(days_to_sunday);
+    (0..count as i64)
+        .map(|week| first + Duration::weeks(week))
+        .collect()
+}
+
+/// When RSVPs close before each match day, e.g. "Sat 20:00" (group time)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    pub weekday: Weekday,
+    pub time: NaiveTime,
+}
+
+impl Deadline {
+    /// Parse "Sat 20:00" (any weekday name or abbreviation, 24-hour time)
+    pub fn parse(text: &str) -> Option<Self> {
+        let (day, time) = text.trim().split_once(' ')?;
+        Some(Self {
+            weekday: day.parse().ok()?,
+            time: NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()?,
+        })
+    }
+
+    /// The last deadline on or before `match_on` (the match day itself for "Sun")
+    pub fn before(&self, match_on: NaiveDate) -> NaiveDateTime {
+        let days_back = (match_on.weekday().num_days_from_monday() + 7
+            - self.weekday.num_days_from_monday())
+            % 7;
+        (match_on - Duration::days(days_back as i64)).and_time(self.time)
+    }
+}
+
+impl fmt::Display for Deadline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.weekday, self.time.format("%H:%M"))
+    }
+}
+
+/// RSVPs for one match day, keyed by player ID
+pub async fn load_for_day(pool: &PgPool, day: NaiveDate) -> HashMap<i32, RsvpStatus> {
+    db::get_rsvps(pool, day, day)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|rsvp| Some((rsvp.player_id, rsvp.status()?)))
+        .collect()
+}
+
+/// One player's RSVPs (with any absence reason) from `days`, keyed by date
+pub async fn load_for_player(
+    pool: &PgPool,
+    player_id: i32,
+    days: &[NaiveDate],
+) -> HashMap<NaiveDate, (RsvpStatus, Option<AbsenceReason>)> {
+    let (Some(&first), Some(&last)) = (days.first(), days.last()) else {
+        return HashMap::new();
+    };
+    db::get_rsvps(pool, first, last)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|rsvp| rsvp.player_id == player_id)
+        .filter_map(|rsvp| Some((rsvp.match_on, (rsvp.status()?, rsvp.reason()))))
+        .collect()
+}
+
+/// Out answers per reason (`None` = no reason given)
+pub type ReasonCounts = HashMap<Option<AbsenceReason>, usize>;
+
+/// Out answers counted per player and per match day, so an injury crisis
+/// (many injured at once) looks different from players drifting away
+#[derive(Debug, Default)]
+pub struct AbsenceReport {
+    pub by_player: HashMap<i32, ReasonCounts>,
+    pub by_day: BTreeMap<NaiveDate, ReasonCounts>,
+}
+
+/// Count the Out answers among `rsvps`
+pub fn absence_report(rsvps: &[Rsvp]) -> AbsenceReport {
+    let mut report = AbsenceReport::default();
+    for rsvp in rsvps {
+        if rsvp.status() != Some(RsvpStatus::Out) {
+            continue;
+        }
+        let reason = rsvp.reason();
+        *report
+            .by_player
+            .entry(rsvp.player_id)
+            .or_default()
+            .entry(reason)
+            .or_default() += 1;
+        *report
+            .by_day
+            .entry(rsvp.match_on)
+            .or_default()
+            .entry(reason)
+            .or_default() += 1;
+    }
+    report
+}
+
+/// Number of players giving each answer
+pub fn tally(rsvps: &HashMap<i32, RsvpStatus>) -> HashMap<RsvpStatus, usize> {
+    let mut counts = HashMap::new();
+    for status in rsvps.values() {
+        *counts.entry(*status).or_default() += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_next_match_days() {
+        // Sunday: today counts
+        assert_eq!(
+            next_match_days(date(2025, 3, 30), 2),
+            vec![date(2025, 3, 30), date(2025, 4, 6)]
+        );
+        // Monday: the coming Sunday
+        assert_eq!(
+            next_match_days(date(2025, 3, 31), 1),
+            vec![date(2025, 4, 6)]
+        );
+        // Saturday
+        assert_eq!(next_match_days(date(2025, 4, 5), 1), vec![date(2025, 4, 6)]);
+        assert!(next_match_days(date(2025, 4, 5), 0).is_empty());
+    }
+
+    #[test]
+    fn test_deadline() {
+        let deadline = Deadline::parse(" saturday 20:00 ").unwrap();
+        assert_eq!(deadline.to_string(), "Sat 20:00");
+        assert_eq!(Deadline::parse("Sat 20:00"), Some(deadline));
+        assert_eq!(
+            deadline.before(date(2025, 4, 6)),
+            date(2025, 4, 5).and_hms_opt(20, 0, 0).unwrap()
+        );
+        // Same-day deadline
+        let morning = Deadline::parse("Sun 08:30").unwrap();
+        assert_eq!(
+            morning.before(date(2025, 4, 6)),
+            date(2025, 4, 6).and_hms_opt(8, 30, 0).unwrap()
+        );
+        assert_eq!(Deadline::parse("Sat"), None);
+        assert_eq!(Deadline::parse("Someday 20:00"), None);
+        assert_eq!(Deadline::parse("Sat 8pm"), None);
+    }
+
+    #[test]
+    fn test_tally() {
+        let rsvps = HashMap::from([
+            (1, RsvpStatus::In),
+            (2, RsvpStatus::In),
+            (3, RsvpStatus::Out),
+        ]);
+        let counts = tally(&rsvps);
+        assert_eq!(counts.get(&RsvpStatus::In), Some(&2));
+        assert_eq!(counts.get(&RsvpStatus::Maybe), None);
+        assert_eq!(counts.get(&RsvpStatus::Out), Some(&1));
+    }
+
+    #[test]
+    fn test_absence_report() {
+        let rsvp = |player_id, day, status: RsvpStatus, reason: Option<AbsenceReason>| Rsvp {
+            player_id,
+            match_on: date(2025, 3, day),
+            status: status.as_str().to_string(),
+            reason: reason.map(|r| r.as_str().to_string()),
+        };
+        let rsvps = vec![
+            rsvp(1, 2, RsvpStatus::Out, Some(AbsenceReason::Injured)),
+            rsvp(1, 9, RsvpStatus::Out, Some(AbsenceReason::Injured)),
+            rsvp(2, 9, RsvpStatus::Out, Some(AbsenceReason::Injured)),
+            rsvp(3, 9, RsvpStatus::Out, None),
+            rsvp(4, 9, RsvpStatus::In, None),
+        ];
+
+        let report = absence_report(&rsvps);
+
+        assert_eq!(report.by_player[&1][&Some(AbsenceReason::Injured)], 2);
+        assert_eq!(report.by_player[&3][&None], 1);
+        assert!(!report.by_player.contains_key(&4));
+        assert_eq!(
+            report.by_day[&date(2025, 3, 9)][&Some(AbsenceReason::Injured)],
+            2
+        );
+        assert_eq!(report.by_day[&date(2025, 3, 9)][&None], 1);
+        assert_eq!(report.by_day.len(), 2);
+    }
+}
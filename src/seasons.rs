@@ -0,0 +1,144 @@
+use crate::models::{Match, Player, SeasonStanding, ELO_DEFAULT};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// Share of each player's distance from the default Elo kept at a rollover
+pub const DEFAULT_CARRY: f32 = 0.5;
+
+/// Pull a rating toward the default Elo, keeping `carry` (0-1) of its distance
+pub fn soft_reset(elo: f32, carry: f32) -> f32 {
+    ELO_DEFAULT + (elo - ELO_DEFAULT) * carry
+}
+
+/// A player's rating after a rollover: everyone is soft-reset except the
+/// rating anchor, who never moves
+pub fn rollover_rating(player_id: i32, elo: f32, carry: f32, anchor: Option<i32>) -> f32 {
+    if anchor == Some(player_id) {
+        elo
+    } else {
+        soft_reset(elo, carry)
+    }
+}
+
+/// Parse the carry field of the rollover form (empty = default)
+pub fn parse_carry(value: Option<&str>) -> Result<f32, String> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        None => Ok(DEFAULT_CARRY),
+        Some(v) => v
+            .parse::<f32>()
+            .ok()
+            .filter(|c| (0.0..=1.0).contains(c))
+            .ok_or_else(|| "Carry must be a number from 0 to 1".to_string()),
+    }
+}
+
+/// Final standings of a season covering matches after `since` (if any) up to
+/// and including `until`: everyone who played in it, ranked by current Elo
+pub fn final_standings(
+    season_id: i32,
+    players: &[Player],
+    matches: &[Match],
+    since: Option<NaiveDate>,
+    until: NaiveDate,
+) -> Vec<SeasonStanding> {
+    let mut appearances: HashMap<i32, i32> = HashMap::new();
+    for m in matches.iter().filter(|m| {
+        m.voided_at.is_none() && m.played_at <= until && since.is_none_or(|s| m.played_at > s)
+    }) {
+        for id in m.team_a.iter().chain(&m.team_b) {
+            *appearances.entry(*id).or_default() += 1;
+        }
+    }
+
+    let mut ranked: Vec<&Player> = players
+        .iter()
+        .filter(|p| appearances.contains_key(&p.id))
+        .collect();
+    ranked.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+    ranked
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| SeasonStanding {
+            season_id,
+            player_id: Some(p.id),
+            name: p.name.clone(),
+            rank: i as i32 + 1,
+            elo: p.elo,
+            matches_played: appearances[&p.id],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn make_player(id: i32, name: &str, elo: f32) -> Player {
+        Player {
+            id,
+            name: name.to_string(),
+            elo,
+            ..Default::default()
+        }
+    }
+
+    fn make_match(id: i32, day: u32, team_a: Vec<i32>, team_b: Vec<i32>) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2025, 1, day).unwrap(),
+            team_a,
+            team_b,
+            score_a: 1,
+            score_b: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_soft_reset() {
+        assert_eq!(soft_reset(1400.0, 0.5), 1300.0);
+        assert_eq!(soft_reset(1000.0, 0.5), 1100.0);
+        assert_eq!(soft_reset(1400.0, 1.0), 1400.0);
+        assert_eq!(soft_reset(1400.0, 0.0), ELO_DEFAULT);
+    }
+
+    #[test]
+    fn test_parse_carry() {
+        assert_eq!(parse_carry(None), Ok(DEFAULT_CARRY));
+        assert_eq!(parse_carry(Some(" ")), Ok(DEFAULT_CARRY));
+        assert_eq!(parse_carry(Some("0.25")), Ok(0.25));
+        assert!(parse_carry(Some("1.5")).is_err());
+        assert!(parse_carry(Some("half")).is_err());
+    }
+
+    #[test]
+    fn test_final_standings_cover_the_season() {
+        let players = vec![
+            make_player(1, "Ann", 1250.0),
+            make_player(2, "Bob", 1300.0),
+            make_player(3, "Cat", 1400.0),
+        ];
+        let mut voided = make_match(4, 20, vec![3], vec![1]);
+        voided.voided_at = Some(Utc::now());
+        let matches = vec![
+            // Before the season started
+            make_match(1, 1, vec![3], vec![1]),
+            make_match(2, 10, vec![1], vec![2]),
+            make_match(3, 17, vec![1], vec![2]),
+            voided,
+            // After the season ended
+            make_match(5, 31, vec![3], vec![1]),
+        ];
+        let since = NaiveDate::from_ymd_opt(2025, 1, 5);
+        let until = NaiveDate::from_ymd_opt(2025, 1, 24).unwrap();
+
+        let standings = final_standings(7, &players, &matches, since, until);
+
+        let names: Vec<&str> = standings.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Bob", "Ann"]);
+        assert_eq!(standings[0].rank, 1);
+        assert_eq!(standings[1].matches_played, 2);
+        assert!(standings.iter().all(|s| s.season_id == 7));
+    }
+}
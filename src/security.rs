@@ -0,0 +1,97 @@
+use crate::AppState;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// Content Security Policy for the app: scripts, styles and everything else
+/// only from this origin. Pages must not use inline `<script>`/`<style>`,
+/// `style=` or `on*=` attributes; put code in static/js and static/css.
+pub const CONTENT_SECURITY_POLICY: &str = "default-src 'self'; script-src 'self'; \
+    style-src 'self'; img-src 'self' data:; font-src 'self'; connect-src 'self'; \
+    object-src 'none'; base-uri 'self'; form-action 'self'; frame-ancestors 'none'";
+
+/// Looser policy for Swagger UI at /api/docs (committed in static/vendor,
+/// pinned in its SHA256SUMS), which sets inline styles
+pub const DOCS_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; script-src 'self'; \
+    style-src 'self' 'unsafe-inline'; img-src 'self' data:; \
+    connect-src 'self'; object-src 'none'; base-uri 'self'; frame-ancestors 'none'";
+
+/// HSTS max-age (one year), sent only when cookies are marked Secure
+const HSTS: &str = "max-age=31536000; includeSubDomains";
+
+/// Headers added to every response; existing values (e.g. the docs CSP) are kept
+fn security_headers(https: bool) -> Vec<(HeaderName, &'static str)> {
+    let mut headers = vec![
+        (header::CONTENT_SECURITY_POLICY, CONTENT_SECURITY_POLICY),
+        (header::X_FRAME_OPTIONS, "DENY"),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        (header::REFERRER_POLICY, "same-origin"),
+        (
+            HeaderName::from_static("permissions-policy"),
+            "camera=(), microphone=(), geolocation=(), payment=()",
+        ),
+        (
+            HeaderName::from_static("cross-origin-opener-policy"),
+            "same-origin",
+        ),
+    ];
+    if https {
+        headers.push((header::STRICT_TRANSPORT_SECURITY, HSTS));
+    }
+    headers
+}
+
+fn apply(headers: &mut HeaderMap, https: bool) {
+    for (name, value) in security_headers(https) {
+        headers
+            .entry(name)
+            .or_insert(HeaderValue::from_static(value));
+    }
+}
+
+/// Middleware adding the CSP and other security headers to every response
+pub async fn add_security_headers(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    apply(response.headers_mut(), state.secure_cookies);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_has_no_inline_code() {
+        assert!(!CONTENT_SECURITY_POLICY.contains("unsafe-inline"));
+        assert!(!CONTENT_SECURITY_POLICY.contains("unsafe-eval"));
+        assert!(CONTENT_SECURITY_POLICY.contains("frame-ancestors 'none'"));
+    }
+
+    #[test]
+    fn test_existing_headers_are_kept() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static(DOCS_CONTENT_SECURITY_POLICY),
+        );
+        apply(&mut headers, false);
+
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            DOCS_CONTENT_SECURITY_POLICY
+        );
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert!(!headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+
+        apply(&mut headers, true);
+        assert!(headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+    }
+}
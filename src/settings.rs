@@ -0,0 +1,596 @@
+use crate::balance::{balancer, AttributeWeights, BalanceOptions, Balancer, Exact};
+use crate::captains::CaptainPick;
+use crate::db;
+use crate::elo::EloConfig;
+use crate::models::MAX_PER_TEAM;
+use crate::onboarding::OnboardingFormula;
+use crate::rsvp::Deadline;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use sqlx::PgPool;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Default number of recent matches used for form
+const DEFAULT_FORM_GAMES: usize = 5;
+/// Default Elo bonus for a perfect winning streak (and penalty for a losing one)
+const DEFAULT_FORM_WEIGHT: f32 = 50.0;
+
+/// Default bib colours for Team A and Team B
+const DEFAULT_BIB_COLORS: [&str; 2] = ["Orange", "Blue"];
+/// Default name in the page header and titles
+const DEFAULT_GROUP_NAME: &str = "Sunday Football Manager";
+const MAX_GROUP_NAME_LENGTH: usize = 60;
+/// Longest kickoff text (time and place for shared team messages)
+const MAX_KICKOFF_LENGTH: usize = 100;
+/// Allowed players per team (the default is `MAX_PER_TEAM`)
+const MIN_TEAM_SIZE: usize = 2;
+const MAX_TEAM_SIZE: usize = 11;
+/// Default hours players can fix a result they played in
+const DEFAULT_EDIT_WINDOW_HOURS: u32 = 24;
+/// Longest edit window (a week)
+const MAX_EDIT_WINDOW_HOURS: u32 = 168;
+
+/// Every setting's key (as stored by the admin page) and environment variable
+pub const KEYS: [(&str, &str); 24] = [
+    ("group_name", "GROUP_NAME"),
+    ("kickoff", "KICKOFF"),
+    ("rsvp_deadline", "RSVP_DEADLINE"),
+    ("bib_colors", "BIB_COLORS"),
+    ("timezone", "TIMEZONE"),
+    ("players_per_team", "PLAYERS_PER_TEAM"),
+    ("edit_window_hours", "EDIT_WINDOW_HOURS"),
+    ("confirm_results", "CONFIRM_RESULTS"),
+    ("team_names", "TEAM_NAMES"),
+    ("peer_surveys", "PEER_SURVEYS"),
+    ("captain_pick", "CAPTAIN_PICK"),
+    ("balancer", "BALANCER"),
+    ("attribute_weights", "ATTRIBUTE_WEIGHTS"),
+    ("form_games", "FORM_GAMES"),
+    ("form_weight", "FORM_WEIGHT"),
+    ("elo_regression", "ELO_REGRESSION"),
+    ("handicap_per_player", "HANDICAP_PER_PLAYER"),
+    ("handicap_curve", "HANDICAP_CURVE"),
+    ("keeper_handicap", "KEEPER_HANDICAP"),
+    ("max_elo_change", "MAX_ELO_CHANGE"),
+    ("absence_days", "ABSENCE_DAYS"),
+    ("anchor_player", "ANCHOR_PLAYER"),
+    ("onboarding_formula", "ONBOARDING_FORMULA"),
+    ("private_pages", "PRIVATE_PAGES"),
+];
+
+/// Group-level settings: environment variables, overridden by values saved
+/// on the admin page
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// Group name shown in the header and page titles
+    pub group_name: String,
+    /// Kickoff time and place for shared team messages, e.g. "10:00, Hackney Marshes" (may be empty)
+    pub kickoff: String,
+    /// When RSVPs close and teams are picked from them automatically (None = never)
+    pub rsvp_deadline: Option<Deadline>,
+    /// Bib colour worn by Team A and Team B
+    pub bib_colors: [String; 2],
+    /// Balancing weights for stamina, pace and age band
+    pub attribute_weights: AttributeWeights,
+    /// Recent matches considered for form-weighted balancing
+    pub form_games: usize,
+    /// Elo added for a perfect winning streak when balancing with form
+    pub form_weight: f32,
+    /// Group timezone: decides which day "today" is and how timestamps are dated
+    pub timezone: Tz,
+    /// Rating engine tuning (regression toward 1200, uneven-team handicap, goalkeeper
+    /// handicap, change cap, return boost, rating anchor)
+    pub elo: EloConfig,
+    /// How new players' questionnaire answers become a starting Elo
+    pub onboarding: OnboardingFormula,
+    /// Players per team: caps check-in and the record form
+    pub players_per_team: usize,
+    /// Hours after recording that players in a match can fix its result (0 = admins only)
+    pub edit_window_hours: u32,
+    /// Hold recorded results until a second person confirms them
+    pub confirm_results: bool,
+    /// Give teams themed names (picked from the split) instead of Team A / B
+    pub team_names: bool,
+    /// Ask logged-in players to rate their recent teammates each month
+    pub peer_surveys: bool,
+    /// How generated teams get a captain nominated (None = no nomination)
+    pub captain_pick: Option<CaptainPick>,
+    /// How "Generate Teams" and automatic team picks split players, unless
+    /// Match Day picks another
+    pub balancer: &'static dyn Balancer,
+    /// Require login to view pages too (only with AUTH_PASSWORD set)
+    pub private_pages: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            group_name: DEFAULT_GROUP_NAME.to_string(),
+            kickoff: String::new(),
+            rsvp_deadline: None,
+            bib_colors: DEFAULT_BIB_COLORS.map(String::from),
+            attribute_weights: AttributeWeights::default(),
+            form_games: DEFAULT_FORM_GAMES,
+            form_weight: DEFAULT_FORM_WEIGHT,
+            timezone: Tz::UTC,
+            elo: EloConfig::default(),
+            onboarding: OnboardingFormula::default(),
+            players_per_team: MAX_PER_TEAM,
+            edit_window_hours: DEFAULT_EDIT_WINDOW_HOURS,
+            confirm_results: false,
+            team_names: false,
+            peer_surveys: false,
+            captain_pick: None,
+            balancer: &Exact,
+            private_pages: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Load from the environment variables in [`KEYS`] (e.g. BIB_COLORS="Orange,Blue",
+    /// ATTRIBUTE_WEIGHTS="stamina=20,pace=20,age=10", TIMEZONE="Europe/London"),
+    /// falling back to defaults for missing or invalid values
+    pub fn from_env() -> Self {
+        let mut settings = Self::default();
+        for (key, env) in KEYS {
+            if let Ok(value) = std::env::var(env) {
+                if let Err(e) = settings.set(key, &value) {
+                    tracing::warn!("Ignoring {}={:?}: {}", env, value, e);
+                }
+            }
+        }
+        settings
+    }
+
+    /// Environment settings with the admin page's saved values on top. Fails
+    /// if the saved values can't be read, rather than quietly dropping them.
+    pub async fn load(pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let mut settings = Self::from_env();
+        for (key, value) in db::get_settings(pool).await? {
+            if let Err(e) = settings.set(&key, &value) {
+                tracing::warn!("Ignoring saved setting {}={:?}: {}", key, value, e);
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Set one setting from its text form (as in the environment variable)
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        let number = |what: &str| format!("{} must be a number", what);
+        match key {
+            "group_name" => {
+                if value.is_empty() || value.chars().count() > MAX_GROUP_NAME_LENGTH {
+                    return Err(format!(
+                        "Group name must be 1-{} characters",
+                        MAX_GROUP_NAME_LENGTH
+                    ));
+                }
+                self.group_name = value.to_string();
+            }
+            "kickoff" => {
+                if value.chars().count() > MAX_KICKOFF_LENGTH {
+                    return Err(format!(
+                        "Kickoff must be at most {} characters",
+                        MAX_KICKOFF_LENGTH
+                    ));
+                }
+                self.kickoff = value.to_string();
+            }
+            "rsvp_deadline" => {
+                self.rsvp_deadline = match value {
+                    "" => None,
+                    value => Some(Deadline::parse(value).ok_or(
+                        "RSVP deadline must be a weekday and 24-hour time, e.g. \"Sat 20:00\"",
+                    )?),
+                };
+            }
+            "bib_colors" => {
+                self.bib_colors = parse_bib_colors(value)
+                    .ok_or("Bib colours must be two colours, e.g. \"Orange,Blue\"")?;
+            }
+            "timezone" => {
+                self.timezone = value
+                    .parse()
+                    .map_err(|_| format!("Unknown timezone \"{}\"", value))?;
+            }
+            "players_per_team" => {
+                self.players_per_team = value
+                    .parse()
+                    .ok()
+                    .filter(|n| (MIN_TEAM_SIZE..=MAX_TEAM_SIZE).contains(n))
+                    .ok_or(format!(
+                        "Players per team must be {} to {}",
+                        MIN_TEAM_SIZE, MAX_TEAM_SIZE
+                    ))?;
+            }
+            "edit_window_hours" => {
+                self.edit_window_hours = value
+                    .parse()
+                    .ok()
+                    .filter(|h| *h <= MAX_EDIT_WINDOW_HOURS)
+                    .ok_or(format!(
+                        "Edit window must be 0 to {} hours",
+                        MAX_EDIT_WINDOW_HOURS
+                    ))?;
+            }
+            "confirm_results" => self.confirm_results = value == "true",
+            "team_names" => self.team_names = value == "true",
+            "peer_surveys" => self.peer_surveys = value == "true",
+            "captain_pick" => {
+                self.captain_pick =
+                    match value {
+                        "" => None,
+                        value => Some(CaptainPick::from_str(value).ok_or(
+                            "Captain pick must be \"elo\" or \"rotation\" (blank for off)",
+                        )?),
+                    };
+            }
+            "balancer" => {
+                self.balancer = balancer(value).ok_or(
+                    "Balancer must be \"exact\", \"randomized\", \"annealing\" or \"draft\"",
+                )?;
+            }
+            "attribute_weights" => {
+                self.attribute_weights = parse_attribute_weights(value, self.attribute_weights)
+                    .ok_or("Attribute weights must look like \"stamina=20,pace=20,age=10\"")?;
+            }
+            "form_games" => {
+                self.form_games = value
+                    .parse()
+                    .ok()
+                    .filter(|g| *g > 0)
+                    .ok_or("Form games must be a whole number above 0")?;
+            }
+            "form_weight" => self.form_weight = value.parse().map_err(|_| number("Form weight"))?,
+            "elo_regression" => {
+                self.elo.regression =
+                    parse_regression(value).ok_or("Elo regression must be between 0 and 1")?;
+            }
+            "handicap_per_player" => {
+                self.elo.handicap_per_player = value
+                    .parse()
+                    .ok()
+                    .filter(|h: &f32| *h >= 0.0)
+                    .ok_or("Handicap per player must be 0 or more")?;
+            }
+            "handicap_curve" => {
+                self.elo.handicap_curve = value
+                    .parse()
+                    .ok()
+                    .filter(|c: &f32| *c > 0.0)
+                    .ok_or("Handicap curve must be above 0")?;
+            }
+            "keeper_handicap" => {
+                self.elo.keeper_handicap = value
+                    .parse()
+                    .ok()
+                    .filter(|h: &f32| *h >= 0.0)
+                    .ok_or("Goalkeeper handicap must be 0 or more")?;
+            }
+            "max_elo_change" => {
+                self.elo.max_change = match value {
+                    "" => None,
+                    value => Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|c: &f32| *c > 0.0)
+                            .ok_or("Max Elo change must be above 0 (blank for no cap)")?,
+                    ),
+                };
+            }
+            "absence_days" => {
+                self.elo.absence_days =
+                    match value {
+                        "" => None,
+                        value => Some(value.parse().ok().filter(|d| *d > 0).ok_or(
+                            "Absence days must be a whole number above 0 (blank for off)",
+                        )?),
+                    };
+            }
+            "anchor_player" => {
+                self.elo.anchor = match value {
+                    "" => None,
+                    value => Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|id: &i32| *id > 0)
+                            .ok_or("Anchor player must be a player ID (blank for none)")?,
+                    ),
+                };
+            }
+            "onboarding_formula" => {
+                self.onboarding = self.onboarding.parse(value).ok_or(
+                    "Onboarding formula must look like \"base=1100,experience=100,fitness=25,keeper=0\"",
+                )?;
+            }
+            "private_pages" => self.private_pages = value == "true",
+            _ => return Err(format!("Unknown setting \"{}\"", key)),
+        }
+        Ok(())
+    }
+
+    /// A setting's current value in the text form [`Settings::set`] accepts
+    pub fn value(&self, key: &str) -> String {
+        match key {
+            "group_name" => self.group_name.clone(),
+            "kickoff" => self.kickoff.clone(),
+            "rsvp_deadline" => self
+                .rsvp_deadline
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            "bib_colors" => self.bib_colors.join(","),
+            "timezone" => self.timezone.name().to_string(),
+            "players_per_team" => self.players_per_team.to_string(),
+            "edit_window_hours" => self.edit_window_hours.to_string(),
+            "confirm_results" => self.confirm_results.to_string(),
+            "team_names" => self.team_names.to_string(),
+            "peer_surveys" => self.peer_surveys.to_string(),
+            "captain_pick" => self
+                .captain_pick
+                .map(|p| p.as_str().to_string())
+                .unwrap_or_default(),
+            "balancer" => self.balancer.key().to_string(),
+            "attribute_weights" => {
+                let w = self.attribute_weights;
+                format!("stamina={},pace={},age={}", w.stamina, w.pace, w.age)
+            }
+            "form_games" => self.form_games.to_string(),
+            "form_weight" => self.form_weight.to_string(),
+            "elo_regression" => self.elo.regression.to_string(),
+            "handicap_per_player" => self.elo.handicap_per_player.to_string(),
+            "handicap_curve" => self.elo.handicap_curve.to_string(),
+            "keeper_handicap" => self.elo.keeper_handicap.to_string(),
+            "max_elo_change" => self
+                .elo
+                .max_change
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+            "absence_days" => self
+                .elo
+                .absence_days
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            "anchor_player" => self.elo.anchor.map(|id| id.to_string()).unwrap_or_default(),
+            "onboarding_formula" => self.onboarding.to_string(),
+            "private_pages" => self.private_pages.to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Today's date in the group timezone
+    pub fn today(&self) -> NaiveDate {
+        self.local_date(Utc::now())
+    }
+
+    /// The group-timezone date of a UTC timestamp
+    pub fn local_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        at.with_timezone(&self.timezone).date_naive()
+    }
+
+    /// Balancer options derived from these settings
+    pub fn balance_options(&self) -> BalanceOptions {
+        BalanceOptions {
+            weights: self.attribute_weights,
+            ..Default::default()
+        }
+    }
+}
+
+/// Settings shared by all requests; the admin page swaps in new values
+/// without a restart
+#[derive(Debug, Clone)]
+pub struct SharedSettings(Arc<RwLock<Arc<Settings>>>);
+
+impl SharedSettings {
+    pub fn new(settings: Settings) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(settings))))
+    }
+
+    /// The current settings
+    pub fn get(&self) -> Arc<Settings> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the settings for every later request
+    pub fn replace(&self, settings: Settings) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(settings);
+    }
+
+    /// Swap in settings reloaded from the database, keeping the current ones
+    /// if the reload failed (a later change notice tries again)
+    pub fn reload(&self, loaded: Result<Settings, sqlx::Error>) {
+        match loaded {
+            Ok(settings) => {
+                self.replace(settings);
+                tracing::info!("Settings reloaded after a change on another instance");
+            }
+            Err(e) => tracing::error!("Failed to reload settings, keeping the current ones: {}", e),
+        }
+    }
+}
+
+/// Parse "Orange,Blue" into Team A / Team B colours
+fn parse_bib_colors(value: &str) -> Option<[String; 2]> {
+    let colors: Vec<String> = value
+        .split(',')
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect();
+    <[String; 2]>::try_from(colors).ok()
+}
+
+/// Parse "stamina=20,pace=10" over `base`; unnamed weights keep their base value
+fn parse_attribute_weights(value: &str, base: AttributeWeights) -> Option<AttributeWeights> {
+    let mut weights = base;
+    for pair in value.split(',').filter(|p| !p.trim().is_empty()) {
+        let (name, weight) = pair.split_once('=')?;
+        let weight: f32 = weight.trim().parse().ok().filter(|w: &f32| *w >= 0.0)?;
+        match name.trim() {
+            "stamina" => weights.stamina = weight,
+            "pace" => weights.pace = weight,
+            "age" => weights.age = weight,
+            _ => return None,
+        }
+    }
+    Some(weights)
+}
+
+/// Parse a regression share between 0 (off) and 1 (reset to 1200 every match)
+fn parse_regression(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|r: &f32| (0.0..=1.0).contains(r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_date_uses_timezone() {
+        // Sunday 23:30 in London is already Monday in Sydney, still Sunday in UTC-5
+        let at = "2025-03-30T22:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let date = |tz: &str| {
+            Settings {
+                timezone: tz.parse().unwrap(),
+                ..Default::default()
+            }
+            .local_date(at)
+        };
+        assert_eq!(
+            date("Europe/London"),
+            NaiveDate::from_ymd_opt(2025, 3, 30).unwrap()
+        );
+        assert_eq!(
+            date("Australia/Sydney"),
+            NaiveDate::from_ymd_opt(2025, 3, 31).unwrap()
+        );
+        assert_eq!(
+            date("America/New_York"),
+            NaiveDate::from_ymd_opt(2025, 3, 30).unwrap()
+        );
+        assert_eq!(Settings::default().timezone, Tz::UTC);
+    }
+
+    #[test]
+    fn test_failed_reload_keeps_current_settings() {
+        let shared = SharedSettings::new(Settings {
+            group_name: "Saved Name".to_string(),
+            ..Default::default()
+        });
+
+        shared.reload(Err(sqlx::Error::PoolTimedOut));
+        assert_eq!(shared.get().group_name, "Saved Name");
+
+        shared.reload(Ok(Settings::default()));
+        assert_eq!(shared.get().group_name, Settings::default().group_name);
+    }
+
+    #[test]
+    fn test_parse_bib_colors() {
+        assert_eq!(
+            parse_bib_colors("Red, White"),
+            Some(["Red".to_string(), "White".to_string()])
+        );
+        assert_eq!(parse_bib_colors("Red"), None);
+        assert_eq!(parse_bib_colors("Red,White,Green"), None);
+        assert_eq!(parse_bib_colors(" , "), None);
+    }
+
+    #[test]
+    fn test_parse_regression() {
+        assert_eq!(parse_regression(" 0.02 "), Some(0.02));
+        assert_eq!(parse_regression("0"), Some(0.0));
+        assert_eq!(parse_regression("1.5"), None);
+        assert_eq!(parse_regression("-0.1"), None);
+        assert_eq!(parse_regression("a lot"), None);
+    }
+
+    #[test]
+    fn test_set_and_value_round_trip() {
+        let mut settings = Settings::default();
+        let values = [
+            ("group_name", "Tuesday Five-a-side"),
+            ("kickoff", "19:30, Powerleague pitch 4"),
+            ("rsvp_deadline", "Sat 20:00"),
+            ("bib_colors", "Red,White"),
+            ("timezone", "Europe/London"),
+            ("players_per_team", "5"),
+            ("edit_window_hours", "48"),
+            ("confirm_results", "true"),
+            ("team_names", "true"),
+            ("peer_surveys", "true"),
+            ("captain_pick", "rotation"),
+            ("balancer", "annealing"),
+            ("attribute_weights", "stamina=10,pace=5,age=0"),
+            ("form_games", "3"),
+            ("form_weight", "25"),
+            ("elo_regression", "0.02"),
+            ("handicap_per_player", "80"),
+            ("handicap_curve", "1.5"),
+            ("keeper_handicap", "60"),
+            ("max_elo_change", "40"),
+            ("absence_days", "90"),
+            ("anchor_player", "7"),
+            (
+                "onboarding_formula",
+                "base=1000,experience=120,fitness=20,keeper=50",
+            ),
+            ("private_pages", "true"),
+        ];
+        assert_eq!(values.len(), KEYS.len());
+        for (key, value) in values {
+            settings.set(key, value).unwrap();
+            assert_eq!(settings.value(key), value, "{}", key);
+        }
+        assert_eq!(settings.players_per_team, 5);
+        assert!(settings.private_pages);
+        assert!(settings.confirm_results);
+    }
+
+    #[test]
+    fn test_set_rejects_invalid_values() {
+        let mut settings = Settings::default();
+        assert!(settings.set("group_name", "  ").is_err());
+        assert!(settings.set("kickoff", &"x".repeat(101)).is_err());
+        assert!(settings.set("rsvp_deadline", "Saturday evening").is_err());
+        assert!(settings.set("captain_pick", "random").is_err());
+        assert!(settings.set("balancer", "coin").is_err());
+        assert!(settings.set("players_per_team", "1").is_err());
+        assert!(settings.set("players_per_team", "12").is_err());
+        assert!(settings.set("edit_window_hours", "169").is_err());
+        assert!(settings.set("timezone", "Mars/Olympus").is_err());
+        assert!(settings.set("form_games", "0").is_err());
+        assert!(settings.set("handicap_curve", "0").is_err());
+        assert!(settings.set("keeper_handicap", "-1").is_err());
+        assert!(settings.set("max_elo_change", "-5").is_err());
+        assert!(settings.set("absence_days", "0").is_err());
+        assert!(settings.set("anchor_player", "Dave").is_err());
+        assert!(settings.set("onboarding_formula", "speed=10").is_err());
+        assert!(settings.set("colour", "Red").is_err());
+        // Failed sets leave the old value
+        assert_eq!(settings.players_per_team, MAX_PER_TEAM);
+    }
+
+    #[test]
+    fn test_parse_attribute_weights() {
+        let base = AttributeWeights::default();
+        let weights = parse_attribute_weights("pace=5, age=0", base).unwrap();
+        assert_eq!(weights.stamina, base.stamina);
+        assert_eq!(weights.pace, 5.0);
+        assert_eq!(weights.age, 0.0);
+
+        assert!(parse_attribute_weights("speed=5", base).is_none());
+        assert!(parse_attribute_weights("pace=-1", base).is_none());
+        assert!(parse_attribute_weights("pace", base).is_none());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::elo::average_elo;
+use crate::models::Player;
+use chrono::NaiveDate;
+
+/// Coloured circle for a bib colour name, or a shirt for colours without one
+fn bib_emoji(color: &str) -> &'static str {
+    match color.trim().to_lowercase().as_str() {
+        "red" => "🔴",
+        "orange" => "🟠",
+        "yellow" => "🟡",
+        "green" => "🟢",
+        "blue" => "🔵",
+        "purple" => "🟣",
+        "brown" => "🟤",
+        "black" => "⚫",
+        "white" => "⚪",
+        _ => "👕",
+    }
+}
+
+/// One team's block: a bold header with bib colour and average Elo, then a
+/// bullet per player in goal rotation order (lowest rated first) with 🧤 on
+/// goalkeepers
+fn team_block(name: &str, color: &str, team: &[Player]) -> String {
+    let mut players: Vec<&Player> = team.iter().collect();
+    players.sort_by(|a, b| a.elo.total_cmp(&b.elo));
+    let has_keeper = players.iter().any(|p| p.is_goalkeeper());
+
+    let mut lines = vec![format!(
+        "{} *{}* ({} bibs) · avg Elo {:.0}",
+        bib_emoji(color),
+        name,
+        color,
+        average_elo(team)
+    )];
+    for player in players {
+        let glove = if player.is_goalkeeper() { " 🧤" } else { "" };
+        lines.push(format!("• {}{}", player.name, glove));
+    }
+    if !has_keeper {
+        lines.push("_🧤 Goal rotation: top of the list first_".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Plain-text teams message for pasting into WhatsApp (`*bold*`, `_italic_`)
+pub fn whatsapp_text(
+    group_name: &str,
+    match_on: NaiveDate,
+    kickoff: &str,
+    bib_colors: &[String; 2],
+    team_names: &[String; 2],
+    team_a: &[Player],
+    team_b: &[Player],
+) -> String {
+    let mut text = format!("⚽ *{}* · {}\n", group_name, match_on.format("%a %-d %b"));
+    if !kickoff.is_empty() {
+        text.push_str(&format!("🕙 {}\n", kickoff));
+    }
+    text.push('\n');
+    text.push_str(&team_block(&team_names[0], &bib_colors[0], team_a));
+    text.push_str("\n\n");
+    text.push_str(&team_block(&team_names[1], &bib_colors[1], team_b));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_player(id: i32, name: &str, elo: f32, position: Option<&str>) -> Player {
+        Player {
+            id,
+            name: name.to_string(),
+            elo,
+            position: position.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bib_emoji() {
+        assert_eq!(bib_emoji("Orange"), "🟠");
+        assert_eq!(bib_emoji(" blue "), "🔵");
+        assert_eq!(bib_emoji("Fluorescent pink"), "👕");
+    }
+
+    #[test]
+    fn test_whatsapp_text() {
+        let team_a = vec![
+            make_player(1, "Sam", 1300.0, None),
+            make_player(2, "Alex", 1100.0, None),
+        ];
+        let team_b = vec![
+            make_player(3, "Jo", 1250.0, Some("GK")),
+            make_player(4, "Kim", 1150.0, None),
+        ];
+        let bibs = ["Orange".to_string(), "Blue".to_string()];
+        let names = ["Team A".to_string(), "Team B".to_string()];
+        let date = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap();
+
+        let text = whatsapp_text(
+            "Sunday FC",
+            date,
+            "10:00, Hackney Marshes",
+            &bibs,
+            &names,
+            &team_a,
+            &team_b,
+        );
+
+        assert_eq!(
+            text,
+            "⚽ *Sunday FC* · Sun 30 Mar\n\
+             🕙 10:00, Hackney Marshes\n\
+             \n\
+             🟠 *Team A* (Orange bibs) · avg Elo 1200\n\
+             • Alex\n\
+             • Sam\n\
+             _🧤 Goal rotation: top of the list first_\n\
+             \n\
+             🔵 *Team B* (Blue bibs) · avg Elo 1200\n\
+             • Kim\n\
+             • Jo 🧤"
+        );
+    }
+
+    #[test]
+    fn test_whatsapp_text_without_kickoff() {
+        let bibs = ["Red".to_string(), "White".to_string()];
+        let date = NaiveDate::from_ymd_opt(2025, 3, 30).unwrap();
+        let names = ["Muddy Badgers".to_string(), "Golden Owls".to_string()];
+        let text = whatsapp_text("Sunday FC", date, "", &bibs, &names, &[], &[]);
+        assert!(!text.contains("🕙"));
+        assert!(text.starts_with("⚽ *Sunday FC* · Sun 30 Mar\n\n🔴 *Muddy Badgers* (Red bibs)"));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::models::Player;
+
+/// Edit distance between two strings, counting insertions, deletions,
+/// substitutions and swaps of adjacent characters (optimal string alignment)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Typos allowed before two names count as different: one for short names, two otherwise
+fn max_typos(name: &str) -> usize {
+    if name.chars().count() <= 4 {
+        1
+    } else {
+        2
+    }
+}
+
+/// Existing players whose name looks like a typo of (or the same as) `name`,
+/// ignoring case and surrounding whitespace
+pub fn similar_players<'a>(name: &str, players: &'a [Player]) -> Vec<&'a Player> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Vec::new();
+    }
+    let limit = max_typos(&name);
+    players
+        .iter()
+        .filter(|p| edit_distance(&name, &p.name.trim().to_lowercase()) <= limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_player(id: i32, name: &str) -> Player {
+        Player {
+            id,
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("john", "john"), 0);
+        assert_eq!(edit_distance("jonh", "john"), 1); // swapped letters
+        assert_eq!(edit_distance("jon", "john"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_similar_players() {
+        let players = vec![
+            make_player(1, "John"),
+            make_player(2, "Joan"),
+            make_player(3, "Christopher"),
+            make_player(4, "Sam"),
+        ];
+
+        let names = |name: &str| -> Vec<i32> {
+            similar_players(name, &players)
+                .iter()
+                .map(|p| p.id)
+                .collect()
+        };
+
+        assert_eq!(names("Jonh"), vec![1]);
+        assert_eq!(names(" JOHN "), vec![1, 2]);
+        assert_eq!(names("Christophr"), vec![3]);
+        assert!(names("Max").is_empty());
+        assert!(names("").is_empty());
+    }
+}
@@ -0,0 +1,411 @@
+use crate::models::{EloSnapshot, Match, Player, WinRecord};
+use crate::privacy::mask_names;
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Matches listed under `recent_matches`
+const RECENT_MATCHES: usize = 5;
+/// Players listed under `top_movers`
+const TOP_MOVERS: usize = 5;
+/// Window for `top_movers`, in days
+const MOVER_DAYS: i64 = 30;
+/// How long clients and proxies may cache the stats, in seconds
+const CACHE_SECONDS: u32 = 60;
+
+/// Public snapshot of the group's ratings and results
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Stats {
+    /// Players with at least one match, highest Elo first
+    pub leaderboard: Vec<LeaderboardEntry>,
+    /// Latest non-voided matches, newest first
+    pub recent_matches: Vec<MatchSummary>,
+    /// Biggest Elo changes over the last 30 days
+    pub top_movers: Vec<Mover>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub name: String,
+    pub elo: i32,
+    pub matches_played: i32,
+    pub wins: i32,
+    pub draws: i32,
+    pub losses: i32,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct MatchSummary {
+    /// Match date (YYYY-MM-DD)
+    pub played_at: String,
+    pub team_a: Vec<String>,
+    pub team_b: Vec<String>,
+    pub score_a: i32,
+    pub score_b: i32,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Mover {
+    pub name: String,
+    /// Net Elo change over the window
+    pub change: i32,
+    pub elo: i32,
+}
+
+/// Build the stats from all players, their win records and the recent
+/// matches (any order; at least the last `MOVER_DAYS` and `RECENT_MATCHES`)
+pub fn build_stats(
+    players: &[Player],
+    records: &[WinRecord],
+    matches: &[Match],
+    today: NaiveDate,
+) -> Stats {
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    let name = |id: &i32| names.get(id).copied().unwrap_or("Unknown").to_string();
+    let records: HashMap<i32, &WinRecord> = records.iter().map(|r| (r.player_id, r)).collect();
+
+    let mut ranked: Vec<&Player> = players.iter().filter(|p| p.matches_played > 0).collect();
+    ranked.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+    let leaderboard = ranked
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let (wins, draws, losses) = records
+                .get(&p.id)
+                .map_or((0, 0, 0), |r| (r.wins, r.draws, r.losses));
+            LeaderboardEntry {
+                rank: i + 1,
+                name: p.name.clone(),
+                elo: p.elo.round() as i32,
+                matches_played: p.matches_played,
+                wins,
+                draws,
+                losses,
+            }
+        })
+        .collect();
+
+    let mut counted: Vec<&Match> = matches.iter().filter(|m| m.voided_at.is_none()).collect();
+    counted.sort_by_key(|m| std::cmp::Reverse((m.played_at, m.created_at, m.id)));
+
+    let recent_matches = counted
+        .iter()
+        .take(RECENT_MATCHES)
+        .map(|m| MatchSummary {
+            played_at: m.played_at.format("%Y-%m-%d").to_string(),
+            team_a: m.team_a.iter().map(name).collect(),
+            team_b: m.team_b.iter().map(name).collect(),
+            score_a: m.score_a,
+            score_b: m.score_b,
+        })
+        .collect();
+
+    let since = today - Duration::days(MOVER_DAYS);
+    let mut changes: HashMap<i32, f32> = HashMap::new();
+    for m in counted.iter().filter(|m| m.played_at > since) {
+        let snapshot: HashMap<i32, EloSnapshot> =
+            serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+        for (id, change) in snapshot {
+            *changes.entry(id).or_default() += change.delta * change.participation;
+        }
+    }
+    let elo: HashMap<i32, f32> = players.iter().map(|p| (p.id, p.elo)).collect();
+    let mut movers: Vec<(i32, f32)> = changes
+        .into_iter()
+        .filter(|(id, change)| elo.contains_key(id) && change.abs() >= 0.5)
+        .collect();
+    movers.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()).then(a.0.cmp(&b.0)));
+    let top_movers = movers
+        .into_iter()
+        .take(TOP_MOVERS)
+        .map(|(id, change)| Mover {
+            name: name(&id),
+            change: change.round() as i32,
+            elo: elo[&id].round() as i32,
+        })
+        .collect();
+
+    Stats {
+        leaderboard,
+        recent_matches,
+        top_movers,
+    }
+}
+
+/// Leaderboard, recent matches and top movers as JSON, for embedding on other sites
+#[utoipa::path(
+    get,
+    path = "/api/stats",
+    tag = "stats",
+    responses(
+        (status = 200, description = "Current stats (cached for 60 seconds)", body = Stats),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn stats(State(state): State<Arc<AppState>>) -> Response {
+    let today = state.settings.get().today();
+    let players = state.players.get(&state.db).await;
+    let records = db::get_win_records(&state.db).await;
+    let since = today - Duration::days(MOVER_DAYS);
+    let matches = db::get_matches_since(&state.db, since, RECENT_MATCHES as i64).await;
+    let (Ok(mut players), Ok(records), Ok(matches)) = (players, records, matches) else {
+        tracing::error!("Failed to load stats");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    // Shown on other sites, so hidden names never appear
+    mask_names(&mut players, false);
+
+    let stats = build_stats(&players, &records, &matches, today);
+    (
+        [
+            (
+                header::CACHE_CONTROL,
+                format!("public, max-age={CACHE_SECONDS}"),
+            ),
+            (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*".to_string()),
+        ],
+        Json(stats),
+    )
+        .into_response()
+}
+
+/// One of a player's matches in their Elo history export
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryRow {
+    pub played_at: NaiveDate,
+    pub match_id: i32,
+    /// Average pre-match Elo of the other team
+    pub opponent_elo: f32,
+    pub participation: f32,
+    /// Elo change actually applied (scaled by participation)
+    pub delta: f32,
+    /// Elo after the match
+    pub elo: f32,
+}
+
+/// A player's non-voided matches with the opposing team's strength and
+/// their rating change, oldest first
+pub fn player_history(player_id: i32, matches: &[Match]) -> Vec<HistoryRow> {
+    let mut counted: Vec<&Match> = matches
+        .iter()
+        .filter(|m| m.voided_at.is_none())
+        .filter(|m| m.team_a.contains(&player_id) || m.team_b.contains(&player_id))
+        .collect();
+    counted.sort_by_key(|m| (m.played_at, m.created_at, m.id));
+
+    counted
+        .into_iter()
+        .filter_map(|m| {
+            let snapshot: HashMap<i32, EloSnapshot> =
+                serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+            let own = snapshot.get(&player_id)?;
+            let opponents = if m.team_a.contains(&player_id) {
+                &m.team_b
+            } else {
+                &m.team_a
+            };
+            let before: Vec<f32> = opponents
+                .iter()
+                .filter_map(|id| snapshot.get(id).map(|s| s.before))
+                .collect();
+            let delta = own.delta * own.participation;
+            Some(HistoryRow {
+                played_at: m.played_at,
+                match_id: m.id,
+                opponent_elo: before.iter().sum::<f32>() / before.len().max(1) as f32,
+                participation: own.participation,
+                delta,
+                elo: own.before + delta,
+            })
+        })
+        .collect()
+}
+
+/// Render history rows as CSV with a header line
+pub fn history_csv(rows: &[HistoryRow]) -> String {
+    let mut csv = String::from("date,match_id,opponent_elo,participation,delta,elo\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{:.1},{:.2},{:.1},{:.1}\n",
+            row.played_at.format("%Y-%m-%d"),
+            row.match_id,
+            row.opponent_elo,
+            row.participation,
+            row.delta,
+            row.elo
+        ));
+    }
+    csv
+}
+
+/// A player's Elo history as CSV, one row per match, for analysing it elsewhere
+#[utoipa::path(
+    get,
+    path = "/api/players/{id}/history.csv",
+    tag = "stats",
+    params(("id" = i32, Path, description = "Player ID")),
+    responses(
+        (status = 200, description = "date, match_id, opponent_elo (average pre-match Elo of the other team), participation, delta and the resulting elo, oldest first", content_type = "text/csv"),
+        (status = 404, description = "Player not found"),
+        (status = 500, description = "Database error")
+    )
+)]
+pub async fn player_history_csv(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> Response {
+    let players = db::get_players_by_ids(&state.db, &[id]).await;
+    let matches = db::get_all_matches(&state.db).await;
+    let (Ok(players), Ok(matches)) = (players, matches) else {
+        tracing::error!("Failed to load Elo history for player {}", id);
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if players.is_empty() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"player-{id}-history.csv\""),
+            ),
+        ],
+        history_csv(&player_history(id, &matches)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn make_player(id: i32, name: &str, elo: f32, matches_played: i32) -> Player {
+        Player {
+            id,
+            name: name.to_string(),
+            elo,
+            matches_played,
+            ..Default::default()
+        }
+    }
+
+    fn make_match(id: i32, played_at: NaiveDate, snapshot: serde_json::Value) -> Match {
+        Match {
+            id,
+            played_at,
+            team_a: vec![1],
+            team_b: vec![2, 99],
+            score_a: 2,
+            score_b: 1,
+            elo_snapshot: snapshot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_stats() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        let players = vec![
+            make_player(1, "Ann", 1230.0, 2),
+            make_player(2, "Bob", 1170.0, 2),
+            make_player(3, "New", 1400.0, 0),
+        ];
+        let old = make_match(
+            1,
+            NaiveDate::from_ymd_opt(2025, 1, 5).unwrap(),
+            json!({ "1": { "before": 1200.0, "delta": 14.0 }, "2": { "before": 1200.0, "delta": -14.0 } }),
+        );
+        let recent = make_match(
+            2,
+            NaiveDate::from_ymd_opt(2025, 3, 30).unwrap(),
+            json!({ "1": { "before": 1214.0, "delta": 16.0 }, "2": { "before": 1186.0, "delta": -16.0, "participation": 0.5 } }),
+        );
+        let mut voided = make_match(
+            3,
+            today,
+            json!({ "1": { "before": 1230.0, "delta": 99.0 } }),
+        );
+        voided.voided_at = Some(Utc::now());
+
+        let records = [WinRecord {
+            player_id: 1,
+            wins: 2,
+            ..Default::default()
+        }];
+
+        let stats = build_stats(&players, &records, &[old, voided, recent], today);
+
+        // Players without matches aren't ranked
+        let names: Vec<&str> = stats.leaderboard.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Ann", "Bob"]);
+        assert_eq!(stats.leaderboard[0].rank, 1);
+        assert_eq!(stats.leaderboard[0].wins, 2);
+        assert_eq!(stats.leaderboard[1].wins, 0);
+
+        // Newest first, voided skipped, deleted players named "Unknown"
+        assert_eq!(stats.recent_matches.len(), 2);
+        assert_eq!(stats.recent_matches[0].played_at, "2025-03-30");
+        assert_eq!(stats.recent_matches[0].team_b, vec!["Bob", "Unknown"]);
+
+        // Only the match inside the 30-day window counts
+        assert_eq!(stats.top_movers[0].name, "Ann");
+        assert_eq!(stats.top_movers[0].change, 16);
+        assert_eq!(stats.top_movers[1].change, -8);
+    }
+
+    #[test]
+    fn test_player_history() {
+        let first = make_match(
+            1,
+            NaiveDate::from_ymd_opt(2025, 1, 5).unwrap(),
+            json!({
+                "1": { "before": 1200.0, "delta": 14.0 },
+                "2": { "before": 1180.0, "delta": -14.0 },
+                "99": { "before": 1240.0, "delta": -14.0 }
+            }),
+        );
+        let mut second = make_match(
+            2,
+            NaiveDate::from_ymd_opt(2025, 1, 12).unwrap(),
+            json!({
+                "1": { "before": 1186.0, "delta": 16.0 },
+                "2": { "before": 1166.0, "delta": -16.0, "participation": 0.5 }
+            }),
+        );
+        second.team_a = vec![2];
+        second.team_b = vec![1];
+        let mut voided = make_match(3, NaiveDate::from_ymd_opt(2025, 1, 19).unwrap(), json!({}));
+        voided.voided_at = Some(Utc::now());
+
+        // Out of order on purpose
+        let history = player_history(2, &[second, voided, first]);
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].match_id, 1);
+        assert_eq!(history[0].opponent_elo, 1200.0);
+        assert_eq!((history[0].delta, history[0].elo), (-14.0, 1166.0));
+        // On team A this time; half participation halves the change
+        assert_eq!(history[1].opponent_elo, 1186.0);
+        assert_eq!((history[1].delta, history[1].elo), (-8.0, 1158.0));
+
+        assert_eq!(
+            history_csv(&history),
+            "date,match_id,opponent_elo,participation,delta,elo\n\
+             2025-01-05,1,1200.0,1.00,-14.0,1166.0\n\
+             2025-01-12,2,1186.0,0.50,-8.0,1158.0\n"
+        );
+    }
+}
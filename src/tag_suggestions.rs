@@ -0,0 +1,272 @@
+use crate::models::{Match, Player, Side, Tag};
+use std::collections::{HashMap, HashSet};
+
+/// Matches with a goal timeline a player needs before their scoring counts
+const MIN_TIMELINE_MATCHES: usize = 5;
+/// Goals per timeline match that suggest adding ATK
+const SCORER_RATE: f32 = 0.75;
+/// Timeline matches without a goal that suggest dropping ATK
+const SCORELESS_MATCHES: usize = 8;
+/// Matches a player needs before their clean sheets count
+const MIN_MATCHES: usize = 5;
+/// How far above the group's clean-sheet rate suggests adding DEF
+const CLEAN_SHEET_MARGIN: f32 = 0.2;
+
+/// Whether a suggestion adds or removes the tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TagChange {
+    Add,
+    Remove,
+}
+
+impl TagChange {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TagChange::Add => "add",
+            TagChange::Remove => "remove",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "add" => Some(TagChange::Add),
+            "remove" => Some(TagChange::Remove),
+            _ => None,
+        }
+    }
+}
+
+/// A suggested change to one player's tags, with the numbers behind it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagSuggestion {
+    pub player_id: i32,
+    pub tag: Tag,
+    pub change: TagChange,
+    pub reason: String,
+}
+
+/// A player's numbers over non-voided matches
+#[derive(Debug, Default)]
+struct Record {
+    matches: usize,
+    clean_sheets: usize,
+    /// Matches with a goal timeline, and their goals in them (own goals aside)
+    timeline_matches: usize,
+    goals: usize,
+}
+
+/// Suggest tag changes from the match history:
+/// - add ATK for someone scoring at least 0.75 a game over 5+ matches with a
+///   goal timeline, and drop it from someone without a goal in 8+ of them
+/// - add DEF for an outfielder whose team keeps clean sheets at least 20
+///   points more often than the group's average over 5+ matches
+pub fn suggest(players: &[Player], matches: &[Match]) -> Vec<TagSuggestion> {
+    let mut records: HashMap<i32, Record> = HashMap::new();
+    let (mut sides, mut clean_sides) = (0, 0);
+    for m in matches.iter().filter(|m| m.voided_at.is_none()) {
+        let goals = m.goals();
+        for (side, team, conceded) in [
+            (Side::A, &m.team_a, m.score_b),
+            (Side::B, &m.team_b, m.score_a),
+        ] {
+            sides += 1;
+            if conceded == 0 {
+                clean_sides += 1;
+            }
+            for &id in team {
+                let record = records.entry(id).or_default();
+                record.matches += 1;
+                if conceded == 0 {
+                    record.clean_sheets += 1;
+                }
+                if !goals.is_empty() {
+                    record.timeline_matches += 1;
+                    record.goals += goals
+                        .iter()
+                        .filter(|g| g.team == side && g.scorer == Some(id))
+                        .count();
+                }
+            }
+        }
+    }
+    let group_clean_sheets = if sides == 0 {
+        0.0
+    } else {
+        clean_sides as f32 / sides as f32
+    };
+
+    let mut suggestions = Vec::new();
+    for player in players {
+        let Some(record) = records.get(&player.id) else {
+            continue;
+        };
+        let mut suggest = |tag, change, reason| {
+            suggestions.push(TagSuggestion {
+                player_id: player.id,
+                tag,
+                change,
+                reason,
+            })
+        };
+
+        if record.timeline_matches >= MIN_TIMELINE_MATCHES {
+            let rate = record.goals as f32 / record.timeline_matches as f32;
+            if rate >= SCORER_RATE && !player.has_tag(Tag::Atk) {
+                suggest(
+                    Tag::Atk,
+                    TagChange::Add,
+                    format!(
+                        "Scores a lot: {} goals in {} matches with a goal timeline",
+                        record.goals, record.timeline_matches
+                    ),
+                );
+            }
+        }
+        if record.timeline_matches >= SCORELESS_MATCHES
+            && record.goals == 0
+            && player.has_tag(Tag::Atk)
+        {
+            suggest(
+                Tag::Atk,
+                TagChange::Remove,
+                format!(
+                    "No goals in {} matches with a goal timeline",
+                    record.timeline_matches
+                ),
+            );
+        }
+
+        if record.matches >= MIN_MATCHES && !player.is_goalkeeper() && !player.has_tag(Tag::Def) {
+            let rate = record.clean_sheets as f32 / record.matches as f32;
+            if rate >= group_clean_sheets + CLEAN_SHEET_MARGIN {
+                suggest(
+                    Tag::Def,
+                    TagChange::Add,
+                    format!(
+                        "Clean sheets in {} of {} matches ({:.0}%, group average {:.0}%)",
+                        record.clean_sheets,
+                        record.matches,
+                        rate * 100.0,
+                        group_clean_sheets * 100.0
+                    ),
+                );
+            }
+        }
+    }
+    suggestions
+}
+
+/// Leave out suggestions the admin dismissed (player, tag, change)
+pub fn without_dismissed(
+    suggestions: Vec<TagSuggestion>,
+    dismissed: &HashSet<(i32, Tag, TagChange)>,
+) -> Vec<TagSuggestion> {
+    suggestions
+        .into_iter()
+        .filter(|s| !dismissed.contains(&(s.player_id, s.tag, s.change)))
+        .collect()
+}
+
+/// A player's comma-separated tags with one added or removed
+pub fn apply(tags: &str, tag: Tag, change: TagChange) -> String {
+    let mut list: Vec<Tag> = tags.split(',').filter_map(Tag::from_str).collect();
+    list.retain(|t| *t != tag);
+    if change == TagChange::Add {
+        list.push(tag);
+    }
+    list.iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    fn make_player(id: i32, tags: &str) -> Player {
+        Player {
+            id,
+            name: format!("P{}", id),
+            tags: tags.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn make_match(id: i32, score: (i32, i32), goals: serde_json::Value) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, 2).unwrap(),
+            team_a: vec![1, 2],
+            team_b: vec![3, 4],
+            score_a: score.0,
+            score_b: score.1,
+            goals,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let players = vec![
+            make_player(1, ""),
+            make_player(2, "RUNNER"),
+            make_player(3, "ATK"),
+            make_player(4, "GK"),
+        ];
+        // Player 1 scores every game and team A never concedes; player 3
+        // scores an own goal, which doesn't count for them
+        let mut matches: Vec<Match> = (1..=8)
+            .map(|id| {
+                make_match(
+                    id,
+                    (2, 0),
+                    json!([{"team": "a", "scorer": 1}, {"team": "a", "scorer": 3}]),
+                )
+            })
+            .collect();
+        // A goalless draw without a timeline: a clean sheet for both teams
+        matches.push(make_match(9, (0, 0), json!([])));
+
+        let found: Vec<(i32, Tag, TagChange)> = suggest(&players, &matches)
+            .iter()
+            .map(|s| (s.player_id, s.tag, s.change))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (1, Tag::Atk, TagChange::Add),
+                (1, Tag::Def, TagChange::Add),
+                (2, Tag::Def, TagChange::Add),
+                (3, Tag::Atk, TagChange::Remove),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dismissed_suggestions_are_left_out() {
+        let suggestion = TagSuggestion {
+            player_id: 1,
+            tag: Tag::Atk,
+            change: TagChange::Add,
+            reason: String::new(),
+        };
+        let dismissed = HashSet::from([(1, Tag::Atk, TagChange::Add)]);
+        assert!(without_dismissed(vec![suggestion.clone()], &dismissed).is_empty());
+        assert_eq!(
+            without_dismissed(vec![suggestion], &HashSet::new()).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(apply("RUNNER", Tag::Atk, TagChange::Add), "RUNNER,ATK");
+        assert_eq!(apply("ATK,DEF", Tag::Atk, TagChange::Add), "DEF,ATK");
+        assert_eq!(apply("ATK,DEF", Tag::Atk, TagChange::Remove), "DEF");
+        assert_eq!(apply("", Tag::Def, TagChange::Remove), "");
+    }
+}
@@ -0,0 +1,112 @@
+use crate::models::{Player, DEFAULT_TEAM_NAMES};
+
+/// First half of a themed team name
+const ADJECTIVES: [&str; 16] = [
+    "Muddy",
+    "Rusty",
+    "Flying",
+    "Golden",
+    "Sleepy",
+    "Mighty",
+    "Thundering",
+    "Wandering",
+    "Rapid",
+    "Royal",
+    "Stubborn",
+    "Windy",
+    "Electric",
+    "Jolly",
+    "Dizzy",
+    "Grumpy",
+];
+
+/// Second half of a themed team name
+const NOUNS: [&str; 16] = [
+    "Badgers",
+    "Foxes",
+    "Otters",
+    "Magpies",
+    "Wolves",
+    "Hedgehogs",
+    "Pigeons",
+    "Herons",
+    "Hornets",
+    "Stags",
+    "Owls",
+    "Ravens",
+    "Seagulls",
+    "Ferrets",
+    "Moles",
+    "Swans",
+];
+
+/// Stable hash of a split (FNV-1a over each team's sorted player IDs), so the
+/// same teams get the same names on every server and after regenerating
+fn split_hash(team_a: &[i32], team_b: &[i32]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for team in [team_a, team_b] {
+        let mut ids = team.to_vec();
+        ids.sort_unstable();
+        // Separate the teams so moving a player across changes the hash
+        for byte in ids.iter().flat_map(|id| id.to_le_bytes()).chain([0xff; 4]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Themed names for both teams, e.g. ["Muddy Badgers", "Golden Owls"]. The
+/// teams never share an animal.
+pub fn themed_names(team_a: &[i32], team_b: &[i32]) -> [String; 2] {
+    let hash = split_hash(team_a, team_b);
+    let pick = |shift: u32, len: usize| ((hash >> shift) % len as u64) as usize;
+    let noun_a = pick(0, NOUNS.len());
+    // Any animal but Team A's
+    let noun_b = (noun_a + 1 + pick(16, NOUNS.len() - 1)) % NOUNS.len();
+    [
+        format!(
+            "{} {}",
+            ADJECTIVES[pick(8, ADJECTIVES.len())],
+            NOUNS[noun_a]
+        ),
+        format!(
+            "{} {}",
+            ADJECTIVES[pick(24, ADJECTIVES.len())],
+            NOUNS[noun_b]
+        ),
+    ]
+}
+
+/// Names to show for generated teams: themed when the setting is on, else
+/// Team A and Team B
+pub fn team_names(themed: bool, team_a: &[Player], team_b: &[Player]) -> [String; 2] {
+    let ids = |team: &[Player]| team.iter().map(|p| p.id).collect::<Vec<i32>>();
+    if themed {
+        themed_names(&ids(team_a), &ids(team_b))
+    } else {
+        DEFAULT_TEAM_NAMES.map(String::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_follow_the_split() {
+        let names = themed_names(&[1, 2, 3], &[4, 5, 6]);
+        // Player order within a team doesn't matter
+        assert_eq!(themed_names(&[3, 1, 2], &[6, 5, 4]), names);
+        assert_ne!(themed_names(&[1, 2, 4], &[3, 5, 6]), names);
+        assert_eq!(team_names(false, &[], &[]), ["Team A", "Team B"]);
+    }
+
+    #[test]
+    fn test_teams_never_share_an_animal() {
+        for first in 1..200 {
+            let [a, b] = themed_names(&[first, first + 1], &[first + 2]);
+            assert_ne!(a.split(' ').nth(1), b.split(' ').nth(1), "{a} / {b}");
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use crate::formation::default_shape;
+use crate::models::{EloSnapshot, Match, Player, Side};
+use crate::positions::{roles_for_player, Role};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// Fantasy points for turning up, per match
+const POINTS_APPEARANCE: i32 = 1;
+/// Fantasy points for a win or a draw
+const POINTS_WIN: i32 = 3;
+const POINTS_DRAW: i32 = 1;
+/// Fantasy points for a clean sheet (the team conceded nothing)
+const POINTS_CLEAN_SHEET: i32 = 1;
+/// Fantasy points per goal scored (own goals don't count)
+const POINTS_GOAL: i32 = 2;
+/// Fantasy points for being the match's MVP
+const POINTS_MVP: i32 = 3;
+/// Elo gained per point of a player's season score
+const ELO_PER_POINT: f32 = 10.0;
+
+/// A player's season in numbers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeasonLine {
+    pub player_id: i32,
+    pub appearances: usize,
+    /// Elo gained over the season (scaled by participation; negative for a loss)
+    pub elo_gain: f32,
+    pub points: i32,
+}
+
+impl SeasonLine {
+    /// What the Team of the Season is picked on: fantasy points plus a point
+    /// per 10 Elo gained
+    pub fn score(&self) -> f32 {
+        self.points as f32 + self.elo_gain / ELO_PER_POINT
+    }
+}
+
+/// The season's best players, one per slot
+#[derive(Debug, Clone)]
+pub struct TeamOfSeason<'a> {
+    pub keeper: Option<(&'a Player, SeasonLine)>,
+    /// Outfield players per line, back to front, each best first
+    pub lines: Vec<(Role, Vec<(&'a Player, SeasonLine)>)>,
+    /// Matches in the season
+    pub matches: usize,
+    /// Fewest appearances to be picked
+    pub min_appearances: usize,
+}
+
+/// Each player's appearances, Elo gain and fantasy points over the matches
+/// after `since` (if any) up to and including `until`. Voided matches don't count.
+pub fn season_lines(
+    matches: &[Match],
+    since: Option<NaiveDate>,
+    until: NaiveDate,
+) -> HashMap<i32, SeasonLine> {
+    let mut lines: HashMap<i32, SeasonLine> = HashMap::new();
+    for m in in_season(matches, since, until) {
+        let snapshot: HashMap<i32, EloSnapshot> =
+            serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+        let goals = m.goals();
+        for (side, team, scored, conceded) in [
+            (Side::A, &m.team_a, m.score_a, m.score_b),
+            (Side::B, &m.team_b, m.score_b, m.score_a),
+        ] {
+            let result = if scored > conceded {
+                POINTS_WIN
+            } else if scored == conceded {
+                POINTS_DRAW
+            } else {
+                0
+            };
+            for &id in team {
+                let line = lines.entry(id).or_insert(SeasonLine {
+                    player_id: id,
+                    appearances: 0,
+                    elo_gain: 0.0,
+                    points: 0,
+                });
+                line.appearances += 1;
+                if let Some(change) = snapshot.get(&id) {
+                    line.elo_gain += change.delta * change.participation;
+                }
+                let goals_scored = goals
+                    .iter()
+                    .filter(|g| g.team == side && g.scorer == Some(id))
+                    .count() as i32;
+                line.points += POINTS_APPEARANCE
+                    + result
+                    + if conceded == 0 { POINTS_CLEAN_SHEET } else { 0 }
+                    + goals_scored * POINTS_GOAL
+                    + if m.mvp == Some(id) { POINTS_MVP } else { 0 };
+            }
+        }
+    }
+    lines
+}
+
+/// Non-voided matches in a season window
+fn in_season(
+    matches: &[Match],
+    since: Option<NaiveDate>,
+    until: NaiveDate,
+) -> impl Iterator<Item = &Match> {
+    matches.iter().filter(move |m| {
+        m.voided_at.is_none() && m.played_at <= until && since.is_none_or(|s| m.played_at > s)
+    })
+}
+
+/// Pick a season's best `size` players (a keeper plus the default shape for
+/// the rest) from whoever played at least a third of its matches. Best
+/// scores pick first: a goalkeeper takes the keeper's slot and an outfielder
+/// the first of their lines with room. Slots nobody's position fits go to the
+/// best players left.
+pub fn team_of_season<'a>(
+    players: &'a [Player],
+    matches: &[Match],
+    since: Option<NaiveDate>,
+    until: NaiveDate,
+    size: usize,
+) -> TeamOfSeason<'a> {
+    let season_matches = in_season(matches, since, until).count();
+    let min_appearances = season_matches.div_ceil(3).max(1);
+    let lines = season_lines(matches, since, until);
+
+    let mut candidates: Vec<(&Player, SeasonLine)> = players
+        .iter()
+        .filter_map(|p| lines.get(&p.id).map(|line| (p, *line)))
+        .filter(|(_, line)| line.appearances >= min_appearances)
+        .collect();
+    // Lower IDs win ties
+    candidates.sort_by(|a, b| {
+        b.1.score()
+            .total_cmp(&a.1.score())
+            .then(a.0.id.cmp(&b.0.id))
+    });
+
+    let shape = default_shape(size.saturating_sub(1));
+    let mut keeper = None;
+    let mut outfield: [Vec<(&Player, SeasonLine)>; 3] = Default::default();
+    let mut left = Vec::new();
+    for candidate in candidates {
+        let player = candidate.0;
+        if player.is_goalkeeper() {
+            if keeper.is_none() && size > 0 {
+                keeper = Some(candidate);
+                continue;
+            }
+        } else if let Some(line) = roles_for_player(player).iter().find_map(|role| {
+            let i = Role::ALL.iter().position(|r| r == role)?;
+            (outfield[i].len() < shape[i]).then_some(i)
+        }) {
+            outfield[line].push(candidate);
+            continue;
+        }
+        left.push(candidate);
+    }
+
+    // Fill the gaps with the best of the rest, keeper first
+    let mut left = left.into_iter();
+    if keeper.is_none() && size > 0 {
+        keeper = left.next();
+    }
+    for (line, wanted) in outfield.iter_mut().zip(shape) {
+        while line.len() < wanted {
+            let Some(candidate) = left.next() else { break };
+            line.push(candidate);
+        }
+        line.sort_by(|a, b| b.1.score().total_cmp(&a.1.score()));
+    }
+
+    TeamOfSeason {
+        keeper,
+        lines: Role::ALL.into_iter().zip(outfield).collect(),
+        matches: season_matches,
+        min_appearances,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn make_player(id: i32, position: Option<&str>) -> Player {
+        Player {
+            id,
+            name: format!("P{}", id),
+            position: position.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    fn make_match(
+        id: i32,
+        day: u32,
+        team_a: Vec<i32>,
+        team_b: Vec<i32>,
+        score: (i32, i32),
+    ) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, day).unwrap(),
+            team_a,
+            team_b,
+            score_a: score.0,
+            score_b: score.1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_season_lines() {
+        let mut first = make_match(1, 2, vec![1, 2], vec![3], (2, 0));
+        first.elo_snapshot = json!({"1": {"before": 1200.0, "delta": 16.0, "participation": 0.5}});
+        first.goals = json!([
+            {"team": "a", "scorer": 1, "minute": 10},
+            {"team": "a", "scorer": 3, "minute": 20}
+        ]);
+        first.mvp = Some(2);
+        let mut voided = make_match(2, 9, vec![1], vec![3], (5, 0));
+        voided.voided_at = Some(Utc::now());
+        // Before the season started
+        let earlier = make_match(3, 1, vec![1], vec![3], (5, 0));
+
+        let since = NaiveDate::from_ymd_opt(2025, 3, 1);
+        let until = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        let lines = season_lines(&[first, voided, earlier], since, until);
+
+        // Appearance, win, clean sheet and a goal
+        assert_eq!(lines[&1].points, 1 + 3 + 1 + 2);
+        assert_eq!(lines[&1].elo_gain, 8.0);
+        assert!((lines[&1].score() - 7.8).abs() < 0.001);
+        // Appearance, win, clean sheet and MVP
+        assert_eq!(lines[&2].points, 1 + 3 + 1 + 3);
+        // Their own goal earns nothing
+        assert_eq!(lines[&3].points, 1);
+        assert_eq!(lines[&3].appearances, 1);
+    }
+
+    #[test]
+    fn test_slots_follow_positions() {
+        let players = vec![
+            make_player(1, Some("GK")),
+            make_player(2, Some("GK")),
+            make_player(3, Some("DEF")),
+            make_player(4, Some("ATT")),
+            make_player(5, Some("ATT")),
+            make_player(6, None),
+            make_player(7, Some("DEF")),
+        ];
+        // Everyone plays twice; the first-listed team always wins
+        let matches = vec![
+            make_match(1, 2, vec![2, 5, 4, 6], vec![1, 3, 7], (1, 0)),
+            make_match(2, 9, vec![2, 5, 4, 3], vec![1, 6, 7], (2, 1)),
+        ];
+        let until = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+        // 4-a-side: a keeper and 1-1-1
+        let team = team_of_season(&players, &matches, None, until, 4);
+
+        assert_eq!(team.keeper.map(|(p, _)| p.id), Some(2));
+        let ids: Vec<Vec<i32>> = team
+            .lines
+            .iter()
+            .map(|(_, line)| line.iter().map(|(p, _)| p.id).collect())
+            .collect();
+        // Player 4 beats 5 to attack on ID; with no midfielders, 5 is the best left
+        assert_eq!(ids, vec![vec![3], vec![5], vec![4]]);
+        assert_eq!((team.matches, team.min_appearances), (2, 1));
+    }
+}
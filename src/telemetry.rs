@@ -0,0 +1,86 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Service name reported to the collector unless OTEL_SERVICE_NAME is set
+const DEFAULT_SERVICE_NAME: &str = "sunday-football-manager";
+
+/// Set up logging to stdout (stderr for CLI commands whose output goes to
+/// stdout), plus OTLP trace export when OTEL_EXPORTER_OTLP_ENDPOINT (or
+/// OTEL_EXPORTER_OTLP_TRACES_ENDPOINT) is set.
+///
+/// Returns the tracer provider so pending spans can be flushed on shutdown.
+pub fn init(log_to_stderr: bool) -> Option<SdkTracerProvider> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let provider = otlp_enabled().then(build_provider).flatten();
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(DEFAULT_SERVICE_NAME))
+    });
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer().with_writer(if log_to_stderr {
+                BoxMakeWriter::new(std::io::stderr)
+            } else {
+                BoxMakeWriter::new(std::io::stdout)
+            }),
+        )
+        .with(otel)
+        .init();
+
+    if provider.is_some() {
+        tracing::info!("Exporting traces over OTLP");
+    }
+    provider
+}
+
+/// Flush pending spans and stop exporting
+pub fn shutdown(provider: Option<SdkTracerProvider>) {
+    if let Some(provider) = provider {
+        if let Err(e) = provider.shutdown() {
+            tracing::warn!("Failed to flush traces: {}", e);
+        }
+    }
+}
+
+fn otlp_enabled() -> bool {
+    [
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    ]
+    .iter()
+    .any(|var| std::env::var(var).is_ok_and(|v| !v.trim().is_empty()))
+}
+
+/// OTLP/HTTP exporter; endpoint, headers and timeout come from the standard OTEL_* variables
+fn build_provider() -> Option<SdkTracerProvider> {
+    let exporter = match SpanExporter::builder().with_http().build() {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            // The subscriber isn't installed yet, so report directly
+            eprintln!(
+                "Failed to create OTLP exporter, traces won't be exported: {}",
+                e
+            );
+            return None;
+        }
+    };
+
+    let mut resource = Resource::builder();
+    if std::env::var("OTEL_SERVICE_NAME").is_err() {
+        resource = resource.with_service_name(DEFAULT_SERVICE_NAME);
+    }
+
+    Some(
+        SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource.build())
+            .build(),
+    )
+}
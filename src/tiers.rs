@@ -0,0 +1,204 @@
+use crate::form::recent_form;
+use crate::models::{Match, Player};
+use crate::settings::Settings;
+use crate::{db, AppState};
+use std::collections::HashMap;
+
+/// Most rounds of refinement before the clustering settles for what it has
+const MAX_ROUNDS: usize = 100;
+
+/// A skill tier, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tier {
+    S,
+    A,
+    B,
+    C,
+}
+
+impl Tier {
+    pub const ALL: [Tier; 4] = [Tier::S, Tier::A, Tier::B, Tier::C];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Tier::S => "S",
+            Tier::A => "A",
+            Tier::B => "B",
+            Tier::C => "C",
+        }
+    }
+
+    /// Name of the tier's term in a split's cost breakdown
+    pub fn term_name(self) -> &'static str {
+        match self {
+            Tier::S => "S-tier players",
+            Tier::A => "A-tier players",
+            Tier::B => "B-tier players",
+            Tier::C => "C-tier players",
+        }
+    }
+
+    /// CSS class for the tier's badge
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Tier::S => "tier-tag tier-s",
+            Tier::A => "tier-tag tier-a",
+            Tier::B => "tier-tag tier-b",
+            Tier::C => "tier-tag tier-c",
+        }
+    }
+}
+
+/// Split scores into up to four tiers with one-dimensional k-means: start
+/// with centres spread evenly from the best score to the worst, then put each
+/// score with its nearest centre and move each centre to its group's mean
+/// until nothing moves. The highest group is S. Fewer distinct scores than
+/// tiers use only the top tiers.
+pub fn cluster(scores: &[(i32, f32)]) -> HashMap<i32, Tier> {
+    let mut values: Vec<f32> = scores.iter().map(|(_, score)| *score).collect();
+    values.sort_by(|a, b| b.total_cmp(a));
+    values.dedup();
+    let k = values.len().min(Tier::ALL.len());
+    if k == 0 {
+        return HashMap::new();
+    }
+
+    let mut centres: Vec<f32> = (0..k)
+        .map(|i| values[i * (values.len() - 1) / (k - 1).max(1)])
+        .collect();
+    let nearest = |centres: &[f32], score: f32| {
+        (0..centres.len())
+            .min_by(|&a, &b| {
+                (score - centres[a])
+                    .abs()
+                    .total_cmp(&(score - centres[b]).abs())
+            })
+            .unwrap_or(0)
+    };
+    let mut groups: Vec<usize> = scores.iter().map(|(_, s)| nearest(&centres, *s)).collect();
+    for _ in 0..MAX_ROUNDS {
+        for (i, centre) in centres.iter_mut().enumerate() {
+            let members: Vec<f32> = scores
+                .iter()
+                .zip(&groups)
+                .filter(|(_, group)| **group == i)
+                .map(|((_, score), _)| *score)
+                .collect();
+            if !members.is_empty() {
+                *centre = members.iter().sum::<f32>() / members.len() as f32;
+            }
+        }
+        let regrouped: Vec<usize> = scores.iter().map(|(_, s)| nearest(&centres, *s)).collect();
+        if regrouped == groups {
+            break;
+        }
+        groups = regrouped;
+    }
+
+    // Centres stay in order in one dimension, but rank them to be sure
+    let mut ranked: Vec<usize> = (0..k).collect();
+    ranked.sort_by(|a, b| centres[*b].total_cmp(&centres[*a]));
+    scores
+        .iter()
+        .zip(groups)
+        .map(|((id, _), group)| {
+            let rank = ranked.iter().position(|g| *g == group).unwrap_or(0);
+            (*id, Tier::ALL[rank])
+        })
+        .collect()
+}
+
+/// Tier every player with at least one match on Elo plus their recent-form
+/// bonus (as the balancer's form option adds it)
+pub fn player_tiers(
+    players: &[Player],
+    matches: &[Match],
+    settings: &Settings,
+) -> HashMap<i32, Tier> {
+    let form = recent_form(matches, settings.form_games);
+    let scores: Vec<(i32, f32)> = players
+        .iter()
+        .filter(|p| p.matches_played > 0)
+        .map(|p| {
+            let bonus = form.get(&p.id).copied().unwrap_or(0.0) * settings.form_weight;
+            (p.id, p.elo + bonus)
+        })
+        .collect();
+    cluster(&scores)
+}
+
+/// Load everyone's tier (empty if the data can't be loaded)
+pub async fn load_tiers(state: &AppState) -> HashMap<i32, Tier> {
+    let players = state.players.get(&state.db).await;
+    let matches = db::get_all_matches(&state.db).await;
+    match (players, matches) {
+        (Ok(players), Ok(matches)) => player_tiers(&players, &matches, &state.settings.get()),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to load players for tiers: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_finds_natural_groups() {
+        let scores = vec![
+            (1, 1610.0),
+            (2, 1590.0),
+            (3, 1420.0),
+            (4, 1400.0),
+            (5, 1390.0),
+            (6, 1210.0),
+            (7, 1200.0),
+            (8, 1010.0),
+        ];
+        let tiers = cluster(&scores);
+        let ids = |tier| {
+            let mut ids: Vec<i32> = tiers
+                .iter()
+                .filter(|(_, t)| **t == tier)
+                .map(|(id, _)| *id)
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+        assert_eq!(ids(Tier::S), vec![1, 2]);
+        assert_eq!(ids(Tier::A), vec![3, 4, 5]);
+        assert_eq!(ids(Tier::B), vec![6, 7]);
+        assert_eq!(ids(Tier::C), vec![8]);
+    }
+
+    #[test]
+    fn test_cluster_with_few_players() {
+        assert!(cluster(&[]).is_empty());
+        // Equal scores share a tier
+        let tiers = cluster(&[(1, 1200.0), (2, 1200.0), (3, 1100.0)]);
+        assert_eq!(tiers[&1], Tier::S);
+        assert_eq!(tiers[&2], Tier::S);
+        assert_eq!(tiers[&3], Tier::A);
+    }
+
+    #[test]
+    fn test_player_tiers_skip_newcomers() {
+        let players = vec![
+            Player {
+                id: 1,
+                elo: 1300.0,
+                matches_played: 3,
+                ..Default::default()
+            },
+            Player {
+                id: 2,
+                elo: 1250.0,
+                ..Default::default()
+            },
+        ];
+        let tiers = player_tiers(&players, &[], &Settings::default());
+        assert_eq!(tiers.get(&1), Some(&Tier::S));
+        assert!(!tiers.contains_key(&2));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::elo::average_elo;
+use crate::models::Player;
+
+/// Fewest and most squads a tournament can be drawn into
+pub const MIN_SQUADS: usize = 2;
+pub const MAX_SQUADS: usize = 8;
+
+/// A tournament squad and its seed (1 = strongest on average Elo)
+#[derive(Debug, Clone)]
+pub struct Squad {
+    pub seed: usize,
+    pub players: Vec<Player>,
+    pub average_elo: f32,
+}
+
+/// Snake draft: players go best first (by current Elo, lower IDs winning
+/// ties) to squads 1, 2, ..., n, then n, ..., 1, and so on, so no squad gets
+/// every first pick
+pub fn snake_draft(players: &[Player], squads: usize) -> Vec<Vec<Player>> {
+    let mut ranked = players.to_vec();
+    ranked.sort_by(|a, b| b.elo.total_cmp(&a.elo).then(a.id.cmp(&b.id)));
+    let mut drafted: Vec<Vec<Player>> = vec![Vec::new(); squads.max(1)];
+    let n = drafted.len();
+    for (pick, player) in ranked.into_iter().enumerate() {
+        let (round, slot) = (pick / n, pick % n);
+        let squad = if round % 2 == 0 { slot } else { n - 1 - slot };
+        drafted[squad].push(player);
+    }
+    drafted
+}
+
+/// Seed squads by average Elo, strongest first (the draft order breaks ties)
+pub fn seed(squads: Vec<Vec<Player>>) -> Vec<Squad> {
+    let mut seeded: Vec<Squad> = squads
+        .into_iter()
+        .map(|players| Squad {
+            seed: 0,
+            average_elo: average_elo(&players),
+            players,
+        })
+        .collect();
+    seeded.sort_by(|a, b| b.average_elo.total_cmp(&a.average_elo));
+    for (i, squad) in seeded.iter_mut().enumerate() {
+        squad.seed = i + 1;
+    }
+    seeded
+}
+
+/// First-round pairings of a knockout bracket for `squads` seeds: 1 plays the
+/// lowest seed, 2 the next lowest and so on. With a squad count that isn't a
+/// power of two the top seeds get byes (None).
+pub fn first_round(squads: usize) -> Vec<(usize, Option<usize>)> {
+    let size = squads.next_power_of_two();
+    (1..=size / 2)
+        .map(|seed| {
+            let opponent = size + 1 - seed;
+            (seed, (opponent <= squads).then_some(opponent))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_players(count: i32) -> Vec<Player> {
+        (1..=count)
+            .map(|id| Player {
+                id,
+                name: format!("P{}", id),
+                elo: 1500.0 - id as f32 * 10.0,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_snake_draft() {
+        let squads = snake_draft(&make_players(7), 3);
+        let ids: Vec<Vec<i32>> = squads
+            .iter()
+            .map(|s| s.iter().map(|p| p.id).collect())
+            .collect();
+        // Picks 1-3 forwards, 4-6 backwards, then forwards again
+        assert_eq!(ids, vec![vec![1, 6, 7], vec![2, 5], vec![3, 4]]);
+    }
+
+    #[test]
+    fn test_seed_by_average_elo() {
+        let seeded = seed(snake_draft(&make_players(8), 4));
+        // Snake drafting leaves every squad level, so draft order stands
+        assert_eq!(seeded[0].players[0].id, 1);
+        assert_eq!(
+            seeded.iter().map(|s| s.seed).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+
+        let seeded = seed(vec![make_players(2)[1..].to_vec(), make_players(1)]);
+        assert_eq!(seeded[0].players[0].id, 1);
+        assert_eq!(seeded[1].average_elo, 1480.0);
+    }
+
+    #[test]
+    fn test_first_round() {
+        assert_eq!(first_round(4), vec![(1, Some(4)), (2, Some(3))]);
+        assert_eq!(
+            first_round(6),
+            vec![(1, None), (2, None), (3, Some(6)), (4, Some(5))]
+        );
+        assert_eq!(first_round(2), vec![(1, Some(2))]);
+    }
+}
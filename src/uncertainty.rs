@@ -0,0 +1,133 @@
+use crate::db;
+use crate::models::{EloSnapshot, Match};
+use crate::replay::chronological;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// Uncertainty of a rating with no matches behind it, in Elo
+pub const UNCERTAINTY_PRIOR: f32 = 200.0;
+
+/// Running ± band for one player's rating.
+///
+/// The band shrinks with the number of matches played (weighted by
+/// participation) and widens with how much their rating swings from match
+/// to match: `sqrt(PRIOR² / (1 + games) + mean squared Elo change)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Uncertainty {
+    games: f32,
+    squared_changes: f32,
+}
+
+impl Uncertainty {
+    /// Record one match's effective Elo change
+    pub fn add(&mut self, effective_delta: f32, participation: f32) {
+        self.games += participation;
+        self.squared_changes += effective_delta * effective_delta;
+    }
+
+    /// Current ± band in Elo
+    pub fn band(&self) -> f32 {
+        let volatility = if self.games > 0.0 {
+            self.squared_changes / self.games
+        } else {
+            0.0
+        };
+        (UNCERTAINTY_PRIOR * UNCERTAINTY_PRIOR / (1.0 + self.games) + volatility).sqrt()
+    }
+}
+
+/// Current ± band per player ID from all non-voided matches
+/// (players without matches are absent: their band is the prior)
+pub fn rating_uncertainty(matches: &[Match]) -> HashMap<i32, f32> {
+    let mut trackers: HashMap<i32, Uncertainty> = HashMap::new();
+    for m in chronological(matches) {
+        if m.voided_at.is_some() {
+            continue;
+        }
+        let snapshot: HashMap<i32, EloSnapshot> =
+            serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
+        for (id, change) in snapshot {
+            trackers
+                .entry(id)
+                .or_default()
+                .add(change.delta * change.participation, change.participation);
+        }
+    }
+    trackers
+        .into_iter()
+        .map(|(id, tracker)| (id, tracker.band()))
+        .collect()
+}
+
+/// Load the current ± band for every player with matches
+pub async fn load_uncertainty(pool: &PgPool) -> HashMap<i32, f32> {
+    let matches = db::get_all_matches(pool).await.unwrap_or_default();
+    rating_uncertainty(&matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+    use serde_json::json;
+
+    fn make_match(id: i32, snapshot: serde_json::Value) -> Match {
+        Match {
+            id,
+            played_at: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + chrono::Days::new(id as u64),
+            team_a: vec![1],
+            team_b: vec![2],
+            score_a: 1,
+            score_b: 0,
+            elo_snapshot: snapshot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_band_shrinks_with_games() {
+        assert_eq!(Uncertainty::default().band(), UNCERTAINTY_PRIOR);
+
+        let mut few = Uncertainty::default();
+        let mut many = Uncertainty::default();
+        for _ in 0..3 {
+            few.add(16.0, 1.0);
+        }
+        for _ in 0..40 {
+            many.add(16.0, 1.0);
+        }
+        assert!((few.band() - (200.0_f32 * 200.0 / 4.0 + 256.0).sqrt()).abs() < 0.01);
+        assert!(many.band() < few.band() / 2.0);
+    }
+
+    #[test]
+    fn test_band_widens_with_volatility() {
+        let mut steady = Uncertainty::default();
+        let mut swingy = Uncertainty::default();
+        for _ in 0..20 {
+            steady.add(4.0, 1.0);
+            swingy.add(40.0, 1.0);
+        }
+        assert!(swingy.band() > steady.band());
+    }
+
+    #[test]
+    fn test_rating_uncertainty_skips_voided() {
+        let mut voided = make_match(2, json!({ "2": { "before": 1200.0, "delta": 50.0 } }));
+        voided.voided_at = Some(Utc::now());
+        let matches = vec![
+            make_match(
+                1,
+                json!({ "1": { "before": 1200.0, "delta": 16.0, "participation": 0.5 } }),
+            ),
+            voided,
+        ];
+
+        let bands = rating_uncertainty(&matches);
+
+        // Half a game played, changing by 8
+        let expected = (200.0_f32 * 200.0 / 1.5 + 64.0 / 0.5).sqrt();
+        assert!((bands[&1] - expected).abs() < 0.01);
+        assert!(!bands.contains_key(&2));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::models::AbsenceReason;
+use crate::rsvp::{absence_report, AbsenceReport, ReasonCounts, ABSENCE_WEEKS, RSVP_WEEKS};
+use crate::{db, AppState};
+use chrono::Duration;
+use maud::{html, Markup};
+use std::collections::HashMap;
+
+/// Reason columns, with answers that gave none last
+const COLUMNS: [Option<AbsenceReason>; 5] = [
+    Some(AbsenceReason::Injured),
+    Some(AbsenceReason::Away),
+    Some(AbsenceReason::Work),
+    Some(AbsenceReason::Other),
+    None,
+];
+
+fn count(counts: &ReasonCounts, reason: Option<AbsenceReason>) -> usize {
+    counts.get(&reason).copied().unwrap_or(0)
+}
+
+/// Header cells for the reason columns
+fn reason_headers() -> Markup {
+    html! {
+        @for reason in COLUMNS {
+            th { (reason.map(|r| r.label()).unwrap_or("No reason")) }
+        }
+    }
+}
+
+/// Count cells for the reason columns
+fn reason_cells(counts: &ReasonCounts) -> Markup {
+    html! {
+        @for reason in COLUMNS {
+            td { (count(counts, reason)) }
+        }
+    }
+}
+
+/// Out answers by match day and by player (a section of the Admin page)
+pub fn render_absences(report: &AbsenceReport, names: &HashMap<i32, &str>) -> Markup {
+    let mut players: Vec<(&i32, &ReasonCounts)> = report.by_player.iter().collect();
+    players.sort_by_key(|(id, counts)| (std::cmp::Reverse(counts.values().sum::<usize>()), **id));
+
+    html! {
+        h3 { "Absences" }
+        @if report.by_day.is_empty() {
+            p class="secondary" { "Nobody has answered Out in the last " (ABSENCE_WEEKS) " weeks." }
+        } @else {
+            p class="secondary" {
+                "Out answers on " a href="/me" { "/me" } " for the last " (ABSENCE_WEEKS)
+                " weeks and the upcoming match days. Lots of injuries at once is a crisis; "
+                "the same names out with no reason may be losing interest."
+            }
+            details {
+                summary { "By match day" }
+                div class="table-container" {
+                    table {
+                        thead { tr { th { "Match day" } (reason_headers()) } }
+                        tbody {
+                            @for (day, counts) in report.by_day.iter().rev() {
+                                tr {
+                                    td { (day.format("%a %-d %b")) }
+                                    (reason_cells(counts))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            details {
+                summary { "By player" }
+                div class="table-container" {
+                    table {
+                        thead { tr { th { "Player" } (reason_headers()) } }
+                        tbody {
+                            @for (id, counts) in players {
+                                tr {
+                                    td { (names.get(id).copied().unwrap_or("Former player")) }
+                                    (reason_cells(counts))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Load and render the absence report
+pub(crate) async fn absence_section(state: &AppState) -> Markup {
+    let today = state.settings.get().today();
+    let from = today - Duration::weeks(ABSENCE_WEEKS as i64);
+    let to = today + Duration::weeks(RSVP_WEEKS as i64);
+    let rsvps = db::get_rsvps(&state.db, from, to).await.unwrap_or_default();
+    let players = state.players.get(&state.db).await.unwrap_or_default();
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    render_absences(&absence_report(&rsvps), &names)
+}
@@ -0,0 +1,289 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::models::{Match, RosterChange, RsvpStatus, TableStats, WebhookDelivery};
+use crate::rsvp::{self, next_match_days};
+use crate::views::errors::error_page;
+use crate::views::layout::{base, AuthState};
+use crate::webhooks::RECENT_DELIVERIES;
+use crate::{db, discipline, injuries, AppState};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use maud::{html, Markup};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Matches listed under "Recently Recorded"
+const RECENT_MATCHES: i64 = 5;
+/// Roster changes listed (the log itself keeps 30 days)
+const RECENT_ROSTER_CHANGES: i64 = 20;
+
+/// A byte count for people, e.g. "12.3 MB"
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn render_matches(matches: &[Match], names: &HashMap<i32, &str>) -> Markup {
+    html! {
+        h3 { "Recently Recorded" }
+        @if matches.is_empty() {
+            p class="secondary" { "No matches recorded yet." }
+        } @else {
+            div class="table-container" {
+                table {
+                    thead {
+                        tr { th { "Match" } th { "Played" } th { "Recorded" } th { "Players" } }
+                    }
+                    tbody {
+                        @for m in matches {
+                            @let [name_a, name_b] = m.team_names();
+                            tr {
+                                td {
+                                    a href=(format!("/history#match-{}", m.id)) {
+                                        (name_a) " " (m.score_a) " - " (m.score_b) " " (name_b)
+                                    }
+                                    @if m.voided_at.is_some() {
+                                        " " span class="tag" { "Voided" }
+                                    }
+                                }
+                                td { (m.played_at.format("%a %-d %b %Y")) }
+                                td { (m.created_at.format("%Y-%m-%d %H:%M UTC")) }
+                                td {
+                                    (m.team_a.len() + m.team_b.len())
+                                    @if let Some(mvp) = m.mvp.and_then(|id| names.get(&id)) {
+                                        br;
+                                        small class="secondary" { "MVP: " (mvp) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn render_roster_changes(changes: &[RosterChange]) -> Markup {
+    html! {
+        h3 { "Roster Changes" }
+        @if changes.is_empty() {
+            p class="secondary" { "No players added, edited or removed in the last 30 days." }
+        } @else {
+            div class="table-container" {
+                table {
+                    thead {
+                        tr { th { "When" } th { "Player" } th { "Change" } }
+                    }
+                    tbody {
+                        @for change in changes {
+                            tr {
+                                td { (change.changed_at.format("%Y-%m-%d %H:%M UTC")) }
+                                td { (change.player_name) }
+                                td {
+                                    (change.change)
+                                    @if !change.detail.is_empty() {
+                                        ": " (change.detail)
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn render_failed_deliveries(failed: &[&WebhookDelivery], failed_jobs: usize) -> Markup {
+    html! {
+        h3 { "Failed Notifications" }
+        @if failed.is_empty() && failed_jobs == 0 {
+            p class="secondary" { "Nothing has failed recently." }
+        } @else {
+            @if failed_jobs > 0 {
+                p {
+                    (failed_jobs) " background job(s) ran out of attempts. "
+                    a href="/admin#job-list" { "Retry or dismiss them" }
+                }
+            }
+            @if !failed.is_empty() {
+                div class="table-container" {
+                    table {
+                        thead {
+                            tr { th { "When" } th { "Webhook" } th { "Event" } th { "Status" } }
+                        }
+                        tbody {
+                            @for delivery in failed {
+                                tr {
+                                    td { (delivery.attempted_at.format("%Y-%m-%d %H:%M UTC")) }
+                                    td { code { (delivery.url) } }
+                                    td { (delivery.event) }
+                                    td { (delivery.status) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn render_storage(
+    database_size: Option<i64>,
+    tables: &[TableStats],
+    pool: &sqlx::PgPool,
+) -> Markup {
+    html! {
+        h3 { "Storage" }
+        p class="secondary" {
+            @if let Some(bytes) = database_size {
+                "Database size: " (format_bytes(bytes)) ". "
+            }
+            "Connections: " (pool.size()) " open (" (pool.num_idle()) " idle) of "
+            (pool.options().get_max_connections()) "."
+        }
+        @if !tables.is_empty() {
+            div class="table-container" {
+                table {
+                    thead {
+                        tr { th { "Table" } th { "Rows (approx.)" } th { "Size" } }
+                    }
+                    tbody {
+                        @for table in tables {
+                            tr {
+                                td { code { (table.name) } }
+                                td { (table.rows) }
+                                td { (format_bytes(table.bytes)) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Activity page - recent matches, roster changes, RSVPs, failures and
+/// storage at a glance (admins only)
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> Response {
+    let settings = state.settings.get();
+    let logged_in = is_authenticated(&jar, &state);
+    let auth = AuthState::new(state.auth.is_some(), logged_in);
+    if !logged_in {
+        return error_page(
+            StatusCode::UNAUTHORIZED,
+            "Log in above to see the group's activity.",
+            &auth,
+            &settings.group_name,
+        );
+    }
+    let players = state.players.get(&state.db).await.unwrap_or_default();
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    let matches = db::get_recently_recorded_matches(&state.db, RECENT_MATCHES)
+        .await
+        .unwrap_or_default();
+    let roster_changes = db::get_roster_changes(&state.db, RECENT_ROSTER_CHANGES)
+        .await
+        .unwrap_or_default();
+
+    // Who has yet to answer for the next match day (unavailable players aren't asked)
+    let match_on = next_match_days(settings.today(), 1)[0];
+    let rsvps = rsvp::load_for_day(&state.db, match_on).await;
+    let rsvp_counts = rsvp::tally(&rsvps);
+    let suspensions = discipline::load_active(&state.db, settings.today()).await;
+    let injuries = injuries::load_active(&state.db).await;
+    let unanswered: Vec<&str> = players
+        .iter()
+        .filter(|p| {
+            !rsvps.contains_key(&p.id)
+                && !suspensions.contains_key(&p.id)
+                && !injuries.contains_key(&p.id)
+        })
+        .map(|p| p.name.as_str())
+        .collect();
+    let registrations = db::get_registrations(&state.db)
+        .await
+        .unwrap_or_default()
+        .len();
+    let pending_jobs = db::count_pending_jobs(&state.db).await.unwrap_or_default();
+
+    let deliveries = db::get_webhook_deliveries(&state.db, RECENT_DELIVERIES)
+        .await
+        .unwrap_or_default();
+    let failed_deliveries: Vec<&WebhookDelivery> =
+        deliveries.iter().filter(|d| !d.delivered).collect();
+    let failed_jobs = db::get_failed_jobs(&state.db)
+        .await
+        .unwrap_or_default()
+        .len();
+
+    let database_size = db::get_database_size(&state.db)
+        .await
+        .inspect_err(|e| tracing::error!("Failed to read the database size: {}", e))
+        .ok();
+    let tables = db::get_table_stats(&state.db).await.unwrap_or_default();
+
+    let content = html! {
+        h2 { "Activity" }
+        p class="secondary" {
+            "What has happened lately. Settings, webhooks and jobs are managed on the "
+            a href="/admin" { "Admin page" } "."
+        }
+
+        (render_matches(&matches, &names))
+
+        (render_roster_changes(&roster_changes))
+
+        h3 { "Pending" }
+        p {
+            "RSVPs for " (match_on.format("%a %-d %b")) ": "
+            @for (i, status) in RsvpStatus::ALL.iter().enumerate() {
+                @if i > 0 { ", " }
+                (rsvp_counts.get(status).copied().unwrap_or_default()) " " (status.label().to_lowercase())
+            }
+            ", " (unanswered.len()) " yet to answer."
+        }
+        @if !unanswered.is_empty() {
+            p class="secondary" { (unanswered.join(", ")) }
+        }
+        p {
+            (registrations) " sign-up(s) awaiting approval on the "
+            a href="/roster" { "Roster page" } ", "
+            (pending_jobs) " background job(s) waiting to run."
+        }
+
+        (render_failed_deliveries(&failed_deliveries, failed_jobs))
+
+        (render_storage(database_size, &tables, &state.db))
+    };
+
+    Html(base("Activity", "admin", &auth, &settings.group_name, content).into_string())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(999), "999 B");
+        assert_eq!(format_bytes(1000), "1.0 KB");
+        assert_eq!(format_bytes(8_650_000), "8.7 MB");
+        assert_eq!(format_bytes(2_000_000_000_000_000), "2000.0 TB");
+    }
+}
@@ -0,0 +1,592 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::balance::BALANCERS;
+use crate::replay::replay;
+use crate::settings::{Settings, KEYS};
+use crate::views::absences::absence_section;
+use crate::views::errors::{error_fragment, error_page};
+use crate::views::flags::flag_queue;
+use crate::views::jobs::render_jobs;
+use crate::views::layout::{base, render_elo_delta, AuthState};
+use crate::views::webhooks::render_webhooks;
+use crate::webhooks::RECENT_DELIVERIES;
+use crate::what_if::{compare_ratings, parse_what_if, WhatIfParams, WhatIfRow};
+use crate::{db, AppState};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::extract::Form;
+use maud::{html, Markup};
+use std::sync::Arc;
+
+/// Admin page - group settings saved to the database
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> Response {
+    let settings = state.settings.get();
+    let logged_in = is_authenticated(&jar, &state);
+    let auth = AuthState::new(state.auth.is_some(), logged_in);
+    if !logged_in {
+        return error_page(
+            StatusCode::UNAUTHORIZED,
+            "Log in above to change the group's settings.",
+            &auth,
+            &settings.group_name,
+        );
+    }
+    let saved = db::get_settings(&state.db).await.unwrap_or_default();
+    let webhooks = db::get_webhooks(&state.db).await.unwrap_or_default();
+    let deliveries = db::get_webhook_deliveries(&state.db, RECENT_DELIVERIES)
+        .await
+        .unwrap_or_default();
+    let pending_jobs = db::count_pending_jobs(&state.db).await.unwrap_or_default();
+    let failed_jobs = db::get_failed_jobs(&state.db).await.unwrap_or_default();
+    let flags = flag_queue(&state).await;
+    let absences = absence_section(&state).await;
+    let players = state.players.get(&state.db).await.unwrap_or_default();
+
+    let content = html! {
+        h2 { "Admin" }
+        p class="secondary" {
+            "Settings start from the environment variables; anything changed here is saved "
+            "and applied straight away. "
+            @if saved.is_empty() {
+                "Nothing is overridden yet."
+            } @else {
+                "Overridden: "
+                @for (i, (key, _)) in saved.iter().enumerate() {
+                    @if i > 0 { ", " }
+                    code { (key) }
+                }
+                "."
+            }
+            " Recent matches, roster changes and failures are on the "
+            a href="/admin/activity" { "Activity page" } "."
+        }
+
+        form id="settings-form" hx-post="/api/admin/settings" hx-target="#settings-result" {
+            fieldset {
+                legend { "Group" }
+                label {
+                    "Group name"
+                    input type="text" name="group_name" value=(settings.value("group_name")) maxlength="60" required;
+                }
+                label {
+                    "Kickoff (time and place for shared team messages)"
+                    input type="text" name="kickoff" value=(settings.value("kickoff")) maxlength="100" placeholder="10:00, Hackney Marshes pitch 3";
+                }
+                label {
+                    "RSVP deadline (teams are picked from RSVPs then; empty = off)"
+                    input type="text" name="rsvp_deadline" value=(settings.value("rsvp_deadline")) placeholder="Sat 20:00";
+                }
+                div class="grid" {
+                    label {
+                        "Bib colours (Team A, Team B)"
+                        input type="text" name="bib_colors" value=(settings.value("bib_colors")) required;
+                    }
+                    label {
+                        "Timezone"
+                        input type="text" name="timezone" value=(settings.value("timezone")) placeholder="Europe/London" required;
+                    }
+                    label {
+                        "Players per team"
+                        input type="number" name="players_per_team" value=(settings.value("players_per_team")) min="2" max="11" required;
+                    }
+                }
+                label {
+                    "Hours players can fix a result they played in (0 = admins only)"
+                    input type="number" name="edit_window_hours" value=(settings.value("edit_window_hours")) min="0" max="168" required;
+                }
+            }
+
+            fieldset {
+                legend { "Team balancing" }
+                div class="grid" {
+                    label {
+                        "Attribute weights"
+                        input type="text" name="attribute_weights" value=(settings.value("attribute_weights")) required;
+                    }
+                    label {
+                        "Form games"
+                        input type="number" name="form_games" value=(settings.value("form_games")) min="1" required;
+                    }
+                    label {
+                        "Form weight (Elo)"
+                        input type="number" name="form_weight" value=(settings.value("form_weight")) step="any" required;
+                    }
+                    label {
+                        "Default balancer"
+                        select name="balancer" {
+                            @for b in BALANCERS {
+                                option value=(b.key()) selected[settings.balancer.key() == b.key()] { (b.label()) }
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Nominate captains for generated teams"
+                    select name="captain_pick" {
+                        @for (value, label) in [("", "Off"), ("elo", "Highest Elo"), ("rotation", "Rotation (fewest times captain)")] {
+                            option value=(value) selected[settings.value("captain_pick") == value] { (label) }
+                        }
+                    }
+                }
+            }
+
+            fieldset {
+                legend { "Ratings" }
+                p class="secondary" {
+                    "Changes apply to matches recorded from now on. Run "
+                    code { "sfm recalc-elo" }
+                    " to replay the history with them."
+                }
+                div class="grid" {
+                    label {
+                        "Regression toward 1200 per match"
+                        input type="number" name="elo_regression" value=(settings.value("elo_regression")) min="0" max="1" step="any" required;
+                    }
+                    label {
+                        "Handicap per extra player"
+                        input type="number" name="handicap_per_player" value=(settings.value("handicap_per_player")) min="0" step="any" required;
+                    }
+                    label {
+                        "Handicap curve"
+                        input type="number" name="handicap_curve" value=(settings.value("handicap_curve")) min="0" step="any" required;
+                    }
+                    label {
+                        "Handicap for no goalkeeper"
+                        input type="number" name="keeper_handicap" value=(settings.value("keeper_handicap")) min="0" step="any" required;
+                    }
+                    label {
+                        "Max Elo change per match (blank = no cap)"
+                        input type="number" name="max_elo_change" value=(settings.value("max_elo_change")) min="1" step="any";
+                    }
+                    label {
+                        "Days away before a return boost (blank = off)"
+                        input type="number" name="absence_days" value=(settings.value("absence_days")) min="1";
+                    }
+                }
+                label {
+                    "Rating anchor (their Elo never moves; their share goes to teammates)"
+                    select name="anchor_player" {
+                        option value="" selected[settings.elo.anchor.is_none()] { "None" }
+                        @for player in &players {
+                            option value=(player.id) selected[settings.elo.anchor == Some(player.id)] {
+                                (player.name) " (" (format!("{:.0}", player.elo)) ")"
+                            }
+                        }
+                    }
+                }
+                label {
+                    "New player starting Elo from the questionnaire"
+                    input type="text" name="onboarding_formula" value=(settings.value("onboarding_formula")) required;
+                    small class="secondary" {
+                        "base + experience × step (new 0 to club 3) + fitness × (answer - 3) + keeper for goalkeepers"
+                    }
+                }
+            }
+
+            fieldset {
+                legend { "Features" }
+                label {
+                    input type="checkbox" role="switch" name="private_pages" value="true" checked[settings.private_pages];
+                    "Private pages (login required to view anything)"
+                }
+                @if state.auth.is_none() {
+                    small class="secondary" { "Has no effect until AUTH_PASSWORD is set." }
+                }
+                label {
+                    input type="checkbox" role="switch" name="confirm_results" value="true" checked[settings.confirm_results];
+                    "Results need a second person to confirm them before they count"
+                }
+                label {
+                    input type="checkbox" role="switch" name="team_names" value="true" checked[settings.team_names];
+                    "Themed team names (e.g. Muddy Badgers) instead of Team A and Team B"
+                }
+                label {
+                    input type="checkbox" role="switch" name="peer_surveys" value="true" checked[settings.peer_surveys];
+                    "Monthly peer survey: players rate their recent teammates on their Me page"
+                }
+            }
+
+            div class="grid" {
+                button type="submit" { "Save Settings" }
+                button
+                    type="button"
+                    class="secondary"
+                    hx-post="/api/admin/settings/reset"
+                    hx-target="#settings-result"
+                    hx-confirm="Forget every saved setting and go back to the environment?"
+                {
+                    "Reset to Environment"
+                }
+            }
+        }
+        div id="settings-result" {}
+
+        h3 { "Rating What-If" }
+        p class="secondary" {
+            "Replay the whole match history with different rating parameters and see how "
+            "final ratings and rankings would change. Nothing is saved."
+        }
+        form id="what-if-form" hx-post="/api/admin/what-if" hx-target="#what-if-result" {
+            div class="grid" {
+                label {
+                    "K-factor"
+                    input type="number" name="k_factor" value=(settings.elo.k_factor) min="1" step="any" required;
+                }
+                label {
+                    "Goal-difference cap"
+                    input type="number" name="gd_cap" value=(settings.elo.gd_cap) min="1" step="any" required;
+                }
+                label {
+                    "Handicap per extra player"
+                    input type="number" name="handicap_per_player" value=(settings.elo.handicap_per_player) min="0" step="any" required;
+                }
+                label {
+                    "Handicap curve"
+                    input type="number" name="handicap_curve" value=(settings.elo.handicap_curve) min="0" step="any" required;
+                }
+                label {
+                    "Regression"
+                    input type="number" name="regression" value=(settings.elo.regression) min="0" max="1" step="any" required;
+                }
+            }
+            button type="submit" hx-indicator="#what-if-spinner" {
+                "Run What-If"
+                span id="what-if-spinner" class="htmx-indicator spinner" {}
+            }
+        }
+        div id="what-if-result" {}
+
+        (flags)
+
+        (absences)
+
+        (render_webhooks(&webhooks, &deliveries))
+
+        (render_jobs(pending_jobs, &failed_jobs))
+    };
+
+    Html(base("Admin", "admin", &auth, &settings.group_name, content).into_string()).into_response()
+}
+
+/// Form data for the admin settings (values as in the matching environment variables)
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct SettingsForm {
+    #[serde(default)]
+    group_name: String,
+    /// e.g. "10:00, Hackney Marshes" (may be empty)
+    #[serde(default)]
+    kickoff: String,
+    /// Weekday and time, e.g. "Sat 20:00" (empty = off)
+    #[serde(default)]
+    rsvp_deadline: String,
+    /// Team A and Team B colours, e.g. "Orange,Blue"
+    #[serde(default)]
+    bib_colors: String,
+    /// IANA timezone name, e.g. "Europe/London"
+    #[serde(default)]
+    timezone: String,
+    /// 2-11
+    #[serde(default)]
+    players_per_team: String,
+    /// 0-168
+    #[serde(default)]
+    edit_window_hours: String,
+    /// Hold results for confirmation when present
+    confirm_results: Option<String>,
+    /// Themed team names when present
+    team_names: Option<String>,
+    /// Monthly peer survey on the Me page when present
+    peer_surveys: Option<String>,
+    /// "elo" or "rotation" (empty = off)
+    #[serde(default)]
+    captain_pick: String,
+    /// "exact", "randomized", "annealing" or "draft"
+    #[serde(default)]
+    balancer: String,
+    /// e.g. "stamina=20,pace=20,age=10"
+    #[serde(default)]
+    attribute_weights: String,
+    #[serde(default)]
+    form_games: String,
+    #[serde(default)]
+    form_weight: String,
+    /// 0-1
+    #[serde(default)]
+    elo_regression: String,
+    #[serde(default)]
+    handicap_per_player: String,
+    #[serde(default)]
+    handicap_curve: String,
+    /// 0 = off
+    #[serde(default)]
+    keeper_handicap: String,
+    /// Blank for no cap
+    #[serde(default)]
+    max_elo_change: String,
+    /// Blank for no return boost
+    #[serde(default)]
+    absence_days: String,
+    /// Player ID whose rating never moves (blank for none)
+    #[serde(default)]
+    anchor_player: String,
+    /// e.g. "base=1100,experience=100,fitness=25,keeper=0"
+    #[serde(default)]
+    onboarding_formula: String,
+    /// Require login to view pages when present
+    private_pages: Option<String>,
+}
+
+impl SettingsForm {
+    /// Submitted values by setting key
+    fn values(&self) -> [(&'static str, &str); KEYS.len()] {
+        [
+            ("group_name", &self.group_name),
+            ("kickoff", &self.kickoff),
+            ("rsvp_deadline", &self.rsvp_deadline),
+            ("bib_colors", &self.bib_colors),
+            ("timezone", &self.timezone),
+            ("players_per_team", &self.players_per_team),
+            ("edit_window_hours", &self.edit_window_hours),
+            ("confirm_results", checkbox(&self.confirm_results)),
+            ("team_names", checkbox(&self.team_names)),
+            ("peer_surveys", checkbox(&self.peer_surveys)),
+            ("captain_pick", &self.captain_pick),
+            ("balancer", &self.balancer),
+            ("attribute_weights", &self.attribute_weights),
+            ("form_games", &self.form_games),
+            ("form_weight", &self.form_weight),
+            ("elo_regression", &self.elo_regression),
+            ("handicap_per_player", &self.handicap_per_player),
+            ("handicap_curve", &self.handicap_curve),
+            ("keeper_handicap", &self.keeper_handicap),
+            ("max_elo_change", &self.max_elo_change),
+            ("absence_days", &self.absence_days),
+            ("anchor_player", &self.anchor_player),
+            ("onboarding_formula", &self.onboarding_formula),
+            ("private_pages", checkbox(&self.private_pages)),
+        ]
+    }
+}
+
+/// A switch's setting value: browsers only submit checked boxes
+fn checkbox(field: &Option<String>) -> &'static str {
+    if field.is_some() {
+        "true"
+    } else {
+        "false"
+    }
+}
+
+/// Save the settings and apply them without a restart (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/admin/settings",
+    tag = "admin",
+    request_body(content = SettingsForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Settings saved; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 422, description = "Invalid setting", content_type = "text/html"),
+        (status = 500, description = "Database error", content_type = "text/html")
+    )
+)]
+pub async fn save_settings(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<SettingsForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    let env = Settings::from_env();
+    let mut settings = env.clone();
+    for (key, value) in form.values() {
+        if let Err(e) = settings.set(key, value) {
+            return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e);
+        }
+    }
+
+    // Only values that differ from the environment are saved, so changing an
+    // environment variable still takes effect for everything else
+    let result: Result<(), sqlx::Error> = async {
+        let mut tx = state.db.begin().await?;
+        db::clear_settings(&mut *tx).await?;
+        for (key, _) in KEYS {
+            let value = settings.value(key);
+            if value != env.value(key) {
+                db::save_setting(&mut *tx, key, &value).await?;
+            }
+        }
+        tx.commit().await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("Failed to save settings: {}", e);
+        return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save settings");
+    }
+
+    state.settings.replace(settings);
+    state.events.settings_changed();
+    tracing::info!("Settings updated from the admin page");
+    refreshed()
+}
+
+/// Forget every saved setting and go back to the environment (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/admin/settings/reset",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Saved settings cleared; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 500, description = "Database error", content_type = "text/html")
+    )
+)]
+pub async fn reset_settings(State(state): State<Arc<AppState>>, jar: AuthJar) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    if let Err(e) = db::clear_settings(&state.db).await {
+        tracing::error!("Failed to reset settings: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to reset settings",
+        );
+    }
+
+    state.settings.replace(Settings::from_env());
+    state.events.settings_changed();
+    tracing::info!("Settings reset to the environment from the admin page");
+    refreshed()
+}
+
+/// Rating parameters to replay the history with (empty = current value)
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct WhatIfForm {
+    /// Elo K-factor (above 0)
+    #[serde(default)]
+    k_factor: String,
+    /// Cap on the goal-difference multiplier (1 or more)
+    #[serde(default)]
+    gd_cap: String,
+    #[serde(default)]
+    handicap_per_player: String,
+    #[serde(default)]
+    handicap_curve: String,
+    /// Pull toward 1200 per match, 0-1
+    #[serde(default)]
+    regression: String,
+}
+
+/// Replay the match history with other rating parameters and compare final
+/// ratings and ranks with the current ones, without saving anything (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/admin/what-if",
+    tag = "admin",
+    request_body(content = WhatIfForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Ratings and ranks under both sets of parameters", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 422, description = "Invalid parameter", content_type = "text/html"),
+        (status = 500, description = "Database error", content_type = "text/html")
+    )
+)]
+pub async fn what_if(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<WhatIfForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    let current = state.settings.get().elo;
+    let params = WhatIfParams {
+        k_factor: &form.k_factor,
+        gd_cap: &form.gd_cap,
+        handicap_per_player: &form.handicap_per_player,
+        handicap_curve: &form.handicap_curve,
+        regression: &form.regression,
+    };
+    let config = match parse_what_if(&params, current) {
+        Ok(config) => config,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+
+    let (Ok(players), Ok(initial_elo), Ok(matches), Ok(seasons)) = (
+        db::get_all_players(&state.db).await,
+        db::get_initial_elos(&state.db).await,
+        db::get_all_matches(&state.db).await,
+        db::get_seasons(&state.db).await,
+    ) else {
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load the match history",
+        );
+    };
+
+    // Both sides are replayed, so the comparison shows only the parameters' effect
+    let now = replay(&players, &initial_elo, &matches, &seasons, &current);
+    let tried = replay(&players, &initial_elo, &matches, &seasons, &config);
+    let rows = compare_ratings(&players, &now.ratings, &tried.ratings);
+
+    Html(render_what_if(&rows).into_string()).into_response()
+}
+
+/// What-if results: a summary, then every rated player by what-if rank
+fn render_what_if(rows: &[WhatIfRow]) -> Markup {
+    if rows.is_empty() {
+        return html! { p { "No matches recorded yet." } };
+    }
+    let mean_change = rows
+        .iter()
+        .map(|r| (r.what_if_elo - r.elo).abs())
+        .sum::<f32>()
+        / rows.len() as f32;
+    let moved = rows.iter().filter(|r| r.rank_change() != 0).count();
+
+    html! {
+        p {
+            "Ratings move " (format!("{:.0}", mean_change)) " Elo on average; "
+            (moved) " of " (rows.len()) " players change rank."
+        }
+        div class="table-container" {
+            table {
+                thead {
+                    tr {
+                        th { "#" } th { "Player" } th { "Now" } th { "What-If" } th { "Change" } th { "Rank" }
+                    }
+                }
+                tbody {
+                    @for row in rows {
+                        tr {
+                            td { (row.what_if_rank) }
+                            td { (row.name) }
+                            td { (format!("{:.0}", row.elo)) }
+                            td { (format!("{:.0}", row.what_if_elo)) }
+                            td { (render_elo_delta(row.what_if_elo - row.elo)) }
+                            td {
+                                @match row.rank_change() {
+                                    0 => span class="secondary" { "–" },
+                                    up if up > 0 => span class="elo-positive" { "▲ " (up) " (was " (row.rank) ")" },
+                                    down => span class="elo-negative" { "▼ " (-down) " (was " (row.rank) ")" },
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Full reload so the header, navigation and forms pick up the new settings
+fn refreshed() -> Response {
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
@@ -0,0 +1,55 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::views::layout::{base, AuthState};
+use crate::AppState;
+use axum::{
+    extract::State,
+    http::{StatusCode, Uri},
+    response::{Html, IntoResponse, Response},
+};
+use maud::{html, Markup};
+use std::sync::Arc;
+
+/// The error message markup shared by fragments and full pages
+pub fn render_error(message: &str) -> Markup {
+    html! { p class="error" { (message) } }
+}
+
+/// Error fragment for htmx endpoints. htmx is configured (in the layout) to
+/// swap 4xx/5xx responses into the target, so the message still shows.
+pub fn error_fragment(status: StatusCode, message: &str) -> Response {
+    (status, Html(render_error(message).into_string())).into_response()
+}
+
+/// Full error page in the base layout, for requests that expect a page
+pub fn error_page(
+    status: StatusCode,
+    message: &str,
+    auth: &AuthState,
+    group_name: &str,
+) -> Response {
+    let title = status.canonical_reason().unwrap_or("Error");
+    let content = html! {
+        h2 { (status.as_u16()) " · " (title) }
+        (render_error(message))
+        p { a href="/" { "Back to Teams" } }
+    };
+    (
+        status,
+        Html(base(title, "", auth, group_name, content).into_string()),
+    )
+        .into_response()
+}
+
+/// Fallback for unknown routes: a fragment under /api, a full page elsewhere
+pub async fn not_found(State(state): State<Arc<AppState>>, jar: AuthJar, uri: Uri) -> Response {
+    if uri.path().starts_with("/api/") {
+        return error_fragment(StatusCode::NOT_FOUND, "No such endpoint");
+    }
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+    error_page(
+        StatusCode::NOT_FOUND,
+        &format!("There's no page at {}.", uri.path()),
+        &auth,
+        &state.settings.get().group_name,
+    )
+}
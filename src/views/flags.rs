@@ -0,0 +1,231 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::edit_window::Editor;
+use crate::models::{clean_note, Match, MatchFlag, MAX_NOTE_LENGTH};
+use crate::settings::Settings;
+use crate::views::errors::error_fragment;
+use crate::views::fragments::match_card_with;
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    Form,
+};
+use maud::{html, Markup};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Trim and cap a flag's reason; a flag needs one
+fn parse_reason(text: &str) -> Result<String, &'static str> {
+    let reason = clean_note(text);
+    if reason.is_empty() {
+        return Err("Say what looks wrong with this result");
+    }
+    Ok(reason)
+}
+
+/// A match's open flags, plus the flag form for logged-in players and admins
+pub fn render_flags(
+    match_id: i32,
+    flags: &[MatchFlag],
+    settings: &Settings,
+    can_flag: bool,
+) -> Markup {
+    html! {
+        @if !flags.is_empty() {
+            section class="match-flags" {
+                h5 { "Flagged for Review" }
+                ul class="player-list" {
+                    @for flag in flags {
+                        li {
+                            strong { (flag.flagged_by) } ": " (flag.reason)
+                            " "
+                            small class="secondary" { (settings.local_date(flag.created_at).format("%Y-%m-%d")) }
+                        }
+                    }
+                }
+            }
+        }
+        @if can_flag {
+            details class="flag-match" {
+                summary { "Flag a problem" }
+                form
+                    hx-post=(format!("/api/matches/{}/flags", match_id))
+                    hx-target=(format!("#match-{}", match_id))
+                    hx-swap="outerHTML"
+                {
+                    input type="text" name="reason" placeholder="What's wrong? e.g. score was 4-3, Sam played for B" maxlength=(MAX_NOTE_LENGTH) required;
+                    button type="submit" class="secondary" { "Flag for Review" }
+                }
+            }
+        }
+    }
+}
+
+/// Open flags with the match they're about and a resolve action (a section
+/// of the Admin page)
+pub fn render_flag_queue(flags: &[MatchFlag], matches: &HashMap<i32, &Match>) -> Markup {
+    html! {
+        div id="flag-queue" {
+            h3 { "Flagged Matches" }
+            @if flags.is_empty() {
+                p class="secondary" { "Nothing flagged. Players can flag a result on History if it looks wrong." }
+            } @else {
+                p class="secondary" {
+                    "Fix or void the match on History, then mark the flag resolved."
+                }
+                div class="table-container" {
+                    table {
+                        thead {
+                            tr { th { "Match" } th { "Flagged by" } th { "Reason" } th { "Flagged" } th {} }
+                        }
+                        tbody {
+                            @for flag in flags {
+                                tr {
+                                    td {
+                                        a href=(format!("/history#match-{}", flag.match_id)) {
+                                            @match matches.get(&flag.match_id) {
+                                                Some(m) => { (m.played_at.format("%Y-%m-%d")) " (" (m.score_a) " : " (m.score_b) ")" }
+                                                None => { "Match " (flag.match_id) }
+                                            }
+                                        }
+                                    }
+                                    td { (flag.flagged_by) }
+                                    td { (flag.reason) }
+                                    td { (flag.created_at.format("%Y-%m-%d %H:%M UTC")) }
+                                    td class="row-actions" {
+                                        button
+                                            hx-post=(format!("/api/admin/flags/{}/resolve", flag.id))
+                                            hx-target="#flag-queue"
+                                            hx-swap="outerHTML"
+                                        {
+                                            "Resolve"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Load and render the flag queue
+pub(crate) async fn flag_queue(state: &AppState) -> Markup {
+    let flags = db::get_open_flags(&state.db).await.unwrap_or_default();
+    let matches = db::get_all_matches(&state.db).await.unwrap_or_default();
+    let by_id: HashMap<i32, &Match> = matches.iter().map(|m| (m.id, m)).collect();
+    render_flag_queue(&flags, &by_id)
+}
+
+/// Form data for flagging a match
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct FlagForm {
+    /// What looks wrong
+    #[serde(default)]
+    reason: String,
+}
+
+/// Flag a match for an admin to review (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/matches/{id}/flags",
+    tag = "matches",
+    params(("id" = i32, Path, description = "Match ID")),
+    request_body(content = FlagForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated match card", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Match not found", content_type = "text/html"),
+        (status = 422, description = "Empty reason", content_type = "text/html")
+    )
+)]
+pub async fn flag_match(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+    Form(form): Form<FlagForm>,
+) -> Response {
+    let Some(editor) = Editor::from_request(&jar, &state) else {
+        return error_fragment(
+            StatusCode::UNAUTHORIZED,
+            "Log in as a player on the Me page to flag a result.",
+        );
+    };
+    let reason = match parse_reason(&form.reason) {
+        Ok(reason) => reason,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, e),
+    };
+    let flagged_by = match editor {
+        Editor::Admin => "Admin".to_string(),
+        Editor::Player(player_id) => db::get_players_by_ids(&state.db, &[player_id])
+            .await
+            .ok()
+            .and_then(|players| players.into_iter().next())
+            .map(|p| p.name)
+            .unwrap_or_else(|| "A player".to_string()),
+    };
+
+    match db::create_flag(&state.db, id, &flagged_by, &reason).await {
+        Ok(true) => {}
+        Ok(false) => return error_fragment(StatusCode::NOT_FOUND, "Match not found"),
+        Err(e) => {
+            tracing::error!("Failed to flag match {}: {}", id, e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to flag match");
+        }
+    }
+    tracing::info!("Match {} flagged by {}", id, flagged_by);
+
+    match_card_with(&state, &jar, id).await
+}
+
+/// Mark a flag as dealt with (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/admin/flags/{id}/resolve",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Flag ID")),
+    responses(
+        (status = 200, description = "Updated flag queue", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "No open flag with that ID", content_type = "text/html")
+    )
+)]
+pub async fn resolve_flag(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    match db::resolve_flag(&state.db, id).await {
+        Ok(true) => {}
+        Ok(false) => return error_fragment(StatusCode::NOT_FOUND, "Flag not found"),
+        Err(e) => {
+            tracing::error!("Failed to resolve flag {}: {}", id, e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve flag");
+        }
+    }
+
+    Html(flag_queue(&state).await.into_string()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reason() {
+        assert_eq!(
+            parse_reason("  score was 4-3 "),
+            Ok("score was 4-3".to_string())
+        );
+        assert!(parse_reason("   ").is_err());
+        let long = "x".repeat(MAX_NOTE_LENGTH + 10);
+        assert_eq!(parse_reason(&long).unwrap().len(), MAX_NOTE_LENGTH);
+    }
+}
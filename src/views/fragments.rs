@@ -0,0 +1,176 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::balance::score_split;
+use crate::captains::nominate_for;
+use crate::edit_window::Editor;
+use crate::models::MatchComment;
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::views::errors::error_fragment;
+use crate::views::history::{history_results_with, render_match};
+use crate::views::match_day::{parse_team_ids, render_generated, ViewTeamsParams};
+use crate::views::roster::{player_list_with, RosterQuery};
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Roster player list for a search/filter/sort (htmx endpoint)
+#[utoipa::path(
+    get,
+    path = "/fragments/players",
+    tag = "fragments",
+    params(RosterQuery),
+    responses((status = 200, description = "Player list", content_type = "text/html"))
+)]
+pub async fn player_list(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Query(query): Query<RosterQuery>,
+) -> impl IntoResponse {
+    let logged_in = is_authenticated(&jar, &state);
+    Html(
+        player_list_with(
+            &state,
+            logged_in,
+            shows_hidden_names(&jar, &state),
+            &query.filter(),
+        )
+        .await
+        .into_string(),
+    )
+}
+
+/// History's Elo chart and match log (htmx endpoint)
+#[utoipa::path(
+    get,
+    path = "/fragments/history",
+    tag = "fragments",
+    responses((status = 200, description = "Chart and match log", content_type = "text/html"))
+)]
+pub async fn history(State(state): State<Arc<AppState>>, jar: AuthJar) -> impl IntoResponse {
+    let logged_in = is_authenticated(&jar, &state);
+    let editor = Editor::from_request(&jar, &state);
+    Html(
+        history_results_with(&state, logged_in, shows_hidden_names(&jar, &state), editor)
+            .await
+            .into_string(),
+    )
+}
+
+/// One match card from History, with its comments (htmx endpoint)
+#[utoipa::path(
+    get,
+    path = "/fragments/matches/{id}",
+    tag = "fragments",
+    params(("id" = i32, Path, description = "Match ID")),
+    responses(
+        (status = 200, description = "Match card", content_type = "text/html"),
+        (status = 404, description = "Match not found (voided matches need login)", content_type = "text/html"),
+        (status = 500, description = "Database error", content_type = "text/html")
+    )
+)]
+pub async fn match_card(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    match_card_with(&state, &jar, id).await
+}
+
+/// Load and render one History match card for whoever is asking
+pub(crate) async fn match_card_with(state: &AppState, jar: &AuthJar, id: i32) -> Response {
+    let logged_in = is_authenticated(jar, state);
+    let m = match db::get_match(&state.db, id).await {
+        // Voided matches are only listed for logged-in users
+        Ok(Some(m)) if logged_in || m.voided_at.is_none() => m,
+        Ok(_) => return error_fragment(StatusCode::NOT_FOUND, "Match not found"),
+        Err(e) => {
+            tracing::error!("Failed to load match {}: {}", id, e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load match");
+        }
+    };
+
+    let ids: Vec<i32> = m.team_a.iter().chain(&m.team_b).copied().collect();
+    let mut players = db::get_players_by_ids(&state.db, &ids)
+        .await
+        .unwrap_or_default();
+    mask_names(&mut players, shows_hidden_names(jar, state));
+    let player_names: HashMap<i32, String> = players.into_iter().map(|p| (p.id, p.name)).collect();
+    let comments: Vec<MatchComment> = db::get_comments_for_match(&state.db, id)
+        .await
+        .unwrap_or_default();
+    let flags = db::get_open_flags_for_match(&state.db, id)
+        .await
+        .unwrap_or_default();
+
+    Html(
+        render_match(
+            &m,
+            &player_names,
+            &comments,
+            &flags,
+            &state.settings.get(),
+            logged_in,
+            Editor::from_request(jar, state),
+        )
+        .into_string(),
+    )
+    .into_response()
+}
+
+/// Team panel for the given player IDs, scored as given (htmx endpoint, used
+/// by shared links, saved teams and swap suggestions)
+#[utoipa::path(
+    get,
+    path = "/fragments/teams",
+    tag = "fragments",
+    params(ViewTeamsParams),
+    responses(
+        (status = 200, description = "Teams for the given player IDs", content_type = "text/html"),
+        (status = 422, description = "Missing team IDs", content_type = "text/html"),
+        (status = 500, description = "Database error", content_type = "text/html")
+    )
+)]
+pub async fn team_panel(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Query(params): Query<ViewTeamsParams>,
+) -> Response {
+    let team_a_ids = parse_team_ids(&params.a);
+    let team_b_ids = parse_team_ids(&params.b);
+
+    if team_a_ids.is_empty() || team_b_ids.is_empty() {
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Invalid team data");
+    }
+
+    // Fetch players for each team
+    let mut team_a = match db::get_players_by_ids(&state.db, &team_a_ids).await {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::error!("Failed to get team A players: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load team A");
+        }
+    };
+
+    let mut team_b = match db::get_players_by_ids(&state.db, &team_b_ids).await {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::error!("Failed to get team B players: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load team B");
+        }
+    };
+
+    let show_hidden = shows_hidden_names(&jar, &state);
+    mask_names(&mut team_a, show_hidden);
+    mask_names(&mut team_b, show_hidden);
+
+    // Score the teams as given so balance details and swap suggestions are accurate
+    let options = state.settings.get().balance_options();
+    let split = score_split(&team_a, &team_b, &options);
+
+    let captains = nominate_for(&state, &split).await;
+    Html(render_generated(&split, &options, captains, &state).into_string()).into_response()
+}
@@ -1,27 +1,65 @@
-use crate::auth::is_authenticated;
-use crate::models::{EloSnapshot, Match, Player};
-use crate::views::layout::{base, render_elo_delta, render_participation, AuthState};
-use crate::{db, AppState};
+use crate::auth::{is_authenticated, AuthJar};
+use crate::edit_window::{can_amend, window_closes, Editor};
+use crate::elo::{recorded_handicap, recorded_keeper_handicap};
+use crate::events::Event;
+use crate::models::{
+    clean_note, EloSnapshot, ImportedMatch, LegacyRating, Match, MatchComment, MatchFlag, NewMatch,
+    Player, RatingBaseline, Side, MAX_BULK_PLAYERS, MAX_IMPORT_MATCHES, MAX_NOTE_LENGTH, MAX_SCORE,
+};
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::settings::Settings;
+use crate::uncertainty::Uncertainty;
+use crate::views::errors::error_fragment;
+use crate::views::flags::render_flags;
+use crate::views::layout::{
+    asset, base, render_calibrating, render_captain, render_elo_delta, render_handicap,
+    render_keeper_handicap, render_participation, render_returning, AuthState,
+};
+use crate::views::pending::{hold_amend, pending_section, submitted_by_player, SUBMITTED_BY_ADMIN};
+use crate::{db, mvp, replay, AppState};
 use axum::{
-    extract::State,
-    response::{Html, IntoResponse},
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    Form,
 };
-use axum_extra::extract::cookie::CookieJar;
+use chrono::{NaiveDate, Utc};
 use maud::{html, Markup};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
-/// Build Elo timeline from matches for each player
-fn build_elo_timeline(matches: &[Match], players: &[Player]) -> serde_json::Value {
+/// Build Elo timeline from matches for each player. Imported baselines start
+/// a player's line with a point flagged `baseline`.
+fn build_elo_timeline(
+    matches: &[Match],
+    players: &[Player],
+    baselines: &[RatingBaseline],
+) -> serde_json::Value {
     // Matches are ordered newest first, reverse for chronological
     let matches_chrono: Vec<_> = matches.iter().rev().collect();
 
     // Build ID → name map
     let id_to_name: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
 
-    // Track Elo for each player over time (keyed by name for chart display)
-    let mut player_history: HashMap<String, Vec<(String, f32)>> = HashMap::new();
+    // Track Elo, its ± band and whether it was imported for each player over
+    // time (keyed by name for chart display)
+    let mut player_history: HashMap<String, Vec<(String, f32, f32, bool)>> = HashMap::new();
+    let mut uncertainty: HashMap<i32, Uncertainty> = HashMap::new();
+
+    for baseline in baselines {
+        if let Some(name) = id_to_name.get(&baseline.player_id) {
+            player_history
+                .entry((*name).to_string())
+                .or_default()
+                .push((
+                    baseline.as_of.format("%Y-%m-%d").to_string(),
+                    baseline.elo,
+                    Uncertainty::default().band(),
+                    true,
+                ));
+        }
+    }
 
     // Calculate starting Elo for each player by working backwards
     let mut starting_elo: HashMap<i32, f32> = players.iter().map(|p| (p.id, p.elo)).collect();
@@ -51,11 +89,13 @@ fn build_elo_timeline(matches: &[Match], players: &[Player]) -> serde_json::Valu
             let effective_delta = change.delta * change.participation;
             let new_elo = change.before + effective_delta;
             current_elo.insert(*player_id, new_elo);
+            let band = uncertainty.entry(*player_id).or_default();
+            band.add(effective_delta, change.participation);
             if let Some(name) = id_to_name.get(player_id) {
                 player_history
                     .entry((*name).to_string())
                     .or_default()
-                    .push((date.clone(), new_elo));
+                    .push((date.clone(), new_elo, band.band(), false));
             }
         }
     }
@@ -73,7 +113,7 @@ fn build_elo_timeline(matches: &[Match], players: &[Player]) -> serde_json::Valu
             let color = colors[i % colors.len()];
             datasets.push(json!({
                 "label": player.name,
-                "data": history.iter().map(|(date, elo)| json!({ "x": date, "y": elo })).collect::<Vec<_>>(),
+                "data": history.iter().map(|(date, elo, band, baseline)| json!({ "x": date, "y": elo, "band": band, "baseline": baseline })).collect::<Vec<_>>(),
                 "borderColor": color,
                 "backgroundColor": color,
                 "fill": false,
@@ -82,89 +122,149 @@ fn build_elo_timeline(matches: &[Match], players: &[Player]) -> serde_json::Valu
         }
     }
 
-    json!({ "datasets": datasets })
+    // Every date in order, so a baseline dated before a line's first match
+    // isn't appended after it
+    let labels: BTreeSet<&String> = player_history
+        .values()
+        .flatten()
+        .map(|(date, ..)| date)
+        .collect();
+
+    json!({ "labels": labels, "datasets": datasets })
 }
 
 /// History page - match history
-pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl IntoResponse {
-    let matches = db::get_all_matches(&state.db).await.unwrap_or_default();
-    let players = db::get_all_players(&state.db).await.unwrap_or_default();
-    let auth = AuthState::new(
-        state.auth_password.is_some(),
-        is_authenticated(&jar, &state),
-    );
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> impl IntoResponse {
+    let logged_in = is_authenticated(&jar, &state);
+    let auth = AuthState::new(state.auth.is_some(), logged_in);
+
+    let content = html! {
+        h2 { "Match History" }
+
+        @if logged_in {
+            details {
+                summary { "Import Past Matches" }
+                form hx-post="/api/matches/import" hx-target="#import-result" {
+                    label {
+                        "One match per line: date, Team A, Team B, score, then optional notes. Separate players with semicolons; everyone must already be on the roster."
+                        textarea
+                            name="lines"
+                            rows="6"
+                            placeholder="date,team_a,team_b,score,notes\n2023-09-10, Sam; Alex; Jo, Kim; Lee; Max, 3-2\n2023-09-17, Sam; Kim; Jo, Alex; Lee; Max, 1-1, Rainy"
+                            required {}
+                    }
+                    button type="submit" class="secondary" { "Import and Replay Ratings" }
+                }
+                div id="import-result" {}
+            }
+            details {
+                summary { "Import Legacy Ratings" }
+                form hx-post="/api/ratings/import" hx-target="#ratings-import-result" {
+                    label {
+                        "One player per line: name, Elo, then an optional date (YYYY-MM-DD). Each rating becomes that player's starting point, shown as a diamond on the chart, and every match is replayed from it."
+                        textarea
+                            name="lines"
+                            rows="6"
+                            placeholder="name,elo,date\nSam, 1340\nAlex, 1185, 2023-08-27"
+                            required {}
+                    }
+                    div class="grid" {
+                        label {
+                            "As of (for lines without a date)"
+                            input type="date" name="as_of" value=(state.settings.get().today()) required;
+                        }
+                        label {
+                            "Source"
+                            input type="text" name="source" placeholder="e.g. 2023 spreadsheet" maxlength="255";
+                        }
+                    }
+                    button type="submit" class="secondary" { "Import and Replay Ratings" }
+                }
+                div id="ratings-import-result" {}
+            }
+        }
+
+        (pending_section(&state, &jar).await)
+
+        // Chart and match log, refreshed live when a result changes elsewhere
+        div
+            id="history-results"
+            data-live="results"
+            hx-get="/fragments/history"
+            hx-trigger="refresh"
+            hx-disinherit="*"
+        {
+            (history_results_with(&state, logged_in, shows_hidden_names(&jar, &state), Editor::from_request(&jar, &state)).await)
+        }
+
+        script src="/static/vendor/chart.umd.js" {}
+        script src=(asset("js/history.js")) {}
+    };
+
+    Html(
+        base(
+            "History",
+            "history",
+            &auth,
+            &state.settings.get().group_name,
+            content,
+        )
+        .into_string(),
+    )
+}
+
+/// Load and render the Elo chart and match log
+pub(crate) async fn history_results_with(
+    state: &AppState,
+    logged_in: bool,
+    show_hidden: bool,
+    editor: Option<Editor>,
+) -> Markup {
+    let all_matches = db::get_all_matches(&state.db).await.unwrap_or_default();
+    let mut players = state.players.get(&state.db).await.unwrap_or_default();
+    mask_names(&mut players, show_hidden);
+    let comments = db::get_all_comments(&state.db).await.unwrap_or_default();
+    let flags = db::get_open_flags(&state.db).await.unwrap_or_default();
+    let baselines = db::get_rating_baselines(&state.db)
+        .await
+        .unwrap_or_default();
+    let settings = state.settings.get();
+
+    // Voided matches don't count; only logged-in users see them (to restore)
+    let matches: Vec<Match> = all_matches
+        .iter()
+        .filter(|m| m.voided_at.is_none())
+        .cloned()
+        .collect();
+    let visible_matches = if logged_in { &all_matches } else { &matches };
+
+    // Group comments by match
+    let mut comments_by_match: HashMap<i32, Vec<MatchComment>> = HashMap::new();
+    for comment in comments {
+        comments_by_match
+            .entry(comment.match_id)
+            .or_default()
+            .push(comment);
+    }
+
+    let mut flags_by_match: HashMap<i32, Vec<MatchFlag>> = HashMap::new();
+    for flag in flags {
+        flags_by_match.entry(flag.match_id).or_default().push(flag);
+    }
 
     // Build player ID → name map for display
     let player_names: HashMap<i32, String> =
         players.iter().map(|p| (p.id, p.name.clone())).collect();
 
-    let chart_data = build_elo_timeline(&matches, &players);
+    let chart_data = build_elo_timeline(&matches, &players, &baselines);
     let chart_data_json = serde_json::to_string(&chart_data).unwrap_or_else(|_| "{}".to_string());
 
-    let content = html! {
-        h2 { "Match History" }
-
+    html! {
         // Elo evolution graph
         @if !matches.is_empty() {
             h3 { "Elo Evolution" }
             div class="chart-container" {
-                canvas id="elo-chart" {}
-            }
-
-            script src="https://cdn.jsdelivr.net/npm/chart.js" {}
-            script {
-                (maud::PreEscaped(format!(r#"
-                    const chartData = {chart_data};
-                    const ctx = document.getElementById('elo-chart').getContext('2d');
-                    new Chart(ctx, {{
-                        type: 'line',
-                        data: chartData,
-                        options: {{
-                            responsive: true,
-                            maintainAspectRatio: false,
-                            plugins: {{
-                                legend: {{
-                                    position: 'bottom',
-                                    labels: {{
-                                        usePointStyle: true,
-                                        padding: 15
-                                    }},
-                                    onClick: function(e, legendItem, legend) {{
-                                        const index = legendItem.datasetIndex;
-                                        const ci = legend.chart;
-                                        const meta = ci.getDatasetMeta(index);
-                                        meta.hidden = meta.hidden === null ? !ci.data.datasets[index].hidden : null;
-                                        ci.update();
-                                    }}
-                                }},
-                                tooltip: {{
-                                    mode: 'index',
-                                    intersect: false
-                                }}
-                            }},
-                            scales: {{
-                                x: {{
-                                    type: 'category',
-                                    title: {{
-                                        display: true,
-                                        text: 'Match Date'
-                                    }}
-                                }},
-                                y: {{
-                                    title: {{
-                                        display: true,
-                                        text: 'Elo Rating'
-                                    }}
-                                }}
-                            }},
-                            interaction: {{
-                                mode: 'nearest',
-                                axis: 'x',
-                                intersect: false
-                            }}
-                        }}
-                    }});
-                "#, chart_data = chart_data_json)))
+                canvas id="elo-chart" data-chart=(chart_data_json) {}
             }
 
             hr;
@@ -172,55 +272,89 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
 
         // Match log
         h3 { "Match Log" }
-        @if matches.is_empty() {
+        @if visible_matches.is_empty() {
             p { "No matches recorded yet." }
         } @else {
-            @for m in &matches {
-                (render_match(m, &player_names))
+            @for m in visible_matches {
+                @let match_comments = comments_by_match.get(&m.id).map(Vec::as_slice).unwrap_or_default();
+                @let match_flags = flags_by_match.get(&m.id).map(Vec::as_slice).unwrap_or_default();
+                (render_match(m, &player_names, match_comments, match_flags, &settings, logged_in, editor))
             }
         }
-    };
-
-    Html(base("History", "history", &auth, content).into_string())
+    }
 }
 
-/// Render a single match as a collapsible card
-fn render_match(m: &Match, player_names: &HashMap<i32, String>) -> Markup {
+/// Render a single match as a collapsible card. A `refresh` event on the card
+/// reloads it from `/fragments/matches/{id}`.
+pub(crate) fn render_match(
+    m: &Match,
+    player_names: &HashMap<i32, String>,
+    comments: &[MatchComment],
+    flags: &[MatchFlag],
+    settings: &Settings,
+    logged_in: bool,
+    editor: Option<Editor>,
+) -> Markup {
+    let [name_a, name_b] = m.team_names();
     let result_text = if m.score_a > m.score_b {
-        "Team A wins"
+        format!("{} wins", name_a)
     } else if m.score_b > m.score_a {
-        "Team B wins"
+        format!("{} wins", name_b)
     } else {
-        "Draw"
+        "Draw".to_string()
     };
 
     // Parse Elo snapshot (ID-keyed format)
     let snapshot: HashMap<i32, EloSnapshot> =
         serde_json::from_value(m.elo_snapshot.clone()).unwrap_or_default();
 
+    let voided = m.voided_at.is_some();
+    let flagged = !flags.is_empty();
+    let amendable =
+        editor.is_some_and(|editor| can_amend(editor, m, settings.edit_window_hours, Utc::now()));
+
     html! {
-        details {
+        details
+            id=(format!("match-{}", m.id))
+            class=[voided.then_some("match-voided").or(flagged.then_some("match-flagged"))]
+            hx-get=(format!("/fragments/matches/{}", m.id))
+            hx-trigger="refresh"
+            hx-swap="outerHTML"
+            hx-disinherit="*"
+        {
             summary {
                 strong { (m.played_at.format("%Y-%m-%d")) }
                 " - "
                 (m.score_a) " : " (m.score_b)
                 " (" (result_text) ")"
+                @if m.k_multiplier != 1.0 {
+                    span class="tag" title="Special event: Elo changes scaled by this K multiplier" { "K ×" (m.k_multiplier) }
+                }
+                @if voided {
+                    span class="tag voided-tag" { "VOIDED" }
+                }
+                @if flagged {
+                    span class="tag flagged-tag" title="A player flagged this result for review" { "FLAGGED" }
+                }
             }
 
             div class="team-grid" {
                 // Team A
                 div {
-                    h4 { "Team A" }
+                    h4 { (name_a) }
                     ul class="player-list" {
                         @for player_id in &m.team_a {
                             @let name = player_names.get(player_id).map(|s| s.as_str()).unwrap_or("Unknown");
                             li {
                                 (name)
+                                (render_captain(m.captain_a == Some(*player_id)))
                                 @if let Some(change) = snapshot.get(player_id) {
                                     " "
                                     @let effective_delta = change.delta * change.participation;
                                     (render_elo_delta(effective_delta))
                                     (render_participation(change.participation))
+                                    (render_calibrating(change.calibrating))
+                                    (render_returning(change.returning))
                                 }
                             }
                         }
@@ -229,23 +363,821 @@ fn render_match(m: &Match, player_names: &HashMap<i32, String>) -> Markup {
 
                 // Team B
                 div {
-                    h4 { "Team B" }
+                    h4 { (name_b) }
                     ul class="player-list" {
                         @for player_id in &m.team_b {
                             @let name = player_names.get(player_id).map(|s| s.as_str()).unwrap_or("Unknown");
                             li {
                                 (name)
+                                (render_captain(m.captain_b == Some(*player_id)))
                                 @if let Some(change) = snapshot.get(player_id) {
                                     " "
                                     @let effective_delta = change.delta * change.participation;
                                     (render_elo_delta(effective_delta))
                                     (render_participation(change.participation))
+                                    (render_calibrating(change.calibrating))
+                                    (render_returning(change.returning))
                                 }
                             }
                         }
                     }
                 }
             }
+
+            (render_goals(m, player_names))
+
+            (render_handicap(recorded_handicap(&m.team_a, &snapshot)))
+            (render_keeper_handicap(recorded_keeper_handicap(&m.team_a, &snapshot)))
+
+            @if !m.notes.is_empty() {
+                p class="match-notes" { (m.notes) }
+            }
+
+            (render_mvp(m, player_names, logged_in))
+
+            (render_comments(m.id, comments, settings, logged_in))
+
+            (render_flags(m.id, flags, settings, editor.is_some() && !voided))
+
+            @if amendable {
+                (render_amend_form(m, settings, editor == Some(Editor::Admin)))
+            }
+
+            @if logged_in {
+                @if voided {
+                    button
+                        class="secondary outline"
+                        hx-post=(format!("/api/matches/{}/restore", m.id))
+                        hx-confirm="Restore this match? It will count towards ratings again."
+                    {
+                        "Restore match"
+                    }
+                } @else {
+                    button
+                        class="secondary outline"
+                        hx-post=(format!("/api/matches/{}/void", m.id))
+                        hx-confirm="Void this match? It will no longer count towards ratings."
+                    {
+                        "Void match"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The match's MVP, or for logged-in users the suggested one with a button
+/// to award it. The Record page shows this under a new result too.
+pub(crate) fn render_mvp(
+    m: &Match,
+    player_names: &HashMap<i32, String>,
+    logged_in: bool,
+) -> Markup {
+    let name = |id: i32| {
+        player_names
+            .get(&id)
+            .map(|s| s.as_str())
+            .unwrap_or("Unknown")
+    };
+    let suggestion = (logged_in && m.voided_at.is_none() && m.mvp.is_none())
+        .then(|| mvp::suggest(m))
+        .flatten();
+    html! {
+        @if let Some(id) = m.mvp {
+            p class="mvp" { "MVP: " strong { (name(id)) } }
+        } @else if let Some(suggestion) = suggestion {
+            div class="mvp" {
+                p {
+                    "Suggested MVP: " strong { (name(suggestion.player_id)) }
+                    " "
+                    small class="secondary" title="Their team's result minus what their own rating predicted against the other team" {
+                        (format!("(beat expectations by {:.2})", suggestion.contribution))
+                    }
+                }
+                button
+                    type="button"
+                    class="secondary outline"
+                    hx-post=(format!("/api/matches/{}/mvp", m.id))
+                    hx-vals=(json!({ "player_id": suggestion.player_id }).to_string())
+                    hx-target="closest .mvp"
+                    hx-swap="outerHTML"
+                {
+                    "Award MVP"
+                }
+            }
+        }
+    }
+}
+
+/// Goal timeline with the running score, Team A's goals on the left and
+/// Team B's on the right. Nothing for matches recorded with only a score.
+pub(crate) fn render_goals(m: &Match, player_names: &HashMap<i32, String>) -> Markup {
+    let goals = m.goals();
+    let names = m.team_names();
+    let (mut score_a, mut score_b) = (0, 0);
+    html! {
+        @if !goals.is_empty() {
+            ol class="goal-timeline" {
+                @for goal in &goals {
+                    @let (side, team_name) = match goal.team {
+                        Side::A => { score_a += 1; ("goal-a", names[0]) }
+                        Side::B => { score_b += 1; ("goal-b", names[1]) }
+                    };
+                    // A scorer from the other team put it in their own net
+                    @let scoring_team = if goal.team == Side::A { &m.team_a } else { &m.team_b };
+                    li class=(side) title=(team_name) {
+                        span class="goal-minute" {
+                            @if let Some(minute) = goal.minute { (minute) "'" } @else { "–" }
+                        }
+                        " ⚽ "
+                        @if let Some(id) = goal.scorer {
+                            (player_names.get(&id).map(|s| s.as_str()).unwrap_or("Unknown"))
+                            @if !scoring_team.contains(&id) { small class="secondary" { " (own goal)" } }
+                        } @else {
+                            span class="secondary" { "Unknown scorer" }
+                        }
+                        " "
+                        strong { (score_a) "–" (score_b) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Collapsed form to fix a result's score or notes. Players are told how
+/// long they have left; admins can always fix it.
+fn render_amend_form(m: &Match, settings: &Settings, admin: bool) -> Markup {
+    let closes = window_closes(m, settings.edit_window_hours).with_timezone(&settings.timezone);
+    html! {
+        details class="amend-result" {
+            summary { "Fix result" }
+            @if !admin {
+                p class="secondary" {
+                    "Players in this match can fix it until " (closes.format("%a %-d %b %H:%M")) "."
+                }
+            }
+            @if !m.goals().is_empty() {
+                p class="secondary" { "Changing the score removes the goal timeline." }
+            }
+            form hx-put=(format!("/api/matches/{}", m.id)) hx-target=(format!("#amend-result-{}", m.id)) {
+                div class="grid" {
+                    label {
+                        "Team A"
+                        input type="number" name="score_a" value=(m.score_a) min="0" max=(MAX_SCORE) required;
+                    }
+                    label {
+                        "Team B"
+                        input type="number" name="score_b" value=(m.score_b) min="0" max=(MAX_SCORE) required;
+                    }
+                }
+                label {
+                    "Notes"
+                    textarea name="notes" rows="2" maxlength=(MAX_NOTE_LENGTH) { (m.notes) }
+                }
+                button type="submit" class="secondary" hx-confirm="Save the fixed result? Ratings are replayed from the start." {
+                    "Save and Replay Ratings"
+                }
+            }
+            div id=(format!("amend-result-{}", m.id)) {}
         }
     }
 }
+
+/// Render a match's comments, plus the add-comment form for logged-in users
+fn render_comments(
+    match_id: i32,
+    comments: &[MatchComment],
+    settings: &Settings,
+    logged_in: bool,
+) -> Markup {
+    html! {
+        section class="match-comments" {
+            @if !comments.is_empty() {
+                h5 { "Comments" }
+                ul class="player-list" {
+                    @for comment in comments {
+                        li {
+                            @if !comment.author.is_empty() {
+                                strong { (comment.author) } ": "
+                            }
+                            (comment.body)
+                            " "
+                            small class="secondary" { (settings.local_date(comment.created_at).format("%Y-%m-%d")) }
+                        }
+                    }
+                }
+            }
+            @if logged_in {
+                form
+                    class="comment-form"
+                    hx-post=(format!("/api/matches/{}/comments", match_id))
+                    hx-target="closest .match-comments"
+                    hx-swap="outerHTML"
+                {
+                    input type="text" name="author" placeholder="Your name" maxlength="255";
+                    input type="text" name="body" placeholder="Add a comment..." maxlength=(MAX_NOTE_LENGTH) required;
+                    button type="submit" class="secondary" { "Comment" }
+                }
+            }
+        }
+    }
+}
+
+/// Form data for adding a comment
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct CommentForm {
+    #[serde(default)]
+    author: String,
+    body: String,
+}
+
+/// Add a comment to a match (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/matches/{id}/comments",
+    tag = "matches",
+    params(("id" = i32, Path, description = "Match ID")),
+    request_body(content = CommentForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated comment list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 422, description = "Empty comment")
+    )
+)]
+pub async fn add_comment(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+    Form(form): Form<CommentForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    let body = clean_note(&form.body);
+    if body.is_empty() {
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Comment cannot be empty");
+    }
+    let author: String = form.author.trim().chars().take(255).collect();
+
+    if let Err(e) = db::create_comment(&state.db, id, &author, &body).await {
+        tracing::error!("Failed to add comment to match {}: {}", id, e);
+        return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add comment");
+    }
+
+    let comments = db::get_comments_for_match(&state.db, id)
+        .await
+        .unwrap_or_default();
+    Html(render_comments(id, &comments, &state.settings.get(), true).into_string()).into_response()
+}
+
+/// Void a match (htmx endpoint) - kept for admins, excluded from ratings
+#[utoipa::path(
+    post,
+    path = "/api/matches/{id}/void",
+    tag = "matches",
+    params(("id" = i32, Path, description = "Match ID")),
+    responses(
+        (status = 200, description = "Voided; ratings recalculated and the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Match not found")
+    )
+)]
+pub async fn void_match(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    set_match_voided(&state, &jar, id, true).await
+}
+
+/// Restore a voided match (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/matches/{id}/restore",
+    tag = "matches",
+    params(("id" = i32, Path, description = "Match ID")),
+    responses(
+        (status = 200, description = "Restored; ratings recalculated and the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Match not found")
+    )
+)]
+pub async fn restore_match(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    set_match_voided(&state, &jar, id, false).await
+}
+
+/// Form data for awarding a match's MVP
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct MvpForm {
+    player_id: i32,
+}
+
+/// Award a match's MVP, usually the suggested one (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/matches/{id}/mvp",
+    tag = "matches",
+    params(("id" = i32, Path, description = "Match ID")),
+    request_body(content = MvpForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Awarded; returns the MVP line", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Match not found or voided", content_type = "text/html"),
+        (status = 422, description = "The player didn't play in the match", content_type = "text/html")
+    )
+)]
+pub async fn award_mvp(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+    Form(form): Form<MvpForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    let mut m = match db::get_match(&state.db, id).await {
+        Ok(Some(m)) if m.voided_at.is_none() => m,
+        Ok(_) => return error_fragment(StatusCode::NOT_FOUND, "Match not found"),
+        Err(e) => {
+            tracing::error!("Failed to load match {}: {}", id, e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load match");
+        }
+    };
+    let ids: Vec<i32> = m.team_a.iter().chain(&m.team_b).copied().collect();
+    if !ids.contains(&form.player_id) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "The MVP must have played in the match",
+        );
+    }
+
+    match db::set_match_mvp(&state.db, id, form.player_id).await {
+        Ok(true) => {}
+        Ok(false) => return error_fragment(StatusCode::NOT_FOUND, "Match not found"),
+        Err(e) => {
+            tracing::error!("Failed to award MVP for match {}: {}", id, e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to award MVP");
+        }
+    }
+    state.events.publish(Event::Results);
+
+    m.mvp = Some(form.player_id);
+    let player_names: HashMap<i32, String> = db::get_players_by_ids(&state.db, &ids)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+    Html(render_mvp(&m, &player_names, true).into_string()).into_response()
+}
+
+/// Form data for fixing a recorded result
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct AmendForm {
+    score_a: i32,
+    score_b: i32,
+    /// Replaces the match notes
+    #[serde(default)]
+    notes: String,
+}
+
+/// Fix a match's score or notes, then replay ratings (htmx endpoint). Admins
+/// can always; players in the match only within the edit window. With result
+/// confirmation on, the fix waits for someone else to confirm it instead.
+#[utoipa::path(
+    put,
+    path = "/api/matches/{id}",
+    tag = "matches",
+    params(("id" = i32, Path, description = "Match ID")),
+    request_body(content = AmendForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Saved (or held for confirmation); the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 403, description = "Not in this match, or the edit window has closed", content_type = "text/html"),
+        (status = 404, description = "Match not found", content_type = "text/html"),
+        (status = 422, description = "Score out of range", content_type = "text/html")
+    )
+)]
+pub async fn amend_match(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+    Form(form): Form<AmendForm>,
+) -> Response {
+    let Some(editor) = Editor::from_request(&jar, &state) else {
+        return error_fragment(
+            StatusCode::UNAUTHORIZED,
+            "Log in (or log in as a player on the Me page) to fix results.",
+        );
+    };
+    let m = match db::get_match(&state.db, id).await {
+        Ok(Some(m)) => m,
+        Ok(None) => return error_fragment(StatusCode::NOT_FOUND, "Match not found"),
+        Err(e) => {
+            tracing::error!("Failed to load match {}: {}", id, e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load match");
+        }
+    };
+    let settings = state.settings.get();
+    if !can_amend(editor, &m, settings.edit_window_hours, Utc::now()) {
+        return error_fragment(
+            StatusCode::FORBIDDEN,
+            "This result can no longer be fixed by players. Ask an admin.",
+        );
+    }
+    if !(0..=MAX_SCORE).contains(&form.score_a) || !(0..=MAX_SCORE).contains(&form.score_b) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!("Scores must be between 0 and {}", MAX_SCORE),
+        );
+    }
+
+    let notes = clean_note(&form.notes);
+
+    // With confirmation on, a fix waits for a second person like a new result
+    if settings.confirm_results {
+        let submitted_by = match editor {
+            Editor::Admin => SUBMITTED_BY_ADMIN.to_string(),
+            Editor::Player(id) => submitted_by_player(id),
+        };
+        return match hold_amend(
+            &state,
+            &m,
+            form.score_a,
+            form.score_b,
+            &notes,
+            &submitted_by,
+        )
+        .await
+        {
+            Ok(pending_id) => {
+                tracing::info!("Fix to match {} held as pending result {}", id, pending_id);
+                ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+            }
+            Err(e) => {
+                tracing::error!("Failed to hold fix to match {}: {}", id, e);
+                error_fragment(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to save the result",
+                )
+            }
+        };
+    }
+
+    match db::amend_match(&state.db, id, form.score_a, form.score_b, &notes).await {
+        Ok(true) => {}
+        Ok(false) => return error_fragment(StatusCode::NOT_FOUND, "Match not found"),
+        Err(e) => {
+            tracing::error!("Failed to amend match {}: {}", id, e);
+            return error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to save the result",
+            );
+        }
+    }
+    tracing::info!("Match {} amended by {:?}", id, editor);
+
+    if let Err(e) = replay::recalculate_all(&state.db, &settings.elo).await {
+        tracing::error!("Failed to recalculate ratings: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to recalculate ratings",
+        );
+    }
+    state.events.publish(Event::Results);
+
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
+
+/// Void/restore a match, then replay history so ratings reflect the change
+async fn set_match_voided(state: &AppState, jar: &AuthJar, id: i32, voided: bool) -> Response {
+    if !is_authenticated(jar, state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    match db::set_match_voided(&state.db, id, voided).await {
+        Ok(true) => {}
+        Ok(false) => return error_fragment(StatusCode::NOT_FOUND, "Match not found"),
+        Err(e) => {
+            tracing::error!("Failed to update match {}: {}", id, e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update match");
+        }
+    }
+
+    if let Err(e) = replay::recalculate_all(&state.db, &state.settings.get().elo).await {
+        tracing::error!("Failed to recalculate ratings: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to recalculate ratings",
+        );
+    }
+    state.events.publish(Event::Results);
+
+    // Full reload so the chart and match log pick up the new ratings
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
+
+/// Form data for importing legacy ratings
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct ImportRatingsForm {
+    /// CSV lines: name, Elo and an optional date. A leading "name,..." header
+    /// line is skipped.
+    lines: String,
+    /// Date for lines without one (YYYY-MM-DD)
+    as_of: String,
+    /// Where the ratings come from, e.g. "2023 spreadsheet"
+    #[serde(default)]
+    source: String,
+}
+
+/// Import ratings from before the app as each player's dated starting
+/// baseline, then replay every rating from them (htmx endpoint). Nothing is
+/// imported unless every line is valid.
+#[utoipa::path(
+    post,
+    path = "/api/ratings/import",
+    tag = "players",
+    request_body(content = ImportRatingsForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Imported and the page is refreshed, or per-line errors", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 422, description = "No lines, too many lines or an invalid date", content_type = "text/html")
+    )
+)]
+pub async fn import_ratings(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<ImportRatingsForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    let Ok(default_as_of) = NaiveDate::parse_from_str(form.as_of.trim(), "%Y-%m-%d") else {
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Invalid \"as of\" date");
+    };
+    let lines: Vec<(usize, &str)> = form
+        .lines
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        // Skip a spreadsheet header row
+        .filter(|(i, line)| *i > 1 || !line.to_lowercase().starts_with("name"))
+        .collect();
+    if lines.is_empty() {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Paste at least one rating",
+        );
+    }
+    if lines.len() > MAX_BULK_PLAYERS {
+        let message = format!(
+            "Too many lines: import at most {} ratings at once",
+            MAX_BULK_PLAYERS
+        );
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &message);
+    }
+
+    let (Ok(players), Ok(matches)) = (
+        db::get_all_players(&state.db).await,
+        db::get_all_matches(&state.db).await,
+    ) else {
+        tracing::error!("Failed to load players and matches for a rating import");
+        return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
+    };
+    let ids: HashMap<String, i32> = players
+        .iter()
+        .map(|p| (p.name.to_lowercase(), p.id))
+        .collect();
+    // A baseline has to come before everything the player earned in the app
+    let mut first_match: HashMap<i32, NaiveDate> = HashMap::new();
+    for m in matches.iter().filter(|m| m.voided_at.is_none()) {
+        for id in m.team_a.iter().chain(&m.team_b) {
+            let first = first_match.entry(*id).or_insert(m.played_at);
+            *first = (*first).min(m.played_at);
+        }
+    }
+
+    let today = state.settings.get().today();
+    let mut baselines: Vec<(i32, f32, NaiveDate)> = Vec::new();
+    let mut errors: Vec<(usize, String)> = Vec::new();
+    for (number, line) in lines {
+        let parsed = LegacyRating::parse_line(line).and_then(|rating| {
+            let id = ids
+                .get(&rating.name.to_lowercase())
+                .copied()
+                .ok_or_else(|| format!("unknown player \"{}\"", rating.name))?;
+            if baselines.iter().any(|(seen, ..)| *seen == id) {
+                return Err(format!("{} is listed twice", rating.name));
+            }
+            let as_of = rating.as_of.unwrap_or(default_as_of);
+            if as_of > today {
+                return Err(format!("{} is in the future", as_of));
+            }
+            if let Some(first) = first_match.get(&id).filter(|first| **first < as_of) {
+                return Err(format!(
+                    "{} already has a match on {}, before {}",
+                    rating.name, first, as_of
+                ));
+            }
+            Ok((id, rating.elo, as_of))
+        });
+        match parsed {
+            Ok(baseline) => baselines.push(baseline),
+            Err(e) => errors.push((number, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Html(
+            html! {
+                p class="error" { "Nothing was imported. Fix these lines and try again:" }
+                ul class="error" {
+                    @for (number, error) in &errors {
+                        li { "Line " (number) ": " (error) }
+                    }
+                }
+            }
+            .into_string(),
+        )
+        .into_response();
+    }
+
+    let source = clean_note(&form.source);
+    if let Err(e) = db::set_rating_baselines(&state.db, &baselines, &source).await {
+        tracing::error!("Failed to import ratings: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to import ratings",
+        );
+    }
+
+    // Every match is re-rated from the new starting points
+    if let Err(e) = replay::recalculate_all(&state.db, &state.settings.get().elo).await {
+        tracing::error!("Failed to recalculate ratings: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Imported, but failed to recalculate ratings",
+        );
+    }
+    state.events.publish(Event::Results);
+
+    // Full reload so the chart and roster show the new ratings
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
+
+/// Form data for importing past matches
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct ImportMatchesForm {
+    /// CSV lines: date, Team A, Team B, score, optional notes (players separated by ";").
+    /// A leading "date,..." header line is skipped.
+    lines: String,
+}
+
+/// Import past matches, then replay every rating from scratch (htmx endpoint).
+/// Nothing is imported unless every line is valid.
+#[utoipa::path(
+    post,
+    path = "/api/matches/import",
+    tag = "matches",
+    request_body(content = ImportMatchesForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Imported and the page is refreshed, or per-line errors", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
+pub async fn import_matches(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<ImportMatchesForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    let lines: Vec<(usize, &str)> = form
+        .lines
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        // Skip a spreadsheet header row
+        .filter(|(i, line)| *i > 1 || !line.to_lowercase().starts_with("date"))
+        .collect();
+    if lines.is_empty() {
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Paste at least one match");
+    }
+    if lines.len() > MAX_IMPORT_MATCHES {
+        let message = format!(
+            "Too many lines: import at most {} matches at once",
+            MAX_IMPORT_MATCHES
+        );
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &message);
+    }
+
+    let players = match db::get_all_players(&state.db).await {
+        Ok(players) => players,
+        Err(e) => {
+            tracing::error!("Failed to load players: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
+        }
+    };
+    let ids: HashMap<String, i32> = players
+        .iter()
+        .map(|p| (p.name.to_lowercase(), p.id))
+        .collect();
+    let resolve = |names: &[String]| -> Result<Vec<i32>, String> {
+        names
+            .iter()
+            .map(|name| {
+                ids.get(&name.to_lowercase())
+                    .copied()
+                    .ok_or_else(|| format!("unknown player \"{}\"", name))
+            })
+            .collect()
+    };
+
+    let today = state.settings.get().today();
+    let mut new_matches = Vec::new();
+    let mut errors: Vec<(usize, String)> = Vec::new();
+    for (number, line) in lines {
+        let parsed = ImportedMatch::parse_line(line).and_then(|m| {
+            if m.played_at > today {
+                return Err(format!("{} is in the future", m.played_at));
+            }
+            Ok(NewMatch {
+                played_at: m.played_at,
+                team_a: resolve(&m.team_a)?,
+                team_b: resolve(&m.team_b)?,
+                score_a: m.score_a,
+                score_b: m.score_b,
+                elo_snapshot: serde_json::json!({}),
+                notes: m.notes,
+                k_multiplier: 1.0,
+                captain_a: None,
+                captain_b: None,
+                team_name_a: None,
+                team_name_b: None,
+                goals: Vec::new(),
+            })
+        });
+        match parsed {
+            Ok(new_match) => new_matches.push(new_match),
+            Err(e) => errors.push((number, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Html(
+            html! {
+                p class="error" { "Nothing was imported. Fix these lines and try again:" }
+                ul class="error" {
+                    @for (number, error) in &errors {
+                        li { "Line " (number) ": " (error) }
+                    }
+                }
+            }
+            .into_string(),
+        )
+        .into_response();
+    }
+
+    // Oldest first, so IDs and recording order follow the real history
+    new_matches.sort_by_key(|m| m.played_at);
+    let result: Result<(), sqlx::Error> = async {
+        let mut tx = state.db.begin().await?;
+        for new_match in &new_matches {
+            db::create_match(&mut *tx, new_match).await?;
+        }
+        tx.commit().await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::error!("Failed to import matches: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to import matches",
+        );
+    }
+
+    // Snapshots are left empty above; the replay fills them in date order
+    if let Err(e) = replay::recalculate_all(&state.db, &state.settings.get().elo).await {
+        tracing::error!("Failed to recalculate ratings: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Imported, but failed to recalculate ratings",
+        );
+    }
+    state.events.publish(Event::Results);
+
+    // Full reload so the chart and match log include the imported matches
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
@@ -0,0 +1,276 @@
+use crate::auth::{hash_password, is_authenticated, login_player, random_token, AuthJar};
+use crate::models::{parse_email, parse_pin, INVITE_DAYS, MAX_EMAIL_LENGTH, MAX_PIN_LENGTH};
+use crate::views::errors::{error_fragment, error_page};
+use crate::views::layout::{base, AuthState};
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::extract::Form;
+use chrono::{Duration, Utc};
+use maud::html;
+use std::sync::Arc;
+
+/// Full link for an invite token, from the Host the admin is using (a bare
+/// path if there is none)
+fn invite_url(host: Option<&str>, secure: bool, token: &str) -> String {
+    match host {
+        Some(host) => format!(
+            "{}://{}/invite/{}",
+            if secure { "https" } else { "http" },
+            host,
+            token
+        ),
+        None => format!("/invite/{}", token),
+    }
+}
+
+/// Create a single-use invite link for a player (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/players/{id}/invite",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    responses(
+        (status = 200, description = "Invite link to share", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Player not found", content_type = "text/html")
+    )
+)]
+pub async fn create_invite(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    let player = match db::get_players_by_ids(&state.db, &[id]).await {
+        Ok(players) => match players.into_iter().next() {
+            Some(player) => player,
+            None => return error_fragment(StatusCode::NOT_FOUND, "Player not found"),
+        },
+        Err(e) => {
+            tracing::error!("Failed to load player: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load player");
+        }
+    };
+    let account = db::get_player_account(&state.db, id)
+        .await
+        .unwrap_or_default();
+
+    let expires_at = Utc::now() + Duration::days(INVITE_DAYS);
+    let invite = match db::create_invite(&state.db, id, &random_token(), expires_at).await {
+        Ok(invite) => invite,
+        Err(e) => {
+            tracing::error!("Failed to create invite: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create invite");
+        }
+    };
+
+    let host = headers.get(header::HOST).and_then(|h| h.to_str().ok());
+    let url = invite_url(host, state.secure_cookies, &invite.token);
+    Html(
+        html! {
+            article class="invite-link" {
+                p {
+                    "Send this link to " strong { (player.name) } ". It works once and expires on "
+                    (invite.expires_at.format("%Y-%m-%d")) "."
+                }
+                input type="text" value=(url) readonly aria-label="Invite link";
+                @if let Some(account) = account {
+                    p class="secondary" {
+                        (player.name) " already claimed an invite on " (account.claimed_at.format("%Y-%m-%d"))
+                        ". Claiming this one replaces their email, notifications and PIN."
+                    }
+                }
+            }
+        }
+        .into_string(),
+    )
+    .into_response()
+}
+
+/// Invite page - the invited player sets their email, notifications and PIN.
+/// Public even when pages are private, since the player can't log in yet.
+pub async fn page(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(token): Path<String>,
+) -> Response {
+    let settings = state.settings.get();
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+
+    let invite = db::get_open_invite(&state.db, &token).await;
+    let player = match &invite {
+        Ok(Some(invite)) => db::get_players_by_ids(&state.db, &[invite.player_id])
+            .await
+            .ok()
+            .and_then(|players| players.into_iter().next()),
+        _ => None,
+    };
+    let Some(player) = player else {
+        return error_page(
+            StatusCode::NOT_FOUND,
+            "This invite link has expired or was already used. Ask the organiser for a new one.",
+            &auth,
+            &settings.group_name,
+        );
+    };
+    // Re-invited players start from their current details
+    let account = db::get_player_account(&state.db, player.id)
+        .await
+        .unwrap_or_default();
+    let email = account.as_ref().and_then(|a| a.email.as_deref());
+    let notify_teams = account.as_ref().is_some_and(|a| a.notify_teams);
+    let notify_results = account.as_ref().is_some_and(|a| a.notify_results);
+    let had_pin = account.as_ref().is_some_and(|a| a.pin_hash.is_some());
+
+    let content = html! {
+        h2 { "Welcome, " (player.name) "!" }
+        p { "You're on the " (settings.group_name) " roster. Add your details to finish joining." }
+        form hx-post=(format!("/api/invites/{}", token)) hx-target="#invite-result" {
+            label {
+                "Email (optional)"
+                input type="email" name="email" value=[email] maxlength=(MAX_EMAIL_LENGTH) autocomplete="email";
+            }
+            fieldset {
+                legend { "Email me" }
+                label { input type="checkbox" name="notify_teams" value="true" checked[notify_teams]; "When teams are picked" }
+                label { input type="checkbox" name="notify_results" value="true" checked[notify_results]; "When a result is recorded" }
+            }
+            div class="grid" {
+                label {
+                    "Personal PIN (optional)"
+                    input type="password" name="pin" inputmode="numeric" maxlength=(MAX_PIN_LENGTH) autocomplete="new-password";
+                }
+                label {
+                    "PIN again"
+                    input type="password" name="pin_confirm" inputmode="numeric" maxlength=(MAX_PIN_LENGTH) autocomplete="new-password";
+                }
+            }
+            @if had_pin {
+                p class="secondary" { "Your old PIN stops working once you claim this link. Set a new one to keep it." }
+            }
+            button type="submit" { "Claim My Spot" }
+        }
+        div id="invite-result" {}
+    };
+
+    Html(base("Invite", "invite", &auth, &settings.group_name, content).into_string())
+        .into_response()
+}
+
+/// Form data for claiming an invite
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct ClaimForm {
+    #[serde(default)]
+    email: String,
+    /// "true" to be emailed when teams are picked
+    notify_teams: Option<String>,
+    /// "true" to be emailed when a result is recorded
+    notify_results: Option<String>,
+    /// Optional personal PIN (4-8 digits)
+    #[serde(default)]
+    pin: String,
+    #[serde(default)]
+    pin_confirm: String,
+}
+
+/// Claim an invite: save the player's contact details and PIN, use up the link and log the player in (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/invites/{token}",
+    tag = "players",
+    params(("token" = String, Path, description = "Invite token from the link")),
+    request_body(content = ClaimForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Invite claimed; sets the player login cookie", content_type = "text/html"),
+        (status = 404, description = "Invite expired, used or unknown", content_type = "text/html"),
+        (status = 422, description = "Invalid email or PIN", content_type = "text/html")
+    )
+)]
+pub async fn claim(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(token): Path<String>,
+    Form(form): Form<ClaimForm>,
+) -> Response {
+    let email = match parse_email(&form.email) {
+        Ok(email) => email,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+    let pin_hash = match parse_pin(&form.pin, &form.pin_confirm) {
+        Ok(pin) => pin.as_deref().map(hash_password),
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+
+    let result: Result<Option<i32>, sqlx::Error> = async {
+        let mut tx = state.db.begin().await?;
+        let Some(player_id) = db::claim_invite(&mut *tx, &token).await? else {
+            return Ok(None);
+        };
+        db::upsert_player_account(
+            &mut *tx,
+            player_id,
+            email.as_deref(),
+            form.notify_teams.is_some(),
+            form.notify_results.is_some(),
+            pin_hash.as_deref(),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Some(player_id))
+    }
+    .await;
+
+    match result {
+        // Claiming the link doubles as a magic-link login
+        Ok(Some(player_id)) => (
+            login_player(jar, player_id, state.secure_cookies),
+            Html(
+                html! {
+                    p class="success-message" {
+                        "You're all set! "
+                        a href="/me" { "RSVP for the next match" }
+                    }
+                }
+                .into_string(),
+            ),
+        )
+            .into_response(),
+        Ok(None) => error_fragment(
+            StatusCode::NOT_FOUND,
+            "This invite link has expired or was already used. Ask the organiser for a new one.",
+        ),
+        Err(e) => {
+            tracing::error!("Failed to claim invite: {}", e);
+            error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to save your details",
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invite_url() {
+        assert_eq!(
+            invite_url(Some("football.example.com"), true, "abc"),
+            "https://football.example.com/invite/abc"
+        );
+        assert_eq!(
+            invite_url(Some("localhost:3000"), false, "abc"),
+            "http://localhost:3000/invite/abc"
+        );
+        assert_eq!(invite_url(None, true, "abc"), "/invite/abc");
+    }
+}
@@ -0,0 +1,133 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::jobs::Job;
+use crate::models::QueuedJob;
+use crate::views::errors::error_fragment;
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use maud::{html, Markup};
+use std::sync::Arc;
+
+/// Queue status and failed jobs (a section of the Admin page)
+pub fn render_jobs(pending: i64, failed: &[QueuedJob]) -> Markup {
+    html! {
+        div id="job-list" {
+            h3 { "Background Jobs" }
+            p class="secondary" {
+                (pending) " waiting to run. Jobs that run out of attempts stay here until retried or dismissed."
+            }
+            @if !failed.is_empty() {
+                div class="table-container" {
+                    table {
+                        thead {
+                            tr { th { "Job" } th { "Queued" } th { "Failed" } th { "Attempts" } th { "Last error" } th {} }
+                        }
+                        tbody {
+                            @for job in failed {
+                                tr {
+                                    td {
+                                        code { (job.kind) }
+                                        @if let Ok(payload) = serde_json::from_value::<Job>(job.payload.clone()) {
+                                            br;
+                                            small class="secondary" { (payload.describe()) }
+                                        }
+                                    }
+                                    td { (job.created_at.format("%Y-%m-%d %H:%M UTC")) }
+                                    td { (job.failed_at.map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string()).unwrap_or_default()) }
+                                    td { (job.attempts) " / " (job.max_attempts) }
+                                    td { (job.last_error.as_deref().unwrap_or_default()) }
+                                    td class="row-actions" {
+                                        button
+                                            hx-post=(format!("/api/admin/jobs/{}/retry", job.id))
+                                            hx-target="#job-list"
+                                            hx-swap="outerHTML"
+                                        {
+                                            "Retry"
+                                        }
+                                        button
+                                            class="secondary outline"
+                                            hx-delete=(format!("/api/admin/jobs/{}", job.id))
+                                            hx-target="#job-list"
+                                            hx-swap="outerHTML"
+                                            hx-confirm="Dismiss this failed job? It won't run again."
+                                        {
+                                            "Dismiss"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reload and render the jobs section (after a change)
+async fn job_list(state: &AppState) -> Response {
+    let pending = db::count_pending_jobs(&state.db).await.unwrap_or_default();
+    let failed = db::get_failed_jobs(&state.db).await.unwrap_or_default();
+    Html(render_jobs(pending, &failed).into_string()).into_response()
+}
+
+/// Give a failed job a fresh set of attempts (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/admin/jobs/{id}/retry",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Updated jobs section", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "No failed job with that ID", content_type = "text/html")
+    )
+)]
+pub async fn retry_job(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    match db::requeue_job(&state.db, id).await {
+        Ok(true) => job_list(&state).await,
+        Ok(false) => error_fragment(StatusCode::NOT_FOUND, "That job isn't failed any more"),
+        Err(e) => {
+            tracing::error!("Failed to requeue job: {}", e);
+            error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to retry job")
+        }
+    }
+}
+
+/// Dismiss a failed job (htmx endpoint)
+#[utoipa::path(
+    delete,
+    path = "/api/admin/jobs/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Updated jobs section", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
+pub async fn dismiss_job(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    if let Err(e) = db::delete_job(&state.db, id).await {
+        tracing::error!("Failed to dismiss job: {}", e);
+        return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to dismiss job");
+    }
+    job_list(&state).await
+}
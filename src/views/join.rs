@@ -0,0 +1,390 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::models::{
+    clean_note, parse_attribute_level, NewPlayer, Player, Position, Registration, ELO_DEFAULT,
+    MAX_NOTE_LENGTH, MAX_PENDING_REGISTRATIONS, MAX_REGISTRATION_NAME_LENGTH, MAX_STARTING_ELO,
+    MIN_STARTING_ELO,
+};
+use crate::onboarding::{Experience, OnboardingFormula};
+use crate::views::errors::error_fragment;
+use crate::views::layout::{base, render_position, AuthState};
+use crate::views::roster::{render_position_select, render_questionnaire_selects};
+use crate::webhooks::{player_json, WebhookEvent};
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::extract::Form;
+use maud::{html, Markup};
+use std::sync::Arc;
+
+/// Sign-up page - new players add themselves to the approval queue.
+/// Public even when pages are private, so it can be shared with newcomers.
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> impl IntoResponse {
+    let settings = state.settings.get();
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+
+    let content = html! {
+        h2 { "Join " (settings.group_name) }
+        p {
+            "New to the group? Leave your name and the organiser will add you to the "
+            "roster once they've had a look."
+        }
+        form hx-post="/api/registrations" hx-target="#join-result" data-reset-on-success {
+            div class="grid" {
+                input type="text" name="name" placeholder="Your name" maxlength=(MAX_REGISTRATION_NAME_LENGTH) required;
+                (render_position_select(None, false))
+            }
+            fieldset {
+                legend { "A bit about your football" }
+                small class="secondary" {
+                    "How much you've played and how fit you are (1 = out of shape, 5 = runs all game) "
+                    "set your starting rating, so the first teams you're picked for are fair."
+                }
+                div class="grid" {
+                    (render_questionnaire_selects(true, false))
+                }
+            }
+            label {
+                "Anything the organiser should know (optional)"
+                textarea name="note" rows="3" maxlength=(MAX_NOTE_LENGTH) placeholder="e.g. Sam's friend, usually plays in goal" {}
+            }
+            button type="submit" { "Sign Up" }
+        }
+        div id="join-result" {}
+    };
+
+    Html(base("Join", "join", &auth, &settings.group_name, content).into_string())
+}
+
+/// Form data for signing up
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct RegistrationForm {
+    #[serde(default)]
+    name: String,
+    /// GK, DEF, MID or ATT (empty for no preference)
+    position: Option<String>,
+    /// "new", "casual", "regular" or "club"
+    #[serde(default)]
+    experience: String,
+    /// Fitness 1-5 (empty for not saying)
+    fitness: Option<String>,
+    /// Free-text note for the organiser
+    #[serde(default)]
+    note: String,
+}
+
+/// Add a self-registration to the approval queue (htmx endpoint, no login needed)
+#[utoipa::path(
+    post,
+    path = "/api/registrations",
+    tag = "players",
+    request_body(content = RegistrationForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Sign-up queued for approval", content_type = "text/html"),
+        (status = 422, description = "Missing name or experience, or queue full", content_type = "text/html")
+    )
+)]
+pub async fn register(
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<RegistrationForm>,
+) -> Response {
+    let name = form.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_REGISTRATION_NAME_LENGTH {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!(
+                "Your name must be 1-{} characters",
+                MAX_REGISTRATION_NAME_LENGTH
+            ),
+        );
+    }
+    let Some(experience) = Experience::from_str(&form.experience) else {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Tell us how much football you've played",
+        );
+    };
+
+    // The answer never says whether a name is taken, which would tell anyone
+    // who is on the roster (hidden players included). A name on the roster is
+    // queued and flagged for the organiser; one already queued isn't queued twice.
+    let pending = db::get_registrations(&state.db).await.unwrap_or_default();
+    if pending.iter().any(|r| r.name.eq_ignore_ascii_case(name)) {
+        return signed_up(name);
+    }
+
+    let position = form
+        .position
+        .as_deref()
+        .and_then(Position::from_str)
+        .map(|p| p.to_string());
+    match db::create_registration(
+        &state.db,
+        name,
+        position.as_deref(),
+        &clean_note(&form.note),
+        experience,
+        parse_attribute_level(form.fitness.as_deref()),
+        MAX_PENDING_REGISTRATIONS,
+    )
+    .await
+    {
+        Ok(true) => signed_up(name),
+        Ok(false) => error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Too many sign-ups are waiting right now. Please ask the organiser to add you.",
+        ),
+        Err(e) => {
+            tracing::error!("Failed to save registration: {}", e);
+            error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sign up")
+        }
+    }
+}
+
+/// The answer to a sign-up, whether or not it was queued
+fn signed_up(name: &str) -> Response {
+    Html(
+        html! {
+            p class="success-message" {
+                "Thanks, " (name) "! You'll appear on the roster once the organiser approves you."
+            }
+        }
+        .into_string(),
+    )
+    .into_response()
+}
+
+/// Whether a name is already taken by a player (ignoring case)
+fn on_roster(name: &str, players: &[Player]) -> bool {
+    players.iter().any(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// Pending sign-ups with approve and reject actions (shown on the Roster when
+/// logged in). The starting Elo comes pre-filled from the questionnaire, and
+/// names already on the roster are flagged.
+pub fn render_registrations(
+    registrations: &[Registration],
+    players: &[Player],
+    formula: &OnboardingFormula,
+) -> Markup {
+    html! {
+        div id="registrations" {
+            @if !registrations.is_empty() {
+                details open {
+                    summary { "Pending Sign-Ups (" (registrations.len()) ")" }
+                    div id="registration-result" {}
+                    div class="table-container" {
+                        table {
+                            thead {
+                                tr {
+                                    th { "Name" } th { "Pos" } th { "Experience" } th { "Fitness" } th { "Note" }
+                                    th { "Signed up" } th { "Starting Elo" } th { "Actions" }
+                                }
+                            }
+                            tbody {
+                                @for registration in registrations {
+                                    @let answers = registration.answers();
+                                    tr id=(format!("registration-{}", registration.id)) {
+                                        td {
+                                            (registration.name)
+                                            @if on_roster(&registration.name, players) {
+                                                " " span class="tag" title="Reject this sign-up, or ask them for a surname or initial" { "Name taken" }
+                                            }
+                                        }
+                                        td { (render_position(registration.position())) }
+                                        td { (answers.map(|a| a.experience.label()).unwrap_or("?")) }
+                                        td { (registration.fitness.map(|f| f.to_string()).unwrap_or_else(|| "?".to_string())) }
+                                        td { (registration.note) }
+                                        td { (registration.created_at.format("%Y-%m-%d")) }
+                                        td {
+                                            @let elo = answers.map_or(ELO_DEFAULT, |a| a.starting_elo(formula));
+                                            input
+                                                type="number"
+                                                name="elo"
+                                                value=(elo)
+                                                min=(MIN_STARTING_ELO)
+                                                max=(MAX_STARTING_ELO)
+                                                aria-label="Starting Elo"
+                                                title=[answers.map(|a| a.explain(formula))];
+                                        }
+                                        td class="row-actions" {
+                                            button
+                                                hx-post=(format!("/api/registrations/{}/approve", registration.id))
+                                                hx-include=(format!("#registration-{}", registration.id))
+                                                hx-target="#registration-result"
+                                            {
+                                                "Approve"
+                                            }
+                                            button
+                                                class="secondary outline"
+                                                hx-delete=(format!("/api/registrations/{}", registration.id))
+                                                hx-target="#registrations"
+                                                hx-swap="outerHTML"
+                                                hx-confirm=(format!("Reject {}'s sign-up?", registration.name))
+                                            {
+                                                "Reject"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Form data for approving a sign-up
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct ApproveForm {
+    /// Starting Elo (800-2000, default from the questionnaire, else 1200)
+    elo: Option<String>,
+}
+
+/// Approve a sign-up: add the player to the roster with the given starting Elo
+/// (htmx endpoint). If that's the Elo their answers give, the answers are kept
+/// with the player.
+#[utoipa::path(
+    post,
+    path = "/api/registrations/{id}/approve",
+    tag = "players",
+    params(("id" = i32, Path, description = "Registration ID")),
+    request_body(content = ApproveForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Player added; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Sign-up not found", content_type = "text/html"),
+        (status = 422, description = "Invalid Elo or name already on the roster", content_type = "text/html")
+    )
+)]
+pub async fn approve(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+    Form(form): Form<ApproveForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    let registration = match db::get_registration(&state.db, id).await {
+        Ok(Some(registration)) => registration,
+        Ok(None) => return error_fragment(StatusCode::NOT_FOUND, "Sign-up not found"),
+        Err(e) => {
+            tracing::error!("Failed to load registration: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load sign-up");
+        }
+    };
+    let players = db::get_all_players(&state.db).await.unwrap_or_default();
+    if on_roster(&registration.name, &players) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!(
+                "{} is already on the roster. Reject this sign-up, or ask them for a surname or initial.",
+                registration.name
+            ),
+        );
+    }
+    let formula = state.settings.get().onboarding;
+    let answers = registration.answers();
+    let suggested = answers.map_or(ELO_DEFAULT, |a| a.starting_elo(&formula));
+
+    let elo = match form.elo.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        None => suggested,
+        Some(value) => match value.parse::<f32>() {
+            Ok(elo) if (MIN_STARTING_ELO..=MAX_STARTING_ELO).contains(&elo) => elo,
+            _ => {
+                return error_fragment(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    &format!(
+                        "Starting Elo must be between {} and {}",
+                        MIN_STARTING_ELO, MAX_STARTING_ELO
+                    ),
+                )
+            }
+        },
+    };
+
+    // An Elo typed over the suggestion didn't come from the answers
+    let answers = answers.filter(|_| (elo - suggested).abs() < 0.5);
+
+    let new_player = NewPlayer {
+        name: registration.name.clone(),
+        elo: Some(elo),
+        stamina: registration.fitness,
+        position: registration.position.clone(),
+        ..Default::default()
+    };
+    let result: Result<Player, sqlx::Error> = async {
+        let mut tx = state.db.begin().await?;
+        let player = db::create_player(&mut *tx, &new_player).await?;
+        if let Some(answers) = &answers {
+            db::save_onboarding(&mut *tx, player.id, answers, &formula).await?;
+        }
+        db::delete_registration(&mut *tx, id).await?;
+        tx.commit().await?;
+        Ok(player)
+    }
+    .await;
+
+    let player = match result {
+        Ok(player) => player,
+        Err(e) => {
+            tracing::error!("Failed to approve registration: {}", e);
+            return error_fragment(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                &format!(
+                    "Couldn't add {}: the name may already be on the roster",
+                    registration.name
+                ),
+            );
+        }
+    };
+    state
+        .webhooks
+        .send(WebhookEvent::PlayerCreated, player_json(&player));
+
+    // Full reload so the roster shows the new player
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
+
+/// Reject a sign-up (htmx endpoint)
+#[utoipa::path(
+    delete,
+    path = "/api/registrations/{id}",
+    tag = "players",
+    params(("id" = i32, Path, description = "Registration ID")),
+    responses(
+        (status = 200, description = "Remaining sign-ups", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
+pub async fn reject(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    if let Err(e) = db::delete_registration(&state.db, id).await {
+        tracing::error!("Failed to reject registration: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to reject sign-up",
+        );
+    }
+
+    let registrations = db::get_registrations(&state.db).await.unwrap_or_default();
+    let players = state.players.get(&state.db).await.unwrap_or_default();
+    Html(
+        render_registrations(&registrations, &players, &state.settings.get().onboarding)
+            .into_string(),
+    )
+    .into_response()
+}
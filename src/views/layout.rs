@@ -1,3 +1,4 @@
+use crate::models::{Position, CALIBRATION_MATCHES};
 use maud::{html, Markup, DOCTYPE};
 
 /// Auth state for layout
@@ -12,82 +13,44 @@ impl AuthState {
     }
 }
 
-/// Base layout wrapper for all pages
-pub fn base(title: &str, current_page: &str, auth: &AuthState, content: Markup) -> Markup {
+/// htmx settings (see the comment where it's used)
+const HTMX_CONFIG: &str = r#"{"includeIndicatorStyles":false,"allowEval":false,"responseHandling":[{"code":"204","swap":false},{"code":"[23]..","swap":true},{"code":"[45]..","swap":true,"error":true}]}"#;
+
+/// URL of a file under /static, versioned so a deploy busts the browser cache
+pub fn asset(path: &str) -> String {
+    format!("/static/{}?v={}", path, env!("CARGO_PKG_VERSION"))
+}
+
+/// Base layout wrapper for all pages, headed with the group's name
+pub fn base(
+    title: &str,
+    current_page: &str,
+    auth: &AuthState,
+    group_name: &str,
+    content: Markup,
+) -> Markup {
     html! {
         (DOCTYPE)
         html lang="en" {
             head {
                 meta charset="utf-8";
                 meta name="viewport" content="width=device-width, initial-scale=1";
-                title { (title) " - Sunday Manager" }
-                // PicoCSS
-                link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/@picocss/pico@2/css/pico.min.css";
-                // htmx
-                script src="https://unpkg.com/htmx.org@2.0.4" {}
-                // Custom styles
-                style {
-                    r#"
-                    .nav-buttons { display: flex; gap: 0.5rem; flex-wrap: wrap; margin-bottom: 1rem; }
-                    .nav-buttons a { flex: 1; text-align: center; min-width: 70px; display: flex; align-items: center; justify-content: center; }
-                    .team-grid { display: grid; grid-template-columns: 1fr; gap: 1rem; }
-                    @media (min-width: 768px) { .team-grid { grid-template-columns: 1fr 1fr; } }
-                    .player-list { list-style: none; padding: 0; }
-                    ol.player-list { list-style: decimal; }
-                    .table-container { overflow-x: auto; }
-                    .player-list li { padding: 0.5rem; border-bottom: 1px solid var(--pico-muted-border-color); }
-                    .tag { display: inline-block; padding: 0.1rem 0.4rem; border-radius: 4px; font-size: 0.75rem; background: var(--pico-primary-background); color: var(--pico-primary-inverse); margin-left: 0.25rem; }
-                    .elo-positive { color: var(--pico-ins-color); }
-                    .elo-negative { color: var(--pico-del-color); }
-                    .cost-breakdown { font-size: 0.875rem; color: var(--pico-muted-color); }
-                    .checkbox-grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 0.5rem; }
-                    .header-row { display: flex; flex-direction: column; align-items: flex-start; gap: 0.5rem; margin-bottom: 1rem; }
-                    @media (min-width: 768px) { .header-row { flex-direction: row; justify-content: space-between; align-items: center; gap: 1rem; } }
-                    .auth-form, .auth-status { margin-left: auto; }
-                    .auth-form { display: flex; gap: 0.5rem; align-items: stretch; margin: 0; }
-                    .auth-form input, .auth-form button { margin: 0; padding: 0.5rem 0.75rem; height: auto; }
-                    .auth-form input { width: 150px; max-width: 40vw; }
-                    .auth-status { display: flex; gap: 0.5rem; align-items: center; }
-                    .success-message {
-                        color: var(--pico-ins-color);
-                        font-weight: bold;
-                        animation: fadeOut 3s forwards;
-                    }
-                    @keyframes fadeOut {
-                        0% { opacity: 1; }
-                        70% { opacity: 1; }
-                        100% { opacity: 0; }
-                    }
-                    .htmx-indicator { display: none; }
-                    .htmx-request .htmx-indicator, .htmx-request.htmx-indicator { display: inline-block; }
-                    .spinner {
-                        display: inline-block;
-                        width: 1em;
-                        height: 1em;
-                        border: 2px solid currentColor;
-                        border-right-color: transparent;
-                        border-radius: 50%;
-                        animation: spin 0.75s linear infinite;
-                        vertical-align: middle;
-                        margin-left: 0.5rem;
-                    }
-                    @keyframes spin { to { transform: rotate(360deg); } }
-                    .page-title { margin: 0; }
-                    .login-hint { margin-top: 0.5rem; font-size: 0.875rem; }
-                    .logged-in-text { color: var(--pico-ins-color); }
-                    .site-footer { margin-top: 2rem; padding-top: 1rem; border-top: 1px solid var(--pico-muted-border-color); text-align: center; }
-                    .chart-container { position: relative; height: 400px; margin-bottom: 2rem; }
-                    .participation-pct { font-size: 0.8em; }
-                    .score-grid { align-items: center; }
-                    .score-separator { text-align: center; font-size: 2rem; }
-                    "#
-                }
+                // htmx must not inject styles or eval code under the CSP, and
+                // swaps 4xx/5xx error fragments into the target like successes
+                meta name="htmx-config" content=(HTMX_CONFIG);
+                title { (title) " - " (group_name) }
+                // PicoCSS and htmx, vendored under /static
+                link rel="stylesheet" href="/static/vendor/pico.min.css";
+                script src="/static/vendor/htmx.min.js" {}
+                // App styles and behaviour (no inline code, see the CSP in security.rs)
+                link rel="stylesheet" href=(asset("css/app.css"));
+                script src=(asset("js/app.js")) {}
             }
             body {
                 main class="container" {
                     // Header with title and auth
                     div class="header-row" {
-                        h1 class="page-title" { "Sunday Football Manager" }
+                        h1 class="page-title" { (group_name) }
 
                         @if auth.enabled {
                             @if auth.logged_in {
@@ -100,6 +63,10 @@ pub fn base(title: &str, current_page: &str, auth: &AuthState, content: Markup)
                             } @else {
                                 form action="/api/login" method="post" class="auth-form" {
                                     input type="password" name="password" placeholder="Password" required;
+                                    label class="remember-me" {
+                                        input type="checkbox" name="remember_me" value="1";
+                                        "Remember me"
+                                    }
                                     button type="submit" { "Login" }
                                 }
                             }
@@ -120,6 +87,23 @@ pub fn base(title: &str, current_page: &str, auth: &AuthState, content: Markup)
                         a href="/history" role="button" class=(if current_page == "history" { "primary" } else { "secondary outline" }) {
                             "History"
                         }
+                        a href="/seasons" role="button" class=(if current_page == "seasons" { "primary" } else { "secondary outline" }) {
+                            "Seasons"
+                        }
+                        a href="/stats" role="button" class=(if current_page == "stats" { "primary" } else { "secondary outline" }) {
+                            "Stats"
+                        }
+                        a href="/records" role="button" class=(if current_page == "records" { "primary" } else { "secondary outline" }) {
+                            "Records"
+                        }
+                        a href="/me" role="button" class=(if current_page == "me" { "primary" } else { "secondary outline" }) {
+                            "Me"
+                        }
+                        @if auth.logged_in {
+                            a href="/admin" role="button" class=(if current_page == "admin" { "primary" } else { "secondary outline" }) {
+                                "Admin"
+                            }
+                        }
                     }
 
                     hr;
@@ -157,6 +141,63 @@ pub fn render_tags(tags: &str) -> Markup {
     }
 }
 
+/// Render a player's position as a badge (nothing if unset)
+pub fn render_position(position: Option<Position>) -> Markup {
+    html! {
+        @if let Some(position) = position {
+            span class="position-tag" title=(position.label()) { (position) }
+        }
+    }
+}
+
+/// Sparkline size in pixels
+const SPARKLINE_WIDTH: f32 = 80.0;
+const SPARKLINE_HEIGHT: f32 = 20.0;
+
+/// SVG polyline points for `values` scaled to fill a `width` × `height` box
+/// (higher values nearer the top; a flat series sits in the middle)
+pub fn sparkline_points(values: &[f32], width: f32, height: f32) -> String {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let step = width / (values.len().max(2) - 1) as f32;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let y = if max > min {
+                height - (value - min) / (max - min) * height
+            } else {
+                height / 2.0
+            };
+            format!("{:.1},{:.1}", i as f32 * step, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Tiny inline Elo trend chart (nothing for fewer than two points)
+pub fn render_sparkline(values: &[f32]) -> Markup {
+    let (Some(first), Some(last)) = (values.first(), values.last()) else {
+        return html! {};
+    };
+    if values.len() < 2 {
+        return html! {};
+    }
+    let class = if last >= first {
+        "sparkline elo-positive"
+    } else {
+        "sparkline elo-negative"
+    };
+    html! {
+        svg class=(class) width=(SPARKLINE_WIDTH) height=(SPARKLINE_HEIGHT)
+            viewBox=(format!("-1 -1 {} {}", SPARKLINE_WIDTH + 2.0, SPARKLINE_HEIGHT + 2.0)) role="img"
+            aria-label=(format!("Elo over the last {} matches: {:.0} to {:.0}", values.len(), first, last)) {
+            title { (format!("Last {} matches: {:.0} → {:.0}", values.len(), first, last)) }
+            polyline points=(sparkline_points(values, SPARKLINE_WIDTH, SPARKLINE_HEIGHT));
+        }
+    }
+}
+
 /// Format Elo delta with color
 pub fn render_elo_delta(delta: f32) -> Markup {
     let sign = if delta >= 0.0 { "+" } else { "" };
@@ -170,6 +211,71 @@ pub fn render_elo_delta(delta: f32) -> Markup {
     }
 }
 
+/// Note which team played short and the Elo handicap it was given (nothing if even)
+pub fn render_handicap(handicap_a: f32) -> Markup {
+    let (team, handicap) = if handicap_a >= 0.0 {
+        ("Team A", handicap_a)
+    } else {
+        ("Team B", -handicap_a)
+    };
+    if handicap < 0.5 {
+        return html! {};
+    }
+    html! {
+        p class="secondary" {
+            (team) " played short: " (format!("{:.0}", handicap)) " Elo handicap"
+        }
+    }
+}
+
+/// Note which team played without a goalkeeper and its Elo handicap (nothing if none)
+pub fn render_keeper_handicap(keeper_a: f32) -> Markup {
+    let (team, handicap) = if keeper_a >= 0.0 {
+        ("Team A", keeper_a)
+    } else {
+        ("Team B", -keeper_a)
+    };
+    if handicap < 0.5 {
+        return html! {};
+    }
+    html! {
+        p class="secondary" {
+            (team) " had no goalkeeper: " (format!("{:.0}", handicap)) " Elo handicap"
+        }
+    }
+}
+
+/// Mark a player's calibration matches (nothing otherwise)
+pub fn render_calibrating(calibrating: bool) -> Markup {
+    html! {
+        @if calibrating {
+            small class="secondary" title=(format!("One of their first {} matches: their rating moves faster and counts less for the team", CALIBRATION_MATCHES)) {
+                " · calibrating"
+            }
+        }
+    }
+}
+
+/// Mark a team's captain (nothing otherwise)
+pub fn render_captain(captain: bool) -> Markup {
+    html! {
+        @if captain {
+            strong class="captain" title="Captain" { " (C)" }
+        }
+    }
+}
+
+/// Mark a player's first match back after a long absence (their rating moved faster)
+pub fn render_returning(returning: bool) -> Markup {
+    html! {
+        @if returning {
+            small class="secondary" title="First match back after a long break: their rating moves faster" {
+                " · returning"
+            }
+        }
+    }
+}
+
 /// Format participation percentage (only shown if < 100%)
 pub fn render_participation(participation: f32) -> Markup {
     if participation < 1.0 {
@@ -182,3 +288,21 @@ pub fn render_participation(participation: f32) -> Markup {
         html! {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline_points_fill_the_box() {
+        assert_eq!(
+            sparkline_points(&[1200.0, 1250.0, 1225.0], 80.0, 20.0),
+            "0.0,20.0 40.0,0.0 80.0,10.0"
+        );
+        // A flat series sits in the middle
+        assert_eq!(
+            sparkline_points(&[1200.0, 1200.0], 80.0, 20.0),
+            "0.0,10.0 80.0,10.0"
+        );
+    }
+}
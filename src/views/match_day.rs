@@ -1,25 +1,68 @@
-use crate::auth::is_authenticated;
-use crate::balance::balance_teams;
+use crate::attendance::{attendance, days_played, match_days, reliability_discount};
+use crate::auth::{is_authenticated, AuthJar};
+use crate::balance::{
+    add_late_player, balancer, explain_split, score_split, split_key, suggest_swaps, top_splits,
+    BalanceOptions, Balancer, Objective, Randomized, BALANCERS,
+};
+use crate::captains::{nominate_for, Captains};
+use crate::discipline::{self, status_label};
 use crate::elo::average_elo;
-use crate::models::{Player, Tag, TeamSplit};
-use crate::views::layout::{base, render_tags, AuthState};
+use crate::events::Event;
+use crate::form::recent_form;
+use crate::formation::{suggest_formation, Formation};
+use crate::injuries;
+use crate::kit::assign_numbers;
+use crate::models::{CoinToss, Player, RsvpStatus, TeamSplit};
+use crate::peer_ratings::load_peer_scores;
+use crate::positions::{current_position_elo, load_role_ratings};
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::rsvp::{self, next_match_days};
+use crate::settings::Settings;
+use crate::share::whatsapp_text;
+use crate::team_names::team_names;
+use crate::tiers::load_tiers;
+use crate::tournament::{first_round, seed, snake_draft, Squad, MAX_SQUADS, MIN_SQUADS};
+use crate::views::errors::{error_fragment, error_page};
+use crate::views::layout::{asset, base, render_captain, render_position, render_tags, AuthState};
+use crate::views::pitch::render_pitch;
 use crate::{db, AppState};
 use axum::{
     extract::{Query, State},
-    response::{Html, IntoResponse},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
 };
-use axum_extra::extract::{cookie::CookieJar, Form};
-use maud::{html, Markup, PreEscaped};
+use axum_extra::extract::Form;
+use maud::{html, Markup, DOCTYPE};
 use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Team Generator page - check-in and team generation
-pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl IntoResponse {
-    let players = db::get_all_players(&state.db).await.unwrap_or_default();
-    let auth = AuthState::new(
-        state.auth_password.is_some(),
-        is_authenticated(&jar, &state),
-    );
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> impl IntoResponse {
+    let mut players = state.players.get(&state.db).await.unwrap_or_default();
+    mask_names(&mut players, shows_hidden_names(&jar, &state));
+    let suspensions = discipline::load_active(&state.db, state.settings.get().today()).await;
+    let injuries = injuries::load_active(&state.db).await;
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+    let settings = state.settings.get();
+    let max_players = settings.players_per_team * 2;
+    // Players' own RSVPs for this (or the next) match day
+    let match_on = next_match_days(settings.today(), 1)[0];
+    let rsvps = rsvp::load_for_day(&state.db, match_on).await;
+    let rsvp_counts = rsvp::tally(&rsvps);
+    // Teams picked from RSVPs at the deadline, shown unless a shared link says otherwise
+    let auto_teams = db::get_auto_teams(&state.db, match_on)
+        .await
+        .unwrap_or_default()
+        .map(|teams| {
+            format!(
+                "a={}&b={}",
+                join_ids(&teams.team_a),
+                join_ids(&teams.team_b)
+            )
+        });
 
     let content = html! {
         h2 { "Team Generator" }
@@ -29,23 +72,96 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
         @if players.is_empty() {
             p { "No players in database. Add players in the Roster page." }
         } @else {
-            form id="checkin-form" {
-                p { "Select players for today's match: " span id="player-count" class="secondary" { "0 / 14" } }
+            form id="checkin-form" data-max-players=(max_players) {
+                p { "Select players for today's match: " span id="player-count" class="secondary" { "0 / " (max_players) } }
+                @if !rsvps.is_empty() {
+                    p class="secondary rsvp-summary" {
+                        "RSVPs for " (match_on.format("%a %-d %b")) ": "
+                        @for (i, status) in RsvpStatus::ALL.iter().enumerate() {
+                            @if i > 0 { ", " }
+                            (rsvp_counts.get(status).copied().unwrap_or_default()) " " (status.label().to_lowercase())
+                        }
+                        @if let Some(deadline) = settings.rsvp_deadline {
+                            " (closing " (deadline) ")"
+                        }
+                    }
+                }
                 div class="checkbox-grid" {
                     @for player in &players {
-                        label {
-                            input
-                                type="checkbox"
-                                name="player_ids"
-                                value=(player.id)
-                                class="player-checkbox";
-                            (player.name)
+                        @if let Some(suspension) = suspensions.get(&player.id) {
+                            label class="suspended" title=(suspension.reason) {
+                                input type="checkbox" disabled;
+                                (player.name) " "
+                                span class="tag suspended-tag" { (status_label(suspension)) }
+                            }
+                        } @else if let Some(injury) = injuries.get(&player.id) {
+                            label class="suspended" title=(injury.note) {
+                                input type="checkbox" disabled;
+                                (player.name) " "
+                                span class="tag injured-tag" { (injuries::status_label(injury, settings.today())) }
+                            }
+                        } @else {
+                            label {
+                                input
+                                    type="checkbox"
+                                    name="player_ids"
+                                    value=(player.id)
+                                    class="player-checkbox";
+                                (player.name)
+                                @if let Some(status) = rsvps.get(&player.id) {
+                                    " "
+                                    span class=(format!("tag rsvp-tag rsvp-{}", status.as_str())) { (status.label()) }
+                                }
+                            }
                         }
                     }
                 }
 
                 hr;
 
+                div class="grid" {
+                    label {
+                        "Balance for"
+                        select name="objective" {
+                            @for objective in Objective::ALL {
+                                option value=(objective.as_str()) { (objective.label()) }
+                            }
+                        }
+                    }
+                    label {
+                        "Balancer"
+                        select name="balancer" {
+                            @for b in BALANCERS {
+                                option value=(b.key()) selected[settings.balancer.key() == b.key()] { (b.label()) }
+                            }
+                        }
+                    }
+                }
+                label {
+                    input type="checkbox" name="use_form" value="true";
+                    "Factor in recent form (last " (settings.form_games) " games)"
+                }
+                label {
+                    input type="checkbox" name="use_positions" value="true";
+                    "Use position ratings (Elo earned in each player's tagged role)"
+                }
+                label {
+                    input type="checkbox" name="use_tiers" value="true";
+                    "Spread skill tiers (S/A/B/C) evenly between the teams"
+                }
+                label {
+                    input type="checkbox" name="use_peer" value="true";
+                    "Blend in peer ratings from the monthly survey"
+                }
+                label {
+                    input type="checkbox" name="use_reliability" value="true";
+                    "Discount players who often don't show after saying In"
+                }
+                label {
+                    input type="checkbox" name="allow_swap" value="true";
+                    "Late arrivals: allow one compensating swap"
+                }
+
                 // Team generation buttons
                 div class="grid" {
                     button
@@ -67,306 +183,1198 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
                         "Shuffle (Re-roll)"
                         span id="shuffle-spinner" class="htmx-indicator spinner" {}
                     }
+                    button
+                        type="submit"
+                        class="secondary outline"
+                        hx-post="/api/late-arrival"
+                        hx-include="#current-teams"
+                        hx-target="#teams-display"
+                        hx-indicator="#late-spinner"
+                        title="Check in the late player(s), then add them to the current teams"
+                    {
+                        "Add Late Arrival"
+                        span id="late-spinner" class="htmx-indicator spinner" {}
+                    }
+                    button
+                        type="submit"
+                        class="secondary outline"
+                        hx-post="/api/generate/compare"
+                        hx-target="#teams-display"
+                        hx-indicator="#compare-spinner"
+                        title="Run every balancing strategy on the checked-in players and compare the results"
+                    {
+                        "Compare Balancers"
+                        span id="compare-spinner" class="htmx-indicator spinner" {}
+                    }
+                }
+
+                details class="tournament" {
+                    summary { "Tournament squads" }
+                    p class="secondary" {
+                        "Snake-draft the checked-in players into squads by Elo, then seed the squads for a knockout draw."
+                    }
+                    div class="grid" {
+                        label {
+                            "Squads"
+                            select name="squads" {
+                                @for n in MIN_SQUADS..=MAX_SQUADS {
+                                    option value=(n) selected[n == 4] { (n) }
+                                }
+                            }
+                        }
+                        button
+                            type="submit"
+                            class="secondary outline"
+                            hx-post="/api/tournament/squads"
+                            hx-target="#teams-display"
+                            hx-indicator="#squads-spinner"
+                        {
+                            "Draft Squads"
+                            span id="squads-spinner" class="htmx-indicator spinner" {}
+                        }
+                    }
                 }
             }
         }
 
         // Teams display area
-        div id="teams-display" {
+        @if auto_teams.is_some() {
+            p class="secondary" { "Teams were picked automatically from the RSVPs for " (match_on.format("%a %-d %b")) "." }
+        }
+        div id="teams-display" data-auto-teams=[auto_teams] {
             p class="secondary" { "Select players and click 'Generate Teams'" }
         }
 
-        // Script to enable/disable buttons and update counter
-        script {
-            (PreEscaped(r#"
-                const MAX_PLAYERS = 14;
-                const buttons = document.querySelectorAll('#checkin-form button[type="submit"]');
-                const counter = document.getElementById('player-count');
-                const checkboxes = document.querySelectorAll('.player-checkbox');
-
-                function updateState() {
-                    const checked = document.querySelectorAll('.player-checkbox:checked').length;
-                    // Update counter
-                    counter.textContent = checked + ' / ' + MAX_PLAYERS;
-                    // Enable buttons when at least 2 players selected (minimum for teams)
-                    buttons.forEach(btn => btn.disabled = checked < 2);
-                    // Disable unchecked boxes when at max
-                    checkboxes.forEach(cb => {
-                        if (!cb.checked) cb.disabled = checked >= MAX_PLAYERS;
-                    });
-                }
-
-                // Initial state
-                updateState();
-
-                // Listen for changes
-                checkboxes.forEach(cb => {
-                    cb.addEventListener('change', updateState);
-                });
-
-                // Parse comma-separated IDs (mirrors Rust parse_team_ids)
-                function parseTeamIds(param) {
-                    if (!param) return [];
-                    return param.split(',').map(s => parseInt(s, 10)).filter(n => !isNaN(n));
-                }
-
-                // Encode team IDs to hash format (mirrors Rust encode_teams_hash)
-                function encodeTeamsHash(teamA, teamB) {
-                    return 'a=' + teamA.join(',') + '&b=' + teamB.join(',');
-                }
-
-                // Restore checkbox selection from team IDs
-                function restoreCheckboxes(teamIds) {
-                    checkboxes.forEach(cb => {
-                        if (teamIds.includes(parseInt(cb.value))) {
-                            cb.checked = true;
-                        }
-                    });
-                    updateState();
-                }
-
-                // On page load: restore state from hash or localStorage
-                window.addEventListener('load', () => {
-                    const hash = window.location.hash.slice(1);
-                    let teamIds = null;
-
-                    if (hash && hash.includes('a=') && hash.includes('b=')) {
-                        const params = new URLSearchParams(hash);
-                        const teamA = parseTeamIds(params.get('a'));
-                        const teamB = parseTeamIds(params.get('b'));
-                        teamIds = [...teamA, ...teamB];
-                        htmx.ajax('GET', '/api/teams?' + hash, '#teams-display');
-                    } else {
-                        const saved = localStorage.getItem('lastTeams');
-                        if (saved) {
-                            try {
-                                const { teamA, teamB } = JSON.parse(saved);
-                                teamIds = [...teamA, ...teamB];
-                                const hash = encodeTeamsHash(teamA, teamB);
-                                htmx.ajax('GET', '/api/teams?' + hash, '#teams-display');
-                                history.replaceState(null, '', '#' + hash);
-                            } catch (e) {}
-                        }
-                    }
-
-                    if (teamIds) restoreCheckboxes(teamIds);
-                });
-
-                // After teams generated: update hash + localStorage
-                document.body.addEventListener('htmx:afterSwap', (e) => {
-                    if (e.detail.target.id === 'teams-display') {
-                        const result = e.detail.target.querySelector('[data-team-a]');
-                        if (result) {
-                            const teamA = JSON.parse(result.dataset.teamA);
-                            const teamB = JSON.parse(result.dataset.teamB);
-                            history.replaceState(null, '', '#' + encodeTeamsHash(teamA, teamB));
-                            localStorage.setItem('lastTeams', JSON.stringify({teamA, teamB}));
-                        }
-                    }
-                });
-
-                // Copy link to clipboard
-                function copyTeamLink() {
-                    const btn = document.getElementById('copy-link-btn');
-                    const orig = btn.textContent;
-                    navigator.clipboard.writeText(window.location.href).then(() => {
-                        btn.textContent = 'Copied!';
-                        setTimeout(() => btn.textContent = orig, 2000);
-                    }).catch(() => {
-                        btn.textContent = 'Failed';
-                        setTimeout(() => btn.textContent = orig, 2000);
-                    });
-                }
-                window.copyTeamLink = copyTeamLink;
-            "#))
-        }
+        // Check-in counter, shared team links and saved teams
+        script src=(asset("js/match_day.js")) {}
     };
 
-    Html(base("Team Generator", "match_day", &auth, content).into_string())
+    Html(
+        base(
+            "Team Generator",
+            "match_day",
+            &auth,
+            &settings.group_name,
+            content,
+        )
+        .into_string(),
+    )
 }
 
 /// Generate teams endpoint (htmx)
+#[utoipa::path(
+    post,
+    path = "/api/generate",
+    tag = "teams",
+    request_body(content = GenerateForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Balanced teams", content_type = "text/html"))
+)]
 pub async fn generate_teams(
     State(state): State<Arc<AppState>>,
+    jar: AuthJar,
     Form(form): Form<GenerateForm>,
 ) -> impl IntoResponse {
     tracing::info!("Generate teams called with: {:?}", form.player_ids);
 
-    let player_ids: Vec<i32> = form
-        .player_ids
-        .iter()
-        .filter_map(|s| s.parse().ok())
-        .collect();
+    let player_ids = match checked_in_ids(&state, &form.player_ids) {
+        Ok(ids) => ids,
+        Err(message) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &message),
+    };
 
     tracing::info!("Parsed player_ids: {:?}", player_ids);
 
-    if player_ids.len() < 2 {
-        return Html(
-            html! {
-                p class="error" { "Select at least 2 players" }
-            }
-            .into_string(),
+    let players = match load_available_players(&state, &jar, &player_ids).await {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::error!("Failed to get players: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
+        }
+    };
+
+    if players.len() < 2 {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Need at least 2 players who aren't suspended",
         );
     }
 
-    let players = match db::get_players_by_ids(&state.db, &player_ids).await {
+    let options = balance_options(
+        &state,
+        form.objective.as_deref(),
+        form.use_form.is_some(),
+        form.use_positions.is_some(),
+        form.use_tiers.is_some(),
+        form.use_peer.is_some(),
+        form.use_reliability.is_some(),
+    )
+    .await;
+    let balancer = form
+        .balancer
+        .as_deref()
+        .and_then(balancer)
+        .unwrap_or(state.settings.get().balancer);
+    match balancer.split(&players, &options) {
+        Some(split) => {
+            let captains = nominate_for(&state, &split).await;
+            Html(render_generated(&split, &options, captains, &state).into_string()).into_response()
+        }
+        None => error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not generate teams",
+        ),
+    }
+}
+
+/// Shuffle teams endpoint (htmx)
+#[utoipa::path(
+    post,
+    path = "/api/shuffle",
+    tag = "teams",
+    request_body(content = GenerateForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Random near-optimal teams", content_type = "text/html"))
+)]
+pub async fn shuffle_teams(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<GenerateForm>,
+) -> impl IntoResponse {
+    let player_ids = match checked_in_ids(&state, &form.player_ids) {
+        Ok(ids) => ids,
+        Err(message) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &message),
+    };
+
+    let players = match load_available_players(&state, &jar, &player_ids).await {
         Ok(p) => p,
         Err(e) => {
             tracing::error!("Failed to get players: {}", e);
-            return Html(
-                html! {
-                    p class="error" { "Failed to load players" }
-                }
-                .into_string(),
-            );
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
         }
     };
 
-    match balance_teams(&players, false) {
-        Some(split) => Html(render_teams(&split).into_string()),
-        None => Html(
-            html! {
-                p class="error" { "Could not generate teams" }
-            }
-            .into_string(),
+    if players.len() < 2 {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Need at least 2 players who aren't suspended",
+        );
+    }
+
+    let options = balance_options(
+        &state,
+        form.objective.as_deref(),
+        form.use_form.is_some(),
+        form.use_positions.is_some(),
+        form.use_tiers.is_some(),
+        form.use_peer.is_some(),
+        form.use_reliability.is_some(),
+    )
+    .await;
+    match Randomized.split(&players, &options) {
+        Some(split) => {
+            let captains = nominate_for(&state, &split).await;
+            Html(render_generated(&split, &options, captains, &state).into_string()).into_response()
+        }
+        None => error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Could not generate teams",
         ),
     }
 }
 
-/// Shuffle teams endpoint (htmx)
-pub async fn shuffle_teams(
+/// Compare balancing strategies on the checked-in players (htmx)
+#[utoipa::path(
+    post,
+    path = "/api/generate/compare",
+    tag = "teams",
+    request_body(content = GenerateForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Each strategy's split, cost and run time", content_type = "text/html"))
+)]
+pub async fn compare_balancers(
     State(state): State<Arc<AppState>>,
+    jar: AuthJar,
     Form(form): Form<GenerateForm>,
 ) -> impl IntoResponse {
-    let player_ids: Vec<i32> = form
+    let player_ids = match checked_in_ids(&state, &form.player_ids) {
+        Ok(ids) => ids,
+        Err(message) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &message),
+    };
+
+    let players = match load_available_players(&state, &jar, &player_ids).await {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::error!("Failed to get players: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
+        }
+    };
+
+    if players.len() < 2 {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Need at least 2 players who aren't suspended",
+        );
+    }
+
+    let options = balance_options(
+        &state,
+        form.objective.as_deref(),
+        form.use_form.is_some(),
+        form.use_positions.is_some(),
+        form.use_tiers.is_some(),
+        form.use_peer.is_some(),
+        form.use_reliability.is_some(),
+    )
+    .await;
+    let results: Vec<(&dyn Balancer, Option<TeamSplit>, Duration)> = BALANCERS
+        .into_iter()
+        .map(|balancer| {
+            let started = Instant::now();
+            let split = balancer.split(&players, &options);
+            (balancer, split, started.elapsed())
+        })
+        .collect();
+
+    Html(render_comparison(&results).into_string()).into_response()
+}
+
+/// Each balancer's teams side by side, with cost, gap to the best and run time
+fn render_comparison(results: &[(&dyn Balancer, Option<TeamSplit>, Duration)]) -> Markup {
+    let best = results
+        .iter()
+        .filter_map(|(_, split, _)| split.as_ref().map(|s| s.cost))
+        .fold(f32::INFINITY, f32::min);
+    let names = |team: &[Player]| {
+        team.iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    html! {
+        h3 { "Balancer Comparison" }
+        p class="secondary" { "Same players and options for every strategy. Lower cost is better." }
+        div class="comparison-grid" {
+            @for (balancer, split, elapsed) in results {
+                article {
+                    header { strong { (balancer.label()) } }
+                    @if let Some(split) = split {
+                        p {
+                            "Cost " strong { (format!("{:.1}", split.cost)) }
+                            @if split.cost > best {
+                                " (+" (format!("{:.1}", split.cost - best)) ")"
+                            }
+                            br;
+                            small class="secondary" {
+                                "Elo gap " (format!("{:.0}", split.elo_diff))
+                                " · " (format!("{:.1}", elapsed.as_secs_f64() * 1000.0)) " ms"
+                            }
+                        }
+                        p { strong { "A: " } (names(&split.team_a)) }
+                        p { strong { "B: " } (names(&split.team_b)) }
+                        @let ids = |team: &[Player]| join_ids(&team.iter().map(|p| p.id).collect::<Vec<_>>());
+                        button
+                            type="button"
+                            class="secondary outline"
+                            hx-get=(format!("/fragments/teams?a={}&b={}", ids(&split.team_a), ids(&split.team_b)))
+                            hx-target="#teams-display"
+                        {
+                            "Use These Teams"
+                        }
+                    } @else {
+                        p class="secondary" { "No split found." }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Add late arrivals to the current teams without regenerating (htmx)
+#[utoipa::path(
+    post,
+    path = "/api/late-arrival",
+    tag = "teams",
+    request_body(content = LateArrivalForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Current teams with the late arrivals added", content_type = "text/html"))
+)]
+pub async fn add_late_arrival(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<LateArrivalForm>,
+) -> impl IntoResponse {
+    let team_a_ids = parse_team_ids(&form.a);
+    let team_b_ids = parse_team_ids(&form.b);
+    if team_a_ids.is_empty() || team_b_ids.is_empty() {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Generate teams first, then check in the late arrival",
+        );
+    }
+
+    // Late arrivals = checked-in players not already on a team
+    let newcomer_ids: Vec<i32> = form
         .player_ids
         .iter()
         .filter_map(|s| s.parse().ok())
+        .filter(|id| !team_a_ids.contains(id) && !team_b_ids.contains(id))
         .collect();
+    if newcomer_ids.is_empty() {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Check in the late arrival first",
+        );
+    }
+    let max_players = state.settings.get().players_per_team * 2;
+    if team_a_ids.len() + team_b_ids.len() + newcomer_ids.len() > max_players {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!("Teams are capped at {} players in total", max_players),
+        );
+    }
 
-    if player_ids.len() < 2 {
-        return Html(
-            html! {
-                p class="error" { "Select at least 2 players" }
-            }
-            .into_string(),
+    let (mut team_a, mut team_b, mut newcomers) = match (
+        db::get_players_by_ids(&state.db, &team_a_ids).await,
+        db::get_players_by_ids(&state.db, &team_b_ids).await,
+        load_available_players(&state, &jar, &newcomer_ids).await,
+    ) {
+        (Ok(a), Ok(b), Ok(n)) => (a, b, n),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            tracing::error!("Failed to load players for late arrival: {}", e);
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
+        }
+    };
+    if newcomers.is_empty() {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Late arrivals are suspended",
+        );
+    }
+    let show_hidden = shows_hidden_names(&jar, &state);
+    mask_names(&mut team_a, show_hidden);
+    mask_names(&mut team_b, show_hidden);
+
+    let options = balance_options(
+        &state,
+        form.objective.as_deref(),
+        form.use_form.is_some(),
+        form.use_positions.is_some(),
+        form.use_tiers.is_some(),
+        form.use_peer.is_some(),
+        form.use_reliability.is_some(),
+    )
+    .await;
+
+    // Place stronger players first, while there's still room to compensate
+    newcomers.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+    let mut split = score_split(&team_a, &team_b, &options);
+    let mut changes = Vec::new();
+    for newcomer in &newcomers {
+        let result = add_late_player(
+            &split.team_a,
+            &split.team_b,
+            newcomer,
+            form.allow_swap.is_some(),
+            &options,
         );
+        let team = if result.joined_team_a { "A" } else { "B" };
+        changes.push(format!("{} joins Team {}", newcomer.name, team));
+        if let Some((to_b, to_a)) = &result.swap {
+            changes.push(format!("{} ↔ {} swapped", to_b.name, to_a.name));
+        }
+        split = result.split;
     }
 
-    let players = match db::get_players_by_ids(&state.db, &player_ids).await {
+    let captains = nominate_for(&state, &split).await;
+    Html(
+        html! {
+            p class="success-message" { (changes.join(". ")) }
+            (render_generated(&split, &options, captains, &state))
+        }
+        .into_string(),
+    )
+    .into_response()
+}
+
+/// Snake-draft the checked-in players into seeded tournament squads (htmx)
+#[utoipa::path(
+    post,
+    path = "/api/tournament/squads",
+    tag = "teams",
+    request_body(content = SquadsForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Seeded squads and first-round pairings", content_type = "text/html"),
+        (status = 422, description = "Bad squad count or too few players", content_type = "text/html")
+    )
+)]
+pub async fn tournament_squads(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<SquadsForm>,
+) -> Response {
+    let squads = form.squads.unwrap_or(4);
+    if !(MIN_SQUADS..=MAX_SQUADS).contains(&squads) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!("Pick {} to {} squads", MIN_SQUADS, MAX_SQUADS),
+        );
+    }
+    let player_ids: Vec<i32> = form
+        .player_ids
+        .iter()
+        .filter_map(|s| s.parse().ok())
+        .collect();
+    let players = match load_available_players(&state, &jar, &player_ids).await {
         Ok(p) => p,
         Err(e) => {
             tracing::error!("Failed to get players: {}", e);
-            return Html(
-                html! {
-                    p class="error" { "Failed to load players" }
-                }
-                .into_string(),
-            );
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
         }
     };
+    if players.len() < squads {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!(
+                "Need at least {} players who aren't suspended for {} squads",
+                squads, squads
+            ),
+        );
+    }
 
-    match balance_teams(&players, true) {
-        Some(split) => Html(render_teams(&split).into_string()),
-        None => Html(
-            html! {
-                p class="error" { "Could not generate teams" }
+    let seeded = seed(snake_draft(&players, squads));
+    Html(render_squads(&seeded).into_string()).into_response()
+}
+
+/// Seeded squads and the first round of the knockout draw
+fn render_squads(squads: &[Squad]) -> Markup {
+    html! {
+        h3 { "Tournament Squads" }
+        p class="secondary" { "Numbered by seed: squad 1 has the highest average Elo." }
+        div class="team-grid squad-grid" {
+            @for squad in squads {
+                article {
+                    header { "Squad " (squad.seed) }
+                    p { strong { "Avg Elo: " (format!("{:.0}", squad.average_elo)) } }
+                    ul class="player-list team-list" {
+                        @for player in &squad.players {
+                            li {
+                                (player.name)
+                                " (" (format!("{:.0}", player.elo)) ")"
+                                (render_position(player.position()))
+                            }
+                        }
+                    }
+                }
             }
-            .into_string(),
-        ),
+        }
+        h4 { "First round" }
+        ul {
+            @for (seed, opponent) in first_round(squads.len()) {
+                li {
+                    "Squad " (seed)
+                    @if let Some(opponent) = opponent {
+                        " v Squad " (opponent)
+                    } @else {
+                        " (bye)"
+                    }
+                }
+            }
+        }
     }
 }
 
 /// Parse comma-separated IDs from URL param (e.g., "1,5,7" → [1, 5, 7])
-fn parse_team_ids(param: &str) -> Vec<i32> {
+pub(crate) fn parse_team_ids(param: &str) -> Vec<i32> {
     param.split(',').filter_map(|s| s.parse().ok()).collect()
 }
 
+/// Join IDs into a comma-separated URL param (e.g., [1, 5, 7] → "1,5,7")
+pub(crate) fn join_ids(ids: &[i32]) -> String {
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// Encode team IDs to URL hash format (e.g., [1,5,7], [2,3,6] → "a=1,5,7&b=2,3,6")
 #[cfg(test)]
 fn encode_teams_hash(team_a: &[i32], team_b: &[i32]) -> String {
-    let join = |ids: &[i32]| {
+    format!("a={}&b={}", join_ids(team_a), join_ids(team_b))
+}
+
+/// Query params for viewing pre-defined teams
+#[derive(Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct ViewTeamsParams {
+    /// Comma-separated player IDs for Team A
+    pub(crate) a: String,
+    /// Comma-separated player IDs for Team B
+    pub(crate) b: String,
+}
+
+/// Form data for team generation
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct GenerateForm {
+    #[serde(default)]
+    player_ids: Vec<String>,
+    /// Balancing objective: "average" (default) or "spread"
+    objective: Option<String>,
+    /// "exact", "randomized", "annealing" or "draft" for Generate Teams
+    /// (default: the BALANCER setting)
+    balancer: Option<String>,
+    /// Blend recent form into ratings when present
+    use_form: Option<String>,
+    /// Rate players by their tagged position instead of overall Elo when present
+    use_positions: Option<String>,
+    /// Spread skill tiers evenly between the teams when present
+    use_tiers: Option<String>,
+    /// Add each player's peer-rating bonus when present
+    use_peer: Option<String>,
+    /// Take each player's no-show discount off when present
+    use_reliability: Option<String>,
+}
+
+/// Form data for drafting tournament squads: the check-in form plus a squad count
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SquadsForm {
+    #[serde(default)]
+    player_ids: Vec<String>,
+    /// Number of squads (2-8, default 4)
+    squads: Option<usize>,
+}
+
+/// Form data for adding late arrivals: the check-in form plus the current teams
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct LateArrivalForm {
+    /// All checked-in players; those not on a team are the late arrivals
+    #[serde(default)]
+    player_ids: Vec<String>,
+    /// Comma-separated player IDs currently on Team A
+    #[serde(default)]
+    a: String,
+    /// Comma-separated player IDs currently on Team B
+    #[serde(default)]
+    b: String,
+    /// Allow one compensating swap per late arrival when present
+    allow_swap: Option<String>,
+    objective: Option<String>,
+    use_form: Option<String>,
+    use_positions: Option<String>,
+    use_tiers: Option<String>,
+    use_peer: Option<String>,
+    use_reliability: Option<String>,
+}
+
+/// Parse the checked-in player IDs, between 2 and two full teams. The
+/// browser enforces the maximum too, but the balancer must never see more.
+fn checked_in_ids(state: &AppState, player_ids: &[String]) -> Result<Vec<i32>, String> {
+    let ids: Vec<i32> = player_ids.iter().filter_map(|s| s.parse().ok()).collect();
+    let max_players = state.settings.get().players_per_team * 2;
+    if ids.len() < 2 {
+        return Err("Select at least 2 players".to_string());
+    }
+    if ids.len() > max_players {
+        return Err(format!("Select at most {} players", max_players));
+    }
+    Ok(ids)
+}
+
+/// Load checked-in players, leaving out anyone currently suspended or injured
+/// (hidden names masked for the viewer)
+async fn load_available_players(
+    state: &AppState,
+    jar: &AuthJar,
+    player_ids: &[i32],
+) -> Result<Vec<Player>, sqlx::Error> {
+    let mut players = db::get_players_by_ids(&state.db, player_ids).await?;
+    mask_names(&mut players, shows_hidden_names(jar, state));
+    let suspensions = discipline::load_active(&state.db, state.settings.get().today()).await;
+    let injuries = injuries::load_active(&state.db).await;
+    Ok(players
+        .into_iter()
+        .filter(|p| !suspensions.contains_key(&p.id) && !injuries.contains_key(&p.id))
+        .collect())
+}
+
+/// Balancer options from settings plus the objective and toggles chosen on Match Day
+async fn balance_options(
+    state: &AppState,
+    objective: Option<&str>,
+    use_form: bool,
+    use_positions: bool,
+    use_tiers: bool,
+    use_peer: bool,
+    use_reliability: bool,
+) -> BalanceOptions {
+    let settings = state.settings.get();
+    let mut options = BalanceOptions {
+        objective: Objective::from_str(objective.unwrap_or_default()),
+        ..settings.balance_options()
+    };
+
+    if use_form {
+        match db::get_all_matches(&state.db).await {
+            Ok(matches) => {
+                options.form_bonus = recent_form(&matches, settings.form_games)
+                    .into_iter()
+                    .map(|(id, form)| (id, form * settings.form_weight))
+                    .collect();
+            }
+            Err(e) => tracing::error!("Failed to load matches for form: {}", e),
+        }
+    }
+
+    if use_reliability {
+        let played = days_played(
+            &db::get_match_days_played(&state.db)
+                .await
+                .unwrap_or_default(),
+        );
+        let days = match_days(&played);
+        let rsvps = match (days.first(), days.last()) {
+            (Some(first), Some(last)) => db::get_rsvps(&state.db, *first, *last)
+                .await
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        options.reliability_discount = reliability_discount(&attendance(&played, &rsvps));
+    }
+
+    if use_positions {
+        let players = state.players.get(&state.db).await.unwrap_or_default();
+        let ratings = load_role_ratings(&state.db).await;
+        options.position_elo = players
+            .iter()
+            .filter_map(|p| Some((p.id, current_position_elo(p, ratings.get(&p.id)?)?)))
+            .collect();
+    }
+
+    if use_tiers {
+        options.tiers = load_tiers(state).await;
+    }
+
+    if use_peer {
+        options.peer_bonus = load_peer_scores(state)
+            .await
+            .into_iter()
+            .map(|(id, score)| (id, score.balance_bonus()))
+            .collect();
+    }
+
+    options
+}
+
+/// Render freshly balanced teams, noting any form adjustments used
+pub(crate) fn render_generated(
+    split: &TeamSplit,
+    options: &BalanceOptions,
+    captains: Option<Captains>,
+    state: &AppState,
+) -> Markup {
+    let mut adjusted: Vec<(&str, f32)> = split
+        .team_a
+        .iter()
+        .chain(&split.team_b)
+        .filter_map(|p| {
+            let bonus = options.form_bonus.get(&p.id).copied()?;
+            (bonus.abs() >= 0.5).then_some((p.name.as_str(), bonus))
+        })
+        .collect();
+    adjusted.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    html! {
+        (render_teams(split, captains, &state.settings.get(), render_balance_details(split, options)))
+        @if !adjusted.is_empty() {
+            p class="secondary form-note" {
+                "Recent form applied: "
+                @for (i, (name, bonus)) in adjusted.iter().enumerate() {
+                    @if i > 0 { ", " }
+                    (name) " " (format!("{:+.0}", bonus))
+                }
+            }
+        }
+        (render_swap_suggestions(split, options))
+    }
+}
+
+/// Number of runner-up splits shown in the balance details
+const RUNNER_UPS: usize = 3;
+
+/// Why this split won: cost per term, each player's inputs, and the
+/// next-best splits the balancer considered
+fn render_balance_details(split: &TeamSplit, options: &BalanceOptions) -> Markup {
+    let terms = explain_split(&split.team_a, &split.team_b, options);
+
+    let mut players: Vec<(&Player, &str)> = split
+        .team_a
+        .iter()
+        .map(|p| (p, "A"))
+        .chain(split.team_b.iter().map(|p| (p, "B")))
+        .collect();
+    players.sort_by(|a, b| {
+        options
+            .effective_elo(b.0)
+            .total_cmp(&options.effective_elo(a.0))
+    });
+
+    let all_players: Vec<Player> = split.team_a.iter().chain(&split.team_b).cloned().collect();
+    let chosen_key = split_key(split);
+    let runner_ups: Vec<TeamSplit> = top_splits(&all_players, options, RUNNER_UPS + 1)
+        .into_iter()
+        .filter(|s| split_key(s) != chosen_key)
+        .take(RUNNER_UPS)
+        .collect();
+    let names = |ids: Vec<&i32>| -> String {
         ids.iter()
-            .map(|id| id.to_string())
+            .filter_map(|id| all_players.iter().find(|p| p.id == **id))
+            .map(|p| p.name.as_str())
             .collect::<Vec<_>>()
-            .join(",")
+            .join(", ")
     };
-    format!("a={}&b={}", join(team_a), join(team_b))
+
+    html! {
+        details {
+            summary { "Balance Details" }
+            table class="cost-breakdown" {
+                thead { tr { th { "Term" } th { "Team A" } th { "Team B" } th { "Cost" } } }
+                tbody {
+                    @for term in &terms {
+                        tr {
+                            td { (term.name) }
+                            td { (format!("{:.1}", term.value_a)) }
+                            td { (format!("{:.1}", term.value_b)) }
+                            td { (format!("{:.1}", term.cost)) }
+                        }
+                    }
+                }
+                tfoot { tr { th colspan="3" { "Total cost" } th { (format!("{:.1}", split.cost)) } } }
+            }
+
+            h6 { "Players" }
+            div class="table-container" {
+                table class="cost-breakdown" {
+                    thead {
+                        tr {
+                            th { "Player" } th { "Team" } th { "Elo" } th { "Form" } th { "Peer" } th { "Rel." }
+                            th { "Pos" } th { "Tags" } th { "STA" } th { "PAC" } th { "Age" }
+                        }
+                    }
+                    tbody {
+                        @for (player, team) in &players {
+                            tr {
+                                td { (player.name) }
+                                td { (team) }
+                                td { (format!("{:.0}", options.base_elo(player))) }
+                                td {
+                                    @if let Some(bonus) = options.form_bonus.get(&player.id) {
+                                        (format!("{:+.0}", bonus))
+                                    }
+                                }
+                                td {
+                                    @if let Some(bonus) = options.peer_bonus.get(&player.id) {
+                                        (format!("{:+.0}", bonus))
+                                    }
+                                }
+                                td {
+                                    @if let Some(discount) = options.reliability_discount.get(&player.id) {
+                                        (format!("{:+.0}", discount))
+                                    }
+                                }
+                                td { (player.position().map(|p| p.to_string()).unwrap_or_default()) }
+                                td { (player.tag_value()) }
+                                td { (format!("{:.0}", player.stamina_value())) }
+                                td { (format!("{:.0}", player.pace_value())) }
+                                td { (player.age_band().map(|b| b.to_string()).unwrap_or_else(|| "?".to_string())) }
+                            }
+                        }
+                    }
+                }
+            }
+            p class="cost-breakdown" { "Unknown stamina/pace count as 3; unknown age as mid-range." }
+
+            @if !runner_ups.is_empty() {
+                h6 { "Runner-up splits" }
+                ol class="cost-breakdown" {
+                    @for runner_up in &runner_ups {
+                        @let key = split_key(runner_up);
+                        li {
+                            "Cost " (format!("{:.1}", runner_up.cost))
+                            " (+" (format!("{:.1}", runner_up.cost - split.cost)) "): "
+                            (names(chosen_key.iter().filter(|id| !key.contains(id)).collect()))
+                            " ↔ "
+                            (names(key.iter().filter(|id| !chosen_key.contains(id)).collect()))
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
-/// View teams from URL params (for shareable links)
-pub async fn view_teams(
+/// Number of swap suggestions shown under the teams
+const SWAP_SUGGESTIONS: usize = 3;
+
+/// The best single swaps for the current teams, each with a button to apply it
+fn render_swap_suggestions(split: &TeamSplit, options: &BalanceOptions) -> Markup {
+    let suggestions = suggest_swaps(&split.team_a, &split.team_b, options, SWAP_SUGGESTIONS);
+    if suggestions.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        details class="swap-suggestions" {
+            summary { "Swap Suggestions" }
+            ul class="player-list" {
+                @for swap in &suggestions {
+                    @let team_a: Vec<i32> = swap.split.team_a.iter().map(|p| p.id).collect();
+                    @let team_b: Vec<i32> = swap.split.team_b.iter().map(|p| p.id).collect();
+                    li {
+                        (swap.from_a.name) " (A) ↔ " (swap.from_b.name) " (B)"
+                        span class="secondary" {
+                            " · cost " (format!("{:.1}", split.cost)) " → " (format!("{:.1}", swap.split.cost))
+                            ", Elo diff " (format!("{:.0}", swap.split.elo_diff))
+                        }
+                        " "
+                        button
+                            type="button"
+                            class="secondary outline"
+                            hx-get=(format!("/fragments/teams?a={}&b={}", join_ids(&team_a), join_ids(&team_b)))
+                            hx-target="#teams-display"
+                        {
+                            "Apply"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Printable kit list: bib colour and shirt numbers for each team
+pub async fn kit_list(
     State(state): State<Arc<AppState>>,
+    jar: AuthJar,
     Query(params): Query<ViewTeamsParams>,
-) -> impl IntoResponse {
-    let team_a_ids = parse_team_ids(&params.a);
-    let team_b_ids = parse_team_ids(&params.b);
+) -> Response {
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+    let settings = state.settings.get();
+    let (team_a, team_b) = match load_teams(&state, &jar, &params).await {
+        Ok(teams) => teams,
+        Err((status, message)) => {
+            return error_page(status, message, &auth, &settings.group_name);
+        }
+    };
 
-    if team_a_ids.is_empty() || team_b_ids.is_empty() {
-        return Html(
-            html! {
-                p class="error" { "Invalid team data" }
-            }
-            .into_string(),
-        );
+    let [color_a, color_b] = &settings.bib_colors;
+    let [name_a, name_b] = team_names(settings.team_names, &team_a, &team_b);
+    let content = html! {
+        h2 { "Kit List" }
+        div class="team-grid" {
+            (render_kit_table(&name_a, color_a, &team_a))
+            (render_kit_table(&name_b, color_b, &team_b))
+        }
+        button type="button" class="no-print" data-print { "🖨 Print" }
+    };
+
+    Html(
+        base(
+            "Kit List",
+            "match_day",
+            &auth,
+            &settings.group_name,
+            content,
+        )
+        .into_string(),
+    )
+    .into_response()
+}
+
+/// Load both teams for a printable view (hidden names masked for the
+/// viewer), or the status and message to show
+async fn load_teams(
+    state: &AppState,
+    jar: &AuthJar,
+    params: &ViewTeamsParams,
+) -> Result<(Vec<Player>, Vec<Player>), (StatusCode, &'static str)> {
+    let (mut team_a, mut team_b) = match (
+        db::get_players_by_ids(&state.db, &parse_team_ids(&params.a)).await,
+        db::get_players_by_ids(&state.db, &parse_team_ids(&params.b)).await,
+    ) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to load team players: {}", e);
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            return Err((status, "Failed to load the players"));
+        }
+    };
+    if team_a.is_empty() || team_b.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Invalid team data"));
     }
+    let show_hidden = shows_hidden_names(jar, state);
+    mask_names(&mut team_a, show_hidden);
+    mask_names(&mut team_b, show_hidden);
+    Ok((team_a, team_b))
+}
 
-    // Fetch players for each team
-    let team_a = match db::get_players_by_ids(&state.db, &team_a_ids).await {
-        Ok(p) => p,
+/// Teams as a plain-text message for WhatsApp: bib colours, average Elo,
+/// goal rotation and the next match day's date and kickoff
+#[utoipa::path(
+    get,
+    path = "/teams/share",
+    tag = "teams",
+    params(ViewTeamsParams),
+    responses(
+        (status = 200, description = "Message to paste", content_type = "text/plain"),
+        (status = 400, description = "Invalid team data", content_type = "text/plain")
+    )
+)]
+pub async fn share_text(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Query(params): Query<ViewTeamsParams>,
+) -> Response {
+    let settings = state.settings.get();
+    match load_teams(&state, &jar, &params).await {
+        Ok((team_a, team_b)) => whatsapp_text(
+            &settings.group_name,
+            next_match_days(settings.today(), 1)[0],
+            &settings.kickoff,
+            &settings.bib_colors,
+            &team_names(settings.team_names, &team_a, &team_b),
+            &team_a,
+            &team_b,
+        )
+        .into_response(),
+        Err((status, message)) => (status, message).into_response(),
+    }
+}
+
+/// Coin toss for a match day's teams: who kicks off and who picks ends, or a
+/// button to toss if nobody has yet
+#[utoipa::path(
+    get,
+    path = "/fragments/toss",
+    tag = "teams",
+    params(ViewTeamsParams),
+    responses(
+        (status = 200, description = "The toss, or a button to make it", content_type = "text/html"),
+        (status = 400, description = "Invalid team data", content_type = "text/html")
+    )
+)]
+pub async fn coin_toss(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Query(params): Query<ViewTeamsParams>,
+) -> Response {
+    let settings = state.settings.get();
+    let (team_a, team_b) = match load_teams(&state, &jar, &params).await {
+        Ok(teams) => teams,
+        Err((status, message)) => return error_fragment(status, message),
+    };
+    let match_on = next_match_days(settings.today(), 1)[0];
+    let (ids_a, ids_b) = (parse_team_ids(&params.a), parse_team_ids(&params.b));
+    match db::get_coin_toss(&state.db, match_on, &ids_a, &ids_b).await {
+        Ok(toss) => {
+            let names = team_names(settings.team_names, &team_a, &team_b);
+            let can_toss = is_authenticated(&jar, &state);
+            Html(
+                render_coin_toss(toss.as_ref(), &names, &params, &settings, can_toss).into_string(),
+            )
+            .into_response()
+        }
         Err(e) => {
-            tracing::error!("Failed to get team A players: {}", e);
-            return Html(
-                html! {
-                    p class="error" { "Failed to load team A" }
-                }
-                .into_string(),
-            );
+            tracing::error!("Failed to load coin toss: {}", e);
+            error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load the coin toss",
+            )
         }
-    };
+    }
+}
 
-    let team_b = match db::get_players_by_ids(&state.db, &team_b_ids).await {
-        Ok(p) => p,
+/// Toss a coin for kickoff and record it for the next match day, so everyone
+/// with the teams link sees the same result. The first toss for a split
+/// stands; tossing again just shows it. Only the organiser tosses.
+#[utoipa::path(
+    post,
+    path = "/api/toss",
+    tag = "teams",
+    request_body(content = ViewTeamsParams, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The toss that stands", content_type = "text/html"),
+        (status = 400, description = "Invalid team data", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
+pub async fn toss_coin(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(params): Form<ViewTeamsParams>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+    let settings = state.settings.get();
+    let (team_a, team_b) = match load_teams(&state, &jar, &params).await {
+        Ok(teams) => teams,
+        Err((status, message)) => return error_fragment(status, message),
+    };
+    let match_on = next_match_days(settings.today(), 1)[0];
+    let (ids_a, ids_b) = (parse_team_ids(&params.a), parse_team_ids(&params.b));
+    let kickoff_team = if rand::random::<bool>() { "a" } else { "b" };
+    match db::create_coin_toss(&state.db, match_on, &ids_a, &ids_b, kickoff_team).await {
+        Ok(toss) => {
+            state.events.publish(Event::CoinToss);
+            let names = team_names(settings.team_names, &team_a, &team_b);
+            Html(render_coin_toss(Some(&toss), &names, &params, &settings, true).into_string())
+                .into_response()
+        }
         Err(e) => {
-            tracing::error!("Failed to get team B players: {}", e);
-            return Html(
-                html! {
-                    p class="error" { "Failed to load team B" }
+            tracing::error!("Failed to record coin toss: {}", e);
+            error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to record the coin toss",
+            )
+        }
+    }
+}
+
+/// The toss result, or the button to toss (for the organiser)
+fn render_coin_toss(
+    toss: Option<&CoinToss>,
+    names: &[String; 2],
+    params: &ViewTeamsParams,
+    settings: &Settings,
+    can_toss: bool,
+) -> Markup {
+    html! {
+        @if let Some(toss) = toss {
+            @let (kicks_off, picks_ends) = if toss.kickoff_team == "a" {
+                (&names[0], &names[1])
+            } else {
+                (&names[1], &names[0])
+            };
+            p {
+                "🪙 " strong { (kicks_off) } " kick off, " strong { (picks_ends) } " pick ends"
+                " "
+                small class="secondary" {
+                    "(tossed " (toss.tossed_at.with_timezone(&settings.timezone).format("%a %H:%M")) ")"
                 }
-                .into_string(),
-            );
+            }
+        } @else if !can_toss {
+            p class="secondary" { "No coin toss yet." }
+        } @else {
+            button
+                type="button"
+                class="secondary outline"
+                hx-post="/api/toss"
+                hx-vals=(json!({ "a": params.a, "b": params.b }).to_string())
+                hx-target="closest .coin-toss"
+            {
+                "🪙 Toss for kickoff"
+            }
+        }
+    }
+}
+
+/// Team sheet for the pitch-side whiteboard: big names and shirt numbers, no
+/// navigation or ratings, laid out to print or screenshot
+pub async fn team_sheet(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Query(params): Query<ViewTeamsParams>,
+) -> Response {
+    let settings = state.settings.get();
+    let (team_a, team_b) = match load_teams(&state, &jar, &params).await {
+        Ok(teams) => teams,
+        Err((status, message)) => {
+            let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+            return error_page(status, message, &auth, &settings.group_name);
         }
     };
 
-    // Build a TeamSplit (cost/diff don't matter for display)
-    let split = TeamSplit {
-        team_a,
-        team_b,
-        cost: 0.0,
-        elo_diff: 0.0,
-        tag_value_a: 0,
-        tag_value_b: 0,
+    let [color_a, color_b] = &settings.bib_colors;
+    let [name_a, name_b] = team_names(settings.team_names, &team_a, &team_b);
+    let markup = html! {
+        (DOCTYPE)
+        html lang="en" {
+            head {
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1";
+                title { "Team Sheet - " (settings.group_name) }
+                link rel="stylesheet" href=(asset("css/team-sheet.css"));
+                script src=(asset("js/app.js")) {}
+            }
+            body class="team-sheet" {
+                header {
+                    h1 { (settings.group_name) }
+                    p { (settings.today().format("%A %-d %B %Y")) }
+                }
+                div class="sheet-teams" {
+                    (render_sheet_team(&name_a, color_a, &team_a))
+                    (render_sheet_team(&name_b, color_b, &team_b))
+                }
+                button type="button" class="no-print" data-print { "Print" }
+            }
+        }
     };
 
-    Html(render_teams(&split).into_string())
+    Html(markup.into_string()).into_response()
 }
 
-/// Query params for viewing pre-defined teams
-#[derive(Deserialize)]
-pub struct ViewTeamsParams {
-    a: String,
-    b: String,
+/// One team on the team sheet: bib colour, then shirt number and name per
+/// player, in goal rotation order when nobody is a GK
+fn render_sheet_team(team_name: &str, color: &str, team: &[Player]) -> Markup {
+    let (sorted, has_gk) = sort_team_for_goal_rotation(team);
+    let numbers: HashMap<i32, i32> = assign_numbers(team)
+        .into_iter()
+        .map(|entry| (entry.player.id, entry.number))
+        .collect();
+    html! {
+        section class="sheet-team" {
+            h2 { (team_name) span class="bibs" { (color) " bibs" } }
+            ol {
+                @for player in &sorted {
+                    li {
+                        span class="number" { (numbers.get(&player.id).copied().unwrap_or_default()) }
+                        " " (player.name)
+                        @if player.is_goalkeeper() { " (GK)" }
+                    }
+                }
+            }
+            @if !has_gk {
+                p class="hint" { "No keeper: take turns in goal, top to bottom" }
+            }
+        }
+    }
 }
 
-/// Form data for team generation
-#[derive(Debug, Deserialize)]
-pub struct GenerateForm {
-    #[serde(default)]
-    player_ids: Vec<String>,
+/// One team's bib colour and numbered players
+fn render_kit_table(team_name: &str, color: &str, team: &[Player]) -> Markup {
+    html! {
+        article class="kit-list" {
+            header { (team_name) " · " strong { (color) " bibs" } }
+            table {
+                thead { tr { th { "#" } th { "Player" } } }
+                tbody {
+                    @for entry in assign_numbers(team) {
+                        tr {
+                            td { strong { (entry.number) } }
+                            td { (entry.player.name) }
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 /// Render the generated teams
-fn render_teams(split: &TeamSplit) -> Markup {
+fn render_teams(
+    split: &TeamSplit,
+    captains: Option<Captains>,
+    settings: &Settings,
+    balance_details: Markup,
+) -> Markup {
+    let bib_colors = &settings.bib_colors;
+    let [name_a, name_b] = team_names(settings.team_names, &split.team_a, &split.team_b);
     let elo_a = average_elo(&split.team_a);
     let elo_b = average_elo(&split.team_b);
 
@@ -379,84 +1387,134 @@ fn render_teams(split: &TeamSplit) -> Markup {
     let team_b_ids: Vec<i32> = team_b_sorted.iter().map(|p| p.id).collect();
     let team_a_json = serde_json::to_string(&team_a_ids).unwrap_or_else(|_| "[]".to_string());
     let team_b_json = serde_json::to_string(&team_b_ids).unwrap_or_else(|_| "[]".to_string());
+    let captains_json = captains.map(|c| format!("[{},{}]", c.a, c.b));
+    let is_captain = |id: i32| captains.is_some_and(|c| c.contains(id));
 
     html! {
-        // Data attributes for JS to read team IDs
-        div data-team-a=(team_a_json) data-team-b=(team_b_json) {
+        // Data attributes for JS to read team IDs (and captains, carried over to Record)
+        div data-team-a=(team_a_json) data-team-b=(team_b_json) data-captains=[captains_json] {
+            // Current teams, sent along when adding late arrivals
+            div id="current-teams" hidden {
+                input type="hidden" name="a" value=(join_ids(&team_a_ids));
+                input type="hidden" name="b" value=(join_ids(&team_b_ids));
+            }
             h3 { "Generated Teams" }
 
             div class="team-grid" {
                 // Team A
                 article {
-                    header { "Team A" }
+                    header { (name_a) " · " (bib_colors[0]) " bibs" }
                     p { strong { "Avg Elo: " (format!("{:.0}", elo_a)) } }
                     @if team_a_has_gk {
-                        ul class="player-list" style="padding-left: 1.25em;" {
+                        ul class="player-list team-list" {
                             @for player in &team_a_sorted {
                                 li {
-                                    (player.name) " (" (format!("{:.0}", player.elo)) ")"
+                                    (player.name)
+                                    (render_captain(is_captain(player.id)))
+                                    " (" (format!("{:.0}", player.elo)) ")"
+                                    (render_position(player.position()))
                                     (render_tags(&player.tags))
                                 }
                             }
                         }
                     } @else {
-                        p class="secondary" style="font-size: 0.85em; margin-bottom: 0.5em;" { "🧤 Goal rotation order" }
-                        ol class="player-list" style="padding-left: 1.5em;" {
+                        p class="secondary rotation-hint" { "🧤 Goal rotation order" }
+                        ol class="player-list team-list" {
                             @for player in &team_a_sorted {
                                 li {
-                                    (player.name) " (" (format!("{:.0}", player.elo)) ")"
+                                    (player.name)
+                                    (render_captain(is_captain(player.id)))
+                                    " (" (format!("{:.0}", player.elo)) ")"
+                                    (render_position(player.position()))
                                     (render_tags(&player.tags))
                                 }
                             }
                         }
                     }
+                    (render_formation(&suggest_formation(&split.team_a)))
                 }
 
                 // Team B
                 article {
-                    header { "Team B" }
+                    header { (name_b) " · " (bib_colors[1]) " bibs" }
                     p { strong { "Avg Elo: " (format!("{:.0}", elo_b)) } }
                     @if team_b_has_gk {
-                        ul class="player-list" style="padding-left: 1.25em;" {
+                        ul class="player-list team-list" {
                             @for player in &team_b_sorted {
                                 li {
-                                    (player.name) " (" (format!("{:.0}", player.elo)) ")"
+                                    (player.name)
+                                    (render_captain(is_captain(player.id)))
+                                    " (" (format!("{:.0}", player.elo)) ")"
+                                    (render_position(player.position()))
                                     (render_tags(&player.tags))
                                 }
                             }
                         }
                     } @else {
-                        p class="secondary" style="font-size: 0.85em; margin-bottom: 0.5em;" { "🧤 Goal rotation order" }
-                        ol class="player-list" style="padding-left: 1.5em;" {
+                        p class="secondary rotation-hint" { "🧤 Goal rotation order" }
+                        ol class="player-list team-list" {
                             @for player in &team_b_sorted {
                                 li {
-                                    (player.name) " (" (format!("{:.0}", player.elo)) ")"
+                                    (player.name)
+                                    (render_captain(is_captain(player.id)))
+                                    " (" (format!("{:.0}", player.elo)) ")"
+                                    (render_position(player.position()))
                                     (render_tags(&player.tags))
                                 }
                             }
                         }
                     }
+                    (render_formation(&suggest_formation(&split.team_b)))
                 }
             }
 
-            // Balance details
-            details {
-                summary { "Balance Details" }
-                p { "Elo Diff: " (format!("{:.1}", split.elo_diff)) }
-                p {
-                    "Tag Value: "
-                    (split.tag_value_a) " vs " (split.tag_value_b)
-                    " (diff: " ((split.tag_value_a - split.tag_value_b).abs()) ")"
-                }
-                p class="secondary" { "Total Cost: " (format!("{:.1}", split.cost)) }
+            // Same teams drawn on a pitch, for screenshots
+            details class="pitch-view" {
+                summary { "⚽ Pitch view" }
+                (render_pitch(&split.team_a, &split.team_b, bib_colors))
             }
 
+            // Balance details
+            (balance_details)
+
+            // Coin toss for kickoff, refreshed when anyone tosses
+            div
+                class="coin-toss"
+                hx-get=(format!("/fragments/toss?a={}&b={}", join_ids(&team_a_ids), join_ids(&team_b_ids)))
+                hx-trigger="load, refresh"
+                data-live="toss"
+            {}
+
             // Action buttons
-            div class="grid" style="margin-top: 1rem;" {
-                button id="copy-link-btn" type="button" class="secondary outline" onclick="copyTeamLink()" {
+            div class="grid team-actions" {
+                button id="copy-link-btn" type="button" class="secondary outline" {
                     "📋 Copy link"
                 }
-                button type="button" onclick="window.location.href='/record'" {
+                button
+                    id="copy-whatsapp-btn"
+                    type="button"
+                    class="secondary outline"
+                    data-share-url=(format!("/teams/share?a={}&b={}", join_ids(&team_a_ids), join_ids(&team_b_ids)))
+                {
+                    "💬 Copy for WhatsApp"
+                }
+                a
+                    role="button"
+                    class="secondary outline"
+                    href=(format!("/kit?a={}&b={}", join_ids(&team_a_ids), join_ids(&team_b_ids)))
+                    target="_blank"
+                {
+                    "🖨 Kit list"
+                }
+                a
+                    role="button"
+                    class="secondary outline"
+                    href=(format!("/teams/print?a={}&b={}", join_ids(&team_a_ids), join_ids(&team_b_ids)))
+                    target="_blank"
+                {
+                    "📝 Team sheet"
+                }
+                a role="button" href="/record" {
                     "Record this match →"
                 }
             }
@@ -464,19 +1522,44 @@ fn render_teams(split: &TeamSplit) -> Markup {
     }
 }
 
+/// Suggested formation and starting lineup, back to front
+fn render_formation(formation: &Formation) -> Markup {
+    html! {
+        div class="formation" {
+            p { strong { "Formation: " (formation) } }
+            ul class="lineup" {
+                @if let Some(keeper) = formation.keeper {
+                    li {
+                        span class="line" { "GK" }
+                        (keeper.name)
+                        @if !formation.dedicated_keeper { small class="secondary" { " (first in rotation)" } }
+                    }
+                }
+                @for (role, players) in &formation.lines {
+                    @if !players.is_empty() {
+                        li {
+                            span class="line" title=(role) { (role.short()) }
+                            (players.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", "))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Sort team by Elo ascending for goal rotation order.
 /// Returns (sorted_players, has_dedicated_gk).
 fn sort_team_for_goal_rotation(team: &[Player]) -> (Vec<Player>, bool) {
     let mut sorted = team.to_vec();
     sorted.sort_by(|a, b| a.elo.partial_cmp(&b.elo).unwrap());
-    let has_gk = sorted.iter().any(|p| p.has_tag(Tag::Gk));
+    let has_gk = sorted.iter().any(|p| p.is_goalkeeper());
     (sorted, has_gk)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
 
     fn make_player(id: i32, name: &str, elo: f32, tags: &str) -> Player {
         Player {
@@ -484,8 +1567,7 @@ mod tests {
             name: name.to_string(),
             elo,
             tags: tags.to_string(),
-            matches_played: 0,
-            created_at: Utc::now(),
+            ..Default::default()
         }
     }
 
@@ -561,7 +1643,7 @@ mod tests {
         let team_b = vec![2, 3, 6];
 
         let hash = encode_teams_hash(&team_a, &team_b);
-        // Parse like view_teams does from query params
+        // Parse like the team panel fragment does from query params
         let params: std::collections::HashMap<&str, &str> =
             hash.split('&').filter_map(|p| p.split_once('=')).collect();
 
@@ -571,4 +1653,18 @@ mod tests {
         assert_eq!(parsed_a, team_a);
         assert_eq!(parsed_b, team_b);
     }
+
+    #[tokio::test]
+    async fn test_anonymous_toss_is_refused() {
+        let state = Arc::new(AppState::for_tests());
+        let jar = crate::auth::anonymous_jar(&state).await;
+        let params = ViewTeamsParams {
+            a: "1,2".to_string(),
+            b: "3,4".to_string(),
+        };
+
+        let response = toss_coin(State(state), jar, Form(params)).await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
 }
@@ -0,0 +1,557 @@
+use crate::auth::{
+    current_player, is_authenticated, login_player, logout_player, verify_hash, AuthJar,
+};
+use crate::injuries;
+use crate::models::{
+    parse_attribute_level, AbsenceReason, OnboardingAnswers, PeerRating, Player, PlayerAccount,
+    RsvpStatus, MAX_ATTRIBUTE_LEVEL, MIN_ATTRIBUTE_LEVEL,
+};
+use crate::peer_ratings::{survey_period, teammates, MIN_RATERS};
+use crate::privacy::pseudonym;
+use crate::rsvp::{load_for_player, next_match_days, Deadline, RSVP_WEEKS};
+use crate::views::errors::{error_fragment, render_error};
+use crate::views::layout::{base, render_position, render_tags, AuthState};
+use crate::{db, AppState};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Form;
+use chrono::NaiveDate;
+use maud::{html, Markup};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Query params for the player page
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct MeQuery {
+    /// Set after a failed PIN login
+    login_error: Option<String>,
+}
+
+/// The logged-in player, if their session is valid and they're still on the roster
+async fn logged_in_player(state: &AppState, jar: &AuthJar) -> Option<Player> {
+    let player_id = current_player(jar)?;
+    db::get_players_by_ids(&state.db, &[player_id])
+        .await
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+/// Player page - a player's own profile and RSVPs, or the PIN login form.
+/// Public even when pages are private, since players handle their own login.
+pub async fn page(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Query(query): Query<MeQuery>,
+) -> impl IntoResponse {
+    let settings = state.settings.get();
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+
+    let content = match logged_in_player(&state, &jar).await {
+        Some(player) => {
+            let account = db::get_player_account(&state.db, player.id)
+                .await
+                .unwrap_or_default();
+            let days = next_match_days(settings.today(), RSVP_WEEKS);
+            let rsvps = load_for_player(&state.db, player.id, &days).await;
+            let injury = injuries::load_active(&state.db)
+                .await
+                .remove(&player.id)
+                .map(|injury| injuries::status_label(&injury, settings.today()));
+            let onboarding = db::get_onboarding(&state.db, player.id)
+                .await
+                .unwrap_or_default();
+            let survey = if settings.peer_surveys {
+                Some(peer_survey_section(&state, player.id).await)
+            } else {
+                None
+            };
+            let availability = Availability {
+                days: &days,
+                rsvps: &rsvps,
+                deadline: settings.rsvp_deadline,
+                injury: injury.as_deref(),
+            };
+            render_profile(
+                &player,
+                account.as_ref(),
+                onboarding.as_ref(),
+                &availability,
+                survey,
+            )
+        }
+        None => html! {
+            h2 { "Player Login" }
+            p { "Log in with your name and the PIN you set when you claimed your invite." }
+            form action="/api/me/login" method="post" {
+                div class="grid" {
+                    input type="text" name="name" placeholder="Your name" autocomplete="username" required;
+                    input type="password" name="pin" placeholder="PIN" inputmode="numeric" autocomplete="current-password" required;
+                }
+                button type="submit" { "Log In" }
+            }
+            @if query.login_error.is_some() {
+                (render_error("That name and PIN don't match."))
+            }
+            p class="secondary" {
+                "No PIN yet? Ask the organiser for an invite link. Claiming it logs you in and lets you set one."
+            }
+        },
+    };
+
+    Html(base("Me", "me", &auth, &settings.group_name, content).into_string())
+}
+
+/// The Availability section's inputs
+struct Availability<'a> {
+    days: &'a [NaiveDate],
+    rsvps: &'a HashMap<NaiveDate, (RsvpStatus, Option<AbsenceReason>)>,
+    deadline: Option<Deadline>,
+    /// Injury status label, if injured
+    injury: Option<&'a str>,
+}
+
+/// A player's profile, RSVPs and notification settings
+fn render_profile(
+    player: &Player,
+    account: Option<&PlayerAccount>,
+    onboarding: Option<&OnboardingAnswers>,
+    availability: &Availability,
+    survey: Option<Markup>,
+) -> Markup {
+    html! {
+        div class="header-row" {
+            h2 { "Hi, " (player.name) "!" }
+            form action="/api/me/logout" method="post" class="auth-form" {
+                button type="submit" class="secondary outline" { "Log Out" }
+            }
+        }
+
+        h3 { "Availability" }
+        @if let Some(injury) = availability.injury {
+            p {
+                span class="tag injured-tag" { (injury) } " "
+                "You can't be checked in until the organiser marks you fit again. Let them know when you're back."
+            }
+        }
+        @if let Some(deadline) = availability.deadline {
+            p class="secondary" { "Answer by " (deadline) " before each match day. Teams are picked from who's In then." }
+        }
+        (render_availability(availability.days, availability.rsvps))
+
+        @if let Some(survey) = survey {
+            h3 { "Rate Your Teammates" }
+            (survey)
+        }
+
+        h3 { "Profile" }
+        table {
+            tbody {
+                tr { th { "Position" } td { (render_position(player.position())) } }
+                tr { th { "Shirt #" } td { (player.jersey_number.map(|n| n.to_string()).unwrap_or_default()) } }
+                tr { th { "Elo" } td { (format!("{:.0}", player.elo)) } }
+                @if let Some(explained) = onboarding.and_then(|o| o.explain()) {
+                    tr { th { "Starting Elo" } td { (explained) small class="secondary" { " (from your sign-up answers)" } } }
+                }
+                tr { th { "Matches played" } td { (player.matches_played) } }
+                tr { th { "Tags" } td { (render_tags(&player.tags)) } }
+            }
+        }
+        p {
+            a href=(format!("/api/players/{}/history.csv", player.id)) download { "Download your Elo history (CSV)" }
+            br;
+            a href=(format!("/api/players/{}/data", player.id)) download { "Download everything we hold about you (JSON)" }
+        }
+
+        h3 { "Privacy" }
+        (render_visibility(player))
+
+        h3 { "Notifications" }
+        @match account {
+            Some(account) => {
+                p {
+                    "Email: " (account.email.as_deref().unwrap_or("none"))
+                    br;
+                    "Teams picked: " (if account.notify_teams { "on" } else { "off" })
+                    br;
+                    "Results recorded: " (if account.notify_results { "on" } else { "off" })
+                }
+            }
+            None => p { "No email set." },
+        }
+        p class="secondary" { "To change these or your PIN, ask the organiser for a new invite link." }
+    }
+}
+
+/// The next match days with In / Maybe / Out buttons (the current answer
+/// highlighted), and a reason picker for days answered Out
+fn render_availability(
+    days: &[NaiveDate],
+    rsvps: &HashMap<NaiveDate, (RsvpStatus, Option<AbsenceReason>)>,
+) -> Markup {
+    html! {
+        div id="availability" {
+            @for day in days {
+                @let current = rsvps.get(day).map(|(status, _)| *status);
+                @let current_reason = rsvps.get(day).and_then(|(_, reason)| *reason);
+                form class="rsvp-row" hx-post="/api/me/rsvp" hx-target="#availability" hx-swap="outerHTML" {
+                    input type="hidden" name="match_on" value=(day.format("%Y-%m-%d"));
+                    span class="rsvp-date" { (day.format("%a %-d %b")) }
+                    @for status in RsvpStatus::ALL {
+                        button
+                            type="submit"
+                            name="status"
+                            value=(status.as_str())
+                            class=(if current == Some(status) { "" } else { "secondary outline" })
+                            aria-pressed=(current == Some(status))
+                        {
+                            (status.label())
+                        }
+                    }
+                    @if current == Some(RsvpStatus::Out) {
+                        span class="rsvp-reasons" {
+                            small class="secondary" { "Why?" }
+                            @for reason in AbsenceReason::ALL {
+                                button
+                                    type="button"
+                                    hx-post="/api/me/rsvp"
+                                    hx-include="closest form"
+                                    hx-vals=r#"{"status": "out"}"#
+                                    name="reason"
+                                    value=(reason.as_str())
+                                    class=(if current_reason == Some(reason) { "" } else { "secondary outline" })
+                                    aria-pressed=(current_reason == Some(reason))
+                                {
+                                    (reason.label())
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// This month's peer survey: everyone the player has played with recently,
+/// with their answers so far
+async fn peer_survey_section(state: &AppState, player_id: i32) -> Markup {
+    let today = state.settings.get().today();
+    let period = survey_period(today);
+    let matches = db::get_all_matches(&state.db).await.unwrap_or_default();
+    let ids = teammates(&matches, player_id, today);
+    let players = state.players.get(&state.db).await.unwrap_or_default();
+    let teammates: Vec<&Player> = players.iter().filter(|p| ids.contains(&p.id)).collect();
+    let given: HashMap<i32, PeerRating> = db::get_peer_ratings_by(&state.db, player_id)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| r.period == period)
+        .map(|r| (r.player_id, r))
+        .collect();
+    render_peer_survey(&teammates, &given, period)
+}
+
+/// One row per teammate with 1-5 selects for skill, stamina and pace
+fn render_peer_survey(
+    teammates: &[&Player],
+    given: &HashMap<i32, PeerRating>,
+    period: NaiveDate,
+) -> Markup {
+    let select = |name: &str, label: &str, current: Option<i32>| {
+        html! {
+            label {
+                small { (label) }
+                select name=(name) required {
+                    option value="" selected[current.is_none()] { "-" }
+                    @for level in MIN_ATTRIBUTE_LEVEL..=MAX_ATTRIBUTE_LEVEL {
+                        option value=(level) selected[current == Some(level)] { (level) }
+                    }
+                }
+            }
+        }
+    };
+
+    html! {
+        div id="peer-survey" {
+            p class="secondary" {
+                "The " (period.format("%B")) " survey: rate the people you've played with lately from 1 (weakest) to 5. "
+                "Answers are anonymous. The roster only shows averages once " (MIN_RATERS) " people have rated someone, "
+                "and you can change yours until the month is out."
+            }
+            @if teammates.is_empty() {
+                p { "No recent matches yet. Come back after you've played." }
+            }
+            @for teammate in teammates {
+                @let answer = given.get(&teammate.id);
+                form class="peer-row" hx-post="/api/me/peer-ratings" hx-target="#peer-survey" hx-swap="outerHTML" {
+                    input type="hidden" name="player_id" value=(teammate.id);
+                    span class="peer-name" {
+                        (teammate.name)
+                        @if answer.is_some() { " " small class="secondary" { "✓" } }
+                    }
+                    (select("skill", "Skill", answer.map(|a| a.skill)))
+                    (select("stamina", "Stamina", answer.map(|a| a.stamina)))
+                    (select("pace", "Pace", answer.map(|a| a.pace)))
+                    button type="submit" class="secondary outline" { "Save" }
+                }
+            }
+        }
+    }
+}
+
+/// Toggle for hiding the player's name from visitors
+fn render_visibility(player: &Player) -> Markup {
+    html! {
+        form id="visibility" hx-post="/api/me/visibility" hx-target="this" hx-swap="outerHTML" {
+            input type="hidden" name="hide_name" value=(!player.hide_name);
+            @if player.hide_name {
+                p {
+                    "Visitors who aren't logged in see you as " strong { (pseudonym(player.id)) } ". "
+                    "Logged-in players and admins still see your name."
+                }
+                button type="submit" class="secondary outline" { "Show My Name" }
+            } @else {
+                p { "Your name appears on public pages like the Roster, History and Stats." }
+                button type="submit" class="secondary outline" { "Hide My Name from Visitors" }
+            }
+        }
+    }
+}
+
+/// Visibility form data
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct VisibilityForm {
+    /// Whether visitors should see a pseudonym instead of the name
+    hide_name: bool,
+}
+
+/// Hide or show the logged-in player's name on public pages (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/me/visibility",
+    tag = "players",
+    request_body(content = VisibilityForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated privacy setting", content_type = "text/html"),
+        (status = 401, description = "No player logged in", content_type = "text/html")
+    )
+)]
+pub async fn set_visibility(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<VisibilityForm>,
+) -> Response {
+    let Some(mut player) = logged_in_player(&state, &jar).await else {
+        return error_fragment(StatusCode::UNAUTHORIZED, "Log in on the Me page first.");
+    };
+
+    if let Err(e) = db::set_hide_name(&state.db, player.id, form.hide_name).await {
+        tracing::error!("Failed to save visibility: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to save your setting",
+        );
+    }
+    player.hide_name = form.hide_name;
+    Html(render_visibility(&player).into_string()).into_response()
+}
+
+/// Player login form data
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct PlayerLoginForm {
+    name: String,
+    pin: String,
+}
+
+/// Log a player in with their name and PIN
+#[utoipa::path(
+    post,
+    path = "/api/me/login",
+    tag = "auth",
+    request_body(content = PlayerLoginForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 303, description = "Redirect to /me, with `?login_error=1` on a wrong name or PIN"))
+)]
+pub async fn login(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<PlayerLoginForm>,
+) -> Response {
+    let found = db::get_player_pin_hash(&state.db, form.name.trim())
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to look up player login: {}", e);
+            None
+        });
+    match found {
+        Some((player_id, Some(pin_hash)))
+            if state.pin_lockout.attempt(player_id, Instant::now(), || {
+                verify_hash(&pin_hash, form.pin.trim())
+            }) =>
+        {
+            (
+                login_player(jar, player_id, state.secure_cookies),
+                Redirect::to("/me"),
+            )
+                .into_response()
+        }
+        _ => (jar, Redirect::to("/me?login_error=1")).into_response(),
+    }
+}
+
+/// Log the player out
+#[utoipa::path(
+    post,
+    path = "/api/me/logout",
+    tag = "auth",
+    responses((status = 303, description = "Player cookie cleared, redirect to /me"))
+)]
+pub async fn logout(jar: AuthJar) -> impl IntoResponse {
+    (logout_player(jar), Redirect::to("/me"))
+}
+
+/// Form data for an RSVP
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct RsvpForm {
+    /// Match day (YYYY-MM-DD), one of the next four
+    match_on: String,
+    /// "in", "maybe" or "out"
+    status: String,
+    /// Why they're out: "injured", "away", "work" or "other" (optional, only with "out")
+    reason: Option<String>,
+}
+
+/// Set the logged-in player's answer for an upcoming match day (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/me/rsvp",
+    tag = "players",
+    request_body(content = RsvpForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated availability", content_type = "text/html"),
+        (status = 401, description = "No player logged in", content_type = "text/html"),
+        (status = 422, description = "Not an upcoming match day, or unknown status or reason", content_type = "text/html")
+    )
+)]
+pub async fn rsvp(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<RsvpForm>,
+) -> Response {
+    let Some(player) = logged_in_player(&state, &jar).await else {
+        return error_fragment(StatusCode::UNAUTHORIZED, "Log in on the Me page to RSVP.");
+    };
+
+    let days = next_match_days(state.settings.get().today(), RSVP_WEEKS);
+    let Some(day) = NaiveDate::parse_from_str(&form.match_on, "%Y-%m-%d")
+        .ok()
+        .filter(|day| days.contains(day))
+    else {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "You can only RSVP for the next few match days",
+        );
+    };
+    let Some(status) = RsvpStatus::from_str(&form.status) else {
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Unknown RSVP");
+    };
+    // Reasons only go with Out; any other answer clears them
+    let reason = match form.reason.as_deref().filter(|r| !r.is_empty()) {
+        Some(reason) if status == RsvpStatus::Out => match AbsenceReason::from_str(reason) {
+            Some(reason) => Some(reason),
+            None => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Unknown reason"),
+        },
+        _ => None,
+    };
+
+    if let Err(e) = db::set_rsvp(&state.db, player.id, day, status, reason).await {
+        tracing::error!("Failed to save RSVP: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to save your RSVP",
+        );
+    }
+
+    let rsvps = load_for_player(&state.db, player.id, &days).await;
+    Html(render_availability(&days, &rsvps).into_string()).into_response()
+}
+
+/// Form data for one peer survey answer
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct PeerRatingForm {
+    /// The teammate being rated
+    player_id: i32,
+    /// 1-5
+    #[serde(default)]
+    skill: String,
+    /// 1-5
+    #[serde(default)]
+    stamina: String,
+    /// 1-5
+    #[serde(default)]
+    pace: String,
+}
+
+/// Rate a recent teammate in this month's peer survey (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/me/peer-ratings",
+    tag = "players",
+    request_body(content = PeerRatingForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated survey", content_type = "text/html"),
+        (status = 401, description = "No player logged in", content_type = "text/html"),
+        (status = 403, description = "Peer surveys are turned off", content_type = "text/html"),
+        (status = 422, description = "Not a recent teammate, or a rating outside 1-5", content_type = "text/html")
+    )
+)]
+pub async fn rate_teammate(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<PeerRatingForm>,
+) -> Response {
+    let Some(player) = logged_in_player(&state, &jar).await else {
+        return error_fragment(StatusCode::UNAUTHORIZED, "Log in on the Me page first.");
+    };
+    let settings = state.settings.get();
+    if !settings.peer_surveys {
+        return error_fragment(StatusCode::FORBIDDEN, "Peer surveys are turned off");
+    }
+
+    let today = settings.today();
+    let matches = db::get_all_matches(&state.db).await.unwrap_or_default();
+    if !teammates(&matches, player.id, today).contains(&form.player_id) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "You can only rate people you've played with recently",
+        );
+    }
+    let levels = [&form.skill, &form.stamina, &form.pace].map(|v| parse_attribute_level(Some(v)));
+    let [Some(skill), Some(stamina), Some(pace)] = levels else {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Rate skill, stamina and pace from 1 to 5",
+        );
+    };
+
+    let rating = PeerRating {
+        rater_id: player.id,
+        player_id: form.player_id,
+        period: survey_period(today),
+        skill,
+        stamina,
+        pace,
+    };
+    if let Err(e) = db::set_peer_rating(&state.db, &rating).await {
+        tracing::error!("Failed to save peer rating: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to save your rating",
+        );
+    }
+    Html(peer_survey_section(&state, player.id).await.into_string()).into_response()
+}
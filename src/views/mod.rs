@@ -1,5 +1,23 @@
+pub mod absences;
+pub mod activity;
+pub mod admin;
+pub mod errors;
+pub mod flags;
+pub mod fragments;
 pub mod history;
+pub mod invite;
+pub mod jobs;
+pub mod join;
 pub mod layout;
 pub mod match_day;
+pub mod me;
+pub mod pending;
+pub mod pitch;
+pub mod recalibration;
 pub mod record;
+pub mod records;
 pub mod roster;
+pub mod seasons;
+pub mod stats;
+pub mod tag_suggestions;
+pub mod webhooks;
@@ -0,0 +1,527 @@
+use crate::auth::{current_player, is_authenticated, AuthJar};
+use crate::events::Event;
+use crate::models::{Match, NewMatch, PendingResult, Player};
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::views::errors::error_fragment;
+use crate::views::record::{save_result, NewResult};
+use crate::{db, replay, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::NaiveDate;
+use maud::{html, Markup};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// `submitted_by` for results from the Record form
+pub const SUBMITTED_BY_ADMIN: &str = "admin";
+/// `submitted_by` for results from `/api/hooks/record`
+pub const SUBMITTED_BY_HOOK: &str = "hook";
+
+/// `submitted_by` for a fix sent by a logged-in player
+pub fn submitted_by_player(id: i32) -> String {
+    format!("player:{}", id)
+}
+
+/// Who sent a pending result, for display: the player's name or "admin"/"hook"
+fn submitter<'a>(pending: &'a PendingResult, names: &HashMap<i32, &'a str>) -> &'a str {
+    pending
+        .submitted_by
+        .strip_prefix("player:")
+        .and_then(|id| id.parse().ok())
+        .map(|id| names.get(&id).copied().unwrap_or("?"))
+        .unwrap_or(&pending.submitted_by)
+}
+
+/// Queue a validated result for confirmation instead of rating it. Returns
+/// the pending result's ID.
+pub async fn hold_result(
+    state: &AppState,
+    team_a: &[Player],
+    team_b: &[Player],
+    result: &NewResult,
+    submitted_by: &str,
+) -> Result<i32, sqlx::Error> {
+    let new_match = NewMatch {
+        played_at: result.played_at,
+        team_a: team_a.iter().map(|p| p.id).collect(),
+        team_b: team_b.iter().map(|p| p.id).collect(),
+        score_a: result.score_a,
+        score_b: result.score_b,
+        elo_snapshot: serde_json::json!({}),
+        notes: result.notes.clone(),
+        k_multiplier: result.k_multiplier,
+        captain_a: result.captain_a,
+        captain_b: result.captain_b,
+        team_name_a: None,
+        team_name_b: None,
+        goals: result.goals.clone(),
+    };
+    let participation = serde_json::to_value(&result.participation).unwrap_or_default();
+    db::create_pending_result(&state.db, &new_match, &participation, submitted_by, None).await
+}
+
+/// Queue a fix to a recorded match's score and notes for confirmation
+/// instead of applying it. Returns the pending result's ID.
+pub async fn hold_amend(
+    state: &AppState,
+    m: &Match,
+    score_a: i32,
+    score_b: i32,
+    notes: &str,
+    submitted_by: &str,
+) -> Result<i32, sqlx::Error> {
+    let new_match = NewMatch {
+        played_at: m.played_at,
+        team_a: m.team_a.clone(),
+        team_b: m.team_b.clone(),
+        score_a,
+        score_b,
+        elo_snapshot: serde_json::json!({}),
+        notes: notes.to_string(),
+        k_multiplier: m.k_multiplier,
+        captain_a: m.captain_a,
+        captain_b: m.captain_b,
+        team_name_a: None,
+        team_name_b: None,
+        goals: Vec::new(),
+    };
+    db::create_pending_result(
+        &state.db,
+        &new_match,
+        &serde_json::json!({}),
+        submitted_by,
+        Some(m.id),
+    )
+    .await
+}
+
+/// Whether someone may confirm a pending result: any logged-in player from
+/// the match, or an admin. Never whoever submitted it: the confirmer has to
+/// be a second person, and an admin session that also holds a player login
+/// counts as both (admins share one password).
+pub fn can_confirm(admin: bool, player: Option<i32>, pending: &PendingResult) -> bool {
+    let submitted_it = (admin && pending.submitted_by == SUBMITTED_BY_ADMIN)
+        || player.is_some_and(|id| pending.submitted_by == submitted_by_player(id));
+    if submitted_it {
+        return false;
+    }
+    let played =
+        player.is_some_and(|id| pending.team_a.contains(&id) || pending.team_b.contains(&id));
+    played || admin
+}
+
+/// Results waiting for confirmation with confirm and discard actions (shown
+/// on History)
+pub fn render_pending(
+    results: &[PendingResult],
+    names: &HashMap<i32, &str>,
+    admin: bool,
+    player: Option<i32>,
+) -> Markup {
+    let team = |ids: &[i32]| {
+        ids.iter()
+            .map(|id| names.get(id).copied().unwrap_or("?"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    html! {
+        @if !results.is_empty() {
+            section id="pending-results" {
+                h3 { "Waiting for Confirmation (" (results.len()) ")" }
+                p class="secondary" {
+                    "These results and fixes don't count yet. Someone who played, or an admin, other than whoever sent them has to confirm them."
+                }
+                div id="pending-result" {}
+                @for pending in results {
+                    article {
+                        header {
+                            strong { (pending.played_at.format("%Y-%m-%d")) }
+                            @if pending.amends.is_some() {
+                                " " span class="tag" { "Fix" }
+                            }
+                            " "
+                            small class="secondary" {
+                                "sent by " (submitter(pending, names)) " on " (pending.created_at.format("%-d %b"))
+                            }
+                        }
+                        p {
+                            (team(&pending.team_a)) " "
+                            strong { (pending.score_a) " : " (pending.score_b) }
+                            " " (team(&pending.team_b))
+                        }
+                        @if !pending.notes.is_empty() {
+                            p class="secondary" { (pending.notes) }
+                        }
+                        div class="row-actions" {
+                            @if can_confirm(admin, player, pending) {
+                                button
+                                    hx-post=(format!("/api/pending/{}/confirm", pending.id))
+                                    hx-target="#pending-result"
+                                {
+                                    "Confirm"
+                                }
+                            }
+                            @if admin {
+                                button
+                                    class="secondary outline"
+                                    hx-delete=(format!("/api/pending/{}", pending.id))
+                                    hx-target="#pending-result"
+                                    hx-confirm="Discard this result without rating it?"
+                                {
+                                    "Discard"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Load and render the results waiting for confirmation
+pub(crate) async fn pending_section(state: &AppState, jar: &AuthJar) -> Markup {
+    let results = db::get_pending_results(&state.db).await.unwrap_or_default();
+    if results.is_empty() {
+        return html! {};
+    }
+    let mut players = state.players.get(&state.db).await.unwrap_or_default();
+    mask_names(&mut players, shows_hidden_names(jar, state));
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    render_pending(
+        &results,
+        &names,
+        is_authenticated(jar, state),
+        current_player(jar),
+    )
+}
+
+/// Confirm a pending result: rate and record it as if just submitted (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/pending/{id}/confirm",
+    tag = "matches",
+    params(("id" = i32, Path, description = "Pending result ID")),
+    responses(
+        (status = 200, description = "Result recorded; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 403, description = "Not in this match, or whoever submitted it", content_type = "text/html"),
+        (status = 404, description = "Already confirmed or discarded", content_type = "text/html"),
+        (status = 409, description = "A fix to a match that has since been voided; the fix is discarded", content_type = "text/html"),
+        (status = 422, description = "A player in the result has left the roster", content_type = "text/html")
+    )
+)]
+pub async fn confirm_pending(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    let admin = is_authenticated(&jar, &state);
+    let player = current_player(&jar);
+    if !admin && player.is_none() {
+        return error_fragment(
+            StatusCode::UNAUTHORIZED,
+            "Log in (or log in as a player on the Me page) to confirm results.",
+        );
+    }
+    let pending = match db::get_pending_result(&state.db, id).await {
+        Ok(Some(pending)) => pending,
+        Ok(None) => {
+            return error_fragment(
+                StatusCode::NOT_FOUND,
+                "This result was already confirmed or discarded",
+            )
+        }
+        Err(e) => {
+            tracing::error!("Failed to load pending result {}: {}", id, e);
+            return error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load the result",
+            );
+        }
+    };
+    if !can_confirm(admin, player, &pending) {
+        return error_fragment(
+            StatusCode::FORBIDDEN,
+            "Only someone who played, or an admin, other than whoever sent it can confirm this.",
+        );
+    }
+    if let Some(match_id) = pending.amends {
+        return confirm_amend(&state, id, match_id, &pending).await;
+    }
+
+    let (Ok(team_a), Ok(team_b)) = (
+        db::get_players_by_ids(&state.db, &pending.team_a).await,
+        db::get_players_by_ids(&state.db, &pending.team_b).await,
+    ) else {
+        tracing::error!("Failed to load players for pending result {}", id);
+        return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
+    };
+    if team_a.len() != pending.team_a.len() || team_b.len() != pending.team_b.len() {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "A player in this result has left the roster. Discard it and record it again.",
+        );
+    }
+
+    let result = NewResult {
+        played_at: pending.played_at,
+        score_a: pending.score_a,
+        score_b: pending.score_b,
+        participation: serde_json::from_value(pending.participation).unwrap_or_default(),
+        k_multiplier: pending.k_multiplier,
+        notes: pending.notes,
+        captain_a: pending.captain_a,
+        captain_b: pending.captain_b,
+        goals: serde_json::from_value(pending.goals).unwrap_or_default(),
+        confirms: Some(id),
+    };
+    let played_at = pending.played_at;
+    match save_result(&state, &team_a, &team_b, result).await {
+        Ok(_) => {}
+        Err(sqlx::Error::RowNotFound) => {
+            return error_fragment(
+                StatusCode::NOT_FOUND,
+                "This result was already confirmed or discarded",
+            )
+        }
+        Err(e) => {
+            tracing::error!("Failed to confirm pending result {}: {}", id, e);
+            return error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to save match record",
+            );
+        }
+    }
+    tracing::info!("Pending result {} confirmed", id);
+
+    // It was rated against today's ratings; matches played after it need them
+    // as they were on the day, so replay
+    let matches = db::get_all_matches(&state.db).await.unwrap_or_else(|e| {
+        tracing::error!("Failed to load matches after confirming {}: {}", id, e);
+        Vec::new()
+    });
+    if played_before_latest(played_at, &matches) {
+        if let Err(e) = replay::recalculate_all(&state.db, &state.settings.get().elo).await {
+            tracing::error!("Failed to recalculate ratings: {}", e);
+            return error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Recorded, but failed to recalculate ratings",
+            );
+        }
+        state.events.publish(Event::Results);
+    }
+
+    // Full reload so the chart and match log show the new result
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
+
+/// Whether a result dated `played_at` belongs before a match already recorded
+fn played_before_latest(played_at: NaiveDate, matches: &[Match]) -> bool {
+    matches
+        .iter()
+        .any(|m| m.voided_at.is_none() && m.played_at > played_at)
+}
+
+/// What confirming a fix did
+#[derive(Debug, PartialEq)]
+enum Amend {
+    Applied,
+    /// Someone else confirmed or discarded it first
+    AlreadyHandled,
+    /// Its match was voided since; the fix is dropped
+    MatchVoided,
+}
+
+impl Amend {
+    /// From whether the pending row was still there to delete and whether
+    /// its match took the new score. A fix to a voided match is still
+    /// deleted, so it doesn't sit in the queue unconfirmable.
+    fn from_rows(deleted: bool, amended: bool) -> Self {
+        match (deleted, amended) {
+            (false, _) => Amend::AlreadyHandled,
+            (true, false) => Amend::MatchVoided,
+            (true, true) => Amend::Applied,
+        }
+    }
+}
+
+/// Apply a confirmed fix to its match and replay ratings
+async fn confirm_amend(
+    state: &AppState,
+    id: i32,
+    match_id: i32,
+    pending: &PendingResult,
+) -> Response {
+    let applied = async {
+        let mut tx = state.db.begin().await?;
+        let deleted = db::delete_pending_result(&mut *tx, id).await?;
+        let amended = deleted
+            && db::amend_match(
+                &mut *tx,
+                match_id,
+                pending.score_a,
+                pending.score_b,
+                &pending.notes,
+            )
+            .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(Amend::from_rows(deleted, amended))
+    }
+    .await;
+    match applied {
+        Ok(Amend::Applied) => {}
+        Ok(Amend::AlreadyHandled) => {
+            return error_fragment(
+                StatusCode::NOT_FOUND,
+                "This fix was already confirmed or discarded",
+            )
+        }
+        Ok(Amend::MatchVoided) => {
+            tracing::info!("Fix {} dropped: match {} was voided", id, match_id);
+            return error_fragment(
+                StatusCode::CONFLICT,
+                "Its match was voided, so this fix was discarded. Restore the match and send the fix again.",
+            );
+        }
+        Err(e) => {
+            tracing::error!("Failed to confirm fix {} to match {}: {}", id, match_id, e);
+            return error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to save the result",
+            );
+        }
+    }
+    if let Err(e) = replay::recalculate_all(&state.db, &state.settings.get().elo).await {
+        tracing::error!("Failed to recalculate ratings: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to recalculate ratings",
+        );
+    }
+    state.events.publish(Event::Results);
+    tracing::info!("Fix {} to match {} confirmed", id, match_id);
+
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
+
+/// Discard a pending result without rating it (htmx endpoint)
+#[utoipa::path(
+    delete,
+    path = "/api/pending/{id}",
+    tag = "matches",
+    params(("id" = i32, Path, description = "Pending result ID")),
+    responses(
+        (status = 200, description = "Discarded; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
+pub async fn discard_pending(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    if let Err(e) = db::delete_pending_result(&state.db, id).await {
+        tracing::error!("Failed to discard pending result {}: {}", id, e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to discard the result",
+        );
+    }
+
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn make_pending(submitted_by: &str) -> PendingResult {
+        PendingResult {
+            id: 1,
+            played_at: NaiveDate::from_ymd_opt(2025, 3, 30).unwrap(),
+            team_a: vec![1, 2],
+            team_b: vec![3, 4],
+            score_a: 2,
+            score_b: 1,
+            participation: serde_json::json!({}),
+            k_multiplier: 1.0,
+            notes: String::new(),
+            submitted_by: submitted_by.to_string(),
+            created_at: Utc::now(),
+            captain_a: None,
+            captain_b: None,
+            goals: serde_json::json!([]),
+            amends: None,
+        }
+    }
+
+    #[test]
+    fn test_can_confirm() {
+        let from_admin = make_pending(SUBMITTED_BY_ADMIN);
+        let from_hook = make_pending(SUBMITTED_BY_HOOK);
+
+        // Players from the match, on either team
+        assert!(can_confirm(false, Some(1), &from_admin));
+        assert!(can_confirm(false, Some(4), &from_hook));
+        assert!(!can_confirm(false, Some(5), &from_admin));
+        assert!(!can_confirm(false, None, &from_hook));
+
+        // Admins only for results they didn't submit
+        assert!(!can_confirm(true, None, &from_admin));
+        assert!(can_confirm(true, None, &from_hook));
+        // ...even if they also played: it's the same person
+        assert!(!can_confirm(true, Some(2), &from_admin));
+
+        // A player's fix needs someone else
+        let from_player = make_pending(&submitted_by_player(1));
+        assert!(!can_confirm(false, Some(1), &from_player));
+        assert!(can_confirm(false, Some(3), &from_player));
+        assert!(can_confirm(true, None, &from_player));
+        assert!(!can_confirm(true, Some(1), &from_player));
+    }
+
+    #[test]
+    fn test_participation_round_trips_through_json() {
+        let participation = HashMap::from([(7, 0.5_f32)]);
+        let value = serde_json::to_value(&participation).unwrap();
+        assert_eq!(value, serde_json::json!({ "7": 0.5 }));
+        let back: HashMap<i32, f32> = serde_json::from_value(value).unwrap();
+        assert_eq!(back, participation);
+    }
+
+    #[test]
+    fn test_played_before_latest() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        let matches = vec![
+            Match {
+                played_at: day(23),
+                ..Match::default()
+            },
+            Match {
+                played_at: day(30),
+                voided_at: Some(Utc::now()),
+                ..Match::default()
+            },
+        ];
+        assert!(played_before_latest(day(16), &matches));
+        // Same day as the latest, or only before a voided match: no replay
+        assert!(!played_before_latest(day(23), &matches));
+        assert!(!played_before_latest(day(27), &matches));
+    }
+
+    #[test]
+    fn test_fix_to_a_voided_match_is_dropped() {
+        assert_eq!(Amend::from_rows(true, true), Amend::Applied);
+        assert_eq!(Amend::from_rows(true, false), Amend::MatchVoided);
+        assert_eq!(Amend::from_rows(false, false), Amend::AlreadyHandled);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::formation::{suggest_formation, Formation};
+use crate::kit::assign_numbers;
+use crate::models::Player;
+use maud::{html, Markup};
+use std::collections::HashMap;
+
+/// Pitch size in SVG units (drawn landscape, Team A defending the left goal)
+const WIDTH: f32 = 1000.0;
+const HEIGHT: f32 = 640.0;
+/// Distance of the keeper from their goal line
+const KEEPER_X: f32 = 60.0;
+/// Outfield lines sit between these distances from a team's goal line
+const BACK_LINE_X: f32 = 170.0;
+const FRONT_LINE_X: f32 = 440.0;
+/// Radius of a player marker
+const MARKER_RADIUS: f32 = 22.0;
+
+/// A player placed on the pitch
+struct Marker<'a> {
+    x: f32,
+    y: f32,
+    player: &'a Player,
+    number: i32,
+}
+
+/// Place one team in its half, keeper at the back and each formation line
+/// spread across the pitch. `mirror` puts the team in the right half.
+fn place_team<'a>(team: &'a [Player], formation: &Formation<'a>, mirror: bool) -> Vec<Marker<'a>> {
+    let numbers: HashMap<i32, i32> = assign_numbers(team)
+        .into_iter()
+        .map(|entry| (entry.player.id, entry.number))
+        .collect();
+    let lines: Vec<&Vec<&Player>> = formation
+        .lines
+        .iter()
+        .map(|(_, players)| players)
+        .filter(|players| !players.is_empty())
+        .collect();
+
+    let mut placed = Vec::with_capacity(team.len());
+    if let Some(keeper) = formation.keeper {
+        placed.push((KEEPER_X, HEIGHT / 2.0, keeper));
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let x = if lines.len() == 1 {
+            (BACK_LINE_X + FRONT_LINE_X) / 2.0
+        } else {
+            BACK_LINE_X + (FRONT_LINE_X - BACK_LINE_X) * i as f32 / (lines.len() - 1) as f32
+        };
+        for (j, player) in line.iter().enumerate() {
+            let y = HEIGHT * (j + 1) as f32 / (line.len() + 1) as f32;
+            placed.push((x, y, *player));
+        }
+    }
+
+    placed
+        .into_iter()
+        .map(|(x, y, player)| Marker {
+            x: if mirror { WIDTH - x } else { x },
+            y,
+            player,
+            number: numbers.get(&player.id).copied().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Both teams on an SVG pitch in their suggested formations, with shirt
+/// numbers in bib-coloured markers
+pub fn render_pitch(team_a: &[Player], team_b: &[Player], bib_colors: &[String; 2]) -> Markup {
+    let formation_a = suggest_formation(team_a);
+    let formation_b = suggest_formation(team_b);
+    let teams = [
+        (place_team(team_a, &formation_a, false), &bib_colors[0]),
+        (place_team(team_b, &formation_b, true), &bib_colors[1]),
+    ];
+    let (mid_x, mid_y) = (WIDTH / 2.0, HEIGHT / 2.0);
+    let box_depth = 120.0;
+    let box_width = 300.0;
+
+    html! {
+        svg class="pitch" viewBox=(format!("0 0 {} {}", WIDTH, HEIGHT)) role="img"
+            aria-label=(format!("Team A ({}) against Team B ({})", formation_a, formation_b)) {
+            rect class="pitch-grass" x="0" y="0" width=(WIDTH) height=(HEIGHT);
+            g class="pitch-lines" {
+                rect x="10" y="10" width=(WIDTH - 20.0) height=(HEIGHT - 20.0);
+                line x1=(mid_x) y1="10" x2=(mid_x) y2=(HEIGHT - 10.0);
+                circle cx=(mid_x) cy=(mid_y) r="70";
+                rect x="10" y=(mid_y - box_width / 2.0) width=(box_depth) height=(box_width);
+                rect x=(WIDTH - 10.0 - box_depth) y=(mid_y - box_width / 2.0) width=(box_depth) height=(box_width);
+            }
+            @for (markers, color) in &teams {
+                g class="pitch-team" {
+                    @for marker in markers {
+                        circle cx=(marker.x) cy=(marker.y) r=(MARKER_RADIUS) fill=(color.to_lowercase());
+                        text class="pitch-number" x=(marker.x) y=(marker.y) { (marker.number) }
+                        text class="pitch-name" x=(marker.x) y=(marker.y + MARKER_RADIUS + 20.0) { (marker.player.name) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_team(ids: std::ops::RangeInclusive<i32>) -> Vec<Player> {
+        ids.map(|id| Player {
+            id,
+            name: format!("P{}", id),
+            elo: 1200.0 + id as f32,
+            ..Default::default()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_teams_stay_in_their_half() {
+        let team_a = make_team(1..=7);
+        let team_b = make_team(8..=14);
+        let formation_a = suggest_formation(&team_a);
+        let formation_b = suggest_formation(&team_b);
+
+        let markers_a = place_team(&team_a, &formation_a, false);
+        let markers_b = place_team(&team_b, &formation_b, true);
+
+        assert_eq!(markers_a.len(), 7);
+        assert!(markers_a.iter().all(|m| m.x < WIDTH / 2.0));
+        assert!(markers_b.iter().all(|m| m.x > WIDTH / 2.0));
+        // The keeper is nearest their own goal
+        assert_eq!(markers_a[0].x, KEEPER_X);
+        assert_eq!(markers_b[0].x, WIDTH - KEEPER_X);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::events::Event;
+use crate::recalibration::{recalibrations, Recalibration, RECALIBRATION_MATCHES};
+use crate::views::errors::error_fragment;
+use crate::{db, replay, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use maud::{html, Markup};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// New players whose first results point to a different starting Elo, each
+/// with a recalibrate button (a section of the Roster page for admins)
+pub fn render_recalibrations(offers: &[Recalibration], names: &HashMap<i32, &str>) -> Markup {
+    html! {
+        @if !offers.is_empty() {
+            details {
+                summary { "Starting Elo Recalibration (" (offers.len()) ")" }
+                p class="secondary" {
+                    "Starting ratings back-solved from each new player's first "
+                    (RECALIBRATION_MATCHES)
+                    " matches. Recalibrating replaces the starting Elo and replays every match, "
+                    "so teammates and opponents from those matches are re-rated too."
+                }
+                div class="table-container" {
+                    table {
+                        thead {
+                            tr {
+                                th { "Player" }
+                                th { "First " (RECALIBRATION_MATCHES) " (W-D-L)" }
+                                th { "Starting Elo" }
+                                th { "Estimate" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            @for offer in offers {
+                                @let name = names.get(&offer.player_id).copied().unwrap_or("Unknown");
+                                @let (won, drawn, lost) = offer.record;
+                                tr {
+                                    td { (name) }
+                                    td { (won) "-" (drawn) "-" (lost) }
+                                    td { (format!("{:.0}", offer.current)) }
+                                    td { (format!("{:.0}", offer.estimate)) }
+                                    td class="row-actions" {
+                                        button
+                                            hx-post=(format!("/api/players/{}/recalibrate", offer.player_id))
+                                            hx-confirm=(format!(
+                                                "Change {}'s starting Elo from {:.0} to {:.0} and replay all matches?",
+                                                name, offer.current, offer.estimate
+                                            ))
+                                        {
+                                            "Recalibrate"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Work out recalibration offers: players with an imported rating baseline
+/// or an earlier recalibration are skipped
+async fn load_offers(state: &AppState) -> Result<Vec<Recalibration>, sqlx::Error> {
+    let players = state.players.get(&state.db).await?;
+    let matches = db::get_all_matches(&state.db).await?;
+    let initial_elo = db::get_initial_elos(&state.db).await?;
+    let mut skip: HashSet<i32> = db::get_recalibrated(&state.db).await?.into_iter().collect();
+    skip.extend(
+        db::get_rating_baselines(&state.db)
+            .await?
+            .iter()
+            .map(|b| b.player_id),
+    );
+    Ok(recalibrations(&players, &initial_elo, &matches, &skip))
+}
+
+/// Load and render the recalibration offers
+pub(crate) async fn recalibration_section(state: &AppState) -> Markup {
+    let offers = load_offers(state).await.unwrap_or_else(|e| {
+        tracing::error!("Failed to work out recalibrations: {}", e);
+        Vec::new()
+    });
+    let players = state.players.get(&state.db).await.unwrap_or_default();
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    render_recalibrations(&offers, &names)
+}
+
+/// Replace a new player's starting Elo with the estimate from their first
+/// matches and replay all matches (htmx endpoint; reloads the page)
+#[utoipa::path(
+    post,
+    path = "/api/players/{id}/recalibrate",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    responses(
+        (status = 200, description = "Starting Elo changed and ratings replayed; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "No recalibration on offer for this player", content_type = "text/html")
+    )
+)]
+pub async fn recalibrate(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+    let offer = match load_offers(&state).await {
+        Ok(offers) => offers.into_iter().find(|o| o.player_id == id),
+        Err(e) => {
+            tracing::error!("Failed to work out recalibrations: {}", e);
+            return error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to work out recalibration",
+            );
+        }
+    };
+    let Some(offer) = offer else {
+        return error_fragment(
+            StatusCode::NOT_FOUND,
+            "No recalibration on offer for this player",
+        );
+    };
+
+    if let Err(e) = db::recalibrate_player(&state.db, id, offer.current, offer.estimate).await {
+        tracing::error!("Failed to recalibrate player {}: {}", id, e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to recalibrate player",
+        );
+    }
+    if let Err(e) = replay::recalculate_all(&state.db, &state.settings.get().elo).await {
+        tracing::error!("Failed to recalculate ratings: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to recalculate ratings",
+        );
+    }
+    state.events.publish(Event::Results);
+    tracing::info!(
+        "Recalibrated player {}: starting Elo {:.0} -> {:.0}",
+        id,
+        offer.current,
+        offer.estimate
+    );
+
+    // Every rating on the page may have moved
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
@@ -1,23 +1,44 @@
-use crate::auth::is_authenticated;
-use crate::elo::calculate_elo_changes;
-use crate::models::{EloSnapshot, Player, MAX_PER_TEAM};
-use crate::views::layout::{base, render_elo_delta, render_participation, AuthState};
+use crate::auth::{is_authenticated, AuthJar};
+use crate::balance::{balance_teams_with, BalanceOptions};
+use crate::elo::{calculate_elo_changes, recorded_handicap, recorded_keeper_handicap};
+use crate::events::Event;
+use crate::milestones::{milestones, PlayerMilestone};
+use crate::models::{
+    clean_note, goal_score, parse_goal, parse_minutes, parse_participation, EloSnapshot, Goal,
+    Match, NewMatch, Player, TeamSplit, DEFAULT_MATCH_MINUTES, MAX_K_MULTIPLIER, MAX_MATCH_MINUTES,
+    MAX_NOTE_LENGTH, MAX_SCORE,
+};
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::team_names::{team_names, themed_names};
+use crate::views::errors::error_fragment;
+use crate::views::history::{render_goals, render_mvp};
+use crate::views::layout::{
+    asset, base, render_calibrating, render_elo_delta, render_handicap, render_keeper_handicap,
+    render_participation, render_returning, AuthState,
+};
+use crate::views::match_day::join_ids;
+use crate::views::pending::{hold_result, SUBMITTED_BY_ADMIN};
+use crate::webhooks::{result_json, WebhookEvent};
 use crate::{db, AppState};
 use axum::{
     extract::State,
+    http::StatusCode,
     response::{Html, IntoResponse},
 };
-use axum_extra::extract::{cookie::CookieJar, Form};
+use axum_extra::extract::Form;
+use chrono::NaiveDate;
 use maud::{html, Markup};
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Record Result page
-pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl IntoResponse {
-    let players = db::get_all_players(&state.db).await.unwrap_or_default();
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> impl IntoResponse {
+    let mut players = state.players.get(&state.db).await.unwrap_or_default();
+    mask_names(&mut players, shows_hidden_names(&jar, &state));
     let logged_in = is_authenticated(&jar, &state);
-    let auth = AuthState::new(state.auth_password.is_some(), logged_in);
+    let auth = AuthState::new(state.auth.is_some(), logged_in);
+    let per_team = state.settings.get().players_per_team;
 
     // Serialize players for JavaScript (include ID for participation tracking)
     let players_json: Vec<serde_json::Value> = players
@@ -30,87 +51,19 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
     let content = html! {
         h2 { "Record Match Result" }
 
-        // CSS for chip selector
-        style {
-            (maud::PreEscaped(r#"
-                .player-select { position: relative; }
-                .player-search { width: 100%; margin-bottom: 0.5rem; }
-                .player-dropdown {
-                    position: absolute;
-                    z-index: 100;
-                    width: 100%;
-                    max-height: 200px;
-                    overflow-y: auto;
-                    background: var(--pico-card-background-color);
-                    border: 1px solid var(--pico-muted-border-color);
-                    border-radius: var(--pico-border-radius);
-                    list-style: none;
-                    margin: 0;
-                    padding: 0;
-                    display: none;
-                }
-                .player-dropdown.open { display: block; }
-                .player-dropdown li {
-                    padding: 0.5rem 0.75rem;
-                    cursor: pointer;
-                }
-                .player-dropdown li:hover {
-                    background: var(--pico-primary-hover-background);
-                }
-                .selected-chips {
-                    display: flex;
-                    flex-wrap: wrap;
-                    gap: 0.5rem;
-                    min-height: 2.5rem;
-                    margin-top: 0.5rem;
-                }
-                .chip {
-                    display: inline-flex;
-                    align-items: center;
-                    gap: 0.25rem;
-                    padding: 0.25rem 0.5rem;
-                    background: var(--pico-primary-background);
-                    color: var(--pico-primary-inverse);
-                    border-radius: 1rem;
-                    font-size: 0.875rem;
-                }
-                .chip button {
-                    background: none;
-                    border: none;
-                    color: inherit;
-                    cursor: pointer;
-                    padding: 0 0.25rem;
-                    margin: 0;
-                    font-size: 1rem;
-                    line-height: 1;
-                }
-                .chip button:hover { opacity: 0.7; }
-                .participation-row {
-                    display: flex;
-                    justify-content: space-between;
-                    align-items: center;
-                    padding: 0.5rem;
-                    border-bottom: 1px solid var(--pico-muted-border-color);
-                }
-                .participation-row:last-child { border-bottom: none; }
-                .participation-row.partial {
-                    background: var(--pico-del-color);
-                    background: color-mix(in srgb, var(--pico-del-color) 15%, transparent);
-                }
-                .participation-row select {
-                    width: auto;
-                    margin: 0;
-                    padding: 0.25rem 0.5rem;
-                }
-            "#))
-        }
-
-        form id="record-form" hx-post="/api/record" hx-target="#result-display" {
+        form
+            id="record-form"
+            hx-post="/api/record"
+            hx-target="#result-display"
+            data-players=(players_json_str)
+            data-max-per-team=(per_team)
+            data-default-minutes=(DEFAULT_MATCH_MINUTES)
+        {
             // Team selection
             div class="team-grid" {
                 // Team A
                 fieldset {
-                    legend { "Team A (max " (MAX_PER_TEAM) ")" }
+                    legend { "Team A (max " (per_team) ")" }
                     div class="player-select" data-team="a" {
                         input
                             type="text"
@@ -120,11 +73,17 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
                         ul class="player-dropdown" {}
                         div class="selected-chips" {}
                     }
+                    label {
+                        "Captain"
+                        select name="captain_a" {
+                            option value="" { "No captain" }
+                        }
+                    }
                 }
 
                 // Team B
                 fieldset {
-                    legend { "Team B (max " (MAX_PER_TEAM) ")" }
+                    legend { "Team B (max " (per_team) ")" }
                     div class="player-select" data-team="b" {
                         input
                             type="text"
@@ -134,12 +93,30 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
                         ul class="player-dropdown" {}
                         div class="selected-chips" {}
                     }
+                    label {
+                        "Captain"
+                        select name="captain_b" {
+                            option value="" { "No captain" }
+                        }
+                    }
                 }
             }
 
             // Participation section (collapsed by default)
             details id="participation-section" {
                 summary { "Participation (expand if someone played partial)" }
+                p class="secondary" {
+                    "Enter a percentage, or the minute a player joined and/or left and the share is worked out from the match length."
+                }
+                label class="match-duration" {
+                    "Match length (minutes) "
+                    input
+                        type="number"
+                        name="duration"
+                        value=(DEFAULT_MATCH_MINUTES)
+                        min="1"
+                        max=(MAX_MATCH_MINUTES);
+                }
                 div id="participation-list" {
                     p class="secondary" { "Select players first" }
                 }
@@ -161,6 +138,43 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
                 }
             }
 
+            // Goal events (collapsed by default); rows are added by record.js
+            details id="goals-section" {
+                summary { "Goals (optional: who scored and when)" }
+                p class="secondary" {
+                    "Add each goal and the score is worked out from them. Pick a player from the other team for an own goal."
+                }
+                div id="goal-list" {}
+                div class="grid" {
+                    button type="button" class="secondary outline add-goal" data-team="a" { "+ Team A goal" }
+                    button type="button" class="secondary outline add-goal" data-team="b" { "+ Team B goal" }
+                }
+            }
+
+            label {
+                "Notes (optional)"
+                textarea
+                    name="notes"
+                    rows="2"
+                    maxlength=(MAX_NOTE_LENGTH)
+                    placeholder="Rained all game, Dave's screamer from halfway..." {}
+            }
+
+            details {
+                summary { "Special event (custom K factor)" }
+                label {
+                    "K multiplier: how much this match counts compared to a normal one"
+                    input
+                        type="number"
+                        name="k_multiplier"
+                        value="1"
+                        min="0.1"
+                        max=(MAX_K_MULTIPLIER)
+                        step="0.1";
+                }
+                small class="secondary" { "E.g. 0.5 for a charity kickabout, 2 for a tournament final." }
+            }
+
             button type="submit" disabled[!logged_in] hx-indicator="#submit-spinner" {
                 "Submit Result"
                 span id="submit-spinner" class="htmx-indicator spinner" {}
@@ -173,246 +187,52 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
         // Result display area
         div id="result-display" {}
 
-        // JavaScript for chip selector
-        script {
-            (maud::PreEscaped(format!(r#"
-                const allPlayers = {players_json};
-                const maxPerTeam = {max_per_team};
-                const selectedA = new Set();
-                const selectedB = new Set();
-
-                function getAvailable() {{
-                    return allPlayers.filter(p => !selectedA.has(p.name) && !selectedB.has(p.name));
-                }}
-
-                function renderDropdown(container, filter) {{
-                    const dropdown = container.querySelector('.player-dropdown');
-                    const team = container.dataset.team;
-                    const selected = team === 'a' ? selectedA : selectedB;
-
-                    if (selected.size >= maxPerTeam) {{
-                        dropdown.innerHTML = '<li style="color: var(--pico-muted-color)">Max players reached</li>';
-                        return;
-                    }}
-
-                    const available = getAvailable();
-                    const filtered = filter
-                        ? available.filter(p => p.name.toLowerCase().includes(filter.toLowerCase()))
-                        : available;
-
-                    if (filtered.length === 0) {{
-                        dropdown.innerHTML = '<li style="color: var(--pico-muted-color)">No players found</li>';
-                        return;
-                    }}
-
-                    dropdown.innerHTML = filtered.map(p =>
-                        `<li data-name="${{p.name}}" data-id="${{p.id}}">${{p.name}}</li>`
-                    ).join('');
-
-                    dropdown.querySelectorAll('li[data-name]').forEach(li => {{
-                        li.addEventListener('click', () => {{
-                            selectPlayer(container, li.dataset.name, parseInt(li.dataset.id));
-                        }});
-                    }});
-                }}
-
-                // Track participation values (default 1.0)
-                const participationValues = {{}};
-
-                function selectPlayer(container, name, playerId) {{
-                    const team = container.dataset.team;
-                    const selected = team === 'a' ? selectedA : selectedB;
-                    const inputName = team === 'a' ? 'team_a' : 'team_b';
-
-                    if (selected.size >= maxPerTeam) return;
-
-                    selected.add(name);
-                    participationValues[playerId] = 1.0;
-
-                    // Add simple chip (just name and remove button)
-                    const chipsContainer = container.querySelector('.selected-chips');
-                    const chip = document.createElement('span');
-                    chip.className = 'chip';
-                    chip.dataset.name = name;
-                    chip.dataset.playerId = playerId;
-                    chip.innerHTML = `${{name}}<button type="button">&times;</button>`;
-
-                    chip.querySelector('button').addEventListener('click', () => {{
-                        removePlayer(container, name, playerId);
-                    }});
-                    chipsContainer.appendChild(chip);
-
-                    // Add hidden input for team
-                    const hidden = document.createElement('input');
-                    hidden.type = 'hidden';
-                    hidden.name = inputName;
-                    hidden.value = name;
-                    hidden.dataset.playerName = name;
-                    container.appendChild(hidden);
-
-                    // Clear search and close dropdown
-                    const search = container.querySelector('.player-search');
-                    search.value = '';
-                    container.querySelector('.player-dropdown').classList.remove('open');
-
-                    // Update participation list
-                    renderParticipationList();
-                }}
-
-                function updateParticipation(playerId, value) {{
-                    participationValues[playerId] = parseFloat(value);
-                    // Update row styling and hidden input
-                    const row = document.querySelector(`.participation-row[data-player-id="${{playerId}}"]`);
-                    if (row) {{
-                        row.classList.toggle('partial', value !== '1.0');
-                        const hidden = row.querySelector('input[name="participation"]');
-                        if (hidden) hidden.value = `${{playerId}}=${{value}}`;
-                    }}
-                }}
-
-                function removePlayer(container, name, playerId) {{
-                    const team = container.dataset.team;
-                    const selected = team === 'a' ? selectedA : selectedB;
-
-                    selected.delete(name);
-                    delete participationValues[playerId];
-
-                    // Remove chip
-                    const chip = container.querySelector(`.chip[data-name="${{name}}"]`);
-                    if (chip) chip.remove();
-
-                    // Remove hidden input
-                    const hidden = container.querySelector(`input[data-player-name="${{name}}"]`);
-                    if (hidden) hidden.remove();
-
-                    // Update participation list
-                    renderParticipationList();
-                }}
-
-                function renderParticipationList() {{
-                    const list = document.getElementById('participation-list');
-                    const allSelected = [];
-
-                    // Gather all selected players
-                    document.querySelectorAll('.chip').forEach(chip => {{
-                        const playerId = parseInt(chip.dataset.playerId);
-                        const name = chip.dataset.name;
-                        const team = chip.closest('.player-select').dataset.team;
-                        allSelected.push({{ playerId, name, team }});
-                    }});
-
-                    if (allSelected.length === 0) {{
-                        list.innerHTML = '<p class="secondary">Select players first</p>';
-                        return;
-                    }}
-
-                    // Build participation list HTML
-                    let html = '';
-                    allSelected.forEach(({{ playerId, name, team }}) => {{
-                        const value = participationValues[playerId] || 1.0;
-                        const isPartial = value < 1.0;
-                        html += `
-                            <div class="participation-row${{isPartial ? ' partial' : ''}}" data-player-id="${{playerId}}">
-                                <span>${{name}} <small class="secondary">(Team ${{team.toUpperCase()}})</small></span>
-                                <select onchange="updateParticipation(${{playerId}}, this.value)">
-                                    <option value="1.0"${{value === 1.0 ? ' selected' : ''}}>100%</option>
-                                    <option value="0.75"${{value === 0.75 ? ' selected' : ''}}>75%</option>
-                                    <option value="0.5"${{value === 0.5 ? ' selected' : ''}}>50%</option>
-                                    <option value="0.25"${{value === 0.25 ? ' selected' : ''}}>25%</option>
-                                </select>
-                                <input type="hidden" name="participation" value="${{playerId}}=${{value}}">
-                            </div>
-                        `;
-                    }});
-                    list.innerHTML = html;
-                }}
-
-                // Setup event listeners
-                document.querySelectorAll('.player-select').forEach(container => {{
-                    const search = container.querySelector('.player-search');
-                    const dropdown = container.querySelector('.player-dropdown');
-
-                    search.addEventListener('focus', () => {{
-                        renderDropdown(container, search.value);
-                        dropdown.classList.add('open');
-                    }});
-
-                    search.addEventListener('input', () => {{
-                        renderDropdown(container, search.value);
-                        dropdown.classList.add('open');
-                    }});
-                }});
-
-                // Close dropdown when clicking outside
-                document.addEventListener('click', (e) => {{
-                    document.querySelectorAll('.player-select').forEach(container => {{
-                        if (!container.contains(e.target)) {{
-                            container.querySelector('.player-dropdown').classList.remove('open');
-                        }}
-                    }});
-                }});
-
-                // Load teams from localStorage (set by Teams page)
-                const savedTeams = localStorage.getItem('lastTeams');
-                if (savedTeams) {{
-                    try {{
-                        const {{ teamA, teamB }} = JSON.parse(savedTeams);
-                        const containerA = document.querySelector('.player-select[data-team="a"]');
-                        const containerB = document.querySelector('.player-select[data-team="b"]');
-                        
-                        // Map IDs to player objects and select them
-                        teamA.forEach(id => {{
-                            const player = allPlayers.find(p => p.id === id);
-                            if (player && containerA) {{
-                                selectPlayer(containerA, player.name, player.id);
-                            }}
-                        }});
-                        teamB.forEach(id => {{
-                            const player = allPlayers.find(p => p.id === id);
-                            if (player && containerB) {{
-                                selectPlayer(containerB, player.name, player.id);
-                            }}
-                        }});
-                    }} catch (e) {{
-                        console.error('Failed to load saved teams:', e);
-                    }}
-                }}
-            "#, players_json = players_json_str, max_per_team = MAX_PER_TEAM)))
-        }
+        // Chip selector and participation editor
+        script src=(asset("js/record.js")) {}
     };
 
-    Html(base("Record Result", "record", &auth, content).into_string())
+    Html(
+        base(
+            "Record Result",
+            "record",
+            &auth,
+            &state.settings.get().group_name,
+            content,
+        )
+        .into_string(),
+    )
 }
 
 /// Submit match result (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/record",
+    tag = "matches",
+    request_body(content = RecordForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Recorded result with Elo changes, an uneven-teams confirmation, or a note that it awaits confirmation", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 422, description = "Invalid teams, participation or K multiplier", content_type = "text/html"),
+        (status = 500, description = "Database error", content_type = "text/html")
+    )
+)]
 pub async fn submit_result(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    jar: AuthJar,
     Form(form): Form<RecordForm>,
 ) -> impl IntoResponse {
     if !is_authenticated(&jar, &state) {
-        return Html(
-            html! {
-                p class="error" { "Unauthorized. Please log in." }
-            }
-            .into_string(),
-        );
+        return error_fragment(StatusCode::UNAUTHORIZED, "Unauthorized. Please log in.");
     }
 
     let team_a_names = form.team_a.unwrap_or_default();
     let team_b_names = form.team_b.unwrap_or_default();
 
-    // Validate scores (0-50 range)
-    let score_a = form.score_a.clamp(0, 50);
-    let score_b = form.score_b.clamp(0, 50);
-
     // Validation
     if team_a_names.is_empty() || team_b_names.is_empty() {
-        return Html(
-            html! {
-                p class="error" { "Both teams must have players" }
-            }
-            .into_string(),
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Both teams must have players",
         );
     }
 
@@ -427,11 +247,9 @@ pub async fn submit_result(
             .map(|s| s.as_str())
             .collect::<Vec<_>>()
             .join(", ");
-        return Html(
-            html! {
-                p class="error" { "Players cannot be on both teams: " (overlap_str) }
-            }
-            .into_string(),
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!("Players cannot be on both teams: {}", overlap_str),
         );
     }
 
@@ -440,12 +258,7 @@ pub async fn submit_result(
         Ok(p) => p,
         Err(e) => {
             tracing::error!("Failed to load players: {}", e);
-            return Html(
-                html! {
-                    p class="error" { "Failed to load players" }
-                }
-                .into_string(),
-            );
+            return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load players");
         }
     };
 
@@ -463,11 +276,9 @@ pub async fn submit_result(
         .collect();
 
     if team_a.len() != team_a_names.len() || team_b.len() != team_b_names.len() {
-        return Html(
-            html! {
-                p class="error" { "Some players not found in database" }
-            }
-            .into_string(),
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Some players not found in database",
         );
     }
 
@@ -489,99 +300,291 @@ pub async fn submit_result(
                     "Yes, record match"
                 }
             }
-        }.into_string());
+        }.into_string()).into_response();
     }
 
-    // Build participation map from form data (format: "PlayerID=0.75")
-    let mut participation: HashMap<i32, f32> = HashMap::new();
-    if let Some(parts) = &form.participation {
-        for entry in parts {
-            if let Some((id_str, value)) = entry.split_once('=') {
-                if let (Ok(id), Ok(v)) = (id_str.parse::<i32>(), value.parse::<f32>()) {
-                    participation.insert(id, v.clamp(0.0, 1.0));
-                }
+    let (captain_a, captain_b) = match (
+        find_captain(form.captain_a.as_deref(), &team_a),
+        find_captain(form.captain_b.as_deref(), &team_b),
+    ) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, e),
+    };
+
+    // Goal events (format: "a:PlayerID:minute"); when given they decide the score
+    let mut goals: Vec<Goal> = match form.goals.iter().flatten().map(|e| parse_goal(e)).collect() {
+        Ok(goals) => goals,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+    let played = |id: &i32| team_a.iter().chain(&team_b).any(|p| p.id == *id);
+    if !goals.iter().filter_map(|g| g.scorer.as_ref()).all(played) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "A goal scorer must have played in the match",
+        );
+    }
+    // Goals without a minute go last
+    goals.sort_by_key(|g| (g.minute.is_none(), g.minute));
+    let (score_a, score_b) = if goals.is_empty() {
+        (form.score_a, form.score_b)
+    } else {
+        goal_score(&goals)
+    };
+    // Validate scores (0-50 range)
+    let score_a = score_a.clamp(0, MAX_SCORE);
+    let score_b = score_b.clamp(0, MAX_SCORE);
+
+    // Build participation map from form data (format: "PlayerID=0.17")
+    let mut participation: HashMap<i32, f32> = match form
+        .participation
+        .iter()
+        .flatten()
+        .map(|entry| parse_participation(entry))
+        .collect()
+    {
+        Ok(participation) => participation,
+        Err(e) => {
+            return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e);
+        }
+    };
+
+    // Arrival/departure minutes (format: "PlayerID=20-55") override percentages
+    let duration = form.duration.unwrap_or(DEFAULT_MATCH_MINUTES);
+    if !(1..=MAX_MATCH_MINUTES).contains(&duration) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!(
+                "Match length must be between 1 and {} minutes",
+                MAX_MATCH_MINUTES
+            ),
+        );
+    }
+    for entry in form.minutes.iter().flatten() {
+        match parse_minutes(entry, duration) {
+            Ok((id, share)) => {
+                participation.insert(id, share);
+            }
+            Err(e) => {
+                return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e);
             }
         }
     }
 
-    // Calculate Elo changes with handicap system (keyed by player ID)
-    let elo_changes = calculate_elo_changes(&team_a, &team_b, score_a, score_b, &participation);
-
-    // Build snapshot (keyed by player ID)
-    let snapshot: HashMap<i32, EloSnapshot> = elo_changes.clone();
-    let snapshot_json = serde_json::to_value(&snapshot).unwrap_or(json!({}));
+    // K multiplier for special events (blank = normal match)
+    let k_multiplier = form.k_multiplier.unwrap_or(1.0);
+    if !(k_multiplier > 0.0 && k_multiplier <= MAX_K_MULTIPLIER) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!(
+                "K multiplier must be above 0 and at most {}",
+                MAX_K_MULTIPLIER
+            ),
+        );
+    }
 
-    // Use a transaction to ensure all updates are atomic
-    let mut tx = match state.db.begin().await {
-        Ok(tx) => tx,
-        Err(e) => {
-            tracing::error!("Failed to start transaction: {}", e);
-            return Html(
+    let settings = state.settings.get();
+    let result = NewResult {
+        played_at: settings.today(),
+        score_a,
+        score_b,
+        participation,
+        k_multiplier,
+        notes: clean_note(form.notes.as_deref().unwrap_or_default()),
+        captain_a,
+        captain_b,
+        goals,
+        confirms: None,
+    };
+    if settings.confirm_results {
+        return match hold_result(&state, &team_a, &team_b, &result, SUBMITTED_BY_ADMIN).await {
+            Ok(_) => Html(
                 html! {
-                    p class="error" { "Database error" }
+                    p class="success-message" {
+                        "Saved for confirmation. It counts once someone who played confirms it on "
+                        a href="/history" { "History" } "."
+                    }
                 }
                 .into_string(),
-            );
-        }
+            )
+            .into_response(),
+            Err(e) => {
+                tracing::error!("Failed to save pending result: {}", e);
+                error_fragment(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to save match record",
+                )
+            }
+        };
+    }
+    let (saved_match, elo_changes, reached) =
+        match save_result(&state, &team_a, &team_b, result).await {
+            Ok(saved) => saved,
+            Err(e) => {
+                tracing::error!("Failed to save match: {}", e);
+                return error_fragment(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to save match record",
+                );
+            }
+        };
+
+    // Same players rebalanced on their new ratings, for next week
+    let next =
+        next_split(&team_a, &team_b, &elo_changes, &settings.balance_options()).map(|split| {
+            let names = team_names(settings.team_names, &split.team_a, &split.team_b);
+            render_next_teams(&split, &names)
+        });
+
+    // Render success with Elo changes
+    Html(render_result(&saved_match, &team_a, &team_b, &elo_changes, &reached, next).into_string())
+        .into_response()
+}
+
+/// Score and rating options for a result being saved
+pub struct NewResult {
+    /// Match date in the group timezone
+    pub played_at: NaiveDate,
+    pub score_a: i32,
+    pub score_b: i32,
+    /// Participation share by player ID (missing = played the whole match)
+    pub participation: HashMap<i32, f32>,
+    pub k_multiplier: f32,
+    pub notes: String,
+    /// Captain player IDs (optional)
+    pub captain_a: Option<i32>,
+    pub captain_b: Option<i32>,
+    /// Goal events, ordered by minute (empty = score only)
+    pub goals: Vec<Goal>,
+    /// Pending result this confirms; it is removed in the same transaction
+    pub confirms: Option<i32>,
+}
+
+/// Rate a validated result and save it with the players' new Elo in one
+/// transaction, then notify open pages and webhooks with any milestones. Shared by the Record
+/// form, the inbound `/api/hooks/record` and confirming a pending result
+/// (`RowNotFound` if someone else confirmed or discarded it first).
+pub async fn save_result(
+    state: &AppState,
+    team_a: &[Player],
+    team_b: &[Player],
+    result: NewResult,
+) -> Result<(Match, HashMap<i32, EloSnapshot>, Vec<PlayerMilestone>), sqlx::Error> {
+    // Earlier matches, for milestones and time away (none if they can't be loaded)
+    let earlier = db::get_all_matches(&state.db)
+        .await
+        .inspect_err(|e| tracing::warn!("Skipping milestones: {}", e))
+        .ok();
+
+    // Rate against Elo read under the ratings lock, so a replay running
+    // meanwhile finishes first instead of overwriting this result
+    let mut tx = state.db.begin().await?;
+    db::lock_ratings(&mut tx).await?;
+    let ids: Vec<i32> = team_a.iter().chain(team_b).map(|p| p.id).collect();
+    let elos = db::get_elos(&mut *tx, &ids).await?;
+    let current = |team: &[Player]| -> Vec<Player> {
+        team.iter()
+            .map(|p| Player {
+                elo: elos.get(&p.id).copied().unwrap_or(p.elo),
+                ..p.clone()
+            })
+            .collect()
     };
+    let (team_a_now, team_b_now) = (current(team_a), current(team_b));
+    let (fielded_a, fielded_b) = match &earlier {
+        Some(earlier) => (
+            away_on(&team_a_now, result.played_at, earlier),
+            away_on(&team_b_now, result.played_at, earlier),
+        ),
+        None => (team_a_now, team_b_now),
+    };
+
+    // Calculate Elo changes with handicap system (keyed by player ID)
+    let elo_changes = calculate_elo_changes(
+        &fielded_a,
+        &fielded_b,
+        result.score_a,
+        result.score_b,
+        &result.participation,
+        result.k_multiplier,
+        &state.settings.get().elo,
+    );
+    let snapshot_json = serde_json::to_value(&elo_changes).unwrap_or(json!({}));
+
+    if let Some(id) = result.confirms {
+        if !db::delete_pending_result(&mut *tx, id).await? {
+            return Err(sqlx::Error::RowNotFound);
+        }
+    }
 
-    // Update player Elos in database (applying participation for partial credit)
+    // Update player Elos (applying participation for partial credit)
     for player in team_a.iter().chain(team_b.iter()) {
         if let Some(change) = elo_changes.get(&player.id) {
-            // Apply participation: injured players get proportional Elo change
-            let effective_delta = change.delta * change.participation;
-            let new_elo = change.before + effective_delta;
-            if let Err(e) = db::update_player_elo(&mut *tx, player.id, new_elo).await {
-                tracing::error!("Failed to update Elo for {}: {}", player.name, e);
-                return Html(
-                    html! {
-                        p class="error" { "Failed to update player Elo" }
-                    }
-                    .into_string(),
-                );
-            }
+            let new_elo = change.before + change.delta * change.participation;
+            db::update_player_elo(&mut *tx, player.id, new_elo).await?;
         }
     }
 
-    // Save match record (with player IDs)
+    // Save match record (with player IDs, and themed names if they're on)
     let team_a_ids: Vec<i32> = team_a.iter().map(|p| p.id).collect();
     let team_b_ids: Vec<i32> = team_b.iter().map(|p| p.id).collect();
-    if let Err(e) = db::create_match(
-        &mut *tx,
-        &team_a_ids,
-        &team_b_ids,
-        score_a,
-        score_b,
-        snapshot_json,
-    )
-    .await
-    {
-        tracing::error!("Failed to save match: {}", e);
-        return Html(
-            html! {
-                p class="error" { "Failed to save match record" }
-            }
-            .into_string(),
-        );
-    }
+    let [team_name_a, team_name_b] = if state.settings.get().team_names {
+        themed_names(&team_a_ids, &team_b_ids).map(Some)
+    } else {
+        [None, None]
+    };
+    let new_match = NewMatch {
+        played_at: result.played_at,
+        team_a: team_a_ids,
+        team_b: team_b_ids,
+        score_a: result.score_a,
+        score_b: result.score_b,
+        elo_snapshot: snapshot_json,
+        notes: result.notes,
+        k_multiplier: result.k_multiplier,
+        captain_a: result.captain_a,
+        captain_b: result.captain_b,
+        team_name_a,
+        team_name_b,
+        goals: result.goals,
+    };
+    let saved_match = db::create_match(&mut *tx, &new_match).await?;
+    let fielded = [saved_match.team_a.as_slice(), &saved_match.team_b].concat();
+    db::refresh_player_stats(&mut tx, Some(&fielded)).await?;
+    tx.commit().await?;
+    let reached = earlier
+        .map(|earlier| milestones(&saved_match, &earlier))
+        .unwrap_or_default();
+
+    state.events.publish(Event::Results);
+    state.webhooks.send(
+        WebhookEvent::ResultRecorded,
+        result_json(&saved_match, team_a, team_b, &elo_changes, &reached),
+    );
+    Ok((saved_match, elo_changes, reached))
+}
 
-    // Commit the transaction
-    if let Err(e) = tx.commit().await {
-        tracing::error!("Failed to commit transaction: {}", e);
-        return Html(
-            html! {
-                p class="error" { "Failed to save changes" }
+/// A team with each player's days away counted back from `played_at` to their
+/// last match on or before it, as [`crate::replay::replay`] does, rather than
+/// from today (a confirmed or backdated result may be days old)
+fn away_on(team: &[Player], played_at: NaiveDate, earlier: &[Match]) -> Vec<Player> {
+    team.iter()
+        .map(|p| {
+            let last = earlier
+                .iter()
+                .filter(|m| m.voided_at.is_none() && m.played_at <= played_at)
+                .filter(|m| m.team_a.contains(&p.id) || m.team_b.contains(&p.id))
+                .map(|m| m.played_at)
+                .max();
+            Player {
+                days_away: last.map(|last| (played_at - last).num_days() as i32),
+                ..p.clone()
             }
-            .into_string(),
-        );
-    }
-
-    // Render success with Elo changes
-    Html(render_result(&team_a, &team_b, score_a, score_b, &elo_changes).into_string())
+        })
+        .collect()
 }
 
 /// Form data for recording a match
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct RecordForm {
     team_a: Option<Vec<String>>,
     team_b: Option<Vec<String>>,
@@ -589,25 +592,117 @@ pub struct RecordForm {
     score_b: i32,
     #[serde(default)]
     confirm_uneven: bool,
-    /// Participation percentages: "PlayerName=0.75" format
+    /// Participation shares: "PlayerID=0.17" format (1% to 100%)
     #[serde(default)]
     participation: Option<Vec<String>>,
+    /// Arrival/departure minutes: "PlayerID=20-55" format, either side may be blank
+    #[serde(default)]
+    minutes: Option<Vec<String>>,
+    /// Match length in minutes for the arrival/departure entries (default 60)
+    #[serde(default)]
+    duration: Option<u32>,
+    /// Free-text match notes
+    #[serde(default)]
+    notes: Option<String>,
+    /// K factor multiplier for special events (default 1, max 5)
+    #[serde(default)]
+    k_multiplier: Option<f32>,
+    /// Team A captain's name (blank = none)
+    #[serde(default)]
+    captain_a: Option<String>,
+    /// Team B captain's name (blank = none)
+    #[serde(default)]
+    captain_b: Option<String>,
+    /// Goal events: "a:PlayerID:minute" format, scorer and minute may be blank.
+    /// When given, they decide the score.
+    #[serde(default)]
+    goals: Option<Vec<String>>,
+}
+
+/// A captain's player ID from their name (blank = no captain). They have to
+/// be on the team they captain.
+fn find_captain(name: Option<&str>, team: &[Player]) -> Result<Option<i32>, &'static str> {
+    match name.map(str::trim).filter(|n| !n.is_empty()) {
+        None => Ok(None),
+        Some(name) => team
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| Some(p.id))
+            .ok_or("A captain must play for the team they captain"),
+    }
 }
 
-/// Render the match result with Elo changes
+/// The best split of a match's players on their ratings after it, so a
+/// group with the same lineup every week can reuse it
+fn next_split(
+    team_a: &[Player],
+    team_b: &[Player],
+    elo_changes: &HashMap<i32, EloSnapshot>,
+    options: &BalanceOptions,
+) -> Option<TeamSplit> {
+    let players: Vec<Player> = team_a
+        .iter()
+        .chain(team_b)
+        .map(|p| {
+            let mut player = p.clone();
+            if let Some(change) = elo_changes.get(&p.id) {
+                player.elo = change.before + change.delta * change.participation;
+            }
+            player
+        })
+        .collect();
+    balance_teams_with(&players, false, options)
+}
+
+/// Suggested teams for next week with a link to open them on Match Day
+fn render_next_teams(split: &TeamSplit, names: &[String; 2]) -> Markup {
+    let ids = |team: &[Player]| join_ids(&team.iter().map(|p| p.id).collect::<Vec<_>>());
+    let players = |team: &[Player]| {
+        team.iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    html! {
+        h4 { "Same players next week?" }
+        p class="secondary" {
+            "Balanced on the new ratings (Elo difference " (format!("{:.0}", split.elo_diff)) "):"
+        }
+        ul class="next-teams" {
+            li { strong { (names[0]) ": " } (players(&split.team_a)) }
+            li { strong { (names[1]) ": " } (players(&split.team_b)) }
+        }
+        p {
+            a href=(format!("/#a={}&b={}", ids(&split.team_a), ids(&split.team_b))) {
+                "Open these teams on Match Day →"
+            }
+        }
+    }
+}
+
+/// Render the match result with Elo changes, the suggested MVP and a
+/// suggested split for next week
 fn render_result(
+    m: &Match,
     team_a: &[Player],
     team_b: &[Player],
-    score_a: i32,
-    score_b: i32,
     elo_changes: &HashMap<i32, EloSnapshot>,
+    milestones: &[PlayerMilestone],
+    next: Option<Markup>,
 ) -> Markup {
+    let (score_a, score_b) = (m.score_a, m.score_b);
+    let player_names: HashMap<i32, String> = team_a
+        .iter()
+        .chain(team_b)
+        .map(|p| (p.id, p.name.clone()))
+        .collect();
+    let [name_a, name_b] = m.team_names();
     let result_text = if score_a > score_b {
-        "Team A wins!"
+        format!("{} wins!", name_a)
     } else if score_b > score_a {
-        "Team B wins!"
+        format!("{} wins!", name_b)
     } else {
-        "Draw!"
+        "Draw!".to_string()
     };
 
     html! {
@@ -620,7 +715,7 @@ fn render_result(
             div class="team-grid" {
                 // Team A changes
                 div {
-                    h4 { "Team A" }
+                    h4 { (name_a) }
                     ul class="player-list" {
                         @for player in team_a {
                             @if let Some(change) = elo_changes.get(&player.id) {
@@ -629,6 +724,8 @@ fn render_result(
                                     (player.name) ": "
                                     (render_elo_delta(effective_delta))
                                     (render_participation(change.participation))
+                                    (render_calibrating(change.calibrating))
+                                    (render_returning(change.returning))
                                     " (" (format!("{:.0}", change.before)) " → " (format!("{:.0}", change.before + effective_delta)) ")"
                                 }
                             }
@@ -638,7 +735,7 @@ fn render_result(
 
                 // Team B changes
                 div {
-                    h4 { "Team B" }
+                    h4 { (name_b) }
                     ul class="player-list" {
                         @for player in team_b {
                             @if let Some(change) = elo_changes.get(&player.id) {
@@ -647,6 +744,8 @@ fn render_result(
                                     (player.name) ": "
                                     (render_elo_delta(effective_delta))
                                     (render_participation(change.participation))
+                                    (render_calibrating(change.calibrating))
+                                    (render_returning(change.returning))
                                     " (" (format!("{:.0}", change.before)) " → " (format!("{:.0}", change.before + effective_delta)) ")"
                                 }
                             }
@@ -655,9 +754,66 @@ fn render_result(
                 }
             }
 
+            (render_goals(m, &player_names))
+
+            @let team_a_ids: Vec<i32> = team_a.iter().map(|p| p.id).collect();
+            (render_handicap(recorded_handicap(&team_a_ids, elo_changes)))
+            (render_keeper_handicap(recorded_keeper_handicap(&team_a_ids, elo_changes)))
+
+            @if !milestones.is_empty() {
+                h4 { "Milestones" }
+                ul class="milestones" {
+                    @for reached in milestones {
+                        li {
+                            strong { (player_names.get(&reached.player_id).map(|s| s.as_str()).unwrap_or("Unknown")) }
+                            ": " (reached.milestone.label())
+                        }
+                    }
+                }
+            }
+
+            (render_mvp(m, &player_names, true))
+
+            @if let Some(next) = next {
+                (next)
+            }
+
             footer {
                 a href="/history" { "View History →" }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_player(id: i32, elo: f32) -> Player {
+        Player {
+            id,
+            name: format!("Player {}", id),
+            elo,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_next_split_uses_new_ratings() {
+        let team_a = vec![make_player(1, 1300.0), make_player(2, 1100.0)];
+        let team_b = vec![make_player(3, 1200.0), make_player(4, 1200.0)];
+        // Player 2 had a big day and is now level with player 1
+        let elo_changes: HashMap<i32, EloSnapshot> =
+            serde_json::from_value(json!({"2": {"before": 1100.0, "delta": 200.0}})).unwrap();
+
+        let split = next_split(&team_a, &team_b, &elo_changes, &BalanceOptions::default()).unwrap();
+
+        let with_1 = if split.team_a.iter().any(|p| p.id == 1) {
+            &split.team_a
+        } else {
+            &split.team_b
+        };
+        assert!(!with_1.iter().any(|p| p.id == 2));
+        assert_eq!(split.elo_diff, 0.0);
+    }
+}
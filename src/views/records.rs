@@ -0,0 +1,107 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::records::{club_records, ClubRecords, MatchRecord};
+use crate::views::layout::{base, AuthState};
+use crate::{db, AppState};
+use axum::{extract::State, response::Html, response::IntoResponse};
+use maud::{html, Markup};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Records page - all-time club records from the match history
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> impl IntoResponse {
+    let matches = db::get_all_matches(&state.db).await.unwrap_or_default();
+    let mut players = state.players.get(&state.db).await.unwrap_or_default();
+    mask_names(&mut players, shows_hidden_names(&jar, &state));
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+
+    let content = html! {
+        h2 { "Club Records" }
+        (render_records(&club_records(&matches), &names))
+    };
+
+    Html(
+        base(
+            "Records",
+            "records",
+            &auth,
+            &state.settings.get().group_name,
+            content,
+        )
+        .into_string(),
+    )
+}
+
+/// Link to a record-setting match on History
+fn match_link(record: &MatchRecord) -> Markup {
+    html! {
+        a href=(format!("/history#match-{}", record.match_id)) {
+            (record.played_at.format("%Y-%m-%d"))
+        }
+    }
+}
+
+/// The records table, one row per record
+fn render_records(records: &ClubRecords, names: &HashMap<i32, &str>) -> Markup {
+    let name = |id: i32| names.get(&id).copied().unwrap_or("Former player");
+    if records.most_appearances.is_none() {
+        return html! { p class="secondary" { "No matches recorded yet." } };
+    }
+
+    html! {
+        p class="secondary" {
+            "Across every match that counts (voided ones don't). Ties go to whoever got there first."
+        }
+        div class="table-container" {
+            table {
+                thead { tr { th { "Record" } th { "Holder" } th { "Mark" } th { "When" } } }
+                tbody {
+                    @if let Some(streak) = &records.win_streak {
+                        tr {
+                            td { "Longest win streak" }
+                            td { (name(streak.player_id)) }
+                            td { (streak.wins) " wins" }
+                            td {
+                                (streak.from.format("%Y-%m-%d"))
+                                @if streak.to != streak.from { " to " (streak.to.format("%Y-%m-%d")) }
+                            }
+                        }
+                    }
+                    @if let Some(win) = &records.biggest_win {
+                        tr {
+                            td { "Biggest win" }
+                            td { "—" }
+                            td { "by " (win.value) " goals" }
+                            td { (match_link(win)) }
+                        }
+                    }
+                    @if let Some(peak) = &records.highest_elo {
+                        tr {
+                            td { "Highest Elo" }
+                            td { (peak.player_id.map_or("—", name)) }
+                            td { (format!("{:.0}", peak.value)) }
+                            td { (match_link(peak)) }
+                        }
+                    }
+                    @if let Some((id, played)) = records.most_appearances {
+                        tr {
+                            td { "Most appearances" }
+                            td { (name(id)) }
+                            td { (played) " matches" }
+                            td { "all time" }
+                        }
+                    }
+                    @if let Some(swing) = &records.biggest_swing {
+                        tr {
+                            td { "Biggest Elo swing" }
+                            td { (swing.player_id.map_or("—", name)) }
+                            td { (format!("{:+.0}", swing.value)) }
+                            td { (match_link(swing)) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,21 +1,71 @@
-use crate::auth::is_authenticated;
-use crate::models::{NewPlayer, UpdatePlayer, TAG_WEIGHTS};
-use crate::views::layout::{base, render_tags, AuthState};
+use crate::appearances::{appearances_by_season, seasons};
+use crate::auth::{is_authenticated, AuthJar};
+use crate::discipline::{load_active, status_label};
+use crate::injuries;
+use crate::kit::parse_jersey_number;
+use crate::models::{
+    clean_note, parse_attribute_level, AgeBand, Injury, NewPlayer, Player, PlayerFilter,
+    PlayerSort, PlayerStatus, Position, Suspension, Tag, UpdatePlayer, ELO_DEFAULT,
+    MAX_ATTRIBUTE_LEVEL, MAX_BULK_PLAYERS, MAX_JERSEY_NUMBER, MAX_NOTE_LENGTH, MIN_ATTRIBUTE_LEVEL,
+    TAG_WEIGHTS,
+};
+use crate::onboarding::{Answers, Experience};
+use crate::peer_ratings::{load_peer_scores, PeerScore};
+use crate::positions::{load_role_ratings, Role};
+use crate::privacy::{anonymized_name, mask_names, pseudonym, shows_hidden_names};
+use crate::similarity::similar_players;
+use crate::tiers::{load_tiers, Tier};
+use crate::uncertainty::{load_uncertainty, UNCERTAINTY_PRIOR};
+use crate::views::errors::error_fragment;
+use crate::views::join::render_registrations;
+use crate::views::layout::{base, render_position, render_sparkline, render_tags, AuthState};
+use crate::views::recalibration::recalibration_section;
+use crate::views::tag_suggestions::tag_suggestion_section;
+use crate::webhooks::{player_json, WebhookEvent};
 use crate::{db, AppState};
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{Html, IntoResponse},
 };
-use axum_extra::extract::{cookie::CookieJar, Form};
+use axum_extra::extract::Form;
+use chrono::NaiveDate;
 use maud::{html, Markup};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+/// Matches shown in each player's Elo trend sparkline
+const TREND_MATCHES: i64 = 10;
+
 /// Roster page - player management
-pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl IntoResponse {
-    let players = db::get_all_players(&state.db).await.unwrap_or_default();
+pub async fn page(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Query(query): Query<RosterQuery>,
+) -> impl IntoResponse {
+    let show_hidden = shows_hidden_names(&jar, &state);
+    let mut players = state.players.get(&state.db).await.unwrap_or_default();
+    mask_names(&mut players, show_hidden);
+    let filter = query.filter();
+    let matches = db::get_all_matches(&state.db).await.unwrap_or_default();
+    let appearances = appearances_by_season(&matches);
     let logged_in = is_authenticated(&jar, &state);
-    let auth = AuthState::new(state.auth_password.is_some(), logged_in);
+    let auth = AuthState::new(state.auth.is_some(), logged_in);
+    let registrations = if logged_in {
+        db::get_registrations(&state.db).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let tag_suggestions = if logged_in {
+        Some(tag_suggestion_section(&state).await)
+    } else {
+        None
+    };
+    let recalibrations = if logged_in {
+        Some(recalibration_section(&state).await)
+    } else {
+        None
+    };
 
     let content = html! {
         h2 { "Roster Management" }
@@ -23,24 +73,30 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
         // Add player form
         details open {
             summary { "Add New Player" }
-            form hx-post="/api/players" hx-target="#player-list" hx-swap="innerHTML" hx-on--after-request="if(event.detail.successful) this.reset()" {
+            form hx-post="/api/players" hx-target="#player-list" hx-swap="innerHTML" data-reset-on-success {
                 div class="grid" {
                     input type="text" name="name" placeholder="Player name" required disabled[!logged_in];
-                    input type="number" name="elo" placeholder="Starting Elo" value="1200" min="800" max="2000" disabled[!logged_in];
+                    input type="number" name="jersey_number" placeholder="Shirt # (optional)" min="0" max=(MAX_JERSEY_NUMBER) disabled[!logged_in];
+                    (render_position_select(None, !logged_in))
+                }
+                fieldset {
+                    legend { "Starting rating" }
+                    div class="grid" {
+                        (render_questionnaire_selects(false, !logged_in))
+                    }
+                    small class="secondary" {
+                        "Starting Elo comes from experience, fitness and position (see the Admin page for the formula). "
+                        "Leave experience blank to start at " (ELO_DEFAULT) "."
+                    }
                 }
                 fieldset {
                     legend { "Tags" }
-                    div class="checkbox-grid" {
-                        @for (tag, _) in TAG_WEIGHTS {
-                            label {
-                                input type="checkbox" name="tags" value=(tag) disabled[!logged_in];
-                                (tag)
-                            }
-                        }
-                        label {
-                            input type="checkbox" name="tags" value="GK" disabled[!logged_in];
-                            "GK"
-                        }
+                    (render_tag_checkboxes("", !logged_in))
+                }
+                fieldset {
+                    legend { "Attributes (optional)" }
+                    div class="grid" {
+                        (render_attribute_selects(&Player::default(), !logged_in))
                     }
                 }
                 button type="submit" disabled[!logged_in] hx-indicator="#add-spinner" {
@@ -53,46 +109,535 @@ pub async fn page(State(state): State<Arc<AppState>>, jar: CookieJar) -> impl In
             }
         }
 
+        // Bulk paste
+        details {
+            summary { "Paste Many Players" }
+            form hx-post="/api/players/bulk" hx-target="#player-list" hx-swap="innerHTML" {
+                label {
+                    "One player per line: name, then optional starting Elo and tags"
+                    textarea
+                        name="lines"
+                        rows="6"
+                        placeholder="Sam, 1250, DEF\nAlex, ATK RUNNER\nJo"
+                        required
+                        disabled[!logged_in] {}
+                }
+                button type="submit" class="secondary" disabled[!logged_in] { "Add Players" }
+            }
+        }
+
+        // Suspend / ban form
+        @if logged_in && !players.is_empty() {
+            details {
+                summary { "Suspend a Player" }
+                form hx-post="/api/suspensions" hx-target="#player-list" hx-swap="innerHTML" data-reset-on-success {
+                    div class="grid" {
+                        select name="player_id" aria-label="Player" required {
+                            option value="" { "Player..." }
+                            @for player in &players {
+                                option value=(player.id) { (player.name) }
+                            }
+                        }
+                        input type="text" name="reason" placeholder="Reason" maxlength="255";
+                        label {
+                            "Back on (leave empty for a ban)"
+                            input type="date" name="returns_on";
+                        }
+                    }
+                    button type="submit" class="secondary" { "Suspend" }
+                }
+            }
+            details {
+                summary { "Record an Injury" }
+                form hx-post="/api/injuries" hx-target="#player-list" hx-swap="innerHTML" data-reset-on-success {
+                    div class="grid" {
+                        select name="player_id" aria-label="Player" required {
+                            option value="" { "Player..." }
+                            @for player in &players {
+                                option value=(player.id) { (player.name) }
+                            }
+                        }
+                        input type="text" name="note" placeholder="e.g. Hamstring" maxlength="255";
+                        label {
+                            "Injured on"
+                            input type="date" name="started_on" value=(state.settings.get().today());
+                        }
+                        label {
+                            "Expected back (leave empty if unknown)"
+                            input type="date" name="expected_return";
+                        }
+                    }
+                    small class="secondary" {
+                        "Injured players can't be checked in until you mark them Fit Again on the Roster."
+                    }
+                    button type="submit" class="secondary" { "Record Injury" }
+                }
+            }
+        }
+
+        // Self-registrations from /join waiting for approval
+        @if logged_in {
+            (render_registrations(&registrations, &players, &state.settings.get().onboarding))
+            p class="secondary login-hint" {
+                "New players can sign themselves up at " a href="/join" { "/join" } "."
+            }
+        }
+
+        // Tag changes suggested by goals and clean sheets
+        @if let Some(section) = tag_suggestions {
+            (section)
+        }
+
+        // Starting Elos re-estimated from new players' first matches
+        @if let Some(section) = recalibrations {
+            (section)
+        }
+
         hr;
 
         // Player list
         h3 { "Current Roster (" (players.len()) " players)" }
-        div id="player-list" {
-            (render_player_list(&players, logged_in))
+        (render_filters(&filter))
+        // Invite links created from the row actions
+        div id="invite-result" {}
+        // A `refresh` event (e.g. a live results update) reloads the list with
+        // the current filters
+        div
+            id="player-list"
+            data-live="results"
+            hx-get="/fragments/players"
+            hx-trigger="refresh"
+            hx-include="#roster-filters, #roster-sort"
+            hx-disinherit="*"
+        {
+            (player_list_with(&state, logged_in, show_hidden, &filter).await)
         }
+
+        (render_season_appearances(&players, &appearances))
     };
 
-    Html(base("Roster", "roster", &auth, content).into_string())
+    Html(
+        base(
+            "Roster",
+            "roster",
+            &auth,
+            &state.settings.get().group_name,
+            content,
+        )
+        .into_string(),
+    )
+}
+
+/// Appearances per player per season (most appearances first)
+fn render_season_appearances(
+    players: &[Player],
+    appearances: &HashMap<i32, BTreeMap<i32, i32>>,
+) -> Markup {
+    let seasons = seasons(appearances);
+    if seasons.is_empty() {
+        return html! {};
+    }
+    let mut rows: Vec<&Player> = players
+        .iter()
+        .filter(|p| appearances.contains_key(&p.id))
+        .collect();
+    rows.sort_by_key(|p| std::cmp::Reverse(p.matches_played));
+
+    html! {
+        details {
+            summary { "Appearances by Season" }
+            div class="table-container" {
+                table {
+                    thead {
+                        tr {
+                            th { "Player" }
+                            @for season in &seasons { th { (season) } }
+                            th { "Total" }
+                        }
+                    }
+                    tbody {
+                        @for player in rows {
+                            @let by_season = &appearances[&player.id];
+                            tr {
+                                td { (player.name) }
+                                @for season in &seasons {
+                                    td { (by_season.get(season).copied().unwrap_or(0)) }
+                                }
+                                td { (player.matches_played) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Tag checkboxes, pre-checked from a comma-separated tag string
+fn render_tag_checkboxes(selected: &str, disabled: bool) -> Markup {
+    let selected: Vec<&str> = selected.split(',').map(str::trim).collect();
+    html! {
+        div class="checkbox-grid" {
+            @for tag in TAG_WEIGHTS.iter().map(|(tag, _)| *tag).chain(["GK"]) {
+                label {
+                    input type="checkbox" name="tags" value=(tag) checked[selected.contains(&tag)] disabled[disabled];
+                    (tag)
+                }
+            }
+        }
+    }
+}
+
+/// Position select, pre-selected from `current`
+pub(crate) fn render_position_select(current: Option<Position>, disabled: bool) -> Markup {
+    html! {
+        select name="position" aria-label="Position" disabled[disabled] {
+            option value="" selected[current.is_none()] { "Position: ?" }
+            @for position in Position::ALL {
+                option value=(position) selected[current == Some(position)] { (position.label()) }
+            }
+        }
+    }
+}
+
+/// Onboarding questionnaire selects: experience and fitness (with the
+/// position select, they decide a new player's starting Elo)
+pub(crate) fn render_questionnaire_selects(required: bool, disabled: bool) -> Markup {
+    html! {
+        select name="experience" aria-label="Experience" required[required] disabled[disabled] {
+            option value="" { "Experience: ?" }
+            @for experience in Experience::ALL {
+                option value=(experience.as_str()) { (experience.label()) }
+            }
+        }
+        select name="fitness" aria-label="Fitness" required[required] disabled[disabled] {
+            option value="" { "Fitness: ?" }
+            @for level in MIN_ATTRIBUTE_LEVEL..=MAX_ATTRIBUTE_LEVEL {
+                option value=(level) { "Fitness: " (level) "/5" }
+            }
+        }
+    }
+}
+
+/// Select inputs for stamina, pace and age band, pre-selected from `player`
+fn render_attribute_selects(player: &Player, disabled: bool) -> Markup {
+    let level_select = |name: &str, label: &str, current: Option<i32>| {
+        html! {
+            select name=(name) aria-label=(label) disabled[disabled] {
+                option value="" selected[current.is_none()] { (label) ": ?" }
+                @for level in MIN_ATTRIBUTE_LEVEL..=MAX_ATTRIBUTE_LEVEL {
+                    option value=(level) selected[current == Some(level)] { (label) ": " (level) }
+                }
+            }
+        }
+    };
+    let age_band = player.age_band();
+    html! {
+        (level_select("stamina", "Stamina", player.stamina))
+        (level_select("pace", "Pace", player.pace))
+        select name="age_band" aria-label="Age band" disabled[disabled] {
+            option value="" selected[age_band.is_none()] { "Age: ?" }
+            @for band in AgeBand::ALL {
+                option value=(band) selected[age_band == Some(band)] { "Age: " (band) }
+            }
+        }
+    }
+}
+
+/// Compact attribute summary for the roster table
+fn render_attributes(player: &Player) -> Markup {
+    html! {
+        span class="attributes" {
+            @if let Some(stamina) = player.stamina { "STA " (stamina) " " }
+            @if let Some(pace) = player.pace { "PAC " (pace) " " }
+            @if let Some(band) = player.age_band() { (band) }
+        }
+    }
+}
+
+/// Per-position ratings, e.g. "DEF 1210 ATK 1192"
+fn render_role_ratings(ratings: &HashMap<Role, f32>) -> Markup {
+    html! {
+        span class="attributes" {
+            @for role in Role::ALL {
+                @if let Some(elo) = ratings.get(&role) {
+                    span title=(format!("{} rating", role)) { (role.short()) " " (format!("{:.0}", elo)) " " }
+                }
+            }
+        }
+    }
+}
+
+/// Search box and tag/status filters above the roster table
+fn render_filters(filter: &PlayerFilter) -> Markup {
+    let selected_tag = filter.tag.map(|t| t.to_string()).unwrap_or_default();
+    html! {
+        form
+            id="roster-filters"
+            class="roster-filters"
+            action="/roster"
+            hx-get="/fragments/players"
+            hx-target="#player-list"
+            hx-swap="innerHTML"
+            hx-include="#roster-sort"
+            hx-trigger="input delay:300ms, change, submit"
+        {
+            input type="search" name="q" placeholder="Search by name" value=(filter.search) aria-label="Search by name";
+            select name="tag" aria-label="Filter by tag" {
+                option value="" { "Any tag" }
+                @for tag in TAG_WEIGHTS.iter().map(|(tag, _)| *tag).chain(["GK"]) {
+                    option value=(tag) selected[selected_tag == tag] { (tag) }
+                }
+            }
+            select name="status" aria-label="Filter by status" {
+                @for (status, label) in [
+                    (PlayerStatus::All, "Everyone"),
+                    (PlayerStatus::Available, "Available"),
+                    (PlayerStatus::Suspended, "Suspended"),
+                    (PlayerStatus::Injured, "Injured"),
+                ] {
+                    option value=(status.as_str()) selected[filter.status == status] { (label) }
+                }
+            }
+        }
+    }
+}
+
+/// Sortable column header: clicking sorts by `sort`, or flips the direction
+/// if the table is already sorted by it
+fn render_sort_header(label: &str, sort: PlayerSort, filter: &PlayerFilter) -> Markup {
+    let active = filter.sort == sort;
+    let descending = if active {
+        !filter.descending
+    } else {
+        sort.default_descending()
+    };
+    let url = format!(
+        "/fragments/players?sort={}&dir={}",
+        sort.as_str(),
+        if descending { "desc" } else { "asc" }
+    );
+    html! {
+        th aria-sort=[active.then_some(if filter.descending { "descending" } else { "ascending" })] {
+            a href="#" class="sort-link" hx-get=(url) hx-target="#player-list" hx-swap="innerHTML" hx-include="#roster-filters" {
+                (label)
+                @if active {
+                    @if filter.descending { " ▼" } @else { " ▲" }
+                }
+            }
+        }
+    }
+}
+
+/// Load and render the player list with the default sort (after mutations)
+async fn player_list(state: &AppState, logged_in: bool) -> Markup {
+    player_list_with(state, logged_in, true, &PlayerFilter::default()).await
+}
+
+/// Load and render the player list for a search/filter/sort. Hidden names
+/// are masked unless `show_hidden`.
+pub(crate) async fn player_list_with(
+    state: &AppState,
+    logged_in: bool,
+    show_hidden: bool,
+    filter: &PlayerFilter,
+) -> Markup {
+    let mut players = db::search_players(&state.db, filter, state.settings.get().today())
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("Failed to search players: {}", e);
+            Vec::new()
+        });
+    mask_names(&mut players, show_hidden);
+    // The search ran on real names; don't let it reveal who's behind a pseudonym
+    let search = filter.search.to_lowercase();
+    players.retain(|p| !p.hide_name || show_hidden || p.name.to_lowercase().contains(&search));
+    let today = state.settings.get().today();
+    let suspensions = load_active(&state.db, today).await;
+    let injuries = injuries::load_active(&state.db).await;
+    let role_ratings = load_role_ratings(&state.db).await;
+    let uncertainty = load_uncertainty(&state.db).await;
+    let trends = db::get_recent_elo_history(&state.db, TREND_MATCHES)
+        .await
+        .unwrap_or_default();
+    let tiers = load_tiers(state).await;
+    let peer = load_peer_scores(state).await;
+    // Private notes never leave the server for visitors
+    let notes = if logged_in {
+        db::get_player_notes(&state.db).await.unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
+    let details = RosterDetails {
+        today,
+        suspensions,
+        injuries,
+        role_ratings,
+        uncertainty,
+        trends,
+        tiers,
+        peer,
+        notes,
+    };
+    render_player_list(&players, &details, filter, logged_in)
+}
+
+/// Per-player extras shown alongside the roster, keyed by player ID
+struct RosterDetails {
+    /// The group's local date, for status labels
+    today: NaiveDate,
+    suspensions: HashMap<i32, Suspension>,
+    injuries: HashMap<i32, Injury>,
+    role_ratings: HashMap<i32, HashMap<Role, f32>>,
+    uncertainty: HashMap<i32, f32>,
+    trends: HashMap<i32, Vec<f32>>,
+    /// Skill tier of everyone who has played (see `tiers::cluster`)
+    tiers: HashMap<i32, Tier>,
+    /// Peer survey averages of players with enough raters
+    peer: HashMap<i32, PeerScore>,
+    /// Private admin notes (empty unless logged in)
+    notes: HashMap<i32, String>,
 }
 
 /// Render the player list (used for full page and htmx updates)
-fn render_player_list(players: &[crate::models::Player], logged_in: bool) -> Markup {
+fn render_player_list(
+    players: &[Player],
+    details: &RosterDetails,
+    filter: &PlayerFilter,
+    logged_in: bool,
+) -> Markup {
+    // Current sort, picked up by the filter form so filtering keeps it
+    let sort_state = html! {
+        div id="roster-sort" hidden {
+            input type="hidden" name="sort" value=(filter.sort.as_str());
+            input type="hidden" name="dir" value=(if filter.descending { "desc" } else { "asc" });
+        }
+    };
+
     if players.is_empty() {
         return html! {
-            p { "No players yet. Add your first player above!" }
+            (sort_state)
+            @if filter.is_filtered() {
+                p { "No players match these filters." }
+            } @else {
+                p { "No players yet. Add your first player above!" }
+            }
         };
     }
 
     html! {
+        (sort_state)
         div class="table-container" {
             table {
                 thead {
                     tr {
-                        th { "Name" }
-                        th { "Elo" }
+                        (render_sort_header("Name", PlayerSort::Name, filter))
+                        (render_sort_header("#", PlayerSort::Number, filter))
+                        (render_sort_header("Elo", PlayerSort::Elo, filter))
+                        th { "Trend" }
+                        th { "Pos" }
                         th { "Tags" }
-                        th { "Matches" }
+                        th { "Attributes" }
+                        (render_sort_header("Matches", PlayerSort::Matches, filter))
                         th { "Actions" }
                     }
                 }
                 tbody {
                     @for player in players {
-                        tr id=(format!("player-{}", player.id)) {
-                            td { (player.name) }
-                            td { (format!("{:.0}", player.elo)) }
+                        @let suspension = details.suspensions.get(&player.id);
+                        @let injury = details.injuries.get(&player.id);
+                        tr id=(format!("player-{}", player.id)) class=[(suspension.is_some() || injury.is_some()).then_some("suspended")] {
+                            td {
+                                (player.name)
+                                @if let Some(suspension) = suspension {
+                                    " "
+                                    span class="tag suspended-tag" title=(suspension.reason) { (status_label(suspension)) }
+                                }
+                                @if let Some(injury) = injury {
+                                    " "
+                                    span class="tag injured-tag" title=(injury.note) { (injuries::status_label(injury, details.today)) }
+                                }
+                                @if logged_in && player.hide_name {
+                                    " "
+                                    span class="tag" title=(format!("Visitors see \"{}\"", pseudonym(player.id))) { "Hidden" }
+                                }
+                                @if let Some(note) = details.notes.get(&player.id) {
+                                    br;
+                                    small class="admin-note" title="Private note, only admins see it" { (note) }
+                                }
+                            }
+                            td { (player.jersey_number.map(|n| n.to_string()).unwrap_or_default()) }
+                            td {
+                                (format!("{:.0}", player.elo))
+                                @let band = details.uncertainty.get(&player.id).copied().unwrap_or(UNCERTAINTY_PRIOR);
+                                " "
+                                small class="secondary" title=(format!("Rating uncertainty after {} matches", player.matches_played)) {
+                                    (format!("± {:.0}", band))
+                                }
+                                @if let Some(tier) = details.tiers.get(&player.id) {
+                                    " "
+                                    span class=(tier.css_class()) title="Skill tier from Elo and recent form" { (tier.label()) }
+                                }
+                                @if let Some(peer) = details.peer.get(&player.id) {
+                                    br;
+                                    small class="secondary" title=(format!(
+                                        "Peer rating from {} teammates: skill {:.1}, stamina {:.1}, pace {:.1}",
+                                        peer.raters, peer.skill, peer.stamina, peer.pace
+                                    )) {
+                                        (format!("Peer {:.1}/5", peer.overall()))
+                                    }
+                                }
+                                @if let Some(ratings) = details.role_ratings.get(&player.id) {
+                                    br;
+                                    (render_role_ratings(ratings))
+                                }
+                            }
+                            td { (render_sparkline(details.trends.get(&player.id).map(Vec::as_slice).unwrap_or_default())) }
+                            td { (render_position(player.position())) }
                             td { (render_tags(&player.tags)) }
+                            td { (render_attributes(player)) }
                             td { (player.matches_played) }
-                            td {
+                            td class="row-actions" {
+                                button
+                                    class="secondary outline"
+                                    hx-get=(format!("/api/players/{}/edit", player.id))
+                                    hx-target=(format!("#player-{}", player.id))
+                                    hx-swap="outerHTML"
+                                    disabled[!logged_in]
+                                {
+                                    "Edit"
+                                }
+                                @if let Some(suspension) = suspension {
+                                    button
+                                        class="secondary outline"
+                                        hx-post=(format!("/api/suspensions/{}/lift", suspension.id))
+                                        hx-target="#player-list"
+                                        hx-swap="innerHTML"
+                                        disabled[!logged_in]
+                                    {
+                                        "Lift"
+                                    }
+                                }
+                                @if injury.is_some() {
+                                    button
+                                        class="secondary outline"
+                                        hx-post=(format!("/api/players/{}/injury/clear", player.id))
+                                        hx-target="#player-list"
+                                        hx-swap="innerHTML"
+                                        disabled[!logged_in]
+                                    {
+                                        "Fit Again"
+                                    }
+                                }
+                                @if logged_in {
+                                    button
+                                        class="secondary outline"
+                                        hx-post=(format!("/api/players/{}/invite", player.id))
+                                        hx-target="#invite-result"
+                                    {
+                                        "Invite"
+                                    }
+                                }
                                 button
                                     class="secondary outline"
                                     hx-delete=(format!("/api/players/{}", player.id))
@@ -112,32 +657,230 @@ fn render_player_list(players: &[crate::models::Player], logged_in: bool) -> Mar
     }
 }
 
+/// Render a roster row as an inline edit form
+fn render_edit_row(player: &Player, notes: &str) -> Markup {
+    let row_id = format!("player-{}", player.id);
+    html! {
+        tr id=(row_id) class="edit-row" {
+            td {
+                (player.name)
+                textarea
+                    name="admin_notes"
+                    rows="2"
+                    maxlength=(MAX_NOTE_LENGTH)
+                    placeholder="Private note (admins only)"
+                    aria-label="Private note"
+                { (notes) }
+                label {
+                    input type="checkbox" name="hide_name" value="true" checked[player.hide_name];
+                    "Hide name from visitors"
+                }
+            }
+            td {
+                input type="number" name="jersey_number" min="0" max=(MAX_JERSEY_NUMBER)
+                    value=[player.jersey_number];
+            }
+            td {
+                input type="number" name="elo" min="800" max="2000" step="any"
+                    value=(format!("{:.0}", player.elo)) required;
+            }
+            td {}
+            td { (render_position_select(player.position(), false)) }
+            td { (render_tag_checkboxes(&player.tags, false)) }
+            td { (render_attribute_selects(player, false)) }
+            td { (player.matches_played) }
+            td class="row-actions" {
+                button
+                    hx-put=(format!("/api/players/{}", player.id))
+                    hx-include=(format!("#{}", row_id))
+                    hx-target="#player-list"
+                    hx-swap="innerHTML"
+                {
+                    "Save"
+                }
+                button class="secondary outline" hx-get="/fragments/players" hx-target="#player-list" hx-swap="innerHTML" {
+                    "Cancel"
+                }
+                a href=(format!("/api/players/{}/data", player.id)) download role="button" class="secondary outline" {
+                    "Export Data"
+                }
+                button
+                    class="secondary outline"
+                    hx-post=(format!("/api/players/{}/anonymize", player.id))
+                    hx-confirm=(format!("Anonymize {}? Their name becomes \"{}\" and their profile, account, notes, RSVPs and injuries are deleted. Matches and ratings stay. This can't be undone.", player.name, anonymized_name(player.id)))
+                {
+                    "Anonymize"
+                }
+            }
+        }
+    }
+}
+
+/// Query params for searching, filtering and sorting the roster
+#[derive(Debug, Default, serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RosterQuery {
+    /// Case-insensitive part of a name
+    q: Option<String>,
+    /// Only players with this tag (e.g. DEF)
+    tag: Option<String>,
+    /// "all" (default), "available", "suspended" or "injured"
+    status: Option<String>,
+    /// Sort column: "name", "number", "elo" (default) or "matches"
+    sort: Option<String>,
+    /// "asc" or "desc" (default: descending for Elo and matches, else ascending)
+    dir: Option<String>,
+}
+
+impl RosterQuery {
+    pub(crate) fn filter(&self) -> PlayerFilter {
+        let sort = self
+            .sort
+            .as_deref()
+            .and_then(PlayerSort::from_str)
+            .unwrap_or_default();
+        let descending = match self.dir.as_deref() {
+            Some("asc") => false,
+            Some("desc") => true,
+            _ => sort.default_descending(),
+        };
+        PlayerFilter {
+            search: self.q.as_deref().unwrap_or_default().trim().to_string(),
+            tag: self.tag.as_deref().and_then(Tag::from_str),
+            status: PlayerStatus::from_str(self.status.as_deref().unwrap_or_default()),
+            sort,
+            descending,
+        }
+    }
+}
+
+/// Inline edit form for one roster row (htmx endpoint)
+#[utoipa::path(
+    get,
+    path = "/api/players/{id}/edit",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    responses(
+        (status = 200, description = "Editable table row", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Player not found")
+    )
+)]
+pub async fn edit_player(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    let notes = db::get_player_notes(&state.db).await.unwrap_or_default();
+    match db::get_players_by_ids(&state.db, &[id]).await {
+        Ok(players) => match players.first() {
+            Some(player) => {
+                let notes = notes.get(&id).map(String::as_str).unwrap_or_default();
+                Html(render_edit_row(player, notes).into_string()).into_response()
+            }
+            None => error_fragment(StatusCode::NOT_FOUND, "Player not found"),
+        },
+        Err(e) => {
+            tracing::error!("Failed to load player: {}", e);
+            error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load player")
+        }
+    }
+}
+
 /// Create a new player (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/players",
+    tag = "players",
+    request_body(content = NewPlayerForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated player list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
 pub async fn create_player(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    jar: AuthJar,
     Form(form): Form<NewPlayerForm>,
 ) -> impl IntoResponse {
     if !is_authenticated(&jar, &state) {
         return crate::auth::unauthorized().into_response();
     }
 
+    let jersey_number = match parse_jersey_number(form.jersey_number.as_deref()) {
+        Ok(n) => n,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+
+    // Warn about likely typos of existing names unless the user already confirmed
+    if form.allow_similar.is_none() {
+        let existing = db::get_all_players(&state.db).await.unwrap_or_default();
+        let similar = similar_players(&form.name, &existing);
+        if !similar.is_empty() {
+            let names: Vec<&str> = similar.iter().map(|p| p.name.as_str()).collect();
+            let list = player_list(&state, true).await;
+            return Html(render_similar_warning(form, &names, list).into_string()).into_response();
+        }
+    }
+
     // Combine tags from checkboxes
     let tags = form.tags.unwrap_or_default().join(",");
 
+    // The questionnaire decides the starting Elo unless one is given outright
+    let fitness = parse_attribute_level(form.fitness.as_deref());
+    let formula = state.settings.get().onboarding;
+    let answers = form
+        .experience
+        .as_deref()
+        .and_then(Experience::from_str)
+        .map(|experience| Answers {
+            experience,
+            position: form.position.as_deref().and_then(Position::from_str),
+            fitness,
+        })
+        .filter(|_| form.elo.is_none());
+
     let new_player = NewPlayer {
         name: form.name,
-        elo: form.elo,
+        elo: form.elo.or(answers.map(|a| a.starting_elo(&formula))),
         tags: Some(tags),
+        jersey_number,
+        stamina: parse_attribute_level(form.stamina.as_deref()).or(fitness),
+        pace: parse_attribute_level(form.pace.as_deref()),
+        age_band: parse_age_band(form.age_band.as_deref()),
+        position: parse_position(form.position.as_deref()),
     };
 
-    match db::create_player(&state.db, &new_player).await {
+    let result: Result<Player, sqlx::Error> = async {
+        let mut tx = state.db.begin().await?;
+        let player = db::create_player(&mut *tx, &new_player).await?;
+        if let Some(answers) = &answers {
+            db::save_onboarding(&mut *tx, player.id, answers, &formula).await?;
+        }
+        tx.commit().await?;
+        Ok(player)
+    }
+    .await;
+
+    match result {
         Ok(player) => {
-            let players = db::get_all_players(&state.db).await.unwrap_or_default();
+            state
+                .webhooks
+                .send(WebhookEvent::PlayerCreated, player_json(&player));
+            let list = player_list(&state, true).await;
             Html(
                 html! {
-                    p class="success-message" { "Added " (player.name) "!" }
-                    (render_player_list(&players, true))
+                    p class="success-message" {
+                        "Added " (player.name) "!"
+                        @if let Some(answers) = &answers {
+                            " Starting Elo: " (answers.explain(&formula)) "."
+                        }
+                    }
+                    (list)
                 }
                 .into_string(),
             )
@@ -145,53 +888,281 @@ pub async fn create_player(
         }
         Err(e) => {
             tracing::error!("Failed to create player: {}", e);
-            Html(
-                html! {
-                    p class="error" { "Failed to create player: name may already exist" }
-                }
-                .into_string(),
+            error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create player: name may already exist",
             )
-            .into_response()
         }
     }
 }
 
+/// Create many players from pasted lines (htmx endpoint).
+/// Valid lines are added; the rest are reported by line number.
+#[utoipa::path(
+    post,
+    path = "/api/players/bulk",
+    tag = "players",
+    request_body(content = BulkPlayersForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Per-line results and updated player list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
+pub async fn create_players_bulk(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<BulkPlayersForm>,
+) -> impl IntoResponse {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    let lines: Vec<(usize, &str)> = form
+        .lines
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    if lines.len() > MAX_BULK_PLAYERS {
+        let message = format!(
+            "Too many lines: paste at most {} players at once",
+            MAX_BULK_PLAYERS
+        );
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &message);
+    }
+
+    // Lowercased name -> where it came from, to catch duplicates before hitting the DB
+    let mut seen: HashMap<String, Option<usize>> = db::get_all_players(&state.db)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| (p.name.to_lowercase(), None))
+        .collect();
+    let mut added = Vec::new();
+    let mut errors: Vec<(usize, String)> = Vec::new();
+
+    for (number, line) in lines {
+        let new_player = match NewPlayer::parse_line(line) {
+            Ok(p) => p,
+            Err(e) => {
+                errors.push((number, e));
+                continue;
+            }
+        };
+        if let Some(origin) = seen.get(&new_player.name.to_lowercase()) {
+            let message = match origin {
+                Some(earlier) => format!("{} is already on line {}", new_player.name, earlier),
+                None => format!("{} already exists", new_player.name),
+            };
+            errors.push((number, message));
+            continue;
+        }
+
+        match db::create_player(&state.db, &new_player).await {
+            Ok(player) => {
+                state
+                    .webhooks
+                    .send(WebhookEvent::PlayerCreated, player_json(&player));
+                seen.insert(player.name.to_lowercase(), Some(number));
+                added.push(player.name);
+            }
+            Err(e) => {
+                tracing::error!("Failed to create player: {}", e);
+                errors.push((number, format!("could not add {}", new_player.name)));
+            }
+        }
+    }
+
+    let list = player_list(&state, true).await;
+    Html(
+        html! {
+            @if !added.is_empty() {
+                p class="success-message" { "Added " (added.len()) " players: " (added.join(", ")) }
+            }
+            @if !errors.is_empty() {
+                ul class="error" {
+                    @for (number, error) in &errors {
+                        li { "Line " (number) ": " (error) }
+                    }
+                }
+            }
+            (list)
+        }
+        .into_string(),
+    )
+    .into_response()
+}
+
+/// Warning that a new player's name looks like an existing one, with a
+/// button that re-submits the same form with the check overridden
+fn render_similar_warning(form: NewPlayerForm, similar: &[&str], list: Markup) -> Markup {
+    let name = form.name.clone();
+    let retry = NewPlayerForm {
+        allow_similar: Some("true".to_string()),
+        ..form
+    };
+    let values = serde_json::to_string(&retry).unwrap_or_default();
+
+    html! {
+        div class="similar-warning" {
+            p class="error" {
+                "\"" (name) "\" looks like " (similar.join(", ")) ", who "
+                @if similar.len() == 1 { "is" } @else { "are" }
+                " already on the roster. Add anyway?"
+            }
+            button
+                class="secondary outline"
+                hx-post="/api/players"
+                hx-vals=(values)
+                hx-target="#player-list"
+                hx-swap="innerHTML"
+            {
+                "Add " (name) " anyway"
+            }
+        }
+        (list)
+    }
+}
+
+/// Form data for pasting many players
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct BulkPlayersForm {
+    /// One player per line: "Name", "Name, 1250" or "Name, 1250, DEF ATK"
+    lines: String,
+}
+
 /// Form data for creating a player (with multiple tags as checkboxes)
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, serde::Serialize, utoipa::ToSchema)]
 pub struct NewPlayerForm {
     name: String,
+    /// Starting Elo (overrides the questionnaire; empty = from the answers)
     elo: Option<f32>,
+    /// Questionnaire: "new", "casual", "regular" or "club" (empty = start at 1200)
+    experience: Option<String>,
+    /// Questionnaire: fitness 1-5, also used as stamina if that's empty
+    fitness: Option<String>,
+    tags: Option<Vec<String>>,
+    /// Preferred shirt number (0-99, empty for none)
+    jersey_number: Option<String>,
+    /// Stamina 1-5 (empty for unknown)
+    stamina: Option<String>,
+    /// Pace 1-5 (empty for unknown)
+    pace: Option<String>,
+    /// Age band: U25, 25-34, 35-44 or 45+ (empty for unknown)
+    age_band: Option<String>,
+    /// Position: GK, DEF, MID or ATT (empty for none)
+    position: Option<String>,
+    /// Skip the similar-name check when present
+    allow_similar: Option<String>,
+}
+
+/// Normalise an age band form field (empty or unknown = None)
+fn parse_age_band(value: Option<&str>) -> Option<String> {
+    value
+        .and_then(AgeBand::from_str)
+        .map(|band| band.to_string())
+}
+
+/// Normalise a position form field (empty or unknown = None)
+fn parse_position(value: Option<&str>) -> Option<String> {
+    value
+        .and_then(Position::from_str)
+        .map(|position| position.to_string())
+}
+
+/// Form data for updating a player (tags as checkboxes)
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct UpdatePlayerForm {
+    elo: f32,
     tags: Option<Vec<String>>,
+    /// Preferred shirt number (0-99, empty for none)
+    jersey_number: Option<String>,
+    /// Stamina 1-5 (empty for unknown)
+    stamina: Option<String>,
+    /// Pace 1-5 (empty for unknown)
+    pace: Option<String>,
+    /// Age band: U25, 25-34, 35-44 or 45+ (empty for unknown)
+    age_band: Option<String>,
+    /// Position: GK, DEF, MID or ATT (empty for none)
+    position: Option<String>,
+    /// Private admin note (empty to clear)
+    #[serde(default)]
+    admin_notes: String,
+    /// "true" to show a pseudonym instead of the name to visitors
+    hide_name: Option<String>,
 }
 
 /// Update a player (htmx endpoint)
+#[utoipa::path(
+    put,
+    path = "/api/players/{id}",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    request_body(content = UpdatePlayerForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated player list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Player not found"),
+        (status = 422, description = "Invalid jersey number")
+    )
+)]
 pub async fn update_player(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    jar: AuthJar,
     Path(id): Path<i32>,
-    Form(form): Form<UpdatePlayer>,
+    Form(form): Form<UpdatePlayerForm>,
 ) -> impl IntoResponse {
     if !is_authenticated(&jar, &state) {
         return crate::auth::unauthorized().into_response();
     }
 
-    match db::update_player(&state.db, id, &form).await {
-        Ok(Some(_)) => {
-            let players = db::get_all_players(&state.db).await.unwrap_or_default();
-            Html(render_player_list(&players, true).into_string()).into_response()
-        }
-        Ok(None) => (StatusCode::NOT_FOUND, "Player not found").into_response(),
+    let jersey_number = match parse_jersey_number(form.jersey_number.as_deref()) {
+        Ok(n) => n,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+    let update = UpdatePlayer {
+        elo: form.elo,
+        tags: form.tags.unwrap_or_default().join(","),
+        jersey_number,
+        stamina: parse_attribute_level(form.stamina.as_deref()),
+        pace: parse_attribute_level(form.pace.as_deref()),
+        age_band: parse_age_band(form.age_band.as_deref()),
+        position: parse_position(form.position.as_deref()),
+        hide_name: form.hide_name.is_some(),
+    };
+
+    let result = match db::update_player(&state.db, id, &update).await {
+        Ok(Some(player)) => db::set_player_notes(&state.db, id, &clean_note(&form.admin_notes))
+            .await
+            .map(|_| Some(player)),
+        other => other,
+    };
+    match result {
+        Ok(Some(_)) => Html(player_list(&state, true).await.into_string()).into_response(),
+        Ok(None) => error_fragment(StatusCode::NOT_FOUND, "Player not found"),
         Err(e) => {
             tracing::error!("Failed to update player: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update player").into_response()
+            error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update player")
         }
     }
 }
 
 /// Delete a player (htmx endpoint)
+#[utoipa::path(
+    delete,
+    path = "/api/players/{id}",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    responses(
+        (status = 200, description = "Updated player list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Player not found")
+    )
+)]
 pub async fn delete_player(
     State(state): State<Arc<AppState>>,
-    jar: CookieJar,
+    jar: AuthJar,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
     if !is_authenticated(&jar, &state) {
@@ -199,14 +1170,199 @@ pub async fn delete_player(
     }
 
     match db::delete_player(&state.db, id).await {
-        Ok(true) => {
-            let players = db::get_all_players(&state.db).await.unwrap_or_default();
-            Html(render_player_list(&players, true).into_string()).into_response()
-        }
-        Ok(false) => (StatusCode::NOT_FOUND, "Player not found").into_response(),
+        Ok(true) => Html(player_list(&state, true).await.into_string()).into_response(),
+        Ok(false) => error_fragment(StatusCode::NOT_FOUND, "Player not found"),
         Err(e) => {
             tracing::error!("Failed to delete player: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete player").into_response()
+            error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete player")
+        }
+    }
+}
+
+/// Form data for suspending a player
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct SuspensionForm {
+    player_id: i32,
+    #[serde(default)]
+    reason: String,
+    /// Date the player may play again (YYYY-MM-DD, empty for an indefinite ban)
+    returns_on: Option<String>,
+}
+
+/// Suspend or ban a player (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/suspensions",
+    tag = "players",
+    request_body(content = SuspensionForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated player list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 422, description = "Invalid return date")
+    )
+)]
+pub async fn suspend_player(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<SuspensionForm>,
+) -> impl IntoResponse {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    let Ok(returns_on) = parse_form_date(form.returns_on.as_deref()) else {
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Invalid return date");
+    };
+
+    let reason = clean_note(&form.reason);
+    match db::create_suspension(&state.db, form.player_id, &reason, returns_on).await {
+        Ok(_) => Html(player_list(&state, true).await.into_string()).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to suspend player: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to suspend player",
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Form data for recording an injury
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct InjuryForm {
+    player_id: i32,
+    #[serde(default)]
+    note: String,
+    /// Date of the injury (YYYY-MM-DD, empty for today)
+    started_on: Option<String>,
+    /// Expected return date (YYYY-MM-DD, empty if unknown)
+    expected_return: Option<String>,
+}
+
+/// Parse an optional YYYY-MM-DD form date (empty = None)
+fn parse_form_date(value: Option<&str>) -> Result<Option<NaiveDate>, chrono::ParseError> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map(Some),
+    }
+}
+
+/// Record an injury, keeping the player out of check-in until cleared (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/injuries",
+    tag = "players",
+    request_body(content = InjuryForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated player list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 422, description = "Invalid date, or expected back before the injury", content_type = "text/html")
+    )
+)]
+pub async fn record_injury(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<InjuryForm>,
+) -> impl IntoResponse {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    let (Ok(started_on), Ok(expected_return)) = (
+        parse_form_date(form.started_on.as_deref()),
+        parse_form_date(form.expected_return.as_deref()),
+    ) else {
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Invalid date");
+    };
+    let started_on = started_on.unwrap_or_else(|| state.settings.get().today());
+    if expected_return.is_some_and(|date| date < started_on) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "The expected return can't be before the injury",
+        );
+    }
+
+    let note = clean_note(&form.note);
+    match db::create_injury(
+        &state.db,
+        form.player_id,
+        &note,
+        started_on,
+        expected_return,
+    )
+    .await
+    {
+        Ok(_) => Html(player_list(&state, true).await.into_string()).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to record injury: {}", e);
+            error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to record injury")
+        }
+    }
+}
+
+/// Clear a player's injury so they can be checked in again (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/players/{id}/injury/clear",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    responses(
+        (status = 200, description = "Updated player list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Player isn't injured", content_type = "text/html")
+    )
+)]
+pub async fn clear_injury(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    match db::clear_injuries(&state.db, id).await {
+        Ok(true) => Html(player_list(&state, true).await.into_string()).into_response(),
+        Ok(false) => error_fragment(StatusCode::NOT_FOUND, "This player isn't injured"),
+        Err(e) => {
+            tracing::error!("Failed to clear injury: {}", e);
+            error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to clear injury")
+        }
+    }
+}
+
+/// Lift a suspension early (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/suspensions/{id}/lift",
+    tag = "players",
+    params(("id" = i32, Path, description = "Suspension ID")),
+    responses(
+        (status = 200, description = "Updated player list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Suspension not found or already lifted")
+    )
+)]
+pub async fn lift_suspension(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    match db::lift_suspension(&state.db, id).await {
+        Ok(true) => Html(player_list(&state, true).await.into_string()).into_response(),
+        Ok(false) => error_fragment(StatusCode::NOT_FOUND, "Suspension not found"),
+        Err(e) => {
+            tracing::error!("Failed to lift suspension: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to lift suspension",
+            )
+                .into_response()
         }
     }
 }
@@ -0,0 +1,374 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::events::Event;
+use crate::models::{clean_note, Player, Season, SeasonStanding, ELO_DEFAULT};
+use crate::privacy::{mask_names, pseudonym, shows_hidden_names};
+use crate::seasons::{final_standings, parse_carry, rollover_rating, DEFAULT_CARRY};
+use crate::team_of_season::{team_of_season, SeasonLine, TeamOfSeason};
+use crate::views::errors::{error_fragment, error_page};
+use crate::views::layout::{base, render_elo_delta, AuthState};
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::extract::Form;
+use maud::{html, Markup};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Seasons page - archived standings and the season rollover
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> impl IntoResponse {
+    let seasons = db::get_seasons(&state.db).await.unwrap_or_default();
+    let mut standings = db::get_season_standings(&state.db)
+        .await
+        .unwrap_or_default();
+    let logged_in = is_authenticated(&jar, &state);
+    if !shows_hidden_names(&jar, &state) {
+        // Standings keep a snapshot of the name, so mask by the player's current setting
+        let hidden: HashSet<i32> = state
+            .players
+            .get(&state.db)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.hide_name)
+            .map(|p| p.id)
+            .collect();
+        for standing in &mut standings {
+            if let Some(id) = standing.player_id.filter(|id| hidden.contains(id)) {
+                standing.name = pseudonym(id);
+            }
+        }
+    }
+    let auth = AuthState::new(state.auth.is_some(), logged_in);
+
+    let mut standings_by_season: HashMap<i32, Vec<SeasonStanding>> = HashMap::new();
+    for standing in standings {
+        standings_by_season
+            .entry(standing.season_id)
+            .or_default()
+            .push(standing);
+    }
+
+    let content = html! {
+        h2 { "Seasons" }
+
+        @if logged_in {
+            details {
+                summary { "End Season" }
+                p class="secondary" {
+                    "Archives the current standings, then pulls every rating toward "
+                    (ELO_DEFAULT) ": new = " (ELO_DEFAULT) " + (old − " (ELO_DEFAULT) ") × carry."
+                }
+                form id="rollover-form" {
+                    div class="grid" {
+                        input type="text" name="name" placeholder="Season name (e.g. 2025 Autumn)" required;
+                        input type="number" name="carry" value=(DEFAULT_CARRY) min="0" max="1" step="0.05" aria-label="Carry";
+                    }
+                    div class="grid" {
+                        button type="button" class="secondary" hx-post="/api/seasons/preview" hx-include="#rollover-form" hx-target="#rollover-preview" {
+                            "Preview New Ratings"
+                        }
+                        button
+                            type="button"
+                            hx-post="/api/seasons"
+                            hx-include="#rollover-form"
+                            hx-target="#rollover-preview"
+                            hx-confirm="End the season and reset ratings? This can't be undone."
+                        {
+                            "End Season"
+                        }
+                    }
+                }
+                div id="rollover-preview" {}
+            }
+        }
+
+        @if seasons.is_empty() {
+            p { "No seasons archived yet." }
+        } @else {
+            @for season in seasons.iter().rev() {
+                (render_season(season, standings_by_season.get(&season.id).map(Vec::as_slice).unwrap_or_default()))
+            }
+        }
+    };
+
+    Html(
+        base(
+            "Seasons",
+            "seasons",
+            &auth,
+            &state.settings.get().group_name,
+            content,
+        )
+        .into_string(),
+    )
+}
+
+/// One archived season with its final standings
+fn render_season(season: &Season, standings: &[SeasonStanding]) -> Markup {
+    html! {
+        details {
+            summary {
+                strong { (season.name) }
+                " · ended " (season.ended_on.format("%Y-%m-%d"))
+                " · " (format!("{:.0}", season.carry * 100.0)) "% carried over"
+            }
+            @if standings.is_empty() {
+                p { "Nobody played this season." }
+            } @else {
+                p { a href=(format!("/seasons/{}/team", season.id)) { "⭐ Team of the Season →" } }
+                div class="table-container" {
+                    table {
+                        thead { tr { th { "#" } th { "Player" } th { "Elo" } th { "Matches" } } }
+                        tbody {
+                            @for standing in standings {
+                                tr {
+                                    td { (standing.rank) }
+                                    td { (standing.name) }
+                                    td { (format!("{:.0}", standing.elo)) }
+                                    td { (standing.matches_played) }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Team of the Season page - an archived season's best players in their
+/// positions, laid out to share or screenshot
+pub async fn team_page(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+    let settings = state.settings.get();
+    let seasons = db::get_seasons(&state.db).await.unwrap_or_default();
+    let Some(index) = seasons.iter().position(|s| s.id == id) else {
+        return error_page(
+            StatusCode::NOT_FOUND,
+            "Season not found",
+            &auth,
+            &settings.group_name,
+        );
+    };
+    let season = &seasons[index];
+    // The season covers matches since the one before it ended
+    let since = index.checked_sub(1).map(|i| seasons[i].ended_on);
+
+    let (Ok(mut players), Ok(matches)) = (
+        state.players.get(&state.db).await,
+        db::get_all_matches(&state.db).await,
+    ) else {
+        return error_page(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load players and matches",
+            &auth,
+            &settings.group_name,
+        );
+    };
+    mask_names(&mut players, shows_hidden_names(&jar, &state));
+    let team = team_of_season(
+        &players,
+        &matches,
+        since,
+        season.ended_on,
+        settings.players_per_team,
+    );
+
+    let content = html! {
+        h2 { "Team of the Season" }
+        p class="secondary" {
+            (season.name) " · " (team.matches) " matches · picked from everyone with "
+            (team.min_appearances) "+ appearances on fantasy points plus a point per 10 Elo gained"
+        }
+        (render_team_of_season(&team))
+        p class="no-print" {
+            a href="/seasons" { "← Seasons" }
+            " "
+            button type="button" class="secondary outline" data-print { "🖨 Print" }
+        }
+    };
+
+    Html(
+        base(
+            "Team of the Season",
+            "seasons",
+            &auth,
+            &settings.group_name,
+            content,
+        )
+        .into_string(),
+    )
+    .into_response()
+}
+
+/// The team on a pitch, attack at the top and the keeper at the bottom
+fn render_team_of_season(team: &TeamOfSeason) -> Markup {
+    let card = |label: &str, player: &Player, line: &SeasonLine| {
+        html! {
+            div class="tots-player" {
+                small class="tots-slot" { (label) }
+                strong { (player.name) }
+                small {
+                    (line.points) " pts · " (line.appearances) " apps · "
+                    (format!("{:+.0}", line.elo_gain)) " Elo"
+                }
+            }
+        }
+    };
+    html! {
+        @if team.keeper.is_none() {
+            p { "Nobody played this season." }
+        } @else {
+            div class="tots-pitch" {
+                @for (role, players) in team.lines.iter().rev() {
+                    @if !players.is_empty() {
+                        div class="tots-line" {
+                            @for (player, line) in players {
+                                (card(role.short(), player, line))
+                            }
+                        }
+                    }
+                }
+                @if let Some((player, line)) = &team.keeper {
+                    div class="tots-line" { (card("GK", player, line)) }
+                }
+            }
+        }
+    }
+}
+
+/// Form data for a season rollover
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct RolloverForm {
+    /// Name of the season being ended
+    #[serde(default)]
+    name: String,
+    /// Share of each rating's distance from 1200 to keep (0-1, default 0.5)
+    carry: Option<String>,
+}
+
+/// Preview the ratings a rollover would produce (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/seasons/preview",
+    tag = "seasons",
+    request_body(content = RolloverForm, content_type = "application/x-www-form-urlencoded"),
+    responses((status = 200, description = "Current and new rating per player", content_type = "text/html"))
+)]
+pub async fn preview_rollover(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<RolloverForm>,
+) -> impl IntoResponse {
+    let carry = match parse_carry(form.carry.as_deref()) {
+        Ok(carry) => carry,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+    let mut players = state.players.get(&state.db).await.unwrap_or_default();
+    mask_names(&mut players, shows_hidden_names(&jar, &state));
+    let anchor = state.settings.get().elo.anchor;
+
+    Html(
+        html! {
+            div class="table-container" {
+                table {
+                    thead { tr { th { "Player" } th { "Now" } th { "Next Season" } th { "Change" } } }
+                    tbody {
+                        @for player in &players {
+                            @let new_elo = rollover_rating(player.id, player.elo, carry, anchor);
+                            tr {
+                                td { (player.name) }
+                                td { (format!("{:.0}", player.elo)) }
+                                td { (format!("{:.0}", new_elo)) }
+                                td { (render_elo_delta(new_elo - player.elo)) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        .into_string(),
+    )
+    .into_response()
+}
+
+/// End the season: archive standings and soft-reset every rating (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/seasons",
+    tag = "seasons",
+    request_body(content = RolloverForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Season archived; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
+pub async fn end_season(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<RolloverForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized().into_response();
+    }
+
+    let name = clean_note(&form.name);
+    if name.is_empty() {
+        return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, "Give the season a name");
+    }
+    let carry = match parse_carry(form.carry.as_deref()) {
+        Ok(carry) => carry,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+
+    let settings = state.settings.get();
+    let today = settings.today();
+    let seasons = db::get_seasons(&state.db).await.unwrap_or_default();
+    let since = seasons.last().map(|s| s.ended_on);
+    if since.is_some_and(|ended| ended >= today) {
+        return error_fragment(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "A season already ended today",
+        );
+    }
+
+    let (Ok(players), Ok(matches)) = (
+        db::get_all_players(&state.db).await,
+        db::get_all_matches(&state.db).await,
+    ) else {
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load players and matches",
+        );
+    };
+
+    let result: Result<(), sqlx::Error> = async {
+        let mut tx = state.db.begin().await?;
+        let season = db::create_season(&mut *tx, &name, today, carry).await?;
+        for standing in final_standings(season.id, &players, &matches, since, today) {
+            db::create_season_standing(&mut *tx, &standing).await?;
+        }
+        for player in &players {
+            let elo = rollover_rating(player.id, player.elo, carry, settings.elo.anchor);
+            db::set_player_rating(&mut *tx, player.id, elo).await?;
+        }
+        tx.commit().await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("Failed to end season: {}", e);
+        return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to end season");
+    }
+    state.events.publish(Event::Results);
+
+    // Full reload so the archive shows the new season
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
@@ -0,0 +1,171 @@
+use crate::attendance::{
+    attendance, days_played, match_days, monthly, Attendance, MonthlyAttendance,
+};
+use crate::auth::{is_authenticated, AuthJar};
+use crate::captains::rank_captains;
+use crate::models::WinRecord;
+use crate::privacy::{mask_names, shows_hidden_names};
+use crate::views::layout::{base, AuthState};
+use crate::{db, AppState};
+use axum::{extract::State, response::Html, response::IntoResponse};
+use chrono::NaiveDate;
+use maud::{html, Markup};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Stats page - records across the whole match history
+pub async fn page(State(state): State<Arc<AppState>>, jar: AuthJar) -> impl IntoResponse {
+    let mut players = state.players.get(&state.db).await.unwrap_or_default();
+    mask_names(&mut players, shows_hidden_names(&jar, &state));
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    let played = days_played(
+        &db::get_match_days_played(&state.db)
+            .await
+            .unwrap_or_default(),
+    );
+    let days = match_days(&played);
+    let rsvps = match (days.first(), days.last()) {
+        (Some(first), Some(last)) => db::get_rsvps(&state.db, *first, *last)
+            .await
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    let report = attendance(&played, &rsvps);
+    let captains = rank_captains(db::get_captain_records(&state.db).await.unwrap_or_default());
+    let auth = AuthState::new(state.auth.is_some(), is_authenticated(&jar, &state));
+
+    let content = html! {
+        h2 { "Stats" }
+        (render_attendance(&report, &names))
+        (render_heat_table(&report, &monthly(&played), &names))
+        (render_captains(&captains, &names))
+        h3 { "Raw Data" }
+        p {
+            a href="/api/export/appearances.jsonl" download { "Download every appearance (JSON Lines)" }
+            @if cfg!(feature = "parquet") {
+                " · "
+                a href="/api/export/appearances.parquet" download { "Parquet" }
+            }
+            br;
+            small class="secondary" {
+                "One line per player per match: team, result, score, Elo before, Elo change and participation. "
+                "Loads straight into pandas (read_json with lines=True) or DuckDB (read_json_auto)."
+            }
+        }
+    };
+
+    Html(
+        base(
+            "Stats",
+            "stats",
+            &auth,
+            &state.settings.get().group_name,
+            content,
+        )
+        .into_string(),
+    )
+}
+
+/// Attendance per player, most match days first
+fn render_attendance(report: &[Attendance], names: &HashMap<i32, &str>) -> Markup {
+    html! {
+        h3 { "Attendance" }
+        @if report.is_empty() {
+            p class="secondary" { "No matches recorded yet." }
+        } @else {
+            p class="secondary" {
+                "Counted in match days since each player's first. The streak is match days in a row up to the latest; "
+                "no-shows are days someone RSVP'd In and didn't play."
+            }
+            div class="table-container" {
+                table {
+                    thead {
+                        tr { th { "Player" } th { "Played" } th { "Attendance" } th { "Streak" } th { "No-shows" } }
+                    }
+                    tbody {
+                        @for row in report {
+                            tr {
+                                td { (names.get(&row.player_id).copied().unwrap_or("Former player")) }
+                                td { (row.played) " / " (row.possible) }
+                                td { (format!("{:.0}%", row.rate() * 100.0)) }
+                                td { (row.streak) }
+                                td { (row.no_shows) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Month-by-month share of match days played, shaded by how many
+fn render_heat_table(
+    report: &[Attendance],
+    monthly: &MonthlyAttendance,
+    names: &HashMap<i32, &str>,
+) -> Markup {
+    html! {
+        @if !monthly.months.is_empty() {
+            details class="heat-table" {
+                summary { "By Month" }
+                div class="table-container" {
+                    table {
+                        thead {
+                            tr {
+                                th { "Player" }
+                                @for (year, month) in &monthly.months {
+                                    @let first = NaiveDate::from_ymd_opt(*year, *month, 1).unwrap_or_default();
+                                    th { (first.format("%b %y")) }
+                                }
+                            }
+                        }
+                        tbody {
+                            @for row in report {
+                                tr {
+                                    td { (names.get(&row.player_id).copied().unwrap_or("Former player")) }
+                                    @for month in &monthly.months {
+                                        @let share = monthly.share(row.player_id, *month).unwrap_or(0.0);
+                                        td class=(format!("heat heat-{}", (share * 4.0).round() as u8)) title=(format!("{:.0}% of match days", share * 100.0)) {
+                                            (format!("{:.0}", share * 100.0))
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Captains' results, best win rate first
+fn render_captains(records: &[WinRecord], names: &HashMap<i32, &str>) -> Markup {
+    html! {
+        h3 { "Captains" }
+        @if records.is_empty() {
+            p class="secondary" { "No captains recorded yet. Pick one per team on the Record page." }
+        } @else {
+            div class="table-container" {
+                table {
+                    thead {
+                        tr { th { "Captain" } th { "Matches" } th { "W" } th { "D" } th { "L" } th { "Win rate" } }
+                    }
+                    tbody {
+                        @for record in records {
+                            tr {
+                                td { (names.get(&record.player_id).copied().unwrap_or("Former player")) }
+                                td { (record.played()) }
+                                td { (record.wins) }
+                                td { (record.draws) }
+                                td { (record.losses) }
+                                td { (format!("{:.0}%", record.win_rate() * 100.0)) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
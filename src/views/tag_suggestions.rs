@@ -0,0 +1,218 @@
+use crate::auth::{is_authenticated, AuthJar};
+use crate::models::{Player, Tag};
+use crate::tag_suggestions::{apply, suggest, without_dismissed, TagChange, TagSuggestion};
+use crate::views::errors::error_fragment;
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::extract::Form;
+use maud::{html, Markup};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Suggested tag changes with accept and dismiss actions (a section of the
+/// Roster page for admins)
+pub fn render_tag_suggestions(suggestions: &[TagSuggestion], names: &HashMap<i32, &str>) -> Markup {
+    html! {
+        div id="tag-suggestions" {
+            @if !suggestions.is_empty() {
+                details {
+                    summary { "Tag Suggestions (" (suggestions.len()) ")" }
+                    p class="secondary" {
+                        "Worked out from goals on the timeline and clean sheets. "
+                        "Accept to change the player's tags, or dismiss to stop suggesting it."
+                    }
+                    div class="table-container" {
+                        table {
+                            thead {
+                                tr { th { "Player" } th { "Suggestion" } th { "Why" } th {} }
+                            }
+                            tbody {
+                                @for suggestion in suggestions {
+                                    @let url = format!("/api/players/{}/tag-suggestions", suggestion.player_id);
+                                    @let values = format!(
+                                        r#"{{"tag": "{}", "change": "{}"}}"#,
+                                        suggestion.tag,
+                                        suggestion.change.as_str()
+                                    );
+                                    tr {
+                                        td { (names.get(&suggestion.player_id).copied().unwrap_or("Unknown")) }
+                                        td {
+                                            @match suggestion.change {
+                                                TagChange::Add => { "Add " }
+                                                TagChange::Remove => { "Remove " }
+                                            }
+                                            span class="tag" { (suggestion.tag) }
+                                        }
+                                        td { (suggestion.reason) }
+                                        td class="row-actions" {
+                                            button hx-post=(format!("{}/accept", url)) hx-vals=(values) {
+                                                "Accept"
+                                            }
+                                            button
+                                                class="secondary outline"
+                                                hx-post=(format!("{}/dismiss", url))
+                                                hx-vals=(values)
+                                                hx-target="#tag-suggestions"
+                                                hx-swap="outerHTML"
+                                            {
+                                                "Dismiss"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Load and render the tag suggestions that haven't been dismissed
+pub(crate) async fn tag_suggestion_section(state: &AppState) -> Markup {
+    let players = state.players.get(&state.db).await.unwrap_or_default();
+    let matches = db::get_all_matches(&state.db).await.unwrap_or_default();
+    let dismissed: HashSet<(i32, Tag, TagChange)> = db::get_tag_dismissals(&state.db)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(id, tag, change)| {
+            Some((id, Tag::from_str(&tag)?, TagChange::from_str(&change)?))
+        })
+        .collect();
+    let suggestions = without_dismissed(suggest(&players, &matches), &dismissed);
+    let names: HashMap<i32, &str> = players.iter().map(|p| (p.id, p.name.as_str())).collect();
+    render_tag_suggestions(&suggestions, &names)
+}
+
+/// Form data naming one suggestion
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct TagSuggestionForm {
+    /// Tag, e.g. "ATK"
+    #[serde(default)]
+    tag: String,
+    /// "add" or "remove"
+    #[serde(default)]
+    change: String,
+}
+
+impl TagSuggestionForm {
+    fn parse(&self) -> Result<(Tag, TagChange), &'static str> {
+        match (Tag::from_str(&self.tag), TagChange::from_str(&self.change)) {
+            (Some(tag), Some(change)) => Ok((tag, change)),
+            _ => Err("Unknown tag suggestion"),
+        }
+    }
+}
+
+/// Load one player, or the error response to send instead
+async fn load_player(state: &AppState, id: i32) -> Result<Player, Response> {
+    match db::get_players_by_ids(&state.db, &[id]).await {
+        Ok(players) => players
+            .into_iter()
+            .next()
+            .ok_or_else(|| error_fragment(StatusCode::NOT_FOUND, "Player not found")),
+        Err(e) => {
+            tracing::error!("Failed to load player {}: {}", id, e);
+            Err(error_fragment(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load player",
+            ))
+        }
+    }
+}
+
+/// Apply a suggested tag change (htmx endpoint; reloads the page)
+#[utoipa::path(
+    post,
+    path = "/api/players/{id}/tag-suggestions/accept",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    request_body(content = TagSuggestionForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Tags changed; the page is refreshed"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Player not found", content_type = "text/html"),
+        (status = 422, description = "Unknown tag or change", content_type = "text/html")
+    )
+)]
+pub async fn accept_tag_suggestion(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+    Form(form): Form<TagSuggestionForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+    let (tag, change) = match form.parse() {
+        Ok(parsed) => parsed,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, e),
+    };
+    let player = match load_player(&state, id).await {
+        Ok(player) => player,
+        Err(response) => return response,
+    };
+
+    let tags = apply(&player.tags, tag, change);
+    if let Err(e) = db::set_player_tags(&state.db, id, &tags).await {
+        tracing::error!("Failed to update tags for player {}: {}", id, e);
+        return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update tags");
+    }
+    tracing::info!(
+        "Tag suggestion accepted: {} {} for player {}",
+        change.as_str(),
+        tag,
+        id
+    );
+    // The roster list shows tags too
+    ([("HX-Refresh", "true")], StatusCode::OK).into_response()
+}
+
+/// Stop suggesting a tag change (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/players/{id}/tag-suggestions/dismiss",
+    tag = "players",
+    params(("id" = i32, Path, description = "Player ID")),
+    request_body(content = TagSuggestionForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated tag suggestions", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 404, description = "Player not found", content_type = "text/html"),
+        (status = 422, description = "Unknown tag or change", content_type = "text/html")
+    )
+)]
+pub async fn dismiss_tag_suggestion(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+    Form(form): Form<TagSuggestionForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+    let (tag, change) = match form.parse() {
+        Ok(parsed) => parsed,
+        Err(e) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, e),
+    };
+    if let Err(response) = load_player(&state, id).await {
+        return response;
+    }
+
+    if let Err(e) =
+        db::dismiss_tag_suggestion(&state.db, id, &tag.to_string(), change.as_str()).await
+    {
+        tracing::error!("Failed to dismiss tag suggestion for player {}: {}", id, e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to dismiss suggestion",
+        );
+    }
+    Html(tag_suggestion_section(&state).await.into_string()).into_response()
+}
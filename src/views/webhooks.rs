@@ -0,0 +1,275 @@
+use crate::auth::{is_authenticated, random_token, AuthJar};
+use crate::models::{Webhook, WebhookDelivery};
+use crate::views::errors::error_fragment;
+use crate::webhooks::{WebhookEvent, EVENT_HEADER, SIGNATURE_HEADER};
+use crate::{db, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::extract::Form;
+use maud::{html, Markup};
+use std::sync::Arc;
+
+/// Webhook list, the form to add one and the latest delivery attempts (a
+/// section of the Admin page)
+pub fn render_webhooks(webhooks: &[Webhook], deliveries: &[WebhookDelivery]) -> Markup {
+    html! {
+        h3 { "Webhooks" }
+        p class="secondary" {
+            "Each webhook gets a JSON POST when a chosen event happens, with the event name in "
+            code { (EVENT_HEADER) } " and " code { "sha256=" } "HMAC of the body, keyed with its secret, in "
+            code { (SIGNATURE_HEADER) } ". Failed deliveries are retried five times with backoff."
+        }
+        form hx-post="/api/admin/webhooks" hx-target="#webhook-list" data-reset-on-success {
+            div class="grid" {
+                input type="url" name="url" placeholder="https://example.com/hooks/football" required;
+                input type="text" name="secret" placeholder="Secret (leave empty to generate one)" autocomplete="off";
+            }
+            fieldset {
+                legend { "Events" }
+                @for event in WebhookEvent::ALL {
+                    label {
+                        input type="checkbox" name="events" value=(event.as_str()) checked;
+                        (event.label()) " " code { (event.as_str()) }
+                    }
+                }
+            }
+            button type="submit" class="secondary" { "Add Webhook" }
+        }
+        div id="webhook-list" { (render_webhook_list(webhooks)) }
+        (render_deliveries(deliveries))
+    }
+}
+
+/// Latest delivery attempts, newest first; failures that ran out of attempts
+/// are under Background Jobs to retry
+fn render_deliveries(deliveries: &[WebhookDelivery]) -> Markup {
+    html! {
+        @if !deliveries.is_empty() {
+            details {
+                summary { "Recent Deliveries" }
+                div class="table-container" {
+                    table {
+                        thead {
+                            tr { th { "Attempted" } th { "URL" } th { "Event" } th { "Result" } }
+                        }
+                        tbody {
+                            @for delivery in deliveries {
+                                tr {
+                                    td { (delivery.attempted_at.format("%Y-%m-%d %H:%M:%S UTC")) }
+                                    td { code { (delivery.url) } }
+                                    td { code { (delivery.event) } }
+                                    @if delivery.delivered {
+                                        td { (delivery.status) }
+                                    } @else {
+                                        td class="elo-negative" { (delivery.status) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Table of webhooks with their secret and latest delivery
+fn render_webhook_list(webhooks: &[Webhook]) -> Markup {
+    html! {
+        @if webhooks.is_empty() {
+            p class="secondary" { "No webhooks yet." }
+        } @else {
+            div class="table-container" {
+                table {
+                    thead {
+                        tr { th { "URL" } th { "Events" } th { "Secret" } th { "Last delivery" } th {} }
+                    }
+                    tbody {
+                        @for webhook in webhooks {
+                            tr {
+                                td { code { (webhook.url) } }
+                                td { (webhook.events.replace(',', ", ")) }
+                                td {
+                                    details {
+                                        summary { "Show" }
+                                        code { (webhook.secret) }
+                                    }
+                                }
+                                td {
+                                    @match (&webhook.last_status, webhook.last_attempt_at) {
+                                        (Some(status), Some(at)) => {
+                                            (status) br;
+                                            small class="secondary" { (at.format("%Y-%m-%d %H:%M UTC")) }
+                                        }
+                                        _ => small class="secondary" {
+                                            "None since " (webhook.created_at.format("%Y-%m-%d"))
+                                        },
+                                    }
+                                }
+                                td class="row-actions" {
+                                    button
+                                        class="secondary outline"
+                                        hx-delete=(format!("/api/admin/webhooks/{}", webhook.id))
+                                        hx-target="#webhook-list"
+                                        hx-confirm=(format!("Delete the webhook to {}?", webhook.url))
+                                    {
+                                        "Delete"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Check a webhook URL: absolute http(s) with a host
+fn parse_webhook_url(text: &str) -> Result<String, String> {
+    let url = reqwest::Url::parse(text.trim()).map_err(|_| "Enter a full URL".to_string())?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err("Webhook URLs must start with http:// or https://".to_string());
+    }
+    Ok(url.to_string())
+}
+
+/// Parse the ticked events into the stored comma-separated form
+fn parse_events(events: &[String]) -> Result<String, String> {
+    let mut parsed: Vec<WebhookEvent> = Vec::new();
+    for name in events {
+        let event =
+            WebhookEvent::from_str(name).ok_or_else(|| format!("Unknown event \"{}\"", name))?;
+        if !parsed.contains(&event) {
+            parsed.push(event);
+        }
+    }
+    if parsed.is_empty() {
+        return Err("Choose at least one event".to_string());
+    }
+    Ok(parsed
+        .iter()
+        .map(|e| e.as_str())
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+/// Form data for adding a webhook
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct WebhookForm {
+    #[serde(default)]
+    url: String,
+    /// HMAC key; a random one is generated if empty
+    #[serde(default)]
+    secret: String,
+    /// Event names, e.g. "result.recorded"
+    #[serde(default)]
+    events: Vec<String>,
+}
+
+/// Add an outgoing webhook (htmx endpoint)
+#[utoipa::path(
+    post,
+    path = "/api/admin/webhooks",
+    tag = "admin",
+    request_body(content = WebhookForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Updated webhook list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html"),
+        (status = 422, description = "Invalid URL or events", content_type = "text/html")
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Form(form): Form<WebhookForm>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    let (url, events) = match (parse_webhook_url(&form.url), parse_events(&form.events)) {
+        (Ok(url), Ok(events)) => (url, events),
+        (Err(e), _) | (_, Err(e)) => return error_fragment(StatusCode::UNPROCESSABLE_ENTITY, &e),
+    };
+    let secret = match form.secret.trim() {
+        "" => random_token(),
+        secret => secret.to_string(),
+    };
+
+    if let Err(e) = db::create_webhook(&state.db, &url, &secret, &events).await {
+        tracing::error!("Failed to create webhook: {}", e);
+        return error_fragment(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add webhook");
+    }
+    webhook_list(&state).await
+}
+
+/// Delete an outgoing webhook (htmx endpoint)
+#[utoipa::path(
+    delete,
+    path = "/api/admin/webhooks/{id}",
+    tag = "admin",
+    params(("id" = i32, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, description = "Updated webhook list", content_type = "text/html"),
+        (status = 401, description = "Not logged in", content_type = "text/html")
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    jar: AuthJar,
+    Path(id): Path<i32>,
+) -> Response {
+    if !is_authenticated(&jar, &state) {
+        return crate::auth::unauthorized();
+    }
+
+    if let Err(e) = db::delete_webhook(&state.db, id).await {
+        tracing::error!("Failed to delete webhook: {}", e);
+        return error_fragment(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to delete webhook",
+        );
+    }
+    webhook_list(&state).await
+}
+
+/// Reload and render the webhook list (after a change)
+async fn webhook_list(state: &AppState) -> Response {
+    let webhooks = db::get_webhooks(&state.db).await.unwrap_or_default();
+    Html(render_webhook_list(&webhooks).into_string()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_webhook_url() {
+        assert_eq!(
+            parse_webhook_url(" https://example.com/hook ").as_deref(),
+            Ok("https://example.com/hook")
+        );
+        assert!(parse_webhook_url("example.com/hook").is_err());
+        assert!(parse_webhook_url("ftp://example.com/hook").is_err());
+        assert!(parse_webhook_url("").is_err());
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let events = vec![
+            "result.recorded".to_string(),
+            "player.created".to_string(),
+            "result.recorded".to_string(),
+        ];
+        assert_eq!(
+            parse_events(&events).as_deref(),
+            Ok("result.recorded,player.created")
+        );
+        assert!(parse_events(&[]).is_err());
+        assert!(parse_events(&["match.voided".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+use crate::db;
+use crate::elo::average_elo;
+use crate::jobs::{self, Job};
+use crate::milestones::PlayerMilestone;
+use crate::models::{EloSnapshot, Match, Player, TeamSplit, Webhook};
+use crate::privacy::pseudonym;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Header carrying `sha256=<hex HMAC of the body>` keyed with the webhook's secret
+pub const SIGNATURE_HEADER: &str = "X-SFM-Signature";
+/// Header carrying the event name
+pub const EVENT_HEADER: &str = "X-SFM-Event";
+/// Delivery attempts per event before the job is marked failed
+pub const MAX_ATTEMPTS: i32 = 5;
+/// Delivery attempts kept in the log (across all webhooks)
+const DELIVERY_LOG_SIZE: i64 = 500;
+/// Delivery attempts listed on the Admin page
+pub const RECENT_DELIVERIES: i64 = 20;
+/// Per-request timeout
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Something a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    PlayerCreated,
+    TeamsGenerated,
+    ResultRecorded,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 3] = [
+        WebhookEvent::PlayerCreated,
+        WebhookEvent::TeamsGenerated,
+        WebhookEvent::ResultRecorded,
+    ];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == s)
+    }
+
+    /// Name in payloads, the event header and the `events` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::PlayerCreated => "player.created",
+            WebhookEvent::TeamsGenerated => "teams.generated",
+            WebhookEvent::ResultRecorded => "result.recorded",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WebhookEvent::PlayerCreated => "Player created",
+            WebhookEvent::TeamsGenerated => "Teams generated",
+            WebhookEvent::ResultRecorded => "Result recorded",
+        }
+    }
+}
+
+/// Whether a webhook subscribes to `event`
+pub fn subscribes_to(webhook: &Webhook, event: WebhookEvent) -> bool {
+    webhook
+        .events
+        .split(',')
+        .any(|e| e.trim() == event.as_str())
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with `secret`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Player fields included in payloads. Payloads leave the app, so players
+/// who hide their name get their pseudonym, as anonymous visitors do.
+pub fn player_json(player: &Player) -> Value {
+    let name = if player.hide_name {
+        pseudonym(player.id)
+    } else {
+        player.name.clone()
+    };
+    json!({ "id": player.id, "name": name, "elo": player.elo })
+}
+
+/// Payload for `teams.generated`
+pub fn teams_json(split: &TeamSplit) -> Value {
+    json!({
+        "team_a": split.team_a.iter().map(player_json).collect::<Vec<_>>(),
+        "team_b": split.team_b.iter().map(player_json).collect::<Vec<_>>(),
+        "avg_elo_a": average_elo(&split.team_a),
+        "avg_elo_b": average_elo(&split.team_b),
+    })
+}
+
+/// Payload for `result.recorded`: the match, both teams, each player's
+/// Elo change (after partial participation) and any milestones reached
+pub fn result_json(
+    recorded: &Match,
+    team_a: &[Player],
+    team_b: &[Player],
+    elo_changes: &HashMap<i32, EloSnapshot>,
+    milestones: &[PlayerMilestone],
+) -> Value {
+    let changes: HashMap<String, f32> = elo_changes
+        .iter()
+        .map(|(id, change)| (id.to_string(), change.delta * change.participation))
+        .collect();
+    json!({
+        "match_id": recorded.id,
+        "played_at": recorded.played_at,
+        "score_a": recorded.score_a,
+        "score_b": recorded.score_b,
+        "team_a": team_a.iter().map(player_json).collect::<Vec<_>>(),
+        "team_b": team_b.iter().map(player_json).collect::<Vec<_>>(),
+        "elo_changes": changes,
+        "goals": recorded.goals,
+        "milestones": milestones
+            .iter()
+            .map(|m| json!({ "player_id": m.player_id, "milestone": m.milestone, "text": m.milestone.label() }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// HTTP client for webhook deliveries
+pub fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!(
+            "sunday-football-manager/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()
+        .expect("Failed to build the webhook HTTP client")
+}
+
+/// Queues events for subscribed webhooks (see [`crate::jobs`])
+#[derive(Clone)]
+pub struct Dispatcher {
+    pool: PgPool,
+}
+
+impl Dispatcher {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Queue a delivery of `data` to every webhook subscribed to `event`.
+    /// Returns at once; the job worker sends and retries them.
+    pub fn send(&self, event: WebhookEvent, data: Value) {
+        let pool = self.pool.clone();
+        tokio::spawn(async move {
+            let webhooks = match db::get_webhooks(&pool).await {
+                Ok(webhooks) => webhooks,
+                Err(e) => {
+                    tracing::error!("Failed to load webhooks: {}", e);
+                    return;
+                }
+            };
+            let body = json!({
+                "event": event.as_str(),
+                "sent_at": Utc::now(),
+                "data": data,
+            })
+            .to_string();
+            for webhook in webhooks.into_iter().filter(|w| subscribes_to(w, event)) {
+                let job = Job::DeliverWebhook {
+                    webhook_id: webhook.id,
+                    event: event.as_str().to_string(),
+                    body: body.clone(),
+                };
+                if let Err(e) = jobs::enqueue(&pool, &job).await {
+                    tracing::error!("Failed to queue webhook {}: {}", webhook.id, e);
+                }
+            }
+        });
+    }
+}
+
+/// POST one payload to a webhook, record the outcome on it and log the
+/// attempt. Err (a non-2xx status or the request error) makes the job retry;
+/// a deleted webhook counts as done.
+pub async fn deliver(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    webhook_id: i32,
+    event: &str,
+    body: &str,
+) -> Result<(), String> {
+    let webhook = match db::get_webhook(pool, webhook_id).await {
+        Ok(Some(webhook)) => webhook,
+        Ok(None) => return Ok(()),
+        Err(e) => return Err(format!("Failed to load webhook: {}", e)),
+    };
+
+    let signature = format!("sha256={}", sign(&webhook.secret, body.as_bytes()));
+    let result = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, event)
+        .header(SIGNATURE_HEADER, &signature)
+        .body(body.to_string())
+        .send()
+        .await;
+    let (status, delivered) = match result {
+        Ok(response) => (
+            response.status().as_u16().to_string(),
+            response.status().is_success(),
+        ),
+        Err(e) => (e.to_string(), false),
+    };
+    if let Err(e) = db::set_webhook_status(pool, webhook.id, &status).await {
+        tracing::error!("Failed to save webhook status: {}", e);
+    }
+    if let Err(e) = db::log_webhook_delivery(
+        pool,
+        webhook.id,
+        event,
+        &status,
+        delivered,
+        DELIVERY_LOG_SIZE,
+    )
+    .await
+    {
+        tracing::error!("Failed to log webhook delivery: {}", e);
+    }
+    if delivered {
+        Ok(())
+    } else {
+        Err(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_roundtrip() {
+        for event in WebhookEvent::ALL {
+            assert_eq!(WebhookEvent::from_str(event.as_str()), Some(event));
+        }
+        assert_eq!(WebhookEvent::from_str("match.voided"), None);
+    }
+
+    #[test]
+    fn test_player_json_hides_hidden_names() {
+        let mut player = Player {
+            id: 7,
+            name: "Sam".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(player_json(&player)["name"], "Sam");
+        player.hide_name = true;
+        assert_eq!(player_json(&player)["name"], pseudonym(7));
+    }
+
+    #[test]
+    fn test_subscribes_to() {
+        let webhook = Webhook {
+            id: 1,
+            url: "https://example.com/hook".to_string(),
+            secret: "s".to_string(),
+            events: "player.created, result.recorded".to_string(),
+            last_status: None,
+            last_attempt_at: None,
+            created_at: Utc::now(),
+        };
+        assert!(subscribes_to(&webhook, WebhookEvent::PlayerCreated));
+        assert!(subscribes_to(&webhook, WebhookEvent::ResultRecorded));
+        assert!(!subscribes_to(&webhook, WebhookEvent::TeamsGenerated));
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
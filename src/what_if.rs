@@ -0,0 +1,182 @@
+use crate::elo::EloConfig;
+use crate::models::Player;
+use std::collections::HashMap;
+
+/// One player's final rating and rank under the current and the what-if
+/// rating parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhatIfRow {
+    pub name: String,
+    pub elo: f32,
+    pub rank: usize,
+    pub what_if_elo: f32,
+    pub what_if_rank: usize,
+}
+
+impl WhatIfRow {
+    /// Places gained under the what-if parameters (negative = dropped)
+    pub fn rank_change(&self) -> i32 {
+        self.rank as i32 - self.what_if_rank as i32
+    }
+}
+
+/// Rating parameters to try, in the text form the admin page submits
+#[derive(Debug, Default)]
+pub struct WhatIfParams<'a> {
+    pub k_factor: &'a str,
+    pub gd_cap: &'a str,
+    pub handicap_per_player: &'a str,
+    pub handicap_curve: &'a str,
+    pub regression: &'a str,
+}
+
+/// Parse what-if parameters over `base`; an empty field keeps the base value
+pub fn parse_what_if(params: &WhatIfParams, base: EloConfig) -> Result<EloConfig, String> {
+    let field = |value: &str, current: f32, valid: fn(f32) -> bool, error: &str| {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(current);
+        }
+        value
+            .parse::<f32>()
+            .ok()
+            .filter(|v| v.is_finite() && valid(*v))
+            .ok_or_else(|| error.to_string())
+    };
+
+    Ok(EloConfig {
+        k_factor: field(
+            params.k_factor,
+            base.k_factor,
+            |k| k > 0.0,
+            "K-factor must be above 0",
+        )?,
+        gd_cap: field(
+            params.gd_cap,
+            base.gd_cap,
+            |cap| cap >= 1.0,
+            "Goal-difference cap must be 1 or more",
+        )?,
+        handicap_per_player: field(
+            params.handicap_per_player,
+            base.handicap_per_player,
+            |h| h >= 0.0,
+            "Handicap per player must be 0 or more",
+        )?,
+        handicap_curve: field(
+            params.handicap_curve,
+            base.handicap_curve,
+            |c| c > 0.0,
+            "Handicap curve must be above 0",
+        )?,
+        regression: field(
+            params.regression,
+            base.regression,
+            |r| (0.0..=1.0).contains(&r),
+            "Regression must be between 0 and 1",
+        )?,
+        keeper_handicap: base.keeper_handicap,
+        max_change: base.max_change,
+        absence_days: base.absence_days,
+        anchor: base.anchor,
+    })
+}
+
+/// Rank players by rating, highest first (1-based)
+fn ranks(players: &[&Player], ratings: &HashMap<i32, f32>) -> HashMap<i32, usize> {
+    let mut ranked: Vec<(i32, f32)> = players
+        .iter()
+        .map(|p| (p.id, ratings.get(&p.id).copied().unwrap_or(p.elo)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked
+        .into_iter()
+        .enumerate()
+        .map(|(i, (id, _))| (id, i + 1))
+        .collect()
+}
+
+/// Compare two replays' final ratings for every player with a match,
+/// ordered by what-if rank
+pub fn compare_ratings(
+    players: &[Player],
+    current: &HashMap<i32, f32>,
+    what_if: &HashMap<i32, f32>,
+) -> Vec<WhatIfRow> {
+    let rated: Vec<&Player> = players.iter().filter(|p| p.matches_played > 0).collect();
+    let current_ranks = ranks(&rated, current);
+    let what_if_ranks = ranks(&rated, what_if);
+
+    let mut rows: Vec<WhatIfRow> = rated
+        .iter()
+        .map(|p| WhatIfRow {
+            name: p.name.clone(),
+            elo: current.get(&p.id).copied().unwrap_or(p.elo),
+            rank: current_ranks[&p.id],
+            what_if_elo: what_if.get(&p.id).copied().unwrap_or(p.elo),
+            what_if_rank: what_if_ranks[&p.id],
+        })
+        .collect();
+    rows.sort_by_key(|row| row.what_if_rank);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_player(id: i32, name: &str, matches_played: i32) -> Player {
+        Player {
+            id,
+            name: name.to_string(),
+            elo: 1200.0,
+            matches_played,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_what_if() {
+        let base = EloConfig::default();
+        let params = WhatIfParams {
+            k_factor: "40",
+            gd_cap: " ",
+            ..Default::default()
+        };
+        let config = parse_what_if(&params, base).unwrap();
+        assert_eq!(config.k_factor, 40.0);
+        assert_eq!(config.gd_cap, base.gd_cap);
+        assert_eq!(config.handicap_per_player, base.handicap_per_player);
+
+        let bad = WhatIfParams {
+            gd_cap: "0.5",
+            ..Default::default()
+        };
+        assert!(parse_what_if(&bad, base).is_err());
+        let bad = WhatIfParams {
+            k_factor: "NaN",
+            ..Default::default()
+        };
+        assert!(parse_what_if(&bad, base).is_err());
+    }
+
+    #[test]
+    fn test_compare_ratings() {
+        let players = vec![
+            make_player(1, "Ann", 5),
+            make_player(2, "Bob", 5),
+            make_player(3, "New", 0),
+        ];
+        let current = HashMap::from([(1, 1250.0), (2, 1230.0), (3, 1200.0)]);
+        let what_if = HashMap::from([(1, 1240.0), (2, 1260.0), (3, 1200.0)]);
+
+        let rows = compare_ratings(&players, &current, &what_if);
+
+        // Players without matches are left out
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "Bob");
+        assert_eq!((rows[0].rank, rows[0].what_if_rank), (2, 1));
+        assert_eq!(rows[0].rank_change(), 1);
+        assert_eq!(rows[1].rank_change(), -1);
+    }
+}
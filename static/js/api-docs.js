@@ -0,0 +1,5 @@
+// Swagger UI for /api/docs
+window.ui = SwaggerUIBundle({
+    url: '/api/openapi.json',
+    dom_id: '#swagger-ui',
+});
@@ -0,0 +1,34 @@
+// Shared behaviour for every page (inline handlers are blocked by the CSP)
+
+// Clear forms marked data-reset-on-success after htmx submits them
+document.addEventListener('htmx:afterRequest', (e) => {
+    const form = e.detail.elt.closest('form[data-reset-on-success]');
+    if (form && e.detail.successful) form.reset();
+});
+
+// Buttons marked data-print open the print dialog
+document.addEventListener('click', (e) => {
+    if (e.target.closest('[data-print]')) window.print();
+});
+
+// Sections marked data-live="<event>" refresh (via their htmx `refresh`
+// trigger) when the server sends that event, e.g. a result recorded elsewhere.
+// Sections htmx swaps in later (e.g. generated teams) join in when loaded.
+let liveEvents = null;
+const liveNames = new Set();
+document.addEventListener('htmx:load', (e) => {
+    const root = e.detail.elt;
+    const sections = [...root.querySelectorAll('[data-live]')];
+    if (root.dataset && root.dataset.live) sections.push(root);
+    if (!sections.length || !window.EventSource) return;
+    liveEvents = liveEvents || new EventSource('/api/events');
+    sections.flatMap((el) => el.dataset.live.split(' ')).forEach((name) => {
+        if (liveNames.has(name)) return;
+        liveNames.add(name);
+        liveEvents.addEventListener(name, () => {
+            document.querySelectorAll('[data-live]').forEach((el) => {
+                if (el.dataset.live.split(' ').includes(name)) htmx.trigger(el, 'refresh');
+            });
+        });
+    });
+});
@@ -0,0 +1,115 @@
+// History page: Elo evolution chart (data from the canvas's data-chart attribute),
+// redrawn whenever the live results section is refreshed
+let eloChart = null;
+
+// Shade each visible line's ± uncertainty band
+const uncertaintyBands = {
+    id: 'uncertaintyBands',
+    beforeDatasetsDraw(chart) {
+        const { ctx, scales: { y } } = chart;
+        chart.data.datasets.forEach((dataset, i) => {
+            const meta = chart.getDatasetMeta(i);
+            if (meta.hidden || !chart.isDatasetVisible(i) || meta.data.length === 0) return;
+            const points = meta.data.map((el, j) => ({ x: el.x, point: dataset.data[j] }));
+            ctx.save();
+            ctx.globalAlpha = 0.12;
+            ctx.fillStyle = dataset.borderColor;
+            ctx.beginPath();
+            points.forEach(({ x, point }, j) => {
+                const top = y.getPixelForValue(point.y + point.band);
+                j === 0 ? ctx.moveTo(x, top) : ctx.lineTo(x, top);
+            });
+            points.slice().reverse().forEach(({ x, point }) => {
+                ctx.lineTo(x, y.getPixelForValue(point.y - point.band));
+            });
+            ctx.closePath();
+            ctx.fill();
+            ctx.restore();
+        });
+    }
+};
+
+// Imported baselines (points flagged `baseline`) are diamonds, and the line
+// from one is dashed so it reads apart from earned changes
+const isBaseline = (point) => Boolean(point && point.baseline);
+
+function drawChart() {
+    if (eloChart) eloChart.destroy();
+    eloChart = null;
+    const chartCanvas = document.getElementById('elo-chart');
+    if (!chartCanvas) return;
+    const chartData = JSON.parse(chartCanvas.dataset.chart);
+    const ctx = chartCanvas.getContext('2d');
+
+    eloChart = new Chart(ctx, {
+        type: 'line',
+        data: chartData,
+        plugins: [uncertaintyBands],
+        options: {
+            responsive: true,
+            elements: {
+                point: {
+                    pointStyle: (ctx) => isBaseline(ctx.raw) ? 'rectRot' : 'circle',
+                    radius: (ctx) => isBaseline(ctx.raw) ? 6 : 3
+                }
+            },
+            datasets: {
+                line: {
+                    segment: {
+                        borderDash: (ctx) => isBaseline(ctx.chart.data.datasets[ctx.datasetIndex].data[ctx.p0DataIndex]) ? [6, 4] : undefined
+                    }
+                }
+            },
+            maintainAspectRatio: false,
+            plugins: {
+                legend: {
+                    position: 'bottom',
+                    labels: {
+                        usePointStyle: true,
+                        padding: 15
+                    },
+                    onClick: function(e, legendItem, legend) {
+                        const index = legendItem.datasetIndex;
+                        const ci = legend.chart;
+                        const meta = ci.getDatasetMeta(index);
+                        meta.hidden = meta.hidden === null ? !ci.data.datasets[index].hidden : null;
+                        ci.update();
+                    }
+                },
+                tooltip: {
+                    mode: 'index',
+                    intersect: false,
+                    callbacks: {
+                        label: (item) => `${item.dataset.label}: ${Math.round(item.raw.y)} ± ${Math.round(item.raw.band)}` +
+                            (isBaseline(item.raw) ? ' (imported baseline)' : '')
+                    }
+                }
+            },
+            scales: {
+                x: {
+                    type: 'category',
+                    title: {
+                        display: true,
+                        text: 'Match Date'
+                    }
+                },
+                y: {
+                    title: {
+                        display: true,
+                        text: 'Elo Rating'
+                    }
+                }
+            },
+            interaction: {
+                mode: 'nearest',
+                axis: 'x',
+                intersect: false
+            }
+        }
+    });
+}
+
+drawChart();
+document.addEventListener('htmx:afterSwap', (e) => {
+    if (e.detail.target.id === 'history-results') drawChart();
+});
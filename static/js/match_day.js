@@ -0,0 +1,124 @@
+// Match Day: check-in counter, shared team links and saved teams
+const checkinForm = document.getElementById('checkin-form');
+const MAX_PLAYERS = Number(checkinForm?.dataset.maxPlayers) || 14;
+const buttons = document.querySelectorAll('#checkin-form button[type="submit"]');
+const counter = document.getElementById('player-count');
+const checkboxes = document.querySelectorAll('.player-checkbox');
+
+function updateState() {
+    const checked = document.querySelectorAll('.player-checkbox:checked').length;
+    // Update counter
+    counter.textContent = checked + ' / ' + MAX_PLAYERS;
+    // Enable buttons when at least 2 players selected (minimum for teams)
+    buttons.forEach(btn => btn.disabled = checked < 2);
+    // Disable unchecked boxes when at max
+    checkboxes.forEach(cb => {
+        if (!cb.checked) cb.disabled = checked >= MAX_PLAYERS;
+    });
+}
+
+// Initial state
+updateState();
+
+// Listen for changes
+checkboxes.forEach(cb => {
+    cb.addEventListener('change', updateState);
+});
+
+// Parse comma-separated IDs (mirrors Rust parse_team_ids)
+function parseTeamIds(param) {
+    if (!param) return [];
+    return param.split(',').map(s => parseInt(s, 10)).filter(n => !isNaN(n));
+}
+
+// Encode team IDs to hash format (mirrors Rust encode_teams_hash)
+function encodeTeamsHash(teamA, teamB) {
+    return 'a=' + teamA.join(',') + '&b=' + teamB.join(',');
+}
+
+// Restore checkbox selection from team IDs
+function restoreCheckboxes(teamIds) {
+    checkboxes.forEach(cb => {
+        if (teamIds.includes(parseInt(cb.value))) {
+            cb.checked = true;
+        }
+    });
+    updateState();
+}
+
+// On page load: restore state from the hash, teams picked from RSVPs, or localStorage
+window.addEventListener('load', () => {
+    const linked = window.location.hash.slice(1);
+    const hash = linked && linked.includes('a=') && linked.includes('b=')
+        ? linked
+        : document.getElementById('teams-display').dataset.autoTeams;
+    let teamIds = null;
+
+    if (hash) {
+        const params = new URLSearchParams(hash);
+        const teamA = parseTeamIds(params.get('a'));
+        const teamB = parseTeamIds(params.get('b'));
+        teamIds = [...teamA, ...teamB];
+        htmx.ajax('GET', '/fragments/teams?' + hash, '#teams-display');
+    } else {
+        const saved = localStorage.getItem('lastTeams');
+        if (saved) {
+            try {
+                const { teamA, teamB } = JSON.parse(saved);
+                teamIds = [...teamA, ...teamB];
+                const hash = encodeTeamsHash(teamA, teamB);
+                htmx.ajax('GET', '/fragments/teams?' + hash, '#teams-display');
+                history.replaceState(null, '', '#' + hash);
+            } catch (e) {}
+        }
+    }
+
+    if (teamIds) restoreCheckboxes(teamIds);
+});
+
+// After teams generated: update hash + localStorage
+document.body.addEventListener('htmx:afterSwap', (e) => {
+    if (e.detail.target.id === 'teams-display') {
+        const result = e.detail.target.querySelector('[data-team-a]');
+        if (result) {
+            const teamA = JSON.parse(result.dataset.teamA);
+            const teamB = JSON.parse(result.dataset.teamB);
+            const captains = result.dataset.captains ? JSON.parse(result.dataset.captains) : null;
+            history.replaceState(null, '', '#' + encodeTeamsHash(teamA, teamB));
+            localStorage.setItem('lastTeams', JSON.stringify({teamA, teamB, captains}));
+        }
+    }
+});
+
+// Copy link to clipboard
+function copyTeamLink() {
+    const btn = document.getElementById('copy-link-btn');
+    const orig = btn.textContent;
+    navigator.clipboard.writeText(window.location.href).then(() => {
+        btn.textContent = 'Copied!';
+        setTimeout(() => btn.textContent = orig, 2000);
+    }).catch(() => {
+        btn.textContent = 'Failed';
+        setTimeout(() => btn.textContent = orig, 2000);
+    });
+}
+// Copy the server-formatted WhatsApp message to clipboard
+function copyWhatsAppText(btn) {
+    const orig = btn.textContent;
+    fetch(btn.dataset.shareUrl)
+        .then(res => res.ok ? res.text() : Promise.reject(res.status))
+        .then(text => navigator.clipboard.writeText(text))
+        .then(() => {
+            btn.textContent = 'Copied!';
+            setTimeout(() => btn.textContent = orig, 2000);
+        })
+        .catch(() => {
+            btn.textContent = 'Failed';
+            setTimeout(() => btn.textContent = orig, 2000);
+        });
+}
+document.body.addEventListener('click', (e) => {
+    if (e.target.closest('#copy-link-btn')) copyTeamLink();
+    const whatsApp = e.target.closest('#copy-whatsapp-btn');
+    if (whatsApp) copyWhatsAppText(whatsApp);
+});
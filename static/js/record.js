@@ -0,0 +1,337 @@
+// Record page: team chip selector, participation editor and goal list
+const recordForm = document.getElementById('record-form');
+const allPlayers = JSON.parse(recordForm.dataset.players);
+const maxPerTeam = parseInt(recordForm.dataset.maxPerTeam, 10);
+const defaultMinutes = parseInt(recordForm.dataset.defaultMinutes, 10);
+const selectedA = new Set();
+const selectedB = new Set();
+
+function getAvailable() {
+    return allPlayers.filter(p => !selectedA.has(p.name) && !selectedB.has(p.name));
+}
+
+function renderDropdown(container, filter) {
+    const dropdown = container.querySelector('.player-dropdown');
+    const team = container.dataset.team;
+    const selected = team === 'a' ? selectedA : selectedB;
+
+    if (selected.size >= maxPerTeam) {
+        dropdown.innerHTML = '<li class="dropdown-empty">Max players reached</li>';
+        return;
+    }
+
+    const available = getAvailable();
+    const filtered = filter
+        ? available.filter(p => p.name.toLowerCase().includes(filter.toLowerCase()))
+        : available;
+
+    if (filtered.length === 0) {
+        dropdown.innerHTML = '<li class="dropdown-empty">No players found</li>';
+        return;
+    }
+
+    dropdown.innerHTML = filtered.map(p =>
+        `<li data-name="${p.name}" data-id="${p.id}">${p.name}</li>`
+    ).join('');
+
+    dropdown.querySelectorAll('li[data-name]').forEach(li => {
+        li.addEventListener('click', () => {
+            selectPlayer(container, li.dataset.name, parseInt(li.dataset.id));
+        });
+    });
+}
+
+// Track participation values (default 1.0) and joined/left minutes
+const participationValues = {};
+const minuteValues = {};
+
+function selectPlayer(container, name, playerId) {
+    const team = container.dataset.team;
+    const selected = team === 'a' ? selectedA : selectedB;
+    const inputName = team === 'a' ? 'team_a' : 'team_b';
+
+    if (selected.size >= maxPerTeam) return;
+
+    selected.add(name);
+    participationValues[playerId] = 1.0;
+
+    // Add simple chip (just name and remove button)
+    const chipsContainer = container.querySelector('.selected-chips');
+    const chip = document.createElement('span');
+    chip.className = 'chip';
+    chip.dataset.name = name;
+    chip.dataset.playerId = playerId;
+    chip.innerHTML = `${name}<button type="button">&times;</button>`;
+
+    chip.querySelector('button').addEventListener('click', () => {
+        removePlayer(container, name, playerId);
+    });
+    chipsContainer.appendChild(chip);
+
+    // Add hidden input for team
+    const hidden = document.createElement('input');
+    hidden.type = 'hidden';
+    hidden.name = inputName;
+    hidden.value = name;
+    hidden.dataset.playerName = name;
+    container.appendChild(hidden);
+
+    // Clear search and close dropdown
+    const search = container.querySelector('.player-search');
+    search.value = '';
+    container.querySelector('.player-dropdown').classList.remove('open');
+
+    // Update participation list, captain and scorer choices
+    renderParticipationList();
+    renderCaptainSelect(team);
+    renderScorerSelects();
+}
+
+// Captain picker: one option per selected player, keeping the current choice
+function renderCaptainSelect(team, preselect) {
+    const select = document.querySelector(`select[name="captain_${team}"]`);
+    const selected = team === 'a' ? selectedA : selectedB;
+    const current = preselect ?? select.value;
+    select.innerHTML = '<option value="">No captain</option>' + [...selected].map(name =>
+        `<option value="${name}"${name === current ? ' selected' : ''}>${name}</option>`
+    ).join('');
+}
+
+function updateParticipation(playerId, percent) {
+    // Percent from the input; the server validates the 1-100% range
+    const value = parseFloat(percent) / 100;
+    participationValues[playerId] = value;
+    // Update row styling and hidden input
+    const row = document.querySelector(`.participation-row[data-player-id="${playerId}"]`);
+    if (row) {
+        row.classList.toggle('partial', value < 1.0);
+        const hidden = row.querySelector('input[name="participation"]');
+        if (hidden) hidden.value = `${playerId}=${value}`;
+    }
+}
+
+function updateMinutes(playerId) {
+    const row = document.querySelector(`.participation-row[data-player-id="${playerId}"]`);
+    if (!row) return;
+    const joined = row.querySelector('.joined').value;
+    const left = row.querySelector('.left').value;
+    minuteValues[playerId] = { joined, left };
+
+    // The server works out the share; this only previews it
+    const hidden = row.querySelector('input[name="minutes"]');
+    hidden.disabled = !joined && !left;
+    hidden.value = `${playerId}=${joined}-${left}`;
+    if (hidden.disabled) return;
+    const duration = parseFloat(document.querySelector('input[name="duration"]').value) || defaultMinutes;
+    const share = ((left === '' ? duration : parseFloat(left)) - (joined === '' ? 0 : parseFloat(joined))) / duration;
+    const percent = Math.round(share * 100);
+    row.querySelector('.percent').value = percent;
+    updateParticipation(playerId, percent);
+}
+
+function refreshMinutes() {
+    Object.keys(minuteValues).forEach(playerId => updateMinutes(playerId));
+}
+
+function removePlayer(container, name, playerId) {
+    const team = container.dataset.team;
+    const selected = team === 'a' ? selectedA : selectedB;
+
+    selected.delete(name);
+    delete participationValues[playerId];
+    delete minuteValues[playerId];
+
+    // Remove chip
+    const chip = container.querySelector(`.chip[data-name="${name}"]`);
+    if (chip) chip.remove();
+
+    // Remove hidden input
+    const hidden = container.querySelector(`input[data-player-name="${name}"]`);
+    if (hidden) hidden.remove();
+
+    // Update participation list, captain and scorer choices
+    renderParticipationList();
+    renderCaptainSelect(team);
+    renderScorerSelects();
+}
+
+function renderParticipationList() {
+    const list = document.getElementById('participation-list');
+    const allSelected = [];
+
+    // Gather all selected players
+    document.querySelectorAll('.chip').forEach(chip => {
+        const playerId = parseInt(chip.dataset.playerId);
+        const name = chip.dataset.name;
+        const team = chip.closest('.player-select').dataset.team;
+        allSelected.push({ playerId, name, team });
+    });
+
+    if (allSelected.length === 0) {
+        list.innerHTML = '<p class="secondary">Select players first</p>';
+        return;
+    }
+
+    // Build participation list HTML
+    let html = '';
+    allSelected.forEach(({ playerId, name, team }) => {
+        const value = participationValues[playerId] || 1.0;
+        const isPartial = value < 1.0;
+        const minutes = minuteValues[playerId] || { joined: '', left: '' };
+        const hasMinutes = minutes.joined !== '' || minutes.left !== '';
+        html += `
+            <div class="participation-row${isPartial ? ' partial' : ''}" data-player-id="${playerId}">
+                <span>${name} <small class="secondary">(Team ${team.toUpperCase()})</small></span>
+                <span class="minutes">
+                    <input type="number" class="joined" min="0" placeholder="in '"
+                        value="${minutes.joined}"
+                        aria-label="Minute ${name} joined">
+                    <input type="number" class="left" min="1" placeholder="out '"
+                        value="${minutes.left}"
+                        aria-label="Minute ${name} left">
+                </span>
+                <label>
+                    <input type="number" class="percent" min="1" max="100" step="1"
+                        value="${Math.round(value * 100)}"
+                        aria-label="Participation % for ${name}"> %
+                </label>
+                <input type="hidden" name="participation" value="${playerId}=${value}">
+                <input type="hidden" name="minutes" value="${playerId}=${minutes.joined}-${minutes.left}"${hasMinutes ? '' : ' disabled'}>
+            </div>
+        `;
+    });
+    list.innerHTML = html;
+}
+
+// Participation inputs are re-rendered, so listen on the list
+document.getElementById('participation-list').addEventListener('input', (e) => {
+    const row = e.target.closest('.participation-row');
+    if (!row) return;
+    const playerId = parseInt(row.dataset.playerId, 10);
+    if (e.target.matches('.joined, .left')) updateMinutes(playerId);
+    if (e.target.matches('.percent')) updateParticipation(playerId, e.target.value);
+});
+document.querySelector('input[name="duration"]').addEventListener('input', refreshMinutes);
+
+// Setup event listeners
+document.querySelectorAll('.player-select').forEach(container => {
+    const search = container.querySelector('.player-search');
+    const dropdown = container.querySelector('.player-dropdown');
+
+    search.addEventListener('focus', () => {
+        renderDropdown(container, search.value);
+        dropdown.classList.add('open');
+    });
+
+    search.addEventListener('input', () => {
+        renderDropdown(container, search.value);
+        dropdown.classList.add('open');
+    });
+});
+
+// Close dropdown when clicking outside
+document.addEventListener('click', (e) => {
+    document.querySelectorAll('.player-select').forEach(container => {
+        if (!container.contains(e.target)) {
+            container.querySelector('.player-dropdown').classList.remove('open');
+        }
+    });
+});
+
+// Goal list: one row per goal ("a:PlayerID:minute" for the server). When
+// there are goals they decide the score, so the score inputs follow them.
+const goalList = document.getElementById('goal-list');
+
+function scorerOptions(current) {
+    const chips = [...document.querySelectorAll('.chip')];
+    return '<option value="">Unknown scorer</option>' + chips.map(chip => {
+        const team = chip.closest('.player-select').dataset.team.toUpperCase();
+        const id = chip.dataset.playerId;
+        return `<option value="${id}"${id === current ? ' selected' : ''}>${chip.dataset.name} (Team ${team})</option>`;
+    }).join('');
+}
+
+function renderScorerSelects() {
+    goalList.querySelectorAll('.goal-row').forEach(row => {
+        const select = row.querySelector('.scorer');
+        select.innerHTML = scorerOptions(select.value);
+        updateGoal(row);
+    });
+}
+
+function updateGoal(row) {
+    const scorer = row.querySelector('.scorer').value;
+    const minute = row.querySelector('.minute').value;
+    row.querySelector('input[name="goals"]').value = `${row.dataset.team}:${scorer}:${minute}`;
+}
+
+function updateScoreFromGoals() {
+    const rows = [...goalList.querySelectorAll('.goal-row')];
+    ['a', 'b'].forEach(team => {
+        const input = document.querySelector(`input[name="score_${team}"]`);
+        input.readOnly = rows.length > 0;
+        if (rows.length) input.value = rows.filter(row => row.dataset.team === team).length;
+    });
+}
+
+function addGoal(team) {
+    const row = document.createElement('div');
+    row.className = 'goal-row';
+    row.dataset.team = team;
+    row.innerHTML = `
+        <span>Team ${team.toUpperCase()}</span>
+        <select class="scorer" aria-label="Scorer">${scorerOptions('')}</select>
+        <input type="number" class="minute" min="0" placeholder="min '" aria-label="Minute">
+        <button type="button" class="secondary outline remove-goal" aria-label="Remove goal">&times;</button>
+        <input type="hidden" name="goals">
+    `;
+    goalList.appendChild(row);
+    updateGoal(row);
+    updateScoreFromGoals();
+}
+
+document.querySelectorAll('.add-goal').forEach(button => {
+    button.addEventListener('click', () => addGoal(button.dataset.team));
+});
+goalList.addEventListener('input', (e) => {
+    const row = e.target.closest('.goal-row');
+    if (row) updateGoal(row);
+});
+goalList.addEventListener('click', (e) => {
+    if (!e.target.matches('.remove-goal')) return;
+    e.target.closest('.goal-row').remove();
+    updateScoreFromGoals();
+});
+
+// Load teams from localStorage (set by Teams page)
+const savedTeams = localStorage.getItem('lastTeams');
+if (savedTeams) {
+    try {
+        const { teamA, teamB, captains } = JSON.parse(savedTeams);
+        const containerA = document.querySelector('.player-select[data-team="a"]');
+        const containerB = document.querySelector('.player-select[data-team="b"]');
+
+        // Map IDs to player objects and select them
+        teamA.forEach(id => {
+            const player = allPlayers.find(p => p.id === id);
+            if (player && containerA) {
+                selectPlayer(containerA, player.name, player.id);
+            }
+        });
+        teamB.forEach(id => {
+            const player = allPlayers.find(p => p.id === id);
+            if (player && containerB) {
+                selectPlayer(containerB, player.name, player.id);
+            }
+        });
+        // Captains nominated on the Teams page
+        if (captains) {
+            ['a', 'b'].forEach((team, i) => {
+                const captain = allPlayers.find(p => p.id === captains[i]);
+                if (captain) renderCaptainSelect(team, captain.name);
+            });
+        }
+    } catch (e) {
+        console.error('Failed to load saved teams:', e);
+    }
+}